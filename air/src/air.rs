@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use core::ops::{Add, Mul, Sub};
 
 use p3_field::{ExtensionField, Field, FieldAlgebra, FieldExtensionAlgebra};
@@ -12,6 +13,12 @@ pub trait BaseAir<F>: Sync {
     fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
         None
     }
+
+    /// A name for each of the main trace's columns, which debugging output uses in place of
+    /// column indices.
+    fn column_names(&self) -> Option<Vec<&'static str>> {
+        None
+    }
 }
 
 ///  An AIR with 0 or more public values.
@@ -133,6 +140,12 @@ pub trait PairBuilder: AirBuilder {
     fn preprocessed(&self) -> Self::M;
 }
 
+/// A builder with access to a trace which was committed to outside of this proof, e.g. by a
+/// previous proof which this one continues. Like the main trace, it has a local and a next row.
+pub trait ExternalBuilder: AirBuilder {
+    fn external(&self) -> Self::M;
+}
+
 pub trait ExtensionBuilder: AirBuilder {
     type EF: ExtensionField<Self::F>;
 
@@ -170,6 +183,40 @@ pub trait PermutationAirBuilder: ExtensionBuilder {
     fn permutation_randomness(&self) -> &[Self::RandomVar];
 }
 
+/// A builder which can check that values of the trace appear in a table, which is itself given by
+/// the trace, e.g. to range check a column against a preprocessed column listing every value in the
+/// range.
+///
+/// Each lookup sends one value per row and receives one table value per row, and the values sent
+/// over all rows must be those received, counted with multiplicity. An AIR may make several
+/// lookups, each of which is checked separately.
+pub trait LookupBuilder: AirBuilder {
+    /// Send `send` on every row, and receive `receive` `multiplicity` times, i.e. as many times as
+    /// that table value is looked up.
+    fn lookup<I1, I2, I3>(&mut self, send: I1, receive: I2, multiplicity: I3)
+    where
+        I1: Into<Self::Expr>,
+        I2: Into<Self::Expr>,
+        I3: Into<Self::Expr>,
+    {
+        self.lookup_with_count(Self::Expr::ONE, send, receive, multiplicity);
+    }
+
+    /// Like [`lookup`](Self::lookup), but sends `send` `count` times rather than once, e.g. zero
+    /// times on rows where it needn't be in the table.
+    fn lookup_with_count<I0, I1, I2, I3>(
+        &mut self,
+        count: I0,
+        send: I1,
+        receive: I2,
+        multiplicity: I3,
+    ) where
+        I0: Into<Self::Expr>,
+        I1: Into<Self::Expr>,
+        I2: Into<Self::Expr>,
+        I3: Into<Self::Expr>;
+}
+
 #[derive(Debug)]
 pub struct FilteredAirBuilder<'a, AB: AirBuilder> {
     pub inner: &'a mut AB,
@@ -222,6 +269,29 @@ impl<AB: ExtensionBuilder> ExtensionBuilder for FilteredAirBuilder<'_, AB> {
     }
 }
 
+impl<AB: LookupBuilder> LookupBuilder for FilteredAirBuilder<'_, AB> {
+    /// Both sides of the lookup only count where the condition holds.
+    fn lookup_with_count<I0, I1, I2, I3>(
+        &mut self,
+        count: I0,
+        send: I1,
+        receive: I2,
+        multiplicity: I3,
+    ) where
+        I0: Into<Self::Expr>,
+        I1: Into<Self::Expr>,
+        I2: Into<Self::Expr>,
+        I3: Into<Self::Expr>,
+    {
+        self.inner.lookup_with_count(
+            self.condition() * count.into(),
+            send,
+            receive,
+            self.condition() * multiplicity.into(),
+        );
+    }
+}
+
 impl<AB: PermutationAirBuilder> PermutationAirBuilder for FilteredAirBuilder<'_, AB> {
     type MP = AB::MP;
 
@@ -5,8 +5,8 @@ use p3_baby_bear::BabyBear;
 use p3_field::{Field, FieldAlgebra};
 use p3_field_testing::bench_func::{
     benchmark_add_latency, benchmark_add_throughput, benchmark_inv, benchmark_iter_sum,
-    benchmark_mul_latency, benchmark_mul_throughput, benchmark_sub_latency,
-    benchmark_sub_throughput,
+    benchmark_mul_latency, benchmark_mul_throughput, benchmark_packed_sum, benchmark_sub_latency,
+    benchmark_sub_throughput, benchmark_sum_slice,
 };
 
 type F = BabyBear;
@@ -18,6 +18,7 @@ fn bench_field(c: &mut Criterion) {
     benchmark_iter_sum::<F, 4, REPS>(c, name);
     benchmark_iter_sum::<F, 8, REPS>(c, name);
     benchmark_iter_sum::<F, 12, REPS>(c, name);
+    benchmark_sum_slice::<F, REPS>(c, name);
 
     // Note that each round of throughput has 10 operations
     // So we should have 10 * more repetitions for latency tests.
@@ -43,6 +44,8 @@ fn bench_packedfield(c: &mut Criterion) {
     const REPS: usize = 100;
     const L_REPS: usize = 10 * REPS;
 
+    benchmark_sum_slice::<<F as Field>::Packing, REPS>(c, &name);
+    benchmark_packed_sum::<<F as Field>::Packing>(c, &name);
     benchmark_add_latency::<<F as Field>::Packing, L_REPS>(c, &name);
     benchmark_add_throughput::<<F as Field>::Packing, REPS>(c, &name);
     benchmark_sub_latency::<<F as Field>::Packing, L_REPS>(c, &name);
@@ -0,0 +1,59 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use p3_baby_bear::{BabyBear, BabyBearParameters};
+use p3_field::{FieldAlgebra, PrimeField32};
+use p3_monty_31::{from_canonical_u32_vec, into_canonical_u32_vec};
+use rand::distributions::Standard;
+use rand::{thread_rng, Rng};
+
+const LEN: usize = 1 << 22;
+
+fn bench_canonical_batch(c: &mut Criterion) {
+    let vals: Vec<BabyBear> = thread_rng().sample_iter(Standard).take(LEN).collect();
+    let canonical: Vec<u32> = vals.iter().map(|x| x.as_canonical_u32()).collect();
+
+    let mut group = c.benchmark_group("to canonical u32s, 2^22 BabyBear");
+    group.bench_function("scalar", |b| {
+        b.iter_batched(
+            || vals.clone(),
+            |vals| {
+                vals.iter()
+                    .map(|x| x.as_canonical_u32())
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("into_canonical_u32_vec", |b| {
+        b.iter_batched(
+            || vals.clone(),
+            into_canonical_u32_vec,
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("from canonical u32s, 2^22 BabyBear");
+    group.bench_function("scalar", |b| {
+        b.iter_batched(
+            || canonical.clone(),
+            |canonical| {
+                canonical
+                    .iter()
+                    .map(|&x| BabyBear::from_canonical_u32(x))
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("from_canonical_u32_vec", |b| {
+        b.iter_batched(
+            || canonical.clone(),
+            from_canonical_u32_vec::<BabyBearParameters>,
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_canonical_batch);
+criterion_main!(benches);
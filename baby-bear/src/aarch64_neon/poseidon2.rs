@@ -1,15 +1,25 @@
-//! Eventually this will hold a vectorized Neon implementation of Poseidon2 for PackedBabyBearNeon
-//! Currently this is essentially a placeholder to allow compilation and testing on Neon devices.
+//! Neon implementation of Poseidon2 for PackedBabyBearNeon.
 //!
-//! Converting the AVX2/AVX512 code across to Neon is on the TODO list.
+//! The internal linear layer currently uses the generic implementation for both widths.
+//! Specialising it as for KoalaBear is on the TODO list.
+
+use p3_monty_31::InternalLayerParametersNeon;
+
+use crate::{BabyBearInternalLayerParameters, BabyBearParameters};
+
+impl InternalLayerParametersNeon<BabyBearParameters, 16> for BabyBearInternalLayerParameters {}
+
+impl InternalLayerParametersNeon<BabyBearParameters, 24> for BabyBearInternalLayerParameters {}
 
 #[cfg(test)]
 mod tests {
     use p3_field::FieldAlgebra;
+    use p3_field_testing::check_packed_agreement;
+    use p3_monty_31::packed_ops::{halve, halve_neon, mul_neg_2exp_neg_n, mul_neg_2exp_neg_n_neon};
     use p3_symmetric::Permutation;
     use rand::Rng;
 
-    use crate::{BabyBear, PackedBabyBearNeon, Poseidon2BabyBear};
+    use crate::{BabyBear, BabyBearParameters, PackedBabyBearNeon, Poseidon2BabyBear};
 
     type F = BabyBear;
     type Perm16 = Poseidon2BabyBear<16>;
@@ -56,4 +66,51 @@ mod tests {
 
         assert_eq!(neon_output, expected);
     }
+
+    /// Check that `packed` agrees with `scalar` in every lane.
+    fn check_agreement(
+        packed: impl Fn(PackedBabyBearNeon) -> PackedBabyBearNeon,
+        scalar: impl Fn(F) -> F,
+    ) {
+        // SAFETY: `F` is its MONTY form and `PackedBabyBearNeon` has the layout of `[u32; 4]`.
+        unsafe { check_packed_agreement::<F, _, 4>(packed, scalar) }
+    }
+
+    /// Test the packed multiplications by powers of two against the scalar versions.
+    #[test]
+    fn test_neon_packed_ops_match_scalar() {
+        type P = BabyBearParameters;
+
+        check_agreement(halve_neon::<P>, halve);
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 1, 26>(x),
+            |x| mul_neg_2exp_neg_n(x, 1),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 2, 25>(x),
+            |x| mul_neg_2exp_neg_n(x, 2),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 3, 24>(x),
+            |x| mul_neg_2exp_neg_n(x, 3),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 7, 20>(x),
+            |x| mul_neg_2exp_neg_n(x, 7),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 9, 18>(x),
+            |x| mul_neg_2exp_neg_n(x, 9),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 14, 13>(x),
+            |x| mul_neg_2exp_neg_n(x, 14),
+        );
+        check_agreement(mul_neg_2exp_neg_n_neon::<P, 8, 19>, |x| {
+            mul_neg_2exp_neg_n(x, 8)
+        });
+        check_agreement(mul_neg_2exp_neg_n_neon::<P, 27, 0>, |x| {
+            mul_neg_2exp_neg_n(x, 27)
+        });
+    }
 }
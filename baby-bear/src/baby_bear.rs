@@ -110,10 +110,11 @@ impl BinomialExtensionData<5> for BabyBearParameters {
 
 #[cfg(test)]
 mod tests {
+    use alloc::format;
     use core::array;
 
     use p3_field::{PrimeField32, PrimeField64, TwoAdicField};
-    use p3_field_testing::{test_field, test_field_dft, test_two_adic_field};
+    use p3_field_testing::{test_field, test_field_dft, test_prime_field_64, test_two_adic_field};
 
     use super::*;
 
@@ -139,6 +140,60 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_const_monty_constructors() {
+        const P_MINUS_1: u32 = BabyBear::ORDER_U32 - 1;
+        const VALS: [BabyBear; 3] = BabyBear::new_array([0, 1, P_MINUS_1]);
+        const MONTY_ONE: u32 = BabyBear::new(1).to_monty();
+
+        assert_eq!(VALS, [0, 1, P_MINUS_1].map(F::from_canonical_u32));
+        assert_eq!(MONTY_ONE, ((1u64 << 32) % F::ORDER_U64) as u32);
+        for x in VALS {
+            assert_eq!(F::new_monty(x.to_monty()), x);
+        }
+        assert_eq!(F::new_monty(0), F::ZERO);
+        assert_eq!(F::new_monty(MONTY_ONE), F::ONE);
+    }
+
+    #[test]
+    fn test_canonical_batch_conversion() {
+        use alloc::vec::Vec;
+
+        use p3_monty_31::{
+            from_canonical_slice, from_canonical_u32_vec, into_canonical_u32_vec,
+            to_canonical_slice,
+        };
+        use rand::Rng;
+
+        // Long enough for several parallel chunks, with a tail shorter than a packed vector.
+        let boundary = [0, 1, 2, F::ORDER_U32 - 2, F::ORDER_U32 - 1].map(F::from_canonical_u32);
+        let mut rng = rand::thread_rng();
+        let vals: Vec<F> = boundary
+            .into_iter()
+            .chain((0..(1 << 14) + 3).map(|_| rng.gen()))
+            .collect();
+        let expected: Vec<u32> = vals.iter().map(|x| x.as_canonical_u32()).collect();
+
+        let canonical = into_canonical_u32_vec(vals.clone());
+        assert_eq!(canonical, expected);
+        assert_eq!(
+            from_canonical_u32_vec::<BabyBearParameters>(canonical),
+            vals
+        );
+
+        let mut buf = vals.clone();
+        assert_eq!(to_canonical_slice(&mut buf), expected);
+        let mut buf = expected.clone();
+        assert_eq!(from_canonical_slice::<BabyBearParameters>(&mut buf), vals);
+    }
+
+    #[test]
+    #[should_panic(expected = "Values must be canonical")]
+    fn test_canonical_batch_conversion_rejects_non_canonical() {
+        let mut buf = [1, F::ORDER_U32, 2];
+        let _ = p3_monty_31::from_canonical_slice::<BabyBearParameters>(&mut buf);
+    }
+
     #[test]
     fn test_baby_bear() {
         let f = F::from_canonical_u32(100);
@@ -222,12 +277,23 @@ mod tests {
         let m2_serialized = serde_json::to_string(&m2).unwrap();
         let m2_deserialized: F = serde_json::from_str(&m2_serialized).unwrap();
         assert_eq!(m2, m2_deserialized);
+
+        // Elements are serialized in canonical form, and out of range values are rejected.
+        assert_eq!(serde_json::to_string(&f_2).unwrap(), "2");
+        let order_serialized = serde_json::to_string(&F::ORDER_U32).unwrap();
+        assert!(serde_json::from_str::<F>(&order_serialized).is_err());
+
+        // `Debug` shows the value, while `LowerHex` shows the MONTY form, `2^32 mod P` for one.
+        assert_eq!(format!("{f_1:?}"), "1");
+        assert_eq!(format!("{f_1:x}"), "ffffffe");
     }
 
     test_field!(crate::BabyBear);
+    test_prime_field_64!(crate::BabyBear);
     test_two_adic_field!(crate::BabyBear);
 
     test_field_dft!(radix2dit, crate::BabyBear, p3_dft::Radix2Dit<_>);
+    test_field_dft!(radix4dit, crate::BabyBear, p3_dft::Radix4Dit<_>);
     test_field_dft!(bowers, crate::BabyBear, p3_dft::Radix2Bowers);
     test_field_dft!(parallel, crate::BabyBear, p3_dft::Radix2DitParallel::<_>);
     test_field_dft!(
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test_quartic_extension {
-    use alloc::format;
+    use alloc::{format, vec};
 
     use p3_field::extension::BinomialExtensionField;
     use p3_field::{FieldAlgebra, FieldExtensionAlgebra};
@@ -28,6 +28,18 @@ mod test_quartic_extension {
             "2 + X + 2 X^3"
         );
     }
+
+    #[test]
+    fn debug_and_ord() {
+        let x = EF::from_base_slice(&[F::TWO, F::ONE, F::ZERO, F::NEG_ONE]);
+        let y = EF::from_base_slice(&[F::TWO, F::ONE, F::ONE, F::ZERO]);
+        assert_eq!(format!("{x:?}"), "[2, 1, 0, 2013265920]");
+
+        // Lexicographic in the coefficients, lowest degree first.
+        let mut xs = vec![y, EF::ONE, x, EF::ZERO];
+        xs.sort();
+        assert_eq!(xs, [EF::ZERO, EF::ONE, x, y]);
+    }
 }
 
 #[cfg(test)]
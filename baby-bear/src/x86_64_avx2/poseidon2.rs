@@ -112,11 +112,19 @@ impl InternalLayerParametersAVX2<BabyBearParameters, 24> for BabyBearInternalLay
 
 #[cfg(test)]
 mod tests {
+    use core::arch::x86_64::__m256i;
+
     use p3_field::FieldAlgebra;
+    use p3_field_testing::check_packed_agreement;
+    use p3_monty_31::packed_ops::{
+        halve, halve_avx2, mul_2exp_neg_8_avx2, mul_2exp_neg_n, mul_2exp_neg_n_avx2,
+        mul_2exp_neg_two_adicity_avx2, mul_neg_2exp_neg_8_avx2, mul_neg_2exp_neg_n,
+        mul_neg_2exp_neg_n_avx2, mul_neg_2exp_neg_two_adicity_avx2,
+    };
     use p3_symmetric::Permutation;
     use rand::Rng;
 
-    use crate::{BabyBear, PackedBabyBearAVX2, Poseidon2BabyBear};
+    use crate::{BabyBear, BabyBearParameters, PackedBabyBearAVX2, Poseidon2BabyBear};
 
     type F = BabyBear;
     type Perm16 = Poseidon2BabyBear<16>;
@@ -163,4 +171,82 @@ mod tests {
 
         assert_eq!(avx2_output, expected);
     }
+
+    /// Check that `packed` agrees with `scalar` in every lane.
+    fn check_agreement(packed: impl Fn(__m256i) -> __m256i, scalar: impl Fn(F) -> F) {
+        // SAFETY: `F` is its MONTY form and `__m256i` has the layout of `[u32; 8]`.
+        unsafe { check_packed_agreement::<F, _, 8>(packed, scalar) }
+    }
+
+    /// Test the packed multiplications by powers of two against the scalar versions.
+    #[test]
+    fn test_avx2_packed_ops_match_scalar() {
+        type P = BabyBearParameters;
+
+        check_agreement(halve_avx2::<P>, halve);
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 1, 26>(x) },
+            |x| mul_2exp_neg_n(x, 1),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 2, 25>(x) },
+            |x| mul_2exp_neg_n(x, 2),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 3, 24>(x) },
+            |x| mul_2exp_neg_n(x, 3),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 7, 20>(x) },
+            |x| mul_2exp_neg_n(x, 7),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 9, 18>(x) },
+            |x| mul_2exp_neg_n(x, 9),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 14, 13>(x) },
+            |x| mul_2exp_neg_n(x, 14),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 1, 26>(x) },
+            |x| mul_neg_2exp_neg_n(x, 1),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 2, 25>(x) },
+            |x| mul_neg_2exp_neg_n(x, 2),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 3, 24>(x) },
+            |x| mul_neg_2exp_neg_n(x, 3),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 7, 20>(x) },
+            |x| mul_neg_2exp_neg_n(x, 7),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 9, 18>(x) },
+            |x| mul_neg_2exp_neg_n(x, 9),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 14, 13>(x) },
+            |x| mul_neg_2exp_neg_n(x, 14),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_8_avx2::<P, 19>(x) },
+            |x| mul_2exp_neg_n(x, 8),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_8_avx2::<P, 19>(x) },
+            |x| mul_neg_2exp_neg_n(x, 8),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_two_adicity_avx2::<P, 27, 4>(x) },
+            |x| mul_2exp_neg_n(x, 27),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_two_adicity_avx2::<P, 27, 4>(x) },
+            |x| mul_neg_2exp_neg_n(x, 27),
+        );
+    }
 }
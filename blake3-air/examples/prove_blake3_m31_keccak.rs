@@ -5,7 +5,7 @@ use p3_blake3_air::{generate_trace_rows, Blake3Air};
 use p3_challenger::{HashChallenger, SerializingChallenger32};
 use p3_circle::CirclePcs;
 use p3_commit::ExtensionMmcs;
-use p3_field::extension::BinomialExtensionField;
+use p3_field::extension::ComplexBinomialExtensionField;
 use p3_fri::create_benchmark_fri_config;
 use p3_keccak::Keccak256Hash;
 use p3_merkle_tree::MerkleTreeMmcs;
@@ -32,7 +32,7 @@ fn main() -> Result<(), impl Debug> {
         .init();
 
     type Val = Mersenne31;
-    type Challenge = BinomialExtensionField<Val, 3>;
+    type Challenge = ComplexBinomialExtensionField<Val, 4>;
 
     type ByteHash = Keccak256Hash;
     type FieldHash = SerializingHasher32<ByteHash>;
@@ -66,7 +66,7 @@ fn main() -> Result<(), impl Debug> {
     let trace = generate_trace_rows::<Val>(inputs);
 
     let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let proof = prove(&config, &Blake3Air {}, &mut challenger, trace, &vec![]);
+    let proof = prove(&config, &Blake3Air {}, &mut challenger, trace, &vec![]).unwrap();
 
     let mut challenger = Challenger::from_hasher(vec![], byte_hash);
     verify(&config, &Blake3Air {}, &mut challenger, &proof, &vec![])
@@ -7,7 +7,7 @@ use p3_air::{Air, AirBuilder, BaseAir};
 use p3_field::{FieldAlgebra, PrimeField64};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
-use rand::random;
+use rand::Rng;
 
 use crate::columns::{Blake3Cols, NUM_BLAKE3_COLS};
 use crate::constants::{permute, BITS_PER_LIMB, IV};
@@ -18,8 +18,12 @@ use crate::{generate_trace_rows, Blake3State, FullRound, QuarterRound};
 pub struct Blake3Air {}
 
 impl Blake3Air {
-    pub fn generate_trace_rows<F: PrimeField64>(&self, num_hashes: usize) -> RowMajorMatrix<F> {
-        let inputs = (0..num_hashes).map(|_| random()).collect::<Vec<_>>();
+    pub fn generate_trace_rows<F: PrimeField64, R: Rng>(
+        &self,
+        num_hashes: usize,
+        rng: &mut R,
+    ) -> RowMajorMatrix<F> {
+        let inputs = (0..num_hashes).map(|_| rng.gen()).collect::<Vec<_>>();
         generate_trace_rows(inputs)
     }
 
@@ -2,7 +2,24 @@
 
 #![no_std]
 
-use p3_symmetric::CryptographicHasher;
+use p3_symmetric::{
+    CanonicalSerializingHasher32, CompressionFunctionFromHasher, CryptographicHasher,
+    StatefulHasher,
+};
+
+/// A blake3 digest.
+pub type Blake3Digest = [u8; 32];
+
+/// Hashes rows of 32-bit field elements, such as `MontyField31`s, with blake3. Each element is
+/// serialized to the 4 little-endian bytes of its canonical value.
+///
+/// Together with [`Blake3Compression`], this can be used to build a Merkle tree which can be
+/// checked without any field arithmetic, i.e.
+/// `MerkleTreeMmcs<F, u8, Blake3FieldHasher, Blake3Compression, 32>`.
+pub type Blake3FieldHasher = CanonicalSerializingHasher32<Blake3>;
+
+/// Compresses two blake3 digests by hashing their concatenation.
+pub type Blake3Compression = CompressionFunctionFromHasher<Blake3, 2, 32>;
 
 /// The blake3 hash function.
 #[derive(Copy, Clone, Debug)]
@@ -14,21 +31,35 @@ impl CryptographicHasher<u8, [u8; 32]> for Blake3 {
         I: IntoIterator<Item = u8>,
     {
         const BUFLEN: usize = 512; // Tweakable parameter; determined by experiment
-        let mut hasher = blake3::Hasher::new();
-        p3_util::apply_to_chunks::<BUFLEN, _, _>(input, |buf| {
-            hasher.update(buf);
-        });
-        hasher.finalize().into()
+        let mut state = self.new_state();
+        p3_util::apply_to_chunks::<BUFLEN, _, _>(input, |buf| self.update(&mut state, buf));
+        self.finalize(state)
     }
 
     fn hash_iter_slices<'a, I>(&self, input: I) -> [u8; 32]
     where
         I: IntoIterator<Item = &'a [u8]>,
     {
-        let mut hasher = blake3::Hasher::new();
+        let mut state = self.new_state();
         for chunk in input.into_iter() {
-            hasher.update(chunk);
+            self.update(&mut state, chunk);
         }
-        hasher.finalize().into()
+        self.finalize(state)
+    }
+}
+
+impl StatefulHasher<u8, [u8; 32]> for Blake3 {
+    type State = blake3::Hasher;
+
+    fn new_state(&self) -> Self::State {
+        blake3::Hasher::new()
+    }
+
+    fn update(&self, state: &mut Self::State, input: &[u8]) {
+        state.update(input);
+    }
+
+    fn finalize(&self, state: Self::State) -> [u8; 32] {
+        state.finalize().into()
     }
 }
@@ -12,7 +12,7 @@ use ff::{Field as FFField, PrimeField as FFPrimeField};
 pub use halo2curves::bn256::Fr as FFBn254Fr;
 use halo2curves::serde::SerdeObject;
 use num_bigint::BigUint;
-use p3_field::{Field, FieldAlgebra, Packable, PrimeField, TwoAdicField};
+use p3_field::{assert_two_adicity, Field, FieldAlgebra, Packable, PrimeField, TwoAdicField};
 pub use poseidon2::Poseidon2Bn254;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
@@ -148,6 +148,8 @@ impl Field for Bn254Fr {
     // generator is 5
     const GENERATOR: Self = Self::new(FFBn254Fr::from_raw([5u64, 0, 0, 0]));
 
+    const BITS: usize = 254;
+
     fn is_zero(&self) -> bool {
         self.value.is_zero().into()
     }
@@ -276,6 +278,7 @@ impl TwoAdicField for Bn254Fr {
     const TWO_ADICITY: usize = FFBn254Fr::S as usize;
 
     fn two_adic_generator(bits: usize) -> Self {
+        assert_two_adicity::<Self>(bits);
         let mut omega = FFBn254Fr::ROOT_OF_UNITY;
         for _ in bits..Self::TWO_ADICITY {
             omega = omega.square();
@@ -1,10 +1,16 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::iter;
 
 use p3_field::{ExtensionField, Field, PrimeField64};
 use p3_symmetric::{CryptographicPermutation, Hash};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
-use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger};
+use crate::{
+    uniform_bits, CanObserve, CanSample, CanSampleBits, CanSnapshot, FieldChallenger,
+    InvalidChallengerState, SpongeState,
+};
 
 #[derive(Clone, Debug)]
 pub struct DuplexChallenger<F, P, const WIDTH: usize, const RATE: usize>
@@ -57,6 +63,26 @@ where
     F: PrimeField64,
     P: CryptographicPermutation<[F; WIDTH]>,
 {
+    /// Pending observations are absorbed first, and then the tag's encoding is absorbed in blocks
+    /// of `RATE` elements. Before each of these permutations, one is added to the first element
+    /// of the capacity, which ordinary observations never touch, so the tag can't be confused
+    /// with observed data.
+    fn observe_domain_separator(&mut self, tag: &[u8]) {
+        assert!(RATE < WIDTH, "domain separation needs a nonzero capacity");
+        self.output_buffer.clear();
+        if !self.input_buffer.is_empty() {
+            self.duplexing();
+        }
+
+        let encoding: Vec<F> = iter::once(F::from_canonical_usize(tag.len()))
+            .chain(tag.iter().map(|&byte| F::from_canonical_u8(byte)))
+            .collect();
+        for block in encoding.chunks(RATE) {
+            self.input_buffer.extend_from_slice(block);
+            self.sponge_state[RATE] += F::ONE;
+            self.duplexing();
+        }
+    }
 }
 
 impl<F, P, const WIDTH: usize, const RATE: usize> CanObserve<F>
@@ -154,13 +180,56 @@ where
         let rand_usize = rand_f.as_canonical_u64() as usize;
         rand_usize & ((1 << bits) - 1)
     }
+
+    fn sample_uniform_bits(&mut self, bits: usize) -> usize {
+        debug_assert!(bits < (usize::BITS as usize));
+        debug_assert!((1 << bits) < F::ORDER_U64);
+        uniform_bits(F::ORDER_U64, bits, || {
+            let rand_f: F = self.sample();
+            rand_f.as_canonical_u64()
+        })
+    }
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> CanSnapshot
+    for DuplexChallenger<F, P, WIDTH, RATE>
+where
+    F: Copy + Serialize + DeserializeOwned,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    type State = SpongeState<F>;
+
+    fn snapshot(&self) -> Self::State {
+        SpongeState {
+            sponge_state: self.sponge_state.to_vec(),
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    fn restore(&mut self, state: Self::State) -> Result<(), InvalidChallengerState> {
+        // A full input buffer is absorbed as soon as it fills, and each duplexing squeezes at most
+        // `RATE` outputs.
+        let sponge_state = state
+            .sponge_state
+            .try_into()
+            .map_err(|_| InvalidChallengerState)?;
+        if state.input_buffer.len() >= RATE || state.output_buffer.len() > RATE {
+            return Err(InvalidChallengerState);
+        }
+        self.sponge_state = sponge_state;
+        self.input_buffer = state.input_buffer;
+        self.output_buffer = state.output_buffer;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use core::iter;
 
-    use p3_field::FieldAlgebra;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{FieldAlgebra, FieldExtensionAlgebra};
     use p3_goldilocks::Goldilocks;
     use p3_symmetric::Permutation;
 
@@ -201,4 +270,151 @@ mod tests {
         let samples = <Chal as CanSample<F>>::sample_vec(&mut duplex_challenger, 16);
         assert_eq!(samples, expected_samples);
     }
+
+    /// Mixes every element of the state into every other, unlike `TestPermutation`.
+    #[derive(Clone)]
+    struct MixingPermutation;
+
+    impl Permutation<TestArray> for MixingPermutation {
+        fn permute_mut(&self, input: &mut TestArray) {
+            let total: F = input.iter().copied().sum();
+            for (i, x) in input.iter_mut().enumerate() {
+                *x += total * F::from_canonical_usize(i + 1);
+            }
+        }
+    }
+
+    impl CryptographicPermutation<TestArray> for MixingPermutation {}
+
+    type MixingChallenger = DuplexChallenger<F, MixingPermutation, WIDTH, RATE>;
+
+    fn sample_after(tag: Option<&[u8]>, observed: &[F]) -> Vec<F> {
+        let mut challenger = MixingChallenger::new(MixingPermutation);
+        challenger.observe(F::from_canonical_u8(1));
+        if let Some(tag) = tag {
+            challenger.observe_domain_separator(tag);
+        }
+        challenger.observe_slice(observed);
+        <MixingChallenger as CanSample<F>>::sample_vec(&mut challenger, 4)
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let observed = (0..20).map(F::from_canonical_u8).collect::<Vec<_>>();
+
+        let untagged = sample_after(None, &observed);
+        let first = sample_after(Some(b"first phase"), &observed);
+        let second = sample_after(Some(b"second phase"), &observed);
+        assert_ne!(first, second);
+        assert_ne!(first, untagged);
+        assert_eq!(first, sample_after(Some(b"first phase"), &observed));
+
+        // Observing the tag's encoding as data is not the same as absorbing the tag.
+        let tag = b"first phase";
+        let mut challenger = MixingChallenger::new(MixingPermutation);
+        challenger.observe(F::from_canonical_u8(1));
+        challenger.duplexing();
+        challenger.observe(F::from_canonical_usize(tag.len()));
+        tag.iter()
+            .for_each(|&byte| challenger.observe(F::from_canonical_u8(byte)));
+        challenger.duplexing();
+        challenger.observe_slice(&observed);
+        let spoofed = <MixingChallenger as CanSample<F>>::sample_vec(&mut challenger, 4);
+        assert_ne!(first, spoofed);
+    }
+
+    #[test]
+    fn test_ext_element_ordering() {
+        type EF = BinomialExtensionField<F, 2>;
+        let ext = EF::from_base_slice(&[F::from_canonical_u8(3), F::from_canonical_u8(5)]);
+
+        // The prover and verifier observe the same extension element.
+        let mut prover = MixingChallenger::new(MixingPermutation);
+        let mut verifier = prover.clone();
+        prover.observe_ext_element(ext);
+        verifier.observe_ext_element(ext);
+        let prover_challenge: EF = prover.sample_ext_element();
+        let verifier_challenge: EF = verifier.sample_ext_element();
+        assert_eq!(prover_challenge, verifier_challenge);
+
+        // Coefficients are observed and sampled from low to high.
+        let mut by_hand = MixingChallenger::new(MixingPermutation);
+        by_hand.observe(F::from_canonical_u8(3));
+        by_hand.observe(F::from_canonical_u8(5));
+        let coeffs: [F; 2] = by_hand.sample_array();
+        assert_eq!(prover_challenge, EF::from_base_slice(&coeffs));
+
+        // Sampling an extension element directly agrees.
+        let mut direct = MixingChallenger::new(MixingPermutation);
+        direct.observe_ext_element(ext);
+        let direct_challenge: EF = direct.sample();
+        assert_eq!(prover_challenge, direct_challenge);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut challenger = MixingChallenger::new(MixingPermutation);
+        // Leave observations to absorb and samples to hand out in the snapshot.
+        challenger.observe_slice(&(0..20).map(F::from_canonical_u8).collect::<Vec<_>>());
+        let _: F = challenger.sample();
+        challenger.observe(F::from_canonical_u8(7));
+        let _: [F; 3] = challenger.sample_array();
+
+        let bytes = postcard::to_allocvec(&challenger.snapshot()).unwrap();
+        let mut restored = MixingChallenger::new(MixingPermutation);
+        restored
+            .restore(postcard::from_bytes(&bytes).unwrap())
+            .unwrap();
+
+        let expected = <MixingChallenger as CanSample<F>>::sample_vec(&mut challenger, 40);
+        let samples = <MixingChallenger as CanSample<F>>::sample_vec(&mut restored, 40);
+        assert_eq!(samples, expected);
+    }
+
+    #[test]
+    fn test_restore_rejects_other_widths() {
+        let mut state = MixingChallenger::new(MixingPermutation).snapshot();
+        state.sponge_state.push(F::ZERO);
+        let mut challenger = MixingChallenger::new(MixingPermutation);
+        assert!(challenger.restore(state).is_err());
+
+        let mut state = challenger.snapshot();
+        state.input_buffer = vec![F::ONE; RATE];
+        assert!(challenger.restore(state).is_err());
+    }
+
+    #[test]
+    fn test_sample_uniform_bits() {
+        use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        type Perm = Poseidon2BabyBear<16>;
+        let perm = Perm::new_from_rng_128(&mut StdRng::seed_from_u64(1));
+        let mut challenger = DuplexChallenger::<BabyBear, Perm, 16, 8>::new(perm);
+
+        for bits in [1, 20, 27, 30] {
+            for _ in 0..100 {
+                assert!(challenger.sample_uniform_bits(bits) < 1 << bits);
+            }
+        }
+
+        // A chi-squared test on 4 bit samples. With 15 degrees of freedom, the statistic exceeds
+        // 50 with probability below 1e-5.
+        const BITS: usize = 4;
+        const SAMPLES: usize = 16000;
+        let mut counts = [0usize; 1 << BITS];
+        for _ in 0..SAMPLES {
+            counts[challenger.sample_uniform_bits(BITS)] += 1;
+        }
+        let expected = (SAMPLES >> BITS) as f64;
+        let chi_squared: f64 = counts
+            .iter()
+            .map(|&count| {
+                let difference = count as f64 - expected;
+                difference * difference / expected
+            })
+            .sum();
+        assert!(chi_squared < 50.0, "chi-squared statistic {chi_squared}");
+    }
 }
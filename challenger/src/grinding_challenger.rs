@@ -3,7 +3,9 @@ use p3_maybe_rayon::prelude::*;
 use p3_symmetric::CryptographicPermutation;
 use tracing::instrument;
 
-use crate::{CanObserve, CanSampleBits, DuplexChallenger, MultiField32Challenger};
+use crate::{
+    CanObserve, CanSampleBits, DuplexChallenger, MultiField32Challenger, MultiFieldChallenger,
+};
 
 pub trait GrindingChallenger:
     CanObserve<Self::Witness> + CanSampleBits<usize> + Sync + Clone
@@ -19,6 +21,15 @@ pub trait GrindingChallenger:
     }
 }
 
+/// The expected number of candidates tried when grinding for `bits` bits, which sizes the search
+/// for the parallelism threshold. It saturates rather than overflowing for large `bits`.
+pub(crate) fn grinding_len(bits: usize) -> usize {
+    u32::try_from(bits)
+        .ok()
+        .and_then(|bits| 1_usize.checked_shl(bits))
+        .unwrap_or(usize::MAX)
+}
+
 impl<F, P, const WIDTH: usize, const RATE: usize> GrindingChallenger
     for DuplexChallenger<F, P, WIDTH, RATE>
 where
@@ -29,11 +40,16 @@ where
 
     #[instrument(name = "grind for proof-of-work witness", skip_all)]
     fn grind(&mut self, bits: usize) -> Self::Witness {
-        let witness = (0..F::ORDER_U64)
-            .into_par_iter()
-            .map(|i| F::from_canonical_u64(i))
-            .find_any(|witness| self.clone().check_witness(bits, *witness))
-            .expect("failed to find witness");
+        // `find_first`, unlike `find_any`, gives the same witness however the search is scheduled,
+        // so proofs don't depend on the thread pool.
+        let this = &*self;
+        let witness = p3_maybe_rayon::install(grinding_len(bits), || {
+            (0..F::ORDER_U64)
+                .into_par_iter()
+                .map(|i| F::from_canonical_u64(i))
+                .find_first(|witness| this.clone().check_witness(bits, *witness))
+                .expect("failed to find witness")
+        });
         assert!(self.check_witness(bits, witness));
         witness
     }
@@ -50,12 +66,100 @@ where
 
     #[instrument(name = "grind for proof-of-work witness", skip_all)]
     fn grind(&mut self, bits: usize) -> Self::Witness {
-        let witness = (0..F::ORDER_U64)
-            .into_par_iter()
-            .map(F::from_canonical_u64)
-            .find_any(|witness| self.clone().check_witness(bits, *witness))
-            .expect("failed to find witness");
+        let this = &*self;
+        let witness = p3_maybe_rayon::install(grinding_len(bits), || {
+            (0..F::ORDER_U64)
+                .into_par_iter()
+                .map(F::from_canonical_u64)
+                .find_first(|witness| this.clone().check_witness(bits, *witness))
+                .expect("failed to find witness")
+        });
+        assert!(self.check_witness(bits, witness));
+        witness
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> GrindingChallenger
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    type Witness = F;
+
+    #[instrument(name = "grind for proof-of-work witness", skip_all)]
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        let this = &*self;
+        let witness = p3_maybe_rayon::install(grinding_len(bits), || {
+            (0..F::ORDER_U64)
+                .into_par_iter()
+                .map(F::from_canonical_u64)
+                .find_first(|witness| this.clone().check_witness(bits, *witness))
+                .expect("failed to find witness")
+        });
         assert!(self.check_witness(bits, witness));
         witness
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_field::FieldAlgebra;
+    use p3_goldilocks::Goldilocks;
+    use p3_symmetric::Permutation;
+
+    use super::*;
+    use crate::CanSample;
+
+    type F = Goldilocks;
+    type State = [F; 8];
+
+    /// A cheap permutation which mixes every element of the state into every other.
+    #[derive(Clone)]
+    struct MixingPermutation;
+
+    impl Permutation<State> for MixingPermutation {
+        fn permute_mut(&self, input: &mut State) {
+            let total: F = input.iter().copied().sum();
+            for (i, x) in input.iter_mut().enumerate() {
+                *x += total * F::from_canonical_usize(i + 1);
+            }
+        }
+    }
+
+    impl CryptographicPermutation<State> for MixingPermutation {}
+
+    type Challenger = DuplexChallenger<F, MixingPermutation, 8, 4>;
+
+    #[test]
+    fn grind_and_check_witness_agree() {
+        for bits in 1..=20 {
+            let mut prover = Challenger::new(MixingPermutation);
+            prover.observe(F::from_canonical_usize(bits));
+            let mut verifier = prover.clone();
+
+            let witness = prover.grind(bits);
+            assert!(verifier.check_witness(bits, witness));
+
+            // Both sides are left in the same state.
+            let prover_sample: F = prover.sample();
+            let verifier_sample: F = verifier.sample();
+            assert_eq!(prover_sample, verifier_sample);
+        }
+    }
+
+    #[test]
+    fn wrong_witness_is_rejected() {
+        for bits in [8, 16] {
+            let mut challenger = Challenger::new(MixingPermutation);
+            challenger.observe(F::ONE);
+            let witness = challenger.clone().grind(bits);
+
+            // The search returns the first valid witness, so every smaller one is invalid.
+            assert!(witness > F::ZERO);
+            assert!(!challenger.clone().check_witness(bits, witness - F::ONE));
+            assert!(!challenger.clone().check_witness(bits, F::ZERO));
+        }
+    }
+}
@@ -2,8 +2,10 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use p3_symmetric::CryptographicHasher;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
-use crate::{CanObserve, CanSample};
+use crate::{CanObserve, CanSample, CanSnapshot, InvalidChallengerState, SpongeState};
 
 #[derive(Clone, Debug)]
 pub struct HashChallenger<T, H, const OUT_LEN: usize>
@@ -81,6 +83,31 @@ where
     }
 }
 
+impl<T, H, const OUT_LEN: usize> CanSnapshot for HashChallenger<T, H, OUT_LEN>
+where
+    T: Clone + Serialize + DeserializeOwned,
+    H: CryptographicHasher<T, [T; OUT_LEN]>,
+{
+    type State = SpongeState<T>;
+
+    fn snapshot(&self) -> Self::State {
+        SpongeState {
+            sponge_state: vec![],
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    fn restore(&mut self, state: Self::State) -> Result<(), InvalidChallengerState> {
+        if !state.sponge_state.is_empty() || state.output_buffer.len() > OUT_LEN {
+            return Err(InvalidChallengerState);
+        }
+        self.input_buffer = state.input_buffer;
+        self.output_buffer = state.output_buffer;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use p3_field::FieldAlgebra;
@@ -168,4 +195,25 @@ mod tests {
             [F::from_canonical_u8(new_expected_sum)]
         )
     }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let initial_state = (1..11_u8).map(F::from_canonical_u8).collect::<Vec<_>>();
+        let mut hash_challenger = HashChallenger::new(initial_state, TestHasher {});
+        let _ = hash_challenger.sample();
+        hash_challenger.observe(F::from_canonical_u8(11));
+        let _ = hash_challenger.sample();
+
+        let bytes = postcard::to_allocvec(&hash_challenger.snapshot()).unwrap();
+        let mut restored = HashChallenger::new(vec![], TestHasher {});
+        restored
+            .restore(postcard::from_bytes(&bytes).unwrap())
+            .unwrap();
+        assert_eq!(restored.input_buffer, hash_challenger.input_buffer);
+        assert_eq!(restored.output_buffer, hash_challenger.output_buffer);
+
+        for _ in 0..5 {
+            assert_eq!(restored.sample(), hash_challenger.sample());
+        }
+    }
 }
@@ -8,7 +8,10 @@ mod duplex_challenger;
 mod grinding_challenger;
 mod hash_challenger;
 mod multi_field_challenger;
+mod multi_field_packing_challenger;
 mod serializing_challenger;
+mod snapshot;
+mod transcript_recorder;
 
 use alloc::vec::Vec;
 use core::array;
@@ -17,8 +20,12 @@ pub use duplex_challenger::*;
 pub use grinding_challenger::*;
 pub use hash_challenger::*;
 pub use multi_field_challenger::*;
+pub use multi_field_packing_challenger::*;
 use p3_field::{Field, FieldExtensionAlgebra};
+use p3_matrix::Dimensions;
 pub use serializing_challenger::*;
+pub use snapshot::*;
+pub use transcript_recorder::*;
 
 pub trait CanObserve<T> {
     fn observe(&mut self, value: T);
@@ -46,20 +53,78 @@ pub trait CanSample<T> {
 }
 
 pub trait CanSampleBits<T> {
+    /// Sample `bits` random bits by masking a single sample. When samples are field elements this
+    /// is slightly biased towards small values, since the field's order isn't a power of two.
     fn sample_bits(&mut self, bits: usize) -> T;
+
+    /// Sample `bits` uniformly random bits, resampling whenever a draw falls in the tail of the
+    /// field which would bias them. This can consume more of the transcript than `sample_bits`, so
+    /// the prover and verifier must agree on which of the two they use.
+    ///
+    /// The default calls `sample_bits`, which is only right for challengers whose samples are
+    /// already uniform bits; field-based challengers override it.
+    fn sample_uniform_bits(&mut self, bits: usize) -> T {
+        self.sample_bits(bits)
+    }
+}
+
+/// The low `bits` bits of the first value from `draw` below the largest multiple of `2^bits` which
+/// is at most `order`. If `draw` is uniform below `order`, every `bits`-bit value is equally likely.
+pub(crate) fn uniform_bits(order: u64, bits: usize, mut draw: impl FnMut() -> u64) -> usize {
+    let bound = (order >> bits) << bits;
+    loop {
+        let value = draw();
+        if value < bound {
+            return (value & ((1 << bits) - 1)) as usize;
+        }
+    }
 }
 
 pub trait FieldChallenger<F: Field>:
     CanObserve<F> + CanSample<F> + CanSampleBits<usize> + Sync
 {
+    /// Observe the coefficients of `ext` in its basis, from low to high.
+    ///
+    /// This ordering, like that of `sample_ext_element`, is part of the transcript: the prover and
+    /// verifier must agree on it, so it must not change.
     fn observe_ext_element<EF: FieldExtensionAlgebra<F>>(&mut self, ext: EF) {
         self.observe_slice(ext.as_base_slice());
     }
 
+    /// Sample an extension element whose basis coefficients, from low to high, are consecutive
+    /// base field samples. Extension challenges should always be sampled this way.
     fn sample_ext_element<EF: FieldExtensionAlgebra<F>>(&mut self) -> EF {
         let vec = self.sample_vec(EF::D);
         EF::from_base_slice(&vec)
     }
+
+    /// Absorb a tag marking the start of a new phase of the protocol, so that phases which observe
+    /// the same values still sample different challenges. Challengers which are never given a tag
+    /// behave exactly as before.
+    ///
+    /// The tag is encoded as its length followed by its bytes, behind a marker which no sequence
+    /// of ordinary observations reproduces, e.g. a write to the capacity of a sponge. There is no
+    /// default, since observing the encoding as field elements wouldn't separate anything.
+    fn observe_domain_separator(&mut self, tag: &[u8]);
+
+    /// Observe the dimensions of the matrices under a commitment: their number, then the width and
+    /// height of each. Observing these along with the commitment binds the shape the verifier
+    /// assumes for it, so that a proof can't claim a different one.
+    ///
+    /// This isn't a `CanObserve<Dimensions>` impl since, for challengers generic over the field,
+    /// it would overlap with `CanObserve<F>`.
+    fn observe_shape(&mut self, dims: &[Dimensions]) {
+        self.observe(F::from_canonical_usize(dims.len()));
+        for dim in dims {
+            self.observe(F::from_wrapped_u64(dim.width as u64));
+            self.observe(F::from_wrapped_u64(dim.height as u64));
+        }
+    }
+
+    /// Mark the start of a phase of the protocol, for debugging. Unlike
+    /// `observe_domain_separator`, this is not part of the transcript, and does nothing by default;
+    /// a [`TranscriptRecorder`] labels what it records with the phase.
+    fn begin_phase(&mut self, _phase: &'static str) {}
 }
 
 impl<C, T> CanObserve<T> for &mut C
@@ -108,6 +173,11 @@ where
     fn sample_bits(&mut self, bits: usize) -> T {
         (**self).sample_bits(bits)
     }
+
+    #[inline(always)]
+    fn sample_uniform_bits(&mut self, bits: usize) -> T {
+        (**self).sample_uniform_bits(bits)
+    }
 }
 
 impl<C, F: Field> FieldChallenger<F> for &mut C
@@ -123,4 +193,40 @@ where
     fn sample_ext_element<EF: FieldExtensionAlgebra<F>>(&mut self) -> EF {
         (**self).sample_ext_element()
     }
+
+    #[inline(always)]
+    fn observe_domain_separator(&mut self, tag: &[u8]) {
+        (**self).observe_domain_separator(tag)
+    }
+
+    #[inline(always)]
+    fn observe_shape(&mut self, dims: &[Dimensions]) {
+        (**self).observe_shape(dims)
+    }
+
+    #[inline(always)]
+    fn begin_phase(&mut self, phase: &'static str) {
+        (**self).begin_phase(phase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_uniform_bits_rejects_tail() {
+        // Below an order of 11, masking to 3 bits would make 0, 1 and 2 twice as likely as the
+        // other values, so 8, 9 and 10 are rejected.
+        let mut draws = vec![9, 8, 10, 5, 10, 3, 7].into_iter();
+        let mut draw = || draws.next().unwrap();
+        assert_eq!(uniform_bits(11, 3, &mut draw), 5);
+        assert_eq!(uniform_bits(11, 3, &mut draw), 3);
+        assert_eq!(uniform_bits(11, 3, &mut draw), 7);
+
+        // Nothing is rejected when the order is a multiple of `2^bits`.
+        assert_eq!(uniform_bits(16, 3, || 13), 5);
+    }
 }
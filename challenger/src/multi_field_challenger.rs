@@ -1,11 +1,17 @@
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::iter;
 
 use p3_field::{reduce_32, split_32, ExtensionField, Field, PrimeField, PrimeField32};
 use p3_symmetric::{CryptographicPermutation, Hash};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
-use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger};
+use crate::{
+    uniform_bits, CanObserve, CanSample, CanSampleBits, CanSnapshot, FieldChallenger,
+    InvalidChallengerState, SpongeState,
+};
 
 /// A challenger that operates natively on PF but produces challenges of F: PrimeField32.
 ///
@@ -82,6 +88,26 @@ where
     PF: PrimeField,
     P: CryptographicPermutation<[PF; WIDTH]>,
 {
+    /// Pending observations are absorbed first, and then the tag's encoding is absorbed in blocks
+    /// of `num_f_elms * RATE` elements. Before each of these permutations, one is added to the
+    /// first element of the capacity, which ordinary observations never touch, so the tag can't be
+    /// confused with observed data.
+    fn observe_domain_separator(&mut self, tag: &[u8]) {
+        assert!(RATE < WIDTH, "domain separation needs a nonzero capacity");
+        self.output_buffer.clear();
+        if !self.input_buffer.is_empty() {
+            self.duplexing();
+        }
+
+        let encoding: Vec<F> = iter::once(F::from_canonical_usize(tag.len()))
+            .chain(tag.iter().map(|&byte| F::from_canonical_u8(byte)))
+            .collect();
+        for block in encoding.chunks(self.num_f_elms * RATE) {
+            self.input_buffer.extend_from_slice(block);
+            self.sponge_state[RATE] += PF::ONE;
+            self.duplexing();
+        }
+    }
 }
 
 impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanObserve<F>
@@ -188,4 +214,48 @@ where
         let rand_usize = rand_f.to_unique_u32() as usize;
         rand_usize & ((1 << bits) - 1)
     }
+
+    fn sample_uniform_bits(&mut self, bits: usize) -> usize {
+        debug_assert!(bits < (usize::BITS as usize));
+        debug_assert!((1 << bits) < F::ORDER_U32);
+        uniform_bits(F::ORDER_U64, bits, || {
+            let rand_f: F = self.sample();
+            rand_f.as_canonical_u64()
+        })
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanSnapshot
+    for MultiField32Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32 + Serialize + DeserializeOwned,
+    PF: PrimeField + Serialize + DeserializeOwned,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    type State = SpongeState<F, PF>;
+
+    fn snapshot(&self) -> Self::State {
+        SpongeState {
+            sponge_state: self.sponge_state.to_vec(),
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    fn restore(&mut self, state: Self::State) -> Result<(), InvalidChallengerState> {
+        // Every element of the sponge is squeezed into `num_f_elms` outputs.
+        let sponge_state = state
+            .sponge_state
+            .try_into()
+            .map_err(|_| InvalidChallengerState)?;
+        if state.input_buffer.len() >= self.num_f_elms * RATE
+            || state.output_buffer.len() > self.num_f_elms * WIDTH
+        {
+            return Err(InvalidChallengerState);
+        }
+        self.sponge_state = sponge_state;
+        self.input_buffer = state.input_buffer;
+        self.output_buffer = state.output_buffer;
+        Ok(())
+    }
 }
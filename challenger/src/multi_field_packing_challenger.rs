@@ -0,0 +1,425 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter;
+
+use num_bigint::BigUint;
+use p3_field::{ExtensionField, PrimeField, PrimeField32};
+use p3_symmetric::{CryptographicPermutation, Hash};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{
+    uniform_bits, CanObserve, CanSample, CanSampleBits, CanSnapshot, FieldChallenger,
+    InvalidChallengerState, SpongeState,
+};
+
+/// A challenger that operates natively on PF but observes and produces challenges of
+/// F: PrimeField32, e.g. a Poseidon sponge over BN254 for a BabyBear STARK.
+///
+/// Used for recursive proof verification of STARKs in SNARKs. Unlike
+/// [`MultiField32Challenger`](crate::MultiField32Challenger), samples are unbiased.
+///
+/// Observed elements are packed `num_limbs` at a time into one PF element, as the digits of a base
+/// `|F|` number. `num_limbs` is the largest `k` with `|F|^k <= |PF|`, so packing is injective; for
+/// BabyBear into BN254 it is 8. Challenges are the digits of sponge outputs in the same base, where
+/// outputs at or above the largest multiple of `|F|^num_limbs` below `|PF|` are discarded, so
+/// every digit is uniform and canonical.
+#[derive(Clone, Debug)]
+pub struct MultiFieldChallenger<F, PF, P, const WIDTH: usize, const RATE: usize>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    sponge_state: [PF; WIDTH],
+    input_buffer: Vec<F>,
+    output_buffer: Vec<F>,
+    permutation: P,
+    num_limbs: usize,
+    /// `|F|^num_limbs`, the number of values which `num_limbs` digits can take.
+    limbs_range: BigUint,
+    /// The largest multiple of `limbs_range` no bigger than `|PF|`.
+    sample_bound: BigUint,
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    pub fn new(permutation: P) -> Result<Self, String> {
+        let order = F::order();
+        let pf_order = PF::order();
+        if order >= pf_order {
+            return Err(String::from("F::order() must be less than PF::order()"));
+        }
+        assert!(RATE < WIDTH, "the sponge needs a capacity");
+
+        let mut num_limbs = 0;
+        let mut limbs_range = BigUint::from(1u32);
+        while &limbs_range * &order <= pf_order {
+            limbs_range *= &order;
+            num_limbs += 1;
+        }
+        let sample_bound = &pf_order / &limbs_range * &limbs_range;
+
+        Ok(Self {
+            sponge_state: [PF::default(); WIDTH],
+            input_buffer: vec![],
+            output_buffer: vec![],
+            permutation,
+            num_limbs,
+            limbs_range,
+            sample_bound,
+        })
+    }
+
+    /// The number of F elements packed into each PF element.
+    pub const fn num_limbs(&self) -> usize {
+        self.num_limbs
+    }
+
+    fn duplexing(&mut self) {
+        assert!(self.input_buffer.len() <= self.num_limbs * RATE);
+
+        for (i, limbs) in self.input_buffer.chunks(self.num_limbs).enumerate() {
+            self.sponge_state[i] = pack(limbs);
+        }
+        // Packing drops trailing zeros, so record how many elements were observed to tell e.g.
+        // [x] and [x, 0] apart.
+        self.sponge_state[WIDTH - 1] += PF::from_canonical_usize(self.input_buffer.len());
+        self.input_buffer.clear();
+
+        // Apply the permutation.
+        self.permutation.permute_mut(&mut self.sponge_state);
+
+        self.output_buffer.clear();
+        for pf_val in &self.sponge_state[..RATE] {
+            let pf_val = pf_val.as_canonical_biguint();
+            if pf_val < self.sample_bound {
+                self.output_buffer
+                    .extend(unpack(pf_val % &self.limbs_range, self.num_limbs));
+            }
+        }
+    }
+}
+
+/// The PF element whose base `|F|` digits, least significant first, are `limbs`.
+fn pack<F: PrimeField32, PF: PrimeField>(limbs: &[F]) -> PF {
+    let base = PF::from_canonical_u32(F::ORDER_U32);
+    limbs.iter().rev().fold(PF::ZERO, |acc, limb| {
+        acc * base + PF::from_canonical_u32(limb.as_canonical_u32())
+    })
+}
+
+/// The lowest `num_limbs` base `|F|` digits of `value`, least significant first.
+fn unpack<F: PrimeField32>(mut value: BigUint, num_limbs: usize) -> Vec<F> {
+    let base = BigUint::from(F::ORDER_U32);
+    (0..num_limbs)
+        .map(|_| {
+            let digit = &value % &base;
+            value /= &base;
+            F::from_canonical_u32(digit.to_u32_digits().first().copied().unwrap_or(0))
+        })
+        .collect()
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> FieldChallenger<F>
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    /// Pending observations are absorbed first, and then the tag's encoding is absorbed in blocks
+    /// of `num_limbs * RATE` elements. The last element of the capacity records the number of
+    /// elements in each block, and for these blocks it is also offset by `num_limbs * RATE + 1`,
+    /// which no block of ordinary observations reaches, so the tag can't be confused with
+    /// observed data.
+    fn observe_domain_separator(&mut self, tag: &[u8]) {
+        self.output_buffer.clear();
+        if !self.input_buffer.is_empty() {
+            self.duplexing();
+        }
+
+        let block_len = self.num_limbs * RATE;
+        let encoding: Vec<F> = iter::once(F::from_canonical_usize(tag.len()))
+            .chain(tag.iter().map(|&byte| F::from_canonical_u8(byte)))
+            .collect();
+        for block in encoding.chunks(block_len) {
+            self.input_buffer.extend_from_slice(block);
+            self.sponge_state[WIDTH - 1] += PF::from_canonical_usize(block_len + 1);
+            self.duplexing();
+        }
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanObserve<F>
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, value: F) {
+        // Any buffered output is now invalid.
+        self.output_buffer.clear();
+
+        self.input_buffer.push(value);
+
+        if self.input_buffer.len() == self.num_limbs * RATE {
+            self.duplexing();
+        }
+    }
+}
+
+impl<F, PF, const N: usize, P, const WIDTH: usize, const RATE: usize> CanObserve<[F; N]>
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, values: [F; N]) {
+        for value in values {
+            self.observe(value);
+        }
+    }
+}
+
+impl<F, PF, const N: usize, P, const WIDTH: usize, const RATE: usize> CanObserve<Hash<F, PF, N>>
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, values: Hash<F, PF, N>) {
+        // One more digit than is packed, since PF elements can be up to |F|^(num_limbs + 1).
+        for pf_val in values {
+            for f_val in unpack::<F>(pf_val.as_canonical_biguint(), self.num_limbs + 1) {
+                self.observe(f_val);
+            }
+        }
+    }
+}
+
+// for TrivialPcs
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanObserve<Vec<Vec<F>>>
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, valuess: Vec<Vec<F>>) {
+        for values in valuess {
+            for value in values {
+                self.observe(value);
+            }
+        }
+    }
+}
+
+impl<F, EF, PF, P, const WIDTH: usize, const RATE: usize> CanSample<EF>
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    EF: ExtensionField<F>,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn sample(&mut self) -> EF {
+        EF::from_base_fn(|_| {
+            // If we have buffered inputs, we must perform a duplexing so that the challenge will
+            // reflect them. Or if we've run out of outputs, we must perform a duplexing to get
+            // more, which may take several if every output is discarded.
+            if !self.input_buffer.is_empty() {
+                self.duplexing();
+            }
+            while self.output_buffer.is_empty() {
+                self.duplexing();
+            }
+
+            self.output_buffer
+                .pop()
+                .expect("Output buffer should be non-empty")
+        })
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanSampleBits<usize>
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        debug_assert!(bits < (usize::BITS as usize));
+        debug_assert!((1 << bits) < F::ORDER_U32);
+        let rand_f: F = self.sample();
+        let rand_usize = rand_f.to_unique_u32() as usize;
+        rand_usize & ((1 << bits) - 1)
+    }
+
+    fn sample_uniform_bits(&mut self, bits: usize) -> usize {
+        debug_assert!(bits < (usize::BITS as usize));
+        debug_assert!((1 << bits) < F::ORDER_U32);
+        uniform_bits(F::ORDER_U64, bits, || {
+            let rand_f: F = self.sample();
+            rand_f.as_canonical_u64()
+        })
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanSnapshot
+    for MultiFieldChallenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32 + Serialize + DeserializeOwned,
+    PF: PrimeField + Serialize + DeserializeOwned,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    type State = SpongeState<F, PF>;
+
+    fn snapshot(&self) -> Self::State {
+        SpongeState {
+            sponge_state: self.sponge_state.to_vec(),
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    fn restore(&mut self, state: Self::State) -> Result<(), InvalidChallengerState> {
+        // Each of the `RATE` outputs of the sponge gives at most `num_limbs` samples.
+        let sponge_state = state
+            .sponge_state
+            .try_into()
+            .map_err(|_| InvalidChallengerState)?;
+        if state.input_buffer.len() >= self.num_limbs * RATE
+            || state.output_buffer.len() > self.num_limbs * RATE
+        {
+            return Err(InvalidChallengerState);
+        }
+        self.sponge_state = sponge_state;
+        self.input_buffer = state.input_buffer;
+        self.output_buffer = state.output_buffer;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_bn254_fr::{Bn254Fr, Poseidon2Bn254};
+    use p3_field::FieldAlgebra;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    type F = BabyBear;
+    type PF = Bn254Fr;
+    type Challenger = MultiFieldChallenger<F, PF, Poseidon2Bn254<3>, 3, 2>;
+
+    fn challenger() -> Challenger {
+        let mut rng = StdRng::seed_from_u64(0);
+        Challenger::new(Poseidon2Bn254::new_from_rng(8, 56, &mut rng)).unwrap()
+    }
+
+    #[test]
+    fn packs_eight_babybear_limbs_into_bn254() {
+        assert_eq!(challenger().num_limbs(), 8);
+    }
+
+    #[test]
+    fn packing_round_trips() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let limbs: Vec<F> = (0..8).map(|_| rng.gen()).collect();
+            let packed: PF = pack(&limbs);
+            assert_eq!(unpack::<F>(packed.as_canonical_biguint(), 8), limbs);
+        }
+        // The largest limbs still fit.
+        let limbs = vec![F::NEG_ONE; 8];
+        let packed: PF = pack(&limbs);
+        assert_eq!(unpack::<F>(packed.as_canonical_biguint(), 8), limbs);
+
+        // Any PF element round trips through one more limb.
+        let pf_val: PF = rng.gen();
+        let limbs = unpack::<F>(pf_val.as_canonical_biguint(), 9);
+        assert_eq!(pack::<F, PF>(&limbs), pf_val);
+    }
+
+    #[test]
+    fn trailing_zeros_are_observed() {
+        let mut short = challenger();
+        short.observe(F::ONE);
+        let mut long = challenger();
+        long.observe_slice(&[F::ONE, F::ZERO]);
+        assert_ne!(
+            CanSample::<F>::sample(&mut short),
+            CanSample::<F>::sample(&mut long)
+        );
+    }
+
+    #[test]
+    fn domain_separator_differs_from_its_encoding() {
+        let mut tagged = challenger();
+        tagged.observe_domain_separator(b"ab");
+        let mut observed = challenger();
+        observed.observe_slice(&[
+            F::TWO,
+            F::from_canonical_u8(b'a'),
+            F::from_canonical_u8(b'b'),
+        ]);
+        assert_ne!(
+            CanSample::<F>::sample(&mut tagged),
+            CanSample::<F>::sample(&mut observed)
+        );
+    }
+
+    #[test]
+    fn samples_are_canonical_and_uniform() {
+        const NUM_SAMPLES: usize = 16_000;
+        const NUM_BUCKETS: usize = 16;
+
+        let mut challenger = challenger();
+        challenger.observe(F::ONE);
+        let mut counts = [0usize; NUM_BUCKETS];
+        for _ in 0..NUM_SAMPLES {
+            let sample: F = challenger.sample();
+            let value = sample.as_canonical_u32();
+            assert!(value < F::ORDER_U32);
+            counts[(value as u64 * NUM_BUCKETS as u64 / F::ORDER_U32 as u64) as usize] += 1;
+        }
+
+        // Chi-squared with 15 degrees of freedom, which exceeds 37.7 with probability 0.001.
+        let expected = (NUM_SAMPLES / NUM_BUCKETS) as f64;
+        let chi_squared: f64 = counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum();
+        assert!(chi_squared < 37.7, "chi-squared statistic {chi_squared}");
+    }
+
+    #[test]
+    fn snapshot_round_trips() {
+        let mut original = challenger();
+        original.observe_slice(&[F::ONE; 20]);
+        let _: F = original.sample();
+        original.observe(F::TWO);
+
+        let bytes = postcard::to_allocvec(&original.snapshot()).unwrap();
+        let mut restored = challenger();
+        restored
+            .restore(postcard::from_bytes(&bytes).unwrap())
+            .unwrap();
+
+        let expected: [F; 30] = original.sample_array();
+        let samples: [F; 30] = restored.sample_array();
+        assert_eq!(samples, expected);
+    }
+}
@@ -7,19 +7,30 @@ use p3_symmetric::{CryptographicHasher, Hash};
 use p3_util::log2_ceil_u64;
 use tracing::instrument;
 
+use crate::grinding_challenger::grinding_len;
 use crate::{
-    CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger, HashChallenger,
+    CanObserve, CanSample, CanSampleBits, CanSnapshot, FieldChallenger, GrindingChallenger,
+    HashChallenger, InvalidChallengerState,
 };
 
 /// Given a challenger that can observe and sample bytes, produces a challenger that is able to
 /// sample and observe field elements of a `PrimeField32`.
 ///
 /// **Observing**:
-/// -  Takes a field element will serialize it into a byte array and observe each byte.
+/// -  Takes a field element will serialize its canonical value into a byte array and observe each
+///    byte, so that the transcript doesn't depend on the field's internal representation, such as
+///    Montgomery form.
 ///
 /// **Sampling**:
-/// -  Samples a field element in a prime field of size `p` by sampling uniformly an element in the
-///    range (0..1 << log_2(p)). This avoids modulo bias.
+/// -  Samples a field element in a prime field of size `p` by reducing 8 sampled bytes, read as a
+///    little-endian `u64`, modulo `p`. As `p < 2^32`, the result is within `2^-32` of uniform,
+///    and a sample always takes exactly 8 bytes. Earlier versions rejection-sampled 4 bytes at a
+///    time, so their transcripts differ from this one.
+///
+/// Built with [`SerializingChallenger32::from_hasher`] over a byte hasher such as keccak256, the
+/// transcript only needs that hasher to be reproduced, e.g. by an EVM verifier. Digests are
+/// observed as their raw bytes, and samples take bytes from the end of the latest hash output.
+/// `tests/golden/serializing_challenger32.py` is a reference implementation over keccak256.
 #[derive(Clone, Debug)]
 pub struct SerializingChallenger32<F, Inner> {
     inner: Inner,
@@ -63,7 +74,7 @@ where
 impl<F: PrimeField32, Inner: CanObserve<u8>> CanObserve<F> for SerializingChallenger32<F, Inner> {
     fn observe(&mut self, value: F) {
         self.inner
-            .observe_slice(&value.to_unique_u32().to_le_bytes());
+            .observe_slice(&value.as_canonical_u32().to_le_bytes());
     }
 }
 
@@ -94,17 +105,9 @@ where
     Inner: CanSample<u8>,
 {
     fn sample(&mut self) -> EF {
-        let modulus = F::ORDER_U64 as u32;
-        let log_size = log2_ceil_u64(F::ORDER_U64);
-        // We use u64 to avoid overflow in the case that log_size = 32.
-        let pow_of_two_bound = ((1u64 << log_size) - 1) as u32;
-        // Perform rejection sampling over the uniform range (0..log2_ceil(p))
-        let sample_base = |inner: &mut Inner| loop {
-            let value = u32::from_le_bytes(inner.sample_array::<4>());
-            let value = value & pow_of_two_bound;
-            if value < modulus {
-                return F::from_canonical_u32(value);
-            }
+        let sample_base = |inner: &mut Inner| {
+            let value = u64::from_le_bytes(inner.sample_array::<8>());
+            F::from_canonical_u64(value % F::ORDER_U64)
         };
         EF::from_base_fn(|_| sample_base(&mut self.inner))
     }
@@ -120,6 +123,8 @@ where
         // Limiting the number of bits to the field size
         debug_assert!((1 << bits) <= F::ORDER_U64 as usize);
         let rand_usize = u32::from_le_bytes(self.inner.sample_array::<4>()) as usize;
+        // Masking uniformly random bytes is already unbiased, so the default
+        // `sample_uniform_bits` applies.
         rand_usize & ((1 << bits) - 1)
     }
 }
@@ -133,11 +138,14 @@ where
 
     #[instrument(name = "grind for proof-of-work witness", skip_all)]
     fn grind(&mut self, bits: usize) -> Self::Witness {
-        let witness = (0..F::ORDER_U64)
-            .into_par_iter()
-            .map(|i| F::from_canonical_u64(i))
-            .find_any(|witness| self.clone().check_witness(bits, *witness))
-            .expect("failed to find witness");
+        let this = &*self;
+        let witness = p3_maybe_rayon::install(grinding_len(bits), || {
+            (0..F::ORDER_U64)
+                .into_par_iter()
+                .map(|i| F::from_canonical_u64(i))
+                .find_first(|witness| this.clone().check_witness(bits, *witness))
+                .expect("failed to find witness")
+        });
         assert!(self.check_witness(bits, witness));
         witness
     }
@@ -148,6 +156,25 @@ where
     F: PrimeField32,
     Inner: CanSample<u8> + CanObserve<u8> + Clone + Send + Sync,
 {
+    /// The tag is observed directly as bytes: the marker `u32::MAX`, which isn't the encoding of
+    /// any field element, then the tag's length as a little-endian `u64`, then the tag itself.
+    fn observe_domain_separator(&mut self, tag: &[u8]) {
+        self.inner.observe_slice(&u32::MAX.to_le_bytes());
+        self.inner.observe_slice(&(tag.len() as u64).to_le_bytes());
+        self.inner.observe_slice(tag);
+    }
+}
+
+impl<F, Inner: CanSnapshot> CanSnapshot for SerializingChallenger32<F, Inner> {
+    type State = Inner::State;
+
+    fn snapshot(&self) -> Self::State {
+        self.inner.snapshot()
+    }
+
+    fn restore(&mut self, state: Self::State) -> Result<(), InvalidChallengerState> {
+        self.inner.restore(state)
+    }
 }
 
 impl<F: PrimeField64, Inner: CanObserve<u8>> SerializingChallenger64<F, Inner> {
@@ -230,6 +257,8 @@ where
         // Limiting the number of bits to the field size
         debug_assert!((1 << bits) <= F::ORDER_U64 as usize);
         let rand_usize = u64::from_le_bytes(self.inner.sample_array::<8>()) as usize;
+        // Masking uniformly random bytes is already unbiased, so the default
+        // `sample_uniform_bits` applies.
         rand_usize & ((1 << bits) - 1)
     }
 }
@@ -243,11 +272,14 @@ where
 
     #[instrument(name = "grind for proof-of-work witness", skip_all)]
     fn grind(&mut self, bits: usize) -> Self::Witness {
-        let witness = (0..F::ORDER_U64)
-            .into_par_iter()
-            .map(|i| F::from_canonical_u64(i))
-            .find_any(|witness| self.clone().check_witness(bits, *witness))
-            .expect("failed to find witness");
+        let this = &*self;
+        let witness = p3_maybe_rayon::install(grinding_len(bits), || {
+            (0..F::ORDER_U64)
+                .into_par_iter()
+                .map(|i| F::from_canonical_u64(i))
+                .find_first(|witness| this.clone().check_witness(bits, *witness))
+                .expect("failed to find witness")
+        });
         assert!(self.check_witness(bits, witness));
         witness
     }
@@ -258,4 +290,88 @@ where
     F: PrimeField64,
     Inner: CanSample<u8> + CanObserve<u8> + Clone + Send + Sync,
 {
+    /// The tag is observed directly as bytes: the marker `u64::MAX`, which isn't the encoding of
+    /// any field element, then the tag's length as a little-endian `u64`, then the tag itself.
+    fn observe_domain_separator(&mut self, tag: &[u8]) {
+        self.inner.observe_slice(&u64::MAX.to_le_bytes());
+        self.inner.observe_slice(&(tag.len() as u64).to_le_bytes());
+        self.inner.observe_slice(tag);
+    }
+}
+
+impl<F, Inner: CanSnapshot> CanSnapshot for SerializingChallenger64<F, Inner> {
+    type State = Inner::State;
+
+    fn snapshot(&self) -> Self::State {
+        self.inner.snapshot()
+    }
+
+    fn restore(&mut self, state: Self::State) -> Result<(), InvalidChallengerState> {
+        self.inner.restore(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{FieldAlgebra, FieldExtensionAlgebra};
+    use p3_keccak::Keccak256Hash;
+
+    use super::*;
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+
+    fn decode_hex(hex: &str) -> Vec<u8> {
+        let digits: Vec<u8> = hex
+            .bytes()
+            .map(|b| (b as char).to_digit(16).unwrap() as u8)
+            .collect();
+        digits.chunks_exact(2).map(|d| (d[0] << 4) | d[1]).collect()
+    }
+
+    #[test]
+    fn keccak_transcript_matches_reference() {
+        // Generated by `tests/golden/serializing_challenger32.py`, which describes the format.
+        let fixture = include_str!("../tests/golden/serializing_challenger32_keccak.txt");
+        let mut challenger = SerializingChallenger32::<F, _>::from_hasher(vec![], Keccak256Hash);
+        let parse = |s: &str| s.parse::<u32>().unwrap();
+
+        for line in fixture.lines() {
+            let args: Vec<&str> = line.split(' ').collect();
+            match args[0] {
+                "observe" => challenger.observe(F::from_canonical_u32(parse(args[1]))),
+                "observe_digest" => {
+                    let digest: [u8; 32] = decode_hex(args[1]).try_into().unwrap();
+                    challenger.observe(Hash::<F, u8, 32>::from(digest));
+                }
+                "domain_separator" => challenger.observe_domain_separator(&decode_hex(args[1])),
+                "sample" => {
+                    let sample: F = challenger.sample();
+                    assert_eq!(sample.as_canonical_u32(), parse(args[1]), "{line}");
+                }
+                "sample_ext" => {
+                    let sample: EF = challenger.sample();
+                    let coeffs = <EF as FieldExtensionAlgebra<F>>::as_base_slice(&sample)
+                        .iter()
+                        .map(|c| c.as_canonical_u32())
+                        .collect::<Vec<_>>();
+                    let expected = args[1..].iter().map(|c| parse(*c)).collect::<Vec<_>>();
+                    assert_eq!(coeffs, expected, "{line}");
+                }
+                "sample_bits" => {
+                    let bits = parse(args[1]) as usize;
+                    assert_eq!(
+                        challenger.sample_bits(bits),
+                        parse(args[2]) as usize,
+                        "{line}"
+                    );
+                }
+                op => panic!("unknown transcript operation {op}"),
+            }
+        }
+    }
 }
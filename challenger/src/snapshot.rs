@@ -0,0 +1,49 @@
+use alloc::vec::Vec;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A challenger whose transcript can be saved and picked up again, e.g. by a prover resuming on
+/// another machine. The saved state covers only what has been observed and sampled; the hasher or
+/// permutation isn't part of it, and must be the same in the challenger it's restored into.
+pub trait CanSnapshot {
+    type State: Clone + Serialize + DeserializeOwned;
+
+    /// The state of the transcript so far.
+    fn snapshot(&self) -> Self::State;
+
+    /// Continue the transcript from `state`, discarding this challenger's own.
+    ///
+    /// Fails, leaving this challenger unchanged, if `state` couldn't have been taken from a
+    /// challenger of this type and parameters.
+    fn restore(&mut self, state: Self::State) -> Result<(), InvalidChallengerState>;
+}
+
+/// The state of a sponge-based challenger: its sponge, which is empty for challengers which hash
+/// their whole input at once, the observations it hasn't absorbed yet, and the samples it has
+/// squeezed but not handed out.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpongeState<T, S = T> {
+    pub sponge_state: Vec<S>,
+    pub input_buffer: Vec<T>,
+    pub output_buffer: Vec<T>,
+}
+
+/// A challenger state which doesn't fit the challenger it's restored into, e.g. because its sponge
+/// has a different width.
+#[derive(Debug)]
+pub struct InvalidChallengerState;
+
+impl<C: CanSnapshot> CanSnapshot for &mut C {
+    type State = C::State;
+
+    #[inline(always)]
+    fn snapshot(&self) -> Self::State {
+        (**self).snapshot()
+    }
+
+    #[inline(always)]
+    fn restore(&mut self, state: Self::State) -> Result<(), InvalidChallengerState> {
+        (**self).restore(state)
+    }
+}
@@ -0,0 +1,301 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Write};
+
+use p3_field::{Field, FieldExtensionAlgebra};
+
+use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger};
+
+/// One observation or sample recorded by a [`TranscriptRecorder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptEvent {
+    /// An observed value, by its `Debug` representation.
+    Observe(String),
+    /// A sampled value, by its `Debug` representation.
+    Sample(String),
+    SampleBits {
+        bits: usize,
+        value: usize,
+    },
+    DomainSeparator(Vec<u8>),
+    /// A proof-of-work witness, whether found by `grind` or checked by `check_witness`.
+    Witness {
+        bits: usize,
+        witness: String,
+    },
+}
+
+/// A [`TranscriptEvent`], labelled with the phase which was begun most recently before it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    pub phase: &'static str,
+    pub event: TranscriptEvent,
+}
+
+/// A challenger which records everything observed and sampled through it, for finding where a
+/// prover and verifier stop agreeing when a proof is rejected.
+///
+/// It samples exactly what `inner` would, so a proof made with a recorder verifies without one and
+/// vice versa. Run the prover and the verifier each with their own recorder, and compare their
+/// entries with [`diff_transcripts`].
+#[derive(Clone, Debug)]
+pub struct TranscriptRecorder<C> {
+    pub inner: C,
+    phase: &'static str,
+    entries: Vec<TranscriptEntry>,
+}
+
+impl<C> TranscriptRecorder<C> {
+    pub const fn new(inner: C) -> Self {
+        Self {
+            inner,
+            phase: "",
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<TranscriptEntry> {
+        self.entries
+    }
+
+    /// Write the entries to `writer`, one per line.
+    pub fn write_log<W: Write>(&self, writer: &mut W) -> fmt::Result {
+        for (i, entry) in self.entries.iter().enumerate() {
+            writeln!(writer, "{i} [{}] {:?}", entry.phase, entry.event)?;
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, event: TranscriptEvent) {
+        self.entries.push(TranscriptEntry {
+            phase: self.phase,
+            event,
+        });
+    }
+}
+
+impl<C, T> CanObserve<T> for TranscriptRecorder<C>
+where
+    C: CanObserve<T>,
+    T: Debug,
+{
+    fn observe(&mut self, value: T) {
+        self.record(TranscriptEvent::Observe(format!("{value:?}")));
+        self.inner.observe(value);
+    }
+}
+
+impl<C, T> CanSample<T> for TranscriptRecorder<C>
+where
+    C: CanSample<T>,
+    T: Debug,
+{
+    fn sample(&mut self) -> T {
+        let value = self.inner.sample();
+        self.record(TranscriptEvent::Sample(format!("{value:?}")));
+        value
+    }
+}
+
+impl<C> CanSampleBits<usize> for TranscriptRecorder<C>
+where
+    C: CanSampleBits<usize>,
+{
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        let value = self.inner.sample_bits(bits);
+        self.record(TranscriptEvent::SampleBits { bits, value });
+        value
+    }
+
+    fn sample_uniform_bits(&mut self, bits: usize) -> usize {
+        let value = self.inner.sample_uniform_bits(bits);
+        self.record(TranscriptEvent::SampleBits { bits, value });
+        value
+    }
+}
+
+/// Extension elements and domain separators go to `inner` whole, since it may absorb or sample
+/// them differently from element by element.
+impl<C, F> FieldChallenger<F> for TranscriptRecorder<C>
+where
+    C: FieldChallenger<F>,
+    F: Field,
+{
+    fn observe_ext_element<EF: FieldExtensionAlgebra<F>>(&mut self, ext: EF) {
+        self.record(TranscriptEvent::Observe(format!("{ext:?}")));
+        self.inner.observe_ext_element(ext);
+    }
+
+    fn sample_ext_element<EF: FieldExtensionAlgebra<F>>(&mut self) -> EF {
+        let value: EF = self.inner.sample_ext_element();
+        self.record(TranscriptEvent::Sample(format!("{value:?}")));
+        value
+    }
+
+    fn observe_domain_separator(&mut self, tag: &[u8]) {
+        self.record(TranscriptEvent::DomainSeparator(tag.to_vec()));
+        self.inner.observe_domain_separator(tag);
+    }
+
+    fn begin_phase(&mut self, phase: &'static str) {
+        self.phase = phase;
+        self.inner.begin_phase(phase);
+    }
+}
+
+impl<C> GrindingChallenger for TranscriptRecorder<C>
+where
+    C: GrindingChallenger,
+{
+    type Witness = C::Witness;
+
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        let witness = self.inner.grind(bits);
+        self.record(TranscriptEvent::Witness {
+            bits,
+            witness: format!("{witness:?}"),
+        });
+        witness
+    }
+
+    fn check_witness(&mut self, bits: usize, witness: Self::Witness) -> bool {
+        self.record(TranscriptEvent::Witness {
+            bits,
+            witness: format!("{witness:?}"),
+        });
+        self.inner.check_witness(bits, witness)
+    }
+}
+
+/// The first entry at which two transcripts differ. One of them is missing if its transcript
+/// ended there, e.g. because the verifier rejected the proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DivergencePoint {
+    pub index: usize,
+    pub prover: Option<TranscriptEntry>,
+    pub verifier: Option<TranscriptEntry>,
+}
+
+impl DivergencePoint {
+    /// The phase in which the transcripts diverged, as labelled by whichever has an entry here.
+    pub fn phase(&self) -> &'static str {
+        self.verifier
+            .as_ref()
+            .or(self.prover.as_ref())
+            .map_or("", |entry| entry.phase)
+    }
+}
+
+/// Compare the entries recorded by the prover's and the verifier's [`TranscriptRecorder`]s,
+/// returning the first point at which they differ, or `None` if they're the same.
+pub fn diff_transcripts(
+    prover_log: &[TranscriptEntry],
+    verifier_log: &[TranscriptEntry],
+) -> Option<DivergencePoint> {
+    let index = prover_log
+        .iter()
+        .zip(verifier_log)
+        .position(|(p, v)| p != v)
+        .unwrap_or_else(|| prover_log.len().min(verifier_log.len()));
+    if index == prover_log.len() && index == verifier_log.len() {
+        return None;
+    }
+    Some(DivergencePoint {
+        index,
+        prover: prover_log.get(index).cloned(),
+        verifier: verifier_log.get(index).cloned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_field::FieldAlgebra;
+    use p3_goldilocks::Goldilocks;
+    use p3_symmetric::{CryptographicPermutation, Permutation};
+
+    use super::*;
+    use crate::DuplexChallenger;
+
+    type F = Goldilocks;
+
+    #[derive(Clone)]
+    struct MixingPermutation;
+
+    impl Permutation<[F; 8]> for MixingPermutation {
+        fn permute_mut(&self, input: &mut [F; 8]) {
+            let total: F = input.iter().copied().sum();
+            for (i, x) in input.iter_mut().enumerate() {
+                *x += total * F::from_canonical_usize(i + 1);
+            }
+        }
+    }
+
+    impl CryptographicPermutation<[F; 8]> for MixingPermutation {}
+
+    type Challenger = DuplexChallenger<F, MixingPermutation, 8, 4>;
+
+    fn transcript(observed: &[u64]) -> (Vec<F>, TranscriptRecorder<Challenger>) {
+        let mut challenger = TranscriptRecorder::new(Challenger::new(MixingPermutation));
+        FieldChallenger::<F>::begin_phase(&mut challenger, "first");
+        challenger.observe(F::from_canonical_u64(observed[0]));
+        let first: F = challenger.sample();
+        FieldChallenger::<F>::begin_phase(&mut challenger, "second");
+        challenger.observe_slice(
+            &observed[1..]
+                .iter()
+                .map(|&x| F::from_canonical_u64(x))
+                .collect::<Vec<_>>(),
+        );
+        let second: F = challenger.sample();
+        (vec![first, second], challenger)
+    }
+
+    #[test]
+    fn test_recorder_samples_as_inner() {
+        let mut plain = Challenger::new(MixingPermutation);
+        plain.observe(F::from_canonical_u64(1));
+        let first: F = plain.sample();
+        plain.observe_slice(&[F::from_canonical_u64(2), F::from_canonical_u64(3)]);
+        let second: F = plain.sample();
+
+        let (samples, recorder) = transcript(&[1, 2, 3]);
+        assert_eq!(samples, vec![first, second]);
+        let entries = recorder.entries();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[1].phase, "first");
+        assert_eq!(
+            entries[1].event,
+            TranscriptEvent::Sample(format!("{first:?}"))
+        );
+        assert_eq!(entries[4].phase, "second");
+
+        let mut log = String::new();
+        recorder.write_log(&mut log).unwrap();
+        assert_eq!(log.lines().count(), 5);
+    }
+
+    #[test]
+    fn test_diff_transcripts() {
+        let entries = transcript(&[1, 2, 3]).1.into_entries();
+        assert_eq!(diff_transcripts(&entries, &entries), None);
+
+        // A different observation in the second phase.
+        let other = transcript(&[1, 2, 4]).1.into_entries();
+        let divergence = diff_transcripts(&entries, &other).unwrap();
+        assert_eq!(divergence.index, 3);
+        assert_eq!(divergence.phase(), "second");
+
+        // A transcript which stops early.
+        let divergence = diff_transcripts(&entries, &entries[..2]).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.verifier, None);
+        assert_eq!(divergence.phase(), "second");
+    }
+}
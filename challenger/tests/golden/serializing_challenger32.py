@@ -0,0 +1,153 @@
+"""Reference model of `SerializingChallenger32::from_hasher` over keccak256.
+
+Writes `serializing_challenger32_keccak.txt`, which the Rust tests replay. Each line is one
+transcript operation followed by its arguments and, for samples, the expected output:
+
+    observe <u32>                   a field element, as 4 little-endian bytes
+    observe_digest <hex>            a digest, as its raw bytes
+    domain_separator <hex>          the marker 0xffffffff, the tag's length as 8 little-endian
+                                    bytes, then the tag
+    sample <u32>                    a base field element, as 8 sampled little-endian bytes mod P
+    sample_ext <u32> <u32> ...      an extension field element, by its base coefficients
+    sample_bits <bits> <usize>      `bits` low bits of 4 sampled bytes
+
+Samples take bytes from the end of the most recent keccak256 output, and hash the running input
+(the previous output followed by everything observed since) when it runs out.
+"""
+
+import os
+
+P = 2**31 - 2**27 + 1  # BabyBear
+EXT_DEGREE = 4
+
+RC = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+]
+ROTATIONS = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+]
+MASK = 2**64 - 1
+
+
+def rotl(x, n):
+    return ((x << n) | (x >> (64 - n))) & MASK if n else x
+
+
+def keccak_f(a):
+    for rc in RC:
+        c = [a[x][0] ^ a[x][1] ^ a[x][2] ^ a[x][3] ^ a[x][4] for x in range(5)]
+        d = [c[(x - 1) % 5] ^ rotl(c[(x + 1) % 5], 1) for x in range(5)]
+        a = [[a[x][y] ^ d[x] for y in range(5)] for x in range(5)]
+        b = [[0] * 5 for _ in range(5)]
+        for x in range(5):
+            for y in range(5):
+                b[y][(2 * x + 3 * y) % 5] = rotl(a[x][y], ROTATIONS[x][y])
+        a = [
+            [b[x][y] ^ (~b[(x + 1) % 5][y] & b[(x + 2) % 5][y]) for y in range(5)]
+            for x in range(5)
+        ]
+        a[0][0] ^= rc
+    return a
+
+
+def keccak256(data):
+    rate = 136
+    data = bytearray(data) + b"\x01" + b"\x00" * (rate - 1 - len(data) % rate)
+    data[-1] |= 0x80
+    a = [[0] * 5 for _ in range(5)]
+    for offset in range(0, len(data), rate):
+        block = data[offset : offset + rate]
+        for i in range(rate // 8):
+            a[i % 5][i // 5] ^= int.from_bytes(block[8 * i : 8 * i + 8], "little")
+        a = keccak_f(a)
+    return b"".join(a[i % 5][i // 5].to_bytes(8, "little") for i in range(4))
+
+
+class Transcript:
+    def __init__(self, initial_state):
+        self.input = bytearray(initial_state)
+        self.output = bytearray()
+        self.lines = []
+
+    def observe_bytes(self, data):
+        self.output.clear()
+        self.input += data
+
+    def sample_byte(self):
+        if not self.output:
+            digest = keccak256(self.input)
+            self.input = bytearray(digest)
+            self.output = bytearray(digest)
+        return self.output.pop()
+
+    def sample_uint(self, num_bytes):
+        return int.from_bytes(bytes(self.sample_byte() for _ in range(num_bytes)), "little")
+
+    def sample_base(self):
+        return self.sample_uint(8) % P
+
+    def observe(self, value):
+        self.observe_bytes(value.to_bytes(4, "little"))
+        self.lines.append(f"observe {value}")
+
+    def observe_digest(self, digest):
+        self.observe_bytes(digest)
+        self.lines.append(f"observe_digest {digest.hex()}")
+
+    def domain_separator(self, tag):
+        self.observe_bytes(b"\xff" * 4 + len(tag).to_bytes(8, "little") + tag)
+        self.lines.append(f"domain_separator {tag.hex()}")
+
+    def sample(self):
+        self.lines.append(f"sample {self.sample_base()}")
+
+    def sample_ext(self):
+        coeffs = [self.sample_base() for _ in range(EXT_DEGREE)]
+        self.lines.append("sample_ext " + " ".join(map(str, coeffs)))
+
+    def sample_bits(self, bits):
+        value = self.sample_uint(4) & ((1 << bits) - 1)
+        self.lines.append(f"sample_bits {bits} {value}")
+
+
+def main():
+    assert keccak256(b"").hex() == (
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    )
+
+    t = Transcript(b"")
+    t.domain_separator(b"plonky3 transcript test")
+    for value in [0, 1, 2, P - 1]:
+        t.observe(value)
+    t.sample()
+    t.sample()
+    t.observe_digest(keccak256(b"trace commitment"))
+    t.sample_ext()
+    t.sample_ext()
+    t.observe(12345)
+    t.sample_bits(1)
+    t.sample_bits(20)
+    t.sample_bits(27)
+    for i in range(40):
+        t.sample()
+    t.observe_digest(keccak256(b"quotient commitment"))
+    t.observe(P - 2)
+    t.sample_ext()
+    t.sample_bits(24)
+
+    path = os.path.join(os.path.dirname(__file__), "serializing_challenger32_keccak.txt")
+    with open(path, "w") as f:
+        f.write("\n".join(t.lines) + "\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,21 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_circle::CircleDomain;
+use p3_commit::PolynomialSpace;
+use p3_mersenne_31::Mersenne31;
+
+/// The selectors of a trace domain over a quotient domain twice its size, which the prover
+/// computes once per proof.
+fn bench_selectors_on_coset(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selectors_on_coset");
+    group.sample_size(10);
+    for log_n in [12, 16, 20] {
+        let trace_domain = CircleDomain::<Mersenne31>::standard(log_n);
+        let quotient_domain = trace_domain.create_disjoint_domain(1 << (log_n + 1));
+        group.bench_function(BenchmarkId::from_parameter(1 << log_n), |b| {
+            b.iter(|| trace_domain.selectors_on_coset(quotient_domain))
+        });
+    }
+}
+
+criterion_group!(benches, bench_selectors_on_coset);
+criterion_main!(benches);
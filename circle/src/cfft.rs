@@ -3,11 +3,11 @@ use alloc::vec::Vec;
 
 use itertools::{iterate, izip, Itertools};
 use p3_commit::PolynomialSpace;
-use p3_dft::{divide_by_height, Butterfly, DifButterfly, DitButterfly};
+use p3_dft::{divide_by_height, Butterfly, DifButterfly, DitButterfly, LdeOrder};
 use p3_field::extension::ComplexExtendable;
 use p3_field::{batch_multiplicative_inverse, ExtensionField, Field};
 use p3_matrix::dense::RowMajorMatrix;
-use p3_matrix::Matrix;
+use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_ceil_usize, log2_strict_usize, reverse_slice_index_bits};
 use tracing::{debug_span, instrument};
@@ -99,6 +99,45 @@ impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
         CircleEvaluations::<F>::evaluate(target_domain, self.interpolate())
     }
 
+    /// Like `extrapolate`, but writes the evaluations over `target_domain` into `out` with rows in
+    /// the given order, where `LdeOrder::BitReversed` means cfft order.
+    ///
+    /// `out` must already have the dimensions of the extrapolation. Its storage is reused, so no
+    /// second buffer of that size is allocated.
+    #[instrument(skip_all, fields(dims = %self.values.dimensions()))]
+    pub fn extrapolate_into(
+        self,
+        target_domain: CircleDomain<F>,
+        out: &mut RowMajorMatrix<F>,
+        order: LdeOrder,
+    ) {
+        assert!(target_domain.log_n >= self.domain.log_n);
+        let lde_dims = Dimensions {
+            width: self.values.width(),
+            height: target_domain.size(),
+        };
+        assert_eq!(
+            out.dimensions(),
+            lde_dims,
+            "the output matrix must have the dimensions of the extrapolation"
+        );
+
+        let coeffs = self.interpolate();
+        let mut values = core::mem::take(&mut out.values);
+        values.clear();
+        values.extend_from_slice(&coeffs.values);
+        drop(coeffs);
+
+        let evals = CircleEvaluations::<F>::evaluate(
+            target_domain,
+            RowMajorMatrix::new(values, lde_dims.width),
+        );
+        *out = match order {
+            LdeOrder::Natural => evals.to_natural_order().to_row_major_matrix(),
+            LdeOrder::BitReversed => evals.to_cfft_order(),
+        };
+    }
+
     pub fn evaluate_at_point<EF: ExtensionField<F>>(&self, point: Point<EF>) -> Vec<EF> {
         // Compute z_H
         let lagrange_num = self.domain.zeroifier(point);
@@ -147,7 +186,9 @@ impl<F: ComplexExtendable> CircleEvaluations<F, RowMajorMatrix<F>> {
             // both `x_1` and `x_2` are set to `x_1`).
             // So instead we directly repeat the coeffs and skip the initial layers.
             debug_span!("extend coeffs").in_scope(|| {
-                coeffs.values.reserve(domain.size() * coeffs.width());
+                coeffs
+                    .values
+                    .reserve((domain.size() - coeffs.height()) * coeffs.width());
                 for _ in log_n..domain.log_n {
                     coeffs.values.extend_from_within(..);
                 }
@@ -334,6 +375,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn extrapolate_into_matches_extrapolate() {
+        for (log_n, log_blowup) in iproduct!(2..5, [1, 2]) {
+            let evals = CircleEvaluations::<F>::from_natural_order(
+                CircleDomain::standard(log_n),
+                RowMajorMatrix::rand(&mut thread_rng(), 1 << log_n, 3),
+            );
+            let lde_domain = CircleDomain::standard(log_n + log_blowup);
+            let lde = evals.clone().extrapolate(lde_domain).to_cfft_order();
+
+            let mut out = RowMajorMatrix::default(3, lde_domain.size());
+            evals
+                .clone()
+                .extrapolate_into(lde_domain, &mut out, LdeOrder::BitReversed);
+            assert_eq!(out, lde);
+
+            evals.extrapolate_into(lde_domain, &mut out, LdeOrder::Natural);
+            assert_eq!(out, lde.cfft_perm_rows().to_row_major_matrix());
+        }
+    }
+
     #[test]
     fn eval_at_point_matches_cfft() {
         for (log_n, width) in iproduct!(2..5, [1, 4, 11]) {
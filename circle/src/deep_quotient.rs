@@ -199,7 +199,7 @@ mod tests {
         let alpha: EF = random();
         let zeta: Point<EF> = Point::from_projective_line(random());
 
-        let mut alpha_offset = EF::ONE;
+        let mut alpha_offset = alpha.powers();
         let mut ros = vec![EF::ZERO; 1 << lde_domain.log_n];
 
         for _ in 0..4 {
@@ -212,9 +212,9 @@ mod tests {
             assert!(lde.dim() <= (1 << domain.log_n) + 1);
             let mat_ros = lde.deep_quotient_reduce(alpha, zeta, &ps_at_zeta);
             for (ro, mat_ro) in izip!(&mut ros, mat_ros) {
-                *ro += alpha_offset * mat_ro;
+                *ro += alpha_offset.current * mat_ro;
             }
-            alpha_offset *= alpha.exp_u64(2 * lde.values.width() as u64);
+            alpha_offset.advance_by(2 * lde.values.width() as u64);
         }
 
         let ros = CircleEvaluations::from_cfft_order(
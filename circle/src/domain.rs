@@ -1,10 +1,11 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::iter;
 
 use itertools::{iterate, Itertools};
 use p3_commit::{LagrangeSelectors, PolynomialSpace};
 use p3_field::extension::ComplexExtendable;
-use p3_field::ExtensionField;
+use p3_field::{batch_multiplicative_inverse, ExtensionField};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_util::{log2_ceil_usize, log2_strict_usize};
@@ -104,6 +105,10 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
         self.shift.to_projective_line().unwrap()
     }
 
+    /// The point of the following row. Rows are laid out in the order of `points`, which in
+    /// standard position steps counterclockwise by the generator of the order `2^log_n` subgroup,
+    /// so this rotates `x` forward by that generator. Rotating the other way would give the
+    /// previous row, and transition constraints would then hold on the wrong pairs.
     fn next_point<Ext: ExtensionField<Self::Val>>(&self, x: Ext) -> Option<Ext> {
         // Only in standard position do we have an algebraic expression to access the next point.
         if self.is_standard() {
@@ -200,19 +205,60 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
     chunks=2: 0 1 1 0 0 1 1 0 0 1 1 0 0 1 1 0
     chunks=4: 0 1 2 3 3 2 1 0 0 1 2 3 3 2 1 0
     */
-    // wow, really slow!
-    // todo: batch inverses
+    /// Evaluate the selectors over `coset` with a single batch inversion.
+    ///
+    /// For a point `p` with `p - P = (x, y)`, `s_P(p) = Z(p) (x + 1) / y`, so only the `y`s need
+    /// inverting. The zeroifier `Z(p) = v_n(p) - v_n(shift)` only depends on `2^(log_n - 1) p`,
+    /// which repeats along each half of the twin coset, so few of its values need computing and
+    /// inverting.
     #[instrument(skip_all, fields(log_n = %coset.log_n))]
     fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Self::Val>> {
-        let sels = coset
-            .points()
-            .map(|p| self.selectors_at_point(p.to_projective_line().unwrap()))
+        let points = coset.points().collect_vec();
+        let size = points.len();
+
+        // Along each half of the coset, `2^(log_n - 1) p` has period `2^(coset.log_n - log_n)`,
+        // so along their interleaving it has twice that. It is constant if the coset is no bigger.
+        let log_period = (coset.log_n + 1)
+            .saturating_sub(self.log_n)
+            .min(coset.log_n);
+        let period = 1 << log_period;
+        let shift_v_n = self.shift.v_n(self.log_n);
+        let zeroifier = points[..period]
+            .iter()
+            .map(|p| p.v_n(self.log_n) - shift_v_n)
+            .collect_vec();
+
+        // The differences from the first and last points, `shift` and `-shift`.
+        let diffs = points
+            .iter()
+            .map(|&p| p - self.shift)
+            .chain(points.iter().map(|&p| p + self.shift))
             .collect_vec();
+        let last_row_normalizer = (-self.shift).s_p_at_p(self.log_n);
+
+        let denoms = zeroifier
+            .iter()
+            .copied()
+            .chain(diffs.iter().map(|d| d.y))
+            .chain(iter::once(last_row_normalizer))
+            .collect_vec();
+        let inverses = batch_multiplicative_inverse(&denoms);
+        let (inv_zeroifier, rest) = inverses.split_at(period);
+        let (inv_ys, inv_last_row_normalizer) = rest.split_at(2 * size);
+        let inv_last_row_normalizer = inv_last_row_normalizer[0];
+
+        // The selector at point `i`, where `diffs[j]` is that point minus the one selected.
+        let selector =
+            |i: usize, j: usize| zeroifier[i % period] * (diffs[j].x + F::ONE) * inv_ys[j];
+        let is_last_row = (0..size).map(|i| selector(i, size + i)).collect_vec();
         LagrangeSelectors {
-            is_first_row: sels.iter().map(|s| s.is_first_row).collect(),
-            is_last_row: sels.iter().map(|s| s.is_last_row).collect(),
-            is_transition: sels.iter().map(|s| s.is_transition).collect(),
-            inv_zeroifier: sels.iter().map(|s| s.inv_zeroifier).collect(),
+            is_first_row: (0..size).map(|i| selector(i, i)).collect(),
+            is_transition: is_last_row
+                .iter()
+                .map(|&s| F::ONE - s * inv_last_row_normalizer)
+                .collect(),
+            is_last_row,
+            inv_zeroifier: inv_zeroifier.iter().copied().cycle().take(size).collect(),
         }
     }
 }
@@ -396,6 +442,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn selectors_on_coset_match_per_point() {
+        type F = Mersenne31;
+        for log_n in 2..6 {
+            let d = CircleDomain::<F>::standard(log_n);
+            for log_blowup in 1..=3 {
+                let log_m = log_n + log_blowup;
+                for coset in [
+                    CircleDomain::standard(log_m),
+                    CircleDomain::new(log_m, Point::generator(log_m + 3)),
+                ] {
+                    let sels = d.selectors_on_coset(coset);
+                    for (i, p) in coset.points().enumerate() {
+                        let pt_sels = d.selectors_at_point(p.to_projective_line().unwrap());
+                        assert_eq!(sels.is_first_row[i], pt_sels.is_first_row);
+                        assert_eq!(sels.is_last_row[i], pt_sels.is_last_row);
+                        assert_eq!(sels.is_transition[i], pt_sels.is_transition);
+                        assert_eq!(sels.inv_zeroifier[i], pt_sels.inv_zeroifier);
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_circle_domain() {
         do_test_circle_domain(4, 8);
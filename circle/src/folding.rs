@@ -2,11 +2,11 @@ use alloc::vec::Vec;
 use core::fmt::Debug;
 use core::marker::PhantomData;
 
-use itertools::Itertools;
 use p3_commit::Mmcs;
 use p3_field::extension::ComplexExtendable;
 use p3_field::{batch_multiplicative_inverse, ExtensionField};
-use p3_fri::FriGenericConfig;
+use p3_fri::{fold_matrix_generic, fold_row_generic, FriGenericConfig};
+use p3_matrix::dense::RowMajorMatrixView;
 use p3_matrix::Matrix;
 use p3_util::{log2_strict_usize, reverse_bits_len};
 
@@ -33,47 +33,24 @@ impl<F: ComplexExtendable, EF: ExtensionField<F>, InputProof, InputError: Debug>
         1
     }
 
-    fn fold_row(
-        &self,
-        index: usize,
-        log_folded_height: usize,
-        beta: EF,
-        evals: impl Iterator<Item = EF>,
-    ) -> EF {
-        fold_x_row(index, log_folded_height, beta, evals)
+    fn fold_row(&self, index: usize, log_folded_height: usize, beta: EF, evals: &[EF]) -> EF {
+        fold_x_row(index, log_folded_height, beta, evals.iter().copied())
     }
 
-    fn fold_matrix<M: Matrix<EF>>(&self, beta: EF, m: M) -> Vec<EF> {
+    fn fold_matrix(&self, beta: EF, m: RowMajorMatrixView<'_, EF>) -> Vec<EF> {
         fold_x(beta, m)
     }
 }
 
-fn fold<F: ComplexExtendable, EF: ExtensionField<F>>(
-    evals: impl Matrix<EF>,
-    beta: EF,
-    twiddles: &[F],
-) -> Vec<EF> {
-    evals
-        .rows()
-        .zip(twiddles)
-        .map(|(mut row, &t)| {
-            let (lo, hi) = row.next_tuple().unwrap();
-            let sum = lo + hi;
-            let diff = (lo - hi) * t;
-            (sum + beta * diff).halve()
-        })
-        .collect_vec()
-}
-
 pub(crate) fn fold_y<F: ComplexExtendable, EF: ExtensionField<F>>(
     beta: EF,
-    evals: impl Matrix<EF>,
+    evals: RowMajorMatrixView<'_, EF>,
 ) -> Vec<EF> {
     assert_eq!(evals.width(), 2);
     let log_n = log2_strict_usize(evals.height()) + 1;
-    fold(
-        evals,
+    fold_matrix_generic(
         beta,
+        evals,
         &batch_multiplicative_inverse(&CircleDomain::standard(log_n).y_twiddles()),
     )
 }
@@ -84,26 +61,22 @@ pub(crate) fn fold_y_row<F: ComplexExtendable, EF: ExtensionField<F>>(
     beta: EF,
     evals: impl Iterator<Item = EF>,
 ) -> EF {
-    let evals = evals.collect_vec();
-    assert_eq!(evals.len(), 2);
     let t = CircleDomain::<F>::standard(log_folded_height + 1)
         .nth_y_twiddle(index)
         .inverse();
-    let sum = evals[0] + evals[1];
-    let diff = (evals[0] - evals[1]) * t;
-    (sum + beta * diff).halve()
+    fold_row_generic(evals, beta, t)
 }
 
 pub(crate) fn fold_x<F: ComplexExtendable, EF: ExtensionField<F>>(
     beta: EF,
-    evals: impl Matrix<EF>,
+    evals: RowMajorMatrixView<'_, EF>,
 ) -> Vec<EF> {
     let log_n = log2_strict_usize(evals.width() * evals.height());
     // +1 because twiddles after the first layer come from the x coordinates of the larger domain.
     let domain = CircleDomain::standard(log_n + 1);
-    fold(
-        evals,
+    fold_matrix_generic(
         beta,
+        evals,
         &batch_multiplicative_inverse(&domain.x_twiddles(0)),
     )
 }
@@ -114,25 +87,21 @@ pub(crate) fn fold_x_row<F: ComplexExtendable, EF: ExtensionField<F>>(
     beta: EF,
     evals: impl Iterator<Item = EF>,
 ) -> EF {
-    let evals = evals.collect_vec();
-    assert_eq!(evals.len(), 2);
-    let log_arity = log2_strict_usize(evals.len());
-
-    let t = CircleDomain::<F>::standard(log_folded_height + log_arity + 1)
+    // +1 for the arity, and +1 because the twiddles come from the x coordinates of the larger
+    // domain, as in `fold_x`.
+    let t = CircleDomain::<F>::standard(log_folded_height + 2)
         .nth_x_twiddle(reverse_bits_len(index, log_folded_height))
         .inverse();
-
-    let sum = evals[0] + evals[1];
-    let diff = (evals[0] - evals[1]) * t;
-    (sum + beta * diff).halve()
+    fold_row_generic(evals, beta, t)
 }
 
 #[cfg(test)]
 mod tests {
-    use itertools::iproduct;
-    use p3_field::extension::BinomialExtensionField;
+    use itertools::{iproduct, Itertools};
+    use p3_field::extension::{BinomialExtensionField, ComplexBinomialExtensionField};
     use p3_matrix::dense::RowMajorMatrix;
     use p3_mersenne_31::Mersenne31;
+    use rand::distributions::{Distribution, Standard};
     use rand::{random, thread_rng};
 
     use super::*;
@@ -140,36 +109,61 @@ mod tests {
 
     type F = Mersenne31;
     type EF = BinomialExtensionField<F, 3>;
+    type OcticEF = ComplexBinomialExtensionField<F, 4>;
+
+    fn do_test_fold_matrix_same_as_row<EF: ExtensionField<F>>()
+    where
+        Standard: Distribution<EF>,
+    {
+        for log_folded_height in 1..8 {
+            let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_folded_height, 2);
+            let beta: EF = random();
+
+            let mat_y_folded = fold_y::<F, EF>(beta, m.as_view());
+            let row_y_folded = (0..(1 << log_folded_height))
+                .map(|i| fold_y_row::<F, EF>(i, log_folded_height, beta, m.row(i)))
+                .collect_vec();
+            assert_eq!(mat_y_folded, row_y_folded);
+
+            // The folds after the first go through the FRI folder.
+            let folder = CircleFriGenericConfig::<F, (), ()>(PhantomData);
+            let mat_x_folded = folder.fold_matrix(beta, m.as_view());
+            let row_x_folded = (0..(1 << log_folded_height))
+                .map(|i| folder.fold_row(i, log_folded_height, beta, &m.row_slice(i)))
+                .collect_vec();
+            assert_eq!(mat_x_folded, row_x_folded);
+        }
+    }
 
     #[test]
     fn fold_matrix_same_as_row() {
-        let log_folded_height = 5;
-        let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_folded_height, 2);
+        do_test_fold_matrix_same_as_row::<EF>();
+        do_test_fold_matrix_same_as_row::<OcticEF>();
+    }
+
+    #[test]
+    fn fold_through_dyn_folder() {
+        let folder = CircleFriGenericConfig::<F, (), ()>(PhantomData);
+        let dyn_folder: &dyn FriGenericConfig<EF, InputProof = (), InputError = ()> = &folder;
+        let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << 4, 4);
         let beta: EF = random();
+        assert_eq!(
+            dyn_folder.fold_matrix_with_arity(beta, m.as_view()),
+            folder.fold_matrix_with_arity(beta, m.as_view())
+        );
+        assert_eq!(dyn_folder.extra_query_index_bits(), 1);
+    }
 
-        let mat_y_folded = fold_y::<F, EF>(beta, m.as_view());
-        let row_y_folded = (0..(1 << log_folded_height))
-            .map(|i| fold_y_row::<F, EF>(i, log_folded_height, beta, m.row(i)))
-            .collect_vec();
-        assert_eq!(mat_y_folded, row_y_folded);
-
-        let mat_x_folded = fold_x::<F, EF>(beta, m.as_view());
-        let row_x_folded = (0..(1 << log_folded_height))
-            .map(|i| fold_x_row::<F, EF>(i, log_folded_height, beta, m.row(i)))
-            .collect_vec();
-        assert_eq!(mat_x_folded, row_x_folded);
+    fn vec_dim(evals: &[F]) -> usize {
+        CircleEvaluations::from_cfft_order(
+            CircleDomain::standard(log2_strict_usize(evals.len())),
+            RowMajorMatrix::new_col(evals.to_vec()),
+        )
+        .dim()
     }
 
     #[test]
     fn folded_matrix_remains_low_degree() {
-        let vec_dim = |evals: &[F]| {
-            CircleEvaluations::from_cfft_order(
-                CircleDomain::standard(log2_strict_usize(evals.len())),
-                RowMajorMatrix::new_col(evals.to_vec()),
-            )
-            .dim()
-        };
-
         for (log_n, log_blowup) in iproduct!(3..6, 1..4) {
             let mut values = CircleEvaluations::evaluate(
                 CircleDomain::standard(log_n + log_blowup),
@@ -178,12 +172,44 @@ mod tests {
             .to_cfft_order()
             .values;
 
-            values = fold_y(random(), RowMajorMatrix::new(values, 2));
+            values = fold_y(random(), RowMajorMatrix::new(values, 2).as_view());
             assert_eq!(vec_dim(&values), values.len() >> log_blowup);
             for _ in 0..(log_n - 1) {
-                values = fold_x(random(), RowMajorMatrix::new(values, 2));
+                values = fold_x(random(), RowMajorMatrix::new(values, 2).as_view());
                 assert_eq!(vec_dim(&values), values.len() >> log_blowup);
             }
         }
     }
+
+    #[test]
+    fn fold_with_arity_4() {
+        let folder = CircleFriGenericConfig::<F, (), ()>(PhantomData);
+        for log_folded_height in 1..6 {
+            let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_folded_height, 4);
+            let beta: EF = random();
+
+            let mat_folded = folder.fold_matrix_with_arity(beta, m.as_view());
+            let row_folded = (0..(1 << log_folded_height))
+                .map(|i| {
+                    folder.fold_row_with_arity(i, log_folded_height, 2, beta, m.row(i).collect())
+                })
+                .collect_vec();
+            assert_eq!(mat_folded, row_folded);
+        }
+
+        // Each fold by 4 leaves a quarter of the dimension.
+        let (log_n, log_blowup) = (5, 1);
+        let mut values = CircleEvaluations::evaluate(
+            CircleDomain::standard(log_n + log_blowup),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, 1),
+        )
+        .to_cfft_order()
+        .values;
+        values = fold_y(random(), RowMajorMatrix::new(values, 2).as_view());
+        while values.len() > 1 << log_blowup {
+            values =
+                folder.fold_matrix_with_arity(random(), RowMajorMatrix::new(values, 4).as_view());
+            assert_eq!(vec_dim(&values), values.len() >> log_blowup);
+        }
+    }
 }
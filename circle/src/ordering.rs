@@ -56,6 +56,12 @@ pub struct CfftPerm {
     log_height: usize,
 }
 
+impl CfftPerm {
+    pub const fn new(log_height: usize) -> Self {
+        Self { log_height }
+    }
+}
+
 impl RowIndexMap for CfftPerm {
     fn height(&self) -> usize {
         1 << self.log_height
@@ -91,8 +97,11 @@ impl<T: Send + Sync, M: Matrix<T>> CfftPermutable<T> for M {
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
+    use p3_fri::input_query_row;
+    use p3_mersenne_31::Mersenne31;
 
     use super::*;
+    use crate::CircleDomain;
 
     #[test]
     fn ordering() {
@@ -115,4 +124,28 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn input_query_rows_follow_cfft_order() {
+        // Row `r` of an LDE of height `2^k` in cfft order is evaluated at the
+        // `cfft_permute_index(r, k)`-th point of the standard domain. Circle FRI folds the query's
+        // point of the tallest inputs down to that height by doubling it, up to its conjugate.
+        let log_max_height = 6;
+        let point = |row: usize, log_height: usize| {
+            CircleDomain::<Mersenne31>::standard(log_height)
+                .nth_point(cfft_permute_index(row, log_height))
+        };
+        for log_height in 1..=log_max_height {
+            for index in 0..1 << log_max_height {
+                let row = input_query_row(index, log_max_height, log_height);
+                let folded = (log_height..log_max_height)
+                    .fold(point(index, log_max_height), |p, _| p.double());
+                assert_eq!(
+                    point(row, log_height).x,
+                    folded.x,
+                    "index {index}, log_height {log_height}"
+                );
+            }
+        }
+    }
 }
@@ -1,15 +1,17 @@
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::vec;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace};
+use p3_commit::{
+    leaf_mapping, LeafMapEntry, Mmcs, OpenedValues, Pcs, PolynomialSpace, QueryParameters,
+};
 use p3_field::extension::ComplexExtendable;
-use p3_field::{ExtensionField, Field};
+use p3_field::{ExtensionField, Field, Powers};
 use p3_fri::verifier::FriError;
-use p3_fri::FriConfig;
+use p3_fri::{input_query_row, FriConfig};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
@@ -80,6 +82,21 @@ pub struct CirclePcsProof<
     >,
 }
 
+impl<Val: Field, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
+    /// The logical→leaf mapping of a commitment to evaluations over `domains`, for debugging
+    /// ordering mismatches. Logical rows index the standard LDE domain in natural order; the
+    /// committed LDEs are in cfft order.
+    pub fn trace_leaf_mapping(&self, domains: &[CircleDomain<Val>]) -> Vec<LeafMapEntry> {
+        let log_heights = domains
+            .iter()
+            .map(|domain| domain.log_n + self.fri_config.log_blowup)
+            .collect_vec();
+        leaf_mapping(&log_heights, |log_height, physical_row| {
+            cfft_permute_index(physical_row, log_height)
+        })
+    }
+}
+
 impl<Val, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
     for CirclePcs<Val, InputMmcs, FriMmcs>
 where
@@ -141,6 +158,15 @@ where
         }
     }
 
+    fn max_log_domain_size(&self) -> Option<usize> {
+        // The LDE of a committed matrix must still fit in the circle group.
+        Some(Val::CIRCLE_TWO_ADICITY.saturating_sub(self.fri_config.log_blowup))
+    }
+
+    fn query_parameters(&self) -> Option<QueryParameters> {
+        Some(self.fri_config.query_parameters())
+    }
+
     fn open(
         &self,
         // For each round,
@@ -153,7 +179,21 @@ where
             >,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::Error> {
+        for (data, points_for_mats) in &rounds {
+            let mats = self.mmcs.get_matrices(data);
+            assert_eq!(
+                mats.len(),
+                points_for_mats.len(),
+                "each committed matrix must be given the points to open it at"
+            );
+            // A matrix of height 1 has no sibling to fold with in the first layer.
+            assert!(
+                mats.iter().all(|mat| mat.height() > 1),
+                "circle FRI can't open matrices whose LDEs have a single row"
+            );
+        }
+
         // Batch combination challenge
         let alpha: Challenge = challenger.sample_ext_element();
 
@@ -168,7 +208,8 @@ where
         */
 
         // log_height -> (alpha offset, reduced openings column)
-        let mut reduced_openings: BTreeMap<usize, (Challenge, Vec<Challenge>)> = BTreeMap::new();
+        let mut reduced_openings: BTreeMap<usize, (Powers<Challenge>, Vec<Challenge>)> =
+            BTreeMap::new();
 
         let values: OpenedValues<Challenge> = rounds
             .iter()
@@ -185,7 +226,7 @@ where
 
                         let (alpha_offset, reduced_opening_for_log_height) =
                             reduced_openings.entry(log_height).or_insert_with(|| {
-                                (Challenge::ONE, vec![Challenge::ZERO; 1 << log_height])
+                                (alpha.powers(), vec![Challenge::ZERO; 1 << log_height])
                             });
 
                         points_for_mat
@@ -204,15 +245,18 @@ where
                                 let mat_ros = evals.deep_quotient_reduce(alpha, zeta, &ps_at_zeta);
 
                                 // Fold it into our running reduction, offset by alpha_offset.
-                                reduced_opening_for_log_height
-                                    .par_iter_mut()
-                                    .zip(mat_ros)
-                                    .for_each(|(ro, mat_ro)| {
-                                        *ro += *alpha_offset * mat_ro;
-                                    });
+                                let offset = alpha_offset.current;
+                                p3_maybe_rayon::install(mat_ros.len(), || {
+                                    reduced_opening_for_log_height
+                                        .par_iter_mut()
+                                        .zip(mat_ros)
+                                        .for_each(|(ro, mat_ro)| {
+                                            *ro += offset * mat_ro;
+                                        });
+                                });
 
                                 // Update alpha_offset from α^i -> α^(i + 2 * width)
-                                *alpha_offset *= alpha.exp_u64(2 * evals.values.width() as u64);
+                                alpha_offset.advance_by(2 * evals.values.width() as u64);
 
                                 ps_at_zeta
                             })
@@ -230,7 +274,6 @@ where
         let first_layer_mats: Vec<RowMajorMatrix<Challenge>> = reduced_openings
             .into_iter()
             .map(|(log_height, (_, mut ro))| {
-                assert!(log_height > 0);
                 log_heights.push(log_height);
                 let lambda = extract_lambda(&mut ro, self.fri_config.log_blowup);
                 lambdas.push(lambda);
@@ -274,8 +317,8 @@ where
                 .iter()
                 .map(|(data, _)| {
                     let log_max_batch_height = log2_strict_usize(self.mmcs.get_max_height(data));
-                    let reduced_index = index >> (log_max_height - log_max_batch_height);
-                    let (opened_values, opening_proof) = self.mmcs.open_batch(reduced_index, data);
+                    let row = input_query_row(index, log_max_height, log_max_batch_height);
+                    let (opened_values, opening_proof) = self.mmcs.open_batch(row, data);
                     BatchOpening {
                         opened_values,
                         opening_proof,
@@ -291,8 +334,8 @@ where
                 .open_batch(index >> 1, &first_layer_data);
             let first_layer_siblings = izip!(&first_layer_values, &log_heights)
                 .map(|(v, log_height)| {
-                    let reduced_index = index >> (log_max_height - log_height);
-                    let sibling_index = (reduced_index & 1) ^ 1;
+                    let row = input_query_row(index, log_max_height, *log_height);
+                    let sibling_index = (row & 1) ^ 1;
                     v[sibling_index]
                 })
                 .collect();
@@ -303,14 +346,14 @@ where
             }
         });
 
-        (
+        Ok((
             values,
             CirclePcsProof {
                 first_layer_commitment,
                 lambdas,
                 fri_proof,
             },
-        )
+        ))
     }
 
     fn verify(
@@ -340,15 +383,59 @@ where
         let bivariate_beta: Challenge = challenger.sample_ext_element();
 
         // +1 to account for first layer
-        let log_global_max_height =
-            proof.fri_proof.commit_phase_commits.len() + self.fri_config.log_blowup + 1;
+        let log_global_max_height = proof
+            .fri_proof
+            .log_folding_factor()
+            .saturating_add(self.fri_config.log_blowup + 1);
+
+        // Check the shape of every query's openings up front, so that reducing them below can't
+        // silently drop any of them. Matrices taller than the first FRI round couldn't be queried,
+        // and those of height 1 have no sibling to fold with in the first layer.
+        let log_heights = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(domain, _)| domain.log_n + self.fri_config.log_blowup)
+            .collect::<BTreeSet<_>>();
+        let valid_shape = log_global_max_height <= Val::CIRCLE_TWO_ADICITY
+            && log_heights
+                .iter()
+                .all(|&log_height| 0 < log_height && log_height <= log_global_max_height)
+            && proof.lambdas.len() == log_heights.len()
+            && rounds.iter().all(|(_, mats)| !mats.is_empty())
+            && proof.fri_proof.query_proofs.iter().all(|qp| {
+                let input_proof = &qp.input_proof;
+                input_proof.first_layer_siblings.len() == log_heights.len()
+                    && input_proof.input_openings.len() == rounds.len()
+                    && izip!(&input_proof.input_openings, &rounds).all(
+                        |(batch_opening, (_, mats))| {
+                            batch_opening.opened_values.len() == mats.len()
+                                && izip!(&batch_opening.opened_values, mats).all(
+                                    |(ps_at_x, (_, points_and_values))| {
+                                        points_and_values
+                                            .iter()
+                                            .all(|(_, values)| values.len() == ps_at_x.len())
+                                    },
+                                )
+                        },
+                    )
+            });
+        if !valid_shape {
+            return Err(FriError::InvalidProofShape);
+        }
 
         let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> =
             CircleFriGenericConfig(PhantomData);
 
+        // FRI's inputs are the first layers of each height, tallest first, each folded in half.
+        let fri_log_heights = log_heights
+            .iter()
+            .rev()
+            .map(|&log_height| log_height - 1)
+            .collect_vec();
         verify(
             &g,
             &self.fri_config,
+            &fri_log_heights,
             &proof.fri_proof,
             challenger,
             |index, input_proof| {
@@ -366,11 +453,15 @@ where
                         .iter()
                         .map(|(domain, _)| (domain.size() << self.fri_config.log_blowup))
                         .collect_vec();
-                    let batch_dims: Vec<Dimensions> = batch_heights
-                        .iter()
-                        // todo: mmcs doesn't really need width
-                        .map(|&height| Dimensions { width: 0, height })
-                        .collect_vec();
+                    // The widths are those of the opened rows, which the shape check above held
+                    // to the claimed evaluations.
+                    let batch_dims: Vec<Dimensions> =
+                        izip!(&batch_heights, &batch_opening.opened_values)
+                            .map(|(&height, ps_at_x)| Dimensions {
+                                width: ps_at_x.len(),
+                                height,
+                            })
+                            .collect_vec();
 
                     let log_batch_max_height =
                         log2_strict_usize(batch_heights.iter().max().copied().unwrap());
@@ -379,7 +470,7 @@ where
                         .verify_batch(
                             batch_commit,
                             &batch_dims,
-                            index >> (log_global_max_height - log_batch_max_height),
+                            input_query_row(index, log_global_max_height, log_batch_max_height),
                             &batch_opening.opened_values,
                             &batch_opening.opening_proof,
                         )
@@ -389,8 +480,8 @@ where
                         izip!(&batch_opening.opened_values, mats)
                     {
                         let log_height = mat_domain.log_n + self.fri_config.log_blowup;
-                        let bits_reduced = log_global_max_height - log_height;
-                        let orig_idx = cfft_permute_index(index >> bits_reduced, log_height);
+                        let row = input_query_row(index, log_global_max_height, log_height);
+                        let orig_idx = cfft_permute_index(row, log_height);
 
                         let committed_domain = CircleDomain::standard(log_height);
                         let x = committed_domain.nth_point(orig_idx);
@@ -398,16 +489,18 @@ where
                         let (alpha_offset, ro) = reduced_openings
                             .entry(log_height)
                             .or_insert((Challenge::ONE, Challenge::ZERO));
-                        let alpha_pow_width_2 = alpha.exp_u64(ps_at_x.len() as u64).square();
+                        // Each opening of this matrix accounts for 2 * width powers of α.
+                        let mut offsets = alpha
+                            .shifted_powers(*alpha_offset)
+                            .strided(2 * ps_at_x.len() as u64);
 
                         for (zeta_uni, ps_at_zeta) in mat_points_and_values {
                             let zeta = Point::from_projective_line(*zeta_uni);
 
-                            *ro += *alpha_offset
+                            *ro += offsets.next().unwrap()
                                 * deep_quotient_reduce_row(alpha, x, zeta, ps_at_x, ps_at_zeta);
-
-                            *alpha_offset *= alpha_pow_width_2;
                         }
+                        *alpha_offset = offsets.current;
                     }
                 }
 
@@ -416,11 +509,9 @@ where
                 let (mut fri_input, fl_dims, fl_leaves): (Vec<_>, Vec<_>, Vec<_>) =
                     izip!(reduced_openings, first_layer_siblings, &proof.lambdas)
                         .map(|((log_height, (_, ro)), &fl_sib, &lambda)| {
-                            assert!(log_height > 0);
-
                             let orig_size = log_height - self.fri_config.log_blowup;
-                            let bits_reduced = log_global_max_height - log_height;
-                            let orig_idx = cfft_permute_index(index >> bits_reduced, log_height);
+                            let row = input_query_row(index, log_global_max_height, log_height);
+                            let orig_idx = cfft_permute_index(row, log_height);
 
                             let lde_domain = CircleDomain::standard(log_height);
                             let p: Point<Val> = lde_domain.nth_point(orig_idx);
@@ -428,13 +519,13 @@ where
                             let lambda_corrected = ro - lambda * p.v_n(orig_size);
 
                             let mut fl_values = vec![lambda_corrected; 2];
-                            fl_values[((index >> bits_reduced) & 1) ^ 1] = fl_sib;
+                            fl_values[(row & 1) ^ 1] = fl_sib;
 
                             let fri_input = (
                                 // - 1 here is because we have already folded a layer.
                                 log_height - 1,
                                 fold_y_row(
-                                    index >> (bits_reduced + 1),
+                                    row >> 1,
                                     // - 1 here is log_arity.
                                     log_height - 1,
                                     bivariate_beta,
@@ -475,7 +566,7 @@ where
 mod tests {
     use p3_challenger::{HashChallenger, SerializingChallenger32};
     use p3_commit::ExtensionMmcs;
-    use p3_field::extension::BinomialExtensionField;
+    use p3_field::extension::ComplexBinomialExtensionField;
     use p3_fri::create_test_fri_config;
     use p3_keccak::Keccak256Hash;
     use p3_merkle_tree::MerkleTreeMmcs;
@@ -486,43 +577,41 @@ mod tests {
 
     use super::*;
 
-    #[test]
-    fn circle_pcs() {
-        // Very simple pcs test. More rigorous tests in p3_fri/tests/pcs.
+    type Val = Mersenne31;
+    type Challenge = ComplexBinomialExtensionField<Mersenne31, 4>;
 
-        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    type MyPcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
 
-        type Val = Mersenne31;
-        type Challenge = BinomialExtensionField<Mersenne31, 3>;
-
-        type ByteHash = Keccak256Hash;
-        type FieldHash = SerializingHasher32<ByteHash>;
+    fn test_pcs() -> MyPcs {
         let byte_hash = ByteHash {};
         let field_hash = FieldHash::new(byte_hash);
-
-        type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
         let compress = MyCompress::new(byte_hash);
-
-        type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
         let val_mmcs = ValMmcs::new(field_hash, compress);
-
-        type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
-
-        type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
-
-        let fri_config = create_test_fri_config(challenge_mmcs);
-
-        type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
-        let pcs = Pcs {
+        MyPcs {
             mmcs: val_mmcs,
-            fri_config,
+            fri_config: create_test_fri_config(challenge_mmcs),
             _phantom: PhantomData,
-        };
+        }
+    }
+
+    #[test]
+    fn circle_pcs() {
+        // Very simple pcs test. More rigorous tests in p3_fri/tests/pcs.
+
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let byte_hash = ByteHash {};
+        let pcs = test_pcs();
 
         let log_n = 10;
 
-        let d = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+        let d = <MyPcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
             &pcs,
             1 << log_n,
         );
@@ -530,12 +619,14 @@ mod tests {
         let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 1);
 
         let (comm, data) =
-            <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
+            <MyPcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
 
         let zeta: Challenge = rng.gen();
 
         let mut chal = Challenger::from_hasher(vec![], byte_hash);
-        let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
+        let (values, proof) = pcs
+            .open(vec![(&data, vec![vec![zeta]])], &mut chal)
+            .unwrap();
 
         let mut chal = Challenger::from_hasher(vec![], byte_hash);
         pcs.verify(
@@ -545,4 +636,197 @@ mod tests {
         )
         .expect("verify err");
     }
+
+    #[test]
+    fn circle_pcs_with_higher_arity() {
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let byte_hash = ByteHash {};
+        let mut pcs = test_pcs();
+        pcs.fri_config.log_folding_arity = 2;
+
+        // Two heights, so that the rounds fold by 4 except where that would pass the shorter one.
+        let evaluations = [10, 7]
+            .map(|log_n| {
+                (
+                    CircleDomain::standard(log_n),
+                    RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 2),
+                )
+            })
+            .to_vec();
+        let domains = evaluations.iter().map(|(d, _)| *d).collect_vec();
+        let (comm, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations);
+        let zeta: Challenge = rng.gen();
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        let (values, proof) = pcs
+            .open(vec![(&data, vec![vec![zeta], vec![zeta]])], &mut chal)
+            .unwrap();
+
+        // FRI folds the first layers, of heights 2^10 and 2^7, down to 2^1.
+        assert_eq!(proof.fri_proof.commit_phase_log_arities, [2, 1, 2, 2, 2]);
+
+        let claims = vec![(
+            comm,
+            izip!(domains, &values[0])
+                .map(|(d, values)| (d, vec![(zeta, values[0].clone())]))
+                .collect_vec(),
+        )];
+        let verify = |pcs: &MyPcs, proof: &<MyPcs as Pcs<Challenge, Challenger>>::Proof| {
+            let mut chal = Challenger::from_hasher(vec![], byte_hash);
+            pcs.verify(claims.clone(), proof, &mut chal)
+        };
+        verify(&pcs, &proof).expect("verify err");
+
+        // A verifier which folds by 2 expects other rounds.
+        assert!(matches!(
+            verify(&test_pcs(), &proof),
+            Err(FriError::InvalidProofShape)
+        ));
+        // As it does rounds which fold by other arities, even ones adding up to the same height.
+        let mut reordered = proof;
+        reordered.fri_proof.commit_phase_log_arities.swap(0, 1);
+        assert!(matches!(
+            verify(&pcs, &reordered),
+            Err(FriError::InvalidProofShape)
+        ));
+    }
+
+    #[test]
+    fn rejects_unsupported_configs() {
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let byte_hash = ByteHash {};
+        let mut pcs = test_pcs();
+
+        let d = CircleDomain::standard(6);
+        let evals = RowMajorMatrix::rand(&mut rng, 1 << 6, 1);
+        let (comm, data) =
+            <MyPcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
+        let zeta: Challenge = rng.gen();
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        let (values, proof) = pcs
+            .open(vec![(&data, vec![vec![zeta]])], &mut chal)
+            .unwrap();
+
+        let claims = vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])];
+        let unsupported = [
+            FriConfig {
+                max_direct_opening_height: 4,
+                ..create_test_fri_config(pcs.fri_config.mmcs.clone())
+            },
+            FriConfig {
+                log_final_poly_len: 1,
+                ..create_test_fri_config(pcs.fri_config.mmcs.clone())
+            },
+        ];
+        for fri_config in unsupported {
+            pcs.fri_config = fri_config;
+            let mut chal = Challenger::from_hasher(vec![], byte_hash);
+            assert!(matches!(
+                pcs.verify(claims.clone(), &proof, &mut chal),
+                Err(FriError::UnsupportedConfig)
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_openings() {
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let byte_hash = ByteHash {};
+        let pcs = test_pcs();
+
+        let d = CircleDomain::standard(6);
+        let evals = RowMajorMatrix::rand(&mut rng, 1 << 6, 2);
+        let (comm, data) =
+            <MyPcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
+        let zeta: Challenge = rng.gen();
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        let (values, proof) = pcs
+            .open(vec![(&data, vec![vec![zeta]])], &mut chal)
+            .unwrap();
+        let claimed = values[0][0][0].clone();
+
+        let verify =
+            |claimed: &[Challenge],
+             proof: &<MyPcs as p3_commit::Pcs<Challenge, Challenger>>::Proof| {
+                let mut chal = Challenger::from_hasher(vec![], byte_hash);
+                pcs.verify(
+                    vec![(comm.clone(), vec![(d, vec![(zeta, claimed.to_vec())])])],
+                    proof,
+                    &mut chal,
+                )
+            };
+        assert!(verify(&claimed, &proof).is_ok());
+
+        // A claimed value too few.
+        assert!(matches!(
+            verify(&claimed[1..], &proof),
+            Err(FriError::InvalidProofShape)
+        ));
+
+        let mut missing_lambda = proof.clone();
+        missing_lambda.lambdas.pop();
+        assert!(matches!(
+            verify(&claimed, &missing_lambda),
+            Err(FriError::InvalidProofShape)
+        ));
+
+        let mut missing_sibling = proof.clone();
+        missing_sibling.fri_proof.query_proofs[0]
+            .input_proof
+            .first_layer_siblings
+            .pop();
+        assert!(matches!(
+            verify(&claimed, &missing_sibling),
+            Err(FriError::InvalidProofShape)
+        ));
+
+        let mut missing_opening = proof;
+        missing_opening.fri_proof.query_proofs[0]
+            .input_proof
+            .input_openings[0]
+            .opened_values[0]
+            .pop();
+        assert!(matches!(
+            verify(&claimed, &missing_opening),
+            Err(FriError::InvalidProofShape)
+        ));
+    }
+
+    #[test]
+    fn leaf_mapping_matches_openings() {
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let pcs = test_pcs();
+
+        let evaluations = [3, 2]
+            .map(|log_n| {
+                (
+                    CircleDomain::standard(log_n),
+                    RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 2),
+                )
+            })
+            .to_vec();
+        let domains = evaluations.iter().map(|(d, _)| *d).collect_vec();
+        let (comm, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.clone());
+        let dims = pcs
+            .mmcs
+            .get_matrices(&data)
+            .iter()
+            .map(|m| m.dimensions())
+            .collect_vec();
+
+        let mapping = pcs.trace_leaf_mapping(&domains);
+        assert_eq!(mapping.len(), 16 + 8);
+        for entry in mapping {
+            let (domain, evals) = &evaluations[entry.matrix];
+            let lde_domain = CircleDomain::standard(domain.log_n + pcs.fri_config.log_blowup);
+            let expected = CircleEvaluations::from_natural_order(*domain, evals.as_view())
+                .evaluate_at_point(lde_domain.nth_point(entry.logical_row));
+            for index in entry.leaf_indices {
+                let (opened_values, proof) = pcs.mmcs.open_batch(index, &data);
+                assert_eq!(opened_values[entry.matrix], expected);
+                pcs.mmcs
+                    .verify_batch(&comm, &dims, index, &opened_values, &proof)
+                    .expect("verify err");
+            }
+        }
+    }
 }
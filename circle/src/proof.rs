@@ -11,6 +11,9 @@ use serde::{Deserialize, Serialize};
 ))]
 pub struct CircleFriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     pub commit_phase_commits: Vec<M::Commitment>,
+    /// For each commit phase commitment, the log of the number of evaluations in each row of the
+    /// committed codeword, which that round folds into one.
+    pub commit_phase_log_arities: Vec<usize>,
     pub query_proofs: Vec<CircleQueryProof<F, M, InputProof>>,
     // This could become Vec<FC::Challenge> if this library was generalized to support non-constant
     // final polynomials.
@@ -18,6 +21,16 @@ pub struct CircleFriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     pub pow_witness: Witness,
 }
 
+impl<F: Field, M: Mmcs<F>, Witness, InputProof> CircleFriProof<F, M, Witness, InputProof> {
+    /// The log of the factor the commit phase shrinks codewords by, i.e. the sum of its rounds'
+    /// log arities.
+    pub fn log_folding_factor(&self) -> usize {
+        self.commit_phase_log_arities
+            .iter()
+            .fold(0, |sum, &log_arity| sum.saturating_add(log_arity))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound(
     serialize = "InputProof: Serialize",
@@ -33,10 +46,9 @@ pub struct CircleQueryProof<F: Field, M: Mmcs<F>, InputProof> {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CircleCommitPhaseProofStep<F: Field, M: Mmcs<F>> {
-    /// The opening of the commit phase codeword at the sibling location.
-    // This may change to Vec<FC::Challenge> if the library is generalized to support other FRI
-    // folding arities besides 2, meaning that there can be multiple siblings.
-    pub sibling_value: F,
+    /// The rest of the opened row of the commit phase codeword, i.e. its evaluations at the
+    /// locations which fold together with the queried one, in order.
+    pub sibling_values: Vec<F>,
 
     pub opening_proof: M::Proof,
 }
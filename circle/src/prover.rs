@@ -26,7 +26,7 @@ where
     Challenge: ExtensionField<Val>,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
-    G: FriGenericConfig<Challenge>,
+    G: FriGenericConfig<Challenge> + ?Sized,
 {
     // check sorted descending
     assert!(inputs
@@ -34,20 +34,40 @@ where
         .tuple_windows()
         .all(|(l, r)| l.len() >= r.len()));
 
+    assert_eq!(
+        config.max_direct_opening_height, 0,
+        "Circle FRI doesn't support direct openings of commit phase codewords."
+    );
+    assert_eq!(
+        config.log_final_poly_len, 0,
+        "Circle FRI doesn't support stopping early, and always folds down to a constant."
+    );
     let log_max_height = log2_strict_usize(inputs[0].len());
-
+    assert!(
+        Challenge::EXT_ORDER_BITS >= config.min_challenge_bits(log_max_height),
+        "The challenge field has {} bits, but FRI over codewords of height 2^{} needs at least {}.",
+        Challenge::EXT_ORDER_BITS,
+        log_max_height,
+        config.min_challenge_bits(log_max_height),
+    );
+
+    challenger.begin_phase("fri commit phase");
     let commit_phase_result = commit_phase(g, config, inputs, challenger);
 
+    challenger.begin_phase("fri proof of work");
     let pow_witness = challenger.grind(config.proof_of_work_bits);
 
+    challenger.begin_phase("fri query phase");
+    let query_index_bits = log_max_height + g.extra_query_index_bits();
     let query_proofs = info_span!("query phase").in_scope(|| {
-        iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
+        iter::repeat_with(|| config.sample_query_index(challenger, query_index_bits))
             .take(config.num_queries)
             .map(|index| CircleQueryProof {
                 input_proof: open_input(index),
                 commit_phase_openings: answer_query(
                     config,
                     &commit_phase_result.data,
+                    &commit_phase_result.log_arities,
                     index >> g.extra_query_index_bits(),
                 ),
             })
@@ -56,6 +76,7 @@ where
 
     CircleFriProof {
         commit_phase_commits: commit_phase_result.commits,
+        commit_phase_log_arities: commit_phase_result.log_arities,
         query_proofs,
         final_poly: commit_phase_result.final_poly,
         pow_witness,
@@ -65,6 +86,7 @@ where
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
+    log_arities: Vec<usize>,
     final_poly: F,
 }
 
@@ -80,22 +102,27 @@ where
     Challenge: ExtensionField<Val>,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + CanObserve<M::Commitment>,
-    G: FriGenericConfig<Challenge>,
+    G: FriGenericConfig<Challenge> + ?Sized,
 {
+    let log_heights = inputs
+        .iter()
+        .map(|input| log2_strict_usize(input.len()))
+        .collect_vec();
+    let log_arities = config.commit_phase_log_arities(&log_heights, config.log_blowup);
     let mut inputs_iter = inputs.into_iter().peekable();
     let mut folded = inputs_iter.next().unwrap();
     let mut commits = vec![];
     let mut data = vec![];
 
-    while folded.len() > config.blowup() {
-        let leaves = RowMajorMatrix::new(folded, 2);
+    for &log_arity in &log_arities {
+        let leaves = RowMajorMatrix::new(folded, 1 << log_arity);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
 
         let beta: Challenge = challenger.sample_ext_element();
         // We passed ownership of `current` to the MMCS, so get a reference to it
         let leaves = config.mmcs.get_matrices(&prover_data).pop().unwrap();
-        folded = g.fold_matrix(beta, leaves.as_view());
+        folded = g.fold_matrix_with_arity(beta, leaves.as_view());
 
         commits.push(commit);
         data.push(prover_data);
@@ -116,6 +143,7 @@ where
     CommitPhaseResult {
         commits,
         data,
+        log_arities,
         final_poly,
     }
 }
@@ -123,28 +151,30 @@ where
 fn answer_query<F, M>(
     config: &FriConfig<M>,
     commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
-    index: usize,
+    commit_phase_log_arities: &[usize],
+    mut index: usize,
 ) -> Vec<CircleCommitPhaseProofStep<F, M>>
 where
     F: Field,
     M: Mmcs<F>,
 {
-    commit_phase_commits
-        .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let index_i = index >> i;
-            let index_i_sibling = index_i ^ 1;
-            let index_pair = index_i >> 1;
+    izip!(commit_phase_commits, commit_phase_log_arities)
+        .map(|(commit, &log_arity)| {
+            let index_in_row = index % (1 << log_arity);
+            index >>= log_arity;
 
-            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_pair, commit);
+            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index, commit);
             assert_eq!(opened_rows.len(), 1);
-            let opened_row = opened_rows.pop().unwrap();
-            assert_eq!(opened_row.len(), 2, "Committed data should be in pairs");
-            let sibling_value = opened_row[index_i_sibling % 2];
+            let mut sibling_values = opened_rows.pop().unwrap();
+            assert_eq!(
+                sibling_values.len(),
+                1 << log_arity,
+                "Committed rows should have one value per folded evaluation"
+            );
+            sibling_values.remove(index_in_row);
 
             CircleCommitPhaseProofStep {
-                sibling_value,
+                sibling_values,
                 opening_proof,
             }
         })
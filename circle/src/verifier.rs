@@ -1,4 +1,3 @@
-use alloc::vec;
 use alloc::vec::Vec;
 
 use itertools::{izip, Itertools};
@@ -11,9 +10,12 @@ use p3_matrix::Dimensions;
 
 use crate::{CircleCommitPhaseProofStep, CircleFriProof};
 
+/// Check `proof` that inputs of heights `2^log_heights`, in descending order, are low degree,
+/// folding each round by the arity the config folds these heights with.
 pub fn verify<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    log_heights: &[usize],
     proof: &CircleFriProof<Challenge, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
@@ -23,8 +25,14 @@ where
     Challenge: ExtensionField<Val>,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
-    G: FriGenericConfig<Challenge>,
+    G: FriGenericConfig<Challenge> + ?Sized,
 {
+    // Circle FRI always folds down to a constant, whose codewords are too short to open directly.
+    if config.max_direct_opening_height != 0 || config.log_final_poly_len != 0 {
+        return Err(FriError::UnsupportedConfig);
+    }
+
+    challenger.begin_phase("fri commit phase");
     let betas: Vec<Challenge> = proof
         .commit_phase_commits
         .iter()
@@ -35,19 +43,29 @@ where
         .collect();
     challenger.observe_ext_element(proof.final_poly);
 
-    if proof.query_proofs.len() != config.num_queries {
+    let log_arities = config.commit_phase_log_arities(log_heights, config.log_blowup);
+    if proof.query_proofs.len() != config.num_queries
+        || proof.commit_phase_log_arities != log_arities
+        || proof.commit_phase_commits.len() != log_arities.len()
+    {
         return Err(FriError::InvalidProofShape);
     }
 
+    let log_max_height = proof.log_folding_factor() + config.log_blowup;
+    if Challenge::EXT_ORDER_BITS < config.min_challenge_bits(log_max_height) {
+        return Err(FriError::ChallengeFieldTooSmall);
+    }
+
     // Check PoW.
+    challenger.begin_phase("fri proof of work");
     if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
         return Err(FriError::InvalidPowWitness);
     }
 
-    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
-
+    challenger.begin_phase("fri query phase");
     for qp in &proof.query_proofs {
-        let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
+        let index =
+            config.sample_query_index(challenger, log_max_height + g.extra_query_index_bits());
         let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
 
         debug_assert!(
@@ -62,6 +80,7 @@ where
             izip!(
                 &betas,
                 &proof.commit_phase_commits,
+                &proof.commit_phase_log_arities,
                 &qp.commit_phase_openings
             ),
             ro,
@@ -79,6 +98,7 @@ where
 type CommitStep<'a, F, M> = (
     &'a F,
     &'a <M as Mmcs<F>>::Commitment,
+    &'a usize,
     &'a CircleCommitPhaseProofStep<F, M>,
 );
 
@@ -93,24 +113,29 @@ fn verify_query<'a, G, F, M>(
 where
     F: Field,
     M: Mmcs<F> + 'a,
-    G: FriGenericConfig<F>,
+    G: FriGenericConfig<F> + ?Sized,
 {
     let mut folded_eval = F::ZERO;
     let mut ro_iter = reduced_openings.into_iter().peekable();
+    let mut log_height = log_max_height;
 
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+    for (&beta, comm, &log_arity, opening) in steps {
+        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
             folded_eval += ro;
         }
 
-        let index_sibling = index ^ 1;
-        let index_pair = index >> 1;
+        let log_folded_height = log_height - log_arity;
+        let index_in_row = index % (1 << log_arity);
+        let index_row = index >> log_arity;
 
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
+        if opening.sibling_values.len() != (1 << log_arity) - 1 {
+            return Err(FriError::InvalidProofShape);
+        }
+        let mut evals = opening.sibling_values.clone();
+        evals.insert(index_in_row, folded_eval);
 
         let dims = &[Dimensions {
-            width: 2,
+            width: 1 << log_arity,
             height: 1 << log_folded_height,
         }];
         config
@@ -118,15 +143,16 @@ where
             .verify_batch(
                 comm,
                 dims,
-                index_pair,
+                index_row,
                 &[evals.clone()],
                 &opening.opening_proof,
             )
             .map_err(FriError::CommitPhaseMmcsError)?;
 
-        index = index_pair;
+        index = index_row;
+        log_height = log_folded_height;
 
-        folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
+        folded_eval = g.fold_row_with_arity(index, log_folded_height, log_arity, beta, evals);
     }
 
     debug_assert!(index < config.blowup(), "index was {}", index);
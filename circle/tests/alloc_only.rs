@@ -0,0 +1,71 @@
+//! The circle PCS, proving and verifying through the `core` and `alloc` APIs alone.
+//!
+//! `#![no_std]` only keeps this file from reaching for `std`: the test harness still links it. That
+//! the crates themselves build without `std` is checked by the bare-metal CI job.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec;
+use core::marker::PhantomData;
+
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_circle::{CircleDomain, CirclePcs};
+use p3_commit::{ExtensionMmcs, Pcs};
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::create_test_fri_config;
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+type Val = Mersenne31;
+type Challenge = BinomialExtensionField<Mersenne31, 3>;
+
+type ByteHash = Keccak256Hash;
+type FieldHash = SerializingHasher32<ByteHash>;
+type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+type MyPcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+
+#[test]
+fn circle_pcs_with_only_alloc() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let byte_hash = ByteHash {};
+    let val_mmcs = ValMmcs::new(FieldHash::new(byte_hash), MyCompress::new(byte_hash));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let pcs = MyPcs {
+        mmcs: val_mmcs,
+        fri_config: create_test_fri_config(challenge_mmcs),
+        _phantom: PhantomData,
+    };
+
+    let log_n = 8;
+    let domain: CircleDomain<Val> =
+        <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+    let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 2);
+    let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+    let zeta: Challenge = rng.gen();
+    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+    let (values, proof) = pcs
+        .open(vec![(&data, vec![vec![zeta]])], &mut challenger)
+        .unwrap();
+
+    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+    pcs.verify(
+        vec![(
+            commit,
+            vec![(domain, vec![(zeta, values[0][0][0].clone())])],
+        )],
+        &proof,
+        &mut challenger,
+    )
+    .expect("verification failed");
+}
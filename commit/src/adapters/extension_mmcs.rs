@@ -6,7 +6,7 @@ use p3_field::{ExtensionField, Field};
 use p3_matrix::extension::FlatMatrixView;
 use p3_matrix::{Dimensions, Matrix};
 
-use crate::Mmcs;
+use crate::{Cost, Mmcs, MmcsCostModel};
 
 #[derive(Clone, Debug)]
 pub struct ExtensionMmcs<F, EF, InnerMmcs> {
@@ -88,3 +88,31 @@ where
             .verify_batch(commit, &base_dimensions, index, &opened_base_values, proof)
     }
 }
+
+impl<F, EF, InnerMmcs> MmcsCostModel<EF> for ExtensionMmcs<F, EF, InnerMmcs>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    InnerMmcs: MmcsCostModel<F>,
+{
+    fn commit_cost(&self, dimensions: &[Dimensions]) -> Cost {
+        // The matrices are committed to as views of their base field coefficients.
+        self.inner
+            .commit_cost(&base_dimensions::<F, EF>(dimensions))
+    }
+
+    fn verify_batch_cost(&self, dimensions: &[Dimensions]) -> Cost {
+        self.inner
+            .verify_batch_cost(&base_dimensions::<F, EF>(dimensions))
+    }
+}
+
+fn base_dimensions<F: Field, EF: ExtensionField<F>>(dimensions: &[Dimensions]) -> Vec<Dimensions> {
+    dimensions
+        .iter()
+        .map(|dim| Dimensions {
+            width: dim.width * EF::D,
+            height: dim.height,
+        })
+        .collect()
+}
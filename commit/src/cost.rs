@@ -0,0 +1,139 @@
+//! Estimates of the memory and work of committing and opening, for capacity planning.
+
+use alloc::vec::Vec;
+
+use p3_matrix::Dimensions;
+
+use crate::Mmcs;
+
+/// An estimate of the memory and work needed by a step of the prover.
+///
+/// Memory is counted in bytes of heap allocations made by the step, relative to what was allocated
+/// when it started. `retained_bytes` are still allocated when it returns, e.g. in the prover data
+/// it hands back, and `peak_bytes` is the most it holds at once, including those. A step may free
+/// memory which was allocated before it, e.g. an input it takes ownership of; that isn't counted
+/// against its peak, but is taken off `retained_bytes`.
+///
+/// Work is counted in the operations which dominate proving. Hashes and compressions are counted
+/// once per digest, even when several digests are computed by one packed call. Estimates of
+/// verification only count work, leaving the bytes at zero.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cost {
+    pub retained_bytes: usize,
+    pub peak_bytes: usize,
+    /// Butterflies done by DFTs, each combining a pair of elements.
+    pub dft_butterflies: usize,
+    /// Calls to the leaf hasher of an MMCS.
+    pub hashes: usize,
+    /// The field elements absorbed by all the calls to the leaf hasher.
+    pub hashed_elements: usize,
+    /// Calls to the compression function of an MMCS.
+    pub compressions: usize,
+    /// Multiplications with an extension field element, by either a base or an extension element.
+    pub extension_muls: usize,
+}
+
+impl Cost {
+    /// A step which allocates `bytes` and keeps them.
+    pub const fn allocation(bytes: usize) -> Self {
+        Self {
+            retained_bytes: bytes,
+            peak_bytes: bytes,
+            dft_butterflies: 0,
+            hashes: 0,
+            hashed_elements: 0,
+            compressions: 0,
+            extension_muls: 0,
+        }
+    }
+
+    /// A step which allocates `bytes` and frees them before it returns.
+    pub const fn temporary(bytes: usize) -> Self {
+        Self {
+            retained_bytes: 0,
+            ..Self::allocation(bytes)
+        }
+    }
+
+    /// This step followed by `next`, which starts with what this step retained still allocated.
+    #[must_use]
+    pub const fn then(self, next: Self) -> Self {
+        let peak_after = self.retained_bytes + next.peak_bytes;
+        Self {
+            retained_bytes: self.retained_bytes + next.retained_bytes,
+            peak_bytes: if self.peak_bytes > peak_after {
+                self.peak_bytes
+            } else {
+                peak_after
+            },
+            dft_butterflies: self.dft_butterflies + next.dft_butterflies,
+            hashes: self.hashes + next.hashes,
+            hashed_elements: self.hashed_elements + next.hashed_elements,
+            compressions: self.compressions + next.compressions,
+            extension_muls: self.extension_muls + next.extension_muls,
+        }
+    }
+
+    /// This step, after which `bytes` of what it or an earlier step allocated are freed.
+    #[must_use]
+    pub const fn freeing(mut self, bytes: usize) -> Self {
+        self.retained_bytes = self.retained_bytes.saturating_sub(bytes);
+        self
+    }
+
+    /// An upper bound on the calls to a permutation, for an MMCS whose leaf hasher is a sponge
+    /// absorbing `rate` elements per call to the permutation and whose compression function calls
+    /// it once, as with `PaddingFreeSponge` and `TruncatedPermutation`.
+    pub const fn permutation_calls(&self, rate: usize) -> usize {
+        // Each hash absorbs its elements in blocks of `rate`, the last of which may be partial.
+        self.hashed_elements / rate + self.hashes + self.compressions
+    }
+}
+
+/// An [`Mmcs`] which can estimate what committing to matrices costs.
+pub trait MmcsCostModel<T: Send + Sync>: Mmcs<T> {
+    /// The cost of [`Mmcs::commit`] for matrices of the given dimensions. This only covers what the
+    /// MMCS allocates, not the matrices, which belong to the caller.
+    fn commit_cost(&self, dimensions: &[Dimensions]) -> Cost;
+
+    /// The cost of one call to [`Mmcs::verify_batch`] for matrices of the given dimensions.
+    ///
+    /// Defaults to nothing, for MMCSs which don't estimate their verification.
+    fn verify_batch_cost(&self, _dimensions: &[Dimensions]) -> Cost {
+        Cost::default()
+    }
+}
+
+/// A [`Pcs`](crate::Pcs) which can estimate what committing and opening cost.
+pub trait PcsCostModel<Challenge> {
+    /// The cost of [`Pcs::commit`](crate::Pcs::commit) for evaluations of the given dimensions,
+    /// each over a domain of its height. The evaluations belong to the caller when the step starts.
+    fn commit_cost(&self, dimensions: &[Dimensions]) -> Cost;
+
+    /// The cost of [`Pcs::open`](crate::Pcs::open) given, for each round, the dimensions of the
+    /// evaluations committed to and the number of points each matrix is opened at. A matrix opened
+    /// at `k` points is assumed to be opened at the first `k` of one list of points shared by all.
+    fn open_cost(&self, rounds: &[Vec<(Dimensions, usize)>]) -> Cost;
+
+    /// The cost of [`Pcs::verify`](crate::Pcs::verify) for the rounds given to
+    /// [`Self::open_cost`], not counting the challenger.
+    ///
+    /// Defaults to nothing, for PCSs which don't estimate their verification.
+    fn verify_cost(&self, _rounds: &[Vec<(Dimensions, usize)>]) -> Cost {
+        Cost::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peaks_include_what_earlier_steps_retained() {
+        let cost = Cost::allocation(10)
+            .then(Cost::temporary(5))
+            .then(Cost::allocation(3))
+            .freeing(4);
+        assert_eq!((cost.retained_bytes, cost.peak_bytes), (9, 15));
+    }
+}
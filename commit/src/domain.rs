@@ -9,7 +9,7 @@ use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_util::{log2_ceil_usize, log2_strict_usize};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LagrangeSelectors<T> {
     pub is_first_row: T,
     pub is_last_row: T,
@@ -17,7 +17,8 @@ pub struct LagrangeSelectors<T> {
     pub inv_zeroifier: T,
 }
 
-pub trait PolynomialSpace: Copy {
+/// Domains are compared so that values computed over them can be cached.
+pub trait PolynomialSpace: Copy + PartialEq {
     type Val: Field;
 
     fn size(&self) -> usize;
@@ -54,7 +55,7 @@ pub trait PolynomialSpace: Copy {
     fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Self::Val>>;
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct TwoAdicMultiplicativeCoset<Val: TwoAdicField> {
     pub log_n: usize,
     pub shift: Val,
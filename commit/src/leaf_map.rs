@@ -0,0 +1,46 @@
+//! Diagnostics for where committed rows end up in an MMCS.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Where one row of a committed matrix ended up, for debugging ordering mismatches between
+/// implementations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeafMapEntry {
+    /// The index of the matrix within the commitment.
+    pub matrix: usize,
+    /// The index of the row's point in the natural ordering of the committed domain.
+    pub logical_row: usize,
+    /// The index of the row in the committed matrix.
+    pub physical_row: usize,
+    /// The indices to pass to `Mmcs::open_batch` to open this row. Rows of matrices shorter than
+    /// the tallest one are opened by several indices.
+    pub leaf_indices: Range<usize>,
+}
+
+/// Build the logical→leaf mapping of a commitment to matrices with the given (power of two)
+/// heights, where `logical_row(log_height, physical_row)` is the logical index of a committed row.
+///
+/// Entries are ordered by matrix, then by physical row.
+pub fn leaf_mapping(
+    log_heights: &[usize],
+    logical_row: impl Fn(usize, usize) -> usize,
+) -> Vec<LeafMapEntry> {
+    let Some(&log_max_height) = log_heights.iter().max() else {
+        return Vec::new();
+    };
+    log_heights
+        .iter()
+        .enumerate()
+        .flat_map(|(matrix, &log_height)| {
+            let bits_reduced = log_max_height - log_height;
+            let logical_row = &logical_row;
+            (0..1 << log_height).map(move |physical_row| LeafMapEntry {
+                matrix,
+                logical_row: logical_row(log_height, physical_row),
+                physical_row,
+                leaf_indices: (physical_row << bits_reduced)..((physical_row + 1) << bits_reduced),
+            })
+        })
+        .collect()
+}
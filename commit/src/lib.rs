@@ -5,7 +5,9 @@
 extern crate alloc;
 
 mod adapters;
+mod cost;
 mod domain;
+mod leaf_map;
 mod mmcs;
 mod pcs;
 
@@ -13,6 +15,8 @@ mod pcs;
 pub mod testing;
 
 pub use adapters::*;
+pub use cost::*;
 pub use domain::*;
+pub use leaf_map::*;
 pub use mmcs::*;
 pub use pcs::*;
@@ -16,9 +16,13 @@ use serde::Serialize;
 /// with the largest height. For matrices with smaller heights, some bits of the row index are
 /// removed (from the least-significant side) to get the effective row index. These semantics are
 /// useful in the FRI protocol. See the documentation for `open_batch` for more details.
+///
+/// Nothing here is specific to Merkle trees. Commitments and proofs are opaque, and code which is
+/// generic over an MMCS, such as FRI, only needs its challenger to be able to observe the
+/// commitments. `testing::TrivialMmcs`, which sends every matrix in full, is one other backend.
 pub trait Mmcs<T: Send + Sync>: Clone {
     type ProverData<M>;
-    type Commitment: Clone + Serialize + DeserializeOwned;
+    type Commitment: Clone + PartialEq + Serialize + DeserializeOwned;
     type Proof: Clone + Serialize + DeserializeOwned;
     type Error: Debug;
 
@@ -48,6 +48,34 @@ where
         domain: Self::Domain,
     ) -> impl Matrix<Val<Self::Domain>> + 'a;
 
+    /// The log of the factor by which a domain passed to
+    /// [`get_evaluations_on_domain`](Pcs::get_evaluations_on_domain) may be larger than the domain
+    /// its matrix was committed over, or `None` if it may be of any size.
+    fn max_log_evaluation_blowup(&self) -> Option<usize> {
+        None
+    }
+
+    /// The log of the size of the largest domain a matrix may be committed over, or `None` if
+    /// there's no limit. A verifier checks claimed degrees against this before building domains.
+    fn max_log_domain_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// The parameters of the queries which the soundness of an opening rests on, or `None` if it
+    /// doesn't make any, e.g. because every polynomial is sent in full.
+    fn query_parameters(&self) -> Option<QueryParameters> {
+        None
+    }
+
+    /// Open each round's matrices at the given points.
+    ///
+    /// Prover data isn't used up by opening it, so a commitment can be opened in several calls, e.g.
+    /// again at a point sampled after an earlier opening. Each call makes its own proof, which is
+    /// checked by a matching call to [`verify`](Pcs::verify) at the same point in the transcript.
+    ///
+    /// Fails if the rounds can't be opened at all, e.g. because FRI's final polynomial would be
+    /// longer than the shortest of them.
+    #[allow(clippy::type_complexity)]
     fn open(
         &self,
         // For each round,
@@ -60,8 +88,10 @@ where
             >,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof);
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::Error>;
 
+    /// Check the proof made by a call to [`open`](Pcs::open) with the same rounds and challenger
+    /// state.
     #[allow(clippy::type_complexity)]
     fn verify(
         &self,
@@ -86,6 +116,41 @@ where
     ) -> Result<(), Self::Error>;
 }
 
+/// A [`Pcs`] whose opening proofs can be checkpointed part way through, e.g. after each of FRI's
+/// commit phase rounds, and finished from a checkpoint, e.g. on another machine after this one was
+/// interrupted.
+pub trait ResumablePcs<Challenge, Challenger>: Pcs<Challenge, Challenger>
+where
+    Challenge: ExtensionField<Val<Self::Domain>>,
+{
+    /// The state of an opening proof part way through.
+    type OpeningCheckpoint: Clone + Serialize + DeserializeOwned;
+
+    /// Open as [`open`](Pcs::open) does, calling `on_checkpoint` at each point which the proof can
+    /// be resumed from. With `resume`, the proof is finished from that checkpoint instead, giving
+    /// the same proof as if it hadn't been interrupted: `rounds` must be those it was made from,
+    /// and `challenger` must be in the state the opening started in.
+    ///
+    /// Fails if the checkpoint doesn't fit the rounds, or if the rounds can't be opened at all.
+    #[allow(clippy::type_complexity)]
+    fn open_with_checkpoints(
+        &self,
+        rounds: Vec<(&Self::ProverData, Vec<Vec<Challenge>>)>,
+        challenger: &mut Challenger,
+        resume: Option<Self::OpeningCheckpoint>,
+        on_checkpoint: &mut dyn FnMut(Self::OpeningCheckpoint),
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::Error>;
+}
+
+/// The parameters of a PCS's low-degree test which its soundness is estimated from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct QueryParameters {
+    /// The log of the inverse of the code's rate, i.e. of the blowup of each codeword.
+    pub log_blowup: usize,
+    pub num_queries: usize,
+    pub proof_of_work_bits: usize,
+}
+
 pub type OpenedValues<F> = Vec<OpenedValuesForRound<F>>;
 pub type OpenedValuesForRound<F> = Vec<OpenedValuesForMatrix<F>>;
 pub type OpenedValuesForMatrix<F> = Vec<OpenedValuesForPoint<F>>;
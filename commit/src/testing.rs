@@ -6,11 +6,12 @@ use p3_challenger::CanSample;
 use p3_dft::TwoAdicSubgroupDft;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::dense::RowMajorMatrix;
-use p3_matrix::Matrix;
-use p3_util::log2_strict_usize;
+use p3_matrix::{Dimensions, Matrix};
+use p3_util::{log2_ceil_usize, log2_strict_usize};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use crate::{OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
+use crate::{Mmcs, OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
 
 /// A trivial PCS: its commitment is simply the coefficients of each poly.
 #[derive(Debug)]
@@ -116,8 +117,8 @@ where
             >,
         )>,
         _challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
-        (
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::Error> {
+        Ok((
             rounds
                 .into_iter()
                 .map(|(coeffs_for_round, points_for_round)| {
@@ -134,7 +135,7 @@ where
                 })
                 .collect(),
             (),
-        )
+        ))
     }
 
     fn verify(
@@ -170,3 +171,74 @@ where
         Ok(())
     }
 }
+
+/// A trivial MMCS: its commitment is simply the values of each matrix, row by row, so openings
+/// need no proof.
+///
+/// It is binding but far from succinct, so it's only good for testing code which is generic over
+/// [`Mmcs`], e.g. to check that FRI doesn't rely on its commitments being Merkle roots.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TrivialMmcs;
+
+impl<T> Mmcs<T> for TrivialMmcs
+where
+    T: Clone + PartialEq + Send + Sync + Serialize + DeserializeOwned,
+{
+    type ProverData<M> = Vec<M>;
+    type Commitment = Vec<Vec<T>>;
+    type Proof = ();
+    type Error = ();
+
+    fn commit<M: Matrix<T>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
+        let commitment = inputs
+            .iter()
+            .map(|matrix| matrix.to_row_major_matrix().values)
+            .collect();
+        (commitment, inputs)
+    }
+
+    fn open_batch<M: Matrix<T>>(
+        &self,
+        index: usize,
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<T>>, Self::Proof) {
+        let log_max_height = log2_ceil_usize(self.get_max_height(prover_data));
+        let openings = prover_data
+            .iter()
+            .map(|matrix| {
+                let bits_reduced = log_max_height - log2_ceil_usize(matrix.height());
+                matrix.row(index >> bits_reduced).collect()
+            })
+            .collect();
+        (openings, ())
+    }
+
+    fn get_matrices<'a, M: Matrix<T>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
+        prover_data.iter().collect()
+    }
+
+    fn verify_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        index: usize,
+        opened_values: &[Vec<T>],
+        _proof: &Self::Proof,
+    ) -> Result<(), Self::Error> {
+        if commit.len() != dimensions.len() || opened_values.len() != dimensions.len() {
+            return Err(());
+        }
+        let max_height = dimensions.iter().map(|dims| dims.height).max().ok_or(())?;
+        let log_max_height = log2_ceil_usize(max_height);
+        for ((values, dims), opened_row) in commit.iter().zip(dimensions).zip(opened_values) {
+            let row = index >> (log_max_height - log2_ceil_usize(dims.height));
+            if values.len() != dims.width * dims.height
+                || row >= dims.height
+                || values[row * dims.width..(row + 1) * dims.width] != opened_row[..]
+            {
+                return Err(());
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use p3_baby_bear::BabyBear;
-use p3_dft::{Radix2Bowers, Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_dft::{
+    FourStepDft, Radix2Bowers, Radix2Dit, Radix2DitParallel, Radix4Dit, TwoAdicSubgroupDft,
+};
 use p3_field::extension::Complex;
 use p3_field::TwoAdicField;
 use p3_goldilocks::Goldilocks;
@@ -21,6 +23,8 @@ fn bench_fft(c: &mut Criterion) {
     const BATCH_SIZE: usize = 256;
 
     fft::<BabyBear, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
+    fft::<BabyBear, Radix4Dit<_>, BATCH_SIZE>(c, log_sizes);
+    fft::<BabyBear, FourStepDft<_>, BATCH_SIZE>(c, log_sizes);
     fft::<BabyBear, RecursiveDft<_>, BATCH_SIZE>(c, log_sizes);
     fft::<BabyBear, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
     fft::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
@@ -39,6 +43,7 @@ fn bench_fft(c: &mut Criterion) {
 
     coset_lde::<BabyBear, RecursiveDft<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
+    coset_lde::<BabyBear, Radix4Dit<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<Goldilocks, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
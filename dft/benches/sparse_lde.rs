@@ -0,0 +1,48 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_baby_bear::BabyBear;
+use p3_dft::{coset_lde_sparse_batch_with_threshold, Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::Field;
+use p3_matrix::sparse::SparseSelectorMatrix;
+use rand::{thread_rng, Rng};
+
+type F = BabyBear;
+
+/// The LDE of a selector column of height `2^20` with a few nonzero entries, with the DFT and
+/// directly from its nonzero entries, for choosing `MAX_DIRECT_LDE_NONZEROS`.
+fn bench_sparse_lde(c: &mut Criterion) {
+    const LOG_HEIGHT: usize = 20;
+    const ADDED_BITS: usize = 1;
+
+    let mut rng = thread_rng();
+    let dft = Radix2DitParallel::<F>::default();
+    let mut group = c.benchmark_group("sparse_lde");
+    group.sample_size(10);
+    for nonzeros in [4, 16, 64] {
+        let mut rows = (0..nonzeros)
+            .map(|_| rng.gen_range(0..1 << LOG_HEIGHT))
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows.dedup();
+        let column = rows.into_iter().map(|r| (r, rng.gen())).collect();
+        let selectors = SparseSelectorMatrix::<F>::new(1 << LOG_HEIGHT, vec![column]);
+
+        group.bench_function(BenchmarkId::new("dense", nonzeros), |b| {
+            b.iter(|| dft.coset_lde_batch(selectors.to_dense(), ADDED_BITS, F::GENERATOR))
+        });
+        group.bench_function(BenchmarkId::new("direct", nonzeros), |b| {
+            b.iter(|| {
+                coset_lde_sparse_batch_with_threshold(
+                    &dft,
+                    &selectors,
+                    ADDED_BITS,
+                    F::GENERATOR,
+                    nonzeros,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_sparse_lde);
+criterion_main!(benches);
@@ -85,3 +85,64 @@ impl<F: Field> Butterfly<F> for TwiddleFreeButterfly {
         (x_1 + x_2, x_1 - x_2)
     }
 }
+
+/// A radix-4 DIT butterfly, equivalent to two consecutive layers of `DitButterfly`s.
+///
+/// For a merged layer pair with (wider layer) twiddle `w`, the fields hold `w^2`, `w` and `w^3`
+/// respectively, while `imag` is a primitive 4th root of unity.
+#[derive(Copy, Clone)]
+pub struct Radix4DitButterfly<F> {
+    pub twiddle_1: F,
+    pub twiddle_2: F,
+    pub twiddle_3: F,
+    pub imag: F,
+}
+
+impl<F: Field> Radix4DitButterfly<F> {
+    #[inline]
+    pub fn apply<PF: PackedField<Scalar = F>>(
+        &self,
+        x_0: PF,
+        x_1: PF,
+        x_2: PF,
+        x_3: PF,
+    ) -> [PF; 4] {
+        let x_1_twiddle = x_1 * self.twiddle_1;
+        let x_2_twiddle = x_2 * self.twiddle_2;
+        let x_3_twiddle = x_3 * self.twiddle_3;
+
+        let lo_sum = x_0 + x_1_twiddle;
+        let lo_diff = x_0 - x_1_twiddle;
+        let hi_sum = x_2_twiddle + x_3_twiddle;
+        let hi_diff = (x_2_twiddle - x_3_twiddle) * self.imag;
+
+        [
+            lo_sum + hi_sum,
+            lo_diff + hi_diff,
+            lo_sum - hi_sum,
+            lo_diff - hi_diff,
+        ]
+    }
+
+    #[inline]
+    pub fn apply_to_rows(
+        &self,
+        row_0: &mut [F],
+        row_1: &mut [F],
+        row_2: &mut [F],
+        row_3: &mut [F],
+    ) {
+        let (shorts_0, suffix_0) = F::Packing::pack_slice_with_suffix_mut(row_0);
+        let (shorts_1, suffix_1) = F::Packing::pack_slice_with_suffix_mut(row_1);
+        let (shorts_2, suffix_2) = F::Packing::pack_slice_with_suffix_mut(row_2);
+        let (shorts_3, suffix_3) = F::Packing::pack_slice_with_suffix_mut(row_3);
+        debug_assert_eq!(shorts_0.len(), shorts_3.len());
+        debug_assert_eq!(suffix_0.len(), suffix_3.len());
+        for (x_0, x_1, x_2, x_3) in izip!(shorts_0, shorts_1, shorts_2, shorts_3) {
+            [*x_0, *x_1, *x_2, *x_3] = self.apply(*x_0, *x_1, *x_2, *x_3);
+        }
+        for (x_0, x_1, x_2, x_3) in izip!(suffix_0, suffix_1, suffix_2, suffix_3) {
+            [*x_0, *x_1, *x_2, *x_3] = self.apply::<F>(*x_0, *x_1, *x_2, *x_3);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use alloc::vec;
+use core::mem::size_of;
+
+use p3_field::TwoAdicField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+
+use crate::radix_2_dit::dit_in_place;
+use crate::{Radix2Dit, TwoAdicSubgroupDft};
+
+/// The default cache size hint of `FourStepDft`, in bytes; roughly the size of an L2 cache.
+pub const DEFAULT_CACHE_SIZE_HINT: usize = 1 << 20;
+
+/// The number of grid columns `transpose_row_grid` moves at once.
+const TRANSPOSE_TILE_SIZE: usize = 16;
+
+/// A four-step DFT, which splits a transform of size `N = N_1 N_2` into `N_2` transforms of size
+/// `N_1`, a twiddle multiplication, and `N_1` transforms of size `N_2`.
+///
+/// Rows are transposed between steps so that each sub-transform runs on a contiguous block,
+/// small enough to stay in cache, and the sub-transforms run in parallel. For matrices much larger
+/// than the cache this takes far fewer passes over main memory than `Radix2Dit`, which makes one
+/// per layer. Matrices no larger than the cache size hint are transformed with `Radix2Dit`
+/// directly.
+#[derive(Clone, Debug)]
+pub struct FourStepDft<F: TwoAdicField> {
+    cache_size_hint: usize,
+    radix_2: Radix2Dit<F>,
+}
+
+impl<F: TwoAdicField> Default for FourStepDft<F> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_SIZE_HINT)
+    }
+}
+
+impl<F: TwoAdicField> FourStepDft<F> {
+    /// Create a four-step DFT for a cache of `cache_size_hint` bytes.
+    pub fn new(cache_size_hint: usize) -> Self {
+        Self {
+            cache_size_hint,
+            radix_2: Radix2Dit::default(),
+        }
+    }
+}
+
+impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for FourStepDft<F> {
+    type Evaluations = RowMajorMatrix<F>;
+
+    fn dft_batch(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let w = mat.width();
+        let log_h = log2_strict_usize(h);
+        if log_h < 2 || mat.values.len() * size_of::<F>() <= self.cache_size_hint {
+            return self.radix_2.dft_batch(mat);
+        }
+
+        // Write `x[n_1, n_2]` for input row `N_2 n_1 + n_2`, and similarly `X[k_1, k_2]` for output
+        // row `k_1 + N_1 k_2`.
+        let log_n1 = log_h.div_ceil(2);
+        let log_n2 = log_h - log_n1;
+        let (n1, n2) = (1 << log_n1, 1 << log_n2);
+        let twiddles = self.radix_2.twiddles(log_h);
+        let twiddles_n1 = self.radix_2.twiddles(log_n1);
+        let twiddles_n2 = self.radix_2.twiddles(log_n2);
+
+        // Gather each column `x[., n_2]` of the grid into a block of `N_1` rows.
+        let mut scratch = RowMajorMatrix::new(vec![F::ZERO; mat.values.len()], w);
+        transpose_row_grid(&mat.values, &mut scratch.values, w, n1, n2);
+
+        // Transform each block, giving `Y[k_1, n_2]`, and multiply it by `ω_N^{n_2 k_1}`.
+        scratch
+            .par_row_chunks_exact_mut(n1)
+            .enumerate()
+            .for_each(|(n2_idx, mut block)| {
+                dit_in_place(&mut block, &twiddles_n1);
+                for k1 in 1..n1 {
+                    block.scale_row(k1, twiddles[n2_idx * k1]);
+                }
+            });
+
+        // Gather each row `Y[k_1, .]` into a block of `N_2` rows, and transform it, giving
+        // `X[k_1, k_2]`.
+        transpose_row_grid(&scratch.values, &mut mat.values, w, n2, n1);
+        mat.par_row_chunks_exact_mut(n2)
+            .for_each(|mut block| dit_in_place(&mut block, &twiddles_n2));
+
+        // Put `X` back in natural order.
+        transpose_row_grid(&mat.values, &mut scratch.values, w, n1, n2);
+        scratch
+    }
+}
+
+/// Transpose `src`, a `rows x cols` grid of rows of `width` elements each, into `dst`, so that row
+/// `r * cols + c` of `src` becomes row `c * rows + r` of `dst`.
+///
+/// Columns of the grid are moved in tiles, so that reads from `src` are contiguous.
+fn transpose_row_grid<T: Copy + Send + Sync>(
+    src: &[T],
+    dst: &mut [T],
+    width: usize,
+    rows: usize,
+    cols: usize,
+) {
+    debug_assert_eq!(src.len(), rows * cols * width);
+    debug_assert_eq!(dst.len(), src.len());
+    let tile_size = TRANSPOSE_TILE_SIZE.min(cols);
+    dst.par_chunks_exact_mut(tile_size * rows * width)
+        .enumerate()
+        .for_each(|(tile, dst_tile)| {
+            let c_start = tile * tile_size;
+            for r in 0..rows {
+                let src_start = (r * cols + c_start) * width;
+                let src_rows = &src[src_start..src_start + tile_size * width];
+                for (t, src_row) in src_rows.chunks_exact(width).enumerate() {
+                    let dst_start = (t * rows + r) * width;
+                    dst_tile[dst_start..dst_start + width].copy_from_slice(src_row);
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::Field;
+    use p3_goldilocks::Goldilocks;
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::thread_rng;
+
+    use super::transpose_row_grid;
+    use crate::{FourStepDft, Radix2Dit, TwoAdicSubgroupDft};
+
+    #[test]
+    fn transpose_row_grid_matches_naive() {
+        for (rows, cols, width) in [(4, 8, 1), (8, 4, 3), (32, 32, 2), (2, 64, 5)] {
+            let src = (0..rows * cols * width).collect::<alloc::vec::Vec<_>>();
+            let mut dst = alloc::vec![0; src.len()];
+            transpose_row_grid(&src, &mut dst, width, rows, cols);
+            for r in 0..rows {
+                for c in 0..cols {
+                    assert_eq!(
+                        dst[(c * rows + r) * width..][..width],
+                        src[(r * cols + c) * width..][..width]
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matches_radix_2_dit() {
+        type F = BabyBear;
+        let mut rng = thread_rng();
+        // A tiny cache size hint, so that all but the smallest matrices take the four-step path.
+        let dft = FourStepDft::<F>::new(64);
+        for log_h in 0..=13 {
+            for width in [1, 3, 8] {
+                let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, width);
+                assert_eq!(
+                    dft.dft_batch(mat.clone()),
+                    Radix2Dit::default().dft_batch(mat)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn coset_lde_matches_radix_2_dit() {
+        type F = Goldilocks;
+        let mut rng = thread_rng();
+        let dft = FourStepDft::<F>::new(256);
+        for log_h in [3, 6, 9] {
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, 4);
+            let shift = F::GENERATOR;
+            assert_eq!(
+                dft.coset_lde_batch(mat.clone(), 2, shift),
+                Radix2Dit::default().coset_lde_batch(mat, 2, shift)
+            );
+        }
+    }
+}
@@ -5,17 +5,25 @@
 extern crate alloc;
 
 mod butterflies;
+mod four_step;
 mod naive;
 mod radix_2_bowers;
 mod radix_2_dit;
 mod radix_2_dit_parallel;
+mod radix_4_dit;
+mod sparse_lde;
 mod traits;
+mod twiddle_cache;
 mod util;
 
 pub use butterflies::*;
+pub use four_step::*;
 pub use naive::*;
 pub use radix_2_bowers::*;
 pub use radix_2_dit::*;
 pub use radix_2_dit_parallel::*;
+pub use radix_4_dit::*;
+pub use sparse_lde::*;
 pub use traits::*;
+pub use twiddle_cache::*;
 pub use util::*;
@@ -1,6 +1,4 @@
-use alloc::collections::BTreeMap;
-use alloc::vec::Vec;
-use core::cell::RefCell;
+use alloc::sync::Arc;
 
 use p3_field::{Field, TwoAdicField};
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
@@ -10,13 +8,35 @@ use p3_maybe_rayon::prelude::*;
 use p3_util::log2_strict_usize;
 
 use crate::butterflies::{Butterfly, DitButterfly, TwiddleFreeButterfly};
-use crate::TwoAdicSubgroupDft;
+use crate::radix_4_dit::dit_radix_4_layers;
+use crate::util::coset_shift_cols;
+use crate::{TwiddleCache, TwoAdicSubgroupDft};
+
+/// The smallest log-height of an LDE for which `Radix2Dit::coset_lde_batch` switches to radix-4
+/// layers for the forward transform.
+pub const RADIX_4_LDE_MIN_LOG_HEIGHT: usize = 16;
 
 /// The DIT FFT algorithm.
+///
+/// Clones share the same twiddle cache, so one instance can be cloned into each thread.
 #[derive(Default, Clone, Debug)]
 pub struct Radix2Dit<F: TwoAdicField> {
     /// Memoized twiddle factors for each length log_n.
-    twiddles: RefCell<BTreeMap<usize, Vec<F>>>,
+    twiddles: TwiddleCache<F>,
+}
+
+impl<F: TwoAdicField> Radix2Dit<F> {
+    /// Compute the twiddle factors for DFTs of size `2^log_n` ahead of time, e.g. to keep them out
+    /// of a timing-sensitive section.
+    pub fn precompute_twiddles(&self, log_n: usize) {
+        self.twiddles(log_n);
+    }
+
+    /// The twiddle factors for size `2^log_h`, i.e. the first `2^log_h` powers of the
+    /// corresponding root of unity, computing and memoizing them if necessary.
+    pub(crate) fn twiddles(&self, log_h: usize) -> Arc<[F]> {
+        self.twiddles.get(log_h, F::ONE)
+    }
 }
 
 impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Dit<F> {
@@ -27,23 +47,60 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Dit<F> {
         let log_h = log2_strict_usize(h);
 
         // Compute twiddle factors, or take memoized ones if already available.
-        let mut twiddles_ref_mut = self.twiddles.borrow_mut();
-        let twiddles = twiddles_ref_mut.entry(log_h).or_insert_with(|| {
-            let root = F::two_adic_generator(log_h);
-            root.powers().take(1 << log_h).collect()
-        });
+        let twiddles = self.twiddles(log_h);
 
         // DIT butterfly
-        reverse_matrix_index_bits(&mut mat);
-        for layer in 0..log_h {
-            dit_layer(&mut mat.as_view_mut(), layer, twiddles);
-        }
+        p3_maybe_rayon::install(mat.values.len(), || {
+            dit_in_place(&mut mat.as_view_mut(), &twiddles);
+        });
         mat
     }
+
+    fn coset_lde_batch(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let mut coeffs = self.idft_batch(mat);
+        // PANICS: possible panic if the new resized length overflows
+        coeffs.values.resize(
+            coeffs
+                .values
+                .len()
+                .checked_shl(added_bits.try_into().unwrap())
+                .unwrap(),
+            F::ZERO,
+        );
+        coset_shift_cols(&mut coeffs, shift);
+
+        let log_h = log2_strict_usize(coeffs.height());
+        if log_h < RADIX_4_LDE_MIN_LOG_HEIGHT {
+            return self.dft_batch(coeffs);
+        }
+
+        // Large LDEs are memory bound, so merge pairs of layers to halve the passes over `coeffs`.
+        let twiddles = self.twiddles(log_h);
+        reverse_matrix_index_bits(&mut coeffs);
+        dit_radix_4_layers(&mut coeffs.as_view_mut(), &twiddles);
+        coeffs
+    }
+}
+
+/// A DIT FFT of each column of `mat` in place, given the twiddles for its height.
+pub(crate) fn dit_in_place<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, twiddles: &[F]) {
+    reverse_matrix_index_bits(mat);
+    for layer in 0..log2_strict_usize(mat.height()) {
+        dit_layer(mat, layer, twiddles);
+    }
 }
 
 /// One layer of a DIT butterfly network.
-fn dit_layer<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, layer: usize, twiddles: &[F]) {
+pub(crate) fn dit_layer<F: Field>(
+    mat: &mut RowMajorMatrixViewMut<'_, F>,
+    layer: usize,
+    twiddles: &[F],
+) {
     let h = mat.height();
     let log_h = log2_strict_usize(h);
     let layer_rev = log_h - 1 - layer;
@@ -67,3 +124,30 @@ fn dit_layer<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, layer: usize, twi
                 });
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::thread_rng;
+
+    use crate::{Radix2Dit, TwoAdicSubgroupDft};
+
+    #[test]
+    fn dfts_share_twiddles() {
+        type F = BabyBear;
+        let mut rng = thread_rng();
+        let dft = Radix2Dit::<F>::default();
+        dft.precompute_twiddles(5);
+        let precomputed = dft.twiddles(5);
+
+        let clone = dft.clone();
+        clone.dft_batch(RowMajorMatrix::<F>::rand(&mut rng, 1 << 5, 2));
+        dft.dft_batch(RowMajorMatrix::<F>::rand(&mut rng, 1 << 5, 3));
+        assert!(Arc::ptr_eq(&precomputed, &dft.twiddles(5)));
+        assert!(Arc::ptr_eq(&precomputed, &clone.twiddles(5)));
+        assert!(!Arc::ptr_eq(&precomputed, &dft.twiddles(4)));
+    }
+}
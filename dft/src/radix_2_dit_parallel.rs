@@ -262,21 +262,23 @@ fn first_half<F: Field>(mat: &mut RowMajorMatrix<F>, mid: usize, twiddles: &[F])
     let log_h = log2_strict_usize(mat.height());
 
     // max block size: 2^mid
-    mat.par_row_chunks_exact_mut(1 << mid)
-        .for_each(|mut submat| {
-            let mut backwards = false;
-            for layer in 0..mid {
-                let layer_rev = log_h - 1 - layer;
-                let layer_pow = 1 << layer_rev;
-                dit_layer(
-                    &mut submat,
-                    layer,
-                    twiddles.iter().copied().step_by(layer_pow),
-                    backwards,
-                );
-                backwards = !backwards;
-            }
-        });
+    p3_maybe_rayon::install(mat.values.len(), || {
+        mat.par_row_chunks_exact_mut(1 << mid)
+            .for_each(|mut submat| {
+                let mut backwards = false;
+                for layer in 0..mid {
+                    let layer_rev = log_h - 1 - layer;
+                    let layer_pow = 1 << layer_rev;
+                    dit_layer(
+                        &mut submat,
+                        layer,
+                        twiddles.iter().copied().step_by(layer_pow),
+                        backwards,
+                    );
+                    backwards = !backwards;
+                }
+            });
+    });
 }
 
 /// Like `first_half`, except supporting different twiddle factors per layer, enabling coset shifts
@@ -288,20 +290,22 @@ fn first_half_general<F: Field>(
     twiddles: &[Vec<F>],
 ) {
     let log_h = log2_strict_usize(mat.height());
-    mat.par_row_chunks_exact_mut(1 << mid)
-        .for_each(|mut submat| {
-            let mut backwards = false;
-            for layer in 0..mid {
-                let layer_rev = log_h - 1 - layer;
-                dit_layer(
-                    &mut submat,
-                    layer,
-                    twiddles[layer_rev].iter().copied(),
-                    backwards,
-                );
-                backwards = !backwards;
-            }
-        });
+    p3_maybe_rayon::install(mat.values.len(), || {
+        mat.par_row_chunks_exact_mut(1 << mid)
+            .for_each(|mut submat| {
+                let mut backwards = false;
+                for layer in 0..mid {
+                    let layer_rev = log_h - 1 - layer;
+                    dit_layer(
+                        &mut submat,
+                        layer,
+                        twiddles[layer_rev].iter().copied(),
+                        backwards,
+                    );
+                    backwards = !backwards;
+                }
+            });
+    });
 }
 
 /// Like `first_half_general`, except out-of-place.
@@ -316,41 +320,43 @@ fn first_half_general_oop<F: Field>(
     twiddles: &[Vec<F>],
 ) {
     let log_h = log2_strict_usize(src.height());
-    src.par_row_chunks_exact(1 << mid)
-        .zip(dst_maybe.par_row_chunks_exact_mut(1 << mid))
-        .for_each(|(src_submat, mut dst_submat_maybe)| {
-            debug_assert_eq!(src_submat.dimensions(), dst_submat_maybe.dimensions());
-
-            // The first layer is special, done out-of-place.
-            // (Recall from the mid definition that there must be at least one layer here.)
-            let layer_rev = log_h - 1;
-            dit_layer_oop(
-                &src_submat,
-                &mut dst_submat_maybe,
-                0,
-                twiddles[layer_rev].iter().copied(),
-            );
-
-            // submat is now initialized.
-            let mut dst_submat = unsafe {
-                transmute::<RowMajorMatrixViewMut<MaybeUninit<F>>, RowMajorMatrixViewMut<F>>(
-                    dst_submat_maybe,
-                )
-            };
-
-            // Subsequent layers.
-            let mut backwards = true;
-            for layer in 1..mid {
-                let layer_rev = log_h - 1 - layer;
-                dit_layer(
-                    &mut dst_submat,
-                    layer,
+    p3_maybe_rayon::install(src.values.len(), || {
+        src.par_row_chunks_exact(1 << mid)
+            .zip(dst_maybe.par_row_chunks_exact_mut(1 << mid))
+            .for_each(|(src_submat, mut dst_submat_maybe)| {
+                debug_assert_eq!(src_submat.dimensions(), dst_submat_maybe.dimensions());
+
+                // The first layer is special, done out-of-place.
+                // (Recall from the mid definition that there must be at least one layer here.)
+                let layer_rev = log_h - 1;
+                dit_layer_oop(
+                    &src_submat,
+                    &mut dst_submat_maybe,
+                    0,
                     twiddles[layer_rev].iter().copied(),
-                    backwards,
                 );
-                backwards = !backwards;
-            }
-        });
+
+                // submat is now initialized.
+                let mut dst_submat = unsafe {
+                    transmute::<RowMajorMatrixViewMut<MaybeUninit<F>>, RowMajorMatrixViewMut<F>>(
+                        dst_submat_maybe,
+                    )
+                };
+
+                // Subsequent layers.
+                let mut backwards = true;
+                for layer in 1..mid {
+                    let layer_rev = log_h - 1 - layer;
+                    dit_layer(
+                        &mut dst_submat,
+                        layer,
+                        twiddles[layer_rev].iter().copied(),
+                        backwards,
+                    );
+                    backwards = !backwards;
+                }
+            });
+    });
 }
 
 /// This can be used as the second half of a DIT butterfly network. It works in bit-reversed order.
@@ -369,25 +375,27 @@ fn second_half<F: Field>(
     let log_h = log2_strict_usize(mat.height());
 
     // max block size: 2^(log_h - mid)
-    mat.par_row_chunks_exact_mut(1 << (log_h - mid))
-        .enumerate()
-        .for_each(|(thread, mut submat)| {
-            let mut backwards = false;
-            if let Some(scale) = scale {
-                submat.scale(scale);
-            }
-            for layer in mid..log_h {
-                let first_block = thread << (layer - mid);
-                dit_layer_rev(
-                    &mut submat,
-                    log_h,
-                    layer,
-                    twiddles_rev[first_block..].iter().copied(),
-                    backwards,
-                );
-                backwards = !backwards;
-            }
-        });
+    p3_maybe_rayon::install(mat.values.len(), || {
+        mat.par_row_chunks_exact_mut(1 << (log_h - mid))
+            .enumerate()
+            .for_each(|(thread, mut submat)| {
+                let mut backwards = false;
+                if let Some(scale) = scale {
+                    submat.scale(scale);
+                }
+                for layer in mid..log_h {
+                    let first_block = thread << (layer - mid);
+                    dit_layer_rev(
+                        &mut submat,
+                        log_h,
+                        layer,
+                        twiddles_rev[first_block..].iter().copied(),
+                        backwards,
+                    );
+                    backwards = !backwards;
+                }
+            });
+    });
 }
 
 /// Like `second_half`, except supporting different twiddle factors per layer, enabling coset shifts
@@ -399,23 +407,25 @@ fn second_half_general<F: Field>(
     twiddles_rev: &[Vec<F>],
 ) {
     let log_h = log2_strict_usize(mat.height());
-    mat.par_row_chunks_exact_mut(1 << (log_h - mid))
-        .enumerate()
-        .for_each(|(thread, mut submat)| {
-            let mut backwards = false;
-            for layer in mid..log_h {
-                let layer_rev = log_h - 1 - layer;
-                let first_block = thread << (layer - mid);
-                dit_layer_rev(
-                    &mut submat,
-                    log_h,
-                    layer,
-                    twiddles_rev[layer_rev][first_block..].iter().copied(),
-                    backwards,
-                );
-                backwards = !backwards;
-            }
-        });
+    p3_maybe_rayon::install(mat.values.len(), || {
+        mat.par_row_chunks_exact_mut(1 << (log_h - mid))
+            .enumerate()
+            .for_each(|(thread, mut submat)| {
+                let mut backwards = false;
+                for layer in mid..log_h {
+                    let layer_rev = log_h - 1 - layer;
+                    let first_block = thread << (layer - mid);
+                    dit_layer_rev(
+                        &mut submat,
+                        log_h,
+                        layer,
+                        twiddles_rev[layer_rev][first_block..].iter().copied(),
+                        backwards,
+                    );
+                    backwards = !backwards;
+                }
+            });
+    });
 }
 
 /// One layer of a DIT butterfly network.
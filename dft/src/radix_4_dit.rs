@@ -0,0 +1,139 @@
+use p3_field::{Field, TwoAdicField};
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
+use p3_matrix::util::reverse_matrix_index_bits;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+
+use crate::butterflies::Radix4DitButterfly;
+use crate::radix_2_dit::dit_layer;
+use crate::{Radix2Dit, TwoAdicSubgroupDft};
+
+/// The DIT FFT algorithm, processing two layers of the butterfly network per pass over the data.
+///
+/// When the log-size is odd, a single radix-2 layer is applied first. The output is identical to
+/// that of `Radix2Dit`, whose twiddle cache is reused here.
+#[derive(Default, Clone, Debug)]
+pub struct Radix4Dit<F: TwoAdicField> {
+    radix_2: Radix2Dit<F>,
+}
+
+impl<F: TwoAdicField> Radix4Dit<F> {
+    /// Compute the twiddle factors for DFTs of size `2^log_n` ahead of time, e.g. to keep them out
+    /// of a timing-sensitive section.
+    pub fn precompute_twiddles(&self, log_n: usize) {
+        self.radix_2.precompute_twiddles(log_n);
+    }
+}
+
+impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix4Dit<F> {
+    type Evaluations = RowMajorMatrix<F>;
+
+    fn dft_batch(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let log_h = log2_strict_usize(mat.height());
+        let twiddles = self.radix_2.twiddles(log_h);
+        reverse_matrix_index_bits(&mut mat);
+        dit_radix_4_layers(&mut mat.as_view_mut(), &twiddles);
+        mat
+    }
+}
+
+/// Apply every layer of a DIT butterfly network to `mat`, which must already be in bit-reversed
+/// order. `twiddles` must hold the first `mat.height()` powers of the matching root of unity.
+pub(crate) fn dit_radix_4_layers<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, twiddles: &[F]) {
+    let log_h = log2_strict_usize(mat.height());
+
+    let mut layer = 0;
+    if log_h % 2 == 1 {
+        dit_layer(mat, 0, twiddles);
+        layer = 1;
+    }
+    while layer < log_h {
+        dit_radix_4_layer(mat, layer, twiddles);
+        layer += 2;
+    }
+}
+
+/// Layers `layer` and `layer + 1` of a DIT butterfly network, merged into one pass.
+fn dit_radix_4_layer<F: Field>(
+    mat: &mut RowMajorMatrixViewMut<'_, F>,
+    layer: usize,
+    twiddles: &[F],
+) {
+    let h = mat.height();
+    let log_h = log2_strict_usize(h);
+
+    // Step through `twiddles` corresponding to the root of unity of order `block_size`.
+    let stride = 1 << (log_h - layer - 2);
+    let imag = twiddles[h / 4];
+
+    let quarter_block_size = 1 << layer;
+    let block_size = quarter_block_size * 4;
+
+    mat.par_row_chunks_exact_mut(block_size)
+        .for_each(|mut block_chunks| {
+            let (mut lo_chunks, mut hi_chunks) =
+                block_chunks.split_rows_mut(2 * quarter_block_size);
+            let (mut chunks_0, mut chunks_1) = lo_chunks.split_rows_mut(quarter_block_size);
+            let (mut chunks_2, mut chunks_3) = hi_chunks.split_rows_mut(quarter_block_size);
+            chunks_0
+                .par_rows_mut()
+                .zip(chunks_1.par_rows_mut())
+                .zip(chunks_2.par_rows_mut().zip(chunks_3.par_rows_mut()))
+                .enumerate()
+                .for_each(|(ind, ((row_0, row_1), (row_2, row_3)))| {
+                    let twiddle_idx = ind * stride;
+                    Radix4DitButterfly {
+                        twiddle_1: twiddles[2 * twiddle_idx],
+                        twiddle_2: twiddles[twiddle_idx],
+                        twiddle_3: twiddles[3 * twiddle_idx],
+                        imag,
+                    }
+                    .apply_to_rows(row_0, row_1, row_2, row_3)
+                });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::Field;
+    use p3_goldilocks::Goldilocks;
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::thread_rng;
+
+    use crate::{
+        Radix2Bowers, Radix2Dit, Radix4Dit, TwoAdicSubgroupDft, RADIX_4_LDE_MIN_LOG_HEIGHT,
+    };
+
+    #[test]
+    fn matches_radix_2_dit() {
+        type F = BabyBear;
+        let mut rng = thread_rng();
+        let radix_2 = Radix2Dit::<F>::default();
+        let radix_4 = Radix4Dit::<F>::default();
+        for log_h in 1..=12 {
+            for width in 1..=5 {
+                let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, width);
+                assert_eq!(
+                    radix_2.dft_batch(mat.clone()),
+                    radix_4.dft_batch(mat),
+                    "log_h = {log_h}, width = {width}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn radix_2_coset_lde_above_threshold() {
+        type F = Goldilocks;
+        let mut rng = thread_rng();
+        let log_h = RADIX_4_LDE_MIN_LOG_HEIGHT - 1;
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, 2);
+        let shift = F::GENERATOR;
+        assert_eq!(
+            Radix2Dit::default().coset_lde_batch(mat.clone(), 1, shift),
+            Radix2Bowers.coset_lde_batch(mat, 1, shift)
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use alloc::vec::Vec;
+
+use p3_field::{batch_multiplicative_inverse, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::sparse::SparseSelectorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use tracing::instrument;
+
+use crate::TwoAdicSubgroupDft;
+
+/// Columns with at most this many nonzero entries are extended by [`coset_lde_sparse_batch`]
+/// directly from those entries, rather than with a DFT.
+///
+/// Extending a column with `k` nonzero entries directly takes `k` multiplications per point of the
+/// LDE, after one batch inversion shared by all such columns, while the IDFT and DFT of a dense
+/// column take on the order of `log2` of the LDE's height. `benches/sparse_lde.rs` compares the
+/// two.
+pub const MAX_DIRECT_LDE_NONZEROS: usize = 16;
+
+/// The same LDE as `dft.coset_lde_batch(selectors.to_dense(), added_bits, shift)`, except that
+/// columns with at most [`MAX_DIRECT_LDE_NONZEROS`] nonzero entries are evaluated directly from
+/// them, and only the other columns are densified and go through `dft`.
+pub fn coset_lde_sparse_batch<F, Dft>(
+    dft: &Dft,
+    selectors: &SparseSelectorMatrix<F>,
+    added_bits: usize,
+    shift: F,
+) -> RowMajorMatrix<F>
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    coset_lde_sparse_batch_with_threshold(
+        dft,
+        selectors,
+        added_bits,
+        shift,
+        MAX_DIRECT_LDE_NONZEROS,
+    )
+}
+
+/// Like [`coset_lde_sparse_batch`], extending columns with at most `max_direct_nonzeros` nonzero
+/// entries directly.
+#[instrument(skip_all, fields(dims = %selectors.dimensions(), nonzeros = selectors.num_nonzeros()))]
+pub fn coset_lde_sparse_batch_with_threshold<F, Dft>(
+    dft: &Dft,
+    selectors: &SparseSelectorMatrix<F>,
+    added_bits: usize,
+    shift: F,
+    max_direct_nonzeros: usize,
+) -> RowMajorMatrix<F>
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    let width = selectors.width();
+    let log_n = log2_strict_usize(selectors.height());
+    let log_lde_height = log_n + added_bits;
+    // The direct formula divides by the vanishing polynomial of the original subgroup's points,
+    // so it can't be used on a coset which contains some of them.
+    let coset_is_disjoint = shift.exp_power_of_2(log_lde_height) != F::ONE;
+    let (direct_cols, dense_cols): (Vec<usize>, Vec<usize>) = (0..width).partition(|&c| {
+        coset_is_disjoint && selectors.column_nonzeros(c).len() <= max_direct_nonzeros
+    });
+
+    let mut lde = RowMajorMatrix::new(F::zero_vec(width << log_lde_height), width);
+
+    if !dense_cols.is_empty() {
+        let mut values = F::zero_vec(dense_cols.len() << log_n);
+        for (i, &c) in dense_cols.iter().enumerate() {
+            for &(r, value) in selectors.column_nonzeros(c) {
+                values[r * dense_cols.len() + i] = value;
+            }
+        }
+        let dense_lde = dft
+            .coset_lde_batch(
+                RowMajorMatrix::new(values, dense_cols.len()),
+                added_bits,
+                shift,
+            )
+            .to_row_major_matrix();
+        lde.par_rows_mut()
+            .zip(dense_lde.par_row_slices())
+            .for_each(|(row, dense_row)| {
+                for (&c, &value) in dense_cols.iter().zip(dense_row) {
+                    row[c] = value;
+                }
+            });
+    }
+
+    let direct_cols = direct_cols
+        .into_iter()
+        .filter(|&c| !selectors.column_nonzeros(c).is_empty())
+        .collect::<Vec<_>>();
+    if direct_cols.is_empty() {
+        return lde;
+    }
+
+    // The interpolant of evaluations `v_r` over the subgroup `H = <w>` of order `n` is
+    // `sum_r v_r L_r`, for the Lagrange basis `L_r(x) = L_0(w^{-r} x)` of `H`, where
+    //     L_0(x) = Z(x) / (n (x - 1))
+    // and `Z(x) = x^n - 1` vanishes on `H`. Only the nonzero `v_r` contribute to the sum.
+    let lde_height = 1 << log_lde_height;
+    let points = F::two_adic_generator(log_lde_height)
+        .shifted_powers(shift)
+        .take(lde_height);
+    let inv_denominators =
+        batch_multiplicative_inverse(&points.map(|x| x - F::ONE).collect::<Vec<_>>());
+    // The LDE point `shift w'^j`, for `w'` of order `n << added_bits`, has `x^n = shift^n w''^j`,
+    // for `w''` of order `1 << added_bits`, so `Z` only takes that many values over the coset.
+    let zerofier_over_n = F::two_adic_generator(added_bits)
+        .shifted_powers(shift.exp_power_of_2(log_n))
+        .take(1 << added_bits)
+        .map(|x| (x - F::ONE).div_2exp_u64(log_n as u64))
+        .collect::<Vec<_>>();
+    let lagrange_0 = inv_denominators
+        .into_iter()
+        .enumerate()
+        .map(|(j, inv_denominator)| zerofier_over_n[j % zerofier_over_n.len()] * inv_denominator)
+        .collect::<Vec<_>>();
+
+    // As `w = w'^{1 << added_bits}`, multiplying by `w^{-r}` takes the `j`-th LDE point to the one
+    // `r << added_bits` before it, so `L_r` over the coset is `L_0` rotated by that many points.
+    lde.par_rows_mut().enumerate().for_each(|(j, row)| {
+        for &c in &direct_cols {
+            row[c] = selectors
+                .column_nonzeros(c)
+                .iter()
+                .map(|&(r, value)| {
+                    value * lagrange_0[j.wrapping_sub(r << added_bits) & (lde_height - 1)]
+                })
+                .sum();
+        }
+    });
+    lde
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::FieldAlgebra;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::Radix2Dit;
+
+    type F = BabyBear;
+
+    fn random_selectors(log_n: usize, nonzeros_per_column: &[usize]) -> SparseSelectorMatrix<F> {
+        let mut rng = thread_rng();
+        let columns = nonzeros_per_column
+            .iter()
+            .map(|&k| {
+                let mut rows = (0..k)
+                    .map(|_| rng.gen_range(0..1 << log_n))
+                    .collect::<Vec<_>>();
+                rows.sort_unstable();
+                rows.dedup();
+                rows.into_iter().map(|r| (r, rng.gen())).collect()
+            })
+            .collect();
+        SparseSelectorMatrix::new(1 << log_n, columns)
+    }
+
+    #[test]
+    fn sparse_lde_matches_dense() {
+        let dft = Radix2Dit::default();
+        for log_n in [0, 1, 4, 7] {
+            let selectors = random_selectors(log_n, &[0, 1, 3, 16, 20]);
+            for added_bits in [0, 1, 3] {
+                // The generator gives a disjoint coset; one is the subgroup itself, which has to go
+                // through the DFT.
+                for shift in [F::GENERATOR, F::ONE] {
+                    let dense = dft.coset_lde_batch(selectors.to_dense(), added_bits, shift);
+                    for max_direct_nonzeros in [MAX_DIRECT_LDE_NONZEROS, usize::MAX] {
+                        assert_eq!(
+                            coset_lde_sparse_batch_with_threshold(
+                                &dft,
+                                &selectors,
+                                added_bits,
+                                shift,
+                                max_direct_nonzeros,
+                            ),
+                            dense,
+                            "log_n {log_n}, added_bits {added_bits}, shift {shift}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,12 +1,22 @@
 use alloc::vec::Vec;
 
-use p3_field::TwoAdicField;
+use p3_field::{ExtensionField, TwoAdicField};
 use p3_matrix::bitrev::BitReversableMatrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::swap_rows;
-use p3_matrix::Matrix;
+use p3_matrix::{Dimensions, Matrix};
 
-use crate::util::{coset_shift_cols, divide_by_height};
+use crate::util::{coset_shift_cols, divide_by_height, flatten_to_base, reconstitute_from_base};
+
+/// The row order in which LDEs are written by methods like `coset_lde_batch_into`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LdeOrder {
+    /// Row `i` holds the evaluation at the `i`th point of the domain.
+    Natural,
+    /// Row `i` holds the evaluation at the `reverse_bits(i)`th point of the domain, the order in
+    /// which LDEs are committed. For circle domains, this is the cfft order instead.
+    BitReversed,
+}
 
 pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
     // Effectively this is either RowMajorMatrix or BitReversedMatrixView<RowMajorMatrix>.
@@ -81,6 +91,48 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
         mat
     }
 
+    /// Compute the DFT of each column in `mat`, a matrix of extension field elements, over a
+    /// subgroup of the base field. As the DFT is linear, this is the DFT of each coordinate.
+    fn dft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        let evals = self.dft_batch(flatten_to_base(mat)).to_row_major_matrix();
+        reconstitute_from_base(evals)
+    }
+
+    /// Compute the "coset DFT" of each column in `mat`, a matrix of extension field elements, over
+    /// a coset of a subgroup of the base field.
+    fn coset_dft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+        shift: F,
+    ) -> RowMajorMatrix<EF> {
+        let evals = self
+            .coset_dft_batch(flatten_to_base(mat), shift)
+            .to_row_major_matrix();
+        reconstitute_from_base(evals)
+    }
+
+    /// Compute the inverse DFT of each column in `mat`, a matrix of extension field elements, over
+    /// a subgroup of the base field.
+    fn idft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        reconstitute_from_base(self.idft_batch(flatten_to_base(mat)))
+    }
+
+    /// Compute the "coset iDFT" of each column in `mat`, a matrix of extension field elements,
+    /// over a coset of a subgroup of the base field.
+    fn coset_idft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+        shift: F,
+    ) -> RowMajorMatrix<EF> {
+        reconstitute_from_base(self.coset_idft_batch(flatten_to_base(mat), shift))
+    }
+
     /// Compute the low-degree extension of `vec` onto a larger subgroup.
     fn lde(&self, vec: Vec<F>, added_bits: usize) -> Vec<F> {
         self.lde_batch(RowMajorMatrix::new(vec, 1), added_bits)
@@ -123,4 +175,109 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
         );
         self.coset_dft_batch(coeffs, shift)
     }
+
+    /// Compute the low-degree extension of each column in `mat` onto a coset of a larger subgroup,
+    /// like `coset_lde_batch`, writing it into `out` with rows in the given order.
+    ///
+    /// `out` must already have the dimensions of the LDE. Its storage is reused for the LDE, so
+    /// no second buffer of that size is allocated, and with `LdeOrder::BitReversed` the result can
+    /// be committed without another permutation pass.
+    fn coset_lde_batch_into(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+        out: &mut RowMajorMatrix<F>,
+        order: LdeOrder,
+    ) {
+        let lde_dims = Dimensions {
+            width: mat.width(),
+            height: mat.height() << added_bits,
+        };
+        assert_eq!(
+            out.dimensions(),
+            lde_dims,
+            "the output matrix must have the dimensions of the LDE"
+        );
+
+        let coeffs = self.idft_batch(mat);
+        let mut values = core::mem::take(&mut out.values);
+        values.clear();
+        values.extend_from_slice(&coeffs.values);
+        drop(coeffs);
+        values.resize(lde_dims.width * lde_dims.height, F::ZERO);
+
+        let evals = self.coset_dft_batch(RowMajorMatrix::new(values, lde_dims.width), shift);
+        *out = match order {
+            LdeOrder::Natural => evals.to_row_major_matrix(),
+            LdeOrder::BitReversed => evals.bit_reverse_rows().to_row_major_matrix(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{Field, FieldAlgebra, FieldExtensionAlgebra, TwoAdicField};
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::thread_rng;
+
+    use crate::{NaiveDft, Radix2Dit, TwoAdicSubgroupDft};
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<BabyBear, 4>;
+
+    #[test]
+    fn dft_algebra_matches_naive() {
+        let mut rng = thread_rng();
+        let dft = Radix2Dit::<F>::default();
+        for log_h in 0..6 {
+            let mat = RowMajorMatrix::<EF>::rand(&mut rng, 1 << log_h, 3);
+            let shift = F::GENERATOR;
+            assert_eq!(
+                dft.dft_algebra_batch(mat.clone()),
+                <NaiveDft as TwoAdicSubgroupDft<F>>::dft_algebra_batch(&NaiveDft, mat.clone())
+            );
+            assert_eq!(
+                dft.coset_dft_algebra_batch(mat.clone(), shift),
+                NaiveDft.coset_dft_algebra_batch(mat, shift)
+            );
+        }
+    }
+
+    #[test]
+    fn dft_algebra_matches_evaluation() {
+        let mut rng = thread_rng();
+        let coeffs = RowMajorMatrix::<EF>::rand(&mut rng, 8, 1);
+        let shift = F::GENERATOR;
+        let evals = Radix2Dit::default().coset_dft_algebra_batch(coeffs.clone(), shift);
+
+        let g = F::two_adic_generator(3);
+        for (i, x) in g.shifted_powers(shift).take(8).enumerate() {
+            let expected: EF = coeffs
+                .values
+                .iter()
+                .zip(x.powers())
+                .map(|(&c, x_pow)| c * EF::from_base(x_pow))
+                .sum();
+            assert_eq!(evals.values[i], expected);
+        }
+    }
+
+    #[test]
+    fn idft_algebra_round_trip() {
+        let mut rng = thread_rng();
+        let dft = Radix2Dit::<F>::default();
+        for log_h in 0..8 {
+            let original = RowMajorMatrix::<EF>::rand(&mut rng, 1 << log_h, 3);
+            let shift = F::GENERATOR.square();
+
+            let evals = dft.dft_algebra_batch(original.clone());
+            assert_eq!(dft.idft_algebra_batch(evals), original);
+
+            let coset_evals = dft.coset_dft_algebra_batch(original.clone(), shift);
+            assert_eq!(dft.coset_idft_algebra_batch(coset_evals, shift), original);
+        }
+    }
 }
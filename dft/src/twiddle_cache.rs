@@ -0,0 +1,145 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::fmt::{Debug, Formatter};
+use core::hint::spin_loop;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use p3_field::TwoAdicField;
+use p3_util::linear_map::LinearMap;
+
+/// A cache of twiddle factors, keyed by `(log_n, shift)`, which can be shared across calls and
+/// threads. Clones share the same underlying cache.
+///
+/// Entries are handed out as `Arc<[F]>`, so cache hits never copy twiddles. As this crate is
+/// `no_std`, the map is guarded by a small spin lock rather than `std::sync::RwLock`. The lock is
+/// only held to look up or insert an entry; twiddles are computed without holding it, so if two
+/// threads miss at the same time, both compute the entry and the first to insert it wins.
+#[derive(Clone)]
+pub struct TwiddleCache<F> {
+    entries: Arc<SpinLock<BTreeMap<usize, LinearMap<F, Arc<[F]>>>>>,
+}
+
+impl<F> Default for TwiddleCache<F> {
+    fn default() -> Self {
+        Self {
+            entries: Arc::new(SpinLock::new(BTreeMap::new())),
+        }
+    }
+}
+
+impl<F> Debug for TwiddleCache<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TwiddleCache").finish_non_exhaustive()
+    }
+}
+
+impl<F: TwoAdicField> TwiddleCache<F> {
+    /// Returns `shift * g^i` for `i` in `0..2^log_n`, where `g` is the generator of the subgroup of
+    /// order `2^log_n`, computing and caching them if necessary.
+    pub fn get(&self, log_n: usize, shift: F) -> Arc<[F]> {
+        let cached = self.entries.with(|entries| {
+            entries
+                .get(&log_n)
+                .and_then(|by_shift| by_shift.get(&shift))
+                .cloned()
+        });
+        if let Some(twiddles) = cached {
+            return twiddles;
+        }
+
+        let root = F::two_adic_generator(log_n);
+        let twiddles: Arc<[F]> = root.shifted_powers(shift).take(1 << log_n).collect();
+        self.entries.with(|entries| {
+            entries
+                .entry(log_n)
+                .or_default()
+                .get_or_insert_with(shift, || twiddles)
+                .clone()
+        })
+    }
+}
+
+/// A minimal spin lock, sufficient for the short critical sections of `TwiddleCache`.
+struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: Access to `value` is serialized by `locked`.
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            spin_loop();
+        }
+        // Released on drop, so that a panic in `f` doesn't leave the lock held forever.
+        let _guard = SpinLockGuard(&self.locked);
+        // SAFETY: We hold the lock, so no one else has access to `value`.
+        f(unsafe { &mut *self.value.get() })
+    }
+}
+
+struct SpinLockGuard<'a>(&'a AtomicBool);
+
+impl Drop for SpinLockGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use alloc::sync::Arc;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::{Field, FieldAlgebra};
+
+    use super::{SpinLock, TwiddleCache};
+
+    type F = BabyBear;
+
+    #[test]
+    fn reuses_entries() {
+        let cache = TwiddleCache::<F>::default();
+        let a = cache.get(4, F::ONE);
+        let b = cache.clone().get(4, F::ONE);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(a.len(), 16);
+    }
+
+    #[test]
+    fn separates_shifts() {
+        let cache = TwiddleCache::<F>::default();
+        let unshifted = cache.get(4, F::ONE);
+        let shifted = cache.get(4, F::GENERATOR);
+        assert!(!Arc::ptr_eq(&unshifted, &shifted));
+        assert_eq!(shifted[0], F::GENERATOR);
+        assert_eq!(shifted[1], F::GENERATOR * unshifted[1]);
+        assert!(Arc::ptr_eq(&shifted, &cache.get(4, F::GENERATOR)));
+    }
+
+    #[test]
+    fn lock_is_released_after_panic() {
+        let lock = SpinLock::new(0);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            lock.with(|_| panic!("panic while holding the lock"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(lock.with(|value| *value), 0);
+    }
+}
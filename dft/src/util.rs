@@ -1,7 +1,8 @@
 use core::borrow::BorrowMut;
 
-use p3_field::Field;
+use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::{DenseMatrix, DenseStorage, RowMajorMatrix};
+use p3_matrix::extension::FlatMatrixView;
 use p3_matrix::Matrix;
 use tracing::instrument;
 
@@ -23,3 +24,24 @@ pub(crate) fn coset_shift_cols<F: Field>(mat: &mut RowMajorMatrix<F>, shift: F)
             })
         });
 }
+
+/// Flatten a matrix of extension field elements into one of their base field coordinates, which is
+/// `EF::D` times as wide.
+pub(crate) fn flatten_to_base<F: Field, EF: ExtensionField<F>>(
+    mat: RowMajorMatrix<EF>,
+) -> RowMajorMatrix<F> {
+    FlatMatrixView::<F, EF, _>::new(mat).to_row_major_matrix()
+}
+
+/// The inverse of `flatten_to_base`.
+pub(crate) fn reconstitute_from_base<F: Field, EF: ExtensionField<F>>(
+    mat: RowMajorMatrix<F>,
+) -> RowMajorMatrix<EF> {
+    let width = mat.width() / EF::D;
+    let values = mat
+        .values
+        .chunks_exact(EF::D)
+        .map(EF::from_base_slice)
+        .collect();
+    RowMajorMatrix::new(values, width)
+}
@@ -1,18 +1,17 @@
 use std::marker::PhantomData;
 
 use clap::Parser;
-use p3_baby_bear::{BabyBear, GenericPoseidon2LinearLayersBabyBear, Poseidon2BabyBear};
-use p3_blake3_air::Blake3Air;
-use p3_dft::Radix2DitParallel;
 use p3_examples::airs::ProofObjective;
 use p3_examples::dfts::DftChoice;
 use p3_examples::parsers::{DftOptions, FieldOptions, MerkleHashOptions, ProofOptions};
 use p3_examples::proofs::{prove_hashes_keccak, prove_hashes_poseidon2, report_result};
-use p3_field::extension::BinomialExtensionField;
-use p3_keccak_air::KeccakAir;
-use p3_koala_bear::{GenericPoseidon2LinearLayersKoalaBear, KoalaBear, Poseidon2KoalaBear};
-use p3_monty_31::dft::RecursiveDft;
-use p3_poseidon2_air::{RoundConstants, VectorizedPoseidon2Air};
+use p3_prelude::airs::{Blake3Air, KeccakAir, RoundConstants, VectorizedPoseidon2Air};
+use p3_prelude::baby_bear::{BabyBear, GenericPoseidon2LinearLayersBabyBear, Poseidon2BabyBear};
+use p3_prelude::dft::{Radix2DitParallel, RecursiveDft};
+use p3_prelude::field::BinomialExtensionField;
+use p3_prelude::koala_bear::{
+    GenericPoseidon2LinearLayersKoalaBear, KoalaBear, Poseidon2KoalaBear,
+};
 use rand::thread_rng;
 use tracing_forest::util::LevelFilter;
 use tracing_forest::ForestLayer;
@@ -0,0 +1,429 @@
+//! Prove a hash chain of Poseidon2 permutations, one per row, and print how long each phase of the
+//! prover took as a line of JSON.
+//!
+//! For example, `cargo run --release --example prove_poseidon2_chain -- --field koala-bear --log-rows 16`.
+//! The phase timings are read off the prover's tracing spans, so they cover the same work as the
+//! spans do: `lde` and `merkle_commit` split the trace and quotient commitments between the DFTs
+//! and the Merkle trees built from their output, while `fri` is the whole opening argument,
+//! including the Merkle trees of FRI's commit phase.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use clap::{Parser, ValueEnum};
+use p3_examples::airs::Poseidon2ChainAir;
+use p3_examples::dfts::DftChoice;
+use p3_examples::parsers::{DftOptions, FieldOptions, MerkleHashOptions};
+use p3_prelude::airs::RoundConstants;
+use p3_prelude::baby_bear::{BabyBear, GenericPoseidon2LinearLayersBabyBear, Poseidon2BabyBear};
+use p3_prelude::challenger::{DuplexChallenger, SerializingChallenger32};
+use p3_prelude::commit::{ExtensionMmcs, MerkleTreeMmcs};
+use p3_prelude::dft::{Radix2DitParallel, RecursiveDft, TwoAdicSubgroupDft};
+use p3_prelude::field::{BinomialExtensionField, ExtensionField, PrimeField32, TwoAdicField};
+use p3_prelude::fri::{create_benchmark_fri_config, TwoAdicFriPcs};
+use p3_prelude::keccak::{Keccak256Hash, KeccakF, VECTOR_LEN};
+use p3_prelude::koala_bear::{
+    GenericPoseidon2LinearLayersKoalaBear, KoalaBear, Poseidon2KoalaBear,
+};
+use p3_prelude::poseidon2::{ExternalLayerConstants, GenericPoseidon2LinearLayers};
+use p3_prelude::stark::{prove, verify, StarkConfig, SymbolicExpression};
+use p3_prelude::symmetric::{
+    CompressionFunctionFromHasher, CryptographicPermutation, PaddingFreeSponge,
+    SerializingHasher32To64, TruncatedPermutation,
+};
+use rand::distributions::Standard;
+use rand::prelude::Distribution;
+use rand::{thread_rng, Rng};
+use serde::Serialize;
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Registry;
+
+const WIDTH: usize = 16;
+const HALF_FULL_ROUNDS: usize = 4;
+
+// The prover's spans which the phases are made of.
+const COMMIT_TO_TRACE: &str = "commit to trace data";
+const COMMIT_TO_QUOTIENT: &str = "commit to quotient poly chunks";
+const COMPUTE_QUOTIENT: &str = "compute quotient polynomial";
+const OPEN: &str = "open";
+const BUILD_MERKLE_TREE: &str = "build merkle tree";
+const PHASES: [&str; 4] = [COMMIT_TO_TRACE, COMMIT_TO_QUOTIENT, COMPUTE_QUOTIENT, OPEN];
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// The field to use for our proof.
+    #[arg(short, long, ignore_case = true, value_enum)]
+    field: FieldOptions,
+
+    /// The log base 2 of the length of the chain, which is the number of rows of the trace.
+    #[arg(short, long)]
+    log_rows: u8,
+
+    /// The discrete fourier transform to use in the proof.
+    #[arg(
+        short,
+        long,
+        ignore_case = true,
+        value_enum,
+        default_value = "radix-2-dit-parallel"
+    )]
+    discrete_fourier_transform: DftOptions,
+
+    /// The hash function to use when assembling the Merkle tree.
+    #[arg(
+        short,
+        long,
+        ignore_case = true,
+        value_enum,
+        default_value = "poseidon-2"
+    )]
+    merkle_hash: MerkleHashOptions,
+}
+
+/// When a span was created, kept in its extensions until it closes.
+struct Start(Instant);
+
+/// A tracing layer adding up the time spent in each span, by the span's name and the name of the
+/// innermost of the `PHASES` spans it ran in.
+#[derive(Clone, Default)]
+struct PhaseTimer {
+    timings: Arc<Mutex<BTreeMap<(&'static str, Option<&'static str>), Duration>>>,
+}
+
+impl PhaseTimer {
+    fn get(&self, name: &'static str, phase: Option<&'static str>) -> Duration {
+        self.timings
+            .lock()
+            .unwrap()
+            .get(&(name, phase))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for PhaseTimer {
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Start(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(elapsed) = span.extensions().get::<Start>().map(|s| s.0.elapsed()) else {
+            return;
+        };
+        let phase = span
+            .scope()
+            .skip(1)
+            .map(|parent| parent.name())
+            .find(|name| PHASES.contains(name));
+        *self
+            .timings
+            .lock()
+            .unwrap()
+            .entry((span.name(), phase))
+            .or_default() += elapsed;
+    }
+}
+
+/// What is printed, with all times in milliseconds.
+#[derive(Serialize)]
+struct Report {
+    field: String,
+    log_rows: u8,
+    dft: String,
+    merkle_hash: String,
+    trace_generation_ms: f64,
+    lde_ms: f64,
+    merkle_commit_ms: f64,
+    quotient_ms: f64,
+    fri_ms: f64,
+    /// Trace generation and proving.
+    total_ms: f64,
+    verify_ms: f64,
+    proof_size_bytes: usize,
+}
+
+/// The times of a proof, as measured directly and by the timer.
+struct Timings {
+    trace_generation: Duration,
+    prove: Duration,
+    verify: Duration,
+    proof_size_bytes: usize,
+}
+
+impl Report {
+    fn new(args: &Args, timer: &PhaseTimer, timings: Timings) -> Self {
+        let commit = timer.get(COMMIT_TO_TRACE, None) + timer.get(COMMIT_TO_QUOTIENT, None);
+        let merkle_commit = timer.get(BUILD_MERKLE_TREE, Some(COMMIT_TO_TRACE))
+            + timer.get(BUILD_MERKLE_TREE, Some(COMMIT_TO_QUOTIENT));
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        Self {
+            field: name(args.field),
+            log_rows: args.log_rows,
+            dft: name(args.discrete_fourier_transform),
+            merkle_hash: name(args.merkle_hash),
+            trace_generation_ms: ms(timings.trace_generation),
+            lde_ms: ms(commit.saturating_sub(merkle_commit)),
+            merkle_commit_ms: ms(merkle_commit),
+            quotient_ms: ms(timer.get(COMPUTE_QUOTIENT, None)),
+            fri_ms: ms(timer.get(OPEN, None)),
+            total_ms: ms(timings.trace_generation + timings.prove),
+            verify_ms: ms(timings.verify),
+            proof_size_bytes: timings.proof_size_bytes,
+        }
+    }
+}
+
+fn name<T: ValueEnum>(value: T) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+/// Prove the chain using the Keccak hash function to build the merkle tree.
+fn prove_chain_keccak<
+    F: PrimeField32 + TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    DFT: TwoAdicSubgroupDft<F>,
+    LinearLayers: GenericPoseidon2LinearLayers<F, WIDTH>
+        + GenericPoseidon2LinearLayers<SymbolicExpression<F>, WIDTH>
+        + GenericPoseidon2LinearLayers<F::Packing, WIDTH>
+        + GenericPoseidon2LinearLayers<EF, WIDTH>,
+    Perm16: CryptographicPermutation<[F; WIDTH]>,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const PARTIAL_ROUNDS: usize,
+>(
+    air: &Poseidon2ChainAir<
+        F,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >,
+    perm16: &Perm16,
+    dft: DFT,
+    num_rows: usize,
+    _ef: PhantomData<EF>, // A simple workaround allowing the compiler to determine all generic parameters
+) -> Result<Timings, impl Debug>
+where
+    Standard: Distribution<F>,
+{
+    let byte_hash = Keccak256Hash {};
+    let u64_hash = PaddingFreeSponge::<KeccakF, 25, 17, 4>::new(KeccakF {});
+    let field_hash = SerializingHasher32To64::new(u64_hash);
+
+    let compress = CompressionFunctionFromHasher::<_, 2, 4>::new(u64_hash);
+
+    let val_mmcs =
+        MerkleTreeMmcs::<[F; VECTOR_LEN], [u64; VECTOR_LEN], _, _, 4>::new(field_hash, compress);
+
+    let challenge_mmcs = ExtensionMmcs::<F, EF, _>::new(val_mmcs.clone());
+
+    let fri_config = create_benchmark_fri_config(challenge_mmcs);
+
+    let pcs = TwoAdicFriPcs::new(dft, val_mmcs, fri_config);
+
+    let config = StarkConfig::new(pcs);
+
+    let start = Instant::now();
+    let trace = air.generate_trace_rows(perm16, thread_rng().gen(), num_rows);
+    let trace_generation = start.elapsed();
+
+    let mut proof_challenger = SerializingChallenger32::from_hasher(vec![], byte_hash);
+    let start = Instant::now();
+    let proof = prove(&config, air, &mut proof_challenger, trace, &vec![]).unwrap();
+    let prove_time = start.elapsed();
+
+    let mut verif_challenger = SerializingChallenger32::from_hasher(vec![], byte_hash);
+    let start = Instant::now();
+    verify(&config, air, &mut verif_challenger, &proof, &vec![])?;
+    Ok(Timings {
+        trace_generation,
+        prove: prove_time,
+        verify: start.elapsed(),
+        proof_size_bytes: postcard::to_allocvec(&proof).unwrap().len(),
+    })
+}
+
+/// Prove the chain using the Poseidon2 hash function to build the merkle tree.
+fn prove_chain_poseidon2<
+    F: PrimeField32 + TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    DFT: TwoAdicSubgroupDft<F>,
+    LinearLayers: GenericPoseidon2LinearLayers<F, WIDTH>
+        + GenericPoseidon2LinearLayers<SymbolicExpression<F>, WIDTH>
+        + GenericPoseidon2LinearLayers<F::Packing, WIDTH>
+        + GenericPoseidon2LinearLayers<EF, WIDTH>,
+    Perm16: CryptographicPermutation<[F; 16]> + CryptographicPermutation<[F::Packing; 16]>,
+    Perm24: CryptographicPermutation<[F; 24]> + CryptographicPermutation<[F::Packing; 24]>,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const PARTIAL_ROUNDS: usize,
+>(
+    air: &Poseidon2ChainAir<
+        F,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >,
+    perm16: &Perm16,
+    perm24: &Perm24,
+    dft: DFT,
+    num_rows: usize,
+    _ef: PhantomData<EF>, // A simple workaround allowing the compiler to determine all generic parameters
+) -> Result<Timings, impl Debug>
+where
+    Standard: Distribution<F>,
+{
+    let hash = PaddingFreeSponge::<_, 24, 16, 8>::new(perm24.clone());
+
+    let compress = TruncatedPermutation::new(perm16.clone());
+
+    let val_mmcs = MerkleTreeMmcs::<F::Packing, F::Packing, _, _, 8>::new(hash, compress);
+
+    let challenge_mmcs = ExtensionMmcs::<F, EF, _>::new(val_mmcs.clone());
+
+    let fri_config = create_benchmark_fri_config(challenge_mmcs);
+
+    let pcs = TwoAdicFriPcs::new(dft, val_mmcs, fri_config);
+
+    let config = StarkConfig::new(pcs);
+
+    let start = Instant::now();
+    let trace = air.generate_trace_rows(perm16, thread_rng().gen(), num_rows);
+    let trace_generation = start.elapsed();
+
+    let mut proof_challenger = DuplexChallenger::<_, _, 24, 16>::new(perm24.clone());
+    let start = Instant::now();
+    let proof = prove(&config, air, &mut proof_challenger, trace, &vec![]).unwrap();
+    let prove_time = start.elapsed();
+
+    let mut verif_challenger = DuplexChallenger::new(perm24.clone());
+    let start = Instant::now();
+    verify(&config, air, &mut verif_challenger, &proof, &vec![])?;
+    Ok(Timings {
+        trace_generation,
+        prove: prove_time,
+        verify: start.elapsed(),
+        proof_size_bytes: postcard::to_allocvec(&proof).unwrap().len(),
+    })
+}
+
+/// Random constants for the permutation in the chain.
+fn random_constants<F, const PARTIAL_ROUNDS: usize>() -> (ExternalLayerConstants<F, WIDTH>, Vec<F>)
+where
+    Standard: Distribution<F> + Distribution<[F; WIDTH]>,
+{
+    let mut rng = thread_rng();
+    let external = ExternalLayerConstants::new_from_rng(2 * HALF_FULL_ROUNDS, &mut rng);
+    let internal = (&mut rng)
+        .sample_iter(Standard)
+        .take(PARTIAL_ROUNDS)
+        .collect();
+    (external, internal)
+}
+
+fn main() {
+    let timer = PhaseTimer::default();
+    // The Merkle tree spans are at the debug level.
+    Registry::default()
+        .with(LevelFilter::DEBUG)
+        .with(timer.clone())
+        .init();
+
+    let args = Args::parse();
+    let num_rows = 1 << args.log_rows;
+
+    let dft = match args.discrete_fourier_transform {
+        DftOptions::RecursiveDft => DftChoice::Recursive(RecursiveDft::new(num_rows << 1)),
+        DftOptions::Radix2DitParallel => DftChoice::Parallel(Radix2DitParallel::default()),
+    };
+
+    let timings = match args.field {
+        FieldOptions::KoalaBear => {
+            type EF = BinomialExtensionField<KoalaBear, 4>;
+            const SBOX_DEGREE: u64 = 3;
+            const SBOX_REGISTERS: usize = 0;
+            const PARTIAL_ROUNDS: usize = 20;
+
+            let (external, internal) = random_constants::<KoalaBear, PARTIAL_ROUNDS>();
+            let air: Poseidon2ChainAir<
+                KoalaBear,
+                GenericPoseidon2LinearLayersKoalaBear,
+                WIDTH,
+                SBOX_DEGREE,
+                SBOX_REGISTERS,
+                HALF_FULL_ROUNDS,
+                PARTIAL_ROUNDS,
+            > = Poseidon2ChainAir::new(RoundConstants::from_poseidon2_constants(
+                &external, &internal,
+            ));
+            let perm16 = Poseidon2KoalaBear::<16>::new(external, internal);
+
+            match args.merkle_hash {
+                MerkleHashOptions::KeccakF => {
+                    prove_chain_keccak(&air, &perm16, dft, num_rows, PhantomData::<EF>)
+                        .map_err(|err| format!("{err:?}"))
+                }
+                MerkleHashOptions::Poseidon2 => {
+                    let perm24 = Poseidon2KoalaBear::<24>::new_from_rng_128(&mut thread_rng());
+                    prove_chain_poseidon2(&air, &perm16, &perm24, dft, num_rows, PhantomData::<EF>)
+                        .map_err(|err| format!("{err:?}"))
+                }
+            }
+        }
+        FieldOptions::BabyBear => {
+            type EF = BinomialExtensionField<BabyBear, 4>;
+            const SBOX_DEGREE: u64 = 7;
+            const SBOX_REGISTERS: usize = 1;
+            const PARTIAL_ROUNDS: usize = 13;
+
+            let (external, internal) = random_constants::<BabyBear, PARTIAL_ROUNDS>();
+            let air: Poseidon2ChainAir<
+                BabyBear,
+                GenericPoseidon2LinearLayersBabyBear,
+                WIDTH,
+                SBOX_DEGREE,
+                SBOX_REGISTERS,
+                HALF_FULL_ROUNDS,
+                PARTIAL_ROUNDS,
+            > = Poseidon2ChainAir::new(RoundConstants::from_poseidon2_constants(
+                &external, &internal,
+            ));
+            let perm16 = Poseidon2BabyBear::<16>::new(external, internal);
+
+            match args.merkle_hash {
+                MerkleHashOptions::KeccakF => {
+                    prove_chain_keccak(&air, &perm16, dft, num_rows, PhantomData::<EF>)
+                        .map_err(|err| format!("{err:?}"))
+                }
+                MerkleHashOptions::Poseidon2 => {
+                    let perm24 = Poseidon2BabyBear::<24>::new_from_rng_128(&mut thread_rng());
+                    prove_chain_poseidon2(&air, &perm16, &perm24, dft, num_rows, PhantomData::<EF>)
+                        .map_err(|err| format!("{err:?}"))
+                }
+            }
+        }
+    };
+
+    let timings = timings.unwrap_or_else(|err| panic!("The proof failed to verify: {err}"));
+    let report = Report::new(&args, &timer, timings);
+    println!("{}", serde_json::to_string(&report).unwrap());
+}
@@ -1,12 +1,18 @@
-use p3_air::{Air, AirBuilder, BaseAir};
-use p3_blake3_air::Blake3Air;
-use p3_field::{Field, PrimeField64};
-use p3_keccak_air::KeccakAir;
-use p3_matrix::dense::RowMajorMatrix;
-use p3_poseidon2::GenericPoseidon2LinearLayers;
-use p3_poseidon2_air::VectorizedPoseidon2Air;
+use core::borrow::Borrow;
+use core::iter;
+
+use p3_prelude::air::{Air, AirBuilder, BaseAir};
+use p3_prelude::airs::{
+    generate_poseidon2_trace_rows, Blake3Air, KeccakAir, Poseidon2Air, Poseidon2Cols,
+    RoundConstants, VectorizedPoseidon2Air,
+};
+use p3_prelude::field::{Field, PrimeField, PrimeField64};
+use p3_prelude::matrix::{Matrix, RowMajorMatrix};
+use p3_prelude::poseidon2::GenericPoseidon2LinearLayers;
+use p3_prelude::symmetric::Permutation;
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
+use rand::thread_rng;
 
 /// An enum containing the three different AIR's.
 ///
@@ -63,10 +69,13 @@ impl<
     where
         Standard: Distribution<F>,
     {
+        let rng = &mut thread_rng();
         match self {
-            ProofObjective::Blake3(b3_air) => b3_air.generate_trace_rows(num_hashes),
-            ProofObjective::Poseidon2(p2_air) => p2_air.generate_vectorized_trace_rows(num_hashes),
-            ProofObjective::Keccak(k_air) => k_air.generate_trace_rows(num_hashes),
+            ProofObjective::Blake3(b3_air) => b3_air.generate_trace_rows(num_hashes, rng),
+            ProofObjective::Poseidon2(p2_air) => {
+                p2_air.generate_vectorized_trace_rows(num_hashes, rng)
+            }
+            ProofObjective::Keccak(k_air) => k_air.generate_trace_rows(num_hashes, rng),
         }
     }
 }
@@ -132,3 +141,159 @@ impl<
         }
     }
 }
+
+/// A hash chain: one Poseidon2 permutation per row, each taking the previous row's output as its
+/// input.
+///
+/// This is the Poseidon2 AIR with one extra transition constraint per state element, tying the
+/// rows together so that the trace can't be split into independent permutations.
+pub struct Poseidon2ChainAir<
+    F: Field,
+    LinearLayers,
+    const WIDTH: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+> {
+    air: Poseidon2Air<
+        F,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >,
+    constants: RoundConstants<F, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
+}
+
+impl<
+        F: Field,
+        LinearLayers,
+        const WIDTH: usize,
+        const SBOX_DEGREE: u64,
+        const SBOX_REGISTERS: usize,
+        const HALF_FULL_ROUNDS: usize,
+        const PARTIAL_ROUNDS: usize,
+    >
+    Poseidon2ChainAir<
+        F,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >
+{
+    pub fn new(constants: RoundConstants<F, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>) -> Self {
+        Self {
+            air: Poseidon2Air::new(constants.clone()),
+            constants,
+        }
+    }
+
+    /// The trace of a chain of `num_rows` permutations starting from `input`.
+    ///
+    /// `perm` computes each row's input from the previous one, so it must be the permutation whose
+    /// constants the AIR was built from (see `RoundConstants::from_poseidon2_constants`).
+    pub fn generate_trace_rows<P: Permutation<[F; WIDTH]>>(
+        &self,
+        perm: &P,
+        input: [F; WIDTH],
+        num_rows: usize,
+    ) -> RowMajorMatrix<F>
+    where
+        F: PrimeField,
+        LinearLayers: GenericPoseidon2LinearLayers<F, WIDTH>,
+    {
+        let inputs = iter::successors(Some(input), |state| Some(perm.permute(*state)))
+            .take(num_rows)
+            .collect();
+        generate_poseidon2_trace_rows::<
+            F,
+            LinearLayers,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        >(inputs, &self.constants)
+    }
+}
+
+impl<
+        F: Field,
+        LinearLayers: Sync,
+        const WIDTH: usize,
+        const SBOX_DEGREE: u64,
+        const SBOX_REGISTERS: usize,
+        const HALF_FULL_ROUNDS: usize,
+        const PARTIAL_ROUNDS: usize,
+    > BaseAir<F>
+    for Poseidon2ChainAir<
+        F,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >
+{
+    #[inline]
+    fn width(&self) -> usize {
+        self.air.width()
+    }
+}
+
+impl<
+        AB: AirBuilder,
+        LinearLayers: GenericPoseidon2LinearLayers<AB::Expr, WIDTH>,
+        const WIDTH: usize,
+        const SBOX_DEGREE: u64,
+        const SBOX_REGISTERS: usize,
+        const HALF_FULL_ROUNDS: usize,
+        const PARTIAL_ROUNDS: usize,
+    > Air<AB>
+    for Poseidon2ChainAir<
+        AB::F,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >
+{
+    #[inline]
+    fn eval(&self, builder: &mut AB) {
+        self.air.eval(builder);
+
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let local: &Poseidon2Cols<
+            AB::Var,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        > = (*local).borrow();
+        let next: &Poseidon2Cols<
+            AB::Var,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        > = (*next).borrow();
+
+        let output = &local.ending_full_rounds[HALF_FULL_ROUNDS - 1].post;
+        let mut builder = builder.when_transition();
+        for (&next_input, &local_output) in next.inputs.iter().zip(output) {
+            builder.assert_eq(next_input, local_output);
+        }
+    }
+}
@@ -1,8 +1,6 @@
-use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
-use p3_field::TwoAdicField;
-use p3_matrix::bitrev::BitReversedMatrixView;
-use p3_matrix::dense::RowMajorMatrix;
-use p3_monty_31::dft::RecursiveDft;
+use p3_prelude::dft::{Radix2DitParallel, RecursiveDft, TwoAdicSubgroupDft};
+use p3_prelude::field::TwoAdicField;
+use p3_prelude::matrix::{BitReversedMatrixView, RowMajorMatrix};
 
 /// An enum containing several different options for discrete Fourier Transform.
 ///
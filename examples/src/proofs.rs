@@ -1,19 +1,18 @@
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
-use p3_challenger::{DuplexChallenger, SerializingChallenger32};
-use p3_commit::ExtensionMmcs;
-use p3_dft::TwoAdicSubgroupDft;
-use p3_field::{ExtensionField, PrimeField32, TwoAdicField};
-use p3_fri::{create_benchmark_fri_config, TwoAdicFriPcs};
-use p3_keccak::{Keccak256Hash, KeccakF};
-use p3_merkle_tree::MerkleTreeMmcs;
-use p3_poseidon2::GenericPoseidon2LinearLayers;
-use p3_symmetric::{
+use p3_prelude::challenger::{DuplexChallenger, SerializingChallenger32};
+use p3_prelude::commit::{ExtensionMmcs, MerkleTreeMmcs};
+use p3_prelude::dft::TwoAdicSubgroupDft;
+use p3_prelude::field::{ExtensionField, PrimeField32, TwoAdicField};
+use p3_prelude::fri::{create_benchmark_fri_config, TwoAdicFriPcs};
+use p3_prelude::keccak::{Keccak256Hash, KeccakF, VECTOR_LEN};
+use p3_prelude::poseidon2::GenericPoseidon2LinearLayers;
+use p3_prelude::stark::{prove, verify, StarkConfig, SymbolicExpression};
+use p3_prelude::symmetric::{
     CompressionFunctionFromHasher, CryptographicPermutation, PaddingFreeSponge,
     SerializingHasher32To64, TruncatedPermutation,
 };
-use p3_uni_stark::{prove, verify, StarkConfig, SymbolicExpression};
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
 
@@ -65,9 +64,7 @@ where
     let compress = CompressionFunctionFromHasher::<_, 2, 4>::new(u64_hash);
 
     let val_mmcs =
-        MerkleTreeMmcs::<[F; p3_keccak::VECTOR_LEN], [u64; p3_keccak::VECTOR_LEN], _, _, 4>::new(
-            field_hash, compress,
-        );
+        MerkleTreeMmcs::<[F; VECTOR_LEN], [u64; VECTOR_LEN], _, _, 4>::new(field_hash, compress);
 
     let challenge_mmcs = ExtensionMmcs::<F, EF, _>::new(val_mmcs.clone());
 
@@ -82,7 +79,7 @@ where
     let mut proof_challenger = SerializingChallenger32::from_hasher(vec![], byte_hash);
     let mut verif_challenger = SerializingChallenger32::from_hasher(vec![], byte_hash);
 
-    let proof = prove(&config, &proof_goal, &mut proof_challenger, trace, &vec![]);
+    let proof = prove(&config, &proof_goal, &mut proof_challenger, trace, &vec![]).unwrap();
     verify(&config, &proof_goal, &mut verif_challenger, &proof, &vec![])
 }
 
@@ -148,7 +145,7 @@ where
     let mut proof_challenger = DuplexChallenger::<_, _, 24, 16>::new(perm24.clone());
     let mut verif_challenger = DuplexChallenger::new(perm24.clone());
 
-    let proof = prove(&config, &proof_goal, &mut proof_challenger, trace, &vec![]);
+    let proof = prove(&config, &proof_goal, &mut proof_challenger, trace, &vec![]).unwrap();
     verify(&config, &proof_goal, &mut verif_challenger, &proof, &vec![])
 }
 
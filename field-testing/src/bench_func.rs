@@ -2,7 +2,7 @@ use alloc::format;
 use alloc::vec::Vec;
 
 use criterion::{black_box, BatchSize, Criterion};
-use p3_field::{Field, FieldAlgebra};
+use p3_field::{Field, FieldAlgebra, PackedField, PackedValue};
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
 use rand::Rng;
@@ -53,6 +53,43 @@ pub fn benchmark_iter_sum<F: Field, const N: usize, const REPS: usize>(
     });
 }
 
+/// Benchmark the time taken to sum a slice of N elements using `sum_slice`, against chaining
+/// additions.
+pub fn benchmark_sum_slice<FA: FieldAlgebra + Copy, const N: usize>(c: &mut Criterion, name: &str)
+where
+    Standard: Distribution<FA>,
+{
+    let mut rng = rand::thread_rng();
+    let input = (0..N).map(|_| rng.gen()).collect::<Vec<FA>>();
+    c.bench_function(&format!("{} sum_slice/{}", name, N), |b| {
+        b.iter(|| FA::sum_slice(black_box(&input)))
+    });
+    c.bench_function(&format!("{} chained add/{}", name, N), |b| {
+        b.iter(|| black_box(&input).iter().fold(FA::ZERO, |acc, &x| acc + x))
+    });
+}
+
+/// Benchmark the time taken to sum the lanes of a packed field element using `packed_sum`,
+/// against summing the extracted lanes.
+pub fn benchmark_packed_sum<PF: PackedField>(c: &mut Criterion, name: &str)
+where
+    Standard: Distribution<PF::Scalar>,
+{
+    let mut rng = rand::thread_rng();
+    let x = PF::from_fn(|_| rng.gen());
+    c.bench_function(&format!("{} packed_sum", name), |b| {
+        b.iter(|| black_box(x).packed_sum())
+    });
+    c.bench_function(&format!("{} lane by lane sum", name), |b| {
+        b.iter(|| {
+            black_box(x)
+                .as_slice()
+                .iter()
+                .fold(PF::Scalar::ZERO, |acc, &y| acc + y)
+        })
+    });
+}
+
 pub fn benchmark_add_latency<FA: FieldAlgebra + Copy, const N: usize>(c: &mut Criterion, name: &str)
 where
     Standard: Distribution<FA>,
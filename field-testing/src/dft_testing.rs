@@ -1,5 +1,6 @@
-use p3_dft::{NaiveDft, TwoAdicSubgroupDft};
+use p3_dft::{LdeOrder, NaiveDft, TwoAdicSubgroupDft};
 use p3_field::TwoAdicField;
+use p3_matrix::bitrev::BitReversableMatrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use rand::distributions::{Distribution, Standard};
@@ -110,6 +111,52 @@ where
     }
 }
 
+pub fn test_coset_lde_into_matches_coset_lde<F, Dft>()
+where
+    F: TwoAdicField,
+    Standard: Distribution<F>,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    let dft = Dft::default();
+    let mut rng = thread_rng();
+    for log_h in 0..5 {
+        let h = 1 << log_h;
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
+        let shift = F::GENERATOR;
+        let coset_lde = dft
+            .coset_lde_batch(mat.clone(), 2, shift)
+            .to_row_major_matrix();
+
+        let mut out = RowMajorMatrix::default(3, h << 2);
+        dft.coset_lde_batch_into(mat.clone(), 2, shift, &mut out, LdeOrder::Natural);
+        assert_eq!(out, coset_lde);
+
+        dft.coset_lde_batch_into(mat, 2, shift, &mut out, LdeOrder::BitReversed);
+        assert_eq!(
+            out,
+            coset_lde.clone().bit_reverse_rows().to_row_major_matrix()
+        );
+    }
+}
+
+pub fn test_coset_dft_idft_consistency<F, Dft>()
+where
+    F: TwoAdicField,
+    Standard: Distribution<F>,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    let dft = Dft::default();
+    let mut rng = thread_rng();
+    for log_h in 0..12 {
+        let h = 1 << log_h;
+        let original = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
+        let shift = F::GENERATOR.exp_u64(3);
+        let dft_output = dft.coset_dft_batch(original.clone(), shift);
+        let idft_output = dft.coset_idft_batch(dft_output.to_row_major_matrix(), shift);
+        assert_eq!(original, idft_output);
+    }
+}
+
 pub fn test_dft_idft_consistency<F, Dft>()
 where
     F: TwoAdicField,
@@ -161,10 +208,20 @@ macro_rules! test_field_dft {
                 $crate::test_coset_lde_matches_naive::<$field, $dft>();
             }
 
+            #[test]
+            fn coset_lde_into_matches_coset_lde() {
+                $crate::test_coset_lde_into_matches_coset_lde::<$field, $dft>();
+            }
+
             #[test]
             fn dft_idft_consistency() {
                 $crate::test_dft_idft_consistency::<$field, $dft>();
             }
+
+            #[test]
+            fn coset_dft_idft_consistency() {
+                $crate::test_coset_dft_idft_consistency::<$field, $dft>();
+            }
         }
     };
 }
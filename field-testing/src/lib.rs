@@ -8,13 +8,18 @@ pub mod bench_func;
 pub mod dft_testing;
 pub mod packedfield_testing;
 
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::hash::{Hash, Hasher};
+
 pub use bench_func::*;
 pub use dft_testing::*;
 use num_bigint::BigUint;
 use num_traits::identities::One;
 use p3_field::{
     cyclic_subgroup_coset_known_order, cyclic_subgroup_known_order, two_adic_coset_zerofier,
-    two_adic_subgroup_zerofier, ExtensionField, Field, TwoAdicField,
+    two_adic_subgroup_zerofier, ExtensionField, Field, PackedValue, PrimeField64, TwoAdicField,
+    TwoAdicityExceeded,
 };
 pub use packedfield_testing::*;
 use rand::distributions::{Distribution, Standard};
@@ -86,6 +91,55 @@ where
     }
 }
 
+/// FNV-1a, as a hasher which is deterministic, unlike `std`'s, and available without `std`.
+struct FnvHasher(u64);
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+}
+
+fn hash_of<T: Hash>(x: &T) -> u64 {
+    let mut hasher = FnvHasher(0xcbf2_9ce4_8422_2325);
+    x.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn test_hash_matches_eq<F: Field>()
+where
+    Standard: Distribution<F>,
+{
+    let mut rng = rand::thread_rng();
+    let x = rng.gen::<F>();
+    let y = rng.gen::<F>();
+    // Equal elements reached in different ways, which may leave them with different internal
+    // representations.
+    let pairs = [
+        (F::ZERO, F::ONE + F::NEG_ONE),
+        (F::ZERO, x - x),
+        (F::ONE, F::NEG_ONE * F::NEG_ONE),
+        (-F::TWO, F::NEG_ONE + F::NEG_ONE),
+        (
+            F::from_canonical_u32(1000),
+            -F::sum_slice(&[F::NEG_ONE; 1000]),
+        ),
+        (x, (x + y) - y),
+        (x, x.halve().double()),
+        (x * y, y * x),
+    ];
+    for (a, b) in pairs {
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b), "{a:?} and {b:?} hash differently");
+    }
+}
+
 pub fn test_multiplicative_group_factors<F: Field>() {
     let product: BigUint = F::multiplicative_group_factors()
         .into_iter()
@@ -94,6 +148,10 @@ pub fn test_multiplicative_group_factors<F: Field>() {
     assert_eq!(product + BigUint::one(), F::order());
 }
 
+pub fn test_bits<F: Field>() {
+    assert_eq!(F::BITS, F::bits());
+}
+
 pub fn test_two_adic_subgroup_zerofier<F: TwoAdicField>() {
     for log_n in 0..5 {
         let g = F::two_adic_generator(log_n);
@@ -123,6 +181,46 @@ pub fn test_two_adic_generator_consistency<F: TwoAdicField>() {
     }
 }
 
+pub fn test_try_two_adic_generator<F: TwoAdicField>() {
+    for bits in 0..=F::TWO_ADICITY {
+        assert_eq!(
+            F::try_two_adic_generator(bits),
+            Ok(F::two_adic_generator(bits))
+        );
+    }
+    for bits in 1..=F::TWO_ADICITY {
+        assert_eq!(
+            F::two_adic_generator(bits).square(),
+            F::two_adic_generator(bits - 1)
+        );
+    }
+    assert_eq!(
+        F::try_two_adic_generator(F::TWO_ADICITY + 1),
+        Err(TwoAdicityExceeded {
+            bits: F::TWO_ADICITY + 1,
+            two_adicity: F::TWO_ADICITY,
+        })
+    );
+}
+
+/// The largest generator has order exactly `2^TWO_ADICITY`: it isn't a root of unity of any smaller
+/// power of two order, which the squaring chain down to `two_adic_generator(0)` wouldn't catch.
+pub fn test_two_adic_generator_order<F: TwoAdicField>() {
+    let g = F::two_adic_generator(F::TWO_ADICITY);
+    if F::TWO_ADICITY > 0 {
+        assert_eq!(g.exp_power_of_2(F::TWO_ADICITY - 1), F::NEG_ONE);
+    }
+    assert_eq!(g.exp_power_of_2(F::TWO_ADICITY), F::ONE);
+}
+
+/// An extension's `TWO_ADICITY` is the full 2-adic valuation of its multiplicative group's order,
+/// which may be more than the base field's.
+pub fn test_ef_two_adicity_is_maximal<F: TwoAdicField, EF: TwoAdicField + ExtensionField<F>>() {
+    let group_order = EF::order() - BigUint::one();
+    assert_eq!(group_order.trailing_zeros(), Some(EF::TWO_ADICITY as u64));
+    assert!(EF::TWO_ADICITY >= F::TWO_ADICITY);
+}
+
 pub fn test_ef_two_adic_generator_consistency<
     F: TwoAdicField,
     EF: TwoAdicField + ExtensionField<F>,
@@ -133,6 +231,202 @@ pub fn test_ef_two_adic_generator_consistency<
     );
 }
 
+pub fn test_ef_order_bits<F: Field, EF: ExtensionField<F>>() {
+    assert_eq!(EF::EXT_ORDER_BITS, EF::order().bits() as usize);
+    assert!(EF::EXT_ORDER_BITS >= F::BITS);
+}
+
+pub fn test_sum_slice<F: Field>()
+where
+    Standard: Distribution<F>,
+{
+    let mut rng = rand::thread_rng();
+    for len in [0, 1, 2, 7, 100, 1000] {
+        let xs = (0..len).map(|_| rng.gen()).collect::<Vec<F>>();
+        let expected = xs.iter().fold(F::ZERO, |acc, &x| acc + x);
+        assert_eq!(F::sum_slice(&xs), expected, "sum of {len} elements");
+    }
+
+    // Many copies of the largest element, so that any deferred reduction has to carry.
+    let xs = vec![F::NEG_ONE; 1000];
+    assert_eq!(F::sum_slice(&xs), -F::from_canonical_u32(1000));
+}
+
+pub fn test_exp_const_u64<F: Field>()
+where
+    Standard: Distribution<F>,
+{
+    let mut rng = rand::thread_rng();
+    for _ in 0..10 {
+        let x = rng.gen::<F>();
+        assert_eq!(x.exp_const_u64::<0>(), x.exp_u64(0));
+        assert_eq!(x.exp_const_u64::<1>(), x.exp_u64(1));
+        assert_eq!(x.exp_const_u64::<2>(), x.exp_u64(2));
+        assert_eq!(x.exp_const_u64::<3>(), x.exp_u64(3));
+        assert_eq!(x.exp_const_u64::<4>(), x.exp_u64(4));
+        assert_eq!(x.exp_const_u64::<5>(), x.exp_u64(5));
+        assert_eq!(x.exp_const_u64::<6>(), x.exp_u64(6));
+        assert_eq!(x.exp_const_u64::<7>(), x.exp_u64(7));
+        assert_eq!(x.exp_const_u64::<11>(), x.exp_u64(11));
+    }
+}
+
+pub fn test_from_uniform_bytes<F: PrimeField64>() {
+    let bytes_of = |n: u128| {
+        let mut bytes = [0; 24];
+        bytes[..16].copy_from_slice(&n.to_le_bytes());
+        bytes
+    };
+    let order = F::ORDER_U64 as u128;
+    assert_eq!(F::from_uniform_bytes(&[0; 24]), F::ZERO);
+    assert_eq!(F::from_uniform_bytes(&bytes_of(order)), F::ZERO);
+    assert_eq!(F::from_uniform_bytes(&bytes_of(order + 1)), F::ONE);
+    assert_eq!(
+        F::from_uniform_bytes(&bytes_of(u128::MAX)),
+        F::from_canonical_u64((u128::MAX % order) as u64)
+    );
+    let mut top = [0; 24];
+    top[16] = 1;
+    assert_eq!(
+        F::from_uniform_bytes(&top),
+        F::TWO.exp_u64(128),
+        "the last 8 bytes are the most significant"
+    );
+
+    // A chi-squared test over 16 equal ranges of the field. With 15 degrees of freedom, a
+    // uniform sampler exceeds 70 with probability below 10^-8.
+    const BUCKETS: usize = 16;
+    const PER_BUCKET: usize = 1000;
+    let mut rng = rand::thread_rng();
+    let mut counts = [0usize; BUCKETS];
+    for _ in 0..BUCKETS * PER_BUCKET {
+        let x = F::from_uniform_bytes(&rng.gen());
+        counts[(x.as_canonical_u64() as u128 * BUCKETS as u128 / order) as usize] += 1;
+    }
+    let chi_squared = counts
+        .iter()
+        .map(|&count| (count.abs_diff(PER_BUCKET) as f64).powi(2) / PER_BUCKET as f64)
+        .sum::<f64>();
+    assert!(chi_squared < 70.0, "chi-squared of {chi_squared}");
+}
+
+pub fn test_from_wrapped_u64<F: PrimeField64>() {
+    let p = F::ORDER_U64;
+    let inputs = [
+        0,
+        1,
+        p - 1,
+        p,
+        p + 1,
+        u32::MAX as u64,
+        1 << 32,
+        p << 32,
+        (p << 32) - 1,
+        0x0123_4567_89ab_cdef,
+        u64::MAX - p,
+        u64::MAX,
+    ];
+    for x in inputs {
+        let expected = (x as u128 % p as u128) as u64;
+        assert_eq!(F::from_wrapped_u64(x).as_canonical_u64(), expected);
+    }
+}
+
+pub fn test_ord_hash_and_debug_are_canonical<F: PrimeField64>()
+where
+    Standard: Distribution<F>,
+{
+    let wrapped = F::from_wrapped_u64(F::ORDER_U64 + 5);
+    assert_eq!(wrapped, F::from_canonical_u8(5));
+    assert_eq!(hash_of(&wrapped), hash_of(&F::from_canonical_u8(5)));
+
+    let mut rng = rand::thread_rng();
+    let mut xs = (0..100)
+        .map(|_| rng.gen::<F>())
+        .chain([F::ZERO, F::ONE + F::NEG_ONE, F::NEG_ONE, wrapped])
+        .collect::<Vec<_>>();
+    let mut canonical = xs.iter().map(|x| x.as_canonical_u64()).collect::<Vec<_>>();
+    xs.sort();
+    canonical.sort();
+    assert_eq!(
+        xs.iter().map(|x| x.as_canonical_u64()).collect::<Vec<_>>(),
+        canonical
+    );
+
+    for x in xs {
+        assert_eq!(format!("{x:?}"), format!("{}", x.as_canonical_u64()));
+        assert_eq!(format!("{x}"), format!("{}", x.as_canonical_u64()));
+    }
+}
+
+pub fn test_powers<F: Field>()
+where
+    Standard: Distribution<F>,
+{
+    let mut rng = rand::thread_rng();
+    let base = rng.gen::<F>();
+    let shift = rng.gen::<F>();
+    let sequential = base.shifted_powers(shift).take(100).collect::<Vec<_>>();
+
+    for k in [0, 1, 2, 37, 99] {
+        assert_eq!(
+            base.shifted_powers(shift).nth_power(k as u64),
+            sequential[k]
+        );
+        assert_eq!(base.shifted_powers(shift).nth(k), Some(sequential[k]));
+        let mut powers = base.shifted_powers(shift);
+        powers.advance_by(k as u64);
+        assert_eq!(powers.take(100 - k).collect::<Vec<_>>(), &sequential[k..]);
+    }
+
+    let width = F::Packing::WIDTH;
+    for n in [0, 1, width - 1, width, width + 1, 3 * width + 2, 99] {
+        assert_eq!(base.shifted_powers(shift).collect_n(n), &sequential[..n]);
+    }
+    let n = 5000;
+    assert_eq!(
+        base.shifted_powers(shift).collect_n(n),
+        base.shifted_powers(shift).take(n).collect::<Vec<_>>()
+    );
+
+    for stride in [1, 3, 8] {
+        let strided = base.shifted_powers(shift).strided(stride);
+        let starts = strided
+            .clone()
+            .take(100 / stride as usize)
+            .collect::<Vec<_>>();
+        for (i, start) in starts.into_iter().enumerate() {
+            let chunk_start = i * stride as usize;
+            assert_eq!(start, sequential[chunk_start]);
+            let chunk = strided
+                .chunk(i as u64)
+                .take(stride as usize)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                chunk,
+                &sequential[chunk_start..chunk_start + stride as usize]
+            );
+        }
+    }
+
+    // Jumping past 2^32 agrees with stepping sequentially from just below it.
+    let below = (1 << 32) - 3;
+    let mut powers = base.shifted_powers(shift);
+    powers.advance_by(below);
+    let stepped = powers.take(6).collect::<Vec<_>>();
+    for (i, power) in stepped.into_iter().enumerate() {
+        assert_eq!(power, shift * base.exp_u64(below + i as u64));
+        assert_eq!(
+            base.shifted_powers(shift).nth_power(below + i as u64),
+            power
+        );
+    }
+    assert_eq!(
+        base.powers().strided(1 << 16).chunk(1 << 16).next(),
+        Some(base.exp_u64(1 << 32))
+    );
+}
+
 #[macro_export]
 macro_rules! test_field {
     ($field:ty) => {
@@ -153,6 +447,46 @@ macro_rules! test_field {
             fn test_multiplicative_group_factors() {
                 $crate::test_multiplicative_group_factors::<$field>();
             }
+            #[test]
+            fn test_bits() {
+                $crate::test_bits::<$field>();
+            }
+            #[test]
+            fn test_exp_const_u64() {
+                $crate::test_exp_const_u64::<$field>();
+            }
+            #[test]
+            fn test_powers() {
+                $crate::test_powers::<$field>();
+            }
+            #[test]
+            fn test_sum_slice() {
+                $crate::test_sum_slice::<$field>();
+            }
+            #[test]
+            fn test_hash_matches_eq() {
+                $crate::test_hash_matches_eq::<$field>();
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! test_prime_field_64 {
+    ($field:ty) => {
+        mod prime_field_64_tests {
+            #[test]
+            fn test_from_uniform_bytes() {
+                $crate::test_from_uniform_bytes::<$field>();
+            }
+            #[test]
+            fn test_from_wrapped_u64() {
+                $crate::test_from_wrapped_u64::<$field>();
+            }
+            #[test]
+            fn test_ord_hash_and_debug_are_canonical() {
+                $crate::test_ord_hash_and_debug_are_canonical::<$field>();
+            }
         }
     };
 }
@@ -173,6 +507,14 @@ macro_rules! test_two_adic_field {
             fn test_two_adic_consisitency() {
                 $crate::test_two_adic_generator_consistency::<$field>();
             }
+            #[test]
+            fn test_try_two_adic_generator() {
+                $crate::test_try_two_adic_generator::<$field>();
+            }
+            #[test]
+            fn test_two_adic_generator_order() {
+                $crate::test_two_adic_generator_order::<$field>();
+            }
         }
     };
 }
@@ -190,6 +532,16 @@ macro_rules! test_two_adic_extension_field {
             fn test_ef_two_adic_generator_consistency() {
                 $crate::test_ef_two_adic_generator_consistency::<$field, $ef>();
             }
+
+            #[test]
+            fn test_ef_order_bits() {
+                $crate::test_ef_order_bits::<$field, $ef>();
+            }
+
+            #[test]
+            fn test_ef_two_adicity_is_maximal() {
+                $crate::test_ef_two_adicity_is_maximal::<$field, $ef>();
+            }
         }
     };
 }
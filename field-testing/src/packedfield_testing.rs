@@ -1,7 +1,8 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::mem::{size_of, transmute_copy};
 
-use p3_field::{Field, FieldAlgebra, PackedField, PackedFieldPow2, PackedValue};
+use p3_field::{Field, FieldAlgebra, PackedField, PackedFieldPow2, PackedValue, PrimeField32};
 use rand::distributions::{Distribution, Standard};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
@@ -434,6 +435,75 @@ where
     );
 }
 
+pub fn test_packed_sum<PF>()
+where
+    PF: PackedField + Eq,
+    Standard: Distribution<PF::Scalar>,
+{
+    let vec: PF = packed_from_random(0x6a2f1c0e8d4b3957);
+    let expected = vec
+        .as_slice()
+        .iter()
+        .fold(PF::Scalar::ZERO, |acc, &x| acc + x);
+    assert_eq!(vec.packed_sum(), expected, "Error when testing packed_sum.");
+    assert_eq!(
+        PF::from(PF::Scalar::NEG_ONE).packed_sum(),
+        -PF::Scalar::from_canonical_usize(PF::WIDTH)
+    );
+
+    for (len, seed) in [
+        (0, 0x19a8f3e7c2d04b61),
+        (1, 0x7e03b5d29c4f8a16),
+        (2, 0x42c7e91d0b6f3a85),
+        (37, 0xd5b20a6e4f7c1983),
+    ] {
+        let xs = (0..len)
+            .map(|i| packed_from_random::<PF>(seed + i))
+            .collect::<Vec<_>>();
+        let expected = xs.iter().fold(PF::ZERO, |acc, &x| acc + x);
+        assert_eq!(
+            PF::sum_slice(&xs),
+            expected,
+            "Error when testing sum_slice of {} vectors.",
+            len
+        );
+    }
+}
+
+/// Check that `packed`, a map on vectors of `WIDTH` lanes, agrees with `scalar` in every lane.
+///
+/// The inputs are the vectors of the representations `0`, `1` and `P - 1` followed by random
+/// ones, all in `[0, P)`. Outputs are reduced from `(-P, P]`, read as `i32`s, as vectorized maps
+/// used inside larger computations often leave them there.
+///
+/// # Safety
+///
+/// `F` must have the layout of its representation as a `u32`, and `V` that of `[u32; WIDTH]`. Any
+/// such value of `V` must be a valid input of `packed`.
+pub unsafe fn check_packed_agreement<F, V, const WIDTH: usize>(
+    packed: impl Fn(V) -> V,
+    scalar: impl Fn(F) -> F,
+) where
+    F: PrimeField32,
+    V: Copy,
+{
+    assert_eq!(size_of::<F>(), size_of::<u32>());
+    assert_eq!(size_of::<V>(), size_of::<[u32; WIDTH]>());
+
+    let mut rng = rand::thread_rng();
+    let mut values = vec![0, 1, F::ORDER_U32 - 1];
+    values.extend((3..WIDTH * 128).map(|_| rng.gen_range(0..F::ORDER_U32)));
+    for input in values.chunks_exact(WIDTH) {
+        let input: [u32; WIDTH] = input.try_into().unwrap();
+        let output: [i32; WIDTH] = transmute_copy(&packed(transmute_copy(&input)));
+        for (x, y) in input.into_iter().zip(output) {
+            let y = y.rem_euclid(F::ORDER_U32 as i32) as u32;
+            let (x, y) = (transmute_copy::<u32, F>(&x), transmute_copy::<u32, F>(&y));
+            assert_eq!(y, scalar(x), "disagreement at input {x}");
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! test_packed_field {
     ($packedfield:ty, $zeros:expr, $specials:expr) => {
@@ -464,6 +534,10 @@ macro_rules! test_packed_field {
             fn test_multiplicative_inverse() {
                 $crate::test_multiplicative_inverse::<$packedfield>();
             }
+            #[test]
+            fn test_packed_sum() {
+                $crate::test_packed_sum::<$packedfield>();
+            }
         }
     };
 }
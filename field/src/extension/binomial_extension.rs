@@ -9,6 +9,7 @@ use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAss
 use itertools::Itertools;
 use num_bigint::BigUint;
 use p3_util::convert_vec;
+use p3_util::encoding::{CanonicalEncoding, DecodingError};
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
 use serde::{Deserialize, Serialize};
@@ -17,10 +18,11 @@ use super::{HasFrobenius, HasTwoAdicBinomialExtension};
 use crate::extension::BinomiallyExtendable;
 use crate::field::Field;
 use crate::{
-    field_to_array, ExtensionField, FieldAlgebra, FieldExtensionAlgebra, Packable, TwoAdicField,
+    assert_two_adicity, field_to_array, ExtensionField, FieldAlgebra, FieldExtensionAlgebra,
+    Packable, TwoAdicField,
 };
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize, PartialOrd, Ord)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
 #[repr(transparent)] // to make the zero_vec implementation safe
 pub struct BinomialExtensionField<FA, const D: usize> {
     #[serde(
@@ -30,6 +32,25 @@ pub struct BinomialExtensionField<FA, const D: usize> {
     pub(crate) value: [FA; D],
 }
 
+impl<FA: CanonicalEncoding, const D: usize> CanonicalEncoding for BinomialExtensionField<FA, D> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.value.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            value: <[FA; D]>::decode(input)?,
+        })
+    }
+}
+
+/// Formats the list of coefficients, lowest degree first.
+impl<FA: Debug, const D: usize> Debug for BinomialExtensionField<FA, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.value, f)
+    }
+}
+
 impl<FA: FieldAlgebra, const D: usize> Default for BinomialExtensionField<FA, D> {
     fn default() -> Self {
         Self {
@@ -216,6 +237,10 @@ impl<F: BinomiallyExtendable<D>, const D: usize> Field for BinomialExtensionFiel
         value: F::EXT_GENERATOR,
     };
 
+    // `|F|^D` has exactly `D * F::BITS` bits as long as `|F| >= 2^(F::BITS - 1/D)`, i.e. the order of
+    // `F` is close enough to the next power of two, which holds for all the fields we extend.
+    const BITS: usize = D * F::BITS;
+
     fn try_inverse(&self) -> Option<Self> {
         if self.is_zero() {
             return None;
@@ -415,10 +440,7 @@ where
         let w_af = FA::from_f(w);
 
         match D {
-            2 => {
-                res.value[0] = a[0].clone() * b[0].clone() + a[1].clone() * w_af * b[1].clone();
-                res.value[1] = a[0].clone() * b[1].clone() + a[1].clone() * b[0].clone();
-            }
+            2 => quadratic_mul(&a, &b, &mut res.value, w_af),
             3 => cubic_mul(&a, &b, &mut res.value, w_af),
             _ =>
             {
@@ -570,6 +592,7 @@ impl<F: Field + HasTwoAdicBinomialExtension<D>, const D: usize> TwoAdicField
 
     #[inline]
     fn two_adic_generator(bits: usize) -> Self {
+        assert_two_adicity::<Self>(bits);
         Self {
             value: F::ext_two_adic_generator(bits),
         }
@@ -604,6 +627,23 @@ fn cubic_inv<F: Field>(a: &[F], w: F) -> [F; 3] {
     ]
 }
 
+/// karatsuba multiplication for quadratic extension field
+#[inline]
+fn quadratic_mul<FA: FieldAlgebra, const D: usize>(
+    a: &[FA; D],
+    b: &[FA; D],
+    res: &mut [FA; D],
+    w: FA,
+) {
+    assert_eq!(D, 2);
+
+    let a0_b0 = a[0].clone() * b[0].clone();
+    let a1_b1 = a[1].clone() * b[1].clone();
+
+    res[0] = a0_b0.clone() + a1_b1.clone() * w;
+    res[1] = (a[0].clone() + a[1].clone()) * (b[0].clone() + b[1].clone()) - a0_b0 - a1_b1;
+}
+
 /// karatsuba multiplication for cubic extension field
 #[inline]
 fn cubic_mul<FA: FieldAlgebra, const D: usize>(a: &[FA; D], b: &[FA; D], res: &mut [FA; D], w: FA) {
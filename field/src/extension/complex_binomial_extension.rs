@@ -0,0 +1,583 @@
+use alloc::vec::Vec;
+use core::array;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use core::slice;
+
+use num_bigint::BigUint;
+use p3_util::convert_vec;
+use p3_util::encoding::{CanonicalEncoding, DecodingError};
+use rand::distributions::Standard;
+use rand::prelude::Distribution;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    BinomialExtensionField, Complex, HasComplexBinomialExtension, HasFrobenius,
+    HasTwoAdicComplexBinomialExtension,
+};
+use crate::field::Field;
+use crate::{ExtensionField, FieldAlgebra, FieldExtensionAlgebra, Packable, TwoAdicField};
+
+/// The binomial extension of degree `D` of `Complex<F>`, as an extension of degree `2 * D` of `F`.
+///
+/// This is the same field as `BinomialExtensionField<Complex<F>, D>`, which is only an extension of
+/// `Complex<F>`. A field with `p = 3 (mod 4)` has no binomial extensions of degree divisible by 4,
+/// so this is how its larger extensions can be used where an extension of `F` itself is expected,
+/// e.g. to draw the challenges of a PCS over `F` from.
+///
+/// The coefficients over `F` are the real and imaginary parts of the coefficients over
+/// `Complex<F>`, in order.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+#[serde(transparent)]
+#[repr(transparent)] // to make the zero_vec and as_base_slice implementations safe
+pub struct ComplexBinomialExtensionField<FA, const D: usize> {
+    value: BinomialExtensionField<Complex<FA>, D>,
+}
+
+impl<FA, const D: usize> ComplexBinomialExtensionField<FA, D> {
+    pub const fn from_complex_extension(value: BinomialExtensionField<Complex<FA>, D>) -> Self {
+        Self { value }
+    }
+
+    pub fn into_complex_extension(self) -> BinomialExtensionField<Complex<FA>, D> {
+        self.value
+    }
+}
+
+/// Formats the list of coefficients over `F`.
+impl<FA: Debug, const D: usize> Debug for ComplexBinomialExtensionField<FA, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.value.value.iter().flat_map(|c| &c.value))
+            .finish()
+    }
+}
+
+impl<FA: CanonicalEncoding, const D: usize> CanonicalEncoding
+    for ComplexBinomialExtensionField<FA, D>
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.value.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            value: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+impl<FA, const D: usize> Default for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<FA, const D: usize> From<FA> for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    fn from(x: FA) -> Self {
+        Self {
+            value: Complex::<FA>::from(x).into(),
+        }
+    }
+}
+
+impl<F: HasComplexBinomialExtension<D>, const D: usize> Packable
+    for ComplexBinomialExtensionField<F, D>
+{
+}
+
+impl<F: HasComplexBinomialExtension<D>, const D: usize> ExtensionField<F>
+    for ComplexBinomialExtensionField<F, D>
+{
+    type ExtensionPacking = ComplexBinomialExtensionField<F::Packing, D>;
+}
+
+impl<F: HasComplexBinomialExtension<D>, const D: usize> HasFrobenius<F>
+    for ComplexBinomialExtensionField<F, D>
+{
+    fn frobenius(&self) -> Self {
+        self.repeated_frobenius(1)
+    }
+
+    /// Repeated Frobenius automorphisms: x -> x^(n^count), where n is the order of `F`.
+    ///
+    /// As `n = 3 (mod 4)`, `x -> x^n` conjugates the coefficients over `Complex<F>`, and sends the
+    /// generator `X` of the extension over `Complex<F>` to `X^n`, which we compute once.
+    fn repeated_frobenius(&self, count: usize) -> Self {
+        let count = count % (2 * D);
+        if count == 0 {
+            return *self;
+        }
+
+        let order = F::order();
+        let x: BinomialExtensionField<Complex<F>, D> =
+            FieldExtensionAlgebra::<Complex<F>>::monomial(1);
+        let mut x_to_n = BinomialExtensionField::ONE;
+        for bit in (0..order.bits()).rev() {
+            x_to_n = x_to_n.square();
+            if order.bit(bit) {
+                x_to_n *= x;
+            }
+        }
+
+        let mut res = self.value;
+        for _ in 0..count {
+            res = res
+                .value
+                .iter()
+                .zip(x_to_n.powers())
+                .map(|(coeff, x_power)| x_power * coeff.conjugate())
+                .sum();
+        }
+        Self { value: res }
+    }
+
+    fn frobenius_inv(&self) -> Self {
+        self.inverse()
+    }
+}
+
+impl<FA, const D: usize> FieldAlgebra for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    type F = ComplexBinomialExtensionField<FA::F, D>;
+
+    const ZERO: Self = Self {
+        value: BinomialExtensionField::ZERO,
+    };
+
+    const ONE: Self = Self {
+        value: BinomialExtensionField::ONE,
+    };
+
+    const TWO: Self = Self {
+        value: BinomialExtensionField::TWO,
+    };
+
+    const NEG_ONE: Self = Self {
+        value: BinomialExtensionField::NEG_ONE,
+    };
+
+    #[inline]
+    fn from_f(f: Self::F) -> Self {
+        Self {
+            value: BinomialExtensionField::from_f(f.value),
+        }
+    }
+
+    #[inline]
+    fn from_bool(b: bool) -> Self {
+        FA::from_bool(b).into()
+    }
+
+    #[inline]
+    fn from_canonical_u8(n: u8) -> Self {
+        FA::from_canonical_u8(n).into()
+    }
+
+    #[inline]
+    fn from_canonical_u16(n: u16) -> Self {
+        FA::from_canonical_u16(n).into()
+    }
+
+    #[inline]
+    fn from_canonical_u32(n: u32) -> Self {
+        FA::from_canonical_u32(n).into()
+    }
+
+    #[inline]
+    fn from_canonical_u64(n: u64) -> Self {
+        FA::from_canonical_u64(n).into()
+    }
+
+    #[inline]
+    fn from_canonical_usize(n: usize) -> Self {
+        FA::from_canonical_usize(n).into()
+    }
+
+    #[inline]
+    fn from_wrapped_u32(n: u32) -> Self {
+        FA::from_wrapped_u32(n).into()
+    }
+
+    #[inline]
+    fn from_wrapped_u64(n: u64) -> Self {
+        FA::from_wrapped_u64(n).into()
+    }
+
+    #[inline]
+    fn zero_vec(len: usize) -> Vec<Self> {
+        // SAFETY: this is a repr(transparent) wrapper around a binomial extension element.
+        unsafe { convert_vec(BinomialExtensionField::<Complex<FA>, D>::zero_vec(len)) }
+    }
+}
+
+impl<F: HasComplexBinomialExtension<D>, const D: usize> Field
+    for ComplexBinomialExtensionField<F, D>
+{
+    type Packing = Self;
+
+    const GENERATOR: Self = Self {
+        value: BinomialExtensionField::GENERATOR,
+    };
+
+    const BITS: usize = 2 * D * F::BITS;
+
+    fn try_inverse(&self) -> Option<Self> {
+        self.value.try_inverse().map(|value| Self { value })
+    }
+
+    fn halve(&self) -> Self {
+        Self {
+            value: self.value.halve(),
+        }
+    }
+
+    fn order() -> BigUint {
+        F::order().pow(2 * D as u32)
+    }
+}
+
+impl<F: HasComplexBinomialExtension<D>, const D: usize> Display
+    for ComplexBinomialExtensionField<F, D>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.value, f)
+    }
+}
+
+impl<FA, const D: usize> Neg for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self { value: -self.value }
+    }
+}
+
+impl<FA, const D: usize> Add for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            value: self.value + rhs.value,
+        }
+    }
+}
+
+impl<FA, const D: usize> Add<FA> for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn add(mut self, rhs: FA) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl<FA, const D: usize> AddAssign for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.value += rhs.value;
+    }
+}
+
+impl<FA, const D: usize> AddAssign<FA> for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    #[inline]
+    fn add_assign(&mut self, rhs: FA) {
+        self.value.value[0] += rhs;
+    }
+}
+
+impl<FA, const D: usize> Sum for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<FA, const D: usize> Sub for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            value: self.value - rhs.value,
+        }
+    }
+}
+
+impl<FA, const D: usize> Sub<FA> for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn sub(mut self, rhs: FA) -> Self {
+        self -= rhs;
+        self
+    }
+}
+
+impl<FA, const D: usize> SubAssign for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        self.value -= rhs.value;
+    }
+}
+
+impl<FA, const D: usize> SubAssign<FA> for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    #[inline]
+    fn sub_assign(&mut self, rhs: FA) {
+        self.value.value[0] -= rhs;
+    }
+}
+
+impl<FA, const D: usize> Mul for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        match D {
+            4 => {
+                let w = Complex::<FA>::from_f(<FA::F as HasComplexBinomialExtension<D>>::W);
+                let product = quartic_mul(
+                    self.value.value.as_slice().try_into().unwrap(),
+                    rhs.value.value.as_slice().try_into().unwrap(),
+                    w,
+                );
+                let mut res = Self::ZERO;
+                res.value.value.clone_from_slice(&product);
+                res
+            }
+            _ => Self {
+                value: self.value * rhs.value,
+            },
+        }
+    }
+}
+
+impl<FA, const D: usize> Mul<FA> for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn mul(mut self, rhs: FA) -> Self {
+        self *= rhs;
+        self
+    }
+}
+
+impl<FA, const D: usize> Product for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<F: HasComplexBinomialExtension<D>, const D: usize> Div
+    for ComplexBinomialExtensionField<F, D>
+{
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    #[inline]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+impl<F: HasComplexBinomialExtension<D>, const D: usize> DivAssign
+    for ComplexBinomialExtensionField<F, D>
+{
+    #[inline]
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<FA, const D: usize> MulAssign for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = self.clone() * rhs;
+    }
+}
+
+impl<FA, const D: usize> MulAssign<FA> for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    #[inline]
+    fn mul_assign(&mut self, rhs: FA) {
+        for coeff in self.value.value.iter_mut() {
+            *coeff *= rhs.clone();
+        }
+    }
+}
+
+impl<FA, const D: usize> FieldExtensionAlgebra<FA> for ComplexBinomialExtensionField<FA, D>
+where
+    FA: FieldAlgebra,
+    FA::F: HasComplexBinomialExtension<D>,
+{
+    const D: usize = 2 * D;
+
+    #[inline]
+    fn from_base(b: FA) -> Self {
+        b.into()
+    }
+
+    #[inline]
+    fn from_base_slice(bs: &[FA]) -> Self {
+        Self::from_base_fn(|i| bs[i].clone())
+    }
+
+    #[inline]
+    fn from_base_fn<F: FnMut(usize) -> FA>(mut f: F) -> Self {
+        Self {
+            value: BinomialExtensionField::from_base_fn(|i| {
+                let real = f(2 * i);
+                Complex::new(real, f(2 * i + 1))
+            }),
+        }
+    }
+
+    #[inline]
+    fn from_base_iter<I: Iterator<Item = FA>>(iter: I) -> Self {
+        let mut res = Self::ZERO;
+        for (i, b) in iter.enumerate() {
+            res.value.value[i / 2].value[i % 2] = b;
+        }
+        res
+    }
+
+    #[inline(always)]
+    fn as_base_slice(&self) -> &[FA] {
+        // SAFETY: `BinomialExtensionField` is a repr(transparent) wrapper around an array, so this
+        // is an array of `D` arrays of 2 elements of `FA`.
+        unsafe { slice::from_raw_parts(self.value.value.as_ptr().cast::<FA>(), 2 * D) }
+    }
+}
+
+impl<F: HasComplexBinomialExtension<D>, const D: usize>
+    Distribution<ComplexBinomialExtensionField<F, D>> for Standard
+where
+    Standard: Distribution<F>,
+{
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> ComplexBinomialExtensionField<F, D> {
+        ComplexBinomialExtensionField {
+            value: Standard.sample(rng),
+        }
+    }
+}
+
+impl<F: HasTwoAdicComplexBinomialExtension<D>, const D: usize> TwoAdicField
+    for ComplexBinomialExtensionField<F, D>
+{
+    const TWO_ADICITY: usize = F::COMPLEX_EXT_TWO_ADICITY;
+
+    #[inline]
+    fn two_adic_generator(bits: usize) -> Self {
+        Self {
+            value: BinomialExtensionField::two_adic_generator(bits),
+        }
+    }
+}
+
+/// Karatsuba multiplication in `R[X]/(X^4 - w)`, applied twice: writing `a = a_lo + a_hi X^2` with
+/// `a_lo` and `a_hi` linear in `X`, and similarly for `b`, this takes 9 multiplications in `R`
+/// rather than 16.
+#[inline]
+fn quartic_mul<R: FieldAlgebra>(a: &[R; 4], b: &[R; 4], w: R) -> [R; 4] {
+    let linear_mul = |x0: R, x1: R, y0: R, y1: R| {
+        let low = x0.clone() * y0.clone();
+        let high = x1.clone() * y1.clone();
+        let mid = (x0 + x1) * (y0 + y1) - low.clone() - high.clone();
+        [low, mid, high]
+    };
+
+    let lo = linear_mul(a[0].clone(), a[1].clone(), b[0].clone(), b[1].clone());
+    let hi = linear_mul(a[2].clone(), a[3].clone(), b[2].clone(), b[3].clone());
+    let cross = linear_mul(
+        a[0].clone() + a[2].clone(),
+        a[1].clone() + a[3].clone(),
+        b[0].clone() + b[2].clone(),
+        b[1].clone() + b[3].clone(),
+    );
+    let mid: [R; 3] = array::from_fn(|i| cross[i].clone() - lo[i].clone() - hi[i].clone());
+
+    // a * b = lo + mid X^2 + hi X^4, with X^4 = w.
+    let [lo0, lo1, lo2] = lo;
+    let [mid0, mid1, mid2] = mid;
+    let [hi0, hi1, hi2] = hi;
+    [
+        lo0 + (hi0 + mid2) * w.clone(),
+        lo1 + hi1 * w.clone(),
+        lo2 + mid0 + hi2 * w,
+        mid1,
+    ]
+}
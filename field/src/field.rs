@@ -1,5 +1,6 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::any::type_name;
 use core::fmt::{Debug, Display};
 use core::hash::Hash;
 use core::iter::{Product, Sum};
@@ -247,6 +248,15 @@ pub trait FieldAlgebra:
         }
     }
 
+    /// Sum a slice of elements.
+    ///
+    /// Implementations may override this with a faster reduction than chaining additions, e.g. one
+    /// which defers the modular reduction to the end.
+    #[must_use]
+    fn sum_slice(xs: &[Self]) -> Self {
+        xs.iter().cloned().sum()
+    }
+
     /// Compute the dot product of two vectors.
     fn dot_product<const N: usize>(u: &[Self; N], v: &[Self; N]) -> Self {
         u.iter().zip(v).map(|(x, y)| x.clone() * y.clone()).sum()
@@ -285,6 +295,10 @@ pub trait Field:
     /// A generator of this field's entire multiplicative group.
     const GENERATOR: Self;
 
+    /// The number of bits in the order of this field, i.e. `Self::bits()` as a constant, so that
+    /// protocol requirements on field sizes can be checked at compile time.
+    const BITS: usize;
+
     fn is_zero(&self) -> bool {
         *self == Self::ZERO
     }
@@ -364,6 +378,25 @@ pub trait PrimeField64: PrimeField {
     /// Return the representative of `value` that is less than `ORDER_U64`.
     fn as_canonical_u64(&self) -> u64;
 
+    /// Convert from a `u64`, or return `None` if it isn't less than `ORDER_U64`.
+    fn try_from_canonical_u64(n: u64) -> Option<Self> {
+        (n < Self::ORDER_U64).then(|| Self::from_canonical_u64(n))
+    }
+
+    /// Map uniformly random bytes to a nearly uniform field element, by reducing them, read as a
+    /// little-endian integer, modulo `ORDER_U64`.
+    ///
+    /// Reducing a uniform integer below `2^192` leaves a statistical distance from uniform of less
+    /// than `ORDER_U64 / 2^192`, which is below `2^-128` for any field of this trait.
+    fn from_uniform_bytes(bytes: &[u8; 24]) -> Self {
+        let order = Self::ORDER_U64 as u128;
+        let reduced = bytes.chunks_exact(8).rev().fold(0, |acc, limb| {
+            let limb = u64::from_le_bytes(limb.try_into().unwrap());
+            ((((acc as u128) << 64) | limb as u128) % order) as u64
+        });
+        Self::from_canonical_u64(reduced)
+    }
+
     /// Convert a field element to a `u64` such that any two field elements
     /// are converted to the same `u64` if and only if they represent the same value.
     ///
@@ -382,6 +415,11 @@ pub trait PrimeField32: PrimeField64 {
     /// Return the representative of `value` that is less than `ORDER_U32`.
     fn as_canonical_u32(&self) -> u32;
 
+    /// Convert from a `u32`, or return `None` if it isn't less than `ORDER_U32`.
+    fn try_from_canonical_u32(n: u32) -> Option<Self> {
+        (n < Self::ORDER_U32).then(|| Self::from_canonical_u32(n))
+    }
+
     /// Convert a field element to a `u32` such that any two field elements
     /// are converted to the same `u32` if and only if they represent the same value.
     ///
@@ -474,6 +512,10 @@ pub trait ExtensionField<Base: Field>: Field + FieldExtensionAlgebra<Base> {
         + Send
         + Sync;
 
+    /// The number of bits in the order of this extension, e.g. for checking that it is large enough
+    /// to sample challenges from.
+    const EXT_ORDER_BITS: usize = Self::BITS;
+
     #[inline(always)]
     fn is_in_basefield(&self) -> bool {
         self.as_base_slice()[1..].iter().all(Field::is_zero)
@@ -546,9 +588,47 @@ pub trait TwoAdicField: Field {
     const TWO_ADICITY: usize;
 
     /// Returns a generator of the multiplicative group of order `2^bits`.
-    /// Assumes `bits <= TWO_ADICITY`, otherwise the result is undefined.
+    ///
+    /// # Panics
+    /// Panics if `bits > TWO_ADICITY`, as there is no such group. See
+    /// [`try_two_adic_generator`](Self::try_two_adic_generator) for a version which doesn't.
     #[must_use]
     fn two_adic_generator(bits: usize) -> Self;
+
+    /// Returns a generator of the multiplicative group of order `2^bits`, or an error if
+    /// `bits > TWO_ADICITY`.
+    fn try_two_adic_generator(bits: usize) -> Result<Self, TwoAdicityExceeded> {
+        if bits > Self::TWO_ADICITY {
+            return Err(TwoAdicityExceeded {
+                bits,
+                two_adicity: Self::TWO_ADICITY,
+            });
+        }
+        Ok(Self::two_adic_generator(bits))
+    }
+}
+
+/// A two-adic subgroup which is larger than the field's multiplicative group allows.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TwoAdicityExceeded {
+    /// The requested subgroup has order `2^bits`.
+    pub bits: usize,
+    /// The field's `TWO_ADICITY`.
+    pub two_adicity: usize,
+}
+
+/// Panics, naming the field and its two-adicity, unless `F` has a subgroup of order `2^bits`.
+///
+/// For use by implementations of [`TwoAdicField::two_adic_generator`].
+#[track_caller]
+#[inline]
+pub fn assert_two_adicity<F: TwoAdicField>(bits: usize) {
+    assert!(
+        bits <= F::TWO_ADICITY,
+        "{} has no subgroup of order 2^{bits}, as its two-adicity is {}",
+        type_name::<F>(),
+        F::TWO_ADICITY
+    );
 }
 
 /// An iterator which returns the powers of a base element `b` shifted by current `c`: `c, c * b, c * b^2, ...`.
@@ -558,6 +638,53 @@ pub struct Powers<F> {
     pub current: F,
 }
 
+impl<FA: FieldAlgebra> Powers<FA> {
+    /// Returns the `k`-th element of the remaining sequence, `c * b^k`, without advancing the iterator.
+    ///
+    /// This uses square-and-multiply, so it costs `O(log k)` multiplications.
+    #[must_use]
+    pub fn nth_power(&self, k: u64) -> FA {
+        self.current.clone() * self.base.exp_u64(k)
+    }
+
+    /// Skips the next `k` elements of the sequence, using `O(log k)` multiplications.
+    pub fn advance_by(&mut self, k: u64) {
+        self.current = self.nth_power(k);
+    }
+
+    /// Returns an iterator over the starting points of consecutive chunks of `stride` elements of
+    /// this sequence: `c, c * b^stride, c * b^(2 * stride), ...`.
+    ///
+    /// `base^stride` is computed once, so that the start of every chunk costs a single
+    /// multiplication, or `O(log i)` for random access to the `i`-th chunk.
+    #[must_use]
+    pub fn strided(self, stride: u64) -> StridedPowers<FA> {
+        StridedPowers {
+            stride_power: self.base.exp_u64(stride),
+            base: self.base,
+            current: self.current,
+        }
+    }
+}
+
+impl<F: Field> Powers<F> {
+    /// Collects the next `n` elements of the sequence, `c, c * b, ..., c * b^(n - 1)`.
+    ///
+    /// Equivalent to `self.take(n).collect()`, but powers are generated a packed vector at a time,
+    /// each from the last with one packed multiplication by `b^WIDTH`.
+    #[must_use]
+    pub fn collect_n(self, n: usize) -> Vec<F> {
+        let width = F::Packing::WIDTH;
+        let mut packed_powers = self.base.shifted_powers_packed::<F::Packing>(self.current);
+        let mut result = Vec::with_capacity(n);
+        for packed in packed_powers.by_ref().take(n / width) {
+            result.extend_from_slice(packed.as_slice());
+        }
+        result.extend_from_slice(&packed_powers.current.as_slice()[..n % width]);
+        result
+    }
+}
+
 impl<FA: FieldAlgebra> Iterator for Powers<FA> {
     type Item = FA;
 
@@ -566,4 +693,43 @@ impl<FA: FieldAlgebra> Iterator for Powers<FA> {
         self.current *= self.base.clone();
         Some(result)
     }
+
+    fn nth(&mut self, n: usize) -> Option<FA> {
+        self.advance_by(n as u64);
+        self.next()
+    }
+}
+
+/// An iterator over the starting points of fixed size chunks of a `Powers` sequence with base `b`
+/// and shift `c`: `c, c * b^stride, c * b^(2 * stride), ...`.
+///
+/// This is useful when chunks of a sequence of powers are processed in parallel, as each chunk can
+/// construct its own `Powers` iterator using [`StridedPowers::chunk`].
+#[derive(Clone, Debug)]
+pub struct StridedPowers<F> {
+    pub base: F,
+    pub stride_power: F,
+    pub current: F,
+}
+
+impl<FA: FieldAlgebra> StridedPowers<FA> {
+    /// Returns the powers making up the `i`-th chunk of the remaining sequence, without advancing the
+    /// iterator: `c * b^(i * stride), c * b^(i * stride + 1), ...`.
+    #[must_use]
+    pub fn chunk(&self, i: u64) -> Powers<FA> {
+        Powers {
+            base: self.base.clone(),
+            current: self.current.clone() * self.stride_power.exp_u64(i),
+        }
+    }
+}
+
+impl<FA: FieldAlgebra> Iterator for StridedPowers<FA> {
+    type Item = FA;
+
+    fn next(&mut self) -> Option<FA> {
+        let result = self.current.clone();
+        self.current *= self.stride_power.clone();
+        Some(result)
+    }
 }
@@ -6,6 +6,8 @@ use core::ops::Mul;
 
 use num_bigint::BigUint;
 use p3_maybe_rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
 
 use crate::field::Field;
 use crate::{FieldAlgebra, PackedValue, PrimeField, PrimeField32, TwoAdicField};
@@ -38,6 +40,14 @@ pub fn cyclic_subgroup_coset_known_order<F: Field>(
     generator.shifted_powers(shift).take(order)
 }
 
+/// `len` elements sampled from `rng` with the `Standard` distribution.
+pub fn random_vec<T, R: Rng>(rng: &mut R, len: usize) -> Vec<T>
+where
+    Standard: Distribution<T>,
+{
+    rng.sample_iter(Standard).take(len).collect()
+}
+
 #[must_use]
 pub fn add_vecs<F: Field>(v: Vec<F>, w: Vec<F>) -> Vec<F> {
     assert_eq!(v.len(), w.len());
@@ -202,6 +212,28 @@ pub fn halve_u64<const P: u64>(input: u64) -> u64 {
     }
 }
 
+/// Convert a slice of `u32`s into field elements, or return `None` if any of them isn't less than
+/// the order of the field.
+pub fn try_from_canonical_u32_slice<F: PrimeField32>(vals: &[u32]) -> Option<Vec<F>> {
+    vals.iter()
+        .map(|&val| F::try_from_canonical_u32(val))
+        .collect()
+}
+
+/// Move an element of one 32-bit prime field into another by its canonical value, or return `None`
+/// if that value isn't less than the order of `Dst`.
+pub fn reinterpret_canonical<Src: PrimeField32, Dst: PrimeField32>(val: Src) -> Option<Dst> {
+    Dst::try_from_canonical_u32(val.as_canonical_u32())
+}
+
+/// Move a slice of elements of one 32-bit prime field into another by their canonical values, or
+/// return `None` if any of those values isn't less than the order of `Dst`.
+pub fn reinterpret_canonical_slice<Src: PrimeField32, Dst: PrimeField32>(
+    vals: &[Src],
+) -> Option<Vec<Dst>> {
+    vals.iter().map(|&val| reinterpret_canonical(val)).collect()
+}
+
 /// Given a slice of SF elements, reduce them to a TF element using a 2^32-base decomposition.
 pub fn reduce_32<SF: PrimeField32, TF: PrimeField>(vals: &[SF]) -> TF {
     let po2 = TF::from_canonical_u64(1u64 << 32);
@@ -143,6 +143,15 @@ pub unsafe trait PackedField: FieldAlgebra<F = Self::Scalar>
     + Div<Self::Scalar, Output = Self>
 {
     type Scalar: Field;
+
+    /// The sum of the lanes of `self`.
+    ///
+    /// Vectorized implementations may override this with a reduction within the registers rather
+    /// than extracting the lanes one by one.
+    #[must_use]
+    fn packed_sum(self) -> Self::Scalar {
+        Self::Scalar::sum_slice(self.as_slice())
+    }
 }
 
 /// # Safety
@@ -0,0 +1,57 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use itertools::Itertools;
+use p3_baby_bear::BabyBear;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_fri::{fold_row_generic, FriGenericConfig, TwoAdicFriGenericConfig};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::{log2_strict_usize, reverse_slice_index_bits};
+use rand::distributions::{Distribution, Standard};
+use rand::{thread_rng, Rng};
+
+/// Compare the commit phase fold, which folds packed extension elements, with folding each row
+/// of extension elements on its own.
+fn bench<F: TwoAdicField, EF: ExtensionField<F>>(c: &mut Criterion, log_sizes: &[usize])
+where
+    Standard: Distribution<EF>,
+{
+    let name = format!("fold_matrix::<{}>", type_name::<EF>());
+    let mut group = c.benchmark_group(&name);
+    group.sample_size(10);
+
+    let config = TwoAdicFriGenericConfig::<(), (), F>(PhantomData);
+    for &log_size in log_sizes {
+        let mut rng = thread_rng();
+        let m = RowMajorMatrix::<EF>::rand(&mut rng, 1 << (log_size - 1), 2);
+        let beta: EF = rng.gen();
+
+        group.bench_with_input(BenchmarkId::new("packed", log_size), &m, |b, m| {
+            b.iter(|| config.fold_matrix(beta, m.as_view()))
+        });
+
+        group.bench_with_input(BenchmarkId::new("row by row", log_size), &m, |b, m| {
+            b.iter(|| {
+                let g_inv = F::two_adic_generator(log2_strict_usize(m.height()) + 1).inverse();
+                let mut twiddles = g_inv.powers().take(m.height()).collect_vec();
+                reverse_slice_index_bits(&mut twiddles);
+                m.rows()
+                    .zip(twiddles)
+                    .map(|(row, twiddle)| fold_row_generic(row, beta, twiddle))
+                    .collect_vec()
+            })
+        });
+    }
+}
+
+fn bench_fold_matrix(c: &mut Criterion) {
+    let log_sizes = [12, 16, 20];
+
+    bench::<BabyBear, BinomialExtensionField<BabyBear, 4>>(c, &log_sizes);
+}
+
+criterion_group!(benches, bench_fold_matrix);
+criterion_main!(benches);
@@ -1,20 +1,90 @@
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
+use p3_challenger::CanSampleBits;
+use p3_commit::QueryParameters;
 use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrixView;
 use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
 
 #[derive(Debug)]
 pub struct FriConfig<M> {
     pub log_blowup: usize,
-    // TODO: This parameter and FRI early stopping are not yet implemented in `CirclePcs`.
+    /// The commit phase stops once the codeword encodes a polynomial with `2^log_final_poly_len`
+    /// coefficients, which are sent to the verifier to evaluate at each query. Zero folds all the
+    /// way down to a constant. Every input must encode a longer polynomial than this, or the
+    /// provers fail with [`FriProverError::FinalPolyTooLong`](crate::prover::FriProverError).
+    /// `CirclePcs` always folds down to a constant, and rejects a nonzero value.
     pub log_final_poly_len: usize,
     pub num_queries: usize,
     pub proof_of_work_bits: usize,
+    /// Commit phase rounds whose codeword matrix has at most this many rows are sent to the
+    /// verifier in full, rather than opened with a Merkle proof in every query. Zero disables this.
+    /// `CirclePcs` doesn't support direct openings, and rejects a nonzero value.
+    // TODO: Only the commit phase is covered. The query phase still opens small input batches,
+    // e.g. preprocessed tables, with a Merkle proof per query.
+    pub max_direct_opening_height: usize,
+    /// Each commit phase round folds the codeword by up to `2^log_folding_arity`, opening that
+    /// many evaluations per query. One is the usual folding in half.
+    pub log_folding_arity: usize,
+    /// Sample query indices with [`CanSampleBits::sample_uniform_bits`], rather than by masking a
+    /// field element, which is slightly biased. This changes the transcript, so a proof only
+    /// verifies with the setting it was made with.
+    pub uniform_query_indices: bool,
     pub mmcs: M,
 }
 
 impl<M> FriConfig<M> {
+    /// Start building a config which commits to its codewords with `mmcs`, see
+    /// [`FriConfigBuilder`].
+    pub const fn builder(mmcs: M) -> FriConfigBuilder<M> {
+        FriConfigBuilder {
+            log_blowup: 1,
+            final_poly_len: 1,
+            num_queries: 0,
+            proof_of_work_bits: 0,
+            max_direct_opening_height: 0,
+            log_folding_arity: 1,
+            uniform_query_indices: false,
+            mmcs,
+        }
+    }
+
+    /// A blowup of 2 with 100 queries and 16 bits of proof of work, for about 116 bits of
+    /// conjectured soundness with fast proving. This is what the benchmarks use.
+    pub const fn standard_fast(mmcs: M) -> Self {
+        Self {
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            num_queries: 100,
+            proof_of_work_bits: 16,
+            max_direct_opening_height: 0,
+            log_folding_arity: 1,
+            uniform_query_indices: false,
+            mmcs,
+        }
+    }
+
+    /// The fewest queries which, with 16 bits of proof of work, give at least 100 bits of
+    /// conjectured soundness at a blowup of `2^log_blowup`.
+    pub fn conjectured_100_bits(log_blowup: usize, mmcs: M) -> Result<Self, FriConfigError> {
+        const PROOF_OF_WORK_BITS: usize = 16;
+        if log_blowup == 0 {
+            return Err(FriConfigError::ZeroLogBlowup);
+        }
+        Ok(Self {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries: (100 - PROOF_OF_WORK_BITS).div_ceil(log_blowup),
+            proof_of_work_bits: PROOF_OF_WORK_BITS,
+            max_direct_opening_height: 0,
+            log_folding_arity: 1,
+            uniform_query_indices: false,
+            mmcs,
+        })
+    }
+
     pub const fn blowup(&self) -> usize {
         1 << self.log_blowup
     }
@@ -23,6 +93,64 @@ impl<M> FriConfig<M> {
         1 << self.log_final_poly_len
     }
 
+    pub const fn folding_arity(&self) -> usize {
+        1 << self.log_folding_arity
+    }
+
+    pub const fn query_parameters(&self) -> QueryParameters {
+        QueryParameters {
+            log_blowup: self.log_blowup,
+            num_queries: self.num_queries,
+            proof_of_work_bits: self.proof_of_work_bits,
+        }
+    }
+
+    /// Sample the index of a query into codewords of height `2^bits`.
+    pub fn sample_query_index<C: CanSampleBits<usize>>(
+        &self,
+        challenger: &mut C,
+        bits: usize,
+    ) -> usize {
+        if self.uniform_query_indices {
+            challenger.sample_uniform_bits(bits)
+        } else {
+            challenger.sample_bits(bits)
+        }
+    }
+
+    /// Whether a commit phase codeword matrix with `height` rows is opened directly.
+    pub const fn opens_directly(&self, height: usize) -> bool {
+        height <= self.max_direct_opening_height
+    }
+
+    /// The log of the arity of each commit phase round, folding inputs whose heights are
+    /// `2^log_heights`, in descending order, down to a codeword of height `2^log_final_height`.
+    ///
+    /// Rounds fold by the full arity, except where that would fold past the height of an input,
+    /// which has to be added to the codeword between rounds, or past the final height.
+    pub fn commit_phase_log_arities(
+        &self,
+        log_heights: &[usize],
+        log_final_height: usize,
+    ) -> Vec<usize> {
+        assert!(self.log_folding_arity > 0, "FRI must fold by at least 2");
+        let mut log_arities = Vec::new();
+        let Some(&(mut log_height)) = log_heights.first() else {
+            return log_arities;
+        };
+        while log_height > log_final_height {
+            let log_next_height = log_heights
+                .iter()
+                .copied()
+                .find(|&h| h < log_height)
+                .map_or(log_final_height, |h| h.max(log_final_height));
+            let log_arity = self.log_folding_arity.min(log_height - log_next_height);
+            log_arities.push(log_arity);
+            log_height -= log_arity;
+        }
+        log_arities
+    }
+
     /// Returns the soundness bits of this FRI instance based on the
     /// [ethSTARK](https://eprint.iacr.org/2021/582) conjecture.
     ///
@@ -31,10 +159,160 @@ impl<M> FriConfig<M> {
     pub fn conjectured_soundness_bits(&self) -> usize {
         self.log_blowup * self.num_queries + self.proof_of_work_bits
     }
+
+    /// The minimum number of bits in the order of the challenge field, for FRI over codewords of
+    /// height up to `2^log_max_height`. Each folding challenge is bad with probability roughly
+    /// `2^log_max_height / |EF|`, so with a smaller field a prover could find a bad one with less
+    /// work than the proof of work asks for. Higher arities fold with higher powers of each
+    /// challenge, which are bad for proportionally more challenges.
+    pub const fn min_challenge_bits(&self, log_max_height: usize) -> usize {
+        log_max_height + self.proof_of_work_bits + self.log_folding_arity.saturating_sub(1)
+    }
+}
+
+/// The row which the query at `index` opens in a committed input matrix of height `2^log_height`,
+/// where `index` is a row of the tallest inputs, of height `2^log_max_height`.
+///
+/// Inputs are committed in an order in which the rows of a shorter matrix line up with the high
+/// bits of the taller ones' rows: bit-reversed for two-adic cosets, and permuted by
+/// `cfft_permute_index` for circle domains. The prover opens the inputs at this row, and the
+/// verifier checks them at it, so the two can't disagree about the convention.
+#[inline]
+pub const fn input_query_row(index: usize, log_max_height: usize, log_height: usize) -> usize {
+    index >> (log_max_height - log_height)
+}
+
+/// A [`FriConfig`] under construction, from [`FriConfig::builder`]. Its parameters are checked
+/// when it's built, rather than deep inside the prover. The number of queries has no default, and
+/// must be set.
+#[derive(Debug)]
+pub struct FriConfigBuilder<M> {
+    log_blowup: usize,
+    final_poly_len: usize,
+    num_queries: usize,
+    proof_of_work_bits: usize,
+    max_direct_opening_height: usize,
+    log_folding_arity: usize,
+    uniform_query_indices: bool,
+    mmcs: M,
+}
+
+impl<M> FriConfigBuilder<M> {
+    /// Defaults to 1, i.e. a blowup of 2.
+    #[must_use]
+    pub const fn log_blowup(mut self, log_blowup: usize) -> Self {
+        self.log_blowup = log_blowup;
+        self
+    }
+
+    /// The length of the polynomial FRI stops folding at, which must be a power of two. Defaults
+    /// to 1, i.e. folding down to a constant.
+    #[must_use]
+    pub const fn final_poly_len(mut self, final_poly_len: usize) -> Self {
+        self.final_poly_len = final_poly_len;
+        self
+    }
+
+    #[must_use]
+    pub const fn num_queries(mut self, num_queries: usize) -> Self {
+        self.num_queries = num_queries;
+        self
+    }
+
+    /// Defaults to no proof of work.
+    #[must_use]
+    pub const fn proof_of_work_bits(mut self, proof_of_work_bits: usize) -> Self {
+        self.proof_of_work_bits = proof_of_work_bits;
+        self
+    }
+
+    /// See [`FriConfig::max_direct_opening_height`]. Defaults to 0, i.e. every round is opened
+    /// with Merkle proofs.
+    #[must_use]
+    pub const fn max_direct_opening_height(mut self, max_direct_opening_height: usize) -> Self {
+        self.max_direct_opening_height = max_direct_opening_height;
+        self
+    }
+
+    /// See [`FriConfig::log_folding_arity`]. Defaults to 1, i.e. folding in half.
+    #[must_use]
+    pub const fn log_folding_arity(mut self, log_folding_arity: usize) -> Self {
+        self.log_folding_arity = log_folding_arity;
+        self
+    }
+
+    /// See [`FriConfig::uniform_query_indices`]. Defaults to false, which keeps the transcript
+    /// compatible with existing proofs.
+    #[must_use]
+    pub const fn uniform_query_indices(mut self, uniform_query_indices: bool) -> Self {
+        self.uniform_query_indices = uniform_query_indices;
+        self
+    }
+
+    /// Check the parameters and build the config, for a proof of work over `F`.
+    ///
+    /// Codeword heights, blowups, final polynomial lengths and folding arities are all powers of
+    /// two, and the last round folds by less if it has to, so the arity needs no check against
+    /// the other sizes.
+    pub fn build<F: Field>(self) -> Result<FriConfig<M>, FriConfigError> {
+        if self.log_blowup == 0 {
+            return Err(FriConfigError::ZeroLogBlowup);
+        }
+        if !self.final_poly_len.is_power_of_two() {
+            return Err(FriConfigError::FinalPolyLenNotPowerOfTwo(
+                self.final_poly_len,
+            ));
+        }
+        if self.num_queries == 0 {
+            return Err(FriConfigError::NoQueries);
+        }
+        if self.log_folding_arity == 0 {
+            return Err(FriConfigError::ZeroLogFoldingArity);
+        }
+        if self.proof_of_work_bits >= F::bits() {
+            return Err(FriConfigError::ProofOfWorkTooLong {
+                proof_of_work_bits: self.proof_of_work_bits,
+                field_bits: F::bits(),
+            });
+        }
+        Ok(FriConfig {
+            log_blowup: self.log_blowup,
+            log_final_poly_len: self.final_poly_len.trailing_zeros() as usize,
+            num_queries: self.num_queries,
+            proof_of_work_bits: self.proof_of_work_bits,
+            max_direct_opening_height: self.max_direct_opening_height,
+            log_folding_arity: self.log_folding_arity,
+            uniform_query_indices: self.uniform_query_indices,
+            mmcs: self.mmcs,
+        })
+    }
+}
+
+/// A reason [`FriConfigBuilder::build`] rejected its parameters.
+#[derive(Debug)]
+pub enum FriConfigError {
+    /// A blowup of 1 leaves the codewords no redundancy for the queries to check.
+    ZeroLogBlowup,
+    /// The final polynomial is what's left after halving the codewords' length, so it can only be
+    /// a power of two long.
+    FinalPolyLenNotPowerOfTwo(usize),
+    /// With no queries, the verifier checks nothing.
+    NoQueries,
+    /// Folding by 1 never shrinks the codeword.
+    ZeroLogFoldingArity,
+    /// The proof-of-work witness is a field element, and only zero has as many leading zeros as
+    /// the field has bits, so fewer than that must be asked of it.
+    ProofOfWorkTooLong {
+        proof_of_work_bits: usize,
+        field_bits: usize,
+    },
 }
 
 /// Whereas `FriConfig` encompasses parameters the end user can set, `FriGenericConfig` is
 /// set by the PCS calling FRI, and abstracts over implementation details of the PCS.
+///
+/// This trait is object safe, so the PCS can be picked at runtime by passing FRI a
+/// `&dyn FriGenericConfig<F, InputProof = _, InputError = _>`.
 pub trait FriGenericConfig<F: Field> {
     type InputProof;
     type InputError: Debug;
@@ -44,18 +322,54 @@ pub trait FriGenericConfig<F: Field> {
     fn extra_query_index_bits(&self) -> usize;
 
     /// Fold a row, returning a single column.
-    /// Right now the input row will always be 2 columns wide,
-    /// but we may support higher folding arity in the future.
-    fn fold_row(
+    /// The input row is always 2 columns wide; higher arities are folded by
+    /// [`fold_row_with_arity`](Self::fold_row_with_arity) in several of these steps.
+    fn fold_row(&self, index: usize, log_height: usize, beta: F, evals: &[F]) -> F;
+
+    /// Same as applying fold_row to every row, possibly faster.
+    fn fold_matrix(&self, beta: F, m: RowMajorMatrixView<'_, F>) -> Vec<F>;
+
+    /// Fold a row of `2^log_arity` evaluations, at `index` in the folded matrix of height
+    /// `2^log_height`, into one.
+    ///
+    /// This folds pairs `log_arity` times, with `beta`, `beta^2`, `beta^4` and so on. If `f` is
+    /// split into `f_0, ..., f_{k-1}` by the residue of its monomials' degrees mod `k`, this gives
+    /// the arity `k` fold `f_0 + beta f_1 + ... + beta^{k-1} f_{k-1}`.
+    fn fold_row_with_arity(
         &self,
         index: usize,
         log_height: usize,
-        beta: F,
-        evals: impl Iterator<Item = F>,
-    ) -> F;
+        log_arity: usize,
+        mut beta: F,
+        mut evals: Vec<F>,
+    ) -> F {
+        assert_eq!(evals.len(), 1 << log_arity);
+        for level in (0..log_arity).rev() {
+            evals = evals
+                .chunks_exact(2)
+                .enumerate()
+                .map(|(i, pair)| {
+                    self.fold_row((index << level) + i, log_height + level, beta, pair)
+                })
+                .collect();
+            beta = beta.square();
+        }
+        evals[0]
+    }
 
-    /// Same as applying fold_row to every row, possibly faster.
-    fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F>;
+    /// Same as applying `fold_row_with_arity` to every row of `m`, whose width is the arity.
+    fn fold_matrix_with_arity(&self, mut beta: F, m: RowMajorMatrixView<'_, F>) -> Vec<F> {
+        let log_arity = log2_strict_usize(m.width());
+        assert!(log_arity > 0);
+        // The rows of `m` are runs of rows of the matrix of pairs, which fold into runs of the
+        // next one's rows, and so on.
+        let mut folded = self.fold_matrix(beta, RowMajorMatrixView::new(m.values, 2));
+        for _ in 1..log_arity {
+            beta = beta.square();
+            folded = self.fold_matrix(beta, RowMajorMatrixView::new(&folded, 2));
+        }
+        folded
+    }
 }
 
 /// Creates a minimal `FriConfig` for testing purposes.
@@ -66,6 +380,9 @@ pub fn create_test_fri_config<Mmcs>(mmcs: Mmcs) -> FriConfig<Mmcs> {
         log_final_poly_len: 0,
         num_queries: 2,
         proof_of_work_bits: 1,
+        max_direct_opening_height: 0,
+        log_folding_arity: 1,
+        uniform_query_indices: false,
         mmcs,
     }
 }
@@ -73,11 +390,141 @@ pub fn create_test_fri_config<Mmcs>(mmcs: Mmcs) -> FriConfig<Mmcs> {
 /// Creates a `FriConfig` suitable for benchmarking.
 /// This configuration represents typical settings used in production-like scenarios.
 pub fn create_benchmark_fri_config<Mmcs>(mmcs: Mmcs) -> FriConfig<Mmcs> {
-    FriConfig {
-        log_blowup: 1,
-        log_final_poly_len: 0,
-        num_queries: 100,
-        proof_of_work_bits: 16,
-        mmcs,
+    FriConfig::standard_fast(mmcs)
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{FieldAlgebra, TwoAdicField};
+    use p3_util::reverse_bits_len;
+
+    use super::*;
+
+    #[test]
+    fn test_builder_accepts_valid_parameters() {
+        let config = FriConfig::builder(())
+            .log_blowup(2)
+            .final_poly_len(8)
+            .num_queries(50)
+            .proof_of_work_bits(20)
+            .max_direct_opening_height(16)
+            .build::<BabyBear>()
+            .unwrap();
+        assert_eq!(config.blowup(), 4);
+        assert_eq!(config.final_poly_len(), 8);
+        assert_eq!(config.num_queries, 50);
+        assert_eq!(config.proof_of_work_bits, 20);
+        assert!(config.opens_directly(16));
+        assert!(!config.opens_directly(32));
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_parameters() {
+        let builder = || FriConfig::builder(()).num_queries(10);
+
+        assert!(matches!(
+            builder().log_blowup(0).build::<BabyBear>(),
+            Err(FriConfigError::ZeroLogBlowup)
+        ));
+        assert!(matches!(
+            builder().num_queries(0).build::<BabyBear>(),
+            Err(FriConfigError::NoQueries)
+        ));
+        assert!(matches!(
+            builder().log_folding_arity(0).build::<BabyBear>(),
+            Err(FriConfigError::ZeroLogFoldingArity)
+        ));
+        // Forgetting to set the number of queries is caught too.
+        assert!(matches!(
+            FriConfig::builder(()).build::<BabyBear>(),
+            Err(FriConfigError::NoQueries)
+        ));
+        for final_poly_len in [0, 3, 12] {
+            assert!(matches!(
+                builder().final_poly_len(final_poly_len).build::<BabyBear>(),
+                Err(FriConfigError::FinalPolyLenNotPowerOfTwo(len)) if len == final_poly_len
+            ));
+        }
+        for proof_of_work_bits in [31, 32] {
+            assert!(matches!(
+                builder().proof_of_work_bits(proof_of_work_bits).build::<BabyBear>(),
+                Err(FriConfigError::ProofOfWorkTooLong {
+                    proof_of_work_bits: bits,
+                    field_bits: 31,
+                }) if bits == proof_of_work_bits
+            ));
+        }
+        assert!(builder().proof_of_work_bits(30).build::<BabyBear>().is_ok());
+    }
+
+    #[test]
+    fn test_commit_phase_log_arities() {
+        let binary = FriConfig::builder(())
+            .num_queries(1)
+            .build::<BabyBear>()
+            .unwrap();
+        assert_eq!(binary.commit_phase_log_arities(&[10, 6], 1), [1; 9]);
+
+        let quaternary = FriConfig::builder(())
+            .num_queries(1)
+            .log_folding_arity(2)
+            .build::<BabyBear>()
+            .unwrap();
+        assert_eq!(quaternary.folding_arity(), 4);
+        // The last round folds by what's left.
+        assert_eq!(
+            quaternary.commit_phase_log_arities(&[10, 6], 1),
+            [2, 2, 2, 2, 1]
+        );
+        // Inputs of heights 2^9 and 2^6 have to be added between rounds.
+        assert_eq!(
+            quaternary.commit_phase_log_arities(&[10, 9, 6], 1),
+            [1, 2, 1, 2, 2, 1]
+        );
+        assert!(quaternary.commit_phase_log_arities(&[1], 1).is_empty());
+        assert!(quaternary.commit_phase_log_arities(&[], 1).is_empty());
+    }
+
+    #[test]
+    fn test_presets() {
+        let fast = FriConfig::standard_fast(());
+        assert_eq!(fast.num_queries, 100);
+        assert_eq!(fast.conjectured_soundness_bits(), 116);
+
+        for (log_blowup, num_queries) in [(1, 84), (2, 42), (3, 28), (4, 21), (5, 17)] {
+            let config = FriConfig::conjectured_100_bits(log_blowup, ()).unwrap();
+            assert_eq!(config.num_queries, num_queries);
+            assert!(config.conjectured_soundness_bits() >= 100);
+            // One query fewer would fall short.
+            assert!(log_blowup * (num_queries - 1) + config.proof_of_work_bits < 100);
+        }
+        assert!(matches!(
+            FriConfig::conjectured_100_bits(0, ()),
+            Err(FriConfigError::ZeroLogBlowup)
+        ));
+    }
+
+    #[test]
+    fn test_input_query_row_follows_bit_reversed_ldes() {
+        // Row `r` of a coset LDE of height `2^k` in bit-reversed order is evaluated at
+        // `g w_k^{rev_k(r)}`. FRI folds the query's point `g w_K^{rev_K(i)}` of the tallest inputs
+        // down to that height by raising its subgroup part to the power `2^{K - k}`.
+        let log_max_height = 6;
+        let subgroup_point = |row: usize, log_height: usize| {
+            BabyBear::two_adic_generator(log_height)
+                .exp_u64(reverse_bits_len(row, log_height) as u64)
+        };
+        for log_height in 0..=log_max_height {
+            for index in 0..1 << log_max_height {
+                let row = input_query_row(index, log_max_height, log_height);
+                assert_eq!(
+                    subgroup_point(row, log_height),
+                    subgroup_point(index, log_max_height)
+                        .exp_power_of_2(log_max_height - log_height),
+                    "index {index}, log_height {log_height}"
+                );
+            }
+        }
     }
 }
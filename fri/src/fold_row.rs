@@ -0,0 +1,140 @@
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use p3_field::{ExtensionField, Field, FieldAlgebra, FieldExtensionAlgebra, PackedValue};
+use p3_matrix::dense::RowMajorMatrixView;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+
+/// Fold a row of evaluations `(p(x_0), p(x_1))` at two points which differ in a single
+/// coordinate `c`, i.e. `c(x_1) = -c(x_0)`, into the evaluation of `p_0 + beta p_1` at the point
+/// below them, where `p = p_0 + c p_1`. That is
+/// ```ignore
+/// (p(x_0) + p(x_1)) / 2 + beta (p(x_0) - p(x_1)) / (2 c(x_0))
+/// ```
+/// in which `twiddle` is `1 / c(x_0)`: the inverse of the point itself for a two-adic coset, or of
+/// its `x` or `y` coordinate on a circle domain.
+///
+/// Each FRI backend only needs to find the twiddle for its geometry, so the prover, which folds
+/// whole matrices, and the verifier, which folds single rows, share the same arithmetic. This does
+/// not allocate, and takes the same steps for any inputs.
+#[inline]
+pub fn fold_row_generic<F: Field, EF: ExtensionField<F>>(
+    evals: impl IntoIterator<Item = EF>,
+    beta: EF,
+    twiddle: F,
+) -> EF {
+    let (lo, hi) = evals
+        .into_iter()
+        .collect_tuple()
+        .expect("can only fold rows of two evaluations");
+    let sum = lo + hi;
+    let diff = (lo - hi) * twiddle;
+    (sum + beta * diff).halve()
+}
+
+/// Fold every row of `m` as [`fold_row_generic`] does, with `twiddles[i]` the twiddle of row `i`.
+///
+/// Runs of `F::Packing::WIDTH` rows are folded together as packed extension elements, and the rows
+/// left over one by one. Either way the results, and their order, are those of folding row by row.
+pub fn fold_matrix_generic<F: Field, EF: ExtensionField<F>>(
+    beta: EF,
+    m: RowMajorMatrixView<'_, EF>,
+    twiddles: &[F],
+) -> Vec<EF> {
+    assert_eq!(m.width(), 2, "can only fold rows of two evaluations");
+    assert_eq!(twiddles.len(), m.height());
+    let width = F::Packing::WIDTH;
+    let packed_height = m.height() - m.height() % width;
+
+    let beta_packed = EF::ExtensionPacking::from_f(beta);
+    let half = F::Packing::from(F::TWO.inverse());
+
+    let mut folded = EF::zero_vec(m.height());
+    let (packed_folded, tail_folded) = folded.split_at_mut(packed_height);
+    let (packed_rows, tail_rows) = m.values.split_at(2 * packed_height);
+    let (packed_twiddles, tail_twiddles) = twiddles.split_at(packed_height);
+
+    packed_folded
+        .par_chunks_exact_mut(width)
+        .zip(packed_rows.par_chunks_exact(2 * width))
+        .zip(packed_twiddles.par_chunks_exact(width))
+        .for_each(|((folded, rows), twiddles)| {
+            let column = |c: usize| {
+                EF::ExtensionPacking::from_base_fn(|i| {
+                    F::Packing::from_fn(|j| {
+                        <EF as FieldExtensionAlgebra<F>>::as_base_slice(&rows[2 * j + c])[i]
+                    })
+                })
+            };
+            let (lo, hi) = (column(0), column(1));
+            let sum = lo + hi;
+            let diff = (lo - hi) * *F::Packing::from_slice(twiddles);
+            let packed = (sum + beta_packed * diff) * half;
+            for (j, folded) in folded.iter_mut().enumerate() {
+                *folded = <EF as FieldExtensionAlgebra<F>>::from_base_fn(|i| {
+                    packed.as_base_slice()[i].as_slice()[j]
+                });
+            }
+        });
+
+    for ((folded, row), &twiddle) in tail_folded
+        .iter_mut()
+        .zip(tail_rows.chunks_exact(2))
+        .zip(tail_twiddles)
+    {
+        *folded = fold_row_generic(row.iter().copied(), beta, twiddle);
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+
+    #[test]
+    fn folds_a_line_to_its_challenge_combination() {
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            // p(c) = a + b c, so p_0 = a and p_1 = b.
+            let (a, b, beta) = (rng.gen::<EF>(), rng.gen::<EF>(), rng.gen::<EF>());
+            let c: F = rng.gen();
+            let evals = [a + b * c, a - b * c];
+            assert_eq!(fold_row_generic(evals, beta, c.inverse()), a + beta * b);
+        }
+    }
+
+    #[test]
+    fn fold_matrix_matches_rows() {
+        let mut rng = thread_rng();
+        // Heights which aren't multiples of the packing width leave rows to fold one by one.
+        for height in [1, 3, 8, 21, 64] {
+            let m = RowMajorMatrix::<EF>::rand(&mut rng, height, 2);
+            let twiddles: Vec<F> = (0..height).map(|_| rng.gen()).collect();
+            let beta: EF = rng.gen();
+            let row_folded: Vec<EF> = m
+                .rows()
+                .zip(&twiddles)
+                .map(|(row, &twiddle)| fold_row_generic(row, beta, twiddle))
+                .collect();
+            assert_eq!(
+                fold_matrix_generic(beta, m.as_view(), &twiddles),
+                row_folded
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "two evaluations")]
+    fn rejects_wider_rows() {
+        fold_row_generic([EF::ONE; 4], EF::ONE, F::ONE);
+    }
+}
@@ -3,7 +3,7 @@ use core::cell::RefCell;
 use core::fmt::Debug;
 
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, TwoAdicMultiplicativeCoset};
+use p3_commit::{Mmcs, OpenedValues, Pcs, QueryParameters, TwoAdicMultiplicativeCoset};
 use p3_dft::TwoAdicSubgroupDft;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::dense::RowMajorMatrix;
@@ -107,6 +107,24 @@ where
         HorizontallyTruncated::new(inner_evals, inner_width - self.num_random_codewords)
     }
 
+    fn max_log_evaluation_blowup(&self) -> Option<usize> {
+        <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::max_log_evaluation_blowup(
+            &self.inner,
+        )
+    }
+
+    fn max_log_domain_size(&self) -> Option<usize> {
+        <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::max_log_domain_size(
+            &self.inner,
+        )
+    }
+
+    fn query_parameters(&self) -> Option<QueryParameters> {
+        <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::query_parameters(
+            &self.inner,
+        )
+    }
+
     fn open(
         &self,
         // For each round,
@@ -119,8 +137,8 @@ where
             >,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
-        let (mut inner_opened_values, inner_proof) = self.inner.open(rounds, challenger);
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::Error> {
+        let (mut inner_opened_values, inner_proof) = self.inner.open(rounds, challenger)?;
 
         // inner_opened_values includes opened values for the random codewords. Those should be
         // hidden from our caller, so we split them off and store them in the proof.
@@ -143,7 +161,7 @@ where
             })
             .collect();
 
-        (inner_opened_values, (opened_values_rand, inner_proof))
+        Ok((inner_opened_values, (opened_values_rand, inner_proof)))
     }
 
     fn verify(
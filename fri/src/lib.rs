@@ -6,6 +6,7 @@ extern crate alloc;
 
 mod config;
 mod fold_even_odd;
+mod fold_row;
 mod hiding_pcs;
 mod proof;
 pub mod prover;
@@ -14,6 +15,7 @@ pub mod verifier;
 
 pub use config::*;
 pub use fold_even_odd::*;
+pub use fold_row::*;
 pub use hiding_pcs::*;
 pub use proof::*;
 pub use two_adic_pcs::*;
@@ -2,6 +2,7 @@ use alloc::vec::Vec;
 
 use p3_commit::Mmcs;
 use p3_field::Field;
+use p3_util::encoding::{CanonicalEncoding, DecodingError};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -11,11 +12,26 @@ use serde::{Deserialize, Serialize};
 ))]
 pub struct FriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     pub commit_phase_commits: Vec<M::Commitment>,
+    /// For each commit phase commitment, how the verifier checks the committed codeword.
+    pub commit_phase_modes: Vec<CommitPhaseMode<F>>,
+    /// For each commit phase commitment, the log of the number of evaluations in each row of the
+    /// committed codeword, which that round folds into one.
+    pub commit_phase_log_arities: Vec<usize>,
     pub query_proofs: Vec<QueryProof<F, M, InputProof>>,
     pub final_poly: Vec<F>,
     pub pow_witness: Witness,
 }
 
+impl<F: Field, M: Mmcs<F>, Witness, InputProof> FriProof<F, M, Witness, InputProof> {
+    /// The log of the factor the commit phase shrinks codewords by, i.e. the sum of its rounds'
+    /// log arities.
+    pub fn log_folding_factor(&self) -> usize {
+        self.commit_phase_log_arities
+            .iter()
+            .fold(0, |sum, &log_arity| sum.saturating_add(log_arity))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound(
     serialize = "InputProof: Serialize",
@@ -23,18 +39,117 @@ pub struct FriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
 ))]
 pub struct QueryProof<F: Field, M: Mmcs<F>, InputProof> {
     pub input_proof: InputProof,
-    /// For each commit phase commitment, this contains openings of a commit phase codeword at the
-    /// queried location, along with an opening proof.
+    /// For each commit phase commitment opened in `CommitPhaseMode::Queried` mode, this contains
+    /// openings of a commit phase codeword at the queried location, along with an opening proof.
     pub commit_phase_openings: Vec<CommitPhaseProofStep<F, M>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CommitPhaseProofStep<F: Field, M: Mmcs<F>> {
-    /// The opening of the commit phase codeword at the sibling location.
-    // This may change to Vec<FC::Challenge> if the library is generalized to support other FRI
-    // folding arities besides 2, meaning that there can be multiple siblings.
-    pub sibling_value: F,
+    /// The rest of the opened row of the commit phase codeword, i.e. its evaluations at the
+    /// locations which fold together with the queried one, in order.
+    pub sibling_values: Vec<F>,
 
     pub opening_proof: M::Proof,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub enum CommitPhaseMode<F: Field> {
+    /// Every query opens the codeword at the queried location, with an MMCS opening proof.
+    Queried,
+    /// The whole codeword is sent once, in the layout it was committed in. The verifier recomputes
+    /// the commitment from it, and every query reads its values from it directly.
+    Direct(Vec<F>),
+}
+
+impl<F, M, Witness, InputProof> CanonicalEncoding for FriProof<F, M, Witness, InputProof>
+where
+    F: Field + CanonicalEncoding,
+    M: Mmcs<F>,
+    M::Commitment: CanonicalEncoding,
+    M::Proof: CanonicalEncoding,
+    Witness: CanonicalEncoding,
+    InputProof: CanonicalEncoding,
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.commit_phase_commits.encode(out);
+        self.commit_phase_modes.encode(out);
+        self.commit_phase_log_arities.encode(out);
+        self.query_proofs.encode(out);
+        self.final_poly.encode(out);
+        self.pow_witness.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            commit_phase_commits: CanonicalEncoding::decode(input)?,
+            commit_phase_modes: CanonicalEncoding::decode(input)?,
+            commit_phase_log_arities: CanonicalEncoding::decode(input)?,
+            query_proofs: CanonicalEncoding::decode(input)?,
+            final_poly: CanonicalEncoding::decode(input)?,
+            pow_witness: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+impl<F, M, InputProof> CanonicalEncoding for QueryProof<F, M, InputProof>
+where
+    F: Field + CanonicalEncoding,
+    M: Mmcs<F>,
+    M::Proof: CanonicalEncoding,
+    InputProof: CanonicalEncoding,
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.input_proof.encode(out);
+        self.commit_phase_openings.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            input_proof: CanonicalEncoding::decode(input)?,
+            commit_phase_openings: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+impl<F, M> CanonicalEncoding for CommitPhaseProofStep<F, M>
+where
+    F: Field + CanonicalEncoding,
+    M: Mmcs<F>,
+    M::Proof: CanonicalEncoding,
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.sibling_values.encode(out);
+        self.opening_proof.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            sibling_values: CanonicalEncoding::decode(input)?,
+            opening_proof: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+/// Encoded as a one byte tag, `0` for `Queried` and `1` for `Direct`, followed by the codeword.
+impl<F: Field + CanonicalEncoding> CanonicalEncoding for CommitPhaseMode<F> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Queried => 0_u8.encode(out),
+            Self::Direct(codeword) => {
+                1_u8.encode(out);
+                codeword.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        match u8::decode(input)? {
+            0 => Ok(Self::Queried),
+            1 => Ok(Self::Direct(CanonicalEncoding::decode(input)?)),
+            tag => Err(DecodingError::InvalidTag(tag)),
+        }
+    }
+}
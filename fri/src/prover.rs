@@ -1,32 +1,299 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::convert::Infallible;
 use core::iter;
 
 use itertools::{izip, Itertools};
-use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_challenger::{CanObserve, CanSnapshot, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
 use p3_util::{log2_strict_usize, reverse_slice_index_bits};
+use serde::{Deserialize, Serialize};
 use tracing::{debug_span, info_span, instrument};
 
-use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof, QueryProof};
+use crate::{
+    CommitPhaseMode, CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof, QueryProof,
+};
 
-#[instrument(name = "FRI prover", skip_all)]
 pub fn prove<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize) -> G::InputProof,
-) -> FriProof<Challenge, M, Challenger::Witness, G::InputProof>
+) -> Result<FriProof<Challenge, M, Challenger::Witness, G::InputProof>, FriProverError>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge> + ?Sized,
+{
+    prove_with_artifacts(g, config, inputs, challenger, open_input).map(|(proof, _)| proof)
+}
+
+/// Why FRI can't prove its inputs.
+#[derive(Debug)]
+pub enum FriProverError {
+    /// The final polynomial is at least as long as the message the shortest input encodes, which
+    /// would then be sent without being folded at all.
+    FinalPolyTooLong {
+        log_final_poly_len: usize,
+        log_min_message_len: usize,
+    },
+}
+
+/// Values derived while proving which aren't otherwise exposed, for protocols embedding FRI.
+#[derive(Clone, Debug)]
+pub struct FriProverArtifacts<F, Commitment> {
+    /// The folding challenge of each commit phase round.
+    pub betas: Vec<F>,
+    /// The commitment of each commit phase round.
+    pub commitments: Vec<Commitment>,
+    /// The coefficients of the final polynomial.
+    pub final_poly: Vec<F>,
+}
+
+/// Like [`prove`], but also returns the per-round challenges and commitments.
+pub fn prove_with_artifacts<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    inputs: Vec<Vec<Challenge>>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize) -> G::InputProof,
+) -> Result<
+    (
+        FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+        FriProverArtifacts<Challenge, M::Commitment>,
+    ),
+    FriProverError,
+>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge> + ?Sized,
+{
+    check_final_poly_len(config, &inputs)?;
+    Ok(prove_inner(
+        g,
+        config,
+        inputs,
+        challenger,
+        open_input,
+        |commit, challenger| {
+            challenger.observe(commit.clone());
+            Ok::<_, Infallible>(challenger.sample_ext_element())
+        },
+    )
+    .unwrap_or_else(|never| match never {}))
+}
+
+/// Like [`prove_with_artifacts`], but takes each round's folding challenge from `external_beta`,
+/// which is given that round's commitment, rather than deriving it from `challenger`.
+///
+/// `challenger` neither observes the commit phase commitments nor samples the folding challenges.
+/// The caller is responsible for binding them in an outer transcript, in the order they are
+/// passed to `external_beta`; otherwise the resulting proof is not sound. The proof must be
+/// checked with [`crate::verifier::verify_with_external_challenges`].
+pub fn prove_with_external_challenges<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    inputs: Vec<Vec<Challenge>>,
+    challenger: &mut Challenger,
+    mut external_beta: impl FnMut(&M::Commitment) -> Challenge,
+    open_input: impl Fn(usize) -> G::InputProof,
+) -> Result<
+    (
+        FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+        FriProverArtifacts<Challenge, M::Commitment>,
+    ),
+    FriProverError,
+>
 where
     Val: Field,
     Challenge: ExtensionField<Val> + TwoAdicField,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
-    G: FriGenericConfig<Challenge>,
+    G: FriGenericConfig<Challenge> + ?Sized,
+{
+    check_final_poly_len(config, &inputs)?;
+    Ok(
+        prove_inner(g, config, inputs, challenger, open_input, |commit, _| {
+            Ok::<_, Infallible>(external_beta(commit))
+        })
+        .unwrap_or_else(|never| match never {}),
+    )
+}
+
+/// The state of the commit phase after some of its rounds, from which [`prove_resume`] can finish
+/// the proof, e.g. on another machine after this one was interrupted.
+///
+/// The codewords themselves aren't kept: they're folded again from the inputs, which the prover
+/// has to have anyway to answer queries, and committed to again to rebuild the prover data. Only
+/// the challenger's state after the last of the rounds has to be carried over.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FriCheckpoint<F, Commitment, State> {
+    /// The commitment of each completed round.
+    pub commitments: Vec<Commitment>,
+    /// The folding challenge of each completed round.
+    pub betas: Vec<F>,
+    /// The challenger's state after sampling the last of `betas`.
+    pub challenger: State,
+}
+
+#[derive(Debug)]
+pub enum FriCheckpointError {
+    /// The checkpoint's challenger state can't be restored into the challenger.
+    InvalidChallengerState,
+    /// The checkpoint has a different number of commitments and folding challenges, or more rounds
+    /// than the commit phase of these inputs.
+    InvalidShape,
+    /// Committing to the codeword of this round again gave a different commitment, which means the
+    /// inputs aren't those which the checkpoint was made from.
+    CommitmentMismatch(usize),
+    /// The inputs can't be proven at all, whatever the checkpoint.
+    Prover(FriProverError),
+}
+
+/// Like [`prove`], but calls `on_checkpoint` after each round of the commit phase with what
+/// [`prove_resume`] needs to continue from there.
+pub fn prove_with_checkpoints<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    inputs: Vec<Vec<Challenge>>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize) -> G::InputProof,
+    on_checkpoint: impl FnMut(FriCheckpoint<Challenge, M::Commitment, Challenger::State>),
+) -> Result<FriProof<Challenge, M, Challenger::Witness, G::InputProof>, FriProverError>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment> + CanSnapshot,
+    G: FriGenericConfig<Challenge> + ?Sized,
+{
+    check_final_poly_len(config, &inputs)?;
+    let checkpoint = FriCheckpoint {
+        commitments: vec![],
+        betas: vec![],
+        challenger: challenger.snapshot(),
+    };
+    Ok(prove_resume(
+        g,
+        config,
+        inputs,
+        checkpoint,
+        challenger,
+        open_input,
+        on_checkpoint,
+    )
+    .expect("a checkpoint of no rounds fits any inputs FRI can prove"))
+}
+
+/// Finish a proof from a checkpoint made by [`prove_with_checkpoints`] or by an earlier call of
+/// this function, giving the same proof as if it hadn't been interrupted. `inputs` must be those
+/// the checkpoint was made from, and `challenger` must have the same parameters, but its state is
+/// replaced by the checkpoint's. `on_checkpoint` is called after each of the remaining rounds.
+pub fn prove_resume<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    inputs: Vec<Vec<Challenge>>,
+    checkpoint: FriCheckpoint<Challenge, M::Commitment, Challenger::State>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize) -> G::InputProof,
+    mut on_checkpoint: impl FnMut(FriCheckpoint<Challenge, M::Commitment, Challenger::State>),
+) -> Result<FriProof<Challenge, M, Challenger::Witness, G::InputProof>, FriCheckpointError>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment> + CanSnapshot,
+    G: FriGenericConfig<Challenge> + ?Sized,
+{
+    check_final_poly_len(config, &inputs).map_err(FriCheckpointError::Prover)?;
+    let FriCheckpoint {
+        mut commitments,
+        mut betas,
+        challenger: state,
+    } = checkpoint;
+    // Each round folds the codeword, until it's the final polynomial's evaluations.
+    let num_rounds = config
+        .commit_phase_log_arities(
+            &log_heights(&inputs),
+            config.log_blowup + config.log_final_poly_len,
+        )
+        .len();
+    if commitments.len() != betas.len() || commitments.len() > num_rounds {
+        return Err(FriCheckpointError::InvalidShape);
+    }
+    // The completed rounds take their folding challenges from the checkpoint, so the challenger
+    // isn't used until the first new round.
+    challenger
+        .restore(state)
+        .map_err(|_| FriCheckpointError::InvalidChallengerState)?;
+
+    let num_completed = commitments.len();
+    let mut round = 0;
+    let (proof, _) = prove_inner(
+        g,
+        config,
+        inputs,
+        challenger,
+        open_input,
+        |commit, challenger| {
+            let beta = if round < num_completed {
+                if *commit != commitments[round] {
+                    return Err(FriCheckpointError::CommitmentMismatch(round));
+                }
+                betas[round]
+            } else {
+                challenger.observe(commit.clone());
+                let beta = challenger.sample_ext_element();
+                commitments.push(commit.clone());
+                betas.push(beta);
+                on_checkpoint(FriCheckpoint {
+                    commitments: commitments.clone(),
+                    betas: betas.clone(),
+                    challenger: challenger.snapshot(),
+                });
+                beta
+            };
+            round += 1;
+            Ok(beta)
+        },
+    )?;
+    Ok(proof)
+}
+
+/// Prove, taking each round's folding challenge from `sample_beta`, which may stop the proof by
+/// returning an error.
+#[instrument(name = "FRI prover", skip_all)]
+#[allow(clippy::type_complexity)]
+fn prove_inner<G, Val, Challenge, M, Challenger, E>(
+    g: &G,
+    config: &FriConfig<M>,
+    inputs: Vec<Vec<Challenge>>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize) -> G::InputProof,
+    sample_beta: impl FnMut(&M::Commitment, &mut Challenger) -> Result<Challenge, E>,
+) -> Result<
+    (
+        FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+        FriProverArtifacts<Challenge, M::Commitment>,
+    ),
+    E,
+>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge> + ?Sized,
 {
     assert!(!inputs.is_empty());
     assert!(
@@ -38,74 +305,134 @@ where
     );
 
     let log_max_height = log2_strict_usize(inputs[0].len());
-    let log_min_height = log2_strict_usize(inputs.last().unwrap().len());
-    if config.log_final_poly_len > 0 {
-        assert!(log_min_height > config.log_final_poly_len + config.log_blowup);
-    }
+    assert!(
+        Challenge::EXT_ORDER_BITS >= config.min_challenge_bits(log_max_height),
+        "The challenge field has {} bits, but FRI over codewords of height 2^{} needs at least {}.",
+        Challenge::EXT_ORDER_BITS,
+        log_max_height,
+        config.min_challenge_bits(log_max_height),
+    );
 
-    let commit_phase_result = commit_phase(g, config, inputs, challenger);
+    challenger.begin_phase("fri commit phase");
+    let commit_phase_result = commit_phase(g, config, inputs, challenger, sample_beta)?;
 
+    challenger.begin_phase("fri proof of work");
     let pow_witness = challenger.grind(config.proof_of_work_bits);
 
+    challenger.begin_phase("fri query phase");
+    let query_index_bits = log_max_height + g.extra_query_index_bits();
     let query_proofs = info_span!("query phase").in_scope(|| {
-        iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
+        iter::repeat_with(|| config.sample_query_index(challenger, query_index_bits))
             .take(config.num_queries)
             .map(|index| QueryProof {
                 input_proof: open_input(index),
                 commit_phase_openings: answer_query(
                     config,
                     &commit_phase_result.data,
+                    &commit_phase_result.modes,
+                    &commit_phase_result.log_arities,
                     index >> g.extra_query_index_bits(),
                 ),
             })
             .collect()
     });
 
-    FriProof {
+    let artifacts = FriProverArtifacts {
+        betas: commit_phase_result.betas,
+        commitments: commit_phase_result.commits.clone(),
+        final_poly: commit_phase_result.final_poly.clone(),
+    };
+    let proof = FriProof {
         commit_phase_commits: commit_phase_result.commits,
+        commit_phase_modes: commit_phase_result.modes,
+        commit_phase_log_arities: commit_phase_result.log_arities,
         query_proofs,
         final_poly: commit_phase_result.final_poly,
         pow_witness,
-    }
+    };
+    Ok((proof, artifacts))
 }
 
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
+    modes: Vec<CommitPhaseMode<F>>,
+    log_arities: Vec<usize>,
+    betas: Vec<F>,
     final_poly: Vec<F>,
 }
 
+/// Check that the final polynomial is shorter than the message of every input, which the provers
+/// do before anything else, so that [`prove_inner`] can assume it.
+fn check_final_poly_len<M, F>(
+    config: &FriConfig<M>,
+    inputs: &[Vec<F>],
+) -> Result<(), FriProverError> {
+    let Some(shortest) = inputs.last() else {
+        return Ok(());
+    };
+    let log_min_height = log2_strict_usize(shortest.len());
+    if config.log_final_poly_len > 0
+        && log_min_height <= config.log_final_poly_len + config.log_blowup
+    {
+        return Err(FriProverError::FinalPolyTooLong {
+            log_final_poly_len: config.log_final_poly_len,
+            log_min_message_len: log_min_height.saturating_sub(config.log_blowup),
+        });
+    }
+    Ok(())
+}
+
+fn log_heights<F>(inputs: &[Vec<F>]) -> Vec<usize> {
+    inputs
+        .iter()
+        .map(|input| log2_strict_usize(input.len()))
+        .collect()
+}
+
 #[instrument(name = "commit phase", skip_all)]
-fn commit_phase<G, Val, Challenge, M, Challenger>(
+fn commit_phase<G, Val, Challenge, M, Challenger, E>(
     g: &G,
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
-) -> CommitPhaseResult<Challenge, M>
+    mut sample_beta: impl FnMut(&M::Commitment, &mut Challenger) -> Result<Challenge, E>,
+) -> Result<CommitPhaseResult<Challenge, M>, E>
 where
     Val: Field,
     Challenge: ExtensionField<Val> + TwoAdicField,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + CanObserve<M::Commitment>,
-    G: FriGenericConfig<Challenge>,
+    G: FriGenericConfig<Challenge> + ?Sized,
 {
+    let log_arities = config.commit_phase_log_arities(
+        &log_heights(&inputs),
+        config.log_blowup + config.log_final_poly_len,
+    );
     let mut inputs_iter = inputs.into_iter().peekable();
     let mut folded = inputs_iter.next().unwrap();
     let mut commits = vec![];
     let mut data = vec![];
+    let mut modes = vec![];
+    let mut betas = vec![];
 
-    while folded.len() > config.blowup() * config.final_poly_len() {
-        let leaves = RowMajorMatrix::new(folded, 2);
+    for &log_arity in &log_arities {
+        let leaves = RowMajorMatrix::new(folded, 1 << log_arity);
+        let mode = if config.opens_directly(leaves.height()) {
+            CommitPhaseMode::Direct(leaves.values.clone())
+        } else {
+            CommitPhaseMode::Queried
+        };
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
-        challenger.observe(commit.clone());
-
-        let beta: Challenge = challenger.sample_ext_element();
+        let beta = sample_beta(&commit, challenger)?;
         // We passed ownership of `current` to the MMCS, so get a reference to it
         let leaves = config.mmcs.get_matrices(&prover_data).pop().unwrap();
-        folded = g.fold_matrix(beta, leaves.as_view());
+        folded = g.fold_matrix_with_arity(beta, leaves.as_view());
 
         commits.push(commit);
         data.push(prover_data);
+        modes.push(mode);
+        betas.push(beta);
 
         if let Some(v) = inputs_iter.next_if(|v| v.len() == folded.len()) {
             izip!(&mut folded, v).for_each(|(c, x)| *c += x);
@@ -122,7 +449,8 @@ where
     reverse_slice_index_bits(&mut folded);
     // TODO: For better performance, we could run the IDFT on only the first half
     //       (or less, depending on `log_blowup`) of `final_poly`.
-    let final_poly = debug_span!("idft final poly").in_scope(|| Radix2Dit::default().idft(folded));
+    let mut final_poly =
+        debug_span!("idft final poly").in_scope(|| Radix2Dit::default().idft(folded));
 
     // The evaluation domain is "blown-up" relative to the polynomial degree of `final_poly`,
     // so all coefficients after the first final_poly_len should be zero.
@@ -133,46 +461,64 @@ where
             .all(|x| x.is_zero()),
         "All coefficients beyond final_poly_len must be zero"
     );
+    // Only the others are sent, so that the verifier's check of the final polynomial is also a
+    // check of its degree.
+    final_poly.truncate(config.final_poly_len());
 
     // Observe all coefficients of the final polynomial.
     for &x in &final_poly {
         challenger.observe_ext_element(x);
     }
 
-    CommitPhaseResult {
+    Ok(CommitPhaseResult {
         commits,
         data,
+        modes,
+        log_arities,
+        betas,
         final_poly,
-    }
+    })
 }
 
 fn answer_query<F, M>(
     config: &FriConfig<M>,
     commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
-    index: usize,
+    commit_phase_modes: &[CommitPhaseMode<F>],
+    commit_phase_log_arities: &[usize],
+    mut index: usize,
 ) -> Vec<CommitPhaseProofStep<F, M>>
 where
     F: Field,
     M: Mmcs<F>,
 {
-    commit_phase_commits
-        .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let index_i = index >> i;
-            let index_i_sibling = index_i ^ 1;
-            let index_pair = index_i >> 1;
-
-            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_pair, commit);
-            assert_eq!(opened_rows.len(), 1);
-            let opened_row = opened_rows.pop().unwrap();
-            assert_eq!(opened_row.len(), 2, "Committed data should be in pairs");
-            let sibling_value = opened_row[index_i_sibling % 2];
-
-            CommitPhaseProofStep {
-                sibling_value,
-                opening_proof,
-            }
-        })
-        .collect()
+    let mut steps = vec![];
+    for (commit, mode, &log_arity) in izip!(
+        commit_phase_commits,
+        commit_phase_modes,
+        commit_phase_log_arities
+    ) {
+        let index_in_row = index % (1 << log_arity);
+        index >>= log_arity;
+
+        // Directly opened codewords are sent in full, so queries don't need to open them.
+        if matches!(mode, CommitPhaseMode::Direct(_)) {
+            continue;
+        }
+
+        let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index, commit);
+        assert_eq!(opened_rows.len(), 1);
+        let mut sibling_values = opened_rows.pop().unwrap();
+        assert_eq!(
+            sibling_values.len(),
+            1 << log_arity,
+            "Committed rows should have one value per folded evaluation"
+        );
+        sibling_values.remove(index_in_row);
+
+        steps.push(CommitPhaseProofStep {
+            sibling_values,
+            opening_proof,
+        });
+    }
+    steps
 }
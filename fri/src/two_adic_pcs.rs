@@ -1,12 +1,16 @@
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 use core::marker::PhantomData;
+use core::mem::size_of;
 
 use itertools::{izip, Itertools};
-use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
+use p3_challenger::{CanObserve, CanSnapshot, FieldChallenger, GrindingChallenger};
+use p3_commit::{
+    leaf_mapping, Cost, LeafMapEntry, Mmcs, MmcsCostModel, OpenedValues, Pcs, PcsCostModel,
+    PolynomialSpace, QueryParameters, ResumablePcs, TwoAdicMultiplicativeCoset,
+};
 use p3_dft::TwoAdicSubgroupDft;
 use p3_field::{
     batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, dot_product, ExtensionField,
@@ -14,16 +18,21 @@ use p3_field::{
 };
 use p3_interpolation::interpolate_coset;
 use p3_matrix::bitrev::{BitReversableMatrix, BitReversalPerm};
-use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
+use p3_util::encoding::{CanonicalEncoding, DecodingError};
 use p3_util::linear_map::LinearMap;
 use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits, VecExt};
 use serde::{Deserialize, Serialize};
 use tracing::{info_span, instrument};
 
+use crate::prover::FriCheckpoint;
 use crate::verifier::{self, FriError};
-use crate::{prover, FriConfig, FriGenericConfig, FriProof};
+use crate::{
+    fold_matrix_generic, fold_row_generic, input_query_row, prover, FriConfig, FriGenericConfig,
+    FriProof,
+};
 
 #[derive(Debug)]
 pub struct TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
@@ -42,6 +51,32 @@ impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
             _phantom: PhantomData,
         }
     }
+
+    /// The dimensions of the LDE committed to for evaluations of the given dimensions.
+    const fn lde_dimensions(&self, dims: Dimensions) -> Dimensions {
+        Dimensions {
+            width: dims.width,
+            height: dims.height << self.fri.log_blowup,
+        }
+    }
+}
+
+impl<Val: TwoAdicField, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
+    /// The logical→leaf mapping of a commitment to evaluations over `domains`, for debugging
+    /// ordering mismatches. Logical rows index the LDE coset in natural order; the committed LDEs
+    /// are bit-reversed.
+    pub fn trace_leaf_mapping(
+        &self,
+        domains: &[TwoAdicMultiplicativeCoset<Val>],
+    ) -> Vec<LeafMapEntry> {
+        let log_heights = domains
+            .iter()
+            .map(|domain| domain.log_n + self.fri.log_blowup)
+            .collect_vec();
+        leaf_mapping(&log_heights, |log_height, physical_row| {
+            reverse_bits_len(physical_row, log_height)
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -51,15 +86,43 @@ pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
     pub opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
 }
 
-pub struct TwoAdicFriGenericConfig<InputProof, InputError>(
-    pub PhantomData<(InputProof, InputError)>,
+impl<Val, InputMmcs> CanonicalEncoding for BatchOpening<Val, InputMmcs>
+where
+    Val: Field + CanonicalEncoding,
+    InputMmcs: Mmcs<Val>,
+    InputMmcs::Proof: CanonicalEncoding,
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.opened_values.encode(out);
+        self.opening_proof.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            opened_values: CanonicalEncoding::decode(input)?,
+            opening_proof: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+/// The folding of FRI over two-adic cosets of `Val`, of codewords over any extension of `Val`.
+///
+/// By default `Val` is [`CodewordField`], which folds over cosets of the codewords' own field, as
+/// this config did before it could be told its base field. Naming the base field lets the folds
+/// use packed base field arithmetic.
+pub struct TwoAdicFriGenericConfig<InputProof, InputError, Val = CodewordField>(
+    pub PhantomData<(InputProof, InputError, Val)>,
 );
 
+/// The `Val` of a [`TwoAdicFriGenericConfig`] which folds over cosets of the field of the codewords
+/// themselves.
+pub struct CodewordField;
+
 pub type TwoAdicFriGenericConfigForMmcs<F, M> =
-    TwoAdicFriGenericConfig<Vec<BatchOpening<F, M>>, <M as Mmcs<F>>::Error>;
+    TwoAdicFriGenericConfig<Vec<BatchOpening<F, M>>, <M as Mmcs<F>>::Error, F>;
 
-impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
-    for TwoAdicFriGenericConfig<InputProof, InputError>
+impl<Val: TwoAdicField, F: ExtensionField<Val>, InputProof, InputError: Debug> FriGenericConfig<F>
+    for TwoAdicFriGenericConfig<InputProof, InputError, Val>
 {
     type InputProof = InputProof;
     type InputError = InputError;
@@ -68,138 +131,90 @@ impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
         0
     }
 
-    fn fold_row(
-        &self,
-        index: usize,
-        log_height: usize,
-        beta: F,
-        evals: impl Iterator<Item = F>,
-    ) -> F {
-        let arity = 2;
-        let log_arity = 1;
-        let (e0, e1) = evals
-            .collect_tuple()
-            .expect("TwoAdicFriFolder only supports arity=2");
-        // If performance critical, make this API stateful to avoid this
-        // This is a bit more math than is necessary, but leaving it here
-        // in case we want higher arity in the future
-        let subgroup_start = F::two_adic_generator(log_height + log_arity)
-            .exp_u64(reverse_bits_len(index, log_height) as u64);
-        let mut xs = F::two_adic_generator(log_arity)
-            .shifted_powers(subgroup_start)
-            .take(arity)
-            .collect_vec();
-        reverse_slice_index_bits(&mut xs);
-        assert_eq!(log_arity, 1, "can only interpolate two points for now");
-        // interpolate and evaluate at beta
-        e0 + (beta - xs[0]) * (e1 - e0) / (xs[1] - xs[0])
+    fn fold_row(&self, index: usize, log_height: usize, beta: F, evals: &[F]) -> F {
+        two_adic_fold_row::<Val, F>(index, log_height, beta, evals)
     }
 
-    fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F> {
-        // We use the fact that
-        //     p_e(x^2) = (p(x) + p(-x)) / 2
-        //     p_o(x^2) = (p(x) - p(-x)) / (2 x)
-        // that is,
-        //     p_e(g^(2i)) = (p(g^i) + p(g^(n/2 + i))) / 2
-        //     p_o(g^(2i)) = (p(g^i) - p(g^(n/2 + i))) / (2 g^i)
-        // so
-        //     result(g^(2i)) = p_e(g^(2i)) + beta p_o(g^(2i))
-        //                    = (1/2 + beta/2 g_inv^i) p(g^i)
-        //                    + (1/2 - beta/2 g_inv^i) p(g^(n/2 + i))
-        let g_inv = F::two_adic_generator(log2_strict_usize(m.height()) + 1).inverse();
-        let one_half = F::ONE.halve();
-        let half_beta = beta * one_half;
-
-        // TODO: vectorize this (after we have packed extension fields)
-
-        // beta/2 times successive powers of g_inv
-        let mut powers = g_inv
-            .shifted_powers(half_beta)
-            .take(m.height())
-            .collect_vec();
-        reverse_slice_index_bits(&mut powers);
-
-        m.par_rows()
-            .zip(powers)
-            .map(|(mut row, power)| {
-                let (lo, hi) = row.next_tuple().unwrap();
-                (one_half + power) * lo + (one_half - power) * hi
-            })
-            .collect()
+    fn fold_matrix(&self, beta: F, m: RowMajorMatrixView<'_, F>) -> Vec<F> {
+        two_adic_fold_matrix::<Val, F>(beta, m)
     }
 }
 
-impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
-    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
-where
-    Val: TwoAdicField,
-    Dft: TwoAdicSubgroupDft<Val>,
-    InputMmcs: Mmcs<Val>,
-    FriMmcs: Mmcs<Challenge>,
-    Challenge: TwoAdicField + ExtensionField<Val>,
-    Challenger:
-        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
+impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
+    for TwoAdicFriGenericConfig<InputProof, InputError, CodewordField>
 {
-    type Domain = TwoAdicMultiplicativeCoset<Val>;
-    type Commitment = InputMmcs::Commitment;
-    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
-    type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
-    type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
+    type InputProof = InputProof;
+    type InputError = InputError;
 
-    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
-        let log_n = log2_strict_usize(degree);
-        TwoAdicMultiplicativeCoset {
-            log_n,
-            shift: Val::ONE,
-        }
+    fn extra_query_index_bits(&self) -> usize {
+        0
     }
 
-    fn commit(
-        &self,
-        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
-    ) -> (Self::Commitment, Self::ProverData) {
-        let ldes: Vec<_> = evaluations
-            .into_iter()
-            .map(|(domain, evals)| {
-                assert_eq!(domain.size(), evals.height());
-                let shift = Val::GENERATOR / domain.shift;
-                // Commit to the bit-reversed LDE.
-                self.dft
-                    .coset_lde_batch(evals, self.fri.log_blowup, shift)
-                    .bit_reverse_rows()
-                    .to_row_major_matrix()
-            })
-            .collect();
-
-        self.mmcs.commit(ldes)
+    fn fold_row(&self, index: usize, log_height: usize, beta: F, evals: &[F]) -> F {
+        two_adic_fold_row::<F, F>(index, log_height, beta, evals)
     }
 
-    fn get_evaluations_on_domain<'a>(
-        &self,
-        prover_data: &'a Self::ProverData,
-        idx: usize,
-        domain: Self::Domain,
-    ) -> impl Matrix<Val> + 'a {
-        // todo: handle extrapolation for LDEs we don't have
-        assert_eq!(domain.shift, Val::GENERATOR);
-        let lde = self.mmcs.get_matrices(prover_data)[idx];
-        assert!(lde.height() >= domain.size());
-        lde.split_rows(domain.size()).0.bit_reverse_rows()
+    fn fold_matrix(&self, beta: F, m: RowMajorMatrixView<'_, F>) -> Vec<F> {
+        two_adic_fold_matrix::<F, F>(beta, m)
     }
+}
 
-    fn open(
+fn two_adic_fold_row<Val: TwoAdicField, F: ExtensionField<Val>>(
+    index: usize,
+    log_height: usize,
+    beta: F,
+    evals: &[F],
+) -> F {
+    // The row holds p(x) and p(-x) for x = g^j, where g generates the domain before folding
+    // and j is the bit reversal of `index`.
+    let x =
+        Val::two_adic_generator(log_height + 1).exp_u64(reverse_bits_len(index, log_height) as u64);
+    fold_row_generic(evals.iter().copied(), beta, x.inverse())
+}
+
+fn two_adic_fold_matrix<Val: TwoAdicField, F: ExtensionField<Val>>(
+    beta: F,
+    m: RowMajorMatrixView<'_, F>,
+) -> Vec<F> {
+    // Row `i` holds p(g^j) and p(-g^j) for j the bit reversal of `i`, so its twiddle is
+    // g_inv^j; see `two_adic_fold_row`.
+    let g_inv = Val::two_adic_generator(log2_strict_usize(m.height()) + 1).inverse();
+
+    let mut twiddles = g_inv.powers().collect_n(m.height());
+    reverse_slice_index_bits(&mut twiddles);
+
+    p3_maybe_rayon::install(m.width() * m.height(), || {
+        fold_matrix_generic(beta, m, &twiddles)
+    })
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+{
+    /// Open each round's matrices at their points as [`Pcs::open`] does, with `prove_fri` proving
+    /// the reduced openings. It's given FRI's inputs and a function opening the input batches at a
+    /// query index.
+    #[allow(clippy::type_complexity)]
+    fn open_with<Challenge, Challenger, R>(
         &self,
-        // For each round,
         rounds: Vec<(
-            &Self::ProverData,
-            // for each matrix,
-            Vec<
-                // points to open
-                Vec<Challenge>,
-            >,
+            &InputMmcs::ProverData<RowMajorMatrix<Val>>,
+            Vec<Vec<Challenge>>,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        prove_fri: impl FnOnce(
+            &TwoAdicFriGenericConfigForMmcs<Val, InputMmcs>,
+            Vec<Vec<Challenge>>,
+            &mut Challenger,
+            &dyn Fn(usize) -> Vec<BatchOpening<Val, InputMmcs>>,
+        ) -> R,
+    ) -> (OpenedValues<Challenge>, R)
+    where
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>,
+    {
         /*
 
         A quick rundown of the optimizations in this function:
@@ -213,7 +228,7 @@ where
         of a matrix, then multiply by an "alpha offset" when accumulating.
               a^0 x0 + a^1 x1 + a^2 x2 + a^3 x3 + ...
             = a^0 ( a^0 x0 + a^1 x1 ) + a^2 ( a^0 x2 + a^1 x3 ) + ...
-            (see `alpha_pows`, `alpha_pow_offset`, `num_reduced`)
+            (see `alpha_pows`, `alpha_pow_offset`, `alpha_offsets`)
 
         - For each unique point z, we precompute 1/(X-z) for the largest subgroup opened at this point.
         Since we compute it in bit-reversed order, smaller subgroups can simply truncate the vector.
@@ -265,15 +280,31 @@ where
         // for that point, and precompute 1/(z - X) for the largest subgroup (in bitrev order).
         let inv_denoms = compute_inverse_denominators(&mats_and_points, Val::GENERATOR);
 
+        let shapes = mats_and_points
+            .iter()
+            .map(|(mats, points)| {
+                izip!(mats, *points)
+                    .map(|(mat, points_for_mat)| OpeningShape {
+                        log_height: log2_strict_usize(mat.height()),
+                        width: mat.width(),
+                        num_points: points_for_mat.len(),
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        let offsets = alpha_power_offsets(&shapes);
+
         let mut all_opened_values: OpenedValues<Challenge> = vec![];
 
         let mut reduced_openings: [_; 32] = core::array::from_fn(|_| None);
-        let mut num_reduced = [0; 32];
 
-        for (mats, points) in mats_and_points {
+        for ((mats, points), offsets) in izip!(mats_and_points, offsets) {
             let opened_values_for_round = all_opened_values.pushed_mut(vec![]);
-            for (mat, points_for_mat) in izip!(mats, points) {
+            for (mat, points_for_mat, offset) in izip!(mats, points, offsets) {
                 let log_height = log2_strict_usize(mat.height());
+                let mut point_weights = alpha.powers();
+                point_weights.advance_by(offset);
+                let mut point_weights = point_weights.strided(mat.width() as u64);
                 let reduced_opening_for_log_height = reduced_openings[log_height]
                     .get_or_insert_with(|| vec![Challenge::ZERO; mat.height()]);
                 debug_assert_eq!(reduced_opening_for_log_height.len(), mat.height());
@@ -290,29 +321,32 @@ where
                             let (low_coset, _) = mat.split_rows(h);
                             let mut inv_denoms = inv_denoms.get(&point).unwrap()[..h].to_vec();
                             reverse_slice_index_bits(&mut inv_denoms);
-                            interpolate_coset(
-                                &BitReversalPerm::new_view(low_coset),
-                                Val::GENERATOR,
-                                point,
-                                Some(&inv_denoms),
-                            )
+                            p3_maybe_rayon::install(mat.width() * h, || {
+                                interpolate_coset(
+                                    &BitReversalPerm::new_view(low_coset),
+                                    Val::GENERATOR,
+                                    point,
+                                    Some(&inv_denoms),
+                                )
+                            })
                         });
 
-                    let alpha_pow_offset = alpha.exp_u64(num_reduced[log_height] as u64);
+                    let alpha_pow_offset = point_weights.next().unwrap();
                     let reduced_ys: Challenge = dot_product(alpha.powers(), ys.iter().copied());
 
                     info_span!("reduce rows").in_scope(|| {
-                        mat.dot_ext_powers(alpha)
-                            .zip(reduced_opening_for_log_height.par_iter_mut())
-                            // This might be longer, but zip will truncate to smaller subgroup
-                            // (which is ok because it's bitrev)
-                            .zip(inv_denoms.get(&point).unwrap().par_iter())
-                            .for_each(|((reduced_row, ro), &inv_denom)| {
-                                *ro += alpha_pow_offset * (reduced_ys - reduced_row) * inv_denom
-                            });
+                        p3_maybe_rayon::install(mat.width() * mat.height(), || {
+                            mat.dot_ext_powers(alpha)
+                                .zip(reduced_opening_for_log_height.par_iter_mut())
+                                // This might be longer, but zip will truncate to smaller subgroup
+                                // (which is ok because it's bitrev)
+                                .zip(inv_denoms.get(&point).unwrap().par_iter())
+                                .for_each(|((reduced_row, ro), &inv_denom)| {
+                                    *ro += alpha_pow_offset * (reduced_ys - reduced_row) * inv_denom
+                                });
+                        })
                     });
 
-                    num_reduced[log_height] += mat.width();
                     opened_values_for_mat.push(ys);
                 }
             }
@@ -322,25 +356,117 @@ where
 
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
-
-        let fri_proof = prover::prove(&g, &self.fri, fri_input, challenger, |index| {
+        let open_input = |index: usize| -> Vec<BatchOpening<Val, InputMmcs>> {
             rounds
                 .iter()
                 .map(|(data, _)| {
                     let log_max_height = log2_strict_usize(self.mmcs.get_max_height(data));
-                    let bits_reduced = log_global_max_height - log_max_height;
-                    let reduced_index = index >> bits_reduced;
-                    let (opened_values, opening_proof) = self.mmcs.open_batch(reduced_index, data);
+                    let row = input_query_row(index, log_global_max_height, log_max_height);
+                    let (opened_values, opening_proof) = self.mmcs.open_batch(row, data);
                     BatchOpening {
                         opened_values,
                         opening_proof,
                     }
                 })
                 .collect()
-        });
+        };
+        let fri_proof = prove_fri(&g, fri_input, challenger, &open_input);
 
         (all_opened_values, fri_proof)
     }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
+    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger:
+        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
+{
+    type Domain = TwoAdicMultiplicativeCoset<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
+    type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        let log_n = log2_strict_usize(degree);
+        TwoAdicMultiplicativeCoset {
+            log_n,
+            shift: Val::ONE,
+        }
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let ldes: Vec<_> = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                assert_eq!(domain.size(), evals.height());
+                let shift = Val::GENERATOR / domain.shift;
+                // Commit to the bit-reversed LDE.
+                self.dft
+                    .coset_lde_batch(evals, self.fri.log_blowup, shift)
+                    .bit_reverse_rows()
+                    .to_row_major_matrix()
+            })
+            .collect();
+
+        self.mmcs.commit(ldes)
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> impl Matrix<Val> + 'a {
+        // todo: handle extrapolation for LDEs we don't have
+        assert_eq!(domain.shift, Val::GENERATOR);
+        let lde = self.mmcs.get_matrices(prover_data)[idx];
+        assert!(lde.height() >= domain.size());
+        lde.split_rows(domain.size()).0.bit_reverse_rows()
+    }
+
+    fn max_log_evaluation_blowup(&self) -> Option<usize> {
+        // Evaluations are read off the committed LDE, which isn't extrapolated any further.
+        Some(self.fri.log_blowup)
+    }
+
+    fn max_log_domain_size(&self) -> Option<usize> {
+        // The LDE of a committed matrix must still fit in the two-adic subgroup.
+        Some(Val::TWO_ADICITY.saturating_sub(self.fri.log_blowup))
+    }
+
+    fn query_parameters(&self) -> Option<QueryParameters> {
+        Some(self.fri.query_parameters())
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::Error> {
+        let (opened_values, fri_proof) =
+            self.open_with(rounds, challenger, |g, inputs, challenger, open_input| {
+                prover::prove(g, &self.fri, inputs, challenger, open_input)
+            });
+        Ok((opened_values, fri_proof.map_err(FriError::Prover)?))
+    }
 
     fn verify(
         &self,
@@ -367,87 +493,439 @@ where
         let alpha: Challenge = challenger.sample_ext_element();
 
         let log_global_max_height =
-            proof.commit_phase_commits.len() + self.fri.log_blowup + self.fri.log_final_poly_len;
+            proof.log_folding_factor() + self.fri.log_blowup + self.fri.log_final_poly_len;
 
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
 
-        verifier::verify(&g, &self.fri, proof, challenger, |index, input_proof| {
-            // TODO: separate this out into functions
+        let shapes = rounds
+            .iter()
+            .map(|(_, mats)| {
+                mats.iter()
+                    .map(|(domain, points_and_values)| OpeningShape {
+                        log_height: log2_strict_usize(domain.size()) + self.fri.log_blowup,
+                        width: points_and_values
+                            .first()
+                            .map_or(0, |(_, values)| values.len()),
+                        num_points: points_and_values.len(),
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        let offsets = alpha_power_offsets(&shapes);
+
+        // Check the shape of every query's openings up front, so that reducing them below can't
+        // index out of bounds. Matrices taller than the first FRI round couldn't be queried.
+        let valid_shape = log_global_max_height <= Val::TWO_ADICITY
+            && izip!(&rounds, &shapes).all(|((_, mats), shapes)| {
+                !mats.is_empty()
+                    && izip!(mats, shapes).all(|((_, points_and_values), shape)| {
+                        shape.log_height <= log_global_max_height
+                            && points_and_values
+                                .iter()
+                                .all(|(_, values)| values.len() == shape.width)
+                    })
+            })
+            && proof.query_proofs.iter().all(|qp| {
+                qp.input_proof.len() == rounds.len()
+                    && izip!(&qp.input_proof, &shapes).all(|(batch_opening, shapes)| {
+                        batch_opening.opened_values.len() == shapes.len()
+                            && izip!(&batch_opening.opened_values, shapes)
+                                .all(|(values, shape)| values.len() == shape.width)
+                    })
+            });
+        if !valid_shape {
+            return Err(FriError::InvalidProofShape);
+        }
+        // FRI's inputs are the reduced openings of each height, tallest first.
+        let log_heights = shapes
+            .iter()
+            .flatten()
+            .map(|shape| shape.log_height)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .rev()
+            .collect_vec();
+
+        verifier::verify(
+            &g,
+            &self.fri,
+            &log_heights,
+            proof,
+            challenger,
+            |index, input_proof| {
+                // TODO: separate this out into functions
 
-            // log_height -> (alpha_pow, reduced_opening)
-            let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
+                // log_height -> reduced_opening
+                let mut reduced_openings = BTreeMap::<usize, Challenge>::new();
 
-            for (batch_opening, (batch_commit, mats)) in izip!(input_proof, &rounds) {
-                let batch_heights = mats
-                    .iter()
-                    .map(|(domain, _)| domain.size() << self.fri.log_blowup)
-                    .collect_vec();
-                let batch_dims = batch_heights
-                    .iter()
-                    // TODO: MMCS doesn't really need width; we put 0 for now.
-                    .map(|&height| Dimensions { width: 0, height })
-                    .collect_vec();
-
-                let batch_max_height = batch_heights.iter().max().expect("Empty batch?");
-                let log_batch_max_height = log2_strict_usize(*batch_max_height);
-                let bits_reduced = log_global_max_height - log_batch_max_height;
-                let reduced_index = index >> bits_reduced;
-
-                self.mmcs.verify_batch(
-                    batch_commit,
-                    &batch_dims,
-                    reduced_index,
-                    &batch_opening.opened_values,
-                    &batch_opening.opening_proof,
-                )?;
-                for (mat_opening, (mat_domain, mat_points_and_values)) in
-                    izip!(&batch_opening.opened_values, mats)
+                for (batch_opening, (batch_commit, mats), offsets, shapes) in
+                    izip!(input_proof, &rounds, &offsets, &shapes)
                 {
-                    let log_height = log2_strict_usize(mat_domain.size()) + self.fri.log_blowup;
+                    // The widths are those of the claimed evaluations, which the shape check
+                    // above held the opened rows to.
+                    let batch_dims = shapes
+                        .iter()
+                        .map(|shape| Dimensions {
+                            width: shape.width,
+                            height: 1 << shape.log_height,
+                        })
+                        .collect_vec();
 
-                    let bits_reduced = log_global_max_height - log_height;
-                    let rev_reduced_index = reverse_bits_len(index >> bits_reduced, log_height);
+                    let log_batch_max_height = shapes
+                        .iter()
+                        .map(|shape| shape.log_height)
+                        .max()
+                        .expect("Empty batch?");
+
+                    self.mmcs.verify_batch(
+                        batch_commit,
+                        &batch_dims,
+                        input_query_row(index, log_global_max_height, log_batch_max_height),
+                        &batch_opening.opened_values,
+                        &batch_opening.opening_proof,
+                    )?;
+                    for (mat_opening, (_, mat_points_and_values), &offset, shape) in
+                        izip!(&batch_opening.opened_values, mats, offsets, shapes)
+                    {
+                        let log_height = shape.log_height;
+
+                        let row = input_query_row(index, log_global_max_height, log_height);
+                        let rev_reduced_index = reverse_bits_len(row, log_height);
+
+                        // todo: this can be nicer with domain methods?
+
+                        let x = Val::GENERATOR
+                            * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
+
+                        let ro = reduced_openings
+                            .entry(log_height)
+                            .or_insert(Challenge::ZERO);
+
+                        let mut point_weights = alpha.powers();
+                        point_weights.advance_by(offset);
+                        let point_weights = point_weights.strided(shape.width as u64);
+                        for ((z, ps_at_z), point_weight) in
+                            izip!(mat_points_and_values, point_weights)
+                        {
+                            for (&p_at_x, &p_at_z, alpha_pow) in
+                                izip!(mat_opening, ps_at_z, alpha.shifted_powers(point_weight))
+                            {
+                                let quotient = (-p_at_z + p_at_x) / (-*z + x);
+                                *ro += alpha_pow * quotient;
+                            }
+                        }
+                    }
+                }
 
-                    // todo: this can be nicer with domain methods?
+                // `reduced_openings` would have a log_height = log_blowup entry only if there was a
+                // trace matrix of height 1. Its polynomial is constant, so the reduced opening is zero
+                // unless the claimed evaluations are wrong, and can be skipped as it will not be checked
+                // against any commit phase commit. A nonzero one is left for FRI to reject.
+                if reduced_openings
+                    .get(&self.fri.log_blowup)
+                    .is_some_and(|ro| ro.is_zero())
+                {
+                    reduced_openings.remove(&self.fri.log_blowup);
+                }
 
-                    let x = Val::GENERATOR
-                        * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
+                // Return reduced openings descending by log_height.
+                Ok(reduced_openings.into_iter().rev().collect())
+            },
+        )
+    }
+}
+
+/// An opening is checkpointed after each round of FRI's commit phase, see
+/// [`prover::prove_with_checkpoints`].
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> ResumablePcs<Challenge, Challenger>
+    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger: FieldChallenger<Val>
+        + CanObserve<FriMmcs::Commitment>
+        + GrindingChallenger<Witness = Val>
+        + CanSnapshot,
+{
+    type OpeningCheckpoint = FriCheckpoint<Challenge, FriMmcs::Commitment, Challenger::State>;
 
-                    let (alpha_pow, ro) = reduced_openings
-                        .entry(log_height)
-                        .or_insert((Challenge::ONE, Challenge::ZERO));
+    fn open_with_checkpoints(
+        &self,
+        rounds: Vec<(&Self::ProverData, Vec<Vec<Challenge>>)>,
+        challenger: &mut Challenger,
+        resume: Option<Self::OpeningCheckpoint>,
+        on_checkpoint: &mut dyn FnMut(Self::OpeningCheckpoint),
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::Error> {
+        let (opened_values, fri_proof) = self.open_with(
+            rounds,
+            challenger,
+            |g, inputs, challenger, open_input| match resume {
+                Some(checkpoint) => prover::prove_resume(
+                    g,
+                    &self.fri,
+                    inputs,
+                    checkpoint,
+                    challenger,
+                    open_input,
+                    on_checkpoint,
+                )
+                .map_err(FriError::Checkpoint),
+                None => prover::prove_with_checkpoints(
+                    g,
+                    &self.fri,
+                    inputs,
+                    challenger,
+                    open_input,
+                    on_checkpoint,
+                )
+                .map_err(FriError::Prover),
+            },
+        );
+        Ok((opened_values, fri_proof?))
+    }
+}
 
-                    for (z, ps_at_z) in mat_points_and_values {
-                        for (&p_at_x, &p_at_z) in izip!(mat_opening, ps_at_z) {
-                            let quotient = (-p_at_z + p_at_x) / (-*z + x);
-                            *ro += *alpha_pow * quotient;
-                            *alpha_pow *= alpha;
-                        }
-                    }
-                }
+/// Costs following the allocations of [`Pcs::commit`] and [`Pcs::open`] above, with a DFT which
+/// computes LDEs in place like `Radix2DitParallel`.
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge> PcsCostModel<Challenge>
+    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    InputMmcs: MmcsCostModel<Val>,
+    FriMmcs: MmcsCostModel<Challenge>,
+    Challenge: ExtensionField<Val>,
+{
+    fn commit_cost(&self, dimensions: &[Dimensions]) -> Cost {
+        let val_bytes = size_of::<Val>();
+        let lde_dimensions = dimensions
+            .iter()
+            .map(|dims| self.lde_dimensions(*dims))
+            .collect_vec();
+
+        let mut cost = Cost::default();
+        for (dims, lde_dims) in izip!(dimensions, &lde_dimensions) {
+            // The evaluations are interpolated in place and grown to hold the LDE, which is
+            // copied once more when it's taken out of its bit-reversed view.
+            let lde_bytes = lde_dims.width * lde_dims.height * val_bytes;
+            let evals_bytes = dims.width * dims.height * val_bytes;
+            let lde = Cost {
+                // One inverse DFT, then a DFT of each coset.
+                dft_butterflies: (1 + self.fri.blowup())
+                    * (dims.height / 2)
+                    * log2_strict_usize(dims.height)
+                    * dims.width,
+                ..Cost::allocation(lde_bytes - evals_bytes)
+            };
+            cost = cost.then(lde).then(Cost::temporary(lde_bytes));
+        }
+        cost.then(self.mmcs.commit_cost(&lde_dimensions))
+    }
+
+    fn open_cost(&self, rounds: &[Vec<(Dimensions, usize)>]) -> Cost {
+        let ext_bytes = size_of::<Challenge>();
+        let mats = rounds
+            .iter()
+            .flatten()
+            .map(|&(dims, num_points)| (self.lde_dimensions(dims), num_points))
+            .collect_vec();
+        let num_points = mats
+            .iter()
+            .map(|&(_, num_points)| num_points)
+            .max()
+            .unwrap();
+        let max_height_at = |point: usize| {
+            mats.iter()
+                .filter(|&&(_, num_points)| num_points > point)
+                .map(|(lde, _)| lde.height)
+                .max()
+                .unwrap()
+        };
+
+        // The inverse denominators for each point, over the largest LDE opened at it.
+        let subgroup_bytes = max_height_at(0) * size_of::<Val>();
+        let mut cost = (0..num_points)
+            .fold(Cost::allocation(subgroup_bytes), |cost, point| {
+                let height = max_height_at(point);
+                let inverses = Cost {
+                    extension_muls: 3 * height,
+                    ..Cost::allocation(height * ext_bytes)
+                };
+                // The differences being inverted.
+                cost.then(inverses.then(Cost::temporary(height * ext_bytes)))
+            })
+            .freeing(subgroup_bytes);
+
+        let mut reduced_opening_heights = BTreeSet::new();
+        for &(lde, num_points) in &mats {
+            if reduced_opening_heights.insert(lde.height) {
+                cost = cost.then(Cost::allocation(lde.height * ext_bytes));
+            }
+            let height = lde.height >> self.fri.log_blowup;
+            for _ in 0..num_points {
+                // Interpolating over the trace domain, scaling its inverse denominators.
+                let opened_values = Cost {
+                    extension_muls: height * (lde.width + 1),
+                    ..Cost::temporary(2 * height * ext_bytes)
+                };
+                // Reducing each row, then mixing it into the reduced opening.
+                let reduced_rows = Cost {
+                    extension_muls: lde.height * (lde.width + 2),
+                    ..Cost::default()
+                };
+                cost = cost.then(opened_values).then(reduced_rows);
             }
+        }
 
-            // `reduced_openings` would have a log_height = log_blowup entry only if there was a
-            // trace matrix of height 1. In this case the reduced opening can be skipped as it will
-            // not be checked against any commit phase commit.
-            if let Some((_alpha_pow, ro)) = reduced_openings.remove(&self.fri.log_blowup) {
-                debug_assert!(ro.is_zero());
+        // The commit phase of FRI, which commits to each codeword before folding it, starting from
+        // the largest reduced opening and adding in the others as it reaches their heights.
+        let log_heights = reduced_opening_heights
+            .iter()
+            .rev()
+            .map(|&height| log2_strict_usize(height))
+            .collect_vec();
+        let log_arities = self.fri.commit_phase_log_arities(
+            &log_heights,
+            self.fri.log_blowup + self.fri.log_final_poly_len,
+        );
+        let mut len = max_height_at(0);
+        for log_arity in log_arities {
+            let leaves = Dimensions {
+                width: 1 << log_arity,
+                height: len >> log_arity,
+            };
+            cost = cost.then(self.fri.mmcs.commit_cost(&[leaves]));
+            if self.fri.opens_directly(leaves.height) {
+                cost = cost.then(Cost::allocation(len * ext_bytes));
+            }
+            // Each fold in half costs a multiplication per evaluation it folds.
+            let folded = Cost {
+                extension_muls: 2 * (len - leaves.height),
+                ..Cost::allocation(leaves.height * ext_bytes)
+            };
+            // The twiddles are computed in the extension field.
+            cost = cost.then(folded.then(Cost::temporary(leaves.height * ext_bytes)));
+            if reduced_opening_heights.contains(&leaves.height) {
+                cost = cost.freeing(leaves.height * ext_bytes);
             }
+            len = leaves.height;
+        }
 
-            // Return reduced openings descending by log_height.
-            Ok(reduced_openings
-                .into_iter()
-                .rev()
-                .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
-                .collect())
-        })
-        .expect("fri err");
+        // Everything but the proof is freed by the time it returns.
+        cost.retained_bytes = 0;
+        cost
+    }
+
+    fn verify_cost(&self, rounds: &[Vec<(Dimensions, usize)>]) -> Cost {
+        let lde_rounds = rounds
+            .iter()
+            .map(|round| {
+                round
+                    .iter()
+                    .map(|&(dims, num_points)| (self.lde_dimensions(dims), num_points))
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        // Each query opens every round, and adds the quotient of each opened value into the
+        // reduced opening for its height: dividing, weighting, and stepping to the next weight.
+        let mut query = Cost::default();
+        for round in &lde_rounds {
+            let dims = round.iter().map(|&(lde, _)| lde).collect_vec();
+            let opened_values: usize = round
+                .iter()
+                .map(|&(lde, num_points)| lde.width * num_points)
+                .sum();
+            query = query.then(self.mmcs.verify_batch_cost(&dims)).then(Cost {
+                extension_muls: 3 * opened_values,
+                ..Cost::default()
+            });
+        }
 
-        Ok(())
+        let heights = lde_rounds
+            .iter()
+            .flatten()
+            .map(|(lde, _)| lde.height)
+            .collect::<BTreeSet<_>>();
+        let log_heights = heights
+            .iter()
+            .rev()
+            .map(|&height| log2_strict_usize(height))
+            .collect_vec();
+        let log_arities = self.fri.commit_phase_log_arities(
+            &log_heights,
+            self.fri.log_blowup + self.fri.log_final_poly_len,
+        );
+        let mut cost = Cost::default();
+        let mut len = *heights.last().unwrap();
+        for log_arity in log_arities {
+            let leaves = Dimensions {
+                width: 1 << log_arity,
+                height: len >> log_arity,
+            };
+            if self.fri.opens_directly(leaves.height) {
+                // The codeword is committed to again, once for all the queries.
+                cost = cost.then(self.fri.mmcs.commit_cost(&[leaves]));
+            } else {
+                query = query.then(self.fri.mmcs.verify_batch_cost(&[leaves]));
+            }
+            // Folding the opened row in half until one evaluation is left.
+            query.extension_muls += 3 * ((1 << log_arity) - 1);
+            len = leaves.height;
+        }
+        // Finding the query's point by squaring, and evaluating the final polynomial there.
+        query.extension_muls += log_heights[0] + 2 * self.fri.final_poly_len();
+
+        let cost = (0..self.fri.num_queries).fold(cost, |cost, _| cost.then(query));
+        Cost {
+            retained_bytes: 0,
+            peak_bytes: 0,
+            ..cost
+        }
     }
 }
 
+/// The shape of one matrix in a batch opening, as far as the weights of its columns are concerned.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OpeningShape {
+    /// The log of the matrix's height on the LDE domain.
+    pub log_height: usize,
+    pub width: usize,
+    /// The number of points at which the matrix is opened.
+    pub num_points: usize,
+}
+
+/// Assigns each opened column a power of the batch combination challenge `alpha`, by which its
+/// quotient is weighted in the reduced opening for its height.
+///
+/// Matrices of the same height share a sequence of powers starting at `alpha^0`, which is handed
+/// out in the canonical order: rounds in order, then the matrices of each round, then the points
+/// at which each matrix is opened, then the matrix's columns. For each round and matrix, this
+/// returns the exponent of the weight of its first column at its first point; column `i` at point
+/// `j` is then weighted by `alpha^(offset + j * width + i)`.
+///
+/// The prover and verifier both derive their weights from this, so they cannot disagree.
+pub fn alpha_power_offsets(shapes: &[Vec<OpeningShape>]) -> Vec<Vec<u64>> {
+    let mut next_offsets = [0_u64; 32];
+    shapes
+        .iter()
+        .map(|round| {
+            round
+                .iter()
+                .map(|shape| {
+                    let next_offset = &mut next_offsets[shape.log_height];
+                    let offset = *next_offset;
+                    *next_offset += (shape.num_points * shape.width) as u64;
+                    offset
+                })
+                .collect()
+        })
+        .collect()
+}
+
 #[instrument(skip_all)]
 fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matrix<F>>(
     mats_and_points: &[(Vec<M>, &Vec<Vec<EF>>)],
@@ -492,3 +970,152 @@ fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matri
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_field::FieldAlgebra;
+
+    use super::*;
+
+    fn shape(log_height: usize, width: usize, num_points: usize) -> OpeningShape {
+        OpeningShape {
+            log_height,
+            width,
+            num_points,
+        }
+    }
+
+    /// The exponent of the weight of every opened `(round, matrix, point, column)`, found by
+    /// walking them in the canonical order and counting separately for each height.
+    fn reference_exponents(shapes: &[Vec<OpeningShape>]) -> Vec<(usize, usize, usize, usize, u64)> {
+        let mut counters = BTreeMap::<usize, u64>::new();
+        let mut exponents = vec![];
+        for (r, round) in shapes.iter().enumerate() {
+            for (m, shape) in round.iter().enumerate() {
+                for p in 0..shape.num_points {
+                    for c in 0..shape.width {
+                        let counter = counters.entry(shape.log_height).or_default();
+                        exponents.push((r, m, p, c, *counter));
+                        *counter += 1;
+                    }
+                }
+            }
+        }
+        exponents
+    }
+
+    #[test]
+    fn offsets_follow_canonical_order() {
+        let shapes = vec![
+            vec![shape(5, 3, 2), shape(4, 2, 1)],
+            vec![shape(5, 1, 1)],
+            vec![shape(4, 4, 2), shape(5, 2, 3), shape(3, 7, 0)],
+        ];
+        assert_eq!(
+            alpha_power_offsets(&shapes),
+            vec![vec![0, 0], vec![6], vec![2, 7, 0]]
+        );
+    }
+
+    #[test]
+    fn strided_weights_match_reference() {
+        type F = p3_baby_bear::BabyBear;
+        let alpha = F::from_canonical_u32(7);
+
+        let cases = vec![
+            vec![],
+            vec![vec![shape(3, 4, 1)]],
+            vec![vec![shape(3, 4, 2), shape(3, 1, 3)], vec![shape(3, 5, 1)]],
+            vec![
+                vec![shape(6, 2, 2), shape(4, 3, 1)],
+                vec![shape(4, 0, 2), shape(6, 1, 0)],
+                vec![shape(6, 3, 1), shape(4, 2, 2)],
+            ],
+        ];
+        for shapes in cases {
+            let offsets = alpha_power_offsets(&shapes);
+            for (r, m, p, c, exponent) in reference_exponents(&shapes) {
+                // The weight as the prover and verifier compute it.
+                let mut point_weights = alpha.powers();
+                point_weights.advance_by(offsets[r][m]);
+                let point_weight = point_weights
+                    .strided(shapes[r][m].width as u64)
+                    .nth(p)
+                    .unwrap();
+                let weight = alpha.shifted_powers(point_weight).nth(c).unwrap();
+                assert_eq!(weight, alpha.exp_u64(exponent));
+            }
+        }
+    }
+
+    #[test]
+    fn fold_matrix_same_as_row() {
+        type F = p3_baby_bear::BabyBear;
+        type EF = p3_field::extension::BinomialExtensionField<F, 4>;
+        let config = TwoAdicFriGenericConfig::<(), (), F>(PhantomData);
+        let mut rng = rand::thread_rng();
+
+        for log_folded_height in 0..8 {
+            let m = RowMajorMatrix::<EF>::rand(&mut rng, 1 << log_folded_height, 2);
+            let beta: EF = rand::Rng::gen(&mut rng);
+
+            let mat_folded = config.fold_matrix(beta, m.as_view());
+            let row_folded = (0..m.height())
+                .map(|i| config.fold_row(i, log_folded_height, beta, &m.row_slice(i)))
+                .collect_vec();
+            assert_eq!(mat_folded, row_folded);
+
+            // Folding over cosets of the extension itself, as by default, gives the same twiddles.
+            let default_config = TwoAdicFriGenericConfig::<(), ()>(PhantomData);
+            assert_eq!(default_config.fold_matrix(beta, m.as_view()), mat_folded);
+        }
+    }
+
+    #[test]
+    fn fold_with_arity_4_combines_residue_classes() {
+        type F = p3_baby_bear::BabyBear;
+        type EF = p3_field::extension::BinomialExtensionField<F, 4>;
+        let config = TwoAdicFriGenericConfig::<(), (), F>(PhantomData);
+        let mut rng = rand::thread_rng();
+        let log_n = 6;
+
+        let eval = |coeffs: &[EF], x: EF| {
+            coeffs
+                .iter()
+                .rev()
+                .fold(EF::ZERO, |acc, &coeff| acc * x + coeff)
+        };
+        // Evaluations over the subgroup of order `2^log_bits`, in bit-reversed order.
+        let evals_over = |coeffs: &[EF], log_bits: usize| {
+            let mut evals = EF::two_adic_generator(log_bits)
+                .powers()
+                .take(1 << log_bits)
+                .map(|x| eval(coeffs, x))
+                .collect_vec();
+            reverse_slice_index_bits(&mut evals);
+            evals
+        };
+
+        let coeffs: Vec<EF> = (0..1 << log_n)
+            .map(|_| rand::Rng::gen(&mut rng))
+            .collect_vec();
+        let beta: EF = rand::Rng::gen(&mut rng);
+        let m = RowMajorMatrix::new(evals_over(&coeffs, log_n), 4);
+
+        // f_0 + beta f_1 + beta^2 f_2 + beta^3 f_3, where f_k has the coefficients of f whose
+        // degrees are k mod 4.
+        let combined: Vec<EF> = coeffs
+            .chunks_exact(4)
+            .map(|chunk| chunk.iter().zip(beta.powers()).map(|(&c, b)| c * b).sum())
+            .collect_vec();
+        let expected = evals_over(&combined, log_n - 2);
+
+        assert_eq!(config.fold_matrix_with_arity(beta, m.as_view()), expected);
+        let row_folded = (0..m.height())
+            .map(|i| config.fold_row_with_arity(i, log_n - 2, 2, beta, m.row(i).collect()))
+            .collect_vec();
+        assert_eq!(row_folded, expected);
+    }
+}
@@ -1,14 +1,15 @@
-use alloc::vec;
 use alloc::vec::Vec;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Dimensions;
 use p3_util::reverse_bits_len;
 
-use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof};
+use crate::prover::{FriCheckpointError, FriProverError};
+use crate::{CommitPhaseMode, CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof};
 
 #[derive(Debug)]
 pub enum FriError<CommitMmcsErr, InputError> {
@@ -17,11 +18,25 @@ pub enum FriError<CommitMmcsErr, InputError> {
     InputError(InputError),
     FinalPolyMismatch,
     InvalidPowWitness,
+    DirectOpeningMismatch,
+    ChallengeFieldTooSmall,
+    /// The config enables a feature which this FRI variant doesn't implement.
+    UnsupportedConfig,
+    /// The prover couldn't make an opening proof at all.
+    Prover(FriProverError),
+    /// An opening proof couldn't be resumed from a checkpoint, see
+    /// [`ResumablePcs`](p3_commit::ResumablePcs).
+    Checkpoint(FriCheckpointError),
 }
 
+/// Check `proof` that inputs of heights `2^log_heights`, in descending order, are low degree.
+///
+/// The heights are the verifier's own, e.g. from the domains it opens, so that the arity of each
+/// round is the one the config folds them with rather than whatever the proof claims.
 pub fn verify<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    log_heights: &[usize],
     proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
@@ -31,8 +46,9 @@ where
     Challenge: ExtensionField<Val> + TwoAdicField,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
-    G: FriGenericConfig<Challenge>,
+    G: FriGenericConfig<Challenge> + ?Sized,
 {
+    challenger.begin_phase("fri commit phase");
     let betas: Vec<Challenge> = proof
         .commit_phase_commits
         .iter()
@@ -42,28 +58,127 @@ where
         })
         .collect();
 
+    verify_after_commit_phase(
+        g,
+        config,
+        log_heights,
+        proof,
+        &betas,
+        challenger,
+        open_input,
+    )
+}
+
+/// Like [`verify`], but uses `betas` as the folding challenges of the commit phase rounds, rather
+/// than deriving them from `challenger`.
+///
+/// `challenger` neither observes the commit phase commitments nor samples the folding challenges,
+/// matching [`crate::prover::prove_with_external_challenges`]. This is only sound if the caller
+/// derived `betas` from an outer transcript which bound `proof.commit_phase_commits`, each one
+/// before the challenge of its round.
+pub fn verify_with_external_challenges<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    log_heights: &[usize],
+    proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+    betas: &[Challenge],
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
+) -> Result<(), FriError<M::Error, G::InputError>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge> + ?Sized,
+{
+    if betas.len() != proof.commit_phase_commits.len() {
+        return Err(FriError::InvalidProofShape);
+    }
+    verify_after_commit_phase(g, config, log_heights, proof, betas, challenger, open_input)
+}
+
+/// Check `proof` given the folding challenges of its commit phase rounds.
+fn verify_after_commit_phase<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    log_heights: &[usize],
+    proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+    betas: &[Challenge],
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
+) -> Result<(), FriError<M::Error, G::InputError>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger,
+    G: FriGenericConfig<Challenge> + ?Sized,
+{
     // Observe all coefficients of the final polynomial.
     proof
         .final_poly
         .iter()
         .for_each(|x| challenger.observe_ext_element(*x));
 
-    if proof.query_proofs.len() != config.num_queries {
+    if proof.query_proofs.len() != config.num_queries
+        || proof.final_poly.len() != config.final_poly_len()
+    {
+        return Err(FriError::InvalidProofShape);
+    }
+
+    let log_arities =
+        config.commit_phase_log_arities(log_heights, config.log_blowup + config.log_final_poly_len);
+    if proof.commit_phase_log_arities != log_arities
+        || proof.commit_phase_commits.len() != log_arities.len()
+    {
+        return Err(FriError::InvalidProofShape);
+    }
+    let log_max_height = proof.log_folding_factor() + config.log_blowup + config.log_final_poly_len;
+    // Larger domains have no generator to query them with.
+    if log_max_height > Challenge::TWO_ADICITY {
         return Err(FriError::InvalidProofShape);
     }
+    if Challenge::EXT_ORDER_BITS < config.min_challenge_bits(log_max_height) {
+        return Err(FriError::ChallengeFieldTooSmall);
+    }
 
     // Check PoW.
+    challenger.begin_phase("fri proof of work");
     if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
         return Err(FriError::InvalidPowWitness);
     }
 
-    let log_max_height =
-        proof.commit_phase_commits.len() + config.log_blowup + config.log_final_poly_len;
+    let num_queried_rounds = verify_commit_phase_modes(config, proof, log_max_height)?;
 
+    challenger.begin_phase("fri query phase");
     for qp in &proof.query_proofs {
-        let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
+        if qp.commit_phase_openings.len() != num_queried_rounds {
+            return Err(FriError::InvalidProofShape);
+        }
+
+        let index =
+            config.sample_query_index(challenger, log_max_height + g.extra_query_index_bits());
         let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
 
+        // The shape check above guarantees one opening per queried round.
+        let mut queried_openings = qp.commit_phase_openings.iter();
+        let steps = izip!(
+            betas,
+            &proof.commit_phase_commits,
+            &proof.commit_phase_modes,
+            &proof.commit_phase_log_arities
+        )
+        .map(|(beta, comm, mode, &log_arity)| {
+            let opening = match mode {
+                CommitPhaseMode::Queried => {
+                    CommitStepOpening::Queried(queried_openings.next().unwrap())
+                }
+                CommitPhaseMode::Direct(codeword) => CommitStepOpening::Direct(codeword),
+            };
+            (beta, comm, log_arity, opening)
+        });
+
         debug_assert!(
             ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
             "reduced openings sorted by height descending"
@@ -73,16 +188,12 @@ where
             g,
             config,
             index >> g.extra_query_index_bits(),
-            izip!(
-                &betas,
-                &proof.commit_phase_commits,
-                &qp.commit_phase_openings
-            ),
+            steps,
             ro,
             log_max_height,
         )?;
 
-        let final_poly_index = index >> (proof.commit_phase_commits.len());
+        let final_poly_index = index >> proof.log_folding_factor();
 
         let mut eval = Challenge::ZERO;
 
@@ -107,10 +218,62 @@ where
     Ok(())
 }
 
+/// Check that each commit phase round uses the mode the config asks for, and that each directly
+/// opened codeword has the right size and matches its commitment.
+///
+/// Returns the number of rounds which queries must open.
+fn verify_commit_phase_modes<F, M, Witness, InputProof, InputError>(
+    config: &FriConfig<M>,
+    proof: &FriProof<F, M, Witness, InputProof>,
+    log_max_height: usize,
+) -> Result<usize, FriError<M::Error, InputError>>
+where
+    F: Field,
+    M: Mmcs<F>,
+{
+    if proof.commit_phase_modes.len() != proof.commit_phase_commits.len() {
+        return Err(FriError::InvalidProofShape);
+    }
+
+    let mut num_queried_rounds = 0;
+    let mut log_height = log_max_height;
+    for (comm, mode, &log_arity) in izip!(
+        &proof.commit_phase_commits,
+        &proof.commit_phase_modes,
+        &proof.commit_phase_log_arities
+    ) {
+        log_height -= log_arity;
+        let height = 1 << log_height;
+        match mode {
+            CommitPhaseMode::Queried if !config.opens_directly(height) => num_queried_rounds += 1,
+            CommitPhaseMode::Direct(codeword)
+                if config.opens_directly(height) && codeword.len() == height << log_arity =>
+            {
+                let (recomputed, _) = config
+                    .mmcs
+                    .commit_matrix(RowMajorMatrix::new(codeword.clone(), 1 << log_arity));
+                if recomputed != *comm {
+                    return Err(FriError::DirectOpeningMismatch);
+                }
+            }
+            _ => return Err(FriError::InvalidProofShape),
+        }
+    }
+    Ok(num_queried_rounds)
+}
+
+/// How a single query checks one commit phase round.
+enum CommitStepOpening<'a, F: Field, M: Mmcs<F>> {
+    Queried(&'a CommitPhaseProofStep<F, M>),
+    /// A codeword which was already checked against its commitment.
+    Direct(&'a [F]),
+}
+
 type CommitStep<'a, F, M> = (
     &'a F,
     &'a <M as Mmcs<F>>::Commitment,
-    &'a CommitPhaseProofStep<F, M>,
+    usize,
+    CommitStepOpening<'a, F, M>,
 );
 
 fn verify_query<'a, G, F, M>(
@@ -124,40 +287,57 @@ fn verify_query<'a, G, F, M>(
 where
     F: Field,
     M: Mmcs<F> + 'a,
-    G: FriGenericConfig<F>,
+    G: FriGenericConfig<F> + ?Sized,
 {
     let mut folded_eval = F::ZERO;
     let mut ro_iter = reduced_openings.into_iter().peekable();
+    let mut log_height = log_max_height;
 
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+    for (&beta, comm, log_arity, opening) in steps {
+        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
             folded_eval += ro;
         }
 
-        let index_sibling = index ^ 1;
-        let index_pair = index >> 1;
-
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
-
-        let dims = &[Dimensions {
-            width: 2,
-            height: 1 << log_folded_height,
-        }];
-        config
-            .mmcs
-            .verify_batch(
-                comm,
-                dims,
-                index_pair,
-                &[evals.clone()],
-                &opening.opening_proof,
-            )
-            .map_err(FriError::CommitPhaseMmcsError)?;
-
-        index = index_pair;
-
-        folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
+        let log_folded_height = log_height - log_arity;
+        let index_in_row = index % (1 << log_arity);
+        let index_row = index >> log_arity;
+
+        let evals = match opening {
+            CommitStepOpening::Queried(opening) => {
+                if opening.sibling_values.len() != (1 << log_arity) - 1 {
+                    return Err(FriError::InvalidProofShape);
+                }
+                let mut evals = opening.sibling_values.clone();
+                evals.insert(index_in_row, folded_eval);
+
+                let dims = &[Dimensions {
+                    width: 1 << log_arity,
+                    height: 1 << log_folded_height,
+                }];
+                config
+                    .mmcs
+                    .verify_batch(
+                        comm,
+                        dims,
+                        index_row,
+                        &[evals.clone()],
+                        &opening.opening_proof,
+                    )
+                    .map_err(FriError::CommitPhaseMmcsError)?;
+                evals
+            }
+            CommitStepOpening::Direct(codeword) => {
+                if codeword[index] != folded_eval {
+                    return Err(FriError::DirectOpeningMismatch);
+                }
+                codeword[index_row << log_arity..(index_row + 1) << log_arity].to_vec()
+            }
+        };
+
+        index = index_row;
+        log_height = log_folded_height;
+
+        folded_eval = g.fold_row_with_arity(index, log_folded_height, log_arity, beta, evals);
     }
 
     debug_assert!(
@@ -165,10 +345,10 @@ where
         "index was {}",
         index,
     );
-    debug_assert!(
-        ro_iter.next().is_none(),
-        "verifier reduced_openings were not in descending order?"
-    );
+    // An input opened at a height which no round folds in would go unchecked.
+    if ro_iter.next().is_some() {
+        return Err(FriError::InvalidProofShape);
+    }
 
     Ok(folded_eval)
 }
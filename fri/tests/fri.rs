@@ -1,17 +1,22 @@
 use core::cmp::Reverse;
 use std::marker::PhantomData;
 
+use itertools::Itertools;
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
-use p3_challenger::{CanSampleBits, DuplexChallenger, FieldChallenger};
-use p3_commit::ExtensionMmcs;
+use p3_challenger::{CanObserve, CanSampleBits, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Mmcs};
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
-use p3_field::{Field, FieldAlgebra};
-use p3_fri::{prover, verifier, FriConfig, TwoAdicFriGenericConfig};
-use p3_matrix::dense::RowMajorMatrix;
+use p3_field::{Field, FieldAlgebra, FieldExtensionAlgebra};
+use p3_fri::verifier::FriError;
+use p3_fri::{
+    prover, verifier, CommitPhaseMode, FriConfig, FriGenericConfig, FriProof,
+    TwoAdicFriGenericConfig,
+};
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
-use p3_merkle_tree::MerkleTreeMmcs;
+use p3_merkle_tree::{MerkleTreeError, MerkleTreeMmcs};
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
 use p3_util::log2_strict_usize;
 use rand::{Rng, SeedableRng};
@@ -28,101 +33,150 @@ type ValMmcs =
 type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
 type MyFriConfig = FriConfig<ChallengeMmcs>;
+type MyInputProof = Vec<(usize, Challenge)>;
+type MyFriProof = FriProof<Challenge, ChallengeMmcs, Val, MyInputProof>;
+type MyFriError = FriError<<ChallengeMmcs as Mmcs<Challenge>>::Error, ()>;
 
-fn get_ldt_for_testing<R: Rng>(rng: &mut R, log_final_poly_len: usize) -> (Perm, MyFriConfig) {
-    let perm = Perm::new_from_rng_128(rng);
+fn get_ldt_for_testing(
+    perm: &Perm,
+    log_final_poly_len: usize,
+    max_direct_opening_height: usize,
+) -> MyFriConfig {
     let hash = MyHash::new(perm.clone());
     let compress = MyCompress::new(perm.clone());
     let mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
-    let fri_config = FriConfig {
+    FriConfig {
         log_blowup: 1,
         log_final_poly_len,
         num_queries: 10,
         proof_of_work_bits: 8,
+        max_direct_opening_height,
+        log_folding_arity: 1,
+        uniform_query_indices: false,
         mmcs,
-    };
-    (perm, fri_config)
+    }
 }
 
-fn do_test_fri_ldt<R: Rng>(rng: &mut R, log_final_poly_len: usize) {
-    let (perm, fc) = get_ldt_for_testing(rng, log_final_poly_len);
-    let dft = Radix2Dit::default();
+fn random_ldes<R: Rng>(rng: &mut R) -> Vec<RowMajorMatrix<Val>> {
+    random_ldes_of_degrees(rng, 5..10)
+}
 
+fn random_ldes_of_degrees<R: Rng>(
+    rng: &mut R,
+    deg_bits: impl IntoIterator<Item = usize>,
+) -> Vec<RowMajorMatrix<Val>> {
+    let dft = Radix2Dit::default();
     let shift = Val::GENERATOR;
 
-    let ldes: Vec<RowMajorMatrix<Val>> = (5..10)
+    deg_bits
+        .into_iter()
         .map(|deg_bits| {
             let evals = RowMajorMatrix::<Val>::rand_nonzero(rng, 1 << deg_bits, 16);
             let mut lde = dft.coset_lde_batch(evals, 1, shift);
             reverse_matrix_index_bits(&mut lde);
             lde
         })
-        .collect();
-
-    let (proof, p_sample) = {
-        // Prover world
-        let mut chal = Challenger::new(perm.clone());
-        let alpha: Challenge = chal.sample_ext_element();
+        .collect()
+}
 
-        let input: [_; 32] = core::array::from_fn(|log_height| {
-            let matrices_with_log_height: Vec<&RowMajorMatrix<Val>> = ldes
-                .iter()
-                .filter(|m| log2_strict_usize(m.height()) == log_height)
+/// Combine `ldes` of equal height with powers of `alpha`, tallest first.
+fn reduce_ldes(alpha: Challenge, ldes: &[RowMajorMatrix<Val>]) -> Vec<Vec<Challenge>> {
+    let input: [_; 32] = core::array::from_fn(|log_height| {
+        let matrices_with_log_height: Vec<&RowMajorMatrix<Val>> = ldes
+            .iter()
+            .filter(|m| log2_strict_usize(m.height()) == log_height)
+            .collect();
+        if matrices_with_log_height.is_empty() {
+            None
+        } else {
+            let reduced: Vec<Challenge> = (0..(1 << log_height))
+                .map(|r| {
+                    alpha
+                        .powers()
+                        .zip(matrices_with_log_height.iter().flat_map(|m| m.row(r)))
+                        .map(|(alpha_pow, v)| alpha_pow * v)
+                        .sum()
+                })
                 .collect();
-            if matrices_with_log_height.is_empty() {
-                None
-            } else {
-                let reduced: Vec<Challenge> = (0..(1 << log_height))
-                    .map(|r| {
-                        alpha
-                            .powers()
-                            .zip(matrices_with_log_height.iter().flat_map(|m| m.row(r)))
-                            .map(|(alpha_pow, v)| alpha_pow * v)
-                            .sum()
-                    })
-                    .collect();
-                Some(reduced)
-            }
-        });
-
-        let input: Vec<Vec<Challenge>> = input.into_iter().rev().flatten().collect();
-
-        let log_max_height = log2_strict_usize(input[0].len());
-
-        let proof = prover::prove(
-            &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
-            &fc,
-            input.clone(),
-            &mut chal,
-            |idx| {
-                // As our "input opening proof", just pass through the literal reduced openings.
-                let mut ro = vec![];
-                for v in &input {
-                    let log_height = log2_strict_usize(v.len());
-                    ro.push((log_height, v[idx >> (log_max_height - log_height)]));
-                }
-                ro.sort_by_key(|(lh, _)| Reverse(*lh));
-                ro
-            },
-        );
+            Some(reduced)
+        }
+    });
 
-        (proof, chal.sample_bits(8))
-    };
+    input.into_iter().rev().flatten().collect()
+}
+
+/// As our "input opening proof", just pass through the literal reduced openings.
+fn open_reduced(input: &[Vec<Challenge>]) -> impl Fn(usize) -> MyInputProof + '_ {
+    let log_max_height = log2_strict_usize(input[0].len());
+    move |idx| {
+        let mut ro = vec![];
+        for v in input {
+            let log_height = log2_strict_usize(v.len());
+            ro.push((log_height, v[idx >> (log_max_height - log_height)]));
+        }
+        ro.sort_by_key(|(lh, _)| Reverse(*lh));
+        ro
+    }
+}
+
+/// Prove that `ldes` are low degree, returning the proof and a sample from the final transcript.
+fn prove_ldes(perm: &Perm, fc: &MyFriConfig, ldes: &[RowMajorMatrix<Val>]) -> (MyFriProof, usize) {
+    let mut chal = Challenger::new(perm.clone());
+    let alpha: Challenge = chal.sample_ext_element();
+    let input = reduce_ldes(alpha, ldes);
+
+    let proof = prover::prove(
+        &TwoAdicFriGenericConfig::<MyInputProof, (), Val>(PhantomData),
+        fc,
+        input.clone(),
+        &mut chal,
+        open_reduced(&input),
+    )
+    .unwrap();
 
-    let mut v_challenger = Challenger::new(perm);
+    (proof, chal.sample_bits(8))
+}
+
+/// The log of the height of each reduced input of `ldes`, tallest first, as the verifier knows
+/// them.
+fn log_heights(ldes: &[RowMajorMatrix<Val>]) -> Vec<usize> {
+    ldes.iter()
+        .map(|lde| log2_strict_usize(lde.height()))
+        .sorted_unstable_by_key(|&log_height| Reverse(log_height))
+        .dedup()
+        .collect()
+}
+
+/// Verify `proof` of `ldes`, returning a sample from the final transcript.
+fn verify_proof(
+    perm: &Perm,
+    fc: &MyFriConfig,
+    ldes: &[RowMajorMatrix<Val>],
+    proof: &MyFriProof,
+) -> Result<usize, MyFriError> {
+    let mut v_challenger = Challenger::new(perm.clone());
     let _alpha: Challenge = v_challenger.sample_ext_element();
     verifier::verify(
-        &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
-        &fc,
-        &proof,
+        &TwoAdicFriGenericConfig::<MyInputProof, (), Val>(PhantomData),
+        fc,
+        &log_heights(ldes),
+        proof,
         &mut v_challenger,
         |_index, proof| Ok(proof.clone()),
-    )
-    .unwrap();
+    )?;
+    Ok(v_challenger.sample_bits(8))
+}
+
+fn do_test_fri_ldt<R: Rng>(rng: &mut R, log_final_poly_len: usize) {
+    let perm = Perm::new_from_rng_128(rng);
+    let fc = get_ldt_for_testing(&perm, log_final_poly_len, 0);
+    let ldes = random_ldes(rng);
+
+    let (proof, p_sample) = prove_ldes(&perm, &fc, &ldes);
+    let v_sample = verify_proof(&perm, &fc, &ldes, &proof).unwrap();
 
     assert_eq!(
-        p_sample,
-        v_challenger.sample_bits(8),
+        p_sample, v_sample,
         "prover and verifier transcript have same state after FRI"
     );
 }
@@ -146,3 +200,681 @@ fn test_fri_ldt_should_panic() {
         do_test_fri_ldt(&mut rng, 5);
     }
 }
+
+#[test]
+fn test_fri_rejects_final_poly_longer_than_input() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+    let mut chal = Challenger::new(perm.clone());
+    let alpha: Challenge = chal.sample_ext_element();
+    let input = reduce_ldes(alpha, &ldes);
+
+    // The shortest input only encodes 2^5 coefficients.
+    let result = prover::prove(
+        &TwoAdicFriGenericConfig::<MyInputProof, (), Val>(PhantomData),
+        &get_ldt_for_testing(&perm, 6, 0),
+        input.clone(),
+        &mut chal,
+        open_reduced(&input),
+    );
+    assert!(matches!(
+        result,
+        Err(prover::FriProverError::FinalPolyTooLong {
+            log_final_poly_len: 6,
+            log_min_message_len: 5,
+        })
+    ));
+}
+
+#[test]
+fn test_fri_early_stopping() {
+    let mut rng = ChaCha20Rng::seed_from_u64(9);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+
+    let mut last_size = usize::MAX;
+    for log_final_poly_len in 0..5 {
+        let fc = get_ldt_for_testing(&perm, log_final_poly_len, 0);
+        let (proof, p_sample) = prove_ldes(&perm, &fc, &ldes);
+        assert_eq!(proof.final_poly.len(), 1 << log_final_poly_len);
+        // Codewords of height 2^10 are folded down to height 2^(log_final_poly_len + 1).
+        assert_eq!(proof.commit_phase_commits.len(), 9 - log_final_poly_len);
+        assert_eq!(verify_proof(&perm, &fc, &ldes, &proof).unwrap(), p_sample);
+
+        // Each round fewer saves more in openings than the longer final polynomial costs.
+        let size = postcard::to_allocvec(&proof).unwrap().len();
+        assert!(
+            size < last_size,
+            "2^{log_final_poly_len} coefficients: {size} bytes, half as many: {last_size} bytes"
+        );
+        last_size = size;
+
+        // The verifier expects the final polynomial of its own config.
+        let other_fc = get_ldt_for_testing(&perm, log_final_poly_len + 1, 0);
+        assert!(matches!(
+            verify_proof(&perm, &other_fc, &ldes, &proof),
+            Err(FriError::InvalidProofShape)
+        ));
+    }
+}
+
+#[test]
+fn test_fri_final_poly_len_1_folds_to_a_constant() {
+    let mut rng = ChaCha20Rng::seed_from_u64(10);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+
+    let fc = get_ldt_for_testing(&perm, 0, 0);
+    let built_fc = FriConfig::builder(fc.mmcs.clone())
+        .final_poly_len(1)
+        .num_queries(fc.num_queries)
+        .proof_of_work_bits(fc.proof_of_work_bits)
+        .build::<Val>()
+        .unwrap();
+    assert_eq!(built_fc.log_final_poly_len, 0);
+
+    let (proof, _) = prove_ldes(&perm, &fc, &ldes);
+    let (built_proof, _) = prove_ldes(&perm, &built_fc, &ldes);
+    assert_eq!(proof.final_poly.len(), 1);
+    assert_eq!(
+        postcard::to_allocvec(&built_proof).unwrap(),
+        postcard::to_allocvec(&proof).unwrap()
+    );
+}
+
+#[test]
+fn test_fri_direct_openings_shrink_proof() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+
+    let queried_fc = get_ldt_for_testing(&perm, 0, 0);
+    let direct_fc = get_ldt_for_testing(&perm, 0, 8);
+
+    let (queried_proof, queried_sample) = prove_ldes(&perm, &queried_fc, &ldes);
+    let (direct_proof, direct_sample) = prove_ldes(&perm, &direct_fc, &ldes);
+
+    // Rounds with at most 8 rows, i.e. the last 3 of them, are opened directly.
+    let num_direct = direct_proof
+        .commit_phase_modes
+        .iter()
+        .filter(|mode| matches!(mode, CommitPhaseMode::Direct(_)))
+        .count();
+    assert_eq!(num_direct, 3);
+    assert!(matches!(
+        direct_proof.commit_phase_modes.last(),
+        Some(CommitPhaseMode::Direct(_))
+    ));
+
+    // Commitments are unchanged, so both proofs leave the transcript in the same state.
+    assert_eq!(queried_sample, direct_sample);
+    assert_eq!(
+        verify_proof(&perm, &direct_fc, &ldes, &direct_proof).unwrap(),
+        direct_sample
+    );
+
+    let queried_size = postcard::to_allocvec(&queried_proof).unwrap().len();
+    let direct_size = postcard::to_allocvec(&direct_proof).unwrap().len();
+    assert!(
+        direct_size < queried_size,
+        "direct: {direct_size} bytes, queried: {queried_size} bytes"
+    );
+
+    // A proof in the other mode doesn't match what the config asks for.
+    assert!(matches!(
+        verify_proof(&perm, &queried_fc, &ldes, &direct_proof),
+        Err(FriError::InvalidProofShape)
+    ));
+}
+
+#[test]
+fn test_fri_direct_openings_reject_tampered_row() {
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+    let fc = get_ldt_for_testing(&perm, 0, 8);
+
+    let (mut proof, _) = prove_ldes(&perm, &fc, &ldes);
+    let Some(CommitPhaseMode::Direct(codeword)) = proof.commit_phase_modes.last_mut() else {
+        panic!("expected the last round to be opened directly");
+    };
+    codeword[1] += Challenge::ONE;
+
+    assert!(matches!(
+        verify_proof(&perm, &fc, &ldes, &proof),
+        Err(FriError::DirectOpeningMismatch)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "The challenge field has 124 bits")]
+fn test_fri_prover_rejects_small_challenge_field() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+    // Codewords of height 2^10 with 120 bits of proof of work need a larger field than BabyBear^4.
+    let fc = MyFriConfig {
+        proof_of_work_bits: 120,
+        ..get_ldt_for_testing(&perm, 0, 0)
+    };
+    prove_ldes(&perm, &fc, &ldes);
+}
+
+#[test]
+fn test_fri_verifier_rejects_small_challenge_field() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+    let (proof, _) = prove_ldes(&perm, &get_ldt_for_testing(&perm, 0, 0), &ldes);
+
+    let fc = MyFriConfig {
+        proof_of_work_bits: 120,
+        ..get_ldt_for_testing(&perm, 0, 0)
+    };
+    assert!(matches!(
+        verify_proof(&perm, &fc, &ldes, &proof),
+        Err(FriError::ChallengeFieldTooSmall)
+    ));
+}
+
+#[test]
+fn test_fri_artifacts_match_proof() {
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let fc = get_ldt_for_testing(&perm, 1, 0);
+    let ldes = random_ldes(&mut rng);
+
+    let mut chal = Challenger::new(perm.clone());
+    let alpha: Challenge = chal.sample_ext_element();
+    let input = reduce_ldes(alpha, &ldes);
+    let (proof, artifacts) = prover::prove_with_artifacts(
+        &TwoAdicFriGenericConfig::<MyInputProof, (), Val>(PhantomData),
+        &fc,
+        input.clone(),
+        &mut chal,
+        open_reduced(&input),
+    )
+    .unwrap();
+
+    assert_eq!(artifacts.commitments, proof.commit_phase_commits);
+    assert_eq!(artifacts.final_poly, proof.final_poly);
+
+    // The betas are the ones the verifier derives from the commitments.
+    let mut v_chal = Challenger::new(perm);
+    let _alpha: Challenge = v_chal.sample_ext_element();
+    let betas: Vec<Challenge> = proof
+        .commit_phase_commits
+        .iter()
+        .map(|&commit| {
+            v_chal.observe(commit);
+            v_chal.sample_ext_element()
+        })
+        .collect();
+    assert_eq!(artifacts.betas, betas);
+}
+
+#[test]
+fn test_fri_external_challenges() {
+    let mut rng = ChaCha20Rng::seed_from_u64(3);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let fc = get_ldt_for_testing(&perm, 0, 8);
+    let ldes = random_ldes(&mut rng);
+    let g = TwoAdicFriGenericConfig::<MyInputProof, (), Val>(PhantomData);
+
+    // An outer protocol binds the commitments and derives the folding challenges in its own
+    // transcript, while FRI's challenger handles everything else.
+    let mut outer = Challenger::new(perm.clone());
+    let mut inner = Challenger::new(perm.clone());
+    let alpha: Challenge = inner.sample_ext_element();
+    let input = reduce_ldes(alpha, &ldes);
+    let (proof, artifacts) = prover::prove_with_external_challenges(
+        &g,
+        &fc,
+        input.clone(),
+        &mut inner,
+        |&commit| {
+            outer.observe(commit);
+            outer.sample_ext_element()
+        },
+        open_reduced(&input),
+    )
+    .unwrap();
+
+    let mut v_outer = Challenger::new(perm.clone());
+    let betas: Vec<Challenge> = proof
+        .commit_phase_commits
+        .iter()
+        .map(|&commit| {
+            v_outer.observe(commit);
+            v_outer.sample_ext_element()
+        })
+        .collect();
+    assert_eq!(betas, artifacts.betas);
+
+    let verify_with_betas = |betas: &[Challenge]| {
+        let mut v_inner = Challenger::new(perm.clone());
+        let _alpha: Challenge = v_inner.sample_ext_element();
+        verifier::verify_with_external_challenges(
+            &g,
+            &fc,
+            &log_heights(&ldes),
+            &proof,
+            betas,
+            &mut v_inner,
+            |_index, proof| Ok(proof.clone()),
+        )
+    };
+    verify_with_betas(&betas).unwrap();
+
+    let mut wrong_betas = betas.clone();
+    wrong_betas[0] += Challenge::ONE;
+    assert!(verify_with_betas(&wrong_betas).is_err());
+    // The last round's fold isn't checked against any codeword, only against the final polynomial.
+    let mut wrong_last_beta = betas.clone();
+    *wrong_last_beta.last_mut().unwrap() += Challenge::ONE;
+    assert!(matches!(
+        verify_with_betas(&wrong_last_beta),
+        Err(FriError::FinalPolyMismatch)
+    ));
+    assert!(matches!(
+        verify_with_betas(&betas[1..]),
+        Err(FriError::InvalidProofShape)
+    ));
+
+    // The challenger alone doesn't know the betas, so the usual verifier rejects the proof.
+    assert!(verify_proof(&perm, &fc, &ldes, &proof).is_err());
+}
+
+#[test]
+fn test_fri_rejects_malformed_proofs() {
+    let mut rng = ChaCha20Rng::seed_from_u64(6);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    // Enough proof of work that a tampered witness is all but certainly invalid.
+    let fc = FriConfig {
+        proof_of_work_bits: 16,
+        ..get_ldt_for_testing(&perm, 0, 0)
+    };
+    let ldes = random_ldes(&mut rng);
+    let (proof, _) = prove_ldes(&perm, &fc, &ldes);
+    let verify_tampered = |tamper: fn(&mut MyFriProof)| {
+        let mut tampered = proof.clone();
+        tamper(&mut tampered);
+        verify_proof(&perm, &fc, &ldes, &tampered)
+    };
+
+    assert!(matches!(
+        verify_tampered(|proof| {
+            proof.final_poly.pop();
+        }),
+        Err(FriError::InvalidProofShape)
+    ));
+    assert!(matches!(
+        verify_tampered(|proof| {
+            proof.query_proofs.pop();
+        }),
+        Err(FriError::InvalidProofShape)
+    ));
+    // So many rounds that the first would be larger than any two-adic subgroup.
+    assert!(matches!(
+        verify_tampered(|proof| {
+            let commit = proof.commit_phase_commits[0];
+            proof.commit_phase_commits.resize(30, commit);
+            proof
+                .commit_phase_modes
+                .resize(30, CommitPhaseMode::Queried);
+            proof.commit_phase_log_arities.resize(30, 1);
+        }),
+        Err(FriError::InvalidProofShape)
+    ));
+    assert!(matches!(
+        verify_tampered(|proof| proof.pow_witness += Val::ONE),
+        Err(FriError::InvalidPowWitness)
+    ));
+    assert!(matches!(
+        verify_tampered(|proof| proof.query_proofs[0].input_proof[0].1 += Challenge::ONE),
+        Err(FriError::CommitPhaseMmcsError(
+            MerkleTreeError::RootMismatch
+        ))
+    ));
+    assert!(matches!(
+        verify_tampered(
+            |proof| proof.query_proofs[0].commit_phase_openings[0].sibling_values[0] +=
+                Challenge::ONE
+        ),
+        Err(FriError::CommitPhaseMmcsError(
+            MerkleTreeError::RootMismatch
+        ))
+    ));
+    // An input below the final polynomial's height, which no round would fold in.
+    assert!(matches!(
+        verify_tampered(|proof| proof.query_proofs[0].input_proof.push((0, Challenge::ZERO))),
+        Err(FriError::InvalidProofShape)
+    ));
+}
+
+/// The number of field elements in the commit phase of `proof`, counting a challenge as its
+/// coefficients and a commitment or Merkle proof as its digests' words.
+fn commit_phase_size(proof: &MyFriProof) -> usize {
+    let challenge_size = <Challenge as FieldExtensionAlgebra<Val>>::D;
+    let openings: usize = proof
+        .query_proofs
+        .iter()
+        .flat_map(|qp| &qp.commit_phase_openings)
+        .map(|step| {
+            step.sibling_values.len() * challenge_size
+                + step
+                    .opening_proof
+                    .iter()
+                    .map(|digest| digest.len())
+                    .sum::<usize>()
+        })
+        .sum();
+    let direct_codewords: usize = proof
+        .commit_phase_modes
+        .iter()
+        .map(|mode| match mode {
+            CommitPhaseMode::Queried => 0,
+            CommitPhaseMode::Direct(codeword) => codeword.len() * challenge_size,
+        })
+        .sum();
+    let commitments = proof.commit_phase_commits.len() * 8;
+    openings + direct_codewords + commitments + proof.final_poly.len() * challenge_size
+}
+
+#[test]
+fn test_fri_higher_arity() {
+    let mut rng = ChaCha20Rng::seed_from_u64(7);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    // Inputs of heights 2^10 and 2^6, which rounds folding by 4 from 2^10 both land on.
+    let ldes = random_ldes_of_degrees(&mut rng, [9, 5]);
+
+    for (log_final_poly_len, max_direct_opening_height) in [(0, 0), (1, 0), (0, 8)] {
+        let binary_fc = get_ldt_for_testing(&perm, log_final_poly_len, max_direct_opening_height);
+        let quaternary_fc = FriConfig {
+            log_folding_arity: 2,
+            ..get_ldt_for_testing(&perm, log_final_poly_len, max_direct_opening_height)
+        };
+        assert_eq!(
+            binary_fc.conjectured_soundness_bits(),
+            quaternary_fc.conjectured_soundness_bits()
+        );
+
+        let (binary_proof, _) = prove_ldes(&perm, &binary_fc, &ldes);
+        let (quaternary_proof, p_sample) = prove_ldes(&perm, &quaternary_fc, &ldes);
+        let v_sample = verify_proof(&perm, &quaternary_fc, &ldes, &quaternary_proof).unwrap();
+        assert_eq!(p_sample, v_sample);
+
+        // Every round but perhaps the last folds by 4.
+        assert!(quaternary_proof
+            .commit_phase_log_arities
+            .iter()
+            .rev()
+            .skip(1)
+            .all(|&log_arity| log_arity == 2));
+        assert_eq!(
+            quaternary_proof.log_folding_factor(),
+            binary_proof.log_folding_factor()
+        );
+
+        let binary_size = commit_phase_size(&binary_proof);
+        let quaternary_size = commit_phase_size(&quaternary_proof);
+        assert!(
+            quaternary_size < binary_size,
+            "arity 4: {quaternary_size} field elements, arity 2: {binary_size}"
+        );
+
+        // A verifier which only folds by 2 doesn't accept rows of 4.
+        assert!(matches!(
+            verify_proof(&perm, &binary_fc, &ldes, &quaternary_proof),
+            Err(FriError::InvalidProofShape)
+        ));
+    }
+}
+
+#[test]
+fn test_fri_higher_arity_rejects_malformed_proofs() {
+    let mut rng = ChaCha20Rng::seed_from_u64(8);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let fc = FriConfig {
+        log_folding_arity: 2,
+        ..get_ldt_for_testing(&perm, 0, 0)
+    };
+    let ldes = random_ldes_of_degrees(&mut rng, [9, 7, 5]);
+    let (proof, _) = prove_ldes(&perm, &fc, &ldes);
+    let verify_tampered = |tamper: fn(&mut MyFriProof)| {
+        let mut tampered = proof.clone();
+        tamper(&mut tampered);
+        verify_proof(&perm, &fc, &ldes, &tampered)
+    };
+
+    assert!(matches!(
+        verify_tampered(|proof| {
+            proof.query_proofs[0].commit_phase_openings[0]
+                .sibling_values
+                .pop();
+        }),
+        Err(FriError::InvalidProofShape)
+    ));
+    assert!(matches!(
+        verify_tampered(
+            |proof| proof.query_proofs[0].commit_phase_openings[0].sibling_values[2] +=
+                Challenge::ONE
+        ),
+        Err(FriError::CommitPhaseMmcsError(
+            MerkleTreeError::RootMismatch
+        ))
+    ));
+    assert!(matches!(
+        verify_tampered(|proof| proof.commit_phase_log_arities[0] = 3),
+        Err(FriError::InvalidProofShape)
+    ));
+    // Folding by 2 where the config folds by 4 is rejected before any codeword is checked.
+    assert!(matches!(
+        verify_tampered(|proof| proof.commit_phase_log_arities[0] = 1),
+        Err(FriError::InvalidProofShape)
+    ));
+}
+
+#[test]
+fn test_fri_resume_from_every_round() {
+    let mut rng = ChaCha20Rng::seed_from_u64(4);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let fc = get_ldt_for_testing(&perm, 1, 0);
+    let ldes = random_ldes(&mut rng);
+    let g = TwoAdicFriGenericConfig::<MyInputProof, (), Val>(PhantomData);
+    let (proof, sample) = prove_ldes(&perm, &fc, &ldes);
+    let proof_bytes = postcard::to_allocvec(&proof).unwrap();
+
+    let mut chal = Challenger::new(perm.clone());
+    let alpha: Challenge = chal.sample_ext_element();
+    let input = reduce_ldes(alpha, &ldes);
+    let mut checkpoints = vec![];
+    let checkpointed = prover::prove_with_checkpoints(
+        &g,
+        &fc,
+        input.clone(),
+        &mut chal,
+        open_reduced(&input),
+        |checkpoint| checkpoints.push(postcard::to_allocvec(&checkpoint).unwrap()),
+    )
+    .unwrap();
+    assert_eq!(postcard::to_allocvec(&checkpointed).unwrap(), proof_bytes);
+    assert_eq!(checkpoints.len(), proof.commit_phase_commits.len());
+
+    for (completed, bytes) in (1..).zip(&checkpoints) {
+        // The challenger to resume with is fresh, as on a machine which didn't start the proof.
+        let mut resumed_chal = Challenger::new(perm.clone());
+        let mut remaining = 0;
+        let resumed = prover::prove_resume(
+            &g,
+            &fc,
+            input.clone(),
+            postcard::from_bytes(bytes).unwrap(),
+            &mut resumed_chal,
+            open_reduced(&input),
+            |_| remaining += 1,
+        )
+        .expect("resuming failed");
+        assert_eq!(postcard::to_allocvec(&resumed).unwrap(), proof_bytes);
+        assert_eq!(resumed_chal.sample_bits(8), sample);
+        assert_eq!(completed + remaining, checkpoints.len());
+    }
+}
+
+#[test]
+fn test_fri_resume_rejects_other_inputs() {
+    let mut rng = ChaCha20Rng::seed_from_u64(5);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let fc = get_ldt_for_testing(&perm, 0, 0);
+    let ldes = random_ldes(&mut rng);
+    let g = TwoAdicFriGenericConfig::<MyInputProof, (), Val>(PhantomData);
+
+    let mut chal = Challenger::new(perm.clone());
+    let alpha: Challenge = chal.sample_ext_element();
+    let input = reduce_ldes(alpha, &ldes);
+    let mut checkpoints = vec![];
+    prover::prove_with_checkpoints(
+        &g,
+        &fc,
+        input.clone(),
+        &mut chal,
+        open_reduced(&input),
+        |checkpoint| checkpoints.push(checkpoint),
+    )
+    .unwrap();
+
+    let mut other_input = input.clone();
+    other_input[0][0] += Challenge::ONE;
+    let result = prover::prove_resume(
+        &g,
+        &fc,
+        other_input.clone(),
+        checkpoints.pop().unwrap(),
+        &mut Challenger::new(perm),
+        open_reduced(&other_input),
+        |_| {},
+    );
+    assert!(matches!(
+        result,
+        Err(prover::FriCheckpointError::CommitmentMismatch(0))
+    ));
+}
+
+#[test]
+fn test_fri_with_uniform_query_indices() {
+    let mut rng = ChaCha20Rng::seed_from_u64(12);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let fc = FriConfig {
+        uniform_query_indices: true,
+        ..get_ldt_for_testing(&perm, 1, 0)
+    };
+    let ldes = random_ldes(&mut rng);
+
+    let (proof, p_sample) = prove_ldes(&perm, &fc, &ldes);
+    let v_sample = verify_proof(&perm, &fc, &ldes, &proof).unwrap();
+    assert_eq!(p_sample, v_sample);
+}
+
+type DynFolder = dyn FriGenericConfig<Challenge, InputProof = MyInputProof, InputError = ()>;
+
+#[test]
+fn test_fri_through_dyn_generic_config() {
+    let mut rng = ChaCha20Rng::seed_from_u64(11);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+    // Chosen at runtime, as an application picking its PCS from its settings would.
+    let g: Box<DynFolder> = Box::new(TwoAdicFriGenericConfig::<MyInputProof, (), Val>(
+        PhantomData,
+    ));
+
+    for log_folding_arity in [1, 2] {
+        let fc = FriConfig {
+            log_folding_arity,
+            ..get_ldt_for_testing(&perm, 1, 0)
+        };
+
+        let mut chal = Challenger::new(perm.clone());
+        let alpha: Challenge = chal.sample_ext_element();
+        let input = reduce_ldes(alpha, &ldes);
+        let proof =
+            prover::prove(&*g, &fc, input.clone(), &mut chal, open_reduced(&input)).unwrap();
+
+        let mut v_challenger = Challenger::new(perm.clone());
+        let _alpha: Challenge = v_challenger.sample_ext_element();
+        verifier::verify(
+            &*g,
+            &fc,
+            &log_heights(&ldes),
+            &proof,
+            &mut v_challenger,
+            |_index, proof| Ok(proof.clone()),
+        )
+        .unwrap();
+        assert_eq!(chal.sample_bits(8), v_challenger.sample_bits(8));
+
+        // The same proof as through the concrete folder.
+        let (static_proof, _) = prove_ldes(&perm, &fc, &ldes);
+        assert_eq!(
+            postcard::to_allocvec(&proof).unwrap(),
+            postcard::to_allocvec(&static_proof).unwrap()
+        );
+    }
+}
+
+/// The two-adic folder, but folding whole matrices one row at a time.
+struct RowByRowFolder(TwoAdicFriGenericConfig<MyInputProof, (), Val>);
+
+impl FriGenericConfig<Challenge> for RowByRowFolder {
+    type InputProof = MyInputProof;
+    type InputError = ();
+
+    fn extra_query_index_bits(&self) -> usize {
+        0
+    }
+
+    fn fold_row(
+        &self,
+        index: usize,
+        log_height: usize,
+        beta: Challenge,
+        evals: &[Challenge],
+    ) -> Challenge {
+        self.0.fold_row(index, log_height, beta, evals)
+    }
+
+    fn fold_matrix(&self, beta: Challenge, m: RowMajorMatrixView<'_, Challenge>) -> Vec<Challenge> {
+        let log_height = log2_strict_usize(m.height());
+        (0..m.height())
+            .map(|i| self.fold_row(i, log_height, beta, &m.row_slice(i)))
+            .collect()
+    }
+}
+
+#[test]
+fn test_packed_folding_matches_row_by_row() {
+    let mut rng = ChaCha20Rng::seed_from_u64(12);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let ldes = random_ldes(&mut rng);
+    let folder = RowByRowFolder(TwoAdicFriGenericConfig(PhantomData));
+
+    for log_folding_arity in [1, 2] {
+        let fc = FriConfig {
+            log_folding_arity,
+            ..get_ldt_for_testing(&perm, 1, 0)
+        };
+
+        let mut chal = Challenger::new(perm.clone());
+        let alpha: Challenge = chal.sample_ext_element();
+        let input = reduce_ldes(alpha, &ldes);
+        let proof =
+            prover::prove(&folder, &fc, input.clone(), &mut chal, open_reduced(&input)).unwrap();
+
+        let (packed_proof, packed_sample) = prove_ldes(&perm, &fc, &ldes);
+        assert_eq!(
+            postcard::to_allocvec(&proof).unwrap(),
+            postcard::to_allocvec(&packed_proof).unwrap()
+        );
+        assert_eq!(chal.sample_bits(8), packed_sample);
+    }
+}
@@ -1,10 +1,10 @@
 use itertools::{izip, Itertools};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
 use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
-use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace};
+use p3_commit::{ExtensionMmcs, OpenedValues, Pcs, PolynomialSpace};
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
-use p3_field::{ExtensionField, Field};
+use p3_field::{ExtensionField, Field, FieldAlgebra};
 use p3_fri::{FriConfig, TwoAdicFriPcs};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_merkle_tree::MerkleTreeMmcs;
@@ -66,7 +66,7 @@ fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
         .map(|log_degrees| vec![vec![zeta]; log_degrees.len()])
         .collect_vec();
     let data_and_points = data_by_round.iter().zip(points_by_round).collect();
-    let (opening_by_round, proof) = pcs.open(data_and_points, &mut p_challenger);
+    let (opening_by_round, proof) = pcs.open(data_and_points, &mut p_challenger).unwrap();
     assert_eq!(opening_by_round.len(), num_rounds);
 
     // Verify the proof.
@@ -95,6 +95,80 @@ fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
         .unwrap()
 }
 
+/// Open one commitment at `zeta`, then again at a point sampled after the first opening, each with
+/// its own proof.
+fn do_test_two_phase_opening<Val, Challenge, Challenger, P>(
+    (pcs, challenger): &(P, Challenger),
+    log_degree: usize,
+) where
+    P: Pcs<Challenge, Challenger>,
+    P::Domain: PolynomialSpace<Val = Val>,
+    Val: Field,
+    Standard: Distribution<Val>,
+    Challenge: ExtensionField<Val>,
+    Challenger: Clone + CanObserve<P::Commitment> + FieldChallenger<Val>,
+{
+    let mut rng = seeded_rng();
+    let domain = pcs.natural_domain_for_degree(1 << log_degree);
+    let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_degree, 3);
+    let (commit, data) = pcs.commit(vec![(domain, evals)]);
+
+    let mut p_challenger = challenger.clone();
+    p_challenger.observe(commit.clone());
+    let zeta: Challenge = p_challenger.sample_ext_element();
+    let (first_opening, first_proof) = pcs
+        .open(vec![(&data, vec![vec![zeta]])], &mut p_challenger)
+        .unwrap();
+    // The second point is sampled after the first opening proof has been absorbed.
+    let eta: Challenge = p_challenger.sample_ext_element();
+    let (second_opening, second_proof) = pcs
+        .open(vec![(&data, vec![vec![eta]])], &mut p_challenger)
+        .unwrap();
+
+    let claims = |point: Challenge, opening: &OpenedValues<Challenge>| {
+        vec![(
+            commit.clone(),
+            vec![(domain, vec![(point, opening[0][0][0].clone())])],
+        )]
+    };
+
+    let mut v_challenger = challenger.clone();
+    v_challenger.observe(commit.clone());
+    let verifier_zeta: Challenge = v_challenger.sample_ext_element();
+    assert_eq!(verifier_zeta, zeta);
+    // Sampling the second point too early gives a different one.
+    let mut early_challenger = v_challenger.clone();
+    let early_eta: Challenge = early_challenger.sample_ext_element();
+    assert_ne!(early_eta, eta);
+
+    pcs.verify(
+        claims(zeta, &first_opening),
+        &first_proof,
+        &mut v_challenger,
+    )
+    .unwrap();
+    let verifier_eta: Challenge = v_challenger.sample_ext_element();
+    assert_eq!(verifier_eta, eta);
+
+    // A tampered second opening is rejected.
+    let mut tampered = second_opening.clone();
+    tampered[0][0][0][0] += Challenge::ONE;
+    assert!(pcs
+        .verify(
+            claims(eta, &tampered),
+            &second_proof,
+            &mut v_challenger.clone()
+        )
+        .is_err());
+
+    pcs.verify(
+        claims(eta, &second_opening),
+        &second_proof,
+        &mut v_challenger,
+    )
+    .unwrap();
+}
+
 // Set it up so we create tests inside a module for each pcs, so we get nice error reports
 // specific to a failing PCS.
 macro_rules! make_tests_for_pcs {
@@ -146,11 +220,30 @@ macro_rules! make_tests_for_pcs {
             $crate::do_test_fri_pcs(&p, &[&[2, 2], &[3, 3]]);
             $crate::do_test_fri_pcs(&p, &[&[3, 3], &[2, 2]]);
             $crate::do_test_fri_pcs(&p, &[&[2], &[3, 3]]);
+            $crate::do_test_fri_pcs(&p, &[&[5, 3], &[4], &[2, 6]]);
+        }
+
+        #[test]
+        fn two_phase_opening() {
+            let p = $p;
+            for i in 3..6 {
+                $crate::do_test_two_phase_opening(&p, i);
+            }
         }
     };
 }
 
 mod babybear_fri_pcs {
+    use p3_commit::Mmcs;
+    use p3_field::{FieldAlgebra, TwoAdicField};
+    use p3_fri::prover::FriProverError;
+    use p3_fri::verifier::FriError;
+    use p3_fri::BatchOpening;
+    use p3_interpolation::interpolate_subgroup;
+    use p3_matrix::Matrix;
+    use p3_merkle_tree::{CompatMode, UpstreamVersion};
+    use p3_util::{log2_strict_usize, reverse_bits_len};
+
     use super::*;
 
     type Val = BabyBear;
@@ -169,11 +262,23 @@ mod babybear_fri_pcs {
     type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 
     fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+        get_pcs_with_arity(log_blowup, 1)
+    }
+
+    fn get_pcs_with_arity(log_blowup: usize, log_folding_arity: usize) -> (MyPcs, Challenger) {
+        get_pcs_with(log_blowup, log_folding_arity, CompatMode::Native)
+    }
+
+    fn get_pcs_with(
+        log_blowup: usize,
+        log_folding_arity: usize,
+        compat_mode: CompatMode,
+    ) -> (MyPcs, Challenger) {
         let perm = Perm::new_from_rng_128(&mut seeded_rng());
         let hash = MyHash::new(perm.clone());
         let compress = MyCompress::new(perm.clone());
 
-        let val_mmcs = ValMmcs::new(hash, compress);
+        let val_mmcs = ValMmcs::new(hash, compress).with_compat_mode(compat_mode);
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
 
         let fri_config = FriConfig {
@@ -181,6 +286,9 @@ mod babybear_fri_pcs {
             log_final_poly_len: 0,
             num_queries: 10,
             proof_of_work_bits: 8,
+            max_direct_opening_height: 0,
+            log_folding_arity,
+            uniform_query_indices: false,
             mmcs: challenge_mmcs,
         };
 
@@ -194,6 +302,335 @@ mod babybear_fri_pcs {
     mod blowup_2 {
         make_tests_for_pcs!(super::get_pcs(2));
     }
+    mod arity_4 {
+        make_tests_for_pcs!(super::get_pcs_with_arity(1, 2));
+    }
+    // The PCS works unchanged over trees with upstream Plonky3's layout.
+    mod upstream_merkle_layout {
+        make_tests_for_pcs!(super::get_pcs_with(
+            1,
+            1,
+            CompatMode::Upstream(UpstreamVersion::V0_1)
+        ));
+    }
+
+    #[test]
+    fn final_poly_longer_than_a_matrix_is_an_error() {
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 3,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            max_direct_opening_height: 0,
+            log_folding_arity: 1,
+            uniform_query_indices: false,
+            mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+        };
+        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+        let mut challenger = Challenger::new(perm);
+
+        // A polynomial of degree below 2^3 is no longer than the final polynomial.
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 3);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 3, 2);
+        let (_, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+        let zeta: Challenge = challenger.sample_ext_element();
+        let result = pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger);
+        assert!(matches!(
+            result,
+            Err(FriError::Prover(FriProverError::FinalPolyTooLong {
+                log_final_poly_len: 3,
+                log_min_message_len: 3,
+            }))
+        ));
+    }
+
+    #[test]
+    fn leaf_mapping_matches_openings() {
+        let log_blowup = 2;
+        let (pcs, _) = get_pcs(log_blowup);
+        // An MMCS equivalent to the PCS's, to open the committed matrices directly.
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+
+        let mut rng = seeded_rng();
+        let evaluations = [3, 2]
+            .map(|log_n| {
+                (
+                    <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                        &pcs,
+                        1 << log_n,
+                    ),
+                    RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 2),
+                )
+            })
+            .to_vec();
+        let domains = evaluations.iter().map(|(d, _)| *d).collect_vec();
+        let (comm, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.clone());
+        let dims = val_mmcs
+            .get_matrices(&data)
+            .iter()
+            .map(|m| m.dimensions())
+            .collect_vec();
+
+        let mapping = pcs.trace_leaf_mapping(&domains);
+        assert_eq!(mapping.len(), 32 + 16);
+        for entry in mapping {
+            let (domain, evals) = &evaluations[entry.matrix];
+            // The LDE is over the coset `g H`, where `g` is the field's generator.
+            let lde_root = Val::two_adic_generator(domain.log_n + log_blowup);
+            let point = Val::GENERATOR * lde_root.exp_u64(entry.logical_row as u64);
+            let expected: Vec<Val> = interpolate_subgroup(evals, point);
+            for index in entry.leaf_indices {
+                let (opened_values, proof) = val_mmcs.open_batch(index, &data);
+                assert_eq!(opened_values[entry.matrix], expected);
+                val_mmcs
+                    .verify_batch(&comm, &dims, index, &opened_values, &proof)
+                    .unwrap();
+            }
+        }
+    }
+
+    /// A prover which mixes up the natural and bit-reversed order of the LDE, opening each committed
+    /// round at the bit reversal of the row its queries map to, is caught.
+    #[test]
+    fn input_openings_at_bit_reversed_rows_are_rejected() {
+        let (pcs, challenger) = get_pcs(1);
+        // An MMCS equivalent to the PCS's, to open the committed matrices directly.
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+
+        let mut rng = seeded_rng();
+        let evaluations_by_round = [&[5, 3][..], &[4]]
+            .map(|log_degrees| {
+                log_degrees
+                    .iter()
+                    .map(|&log_n| {
+                        (
+                            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                                &pcs,
+                                1 << log_n,
+                            ),
+                            RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3),
+                        )
+                    })
+                    .collect_vec()
+            })
+            .to_vec();
+        let (commits, data): (Vec<_>, Vec<_>) = evaluations_by_round
+            .iter()
+            .map(|evaluations| {
+                <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.clone())
+            })
+            .unzip();
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe_slice(&commits);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let data_and_points = izip!(&data, &evaluations_by_round)
+            .map(|(data, evaluations)| (data, vec![vec![zeta]; evaluations.len()]))
+            .collect();
+        let (opened_values, mut proof) =
+            <MyPcs as Pcs<Challenge, Challenger>>::open(&pcs, data_and_points, &mut p_challenger)
+                .unwrap();
+
+        let claims = izip!(&commits, &evaluations_by_round, &opened_values)
+            .map(|(commit, evaluations, openings)| {
+                let mats = izip!(evaluations, openings)
+                    .map(|((domain, _), values)| (*domain, vec![(zeta, values[0].clone())]))
+                    .collect_vec();
+                (*commit, mats)
+            })
+            .collect_vec();
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe_slice(&commits);
+        let _: Challenge = v_challenger.sample_ext_element();
+        <MyPcs as Pcs<Challenge, Challenger>>::verify(
+            &pcs,
+            claims.clone(),
+            &proof,
+            &mut v_challenger.clone(),
+        )
+        .unwrap();
+
+        let mut num_moved = 0;
+        for query_proof in &mut proof.query_proofs {
+            for (batch_opening, data) in izip!(&mut query_proof.input_proof, &data) {
+                let log_height = log2_strict_usize(val_mmcs.get_max_height(data));
+                let row = (0..1 << log_height)
+                    .find(|&row| val_mmcs.open_batch(row, data).0 == batch_opening.opened_values)
+                    .unwrap();
+                let wrong_row = reverse_bits_len(row, log_height);
+                num_moved += usize::from(wrong_row != row);
+                let (opened_values, opening_proof) = val_mmcs.open_batch(wrong_row, data);
+                *batch_opening = BatchOpening {
+                    opened_values,
+                    opening_proof,
+                };
+            }
+        }
+        assert!(num_moved > 0);
+        assert!(<MyPcs as Pcs<Challenge, Challenger>>::verify(
+            &pcs,
+            claims,
+            &proof,
+            &mut v_challenger,
+        )
+        .is_err());
+    }
+
+    /// Matrices of different heights are opened by a single FRI instance: the reduced opening of
+    /// each joins the commit phase at the round which has folded down to its height.
+    #[test]
+    fn mixed_heights_share_one_fri_instance() {
+        let log_blowup = 1;
+        let (pcs, challenger) = get_pcs(log_blowup);
+        let mut rng = seeded_rng();
+        let log_heights = [10, 12, 14];
+        let evaluations = log_heights
+            .map(|log_n| {
+                (
+                    <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                        &pcs,
+                        1 << log_n,
+                    ),
+                    RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3),
+                )
+            })
+            .to_vec();
+        let (comm, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.clone());
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(comm);
+        // A different point for each matrix.
+        let points = (0..evaluations.len())
+            .map(|_| vec![p_challenger.sample_ext_element::<Challenge>()])
+            .collect_vec();
+        let (opened_values, proof) = <MyPcs as Pcs<Challenge, Challenger>>::open(
+            &pcs,
+            vec![(&data, points.clone())],
+            &mut p_challenger,
+        )
+        .unwrap();
+
+        // One commit phase, folding the tallest LDE down to the encoding of a constant.
+        assert_eq!(proof.commit_phase_commits.len(), log_heights[2]);
+
+        let claims = |opened_values: &OpenedValues<Challenge>| {
+            let mats = izip!(&evaluations, &points, &opened_values[0])
+                .map(|((domain, _), points, values)| {
+                    (*domain, vec![(points[0], values[0].clone())])
+                })
+                .collect_vec();
+            vec![(comm, mats)]
+        };
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(comm);
+        for _ in 0..evaluations.len() {
+            let _: Challenge = v_challenger.sample_ext_element();
+        }
+        <MyPcs as Pcs<Challenge, Challenger>>::verify(
+            &pcs,
+            claims(&opened_values),
+            &proof,
+            &mut v_challenger.clone(),
+        )
+        .unwrap();
+
+        // The shortest matrix is bound by the same proof, so a wrong value for it is caught.
+        let mut wrong_values = opened_values.clone();
+        wrong_values[0][0][0][0] += Challenge::ONE;
+        assert!(<MyPcs as Pcs<Challenge, Challenger>>::verify(
+            &pcs,
+            claims(&wrong_values),
+            &proof,
+            &mut v_challenger,
+        )
+        .is_err());
+    }
+}
+
+/// FRI with commitments which aren't Merkle roots: every committed matrix is sent in full.
+mod babybear_trivial_mmcs_fri_pcs {
+    use p3_commit::testing::TrivialMmcs;
+
+    use super::*;
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+
+    type Perm = Poseidon2BabyBear<16>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, TrivialMmcs>;
+
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type MyPcs = TwoAdicFriPcs<Val, Dft, TrivialMmcs, ChallengeMmcs>;
+
+    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            max_direct_opening_height: 0,
+            log_folding_arity: 1,
+            uniform_query_indices: false,
+            mmcs: ChallengeMmcs::new(TrivialMmcs),
+        };
+        let pcs = MyPcs::new(Dft::default(), TrivialMmcs, fri_config);
+        (pcs, Challenger::new(perm))
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1));
+    }
+    mod blowup_2 {
+        make_tests_for_pcs!(super::get_pcs(2));
+    }
+
+    #[test]
+    fn changed_commitment_is_rejected() {
+        let (pcs, challenger) = get_pcs(1);
+        let mut rng = seeded_rng();
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 4);
+        let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 4, 3);
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit.clone());
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opening, proof) = pcs
+            .open(vec![(&data, vec![vec![zeta]])], &mut p_challenger)
+            .unwrap();
+        let claims = |commit: Vec<Vec<Val>>| {
+            vec![(
+                commit,
+                vec![(domain, vec![(zeta, opening[0][0][0].clone())])],
+            )]
+        };
+
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commit.clone());
+        let _: Challenge = v_challenger.sample_ext_element();
+        assert!(pcs
+            .verify(claims(commit.clone()), &proof, &mut v_challenger)
+            .is_ok());
+
+        // Every committed value differs, so whichever rows are queried don't match the opening.
+        let changed = commit
+            .iter()
+            .map(|values| values.iter().map(|&x| x + Val::ONE).collect())
+            .collect_vec();
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commit);
+        let _: Challenge = v_challenger.sample_ext_element();
+        assert!(pcs
+            .verify(claims(changed), &proof, &mut v_challenger)
+            .is_err());
+    }
 }
 
 mod m31_fri_pcs {
@@ -224,6 +661,10 @@ mod m31_fri_pcs {
     type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
 
     fn get_pcs(log_blowup: usize) -> (Pcs, Challenger) {
+        get_pcs_with_arity(log_blowup, 1)
+    }
+
+    fn get_pcs_with_arity(log_blowup: usize, log_folding_arity: usize) -> (Pcs, Challenger) {
         let byte_hash = ByteHash {};
         let field_hash = FieldHash::new(byte_hash);
         let compress = MyCompress::new(byte_hash);
@@ -234,6 +675,9 @@ mod m31_fri_pcs {
             log_final_poly_len: 0,
             num_queries: 10,
             proof_of_work_bits: 8,
+            max_direct_opening_height: 0,
+            log_folding_arity,
+            uniform_query_indices: false,
             mmcs: challenge_mmcs,
         };
         let pcs = Pcs {
@@ -250,4 +694,7 @@ mod m31_fri_pcs {
     mod blowup_2 {
         make_tests_for_pcs!(super::get_pcs(2));
     }
+    mod arity_4 {
+        make_tests_for_pcs!(super::get_pcs_with_arity(1, 2));
+    }
 }
@@ -9,21 +9,21 @@ use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use num_bigint::BigUint;
 use p3_field::{
-    exp_10540996611094048183, exp_u64_by_squaring, halve_u64, Field, FieldAlgebra, Packable,
-    PrimeField, PrimeField64, TwoAdicField,
+    assert_two_adicity, exp_10540996611094048183, exp_u64_by_squaring, halve_u64, Field,
+    FieldAlgebra, Packable, PrimeField, PrimeField64, TwoAdicField,
 };
+use p3_util::encoding::{CanonicalEncoding, DecodingError};
 use p3_util::{assume, branch_hint};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// The Goldilocks prime
 const P: u64 = 0xFFFF_FFFF_0000_0001;
 
 /// The prime field known as Goldilocks, defined as `F_p` where `p = 2^64 - 2^32 + 1`.
-///
-/// Note that the safety of deriving `Serialize` and `Deserialize` relies on the fact that the internal value can be any u64.
-#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default)]
 #[repr(transparent)] // Packed field implementations rely on this!
 pub struct Goldilocks {
     /// Not necessarily canonical.
@@ -37,6 +37,79 @@ impl Goldilocks {
 
     /// Two's complement of `ORDER`, i.e. `2^64 - ORDER = 2^32 - 1`.
     const NEG_ORDER: u64 = Self::ORDER_U64.wrapping_neg();
+
+    /// The generators of the two-adic subgroups: the `i`'th element generates the subgroup of
+    /// order `2^i`, so it squares to the `i - 1`'th.
+    ///
+    /// The last one is `1753635133440165772`, which generates the whole group of order `2^32`.
+    pub const TWO_ADIC_GENERATORS: [Self; 33] = [
+        Self::new(1),
+        Self::new(18_446_744_069_414_584_320),
+        Self::new(281_474_976_710_656),
+        Self::new(18_446_744_069_397_807_105),
+        Self::new(17_293_822_564_807_737_345),
+        Self::new(70_368_744_161_280),
+        Self::new(549_755_813_888),
+        Self::new(17_870_292_113_338_400_769),
+        Self::new(13_797_081_185_216_407_910),
+        Self::new(1_803_076_106_186_727_246),
+        Self::new(11_353_340_290_879_379_826),
+        Self::new(455_906_449_640_507_599),
+        Self::new(17_492_915_097_719_143_606),
+        Self::new(1_532_612_707_718_625_687),
+        Self::new(16_207_902_636_198_568_418),
+        Self::new(17_776_499_369_601_055_404),
+        Self::new(6_115_771_955_107_415_310),
+        Self::new(12_380_578_893_860_276_750),
+        Self::new(9_306_717_745_644_682_924),
+        Self::new(18_146_160_046_829_613_826),
+        Self::new(3_511_170_319_078_647_661),
+        Self::new(17_654_865_857_378_133_588),
+        Self::new(5_416_168_637_041_100_469),
+        Self::new(16_905_767_614_792_059_275),
+        Self::new(9_713_644_485_405_565_297),
+        Self::new(5_456_943_929_260_765_144),
+        Self::new(17_096_174_751_763_063_430),
+        Self::new(1_213_594_585_890_690_845),
+        Self::new(6_414_415_596_519_834_757),
+        Self::new(16_116_352_524_544_190_054),
+        Self::new(9_123_114_210_336_311_365),
+        Self::new(4_614_640_910_117_430_873),
+        Self::new(1_753_635_133_440_165_772),
+    ];
+}
+
+impl Serialize for Goldilocks {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.as_canonical_u64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Goldilocks {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let val = u64::deserialize(d)?;
+        // Only accept canonical values, so that every element has a unique encoding.
+        if val < P {
+            Ok(Self::new(val))
+        } else {
+            Err(D::Error::custom("Value is out of range"))
+        }
+    }
+}
+
+impl CanonicalEncoding for Goldilocks {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_canonical_u64().encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        let val = u64::decode(input)?;
+        if val < P {
+            Ok(Self::new(val))
+        } else {
+            Err(DecodingError::NonCanonical)
+        }
+    }
 }
 
 impl PartialEq for Goldilocks {
@@ -69,13 +142,13 @@ impl PartialOrd for Goldilocks {
 
 impl Display for Goldilocks {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        Display::fmt(&self.value, f)
+        Display::fmt(&self.as_canonical_u64(), f)
     }
 }
 
 impl Debug for Goldilocks {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        Debug::fmt(&self.value, f)
+        Debug::fmt(&self.as_canonical_u64(), f)
     }
 }
 
@@ -181,6 +254,8 @@ impl Field for Goldilocks {
     // Sage: GF(2^64 - 2^32 + 1).multiplicative_generator()
     const GENERATOR: Self = Self::new(7);
 
+    const BITS: usize = (u64::BITS - P.leading_zeros()) as usize;
+
     fn is_zero(&self) -> bool {
         self.value == 0 || self.value == Self::ORDER_U64
     }
@@ -273,9 +348,8 @@ impl TwoAdicField for Goldilocks {
     const TWO_ADICITY: usize = 32;
 
     fn two_adic_generator(bits: usize) -> Self {
-        assert!(bits <= Self::TWO_ADICITY);
-        let base = Self::new(1_753_635_133_440_165_772); // generates the whole 2^TWO_ADICITY group
-        base.exp_power_of_2(Self::TWO_ADICITY - bits)
+        assert_two_adicity::<Self>(bits);
+        Self::TWO_ADIC_GENERATORS[bits]
     }
 }
 
@@ -363,7 +437,8 @@ impl Mul for Goldilocks {
 
     #[inline]
     fn mul(self, rhs: Self) -> Self {
-        reduce128(u128::from(self.value) * u128::from(rhs.value))
+        let (lo, hi) = mul_wide(self.value, rhs.value);
+        reduce_wide(lo, hi)
     }
 }
 
@@ -400,6 +475,12 @@ fn exp_acc<const N: usize>(base: Goldilocks, tail: Goldilocks) -> Goldilocks {
 #[inline]
 pub(crate) fn reduce128(x: u128) -> Goldilocks {
     let (x_lo, x_hi) = split(x); // This is a no-op
+    reduce_wide(x_lo, x_hi)
+}
+
+/// Like `reduce128`, but takes the low and high 64-bit halves of the input separately.
+#[inline]
+fn reduce_wide(x_lo: u64, x_hi: u64) -> Goldilocks {
     let x_hi_hi = x_hi >> 32;
     let x_hi_lo = x_hi & Goldilocks::NEG_ORDER;
 
@@ -419,6 +500,36 @@ const fn split(x: u128) -> (u64, u64) {
     (x as u64, (x >> 64) as u64)
 }
 
+/// The full 128-bit product of `x` and `y`, as its low and high 64-bit halves.
+#[inline]
+const fn mul_wide(x: u64, y: u64) -> (u64, u64) {
+    if cfg!(target_pointer_width = "64") {
+        split(x as u128 * y as u128)
+    } else {
+        mul_wide_limbs(x, y)
+    }
+}
+
+/// `mul_wide` using 32-bit limbs. Targets without native 64-bit multiplication lower a `u128`
+/// product to a call into compiler-rt, which is much slower than these four products.
+#[inline]
+const fn mul_wide_limbs(x: u64, y: u64) -> (u64, u64) {
+    const MASK: u64 = 0xffff_ffff;
+    let (x_lo, x_hi) = (x & MASK, x >> 32);
+    let (y_lo, y_hi) = (y & MASK, y >> 32);
+
+    let lo_lo = x_lo * y_lo;
+    let lo_hi = x_lo * y_hi;
+    let hi_lo = x_hi * y_lo;
+    let hi_hi = x_hi * y_hi;
+
+    // The sum of three values below 2^32, so this can't overflow.
+    let mid = (lo_lo >> 32) + (lo_hi & MASK) + (hi_lo & MASK);
+    let lo = (lo_lo & MASK) | (mid << 32);
+    let hi = hi_hi + (lo_hi >> 32) + (hi_lo >> 32) + (mid >> 32);
+    (lo, hi)
+}
+
 /// Fast addition modulo ORDER for x86-64.
 /// This function is marked unsafe for the following reasons:
 ///   - It is only correct if x + y < 2**64 + ORDER = 0x1ffffffff00000001.
@@ -475,14 +586,65 @@ pub(crate) const fn to_goldilocks_array<const N: usize>(input: [u64; N]) -> [Gol
     output
 }
 
+/// Convert a constant 2d u64 array into a constant 2d Goldilocks array.
+#[inline]
+#[must_use]
+pub(crate) const fn to_goldilocks_2d_array<const N: usize, const M: usize>(
+    input: [[u64; N]; M],
+) -> [[Goldilocks; N]; M] {
+    let mut output = [[Goldilocks { value: 0 }; N]; M];
+    let mut i = 0;
+    loop {
+        if i == M {
+            break;
+        }
+        output[i] = to_goldilocks_array(input[i]);
+        i += 1;
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
-    use p3_field_testing::{test_field, test_field_dft, test_two_adic_field};
+    use p3_field_testing::{test_field, test_field_dft, test_prime_field_64, test_two_adic_field};
+    use rand::Rng;
 
     use super::*;
 
     type F = Goldilocks;
 
+    #[test]
+    fn test_two_adic_generators_table() {
+        assert_eq!(F::TWO_ADIC_GENERATORS[0], F::ONE);
+        assert_eq!(F::TWO_ADIC_GENERATORS[1], F::NEG_ONE);
+        for bits in 1..=F::TWO_ADICITY {
+            assert_eq!(
+                F::TWO_ADIC_GENERATORS[bits].square(),
+                F::TWO_ADIC_GENERATORS[bits - 1]
+            );
+        }
+        assert_eq!(
+            F::TWO_ADIC_GENERATORS[F::TWO_ADICITY],
+            F::new(1_753_635_133_440_165_772)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "has no subgroup of order 2^33, as its two-adicity is 32")]
+    fn test_two_adic_generator_too_large() {
+        let _ = F::two_adic_generator(33);
+    }
+
+    #[test]
+    fn test_serde() {
+        // Elements are serialized in canonical form, and out of range values are rejected.
+        let f = F::new(u64::MAX);
+        let serialized = serde_json::to_string(&f).unwrap();
+        assert_eq!(serialized, (u32::MAX as u64 - 1).to_string());
+        assert_eq!(serde_json::from_str::<F>(&serialized).unwrap(), f);
+        assert!(serde_json::from_str::<F>(&F::ORDER_U64.to_string()).is_err());
+    }
+
     #[test]
     fn test_goldilocks() {
         let f = F::new(100);
@@ -565,7 +727,21 @@ mod tests {
         assert_eq!(f_2.exp_u64(10540996611094048183).exp_const_u64::<7>(), f_2);
     }
 
+    #[test]
+    fn test_mul_wide_limbs() {
+        let mut rng = rand::thread_rng();
+        let edge_cases = [0, 1, u32::MAX as u64, 1 << 32, F::ORDER_U64 - 1, u64::MAX];
+        let pairs = edge_cases
+            .into_iter()
+            .flat_map(|x| edge_cases.map(|y| (x, y)))
+            .chain((0..1000).map(|_| (rng.gen(), rng.gen())));
+        for (x, y) in pairs {
+            assert_eq!(mul_wide_limbs(x, y), split(x as u128 * y as u128));
+        }
+    }
+
     test_field!(crate::Goldilocks);
+    test_prime_field_64!(crate::Goldilocks);
     test_two_adic_field!(crate::Goldilocks);
 
     test_field_dft!(radix2dit, crate::Goldilocks, p3_dft::Radix2Dit<_>);
@@ -14,10 +14,10 @@ use p3_poseidon2::{
     add_rc_and_sbox_generic, external_initial_permute_state, external_terminal_permute_state,
     internal_permute_state, matmul_internal, ExternalLayer, ExternalLayerConstants,
     ExternalLayerConstructor, HLMDSMat4, InternalLayer, InternalLayerConstructor, MDSMat4,
-    Poseidon2,
+    Poseidon2, Poseidon2DefaultParams,
 };
 
-use crate::{to_goldilocks_array, Goldilocks};
+use crate::{to_goldilocks_2d_array, to_goldilocks_array, Goldilocks};
 
 /// Degree of the chosen permutation polynomial for Goldilocks, used as the Poseidon2 S-Box.
 ///
@@ -373,6 +373,378 @@ pub const HL_GOLDILOCKS_8_INTERNAL_ROUND_CONSTANTS: [u64; 22] = [
     0xfbb7865901a1ec41,
 ];
 
+/// The round constants of the initial external rounds of the default parameters for
+/// [`Poseidon2Goldilocks<8>`], generated by the Grain LFSR of the Poseidon2 reference implementation with
+/// `R_F = 8` and `R_P = 22`.
+///
+/// These are the same as the Horizen Labs constants, [`HL_GOLDILOCKS_8_EXTERNAL_ROUND_CONSTANTS`]
+/// and [`HL_GOLDILOCKS_8_INTERNAL_ROUND_CONSTANTS`].
+pub const GOLDILOCKS_RC8_EXTERNAL_INITIAL: [[Goldilocks; 8]; 4] =
+    to_goldilocks_2d_array(HL_GOLDILOCKS_8_EXTERNAL_ROUND_CONSTANTS[0]);
+
+/// The round constants of the internal rounds of the default parameters for
+/// [`Poseidon2Goldilocks<8>`].
+pub const GOLDILOCKS_RC8_INTERNAL: [Goldilocks; 22] =
+    to_goldilocks_array(HL_GOLDILOCKS_8_INTERNAL_ROUND_CONSTANTS);
+
+/// The round constants of the terminal external rounds of the default parameters for
+/// [`Poseidon2Goldilocks<8>`].
+pub const GOLDILOCKS_RC8_EXTERNAL_TERMINAL: [[Goldilocks; 8]; 4] =
+    to_goldilocks_2d_array(HL_GOLDILOCKS_8_EXTERNAL_ROUND_CONSTANTS[1]);
+
+impl Poseidon2DefaultParams<Goldilocks, 8> for Poseidon2InternalLayerGoldilocks {
+    const NAME: &'static str = "poseidon2-goldilocks-8-v1";
+    const EXTERNAL_INITIAL: &'static [[Goldilocks; 8]] = &GOLDILOCKS_RC8_EXTERNAL_INITIAL;
+    const INTERNAL: &'static [Goldilocks] = &GOLDILOCKS_RC8_INTERNAL;
+    const EXTERNAL_TERMINAL: &'static [[Goldilocks; 8]] = &GOLDILOCKS_RC8_EXTERNAL_TERMINAL;
+}
+
+/// The round constants of the initial external rounds of the default parameters for
+/// [`Poseidon2Goldilocks<12>`], generated by the Grain LFSR of the Poseidon2 reference implementation with
+/// `R_F = 8` and `R_P = 22`.
+pub const GOLDILOCKS_RC12_EXTERNAL_INITIAL: [[Goldilocks; 12]; 4] = to_goldilocks_2d_array([
+    [
+        0x13dcf33aba214f46,
+        0x30b3b654a1da6d83,
+        0x1fc634ada6159b56,
+        0x937459964dc03466,
+        0xedd2ef2ca7949924,
+        0xede9affde0e22f68,
+        0x8515b9d6bac9282d,
+        0x6b5c07b4e9e900d8,
+        0x1ec66368838c8a08,
+        0x9042367d80d1fbab,
+        0x400283564a3c3799,
+        0x4a00be0466bca75e,
+    ],
+    [
+        0x7913beee58e3817f,
+        0xf545e88532237d90,
+        0x22f8cb8736042005,
+        0x6f04990e247a2623,
+        0xfe22e87ba37c38cd,
+        0xd20e32c85ffe2815,
+        0x117227674048fe73,
+        0x4e9fb7ea98a6b145,
+        0xe0866c232b8af08b,
+        0x00bbc77916884964,
+        0x7031c0fb990d7116,
+        0x240a9e87cf35108f,
+    ],
+    [
+        0x2e6363a5a12244b3,
+        0x5e1c3787d1b5011c,
+        0x4132660e2a196e8b,
+        0x3a013b648d3d4327,
+        0xf79839f49888ea43,
+        0xfe85658ebafe1439,
+        0xb6889825a14240bd,
+        0x578453605541382b,
+        0x4508cda8f6b63ce9,
+        0x9c3ef35848684c91,
+        0x0812bde23c87178c,
+        0xfe49638f7f722c14,
+    ],
+    [
+        0x8e3f688ce885cbf5,
+        0xb8e110acf746a87d,
+        0xb4b2e8973a6dabef,
+        0x9e714c5da3d462ec,
+        0x6438f9033d3d0c15,
+        0x24312f7cf1a27199,
+        0x23f843bb47acbf71,
+        0x9183f11a34be9f01,
+        0x839062fbb9d45dbf,
+        0x24b56e7e6c2e43fa,
+        0xe1683da61c962a72,
+        0xa95c63971a19bfa7,
+    ],
+]);
+
+/// The round constants of the internal rounds of the default parameters for
+/// [`Poseidon2Goldilocks<12>`].
+pub const GOLDILOCKS_RC12_INTERNAL: [Goldilocks; 22] = to_goldilocks_array([
+    0x4adf842aa75d4316,
+    0xf8fbb871aa4ab4eb,
+    0x68e85b6eb2dd6aeb,
+    0x07a0b06b2d270380,
+    0xd94e0228bd282de4,
+    0x8bdd91d3250c5278,
+    0x209c68b88bba778f,
+    0xb5e18cdab77f3877,
+    0xb296a3e808da93fa,
+    0x8370ecbda11a327e,
+    0x3f9075283775dad8,
+    0xb78095bb23c6aa84,
+    0x3f36b9fe72ad4e5f,
+    0x69bc96780b10b553,
+    0x3f1d341f2eb7b881,
+    0x4e939e9815838818,
+    0xda366b3ae2a31604,
+    0xbc89db1e7287d509,
+    0x6102f411f9ef5659,
+    0x58725c5e7ac1f0ab,
+    0x0df5856c798883e7,
+    0xf7bb62a8da4c961b,
+]);
+
+/// The round constants of the terminal external rounds of the default parameters for
+/// [`Poseidon2Goldilocks<12>`].
+pub const GOLDILOCKS_RC12_EXTERNAL_TERMINAL: [[Goldilocks; 12]; 4] = to_goldilocks_2d_array([
+    [
+        0xc68be7c94882a24d,
+        0xaf996d5d5cdaedd9,
+        0x9717f025e7daf6a5,
+        0x6436679e6e7216f4,
+        0x8a223d99047af267,
+        0xbb512e35a133ba9a,
+        0xfbbf44097671aa03,
+        0xf04058ebf6811e61,
+        0x5cca84703fac7ffb,
+        0x9b55c7945de6469f,
+        0x8e05bf09808e934f,
+        0x2ea900de876307d7,
+    ],
+    [
+        0x7748fff2b38dfb89,
+        0x6b99a676dd3b5d81,
+        0xac4bb7c627cf7c13,
+        0xadb6ebe5e9e2f5ba,
+        0x2d33378cafa24ae3,
+        0x1e5b73807543f8c2,
+        0x09208814bfebb10f,
+        0x782e64b6bb5b93dd,
+        0xadd5a48eac90b50f,
+        0xadd4c54c736ea4b1,
+        0xd58dbb86ed817fd8,
+        0x6d5ed1a533f34ddd,
+    ],
+    [
+        0x28686aa3e36b7cb9,
+        0x591abd3476689f36,
+        0x047d766678f13875,
+        0xa2a11112625f5b49,
+        0x21fd10a3f8304958,
+        0xf9b40711443b0280,
+        0xd2697eb8b2bde88e,
+        0x3493790b51731b3f,
+        0x11caf9dd73764023,
+        0x7acfb8f72878164e,
+        0x744ec4db23cefc26,
+        0x1e00e58f422c6340,
+    ],
+    [
+        0x21dd28d906a62dda,
+        0xf32a46ab5f465b5f,
+        0xbfce13201f3f7e6b,
+        0xf30d2e7adb5304e2,
+        0xecdf4ee4abad48e9,
+        0xf94e82182d395019,
+        0x4ee52e3744d887c5,
+        0xa1341c7cac0083b2,
+        0x2302fb26c30c834a,
+        0xaea3c587273bf7d3,
+        0xf798e24961823ec7,
+        0x962deba3e9a2cd94,
+    ],
+]);
+
+impl Poseidon2DefaultParams<Goldilocks, 12> for Poseidon2InternalLayerGoldilocks {
+    const NAME: &'static str = "poseidon2-goldilocks-12-v1";
+    const EXTERNAL_INITIAL: &'static [[Goldilocks; 12]] = &GOLDILOCKS_RC12_EXTERNAL_INITIAL;
+    const INTERNAL: &'static [Goldilocks] = &GOLDILOCKS_RC12_INTERNAL;
+    const EXTERNAL_TERMINAL: &'static [[Goldilocks; 12]] = &GOLDILOCKS_RC12_EXTERNAL_TERMINAL;
+}
+
+/// The round constants of the initial external rounds of the default parameters for
+/// [`Poseidon2Goldilocks<16>`], generated by the Grain LFSR of the Poseidon2 reference implementation with
+/// `R_F = 8` and `R_P = 22`.
+pub const GOLDILOCKS_RC16_EXTERNAL_INITIAL: [[Goldilocks; 16]; 4] = to_goldilocks_2d_array([
+    [
+        0x15ebea3fc73397c3,
+        0xd73cd9fbfe8e275c,
+        0x8c096bfce77f6c26,
+        0x4e128f68b53d8fea,
+        0x29b779a36b2763f6,
+        0xfe2adc6fb65acd08,
+        0x8d2520e725ad0955,
+        0x1c2392b214624d2a,
+        0x37482118206dcc6e,
+        0x2f829bed19be019a,
+        0x2fe298cb6f8159b0,
+        0x2bbad982deccdbbf,
+        0xbad568b8cc60a81e,
+        0xb86a814265baad10,
+        0xbec2005513b3acb3,
+        0x6bf89b59a07c2a94,
+    ],
+    [
+        0xa25deeb835e230f5,
+        0x3c5bad8512b8b12a,
+        0x7230f73c3cb7a4f2,
+        0xa70c87f095c74d0f,
+        0x6b7606b830bb2e80,
+        0x6cd467cfc4f24274,
+        0xfeed794df42a9b0a,
+        0x8cf7cf6163b7dbd3,
+        0x9a6e9dda597175a0,
+        0xaa52295a684faf7b,
+        0x017b811cc3589d8d,
+        0x55bfb699b6181648,
+        0xc2ccaf71501c2421,
+        0x1707950327596402,
+        0xdd2fcdcd42a8229f,
+        0x8b9d7d5b27778a21,
+    ],
+    [
+        0xac9a05525f9cf512,
+        0x2ba125c58627b5e8,
+        0xc74e91250a8147a5,
+        0xa3e64b640d5bb384,
+        0xf53047d18d1f9292,
+        0xbaaeddacae3a6374,
+        0xf2d0914a808b3db1,
+        0x18af1a3742bfa3b0,
+        0x9a621ef50c55bdb8,
+        0xc615f4d1cc5466f3,
+        0xb7fbac19a35cf793,
+        0xd2b1a15ba517e46d,
+        0x4a290c4d7fd26f6f,
+        0x4f0cf1bb1770c4c4,
+        0x548345386cd377f5,
+        0x33978d2789fddd42,
+    ],
+    [
+        0xab78c59deb77e211,
+        0xc485b2a933d2be7f,
+        0xbde3792c00c03c53,
+        0xab4cefe8f893d247,
+        0xc5c0e752eab7f85f,
+        0xdbf5a76f893bafea,
+        0xa91f6003e3d984de,
+        0x099539077f311e87,
+        0x097ec52232f9559e,
+        0x53641bdf8991e48c,
+        0x2afe9711d5ed9d7c,
+        0xa7b13d3661b5d117,
+        0x5a0e243fe7af6556,
+        0x1076fae8932d5f00,
+        0x9b53a83d434934e3,
+        0xed3fd595a3c0344a,
+    ],
+]);
+
+/// The round constants of the internal rounds of the default parameters for
+/// [`Poseidon2Goldilocks<16>`].
+pub const GOLDILOCKS_RC16_INTERNAL: [Goldilocks; 22] = to_goldilocks_array([
+    0x28eff4b01103d100,
+    0x60400ca3e2685a45,
+    0x1c8636beb3389b84,
+    0xac1332b60e13eff0,
+    0x2adafcc364e20f87,
+    0x79ffc2b14054ea0b,
+    0x3f98e4c0908f0a05,
+    0xcdb230bc4e8a06c4,
+    0x1bcaf7705b152a74,
+    0xd9bca249a82a7470,
+    0x91e24af19bf82551,
+    0xa62b43ba5cb78858,
+    0xb4898117472e797f,
+    0xb3228bca606cdaa0,
+    0x844461051bca39c9,
+    0xf3411581f6617d68,
+    0xf7fd50646782b533,
+    0x6ca664253c18fb48,
+    0x2d2fcdec0886a08f,
+    0x29da00dd799b575e,
+    0x47d966cc3b6e1e93,
+    0xde884e9a17ced59e,
+]);
+
+/// The round constants of the terminal external rounds of the default parameters for
+/// [`Poseidon2Goldilocks<16>`].
+pub const GOLDILOCKS_RC16_EXTERNAL_TERMINAL: [[Goldilocks; 16]; 4] = to_goldilocks_2d_array([
+    [
+        0xdacf46dc1c31a045,
+        0x5d2e3c121eb387f2,
+        0x51f8b0658b124499,
+        0x1e7dbd1daa72167d,
+        0x8275015a25c55b88,
+        0xe8521c24ac7a70b3,
+        0x6521d121c40b3f67,
+        0xac12de797de135b0,
+        0xafa28ead79f6ed6a,
+        0x685174a7a8d26f0b,
+        0xeff92a08d35d9874,
+        0x3058734b76dd123a,
+        0xfa55dcfba429f79c,
+        0x559294d4324c7728,
+        0x7a770f53012dc178,
+        0xedd8f7c408f3883b,
+    ],
+    [
+        0x39b533cf8d795fa5,
+        0x160ef9de243a8c0a,
+        0x431d52da6215fe3f,
+        0x54c51a2a2ef6d528,
+        0x9b13892b46ff9d16,
+        0x263c46fcee210289,
+        0xb738c96d25aabdc4,
+        0x5c33a5203996d38f,
+        0x2626496e7c98d8dd,
+        0xc669e0a52785903a,
+        0xaecde726c8ae1f47,
+        0x039343ef3a81e999,
+        0x2615ceaf044a54f9,
+        0x7e41e834662b66e1,
+        0x4ca5fd4895335783,
+        0x64b334d02916f2b0,
+    ],
+    [
+        0x87268837389a6981,
+        0x034b75bcb20a6274,
+        0x58e658296cc2cd6e,
+        0xe2d0f759acc31df4,
+        0x81a652e435093e20,
+        0x0b72b6e0172eaf47,
+        0x4aec43cec577d66d,
+        0xde78365b028a84e6,
+        0x444e19569adc0ee4,
+        0x942b2451fa40d1da,
+        0xe24506623ea5bd6c,
+        0x082854bf2ef7c743,
+        0x69dbbc566f59d62e,
+        0x248c38d02a7b5cb2,
+        0x4f4e8f8c09d15edb,
+        0xd96682f188d310cf,
+    ],
+    [
+        0x6f9a25d56818b54c,
+        0xb6cefed606546cd9,
+        0x5bc07523da38a67b,
+        0x7df5a3c35b8111cf,
+        0xaaa2cc5d4db34bb0,
+        0x9e673ff22a4653f8,
+        0xbd8b278d60739c62,
+        0xe10d20f6925b8815,
+        0xf6c87b91dd4da2bf,
+        0xfed623e2f71b6f1a,
+        0xa0f02fa52a94d0d3,
+        0xbb5794711b39fa16,
+        0xd3b94fba9d005c7f,
+        0x15a26e89fad946c9,
+        0xf3cb87db8a67cf49,
+        0x400d2bf56aa2a577,
+    ],
+]);
+
+impl Poseidon2DefaultParams<Goldilocks, 16> for Poseidon2InternalLayerGoldilocks {
+    const NAME: &'static str = "poseidon2-goldilocks-16-v1";
+    const EXTERNAL_INITIAL: &'static [[Goldilocks; 16]] = &GOLDILOCKS_RC16_EXTERNAL_INITIAL;
+    const INTERNAL: &'static [Goldilocks] = &GOLDILOCKS_RC16_INTERNAL;
+    const EXTERNAL_TERMINAL: &'static [[Goldilocks; 16]] = &GOLDILOCKS_RC16_EXTERNAL_TERMINAL;
+}
+
 #[cfg(test)]
 mod tests {
     use core::array;
@@ -475,4 +847,92 @@ mod tests {
         hl_poseidon2_goldilocks_width_8(&mut input);
         assert_eq!(input, expected);
     }
+
+    /// The default width 8 parameters are those of the Horizen Labs implementation.
+    #[test]
+    fn test_default_params_match_horizen_labs() {
+        let mut input: [F; 8] = array::from_fn(F::from_canonical_usize);
+        let mut expected = input;
+        hl_poseidon2_goldilocks_width_8(&mut expected);
+
+        let perm = Poseidon2GoldilocksHL::<8>::default_params();
+        perm.permute_mut(&mut input);
+        assert_eq!(input, expected);
+    }
+
+    /// The default parameters must never change, so pin them with a known answer.
+    #[test]
+    fn test_default_params_width_8() {
+        let mut input: [F; 8] = array::from_fn(F::from_canonical_usize);
+
+        let expected: [F; 8] = [
+            7137733176119375907,
+            16630800824116903194,
+            18126280226670130174,
+            9510721725595005919,
+            1121194919828798323,
+            3948592999391028991,
+            12416687089853169879,
+            17637097357382163742,
+        ]
+        .map(F::from_canonical_u64);
+
+        let perm = Poseidon2Goldilocks::<8>::default_params();
+        perm.permute_mut(&mut input);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn test_default_params_width_12() {
+        let mut input: [F; 12] = array::from_fn(F::from_canonical_usize);
+
+        let expected: [F; 12] = [
+            2348706835089070312,
+            5791659502721587828,
+            17085050014551143988,
+            7996123255527740390,
+            5364237439357105725,
+            6232166523434239419,
+            5348727751080203289,
+            3083846318448580106,
+            6651587738082512478,
+            9368625155243168894,
+            11118481721316883880,
+            10095768576572830526,
+        ]
+        .map(F::from_canonical_u64);
+
+        let perm = Poseidon2Goldilocks::<12>::default_params();
+        perm.permute_mut(&mut input);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn test_default_params_width_16() {
+        let mut input: [F; 16] = array::from_fn(F::from_canonical_usize);
+
+        let expected: [F; 16] = [
+            8968116077225421714,
+            18234030492439184886,
+            5766523242491643252,
+            9989585835705349955,
+            9471916580648752255,
+            13221557098371391083,
+            2381881788533491342,
+            12809937238705677955,
+            687349013953852007,
+            12987733614535102716,
+            12982177653566095005,
+            2680595403699246084,
+            7680361604347330536,
+            5293270010458138136,
+            14418793900752031964,
+            10444675088334886422,
+        ]
+        .map(F::from_canonical_u64);
+
+        let perm = Poseidon2Goldilocks::<16>::default_params();
+        perm.permute_mut(&mut input);
+        assert_eq!(input, expected);
+    }
 }
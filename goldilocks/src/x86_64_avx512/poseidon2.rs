@@ -0,0 +1,65 @@
+//! Poseidon2 over `PackedGoldilocksAVX512` runs the generic layers on packed values; these tests
+//! check that it agrees with the scalar permutation.
+
+#[cfg(test)]
+mod tests {
+    use p3_field::FieldAlgebra;
+    use p3_symmetric::Permutation;
+    use rand::Rng;
+
+    use crate::{Goldilocks, PackedGoldilocksAVX512, Poseidon2Goldilocks};
+
+    type F = Goldilocks;
+
+    /// Test that the output is the same as the scalar version on a random input.
+    fn check_avx512_poseidon2<const WIDTH: usize>() {
+        let mut rng = rand::thread_rng();
+
+        // Our Poseidon2 implementation.
+        let poseidon2 = Poseidon2Goldilocks::<WIDTH>::new_from_rng_128(&mut rng);
+
+        let input: [F; WIDTH] = rng.gen();
+
+        let mut expected = input;
+        poseidon2.permute_mut(&mut expected);
+
+        let mut avx512_input = input.map(PackedGoldilocksAVX512::from_f);
+        poseidon2.permute_mut(&mut avx512_input);
+
+        let avx512_output = avx512_input.map(|x| x.0[0]);
+
+        assert_eq!(avx512_output, expected);
+    }
+
+    #[test]
+    fn test_avx512_poseidon2_width_8() {
+        check_avx512_poseidon2::<8>();
+    }
+
+    #[test]
+    fn test_avx512_poseidon2_width_12() {
+        check_avx512_poseidon2::<12>();
+    }
+
+    #[test]
+    fn test_avx512_poseidon2_width_16() {
+        check_avx512_poseidon2::<16>();
+    }
+
+    /// Each lane is permuted independently.
+    #[test]
+    fn test_avx512_poseidon2_lanes_are_independent() {
+        let mut rng = rand::thread_rng();
+        let poseidon2 = Poseidon2Goldilocks::<12>::new_from_rng_128(&mut rng);
+
+        let inputs: [[F; 12]; 8] = rng.gen();
+        let mut packed: [PackedGoldilocksAVX512; 12] =
+            core::array::from_fn(|i| PackedGoldilocksAVX512(inputs.map(|input| input[i])));
+        poseidon2.permute_mut(&mut packed);
+
+        for (lane, mut expected) in inputs.into_iter().enumerate() {
+            poseidon2.permute_mut(&mut expected);
+            assert_eq!(packed.map(|x| x.0[lane]), expected);
+        }
+    }
+}
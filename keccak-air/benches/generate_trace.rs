@@ -0,0 +1,26 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use p3_baby_bear::BabyBear;
+use p3_keccak_air::{generate_trace_rows, generate_trace_rows_packed, PackedKeccakLanes};
+use rand::{thread_rng, Rng};
+
+/// Fills 2^15 rows, with a little padding.
+const NUM_PERMUTATIONS: usize = 1365;
+
+fn bench_generate_trace(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let inputs: Vec<[u64; 25]> = (0..NUM_PERMUTATIONS).map(|_| rng.gen()).collect();
+
+    let mut group = c.benchmark_group("generate keccak trace");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(NUM_PERMUTATIONS as u64));
+    group.bench_function("scalar", |b| {
+        b.iter(|| generate_trace_rows::<BabyBear>(inputs.clone()))
+    });
+    group.bench_function("packed", |b| {
+        b.iter(|| generate_trace_rows_packed::<BabyBear, PackedKeccakLanes>(inputs.clone()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_generate_trace);
+criterion_main!(benches);
@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 use p3_challenger::{HashChallenger, SerializingChallenger32};
 use p3_circle::CirclePcs;
 use p3_commit::ExtensionMmcs;
-use p3_field::extension::BinomialExtensionField;
+use p3_field::extension::ComplexBinomialExtensionField;
 use p3_fri::create_benchmark_fri_config;
 use p3_keccak::Keccak256Hash;
 use p3_keccak_air::{generate_trace_rows, KeccakAir};
@@ -32,7 +32,7 @@ fn main() -> Result<(), impl Debug> {
         .init();
 
     type Val = Mersenne31;
-    type Challenge = BinomialExtensionField<Val, 3>;
+    type Challenge = ComplexBinomialExtensionField<Val, 4>;
 
     type ByteHash = Keccak256Hash;
     type FieldHash = SerializingHasher32<ByteHash>;
@@ -66,7 +66,7 @@ fn main() -> Result<(), impl Debug> {
     let trace = generate_trace_rows::<Val>(inputs);
 
     let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let proof = prove(&config, &KeccakAir {}, &mut challenger, trace, &vec![]);
+    let proof = prove(&config, &KeccakAir {}, &mut challenger, trace, &vec![]).unwrap();
 
     let mut challenger = Challenger::from_hasher(vec![], byte_hash);
     verify(&config, &KeccakAir {}, &mut challenger, &proof, &vec![])
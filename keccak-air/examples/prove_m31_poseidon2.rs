@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 use p3_challenger::DuplexChallenger;
 use p3_circle::CirclePcs;
 use p3_commit::ExtensionMmcs;
-use p3_field::extension::BinomialExtensionField;
+use p3_field::extension::ComplexBinomialExtensionField;
 use p3_field::Field;
 use p3_fri::create_benchmark_fri_config;
 use p3_keccak_air::{generate_trace_rows, KeccakAir};
@@ -32,7 +32,7 @@ fn main() -> Result<(), impl Debug> {
         .init();
 
     type Val = Mersenne31;
-    type Challenge = BinomialExtensionField<Val, 3>;
+    type Challenge = ComplexBinomialExtensionField<Val, 4>;
 
     type Perm16 = Poseidon2Mersenne31<16>;
     let perm16 = Perm16::new_from_rng_128(&mut thread_rng());
@@ -71,7 +71,7 @@ fn main() -> Result<(), impl Debug> {
     let trace = generate_trace_rows::<Val>(inputs);
 
     let mut challenger = Challenger::new(perm24.clone());
-    let proof = prove(&config, &KeccakAir {}, &mut challenger, trace, &vec![]);
+    let proof = prove(&config, &KeccakAir {}, &mut challenger, trace, &vec![]).unwrap();
 
     let mut challenger = Challenger::new(perm24);
     verify(&config, &KeccakAir {}, &mut challenger, &proof, &vec![])
@@ -1,6 +1,14 @@
 //! An AIR for the Keccak-f permutation. Assumes the field size is between 2^16 and 2^32.
 
 #![no_std]
+#![cfg_attr(
+    all(
+        feature = "nightly-features",
+        target_arch = "x86_64",
+        target_feature = "avx512f"
+    ),
+    feature(stdarch_x86_avx512)
+)]
 
 extern crate alloc;
 
@@ -8,12 +16,14 @@ mod air;
 mod columns;
 mod constants;
 mod generation;
+mod packed_generation;
 mod round_flags;
 
 pub use air::*;
 pub use columns::*;
 pub use constants::*;
 pub use generation::*;
+pub use packed_generation::*;
 
 pub const NUM_ROUNDS: usize = 24;
 const BITS_PER_LIMB: usize = 16;
@@ -0,0 +1,453 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_field::PrimeField64;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+use tracing::instrument;
+
+use crate::columns::{KeccakCols, NUM_KECCAK_COLS};
+use crate::constants::{R, RC};
+use crate::{BITS_PER_LIMB, NUM_ROUNDS};
+
+/// A 64-bit Keccak lane of several independent permutations, one in each vector lane.
+///
+/// The packed trace generator evaluates each round on whole words of this type, rather than bit by
+/// bit, and only then splits the words into field elements.
+pub trait KeccakLanes: Copy + Send + Sync {
+    /// The number of permutations evaluated at once.
+    const WIDTH: usize;
+
+    /// Pack `lanes`, which has length `WIDTH`.
+    fn load(lanes: &[u64]) -> Self;
+
+    /// Unpack into `out`, which has length `WIDTH`.
+    fn store(self, out: &mut [u64]);
+
+    fn xor(self, other: Self) -> Self;
+
+    /// `!self & other`.
+    fn andn(self, other: Self) -> Self;
+
+    /// Rotate each lane left by `n < 64` bits.
+    fn rotate_left(self, n: u32) -> Self;
+}
+
+impl KeccakLanes for u64 {
+    const WIDTH: usize = 1;
+
+    #[inline]
+    fn load(lanes: &[u64]) -> Self {
+        lanes[0]
+    }
+
+    #[inline]
+    fn store(self, out: &mut [u64]) {
+        out[0] = self;
+    }
+
+    #[inline]
+    fn xor(self, other: Self) -> Self {
+        self ^ other
+    }
+
+    #[inline]
+    fn andn(self, other: Self) -> Self {
+        !self & other
+    }
+
+    #[inline]
+    fn rotate_left(self, n: u32) -> Self {
+        u64::rotate_left(self, n)
+    }
+}
+
+impl<const N: usize> KeccakLanes for [u64; N] {
+    const WIDTH: usize = N;
+
+    #[inline]
+    fn load(lanes: &[u64]) -> Self {
+        lanes.try_into().unwrap()
+    }
+
+    #[inline]
+    fn store(self, out: &mut [u64]) {
+        out.copy_from_slice(&self);
+    }
+
+    #[inline]
+    fn xor(self, other: Self) -> Self {
+        core::array::from_fn(|i| self[i] ^ other[i])
+    }
+
+    #[inline]
+    fn andn(self, other: Self) -> Self {
+        core::array::from_fn(|i| !self[i] & other[i])
+    }
+
+    #[inline]
+    fn rotate_left(self, n: u32) -> Self {
+        self.map(|lane| lane.rotate_left(n))
+    }
+}
+
+#[cfg(all(
+    feature = "nightly-features",
+    target_arch = "x86_64",
+    target_feature = "avx512f"
+))]
+mod avx512 {
+    use core::arch::x86_64::*;
+
+    use super::KeccakLanes;
+
+    // Safety: the intrinsics below only need AVX512F, which this module is gated on, and the loads
+    // and stores are unaligned, of slices of length `WIDTH`.
+    impl KeccakLanes for __m512i {
+        const WIDTH: usize = 8;
+
+        #[inline]
+        fn load(lanes: &[u64]) -> Self {
+            assert_eq!(lanes.len(), Self::WIDTH);
+            unsafe { _mm512_loadu_si512(lanes.as_ptr().cast()) }
+        }
+
+        #[inline]
+        fn store(self, out: &mut [u64]) {
+            assert_eq!(out.len(), Self::WIDTH);
+            unsafe { _mm512_storeu_si512(out.as_mut_ptr().cast(), self) }
+        }
+
+        #[inline]
+        fn xor(self, other: Self) -> Self {
+            unsafe { _mm512_xor_si512(self, other) }
+        }
+
+        #[inline]
+        fn andn(self, other: Self) -> Self {
+            unsafe { _mm512_andnot_si512(self, other) }
+        }
+
+        #[inline]
+        fn rotate_left(self, n: u32) -> Self {
+            unsafe { _mm512_rolv_epi64(self, _mm512_set1_epi64(n as i64)) }
+        }
+    }
+
+    pub type PackedKeccakLanes = __m512i;
+}
+
+#[cfg(all(
+    target_arch = "x86_64",
+    target_feature = "avx2",
+    not(all(feature = "nightly-features", target_feature = "avx512f"))
+))]
+mod avx2 {
+    use core::arch::x86_64::*;
+
+    use super::KeccakLanes;
+
+    // Safety: the intrinsics below only need AVX2, which this module is gated on, and the loads and
+    // stores are unaligned, of slices of length `WIDTH`.
+    impl KeccakLanes for __m256i {
+        const WIDTH: usize = 4;
+
+        #[inline]
+        fn load(lanes: &[u64]) -> Self {
+            assert_eq!(lanes.len(), Self::WIDTH);
+            unsafe { _mm256_loadu_si256(lanes.as_ptr().cast()) }
+        }
+
+        #[inline]
+        fn store(self, out: &mut [u64]) {
+            assert_eq!(out.len(), Self::WIDTH);
+            unsafe { _mm256_storeu_si256(out.as_mut_ptr().cast(), self) }
+        }
+
+        #[inline]
+        fn xor(self, other: Self) -> Self {
+            unsafe { _mm256_xor_si256(self, other) }
+        }
+
+        #[inline]
+        fn andn(self, other: Self) -> Self {
+            unsafe { _mm256_andnot_si256(self, other) }
+        }
+
+        #[inline]
+        fn rotate_left(self, n: u32) -> Self {
+            // Shifting by 64 gives zero, so a rotation by 0 is correct too.
+            unsafe {
+                let left = _mm256_sll_epi64(self, _mm_cvtsi32_si128(n as i32));
+                let right = _mm256_srl_epi64(self, _mm_cvtsi32_si128(64 - n as i32));
+                _mm256_or_si256(left, right)
+            }
+        }
+    }
+
+    pub type PackedKeccakLanes = __m256i;
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod neon {
+    use core::arch::aarch64::*;
+
+    use super::KeccakLanes;
+
+    // Safety: the intrinsics below only need NEON, which this module is gated on, and the loads and
+    // stores are of slices of length `WIDTH`.
+    impl KeccakLanes for uint64x2_t {
+        const WIDTH: usize = 2;
+
+        #[inline]
+        fn load(lanes: &[u64]) -> Self {
+            assert_eq!(lanes.len(), Self::WIDTH);
+            unsafe { vld1q_u64(lanes.as_ptr()) }
+        }
+
+        #[inline]
+        fn store(self, out: &mut [u64]) {
+            assert_eq!(out.len(), Self::WIDTH);
+            unsafe { vst1q_u64(out.as_mut_ptr(), self) }
+        }
+
+        #[inline]
+        fn xor(self, other: Self) -> Self {
+            unsafe { veorq_u64(self, other) }
+        }
+
+        #[inline]
+        fn andn(self, other: Self) -> Self {
+            unsafe { vbicq_u64(other, self) }
+        }
+
+        #[inline]
+        fn rotate_left(self, n: u32) -> Self {
+            // A negative shift is to the right, and shifting by 64 gives zero, so a rotation by 0
+            // is correct too.
+            unsafe {
+                let left = vshlq_u64(self, vdupq_n_s64(n as i64));
+                let right = vshlq_u64(self, vdupq_n_s64(n as i64 - 64));
+                vorrq_u64(left, right)
+            }
+        }
+    }
+
+    pub type PackedKeccakLanes = uint64x2_t;
+}
+
+#[cfg(all(
+    feature = "nightly-features",
+    target_arch = "x86_64",
+    target_feature = "avx512f"
+))]
+pub use avx512::PackedKeccakLanes;
+
+#[cfg(all(
+    target_arch = "x86_64",
+    target_feature = "avx2",
+    not(all(feature = "nightly-features", target_feature = "avx512f"))
+))]
+pub use avx2::PackedKeccakLanes;
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub use neon::PackedKeccakLanes;
+
+/// The widest [`KeccakLanes`] available on the target, or `u64` where there are no vector
+/// registers to use.
+#[cfg(not(any(
+    all(target_arch = "x86_64", target_feature = "avx2"),
+    all(target_arch = "aarch64", target_feature = "neon"),
+)))]
+pub type PackedKeccakLanes = u64;
+
+/// Generate the same trace as [`generate_trace_rows`](crate::generate_trace_rows), evaluating
+/// `L::WIDTH` permutations at a time.
+#[instrument(name = "generate packed Keccak trace", skip_all)]
+pub fn generate_trace_rows_packed<F: PrimeField64, L: KeccakLanes>(
+    inputs: Vec<[u64; 25]>,
+) -> RowMajorMatrix<F> {
+    let num_rows = (inputs.len() * NUM_ROUNDS).next_power_of_two();
+    let mut trace = RowMajorMatrix::new(F::zero_vec(num_rows * NUM_KECCAK_COLS), NUM_KECCAK_COLS);
+    let (prefix, rows, suffix) = unsafe { trace.values.align_to_mut::<KeccakCols<F>>() };
+    assert!(prefix.is_empty(), "Alignment should match");
+    assert!(suffix.is_empty(), "Alignment should match");
+    assert_eq!(rows.len(), num_rows);
+
+    // As in the scalar generator, the rows past the inputs are filled with permutations of zero.
+    let num_perms = num_rows.div_ceil(NUM_ROUNDS);
+    let mut padded_inputs = inputs;
+    padded_inputs.resize(num_perms.next_multiple_of(L::WIDTH), [0; 25]);
+
+    rows.par_chunks_mut(NUM_ROUNDS * L::WIDTH)
+        .zip(padded_inputs.par_chunks(L::WIDTH))
+        .for_each(|(rows, inputs)| generate_trace_rows_for_perms::<F, L>(rows, inputs));
+
+    trace
+}
+
+/// The words of one round of a single permutation's trace, indexed as in [`KeccakCols`].
+#[derive(Clone)]
+struct RoundWords {
+    a: [[u64; 5]; 5],
+    c: [u64; 5],
+    c_prime: [u64; 5],
+    a_prime: [[u64; 5]; 5],
+    a_prime_prime: [[u64; 5]; 5],
+    a_prime_prime_prime_0_0: u64,
+}
+
+/// `rows` will normally consist of `24 * L::WIDTH` rows, with an exception for the final chunk,
+/// whose last permutations may be cut short or missing.
+fn generate_trace_rows_for_perms<F: PrimeField64, L: KeccakLanes>(
+    rows: &mut [KeccakCols<F>],
+    inputs: &[[u64; 25]],
+) {
+    let mut state: [[L; 5]; 5] = core::array::from_fn(|y| {
+        core::array::from_fn(|x| {
+            let lanes = inputs.iter().map(|input| input[y * 5 + x]);
+            L::load(&lanes.collect::<Vec<_>>())
+        })
+    });
+
+    let mut buf = vec![0; L::WIDTH];
+    let mut words = vec![
+        RoundWords {
+            a: [[0; 5]; 5],
+            c: [0; 5],
+            c_prime: [0; 5],
+            a_prime: [[0; 5]; 5],
+            a_prime_prime: [[0; 5]; 5],
+            a_prime_prime_prime_0_0: 0,
+        };
+        L::WIDTH
+    ];
+
+    for round in 0..NUM_ROUNDS {
+        let c: [L; 5] =
+            core::array::from_fn(|x| (1..5).fold(state[0][x], |acc, y| acc.xor(state[y][x])));
+        let c_prime: [L; 5] =
+            core::array::from_fn(|x| c[x].xor(c[(x + 4) % 5]).xor(c[(x + 1) % 5].rotate_left(1)));
+        let a_prime: [[L; 5]; 5] = core::array::from_fn(|y| {
+            core::array::from_fn(|x| state[y][x].xor(c[x]).xor(c_prime[x]))
+        });
+        // B[x, y] = ROT(A'[a, b], r[a, b]) for a = (x + 3y) % 5 and b = x, as in `KeccakCols::b`.
+        let b: [[L; 5]; 5] = core::array::from_fn(|y| {
+            core::array::from_fn(|x| {
+                let a = (x + 3 * y) % 5;
+                a_prime[x][a].rotate_left(R[a][x] as u32)
+            })
+        });
+        let a_prime_prime: [[L; 5]; 5] = core::array::from_fn(|y| {
+            core::array::from_fn(|x| b[y][x].xor(b[y][(x + 1) % 5].andn(b[y][(x + 2) % 5])))
+        });
+        let rc = L::load(&vec![RC[round]; L::WIDTH]);
+        let a_prime_prime_prime_0_0 = a_prime_prime[0][0].xor(rc);
+
+        for i in 0..25 {
+            let (y, x) = (i / 5, i % 5);
+            unpack(state[y][x], &mut buf, &mut words, |w, v| w.a[y][x] = v);
+            unpack(a_prime[y][x], &mut buf, &mut words, |w, v| {
+                w.a_prime[y][x] = v
+            });
+            unpack(a_prime_prime[y][x], &mut buf, &mut words, |w, v| {
+                w.a_prime_prime[y][x] = v
+            });
+        }
+        for (x, (&c, &c_prime)) in c.iter().zip(&c_prime).enumerate() {
+            unpack(c, &mut buf, &mut words, |w, v| w.c[x] = v);
+            unpack(c_prime, &mut buf, &mut words, |w, v| w.c_prime[x] = v);
+        }
+        unpack(a_prime_prime_prime_0_0, &mut buf, &mut words, |w, v| {
+            w.a_prime_prime_prime_0_0 = v
+        });
+
+        for (perm, (input, perm_words)) in inputs.iter().zip(&words).enumerate() {
+            if let Some(row) = rows.get_mut(perm * NUM_ROUNDS + round) {
+                fill_row(row, round, input, perm_words);
+            }
+        }
+
+        state = a_prime_prime;
+        state[0][0] = a_prime_prime_prime_0_0;
+    }
+}
+
+/// Spread the lanes of `word` across the permutations' `words`, using `set`.
+fn unpack<L: KeccakLanes>(
+    word: L,
+    buf: &mut [u64],
+    words: &mut [RoundWords],
+    set: impl Fn(&mut RoundWords, u64),
+) {
+    word.store(buf);
+    for (perm_words, &lane) in words.iter_mut().zip(buf.iter()) {
+        set(perm_words, lane);
+    }
+}
+
+fn fill_row<F: PrimeField64>(
+    row: &mut KeccakCols<F>,
+    round: usize,
+    input: &[u64; 25],
+    words: &RoundWords,
+) {
+    let limbs = |word: u64| {
+        core::array::from_fn(|limb| {
+            F::from_canonical_u64((word >> (limb * BITS_PER_LIMB)) & 0xFFFF)
+        })
+    };
+    let bits = |word: u64| core::array::from_fn(|z| F::from_bool(((word >> z) & 1) != 0));
+
+    row.step_flags[round] = F::ONE;
+    for y in 0..5 {
+        for x in 0..5 {
+            row.preimage[y][x] = limbs(input[y * 5 + x]);
+            row.a[y][x] = limbs(words.a[y][x]);
+            row.a_prime[y][x] = bits(words.a_prime[y][x]);
+            row.a_prime_prime[y][x] = limbs(words.a_prime_prime[y][x]);
+        }
+    }
+    row.c = words.c.map(bits);
+    row.c_prime = words.c_prime.map(bits);
+    row.a_prime_prime_0_0_bits = bits(words.a_prime_prime[0][0]);
+    row.a_prime_prime_prime_0_0_limbs = limbs(words.a_prime_prime_prime_0_0);
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use rand::Rng;
+
+    use super::*;
+    use crate::generate_trace_rows;
+
+    fn assert_matches_scalar<L: KeccakLanes>(inputs: Vec<[u64; 25]>) {
+        let scalar = generate_trace_rows::<BabyBear>(inputs.clone());
+        let packed = generate_trace_rows_packed::<BabyBear, L>(inputs);
+        assert_eq!(packed.width, scalar.width);
+        assert_eq!(packed.values, scalar.values);
+    }
+
+    fn assert_all_lanes_match_scalar(inputs: Vec<[u64; 25]>) {
+        assert_matches_scalar::<u64>(inputs.clone());
+        assert_matches_scalar::<[u64; 3]>(inputs.clone());
+        assert_matches_scalar::<[u64; 8]>(inputs.clone());
+        assert_matches_scalar::<PackedKeccakLanes>(inputs);
+    }
+
+    #[test]
+    fn test_zero_input() {
+        assert_all_lanes_match_scalar(vec![[0; 25]]);
+    }
+
+    #[test]
+    fn test_random_inputs() {
+        let mut rng = rand::thread_rng();
+        // 11 permutations leave the last of them cut short, and some lane groups partly empty.
+        for num_inputs in [1, 2, 11] {
+            let inputs = (0..num_inputs).map(|_| rng.gen()).collect();
+            assert_all_lanes_match_scalar(inputs);
+        }
+    }
+}
@@ -10,7 +10,7 @@
     feature(stdarch_x86_avx512)
 )]
 
-use p3_symmetric::{CryptographicHasher, CryptographicPermutation, Permutation};
+use p3_symmetric::{CryptographicHasher, CryptographicPermutation, Permutation, StatefulHasher};
 use tiny_keccak::{keccakf, Hasher, Keccak};
 
 #[cfg(all(
@@ -120,25 +120,37 @@ impl CryptographicHasher<u8, [u8; 32]> for Keccak256Hash {
         I: IntoIterator<Item = u8>,
     {
         const BUFLEN: usize = 512; // Tweakable parameter; determined by experiment
-        let mut hasher = Keccak::v256();
-        p3_util::apply_to_chunks::<BUFLEN, _, _>(input, |buf| hasher.update(buf));
-
-        let mut output = [0u8; 32];
-        hasher.finalize(&mut output);
-        output
+        let mut state = self.new_state();
+        p3_util::apply_to_chunks::<BUFLEN, _, _>(input, |buf| self.update(&mut state, buf));
+        self.finalize(state)
     }
 
     fn hash_iter_slices<'a, I>(&self, input: I) -> [u8; 32]
     where
         I: IntoIterator<Item = &'a [u8]>,
     {
-        let mut hasher = Keccak::v256();
+        let mut state = self.new_state();
         for chunk in input.into_iter() {
-            hasher.update(chunk);
+            self.update(&mut state, chunk);
         }
+        self.finalize(state)
+    }
+}
+
+impl StatefulHasher<u8, [u8; 32]> for Keccak256Hash {
+    type State = Keccak;
+
+    fn new_state(&self) -> Self::State {
+        Keccak::v256()
+    }
+
+    fn update(&self, state: &mut Self::State, input: &[u8]) {
+        state.update(input);
+    }
 
+    fn finalize(&self, state: Self::State) -> [u8; 32] {
         let mut output = [0u8; 32];
-        hasher.finalize(&mut output);
+        state.finalize(&mut output);
         output
     }
 }
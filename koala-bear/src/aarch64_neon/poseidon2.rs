@@ -1,15 +1,76 @@
-//! Eventually this will hold a vectorized Neon implementation of Poseidon2 for PackedKoalaBearNeon
-//! Currently this is essentially a placeholder to allow compilation and testing on Neon devices.
+//! Neon implementation of the Poseidon2 internal linear layer for PackedKoalaBearNeon.
 //!
-//! Converting the AVX2/AVX512 code across to Neon is on the TODO list.
+//! Width 24 multiplies by the diagonal using shifts, width 16 still uses the generic layer.
+
+use p3_field::FieldAlgebra;
+use p3_monty_31::{halve_neon, mul_neg_2exp_neg_n_neon, InternalLayerParametersNeon};
+
+use crate::{KoalaBearInternalLayerParameters, KoalaBearParameters, PackedKoalaBearNeon};
+
+impl InternalLayerParametersNeon<KoalaBearParameters, 16> for KoalaBearInternalLayerParameters {}
+
+impl InternalLayerParametersNeon<KoalaBearParameters, 24> for KoalaBearInternalLayerParameters {
+    /// For the KoalaBear field and width 24 we multiply by the diagonal matrix:
+    /// D = [-2, 1, 2, 1/2, 3, 4, -1/2, -3, -4, 1/2^8, 1/4, 1/8, 1/16, 1/32, 1/64, 1/2^24, -1/2^8, -1/8, -1/16, -1/32, -1/64, -1/2^7, -1/2^9, -1/2^24]
+    /// and then add the sum of the state to every element.
+    #[inline(always)]
+    fn internal_layer_mat_mul_neon(state: &mut [PackedKoalaBearNeon; 24]) {
+        let part_sum = state[1..]
+            .iter()
+            .fold(PackedKoalaBearNeon::ZERO, |acc, &x| acc + x);
+        let full_sum = part_sum + state[0];
+
+        // state[0] -> sum - 2*state[0]
+        state[0] = part_sum - state[0];
+
+        // state[1] is multiplied by 1, so is left as is.
+        state[2] = state[2].double();
+        state[3] = halve_neon(state[3]);
+        state[4] = state[4].double() + state[4];
+        state[5] = state[5].double().double();
+
+        // The following 10 muls (from state[6] to state[15]) output the negative of what we want.
+        // This is handled when the sum is added.
+        state[6] = halve_neon(state[6]);
+        state[7] = state[7].double() + state[7];
+        state[8] = state[8].double().double();
+        state[9] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 8, 16>(state[9]);
+        state[10] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 2, 22>(state[10]);
+        state[11] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 3, 21>(state[11]);
+        state[12] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 4, 20>(state[12]);
+        state[13] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 5, 19>(state[13]);
+        state[14] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 6, 18>(state[14]);
+        state[15] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 24, 0>(state[15]);
+
+        // The remaining muls output the correct value again.
+        state[16] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 8, 16>(state[16]);
+        state[17] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 3, 21>(state[17]);
+        state[18] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 4, 20>(state[18]);
+        state[19] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 5, 19>(state[19]);
+        state[20] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 6, 18>(state[20]);
+        state[21] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 7, 17>(state[21]);
+        state[22] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 9, 15>(state[22]);
+        state[23] = mul_neg_2exp_neg_n_neon::<KoalaBearParameters, 24, 0>(state[23]);
+
+        state[1..6].iter_mut().for_each(|x| *x = full_sum + *x);
+        state[6..16].iter_mut().for_each(|x| *x = full_sum - *x);
+        state[16..].iter_mut().for_each(|x| *x = full_sum + *x);
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use p3_field::FieldAlgebra;
+    use p3_field_testing::check_packed_agreement;
+    use p3_monty_31::packed_ops::{halve, halve_neon, mul_neg_2exp_neg_n, mul_neg_2exp_neg_n_neon};
+    use p3_monty_31::{InternalLayerBaseParameters, InternalLayerParametersNeon};
     use p3_symmetric::Permutation;
     use rand::Rng;
 
-    use crate::{KoalaBear, PackedKoalaBearNeon, Poseidon2KoalaBear};
+    use crate::{
+        KoalaBear, KoalaBearInternalLayerParameters, KoalaBearParameters, PackedKoalaBearNeon,
+        Poseidon2KoalaBear,
+    };
 
     type F = KoalaBear;
     type Perm16 = Poseidon2KoalaBear<16>;
@@ -56,4 +117,68 @@ mod tests {
 
         assert_eq!(neon_output, expected);
     }
+
+    /// Test that the width 24 linear layer matches the generic one in every lane.
+    #[test]
+    fn test_neon_internal_layer_width_24() {
+        let mut rng = rand::thread_rng();
+
+        let input: [PackedKoalaBearNeon; 24] =
+            core::array::from_fn(|_| PackedKoalaBearNeon(rng.gen()));
+
+        let mut expected = input;
+        KoalaBearInternalLayerParameters::generic_internal_linear_layer(&mut expected);
+
+        let mut output = input;
+        KoalaBearInternalLayerParameters::internal_layer_mat_mul_neon(&mut output);
+
+        assert_eq!(output, expected);
+    }
+
+    /// Check that `packed` agrees with `scalar` in every lane.
+    fn check_agreement(
+        packed: impl Fn(PackedKoalaBearNeon) -> PackedKoalaBearNeon,
+        scalar: impl Fn(F) -> F,
+    ) {
+        // SAFETY: `F` is its MONTY form and `PackedKoalaBearNeon` has the layout of `[u32; 4]`.
+        unsafe { check_packed_agreement::<F, _, 4>(packed, scalar) }
+    }
+
+    /// Test the packed multiplications by powers of two against the scalar versions.
+    #[test]
+    fn test_neon_packed_ops_match_scalar() {
+        type P = KoalaBearParameters;
+
+        check_agreement(halve_neon::<P>, halve);
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 1, 23>(x),
+            |x| mul_neg_2exp_neg_n(x, 1),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 2, 22>(x),
+            |x| mul_neg_2exp_neg_n(x, 2),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 3, 21>(x),
+            |x| mul_neg_2exp_neg_n(x, 3),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 7, 17>(x),
+            |x| mul_neg_2exp_neg_n(x, 7),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 9, 15>(x),
+            |x| mul_neg_2exp_neg_n(x, 9),
+        );
+        check_agreement(
+            |x| mul_neg_2exp_neg_n_neon::<P, 14, 10>(x),
+            |x| mul_neg_2exp_neg_n(x, 14),
+        );
+        check_agreement(mul_neg_2exp_neg_n_neon::<P, 8, 16>, |x| {
+            mul_neg_2exp_neg_n(x, 8)
+        });
+        check_agreement(mul_neg_2exp_neg_n_neon::<P, 24, 0>, |x| {
+            mul_neg_2exp_neg_n(x, 24)
+        });
+    }
 }
@@ -102,13 +102,92 @@ impl BinomialExtensionData<4> for KoalaBearParameters {
 
 #[cfg(test)]
 mod tests {
-    use p3_field::{PrimeField32, PrimeField64, TwoAdicField};
-    use p3_field_testing::{test_field, test_field_dft, test_two_adic_field};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::{
+        reinterpret_canonical, reinterpret_canonical_slice, try_from_canonical_u32_slice,
+        PrimeField32, PrimeField64, TwoAdicField,
+    };
+    use p3_field_testing::{test_field, test_field_dft, test_prime_field_64, test_two_adic_field};
 
     use super::*;
 
     type F = KoalaBear;
 
+    #[test]
+    fn test_try_from_canonical_u32() {
+        assert_eq!(F::try_from_canonical_u32(0), Some(F::ZERO));
+        assert_eq!(F::try_from_canonical_u32(1), Some(F::ONE));
+        assert_eq!(
+            F::try_from_canonical_u32(F::ORDER_U32 - 1),
+            Some(F::NEG_ONE)
+        );
+        assert_eq!(F::try_from_canonical_u32(F::ORDER_U32), None);
+        assert_eq!(F::try_from_canonical_u32(u32::MAX), None);
+        assert_eq!(
+            F::try_from_canonical_u64(F::ORDER_U64 - 1),
+            Some(F::NEG_ONE)
+        );
+        assert_eq!(F::try_from_canonical_u64(F::ORDER_U64), None);
+
+        assert_eq!(
+            try_from_canonical_u32_slice::<F>(&[0, 1, F::ORDER_U32 - 1]),
+            Some(vec![F::ZERO, F::ONE, F::NEG_ONE])
+        );
+        assert_eq!(try_from_canonical_u32_slice::<F>(&[0, F::ORDER_U32]), None);
+    }
+
+    #[test]
+    fn test_reinterpret_canonical_baby_bear() {
+        // The BabyBear prime is smaller than the KoalaBear one, so every BabyBear value fits but
+        // only KoalaBear values below the BabyBear prime go the other way.
+        let bb_p = BabyBear::ORDER_U32;
+        assert!(bb_p < F::ORDER_U32);
+
+        // The two fields have different MONTY forms, so this checks the values go across
+        // canonically.
+        let bb_values = [0, 1, 2, bb_p - 2, bb_p - 1];
+        for x in bb_values {
+            let bb = BabyBear::from_canonical_u32(x);
+            let kb = F::from_canonical_u32(x);
+            assert_eq!(reinterpret_canonical::<BabyBear, F>(bb), Some(kb));
+            assert_eq!(reinterpret_canonical::<F, BabyBear>(kb), Some(bb));
+        }
+        assert_eq!(
+            reinterpret_canonical::<BabyBear, F>(BabyBear::NEG_ONE),
+            Some(F::from_canonical_u32(bb_p - 1))
+        );
+
+        for x in [bb_p, bb_p + 1, F::ORDER_U32 - 1] {
+            assert_eq!(
+                reinterpret_canonical::<F, BabyBear>(F::from_canonical_u32(x)),
+                None
+            );
+        }
+
+        let bb: Vec<BabyBear> = bb_values.map(BabyBear::from_canonical_u32).to_vec();
+        let kb: Vec<F> = bb_values.map(F::from_canonical_u32).to_vec();
+        assert_eq!(
+            reinterpret_canonical_slice::<BabyBear, F>(&bb),
+            Some(kb.clone())
+        );
+        assert_eq!(reinterpret_canonical_slice::<F, BabyBear>(&kb), Some(bb));
+
+        let straddling = [F::ONE, F::from_canonical_u32(bb_p)];
+        assert_eq!(
+            reinterpret_canonical_slice::<F, BabyBear>(&straddling),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "has no subgroup of order 2^28, as its two-adicity is 24")]
+    fn test_two_adic_generator_too_large() {
+        let _ = F::two_adic_generator(28);
+    }
+
     #[test]
     fn test_koala_bear_two_adicity_generators() {
         let base = KoalaBear::from_canonical_u32(0x6ac49f88);
@@ -203,9 +282,15 @@ mod tests {
         let m2_serialized = serde_json::to_string(&m2).unwrap();
         let m2_deserialized: F = serde_json::from_str(&m2_serialized).unwrap();
         assert_eq!(m2, m2_deserialized);
+
+        // Elements are serialized in canonical form, and out of range values are rejected.
+        assert_eq!(serde_json::to_string(&f_2).unwrap(), "2");
+        let order_serialized = serde_json::to_string(&F::ORDER_U32).unwrap();
+        assert!(serde_json::from_str::<F>(&order_serialized).is_err());
     }
 
     test_field!(crate::KoalaBear);
+    test_prime_field_64!(crate::KoalaBear);
     test_two_adic_field!(crate::KoalaBear);
 
     test_field_dft!(radix2dit, crate::KoalaBear, p3_dft::Radix2Dit<_>);
@@ -20,7 +20,7 @@ use p3_monty_31::{
     GenericPoseidon2LinearLayersMonty31, InternalLayerBaseParameters, InternalLayerParameters,
     MontyField31, Poseidon2ExternalLayerMonty31, Poseidon2InternalLayerMonty31,
 };
-use p3_poseidon2::Poseidon2;
+use p3_poseidon2::{Poseidon2, Poseidon2DefaultParams};
 
 use crate::{KoalaBear, KoalaBearParameters};
 
@@ -258,16 +258,164 @@ impl InternalLayerBaseParameters<KoalaBearParameters, 24> for KoalaBearInternalL
 impl InternalLayerParameters<KoalaBearParameters, 16> for KoalaBearInternalLayerParameters {}
 impl InternalLayerParameters<KoalaBearParameters, 24> for KoalaBearInternalLayerParameters {}
 
+/// The round constants of the initial external rounds of the default parameters for
+/// [`Poseidon2KoalaBear<16>`], generated by the Grain LFSR of the Poseidon2 reference implementation with
+/// `R_F = 8` and `R_P = 20`.
+pub const KOALABEAR_RC16_EXTERNAL_INITIAL: [[KoalaBear; 16]; 4] = KoalaBear::new_2d_array([
+    [
+        0x7ee56a48, 0x11367045, 0x12e41941, 0x7ebbc12b, 0x1970b7d5, 0x662b60e8, 0x3e4990c6,
+        0x679f91f5, 0x350813bb, 0x00874ad4, 0x28a0081a, 0x18fa5872, 0x5f25b071, 0x5e5d5998,
+        0x5e6fd3e7, 0x5b2e2660,
+    ],
+    [
+        0x6f1837bf, 0x3fe6182b, 0x1edd7ac5, 0x57470d00, 0x43d486d5, 0x1982c70f, 0x0ea53af9,
+        0x61d6165b, 0x51639c00, 0x2dec352c, 0x2950e531, 0x2d2cb947, 0x08256cef, 0x1a0109f6,
+        0x1f51faf3, 0x5cef1c62,
+    ],
+    [
+        0x3d65e50e, 0x33d91626, 0x133d5a1e, 0x0ff49b0d, 0x38900cd1, 0x2c22cc3f, 0x28852bb2,
+        0x06c65a02, 0x7b2cf7bc, 0x68016e1a, 0x15e16bc0, 0x5248149a, 0x6dd212a0, 0x18d6830a,
+        0x5001be82, 0x64dac34e,
+    ],
+    [
+        0x5902b287, 0x426583a0, 0x0c921632, 0x3fe028a5, 0x245f8e49, 0x43bb297e, 0x7873dbd9,
+        0x3cc987df, 0x286bb4ce, 0x640a8dcd, 0x512a8e36, 0x03a4cf55, 0x481837a2, 0x03d6da84,
+        0x73726ac7, 0x760e7fdf,
+    ],
+]);
+
+/// The round constants of the internal rounds of the default parameters for
+/// [`Poseidon2KoalaBear<16>`].
+pub const KOALABEAR_RC16_INTERNAL: [KoalaBear; 20] = KoalaBear::new_array([
+    0x54dfeb5d, 0x7d40afd6, 0x722cb316, 0x106a4573, 0x45a7ccdb, 0x44061375, 0x154077a5, 0x45744faa,
+    0x4eb5e5ee, 0x3794e83f, 0x47c7093c, 0x5694903c, 0x69cb6299, 0x373df84c, 0x46a0df58, 0x46b8758a,
+    0x3241ebcb, 0x0b09d233, 0x1af42357, 0x1e66cec2,
+]);
+
+/// The round constants of the terminal external rounds of the default parameters for
+/// [`Poseidon2KoalaBear<16>`].
+pub const KOALABEAR_RC16_EXTERNAL_TERMINAL: [[KoalaBear; 16]; 4] = KoalaBear::new_2d_array([
+    [
+        0x43e7dc24, 0x259a5d61, 0x27e85a3b, 0x1b9133fa, 0x343e5628, 0x485cd4c2, 0x16e269f5,
+        0x165b60c6, 0x25f683d9, 0x124f81f9, 0x174331f9, 0x77344dc5, 0x5a821dba, 0x5fc4177f,
+        0x54153bf5, 0x5e3f1194,
+    ],
+    [
+        0x3bdbf191, 0x088c84a3, 0x68256c9b, 0x3c90bbc6, 0x6846166a, 0x03f4238d, 0x463335fb,
+        0x5e3d3551, 0x6e59ae6f, 0x32d06cc0, 0x596293f3, 0x6c87edb2, 0x08fc60b5, 0x34bcca80,
+        0x24f007f3, 0x62731c6f,
+    ],
+    [
+        0x1e1db6c6, 0x0ca409bb, 0x585c1e78, 0x56e94edc, 0x16d22734, 0x18e11467, 0x7b2c3730,
+        0x770075e4, 0x35d1b18c, 0x22be3db5, 0x4fb1fbb7, 0x477cb3ed, 0x7d5311c6, 0x5b62ae7d,
+        0x559c5fa8, 0x77f15048,
+    ],
+    [
+        0x3211570b, 0x490fef6a, 0x77ec311f, 0x2247171b, 0x4e0ac711, 0x2edf69c9, 0x3b5a8850,
+        0x65809421, 0x5619b4aa, 0x362019a7, 0x6bf9d4ed, 0x5b413dff, 0x617e181e, 0x5e7ab57b,
+        0x33ad7833, 0x3466c7ca,
+    ],
+]);
+
+impl Poseidon2DefaultParams<KoalaBear, 16> for KoalaBearInternalLayerParameters {
+    const NAME: &'static str = "poseidon2-koalabear-16-v1";
+    const EXTERNAL_INITIAL: &'static [[KoalaBear; 16]] = &KOALABEAR_RC16_EXTERNAL_INITIAL;
+    const INTERNAL: &'static [KoalaBear] = &KOALABEAR_RC16_INTERNAL;
+    const EXTERNAL_TERMINAL: &'static [[KoalaBear; 16]] = &KOALABEAR_RC16_EXTERNAL_TERMINAL;
+}
+
+/// The round constants of the initial external rounds of the default parameters for
+/// [`Poseidon2KoalaBear<24>`], generated by the Grain LFSR of the Poseidon2 reference implementation with
+/// `R_F = 8` and `R_P = 23`.
+pub const KOALABEAR_RC24_EXTERNAL_INITIAL: [[KoalaBear; 24]; 4] = KoalaBear::new_2d_array([
+    [
+        0x1d0939dc, 0x6d050f8d, 0x628058ad, 0x2681385d, 0x3e3c62be, 0x032cfad8, 0x5a91ba3c,
+        0x015a56e6, 0x696b889c, 0x0dbcd780, 0x5881b5c9, 0x2a076f2e, 0x55393055, 0x6513a085,
+        0x547ac78f, 0x4281c5b8, 0x3e7a3f6c, 0x34562c19, 0x2c04e679, 0x0ed78234, 0x5f7a1aa9,
+        0x0177640e, 0x0ea4f8d1, 0x15be7692,
+    ],
+    [
+        0x6eafdd62, 0x71a572c6, 0x72416f0a, 0x31ce1ad3, 0x2136a0cf, 0x1507c0eb, 0x1eb6e07a,
+        0x3a0ccf7b, 0x38e4bf31, 0x44128286, 0x6b05e976, 0x244a9b92, 0x6e4b32a8, 0x78ee2496,
+        0x4761115b, 0x3d3a7077, 0x75d3c670, 0x396a2475, 0x26dd00b4, 0x7df50f59, 0x0cb922df,
+        0x0568b190, 0x5bd3fcd6, 0x1351f58e,
+    ],
+    [
+        0x52191b5f, 0x119171b8, 0x1e8bb727, 0x27d21f26, 0x36146613, 0x1ee817a2, 0x71abe84e,
+        0x44b88070, 0x5dc04410, 0x2aeaa2f6, 0x2b7bb311, 0x6906884d, 0x0522e053, 0x0c45a214,
+        0x1b016998, 0x479b1052, 0x3acc89be, 0x0776021a, 0x7a34a1f5, 0x70f87911, 0x2caf9d9e,
+        0x026aff1b, 0x2c42468e, 0x67726b45,
+    ],
+    [
+        0x09b6f53c, 0x73d76589, 0x5793eeb0, 0x29e720f3, 0x75fc8bdf, 0x4c2fae0e, 0x20b41db3,
+        0x7e491510, 0x2cadef18, 0x57fc24d6, 0x4d1ade4a, 0x36bf8e3c, 0x3511b63c, 0x64d8476f,
+        0x732ba706, 0x46634978, 0x0521c17c, 0x5ee69212, 0x3559cba9, 0x2b33df89, 0x653538d6,
+        0x5fde8344, 0x4091605d, 0x2933bdde,
+    ],
+]);
+
+/// The round constants of the internal rounds of the default parameters for
+/// [`Poseidon2KoalaBear<24>`].
+pub const KOALABEAR_RC24_INTERNAL: [KoalaBear; 23] = KoalaBear::new_array([
+    0x1395d4ca, 0x5dbac049, 0x51fc2727, 0x13407399, 0x39ac6953, 0x45e8726c, 0x75a7311c, 0x599f82c9,
+    0x702cf13b, 0x026b8955, 0x44e09bbc, 0x2211207f, 0x5128b4e3, 0x591c41af, 0x674f5c68, 0x3981d0d3,
+    0x2d82f898, 0x707cd267, 0x3b4cca45, 0x2ad0dc3c, 0x0cb79b37, 0x23f2f4e8, 0x3de4e739,
+]);
+
+/// The round constants of the terminal external rounds of the default parameters for
+/// [`Poseidon2KoalaBear<24>`].
+pub const KOALABEAR_RC24_EXTERNAL_TERMINAL: [[KoalaBear; 24]; 4] = KoalaBear::new_2d_array([
+    [
+        0x7d232359, 0x389d82f9, 0x259b2e6c, 0x45a94def, 0x0d497380, 0x5b049135, 0x3c268399,
+        0x78feb2f9, 0x300a3eec, 0x505165bb, 0x20300973, 0x2327c081, 0x1a45a2f4, 0x5b32ea2e,
+        0x2d5d1a70, 0x053e613e, 0x5433e39f, 0x495529f0, 0x1eaa1aa9, 0x578f572a, 0x698ede71,
+        0x5a0f9dba, 0x398a2e96, 0x0c7b2925,
+    ],
+    [
+        0x2e6b9564, 0x026b00de, 0x7644c1e9, 0x5c23d0bd, 0x3470b5ef, 0x6013cf3a, 0x48747288,
+        0x13b7a543, 0x3eaebd44, 0x0004e60c, 0x1e8363a2, 0x2343259a, 0x69da0c2a, 0x06e3e4c4,
+        0x1095018e, 0x0deea348, 0x1f4c5513, 0x4f9a3a98, 0x3179112b, 0x524abb1f, 0x21615ba2,
+        0x23ab4065, 0x1202a1d1, 0x21d25b83,
+    ],
+    [
+        0x6ed17c2f, 0x391e6b09, 0x5e4ed894, 0x6a2f58f2, 0x5d980d70, 0x3fa48c5e, 0x1f6366f7,
+        0x63540f5f, 0x6a8235ed, 0x14c12a78, 0x6edde1c9, 0x58ce1c22, 0x718588bb, 0x334313ad,
+        0x7478dbc7, 0x647ad52f, 0x39e82049, 0x6fee146a, 0x082c2f24, 0x1f093015, 0x30173c18,
+        0x53f70c0d, 0x6028ab0c, 0x2f47a1ee,
+    ],
+    [
+        0x26a6780e, 0x3540bc83, 0x1812b49f, 0x5149c827, 0x631dd925, 0x001f2dea, 0x7dc05194,
+        0x3789672e, 0x7cabf72e, 0x242dbe2f, 0x0b07a51d, 0x38653650, 0x50785c4e, 0x60e8a7e0,
+        0x07464338, 0x3482d6e1, 0x08a69f1e, 0x3f2aff24, 0x5814c30d, 0x13fecab2, 0x61cb291a,
+        0x68c8226f, 0x5c757eea, 0x289b4e1e,
+    ],
+]);
+
+impl Poseidon2DefaultParams<KoalaBear, 24> for KoalaBearInternalLayerParameters {
+    const NAME: &'static str = "poseidon2-koalabear-24-v1";
+    const EXTERNAL_INITIAL: &'static [[KoalaBear; 24]] = &KOALABEAR_RC24_EXTERNAL_INITIAL;
+    const INTERNAL: &'static [KoalaBear] = &KOALABEAR_RC24_INTERNAL;
+    const EXTERNAL_TERMINAL: &'static [[KoalaBear; 24]] = &KOALABEAR_RC24_EXTERNAL_TERMINAL;
+}
+
 #[cfg(test)]
 mod tests {
-    use p3_field::FieldAlgebra;
-    use p3_symmetric::Permutation;
+    use alloc::vec::Vec;
+    use core::array;
+
+    use p3_field::{FieldAlgebra, PackedValue};
+    use p3_poseidon2::{
+        ExternalLayer, ExternalLayerConstructor, InternalLayer, InternalLayerConstructor,
+    };
+    use p3_symmetric::{Permutation, PseudoCompressionFunction, TruncatedPermutation};
+    use rand::distributions::{Distribution, Standard};
     use rand::{Rng, SeedableRng};
     use rand_xoshiro::Xoroshiro128Plus;
 
     use super::*;
 
     type F = KoalaBear;
+    type P = <F as Field>::Packing;
 
     // We need to make some round constants. We use Xoroshiro128Plus for this as we can easily match this PRNG in sage.
     // See: https://github.com/0xPolygonZero/hash-constants for the sage code used to create all these tests.
@@ -365,4 +513,141 @@ mod tests {
 
         assert_eq!(input1, input2);
     }
+
+    /// Test the scalar multiplications by powers of two from `packed_ops` against field
+    /// arithmetic, including on the MONTY forms 0, 1 and P - 1.
+    #[test]
+    fn test_packed_ops_scalar() {
+        use core::mem::transmute;
+
+        use p3_monty_31::packed_ops::{halve, mul_2exp_neg_n, mul_neg_2exp_neg_n};
+
+        let mut rng = rand::thread_rng();
+        let boundary = [0, 1, F::ORDER_U32 - 1].map(|x| unsafe { transmute::<u32, F>(x) });
+        let random: [F; 100] = rng.gen();
+
+        for x in boundary.into_iter().chain(random) {
+            assert_eq!(halve(x), x.halve());
+            for n in 0..=24 {
+                let expected = x.div_2exp_u64(n as u64);
+                assert_eq!(mul_2exp_neg_n(x, n), expected);
+                assert_eq!(mul_neg_2exp_neg_n(x, n), -expected);
+            }
+        }
+    }
+
+    /// The default parameters must never change, so pin them with a known answer.
+    #[test]
+    fn test_default_params_width_16() {
+        let mut input: [F; 16] = array::from_fn(F::from_canonical_usize);
+
+        let expected: [F; 16] = [
+            1259554834, 663463928, 1989430097, 476523442, 836740795, 1803459961, 1229318262,
+            2023956904, 2054405130, 1556655036, 1455339712, 1471465890, 423337459, 353979748,
+            1203410294, 1592576868,
+        ]
+        .map(F::from_canonical_u32);
+
+        let perm = Poseidon2KoalaBear::<16>::default_params();
+        perm.permute_mut(&mut input);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn test_default_params_width_24() {
+        let mut input: [F; 24] = array::from_fn(F::from_canonical_usize);
+
+        let expected: [F; 24] = [
+            723511737, 87131171, 587052829, 1323145575, 949917837, 2060493993, 234724110,
+            834906887, 306751607, 1771020267, 329216878, 823818173, 765507096, 1447982946,
+            605505945, 247386051, 1223069940, 354661286, 233493652, 2075130821, 1961191294,
+            313483662, 1701936810, 1815724394,
+        ]
+        .map(F::from_canonical_u32);
+
+        let perm = Poseidon2KoalaBear::<24>::default_params();
+        perm.permute_mut(&mut input);
+        assert_eq!(input, expected);
+    }
+
+    /// A 2-to-1 compression of 8-element digests leaves 8 capacity lanes of the width-24
+    /// permutation, which must be the trailing ones, zeroed.
+    #[test]
+    fn test_truncated_permutation_width_24_chunk_8() {
+        let left: [F; 8] = array::from_fn(|i| F::from_canonical_usize(i + 1));
+        let right: [F; 8] = array::from_fn(|i| F::from_canonical_usize(i + 9));
+
+        let expected: [F; 8] = [
+            58200502, 1340182327, 424775238, 671454321, 520702422, 1038783073, 417062313, 180585380,
+        ]
+        .map(F::from_canonical_u32);
+
+        let perm = Poseidon2KoalaBear::<24>::default_params();
+        let compress = TruncatedPermutation::<_, 2, 8, 24>::new(perm.clone());
+        let output = compress.compress([left, right]);
+        assert_eq!(output, expected);
+
+        let mut padded = [F::ZERO; 24];
+        padded[..8].copy_from_slice(&left);
+        padded[8..16].copy_from_slice(&right);
+        assert_eq!(output, perm.permute(padded)[..8]);
+    }
+
+    /// Check that Poseidon2 with an S-box of degree `D`, rather than KoalaBear's usual 3, permutes
+    /// each lane of a packed state as it permutes the lane on its own.
+    fn check_packed_matches_scalar<const WIDTH: usize, const D: u64>()
+    where
+        Poseidon2ExternalLayerKoalaBear<WIDTH>: ExternalLayerConstructor<P, WIDTH>
+            + ExternalLayer<F, WIDTH, D>
+            + ExternalLayer<P, WIDTH, D>,
+        Poseidon2InternalLayerKoalaBear<WIDTH>:
+            InternalLayerConstructor<P> + InternalLayer<F, WIDTH, D> + InternalLayer<P, WIDTH, D>,
+        Standard: Distribution<[F; WIDTH]>,
+    {
+        let mut rng = Xoroshiro128Plus::seed_from_u64(D);
+        let perm = Poseidon2::<
+            P,
+            Poseidon2ExternalLayerKoalaBear<WIDTH>,
+            Poseidon2InternalLayerKoalaBear<WIDTH>,
+            WIDTH,
+            D,
+        >::new_from_rng_128(&mut rng);
+
+        let inputs: Vec<[F; WIDTH]> = (0..P::WIDTH).map(|_| rng.gen()).collect();
+        let mut packed: [P; WIDTH] = array::from_fn(|i| P::from_fn(|lane| inputs[lane][i]));
+        perm.permute_mut(&mut packed);
+
+        for (lane, input) in inputs.into_iter().enumerate() {
+            let packed_lane: [F; WIDTH] = array::from_fn(|i| packed[i].as_slice()[lane]);
+            assert_eq!(packed_lane, perm.permute(input), "lane {lane}, D = {D}");
+        }
+    }
+
+    #[test]
+    fn test_sbox_degree_3_packed_matches_scalar() {
+        check_packed_matches_scalar::<16, 3>();
+        check_packed_matches_scalar::<24, 3>();
+    }
+
+    #[test]
+    fn test_sbox_degree_7_packed_matches_scalar() {
+        check_packed_matches_scalar::<16, 7>();
+        check_packed_matches_scalar::<24, 7>();
+    }
+
+    /// A degree 7 S-box needs fewer partial rounds than degree 3, so round numbers chosen for 7 are
+    /// rejected for 3.
+    #[test]
+    fn test_sbox_degree_round_numbers() {
+        let mut rng = Xoroshiro128Plus::seed_from_u64(1);
+        assert!(Poseidon2::<
+            P,
+            Poseidon2ExternalLayerKoalaBear<16>,
+            Poseidon2InternalLayerKoalaBear<16>,
+            16,
+            7,
+        >::try_new_from_rng(8, 13, &mut rng)
+        .is_ok());
+        assert!(Poseidon2KoalaBear::<16>::try_new_from_rng(8, 13, &mut rng).is_err());
+    }
 }
@@ -193,11 +193,19 @@ impl InternalLayerParametersAVX2<KoalaBearParameters, 24> for KoalaBearInternalL
 
 #[cfg(test)]
 mod tests {
+    use core::arch::x86_64::__m256i;
+
     use p3_field::FieldAlgebra;
+    use p3_field_testing::check_packed_agreement;
+    use p3_monty_31::packed_ops::{
+        halve, halve_avx2, mul_2exp_neg_8_avx2, mul_2exp_neg_n, mul_2exp_neg_n_avx2,
+        mul_2exp_neg_two_adicity_avx2, mul_neg_2exp_neg_8_avx2, mul_neg_2exp_neg_n,
+        mul_neg_2exp_neg_n_avx2, mul_neg_2exp_neg_two_adicity_avx2,
+    };
     use p3_symmetric::Permutation;
     use rand::Rng;
 
-    use crate::{KoalaBear, PackedKoalaBearAVX2, Poseidon2KoalaBear};
+    use crate::{KoalaBear, KoalaBearParameters, PackedKoalaBearAVX2, Poseidon2KoalaBear};
 
     type F = KoalaBear;
     type Perm16 = Poseidon2KoalaBear<16>;
@@ -244,4 +252,82 @@ mod tests {
 
         assert_eq!(avx2_output, expected);
     }
+
+    /// Check that `packed` agrees with `scalar` in every lane.
+    fn check_agreement(packed: impl Fn(__m256i) -> __m256i, scalar: impl Fn(F) -> F) {
+        // SAFETY: `F` is its MONTY form and `__m256i` has the layout of `[u32; 8]`.
+        unsafe { check_packed_agreement::<F, _, 8>(packed, scalar) }
+    }
+
+    /// Test the packed multiplications by powers of two against the scalar versions.
+    #[test]
+    fn test_avx2_packed_ops_match_scalar() {
+        type P = KoalaBearParameters;
+
+        check_agreement(halve_avx2::<P>, halve);
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 1, 23>(x) },
+            |x| mul_2exp_neg_n(x, 1),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 2, 22>(x) },
+            |x| mul_2exp_neg_n(x, 2),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 3, 21>(x) },
+            |x| mul_2exp_neg_n(x, 3),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 7, 17>(x) },
+            |x| mul_2exp_neg_n(x, 7),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 9, 15>(x) },
+            |x| mul_2exp_neg_n(x, 9),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_n_avx2::<P, 14, 10>(x) },
+            |x| mul_2exp_neg_n(x, 14),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 1, 23>(x) },
+            |x| mul_neg_2exp_neg_n(x, 1),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 2, 22>(x) },
+            |x| mul_neg_2exp_neg_n(x, 2),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 3, 21>(x) },
+            |x| mul_neg_2exp_neg_n(x, 3),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 7, 17>(x) },
+            |x| mul_neg_2exp_neg_n(x, 7),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 9, 15>(x) },
+            |x| mul_neg_2exp_neg_n(x, 9),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_n_avx2::<P, 14, 10>(x) },
+            |x| mul_neg_2exp_neg_n(x, 14),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_8_avx2::<P, 16>(x) },
+            |x| mul_2exp_neg_n(x, 8),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_8_avx2::<P, 16>(x) },
+            |x| mul_neg_2exp_neg_n(x, 8),
+        );
+        check_agreement(
+            |x| unsafe { mul_2exp_neg_two_adicity_avx2::<P, 24, 7>(x) },
+            |x| mul_2exp_neg_n(x, 24),
+        );
+        check_agreement(
+            |x| unsafe { mul_neg_2exp_neg_two_adicity_avx2::<P, 24, 7>(x) },
+            |x| mul_neg_2exp_neg_n(x, 24),
+        );
+    }
 }
@@ -0,0 +1,49 @@
+use core::mem::size_of;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use p3_baby_bear::BabyBear;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::util::reverse_matrix_index_bits;
+use p3_matrix::Matrix;
+use p3_util::{log2_strict_usize, reverse_bits_len};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+fn bench_reverse_matrix_index_bits(c: &mut Criterion) {
+    let mut rng = ChaChaRng::seed_from_u64(0);
+
+    type F = BabyBear;
+    let log_rows = 22;
+    let width = 8;
+    let m = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_rows, width);
+
+    let mut group = c.benchmark_group("reverse_matrix_index_bits");
+    group
+        .sample_size(10)
+        .throughput(Throughput::Bytes((m.values.len() * size_of::<F>()) as u64));
+
+    group.bench_function("in_place", |b| {
+        b.iter_batched(
+            || m.clone(),
+            |mut m| {
+                reverse_matrix_index_bits(&mut m);
+                m
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    // Copying each row to its bit-reversed position in a new buffer, for comparison.
+    group.bench_function("copy", |b| {
+        b.iter(|| {
+            let log_h = log2_strict_usize(m.height());
+            let values = (0..m.height())
+                .flat_map(|i| m.row_slice(reverse_bits_len(i, log_h)).to_vec())
+                .collect::<Vec<_>>();
+            RowMajorMatrix::new(values, width)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_reverse_matrix_index_bits);
+criterion_main!(benches);
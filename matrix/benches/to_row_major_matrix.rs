@@ -0,0 +1,55 @@
+use core::mem::size_of;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use p3_baby_bear::BabyBear;
+use p3_matrix::bitrev::BitReversableMatrix;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::stack::VerticalPair;
+use p3_matrix::strided::VerticallyStridedRowIndexMap;
+use p3_matrix::Matrix;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+fn to_row_major_matrix(c: &mut Criterion) {
+    let mut rng = ChaChaRng::seed_from_u64(0);
+
+    type F = BabyBear;
+    let log_rows = 16;
+    let width = 1 << 8;
+    let m = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_rows, width);
+
+    let mut group = c.benchmark_group("to_row_major_matrix");
+    group
+        .sample_size(10)
+        .throughput(Throughput::Bytes((m.values.len() * size_of::<F>()) as u64));
+
+    // A plain copy of the buffer, for comparison.
+    group.bench_function("memcpy", |b| b.iter(|| m.values.clone()));
+    group.bench_function("dense_view", |b| {
+        b.iter(|| m.as_view().to_row_major_matrix())
+    });
+    group.bench_function("bit_reversed", |b| {
+        b.iter_batched(
+            || m.clone().bit_reverse_rows(),
+            |view| view.to_row_major_matrix(),
+            BatchSize::PerIteration,
+        )
+    });
+    group.bench_function("strided", |b| {
+        b.iter(|| VerticallyStridedRowIndexMap::new_view(m.as_view(), 1, 0).to_row_major_matrix())
+    });
+    group.bench_function("vertical_pair", |b| {
+        b.iter(|| {
+            let (top, bottom) = m.values.split_at(m.values.len() / 2);
+            VerticalPair::new(
+                RowMajorMatrixView::new(top, width),
+                RowMajorMatrixView::new(bottom, width),
+            )
+            .to_row_major_matrix()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, to_row_major_matrix);
+criterion_main!(benches);
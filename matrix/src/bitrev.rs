@@ -13,12 +13,16 @@ pub trait BitReversableMatrix<T: Send + Sync>: Matrix<T> {
     fn bit_reverse_rows(self) -> Self::BitRev;
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug)]
 pub struct BitReversalPerm {
     log_height: usize,
 }
 
 impl BitReversalPerm {
+    pub const fn new(log_height: usize) -> Self {
+        Self { log_height }
+    }
+
     /// Assumes the inner matrix height is a power of two; panics otherwise.
     pub fn new_view<T: Send + Sync, Inner: Matrix<T>>(
         inner: Inner,
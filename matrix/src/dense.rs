@@ -53,6 +53,36 @@ impl<T: Clone + Send + Sync> DenseStorage<T> for Cow<'_, [T]> {
     }
 }
 
+impl<T: Clone + Send + Sync> RowMajorMatrix<T> {
+    /// A `height x width` matrix whose `r`-th row is a copy of the slice `row(r)`, with rows copied
+    /// in parallel. This is how views which can borrow each row as a slice become dense.
+    pub(crate) fn from_row_slices<R: Deref<Target = [T]>>(
+        height: usize,
+        width: usize,
+        row: impl Fn(usize) -> R + Sync,
+    ) -> Self {
+        if width == 0 {
+            return Self::new(Vec::new(), 0);
+        }
+        let len = height * width;
+        let mut values = Vec::with_capacity(len);
+        values.spare_capacity_mut()[..len]
+            .par_chunks_exact_mut(width)
+            .enumerate()
+            .for_each(|(r, out)| {
+                let row = row(r);
+                assert_eq!(row.len(), width);
+                for (out, value) in out.iter_mut().zip(row.iter()) {
+                    out.write(value.clone());
+                }
+            });
+        // SAFETY: each of the first `len` elements was written above, as the chunks cover them
+        // and each row has `width` elements.
+        unsafe { values.set_len(len) };
+        Self::new(values, width)
+    }
+}
+
 impl<T: Clone + Send + Sync + Default> DenseMatrix<T> {
     /// Create a new dense matrix of the given dimensions, backed by a `Vec`, and filled with
     /// default values.
@@ -182,6 +212,28 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
         scale_slice_in_place(scale, self.values.borrow_mut());
     }
 
+    /// The `r`th row together with the row after it, wrapping around so that the last row is
+    /// paired with the first. This is the `(local, next)` window of a transition constraint.
+    ///
+    /// A matrix of width 0 has no rows to wrap around, but any window of it is a pair of empty
+    /// rows, as for an AIR without preprocessed columns.
+    pub fn row_pair(&self, r: usize) -> (&[T], &[T]) {
+        if self.width == 0 {
+            return (&[], &[]);
+        }
+        let height = self.height();
+        debug_assert!(r < height);
+        let values = self.values.borrow();
+        let row = |i: usize| &values[i * self.width..(i + 1) * self.width];
+        (row(r), row((r + 1) % height))
+    }
+
+    /// Every window given by [`row_pair`](Self::row_pair), in row order, so the last one pairs
+    /// the last row with the first.
+    pub fn row_pairs(&self) -> impl Iterator<Item = (&[T], &[T])> {
+        (0..self.height()).map(|r| self.row_pair(r))
+    }
+
     pub fn split_rows(&self, r: usize) -> (RowMajorMatrixView<T>, RowMajorMatrixView<T>) {
         let (lo, hi) = self.values.borrow().split_at(r * self.width);
         (
@@ -562,4 +614,32 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_row_pair_wraps_around() {
+        let matrix = RowMajorMatrix::new((0..12).collect::<Vec<_>>(), 3);
+        assert_eq!(matrix.row_pair(0), (&[0, 1, 2][..], &[3, 4, 5][..]));
+        assert_eq!(matrix.row_pair(2), (&[6, 7, 8][..], &[9, 10, 11][..]));
+        assert_eq!(matrix.row_pair(3), (&[9, 10, 11][..], &[0, 1, 2][..]));
+
+        // A single row is its own successor.
+        let row = RowMajorMatrix::new_row(vec![1, 2]);
+        assert_eq!(row.row_pair(0), (&[1, 2][..], &[1, 2][..]));
+
+        // Without columns, every window is empty.
+        let empty = RowMajorMatrix::<u32>::new(vec![], 0);
+        assert_eq!(empty.row_pair(3), (&[][..], &[][..]));
+    }
+
+    #[test]
+    fn test_row_pairs() {
+        let matrix = RowMajorMatrix::new((0..8).collect::<Vec<_>>(), 2);
+        let pairs = matrix
+            .row_pairs()
+            .map(|(local, next)| (local[0], next[0]))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, vec![(0, 2), (2, 4), (4, 6), (6, 0)]);
+
+        assert_eq!(RowMajorMatrix::<u32>::new(vec![], 2).row_pairs().count(), 0);
+    }
 }
@@ -17,6 +17,7 @@ use strided::{VerticallyStridedMatrixView, VerticallyStridedRowIndexMap};
 use tracing::instrument;
 
 use crate::dense::RowMajorMatrix;
+use crate::row_index_mapped::RowIndexMap;
 
 pub mod bitrev;
 pub mod dense;
@@ -76,6 +77,17 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
         (0..self.height()).into_par_iter().map(move |r| self.row(r))
     }
 
+    /// Iterate over `(physical_idx, logical_idx, row)`, where `logical_idx` is the index `perm`
+    /// maps row `physical_idx` of this matrix to, e.g. its natural order index if this matrix is
+    /// stored in bit-reversed order. Useful for debugging ordering mismatches.
+    fn enumerate_with_permutation<P: RowIndexMap>(
+        &self,
+        perm: P,
+    ) -> impl Iterator<Item = (usize, usize, Self::Row<'_>)> {
+        assert_eq!(perm.height(), self.height());
+        (0..self.height()).map(move |r| (r, perm.map_row_index(r), self.row(r)))
+    }
+
     // Opaque return type implicitly captures &'_ self
     fn row_slice(&self, r: usize) -> impl Deref<Target = [T]> {
         self.row(r).collect_vec()
@@ -184,13 +196,18 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
     /// row contains the packing of the i'th element of the rows r through r + P::WIDTH - 1.
     /// The i'th element of the second row contains the packing of the i'th element of the
     /// rows r + step through r + step + P::WIDTH - 1. If at some point we exceed the
-    /// height of the matrix, wrap around and include initial rows.
+    /// height of the matrix, wrap around and include initial rows. A matrix of width 0 gives an
+    /// empty vector for any `r`.
     #[inline]
     fn vertically_packed_row_pair<P>(&self, r: usize, step: usize) -> Vec<P>
     where
         T: Copy,
         P: PackedValue<Value = T>,
     {
+        if self.width() == 0 {
+            return Vec::new();
+        }
+
         // Whilst it would appear that this can be replaced by two calls to vertically_packed_row
         // tests seem to indicate that combining them in the same function is slightly faster.
         // It's probably allowing the compiler to make some optimizations on the fly.
@@ -312,6 +329,23 @@ mod tests {
         assert_eq!(m.columnwise_dot_product(&v), expected);
     }
 
+    #[test]
+    fn test_enumerate_with_permutation() {
+        let m = RowMajorMatrix::new((0..8).collect_vec(), 1);
+        let mut bitrev = m.clone();
+        util::reverse_matrix_index_bits(&mut bitrev);
+
+        let entries = bitrev
+            .enumerate_with_permutation(bitrev::BitReversalPerm::new(3))
+            .map(|(physical, logical, row)| (physical, logical, row.collect_vec()))
+            .collect_vec();
+        assert_eq!(entries.len(), 8);
+        for (physical, logical, row) in entries {
+            assert_eq!(row, m.row_slice(logical).to_vec());
+            assert_eq!(bitrev.row_slice(physical).to_vec(), row);
+        }
+    }
+
     // Mock implementation for testing purposes
     struct MockMatrix {
         data: Vec<Vec<u32>>,
@@ -452,4 +486,71 @@ mod tests {
         let all_rows: Vec<Vec<u32>> = matrix.rows().map(|row| row.collect()).collect();
         assert_eq!(all_rows, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
     }
+
+    /// The generic conversion, which every specialized one should agree with.
+    fn generic_row_major<T: Clone + Send + Sync>(m: &impl Matrix<T>) -> RowMajorMatrix<T> {
+        RowMajorMatrix::new((0..m.height()).flat_map(|r| m.row(r)).collect(), m.width())
+    }
+
+    fn check_to_row_major_matrix<M: Matrix<u32>>(make: impl Fn() -> M) {
+        assert_eq!(make().to_row_major_matrix(), generic_row_major(&make()));
+    }
+
+    #[test]
+    fn test_to_row_major_matrix_views() {
+        use crate::bitrev::BitReversableMatrix;
+        use crate::stack::VerticalPair;
+
+        let m = RowMajorMatrix::new((0..8 * 3).collect_vec(), 3);
+        let other = RowMajorMatrix::new((100..100 + 4 * 3).collect_vec(), 3);
+
+        check_to_row_major_matrix(|| m.clone());
+        check_to_row_major_matrix(|| m.as_view());
+        check_to_row_major_matrix(|| m.clone().bit_reverse_rows());
+        check_to_row_major_matrix(|| VerticallyStridedRowIndexMap::new_view(m.as_view(), 3, 1));
+        check_to_row_major_matrix(|| VerticalPair::new(m.clone(), other.as_view()));
+        check_to_row_major_matrix(|| {
+            VerticalPair::new(
+                m.clone().bit_reverse_rows(),
+                VerticallyStridedRowIndexMap::new_view(other.as_view(), 2, 0),
+            )
+        });
+        check_to_row_major_matrix(|| {
+            VerticallyStridedRowIndexMap::new_view(RowMajorMatrix::<u32>::new(vec![], 0), 2, 0)
+        });
+    }
+
+    #[test]
+    fn test_vertically_packed_row_pair_wraps_around() {
+        type P = [u32; 4];
+        let height: u32 = 6;
+        let m = RowMajorMatrix::new((0..height * 2).collect_vec(), 2);
+        let column = |packed: &P| packed.map(|x| x / 2);
+
+        // Rows 4, 5, 0, 1 and, a step of 1 later, rows 5, 0, 1, 2.
+        let pair = m.vertically_packed_row_pair::<P>(4, 1);
+        assert_eq!(pair.len(), 4);
+        assert_eq!(column(&pair[0]), [4, 5, 0, 1]);
+        assert_eq!(column(&pair[2]), [5, 0, 1, 2]);
+        assert_eq!(pair[1], [9, 11, 1, 3]);
+        assert_eq!(pair[3], [11, 1, 3, 5]);
+
+        // Larger steps, as on a quotient domain, wrap the same way.
+        let pair = m.vertically_packed_row_pair::<P>(3, 2);
+        assert_eq!(column(&pair[0]), [3, 4, 5, 0]);
+        assert_eq!(column(&pair[2]), [5, 0, 1, 2]);
+
+        // Each lane agrees with the scalar window on the same row.
+        for r in 0..height as usize {
+            let pair = m.vertically_packed_row_pair::<P>(r, 1);
+            for lane in 0..4 {
+                let (local, next) = m.row_pair((r + lane) % height as usize);
+                assert_eq!([pair[0][lane], pair[1][lane]], local);
+                assert_eq!([pair[2][lane], pair[3][lane]], next);
+            }
+        }
+
+        let empty = RowMajorMatrix::<u32>::new(vec![], 0);
+        assert!(empty.vertically_packed_row_pair::<P>(5, 2).is_empty());
+    }
 }
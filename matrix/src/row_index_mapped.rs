@@ -11,17 +11,14 @@ pub trait RowIndexMap: Send + Sync {
     fn map_row_index(&self, r: usize) -> usize;
 
     /// Permutations can optionally provide an optimized method to
-    /// convert to dense form.
+    /// convert to dense form. By default, the inner matrix's rows are copied as slices.
     fn to_row_major_matrix<T: Clone + Send + Sync, Inner: Matrix<T>>(
         &self,
         inner: Inner,
     ) -> RowMajorMatrix<T> {
-        RowMajorMatrix::new(
-            (0..self.height())
-                .flat_map(|r| inner.row(self.map_row_index(r)))
-                .collect(),
-            inner.width(),
-        )
+        RowMajorMatrix::from_row_slices(self.height(), inner.width(), |r| {
+            inner.row_slice(self.map_row_index(r))
+        })
     }
 }
 
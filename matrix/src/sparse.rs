@@ -6,6 +6,7 @@ use core::ops::Range;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 
+use crate::dense::RowMajorMatrix;
 use crate::Matrix;
 
 /// A sparse matrix stored in the compressed sparse row format.
@@ -89,3 +90,144 @@ impl<T: Clone + Default + Send + Sync> Matrix<T> for CsrMatrix<T> {
         row.into_iter()
     }
 }
+
+/// A matrix whose columns are almost all zero, such as the preprocessed selector columns of an AIR,
+/// stored as the `(row, value)` pairs of each column's nonzero entries, sorted by row.
+///
+/// Zero is `T::default()`. Reading it as a [`Matrix`] looks up every column of a row, so code
+/// which only needs the nonzero entries, like a low-degree extension from them, should use
+/// [`Self::column_nonzeros`] instead, and [`Self::to_dense`] where it needs every value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseSelectorMatrix<T> {
+    height: usize,
+    columns: Vec<Vec<(usize, T)>>,
+}
+
+impl<T: Clone + Default + Send + Sync> SparseSelectorMatrix<T> {
+    /// A matrix of the given height with the nonzero entries of each column in `columns`.
+    ///
+    /// # Panics
+    /// If the rows of a column aren't strictly increasing, or aren't below `height`.
+    pub fn new(height: usize, columns: Vec<Vec<(usize, T)>>) -> Self {
+        for column in &columns {
+            assert!(
+                column.windows(2).all(|w| w[0].0 < w[1].0),
+                "Rows must be strictly increasing"
+            );
+            assert!(
+                column.iter().all(|&(row, _)| row < height),
+                "Rows must be below the height"
+            );
+        }
+        Self { height, columns }
+    }
+
+    /// The entries of `mat` which aren't zero.
+    pub fn from_dense<M: Matrix<T>>(mat: &M) -> Self
+    where
+        T: PartialEq,
+    {
+        let mut columns = vec![vec![]; mat.width()];
+        for (r, row) in mat.rows().enumerate() {
+            for (column, value) in columns.iter_mut().zip(row) {
+                if value != T::default() {
+                    column.push((r, value));
+                }
+            }
+        }
+        Self {
+            height: mat.height(),
+            columns,
+        }
+    }
+
+    /// The `(row, value)` pairs of the nonzero entries of column `c`, sorted by row.
+    #[must_use]
+    pub fn column_nonzeros(&self, c: usize) -> &[(usize, T)] {
+        &self.columns[c]
+    }
+
+    pub fn num_nonzeros(&self) -> usize {
+        self.columns.iter().map(Vec::len).sum()
+    }
+
+    /// The same matrix with every entry stored.
+    pub fn to_dense(&self) -> RowMajorMatrix<T> {
+        let width = self.width();
+        let mut values = vec![T::default(); self.height * width];
+        for (c, column) in self.columns.iter().enumerate() {
+            for &(r, ref value) in column {
+                values[r * width + c] = value.clone();
+            }
+        }
+        RowMajorMatrix::new(values, width)
+    }
+}
+
+impl<T: Clone + Default + Send + Sync> Matrix<T> for SparseSelectorMatrix<T> {
+    fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, r: usize, c: usize) -> T {
+        let column = &self.columns[c];
+        column
+            .binary_search_by_key(&r, |&(row, _)| row)
+            .map_or_else(|_| T::default(), |i| column[i].1.clone())
+    }
+
+    type Row<'a>
+        = <Vec<T> as IntoIterator>::IntoIter
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        (0..self.width())
+            .map(|c| self.get(r, c))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::FieldAlgebra;
+
+    use super::*;
+
+    type F = BabyBear;
+
+    #[test]
+    fn test_sparse_selectors_match_dense() {
+        let columns = vec![
+            vec![(0, F::ONE)],
+            vec![],
+            vec![(2, F::TWO), (5, F::NEG_ONE), (7, F::ONE)],
+        ];
+        let sparse = SparseSelectorMatrix::new(8, columns);
+        assert_eq!(sparse.num_nonzeros(), 4);
+
+        let dense = sparse.to_dense();
+        assert_eq!(dense.dimensions(), sparse.dimensions());
+        assert_eq!(dense.get(5, 2), F::NEG_ONE);
+        assert_eq!(dense.get(5, 0), F::ZERO);
+        for r in 0..8 {
+            assert_eq!(
+                sparse.row(r).collect::<Vec<_>>(),
+                dense.row_slice(r).to_vec()
+            );
+        }
+        assert_eq!(SparseSelectorMatrix::from_dense(&dense), sparse);
+    }
+
+    #[test]
+    #[should_panic = "Rows must be strictly increasing"]
+    fn test_unsorted_rows_are_rejected() {
+        SparseSelectorMatrix::new(8, vec![vec![(3, F::ONE), (1, F::ONE)]]);
+    }
+}
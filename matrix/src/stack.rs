@@ -1,6 +1,7 @@
 use core::iter::Chain;
 use core::ops::Deref;
 
+use crate::dense::RowMajorMatrix;
 use crate::Matrix;
 
 /// A combination of two matrices, stacked together vertically.
@@ -10,7 +11,12 @@ pub struct VerticalPair<First, Second> {
     pub second: Second,
 }
 
-/// A combination of two matrices, stacked together horizontally.
+/// A combination of two matrices, stacked together horizontally, e.g. to see a trace's columns next
+/// to others committed separately without copying either into a wider matrix.
+///
+/// The columns of `first` come before those of `second`, which must have the same height. A row
+/// spans both matrices, so `row_slice` has no contiguous slice to borrow and collects the row into
+/// a new vector; `row` and `get` don't allocate.
 #[derive(Copy, Clone, Debug)]
 pub struct HorizontalPair<First, Second> {
     pub first: First,
@@ -36,7 +42,11 @@ impl<First, Second> HorizontalPair<First, Second> {
         First: Matrix<T>,
         Second: Matrix<T>,
     {
-        assert_eq!(first.height(), second.height());
+        assert_eq!(
+            first.height(),
+            second.height(),
+            "The matrices of a horizontal pair must have the same height"
+        );
         Self { first, second }
     }
 }
@@ -80,6 +90,18 @@ impl<T: Send + Sync, First: Matrix<T>, Second: Matrix<T>> Matrix<T>
             EitherRow::Right(self.second.row_slice(r - self.first.height()))
         }
     }
+
+    fn to_row_major_matrix(self) -> RowMajorMatrix<T>
+    where
+        Self: Sized,
+        T: Clone,
+    {
+        // Each half converts with its own fast path, and the results are concatenated.
+        let width = self.width();
+        let mut values = self.first.to_row_major_matrix().values;
+        values.append(&mut self.second.to_row_major_matrix().values);
+        RowMajorMatrix::new(values, width)
+    }
 }
 
 impl<T: Send + Sync, First: Matrix<T>, Second: Matrix<T>> Matrix<T>
@@ -146,3 +168,39 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn test_horizontal_pair_columns() {
+        // A 3x2 matrix next to a 3x1 one.
+        let left = RowMajorMatrix::new(vec![1, 2, 3, 4, 5, 6], 2);
+        let right = RowMajorMatrix::new(vec![7, 8, 9], 1);
+        let pair = HorizontalPair::new(left.as_view(), right.as_view());
+
+        assert_eq!(pair.width(), 3);
+        assert_eq!(pair.height(), 3);
+        assert_eq!(pair.get(1, 0), 3);
+        assert_eq!(pair.get(1, 1), 4);
+        assert_eq!(pair.get(1, 2), 8);
+        assert_eq!(pair.row(2).collect::<Vec<_>>(), vec![5, 6, 9]);
+        assert_eq!(&*pair.row_slice(0), &[1, 2, 7]);
+        assert_eq!(
+            pair.to_row_major_matrix(),
+            RowMajorMatrix::new(vec![1, 2, 7, 3, 4, 8, 5, 6, 9], 3)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The matrices of a horizontal pair must have the same height")]
+    fn test_horizontal_pair_heights_must_match() {
+        let left = RowMajorMatrix::new(vec![1, 2, 3, 4], 2);
+        let right = RowMajorMatrix::new(vec![5, 6, 7], 1);
+        HorizontalPair::new(left, right);
+    }
+}
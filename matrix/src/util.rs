@@ -7,6 +7,10 @@ use tracing::instrument;
 use crate::dense::{DenseMatrix, DenseStorage, RowMajorMatrix};
 use crate::Matrix;
 
+/// Permute the rows of `mat` into bit-reversed order, in place.
+///
+/// Each pair of rows `i < rev(i)` is swapped once, with the pairs handled in parallel. Panics if
+/// the height isn't a power of two.
 #[instrument(level = "debug", skip_all)]
 pub fn reverse_matrix_index_bits<'a, F, S>(mat: &mut DenseMatrix<F, S>)
 where
@@ -44,3 +48,55 @@ pub(crate) unsafe fn swap_rows_raw<F>(mat: *mut F, w: usize, i: usize, j: usize)
     let row_j = core::slice::from_raw_parts_mut(mat.add(j * w), w);
     row_i.swap_with_slice(row_j);
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use itertools::Itertools;
+
+    use super::*;
+
+    /// The rows of `mat` in bit-reversed order, copied into a new matrix.
+    fn reversed_copy(mat: &RowMajorMatrix<u32>) -> RowMajorMatrix<u32> {
+        let log_h = log2_strict_usize(mat.height());
+        let values = (0..mat.height())
+            .flat_map(|i| mat.row_slice(reverse_bits_len(i, log_h)).to_vec())
+            .collect_vec();
+        RowMajorMatrix::new(values, mat.width())
+    }
+
+    #[test]
+    fn test_reverse_matrix_index_bits() {
+        for log_h in 0..8 {
+            for w in [1, 3, 8] {
+                let mat = RowMajorMatrix::new((0..(w << log_h) as u32).collect_vec(), w);
+                let mut bitrev = mat.clone();
+                reverse_matrix_index_bits(&mut bitrev);
+                assert_eq!(bitrev, reversed_copy(&mat), "log_h = {log_h}, w = {w}");
+
+                reverse_matrix_index_bits(&mut bitrev);
+                assert_eq!(bitrev, mat);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reverse_matrix_index_bits_small() {
+        let mut mat = RowMajorMatrix::new(vec![1, 2], 2);
+        reverse_matrix_index_bits(&mut mat);
+        assert_eq!(mat.values, vec![1, 2]);
+
+        let mut mat = RowMajorMatrix::new(vec![1, 2, 3, 4], 2);
+        reverse_matrix_index_bits(&mut mat);
+        assert_eq!(mat.values, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not a power of two: 6")]
+    fn test_reverse_matrix_index_bits_non_power_of_two() {
+        let mut mat = RowMajorMatrix::new((0..12).collect::<Vec<u32>>(), 2);
+        reverse_matrix_index_bits(&mut mat);
+    }
+}
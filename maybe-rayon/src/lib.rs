@@ -1,3 +1,14 @@
+#![no_std]
+
+#[cfg(feature = "parallel")]
+extern crate std;
+
+mod parallelism;
+
+pub use parallelism::*;
+#[cfg(feature = "parallel")]
+pub use rayon::{ThreadPool, ThreadPoolBuilder};
+
 #[cfg(feature = "parallel")]
 pub mod prelude {
     pub use rayon::prelude::*;
@@ -0,0 +1,270 @@
+//! Per-call control over the thread pool and granularity of parallel loops.
+//!
+//! A `ParallelismConfig` is made current on a thread with [`ParallelismConfig::enter`]. Parallel
+//! phases (DFTs, Merkle tree construction, FRI folding, quotient evaluation, ...) then run through
+//! [`install`], which looks up the current configuration and
+//! - runs the phase inside the configured thread pool, if there is one, and
+//! - runs the phase without parallelism if its input is smaller than `min_parallel_len`.
+//!
+//! Without the `parallel` feature everything runs serially anyway, and these are no-ops.
+
+#[cfg(feature = "parallel")]
+use std::cell::RefCell;
+#[cfg(feature = "parallel")]
+use std::marker::PhantomData;
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+
+#[cfg(feature = "parallel")]
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+/// Configures how parallel phases are executed.
+///
+/// The default configuration runs every phase in the current (usually global) rayon pool, with
+/// no threshold, which is the behavior when no configuration is entered at all.
+#[derive(Clone, Debug, Default)]
+pub struct ParallelismConfig {
+    /// The pool parallel phases are installed in; if `None`, the current pool is used.
+    #[cfg(feature = "parallel")]
+    pool: Option<Arc<ThreadPool>>,
+
+    /// A single-threaded pool, in which phases below the threshold run. Parallel loops installed in
+    /// it run sequentially on its one thread, rather than being split up across the current pool.
+    #[cfg(feature = "parallel")]
+    serial_pool: Option<Arc<ThreadPool>>,
+
+    /// Phases whose input has fewer than this many elements run without parallelism.
+    min_parallel_len: usize,
+}
+
+impl ParallelismConfig {
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "parallel")]
+            pool: None,
+            #[cfg(feature = "parallel")]
+            serial_pool: None,
+            min_parallel_len: 0,
+        }
+    }
+
+    /// Run parallel phases inside `pool` rather than the current pool.
+    #[cfg(feature = "parallel")]
+    #[must_use]
+    pub fn with_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Run phases whose input has fewer than `min_parallel_len` elements without parallelism.
+    ///
+    /// With the `parallel` feature, a nonzero threshold needs a single-threaded pool to run those
+    /// phases in. One is built here unless [`Self::with_serial_pool`] already gave one, and is
+    /// shared by this config's clones.
+    #[must_use]
+    pub fn with_min_parallel_len(mut self, min_parallel_len: usize) -> Self {
+        self.min_parallel_len = min_parallel_len;
+        #[cfg(feature = "parallel")]
+        if min_parallel_len > 0 && self.serial_pool.is_none() {
+            let serial_pool = ThreadPoolBuilder::new()
+                .num_threads(1)
+                .thread_name(|_| "p3-serial".into())
+                .build()
+                .expect("failed to build the serial thread pool");
+            self.serial_pool = Some(Arc::new(serial_pool));
+        }
+        self
+    }
+
+    /// Run phases below the threshold in `serial_pool`, which should have one thread, e.g. to share
+    /// it between configs.
+    #[cfg(feature = "parallel")]
+    #[must_use]
+    pub fn with_serial_pool(mut self, serial_pool: Arc<ThreadPool>) -> Self {
+        self.serial_pool = Some(serial_pool);
+        self
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn pool(&self) -> Option<&Arc<ThreadPool>> {
+        self.pool.as_ref()
+    }
+
+    pub const fn min_parallel_len(&self) -> usize {
+        self.min_parallel_len
+    }
+
+    /// Whether a phase with an input of `len` elements should use parallelism.
+    pub const fn should_parallelize(&self, len: usize) -> bool {
+        len >= self.min_parallel_len
+    }
+
+    /// Make this the current configuration on this thread until the returned guard is dropped.
+    #[must_use]
+    pub fn enter(&self) -> ParallelismGuard {
+        #[cfg(feature = "parallel")]
+        {
+            let previous = CURRENT.with(|current| current.replace(self.clone()));
+            ParallelismGuard {
+                previous: Some(previous),
+                _not_send: PhantomData,
+            }
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            ParallelismGuard { _private: () }
+        }
+    }
+
+    /// Run a parallel phase, whose input has `len` elements, according to this configuration.
+    pub fn install<OP, R>(&self, len: usize, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        #[cfg(feature = "parallel")]
+        {
+            if !self.should_parallelize(len) {
+                // A nonzero threshold always comes with a serial pool.
+                return match &self.serial_pool {
+                    Some(serial_pool) => serial_pool.install(op),
+                    None => op(),
+                };
+            }
+            match &self.pool {
+                Some(pool) => {
+                    let config = self.clone();
+                    pool.install(move || {
+                        // Nested phases running on the pool's threads see the same configuration.
+                        let _guard = config.enter();
+                        op()
+                    })
+                }
+                None => op(),
+            }
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            let _ = len;
+            op()
+        }
+    }
+}
+
+/// Restores the previously current `ParallelismConfig` when dropped.
+#[derive(Debug)]
+pub struct ParallelismGuard {
+    #[cfg(feature = "parallel")]
+    previous: Option<ParallelismConfig>,
+    // The guard restores a thread-local, so it must be dropped on the thread that created it.
+    #[cfg(feature = "parallel")]
+    _not_send: PhantomData<*const ()>,
+    #[cfg(not(feature = "parallel"))]
+    _private: (),
+}
+
+#[cfg(feature = "parallel")]
+impl Drop for ParallelismGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            CURRENT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+}
+
+/// Run a parallel phase, whose input has `len` elements, according to the current configuration.
+pub fn install<OP, R>(len: usize, op: OP) -> R
+where
+    OP: FnOnce() -> R + Send,
+    R: Send,
+{
+    current_parallelism().install(len, op)
+}
+
+/// The configuration most recently entered on this thread, or the default one.
+pub fn current_parallelism() -> ParallelismConfig {
+    #[cfg(feature = "parallel")]
+    {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        ParallelismConfig::new()
+    }
+}
+
+#[cfg(feature = "parallel")]
+std::thread_local! {
+    static CURRENT: RefCell<ParallelismConfig> = const { RefCell::new(ParallelismConfig::new()) };
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::prelude::*;
+
+    fn counting_pool(num_threads: usize, spawned: Arc<AtomicUsize>) -> Arc<ThreadPool> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .spawn_handler(move |thread| {
+                spawned.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || thread.run());
+                Ok(())
+            })
+            .build()
+            .unwrap();
+        Arc::new(pool)
+    }
+
+    #[test]
+    fn install_uses_configured_pool() {
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let config = ParallelismConfig::new().with_pool(counting_pool(2, spawned.clone()));
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+
+        let _guard = config.enter();
+        let (threads, nested_threads) = install(100, || {
+            (current_num_threads(), install(100, current_num_threads))
+        });
+        assert_eq!(threads, 2);
+        assert_eq!(nested_threads, 2);
+    }
+
+    #[test]
+    fn install_below_threshold_is_serial() {
+        let config = ParallelismConfig::new().with_min_parallel_len(100);
+        assert_eq!(config.install(99, current_num_threads), 1);
+        assert_eq!(
+            config.install(100, current_num_threads),
+            current_num_threads()
+        );
+    }
+
+    #[test]
+    fn threshold_uses_the_given_serial_pool() {
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let config = ParallelismConfig::new()
+            .with_serial_pool(counting_pool(1, spawned.clone()))
+            .with_min_parallel_len(100);
+        // No other serial pool was built for the threshold.
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+        assert_eq!(config.clone().install(99, current_num_threads), 1);
+    }
+
+    #[test]
+    fn guard_restores_previous_config() {
+        let outer = ParallelismConfig::new().with_min_parallel_len(10);
+        let inner = ParallelismConfig::new().with_min_parallel_len(20);
+        {
+            let _outer = outer.enter();
+            {
+                let _inner = inner.enter();
+                assert_eq!(current_parallelism().min_parallel_len(), 20);
+            }
+            assert_eq!(current_parallelism().min_parallel_len(), 10);
+        }
+        assert_eq!(current_parallelism().min_parallel_len(), 0);
+    }
+}
@@ -141,6 +141,10 @@ pub trait ParIterExt: Iterator {
     where
         P: Fn(&Self::Item) -> bool + Sync + Send;
 
+    fn find_first<P>(self, predicate: P) -> Option<Self::Item>
+    where
+        P: Fn(&Self::Item) -> bool + Sync + Send;
+
     fn flat_map_iter<U, F>(self, map_op: F) -> FlatMap<Self, U, F>
     where
         Self: Sized,
@@ -156,6 +160,13 @@ impl<T: Iterator> ParIterExt for T {
         self.find(predicate)
     }
 
+    fn find_first<P>(mut self, predicate: P) -> Option<Self::Item>
+    where
+        P: Fn(&Self::Item) -> bool + Sync + Send,
+    {
+        self.find(predicate)
+    }
+
     fn flat_map_iter<U, F>(self, map_op: F) -> FlatMap<Self, U, F>
     where
         Self: Sized,
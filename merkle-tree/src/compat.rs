@@ -0,0 +1,76 @@
+use itertools::Itertools;
+use p3_matrix::Dimensions;
+
+/// The layout a [`MerkleTreeMmcs`](crate::MerkleTreeMmcs) commits to matrices with.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CompatMode {
+    /// This crate's layout, see [`MerkleTree::new`](crate::MerkleTree::new).
+    #[default]
+    Native,
+    /// The layout of a release of upstream Plonky3, so that commitments match those in its proofs.
+    /// Matrices which that release couldn't commit to are rejected, rather than committed to in a
+    /// way it couldn't reproduce.
+    Upstream(UpstreamVersion),
+}
+
+/// A release of upstream Plonky3 whose Merkle tree layout [`CompatMode::Upstream`] reproduces.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UpstreamVersion {
+    /// Plonky3 0.1, which this crate was forked from. Its trees are binary. The leaves are the
+    /// hashes of the concatenated rows of the tallest matrices, in the order they were given. Each
+    /// layer is padded with default digests to an even length, and the rows of shorter matrices
+    /// are hashed and mixed into node `r` of the layer with as many nodes as their height rounded
+    /// up to a power of two, as `c([node, rows])`.
+    ///
+    /// The native layout only differs in what it accepts: matrices whose heights round up to the
+    /// same power of two must have the same height, and the arity must be 2.
+    V0_1,
+}
+
+impl CompatMode {
+    /// Whether a tree with `arity` children per node can commit to matrices of `dimensions` in
+    /// this mode.
+    pub fn supports(&self, arity: usize, dimensions: &[Dimensions]) -> bool {
+        match self {
+            Self::Native => true,
+            Self::Upstream(UpstreamVersion::V0_1) => {
+                arity == 2
+                    && dimensions
+                        .iter()
+                        .map(|dims| dims.height)
+                        .sorted_unstable()
+                        .tuple_windows()
+                        .all(|(a, b)| a == b || a.next_power_of_two() != b.next_power_of_two())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn dims(heights: &[usize]) -> Vec<Dimensions> {
+        heights
+            .iter()
+            .map(|&height| Dimensions { width: 1, height })
+            .collect()
+    }
+
+    #[test]
+    fn upstream_rejects_heights_padding_together() {
+        let upstream = CompatMode::Upstream(UpstreamVersion::V0_1);
+        for heights in [&[8, 8, 4][..], &[5, 3, 2, 1], &[7, 4, 7]] {
+            assert!(upstream.supports(2, &dims(heights)), "{heights:?}");
+        }
+        for heights in [&[8, 7][..], &[4, 5, 6], &[16, 3, 4]] {
+            assert!(!upstream.supports(2, &dims(heights)), "{heights:?}");
+            assert!(CompatMode::Native.supports(2, &dims(heights)));
+        }
+        assert!(!upstream.supports(4, &dims(&[16])));
+        assert!(CompatMode::Native.supports(4, &dims(&[16])));
+    }
+}
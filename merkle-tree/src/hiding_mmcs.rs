@@ -104,7 +104,8 @@ where
         let (openings, salts): (Vec<_>, Vec<_>) = salted_openings
             .into_iter()
             .map(|row| {
-                let (a, b) = row.split_at(row.len() - SALT_ELEMS);
+                // Padding rows are opened empty, without a salt.
+                let (a, b) = row.split_at(row.len().saturating_sub(SALT_ELEMS));
                 (a.to_vec(), b.to_vec())
             })
             .unzip();
@@ -172,22 +173,26 @@ mod tests {
     >;
 
     #[test]
-    #[should_panic]
-    fn mismatched_heights() {
+    fn heights_padded_to_same_power_of_two() -> Result<(), MerkleTreeError> {
         let mut rng = thread_rng();
         let perm = Perm::new_from_rng_128(&mut rng);
         let hash = MyHash::new(perm.clone());
         let compress = MyCompress::new(perm);
         let mmcs = MyMmcs::new(hash, compress, thread_rng());
 
-        // attempt to commit to a mat with 8 rows and a mat with 7 rows. this should panic.
-        let large_mat = RowMajorMatrix::new(
-            [1, 2, 3, 4, 5, 6, 7, 8].map(F::from_canonical_u8).to_vec(),
-            1,
-        );
-        let small_mat =
-            RowMajorMatrix::new([1, 2, 3, 4, 5, 6, 7].map(F::from_canonical_u8).to_vec(), 1);
-        let _ = mmcs.commit(vec![large_mat, small_mat]);
+        // A mat with 8 rows and a mat with 7 rows, which share a layer of the tree.
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 8, 1),
+            RowMajorMatrix::<F>::rand(&mut rng, 7, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+        for index in [0, 6, 7] {
+            let (opened_values, proof) = mmcs.open_batch(index, &prover_data);
+            assert_eq!(opened_values[1].is_empty(), index == 7);
+            mmcs.verify_batch(&commit, &dims, index, &opened_values, &proof)?;
+        }
+        Ok(())
     }
 
     #[test]
@@ -0,0 +1,172 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::array;
+
+use p3_field::PackedValue;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
+
+use crate::merkle_tree::unpack_array;
+use crate::MerkleTree;
+
+/// Builds the Merkle tree of a single matrix whose rows arrive in batches, hashing them as they
+/// come. The result is the same tree which `MerkleTreeMmcs::commit` builds from the whole matrix.
+///
+/// Rows are hashed in chunks of the packing width, and complete groups of `ARITY` digests are
+/// compressed straight away, so all that is left to `finalize` is the frontier: fewer than
+/// `P::WIDTH` unhashed rows, and fewer than `ARITY` uncompressed digests in each layer. The rows
+/// and finished digests are kept, since they make up the prover data needed to open the tree.
+pub struct IncrementalMerkleTree<'a, P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+where
+    P: PackedValue,
+    PW: PackedValue,
+{
+    hash: &'a H,
+    compress: &'a C,
+    /// The rows appended so far, of which the first `hashed_rows` have digests in the first layer.
+    values: Vec<P::Value>,
+    width: Option<usize>,
+    hashed_rows: usize,
+    /// Layer `i + 1` holds the compressions of the first complete groups of layer `i`.
+    digest_layers: Vec<Vec<[PW::Value; DIGEST_ELEMS]>>,
+}
+
+impl<'a, P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    IncrementalMerkleTree<'a, P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+{
+    pub fn new(hash: &'a H, compress: &'a C) -> Self {
+        const { assert!(P::WIDTH == PW::WIDTH, "Packing widths must match") };
+        assert!(
+            ARITY >= 2 && ARITY.is_power_of_two(),
+            "Merkle tree arity must be a power of two"
+        );
+        Self {
+            hash,
+            compress,
+            values: Vec::new(),
+            width: None,
+            hashed_rows: 0,
+            digest_layers: Vec::new(),
+        }
+    }
+
+    /// The number of rows appended so far.
+    pub fn current_leaf_count(&self) -> usize {
+        self.width.map_or(0, |width| self.values.len() / width)
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    IncrementalMerkleTree<'_, P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+    H: Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: Sync,
+    PW::Value: Send + Sync,
+{
+    /// Append `rows` below the rows appended so far. Every batch must have the same width.
+    pub fn append_rows(&mut self, rows: RowMajorMatrix<P::Value>) {
+        let width = *self.width.get_or_insert(rows.width());
+        assert_eq!(rows.width(), width, "all batches must have the same width");
+        self.values.extend(rows.values);
+
+        // Hash as many rows as fill whole packed chunks.
+        let height = self.current_leaf_count();
+        let num_chunks = (height - self.hashed_rows) / P::WIDTH;
+        let view = RowMajorMatrixView::new(&self.values, width);
+        let first_row = self.hashed_rows;
+        let digests: Vec<_> = (0..num_chunks)
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let packed_digest: [PW; DIGEST_ELEMS] = self
+                    .hash
+                    .hash_iter(view.vertically_packed_row::<P>(first_row + i * P::WIDTH));
+                unpack_array(packed_digest)
+            })
+            .collect();
+        self.hashed_rows += num_chunks * P::WIDTH;
+        self.push_digests(digests);
+    }
+
+    /// Append digests to the first layer, compressing any groups which that completes.
+    fn push_digests(&mut self, digests: Vec<[PW::Value; DIGEST_ELEMS]>) {
+        if self.digest_layers.is_empty() {
+            self.digest_layers.push(Vec::new());
+        }
+        self.digest_layers[0].extend(digests);
+
+        let mut layer = 0;
+        while self.digest_layers[layer].len() >= ARITY * (self.num_compressed(layer) + 1) {
+            let next = self.compress_groups(layer, self.digest_layers[layer].len() / ARITY);
+            if layer + 1 == self.digest_layers.len() {
+                self.digest_layers.push(Vec::new());
+            }
+            self.digest_layers[layer + 1].extend(next);
+            layer += 1;
+        }
+    }
+
+    /// The number of groups of `layer` which have been compressed into the next layer.
+    fn num_compressed(&self, layer: usize) -> usize {
+        self.digest_layers.get(layer + 1).map_or(0, Vec::len)
+    }
+
+    /// Compress the groups of `layer` which haven't been yet, up to group `end`.
+    fn compress_groups(&self, layer: usize, end: usize) -> Vec<[PW::Value; DIGEST_ELEMS]> {
+        let digests = &self.digest_layers[layer];
+        (self.num_compressed(layer)..end)
+            .into_par_iter()
+            .map(|i| {
+                self.compress
+                    .compress(array::from_fn(|child| digests[ARITY * i + child]))
+            })
+            .collect()
+    }
+
+    /// Hash the remaining rows and compress the frontier, padding each layer as
+    /// `MerkleTree::new` does.
+    pub fn finalize(
+        mut self,
+    ) -> (
+        Hash<P::Value, PW::Value, DIGEST_ELEMS>,
+        MerkleTree<P::Value, PW::Value, RowMajorMatrix<P::Value>, DIGEST_ELEMS, ARITY>,
+    ) {
+        let width = self.width.expect("No rows appended?");
+        let height = self.current_leaf_count();
+        assert!(height > 0, "No rows appended?");
+
+        let view = RowMajorMatrixView::new(&self.values, width);
+        let stragglers: Vec<_> = (self.hashed_rows..height)
+            .map(|r| self.hash.hash_iter(view.row(r)))
+            .collect();
+        self.push_digests(stragglers);
+
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        let mut layer = 0;
+        while layer + 1 < self.digest_layers.len() || self.digest_layers[layer].len() > 1 {
+            let padded_len = self.digest_layers[layer].len().next_multiple_of(ARITY);
+            self.digest_layers[layer].resize(padded_len, default_digest);
+            let next = self.compress_groups(layer, padded_len / ARITY);
+            if layer + 1 == self.digest_layers.len() {
+                self.digest_layers.push(Vec::new());
+            }
+            self.digest_layers[layer + 1].extend(next);
+            layer += 1;
+        }
+
+        let tree = MerkleTree::from_layers(
+            vec![RowMajorMatrix::new(self.values, width)],
+            self.digest_layers,
+        );
+        (tree.root(), tree)
+    }
+}
@@ -2,10 +2,16 @@
 
 extern crate alloc;
 
+mod compat;
 mod hiding_mmcs;
+mod incremental;
 mod merkle_tree;
 mod mmcs;
+mod multi_proof;
 
+pub use compat::*;
 pub use hiding_mmcs::*;
+pub use incremental::*;
 pub use merkle_tree::*;
 pub use mmcs::*;
+pub use multi_proof::*;
@@ -6,19 +6,23 @@ use core::marker::PhantomData;
 
 use itertools::Itertools;
 use p3_field::PackedValue;
-use p3_matrix::Matrix;
+use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
 use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
+use p3_util::log2_ceil_usize;
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
-/// A binary Merkle tree for packed data. It has leaves of type `F` and digests of type
-/// `[W; DIGEST_ELEMS]`.
+/// A Merkle tree for packed data, in which each node has `ARITY` children. It has leaves of
+/// type `F` and digests of type `[W; DIGEST_ELEMS]`.
+///
+/// `ARITY` must be a power of two. Each layer of digests is padded with default digests to a
+/// multiple of `ARITY`, except for the root layer.
 ///
 /// This generally shouldn't be used directly. If you're using a Merkle tree as an MMCS,
 /// see `MerkleTreeMmcs`.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct MerkleTree<F, W, M, const DIGEST_ELEMS: usize> {
+pub struct MerkleTree<F, W, M, const DIGEST_ELEMS: usize, const ARITY: usize = 2> {
     pub(crate) leaves: Vec<M>,
     // Enable serialization for this type whenever the underlying array type supports it (len 1-32).
     #[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
@@ -28,11 +32,23 @@ pub struct MerkleTree<F, W, M, const DIGEST_ELEMS: usize> {
     _phantom: PhantomData<F>,
 }
 
-impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
-    MerkleTree<F, W, M, DIGEST_ELEMS>
+impl<
+        F: Clone + Send + Sync,
+        W: Clone,
+        M: Matrix<F>,
+        const DIGEST_ELEMS: usize,
+        const ARITY: usize,
+    > MerkleTree<F, W, M, DIGEST_ELEMS, ARITY>
 {
-    /// Matrix heights need not be powers of two. However, if the heights of two given matrices
-    /// round up to the same power of two, they must be equal.
+    /// Matrix heights need not be powers of two, nor equal. Each matrix is padded to the next
+    /// power of two, and its rows are mixed in at the layer of the tree with that many nodes: the
+    /// rows `r` of all matrices with the same padded height are hashed together, in order of
+    /// decreasing height, and the digest is mixed into node `r` of that layer. Padding rows are
+    /// empty, so a matrix only contributes to the nodes below its height, and a node to which no
+    /// matrix contributes gets the default digest.
+    ///
+    /// For `ARITY > 2`, the padded heights must also be powers of `ARITY` apart (or 1), so that
+    /// every matrix has a layer of matching size.
     #[instrument(name = "build merkle tree", level = "debug", skip_all,
                  fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
     pub fn new<P, PW, H, C>(h: &H, c: &C, leaves: Vec<M>) -> Self
@@ -42,60 +58,90 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
         H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
         H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
         H: Sync,
-        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], 2>,
-        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
         C: Sync,
+        W: Send + Sync,
     {
         assert!(!leaves.is_empty(), "No matrices given?");
 
-        assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
-
-        let mut leaves_largest_first = leaves
-            .iter()
-            .sorted_by_key(|l| Reverse(l.height()))
-            .peekable();
+        const { assert!(P::WIDTH == PW::WIDTH, "Packing widths must match") };
 
-        // check height property
         assert!(
-            leaves_largest_first
-                .clone()
-                .map(|m| m.height())
-                .tuple_windows()
-                .all(|(curr, next)| curr == next
-                    || curr.next_power_of_two() != next.next_power_of_two()),
-            "matrix heights that round up to the same power of two must be equal"
+            ARITY >= 2 && ARITY.is_power_of_two(),
+            "Merkle tree arity must be a power of two"
         );
 
-        let max_height = leaves_largest_first.peek().unwrap().height();
-        let tallest_matrices = leaves_largest_first
-            .peeking_take_while(|m| m.height() == max_height)
-            .collect_vec();
-
-        let mut digest_layers = vec![first_digest_layer::<P, PW, H, M, DIGEST_ELEMS>(
-            h,
-            tallest_matrices,
-        )];
-        loop {
-            let prev_layer = digest_layers.last().unwrap().as_slice();
-            if prev_layer.len() == 1 {
-                break;
-            }
-            let next_layer_len = (prev_layer.len() / 2).next_power_of_two();
+        let total_len = leaves.iter().map(|l| l.width() * l.height()).sum();
+        let digest_layers = p3_maybe_rayon::install(total_len, || {
+            let mut leaves_largest_first = leaves
+                .iter()
+                .sorted_by_key(|l| Reverse(l.height()))
+                .peekable();
 
-            // The matrices that get injected at this layer.
-            let matrices_to_inject = leaves_largest_first
-                .peeking_take_while(|m| m.height().next_power_of_two() == next_layer_len)
+            let max_height_padded = leaves_largest_first
+                .peek()
+                .unwrap()
+                .height()
+                .next_power_of_two();
+            let tallest_matrices = leaves_largest_first
+                .peeking_take_while(|m| m.height().next_power_of_two() == max_height_padded)
                 .collect_vec();
 
-            let next_digests = compress_and_inject::<P, PW, H, C, M, DIGEST_ELEMS>(
-                prev_layer,
-                matrices_to_inject,
+            let mut digest_layers = vec![first_digest_layer::<P, PW, H, M, DIGEST_ELEMS, ARITY>(
                 h,
-                c,
+                tallest_matrices,
+            )];
+            let mut layer_height_padded = max_height_padded;
+            loop {
+                let prev_layer = digest_layers.last().unwrap().as_slice();
+                if prev_layer.len() == 1 {
+                    break;
+                }
+                layer_height_padded = layer_height_padded.div_ceil(ARITY);
+
+                // The matrices that get injected at this layer.
+                let matrices_to_inject = leaves_largest_first
+                    .peeking_take_while(|m| m.height().next_power_of_two() == layer_height_padded)
+                    .collect_vec();
+
+                // Each layer is compressed in parallel chunks of the packing width. The layers near
+                // the root are small enough that parallelism would only add overhead, so each one
+                // is installed separately to let the threshold apply to it.
+                let layer_len = prev_layer.len() * DIGEST_ELEMS
+                    + matrices_to_inject
+                        .iter()
+                        .map(|m| m.width() * m.height())
+                        .sum::<usize>();
+                let next_digests = p3_maybe_rayon::install(layer_len, || {
+                    compress_and_inject::<P, PW, H, C, M, DIGEST_ELEMS, ARITY>(
+                        prev_layer,
+                        matrices_to_inject,
+                        h,
+                        c,
+                    )
+                });
+                digest_layers.push(next_digests);
+            }
+            assert!(
+                leaves_largest_first.peek().is_none(),
+                "matrix heights must round up to powers of two which are a power of the arity apart"
             );
-            digest_layers.push(next_digests);
+            digest_layers
+        });
+
+        Self {
+            leaves,
+            digest_layers,
+            _phantom: PhantomData,
         }
+    }
 
+    /// A tree whose digest layers were already computed, as `new` would from `leaves`.
+    pub(crate) const fn from_layers(
+        leaves: Vec<M>,
+        digest_layers: Vec<Vec<[W; DIGEST_ELEMS]>>,
+    ) -> Self {
         Self {
             leaves,
             digest_layers,
@@ -110,10 +156,49 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
     {
         self.digest_layers.last().unwrap()[0].into()
     }
+
+    /// The dimensions of the committed matrices, in the order they were given.
+    pub fn dimensions(&self) -> Vec<Dimensions> {
+        self.leaves.iter().map(|m| m.dimensions()).collect()
+    }
+
+    /// The row of the `matrix`th matrix which is opened at `index`, or `None` if it has no row
+    /// there.
+    ///
+    /// If the tallest matrices are padded to `2^n` rows and this one to `2^k`, its rows are mixed in
+    /// `n - k` layers above the leaves, so `index` maps to row `index >> (n - k)`. Rows past the
+    /// matrix's height are padding, which is opened as an empty row.
+    pub fn local_row(&self, matrix: usize, index: usize) -> Option<usize> {
+        let max_height = self.leaves.iter().map(|m| m.height()).max().unwrap();
+        let height = self.leaves[matrix].height();
+        let bits_reduced = log2_ceil_usize(max_height) - log2_ceil_usize(height);
+        let row = index >> bits_reduced;
+        (row < height).then_some(row)
+    }
+
+    /// The row of each matrix which is opened at `index`, following `local_row`.
+    pub(crate) fn rows_at(&self, index: usize) -> Vec<Vec<F>> {
+        (0..self.leaves.len())
+            .map(|matrix| match self.local_row(matrix, index) {
+                Some(row) => self.leaves[matrix].row(row).collect(),
+                None => Vec::new(),
+            })
+            .collect()
+    }
+}
+
+/// The length of a layer with `len` digests once padded, i.e. rounded up to a multiple of `ARITY`
+/// unless it's the root.
+pub(crate) const fn padded_layer_len<const ARITY: usize>(len: usize) -> usize {
+    if len == 1 {
+        1
+    } else {
+        len.next_multiple_of(ARITY)
+    }
 }
 
 #[instrument(name = "first digest layer", level = "debug", skip_all)]
-fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize>(
+fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize, const ARITY: usize>(
     h: &H,
     tallest_matrices: Vec<&M>,
 ) -> Vec<[PW::Value; DIGEST_ELEMS]>
@@ -126,18 +211,17 @@ where
     M: Matrix<P::Value>,
 {
     let width = PW::WIDTH;
+    // The matrices are sorted by decreasing height.
     let max_height = tallest_matrices[0].height();
-    // we always want to return an even number of digests, except when it's the root.
-    let max_height_padded = if max_height == 1 {
-        1
-    } else {
-        max_height + max_height % 2
-    };
+    let min_height = tallest_matrices.last().unwrap().height();
+    // we always want to return a multiple of ARITY digests, except when it's the root.
+    let max_height_padded = padded_layer_len::<ARITY>(max_height);
 
     let default_digest: [PW::Value; DIGEST_ELEMS] = [PW::Value::default(); DIGEST_ELEMS];
     let mut digests = vec![default_digest; max_height_padded];
 
-    digests[0..max_height]
+    // Rows which all the matrices have can be hashed in packed chunks.
+    digests[0..min_height]
         .par_chunks_exact_mut(width)
         .enumerate()
         .for_each(|(i, digests_chunk)| {
@@ -152,20 +236,20 @@ where
             }
         });
 
-    // If our packing width did not divide max_height, fall back to single-threaded scalar code
-    // for the last bit.
+    // If our packing width did not divide min_height, or the matrices have different heights,
+    // fall back to single-threaded scalar code for the last bit.
     #[allow(clippy::needless_range_loop)]
-    for i in (max_height / width * width)..max_height {
-        digests[i] = h.hash_iter(tallest_matrices.iter().flat_map(|m| m.row(i)));
+    for i in (min_height / width * width)..max_height {
+        digests[i] = h.hash_iter(rows_at_position(&tallest_matrices, i));
     }
 
     // Everything has been initialized so we can safely cast.
     digests
 }
 
-/// Compress `n` digests from the previous layer into `n/2` digests, while potentially mixing in
-/// some leaf data, if there are input matrices with (padded) height `n/2`.
-fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize>(
+/// Compress `n` digests from the previous layer into `n / ARITY` digests, while potentially mixing
+/// in some leaf data, if there are input matrices with (padded) height `n / ARITY`.
+fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize, const ARITY: usize>(
     prev_layer: &[[PW::Value; DIGEST_ELEMS]],
     matrices_to_inject: Vec<&M>,
     h: &H,
@@ -177,87 +261,82 @@ where
     H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
     H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
     H: Sync,
-    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
     C: Sync,
     M: Matrix<P::Value>,
 {
     if matrices_to_inject.is_empty() {
-        return compress::<PW, C, DIGEST_ELEMS>(prev_layer, c);
+        return compress::<PW, C, DIGEST_ELEMS, ARITY>(prev_layer, c);
     }
 
     let width = PW::WIDTH;
+    // The matrices are sorted by decreasing height.
     let next_len = matrices_to_inject[0].height();
-    // We always want to return an even number of digests, except when it's the root.
-    let next_len_padded = if prev_layer.len() == 2 {
-        1
-    } else {
-        (prev_layer.len() / 2 + 1) & !1
-    };
+    let min_len = matrices_to_inject.last().unwrap().height();
+    // We always want to return a multiple of ARITY digests, except when it's the root.
+    let next_len_padded = padded_layer_len::<ARITY>(prev_layer.len() / ARITY);
 
     let default_digest: [PW::Value; DIGEST_ELEMS] = [PW::Value::default(); DIGEST_ELEMS];
+    let packed_default_digest: [PW; DIGEST_ELEMS] =
+        [PW::from_fn(|_| PW::Value::default()); DIGEST_ELEMS];
     let mut next_digests = vec![default_digest; next_len_padded];
-    next_digests[0..next_len]
+    // Rows which all the matrices have can be hashed in packed chunks.
+    next_digests[0..min_len]
         .par_chunks_exact_mut(width)
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
-            let left = array::from_fn(|j| PW::from_fn(|k| prev_layer[2 * (first_row + k)][j]));
-            let right = array::from_fn(|j| PW::from_fn(|k| prev_layer[2 * (first_row + k) + 1][j]));
-            let mut packed_digest = c.compress([left, right]);
+            let children = array::from_fn(|child| {
+                array::from_fn(|j| PW::from_fn(|k| prev_layer[ARITY * (first_row + k) + child][j]))
+            });
+            let packed_digest = c.compress(children);
             let tallest_digest = h.hash_iter(
                 matrices_to_inject
                     .iter()
                     .flat_map(|m| m.vertically_packed_row(first_row)),
             );
-            packed_digest = c.compress([packed_digest, tallest_digest]);
+            let packed_digest =
+                inject::<_, _, ARITY>(c, packed_digest, tallest_digest, packed_default_digest);
             for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
                 *dst = src;
             }
         });
 
-    // If our packing width did not divide next_len, fall back to single-threaded scalar code
-    // for the last bit.
-    for i in (next_len / width * width)..next_len {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
-        let rows_digest = h.hash_iter(matrices_to_inject.iter().flat_map(|m| m.row(i)));
-        next_digests[i] = c.compress([digest, rows_digest]);
+    // If our packing width did not divide min_len, or the matrices have different heights, fall
+    // back to single-threaded scalar code for the last bit.
+    for i in (min_len / width * width)..next_len {
+        let digest = c.compress(array::from_fn(|child| prev_layer[ARITY * i + child]));
+        let rows_digest = h.hash_iter(rows_at_position(&matrices_to_inject, i));
+        next_digests[i] = inject::<_, _, ARITY>(c, digest, rows_digest, default_digest);
     }
 
     // At this point, we've exceeded the height of the matrices to inject, so we continue the
     // process above except with default_digest in place of an input digest.
-    // We only need go as far as half the length of the previous layer.
-    for i in next_len..(prev_layer.len() / 2) {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
-        next_digests[i] = c.compress([digest, default_digest]);
+    // We only need go as far as the length of the previous layer divided by ARITY.
+    for i in next_len..(prev_layer.len() / ARITY) {
+        let digest = c.compress(array::from_fn(|child| prev_layer[ARITY * i + child]));
+        next_digests[i] = inject::<_, _, ARITY>(c, digest, default_digest, default_digest);
     }
 
     next_digests
 }
 
-/// Compress `n` digests from the previous layer into `n/2` digests.
-fn compress<P, C, const DIGEST_ELEMS: usize>(
+/// Compress `n` digests from the previous layer into `n / ARITY` digests.
+fn compress<P, C, const DIGEST_ELEMS: usize, const ARITY: usize>(
     prev_layer: &[[P::Value; DIGEST_ELEMS]],
     c: &C,
 ) -> Vec<[P::Value; DIGEST_ELEMS]>
 where
     P: PackedValue,
-    C: PseudoCompressionFunction<[P::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[P; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[P::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[P; DIGEST_ELEMS], ARITY>,
     C: Sync,
 {
     let width = P::WIDTH;
-    // Always return an even number of digests, except when it's the root.
-    let next_len_padded = if prev_layer.len() == 2 {
-        1
-    } else {
-        (prev_layer.len() / 2 + 1) & !1
-    };
-    let next_len = prev_layer.len() / 2;
+    let next_len = prev_layer.len() / ARITY;
+    // Always return a multiple of ARITY digests, except when it's the root.
+    let next_len_padded = padded_layer_len::<ARITY>(next_len);
 
     let default_digest: [P::Value; DIGEST_ELEMS] = [P::Value::default(); DIGEST_ELEMS];
     let mut next_digests = vec![default_digest; next_len_padded];
@@ -267,9 +346,10 @@ where
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
-            let left = array::from_fn(|j| P::from_fn(|k| prev_layer[2 * (first_row + k)][j]));
-            let right = array::from_fn(|j| P::from_fn(|k| prev_layer[2 * (first_row + k) + 1][j]));
-            let packed_digest = c.compress([left, right]);
+            let children = array::from_fn(|child| {
+                array::from_fn(|j| P::from_fn(|k| prev_layer[ARITY * (first_row + k) + child][j]))
+            });
+            let packed_digest = c.compress(children);
             for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
                 *dst = src;
             }
@@ -278,18 +358,42 @@ where
     // If our packing width did not divide next_len, fall back to single-threaded scalar code
     // for the last bit.
     for i in (next_len / width * width)..next_len {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        next_digests[i] = c.compress([left, right]);
+        next_digests[i] = c.compress(array::from_fn(|child| prev_layer[ARITY * i + child]));
     }
 
     // Everything has been initialized so we can safely cast.
     next_digests
 }
 
+/// The concatenated rows `i` of those matrices which have one.
+fn rows_at_position<'a, T, M>(matrices: &'a [&'a M], i: usize) -> impl Iterator<Item = T> + 'a
+where
+    T: Send + Sync,
+    M: Matrix<T>,
+{
+    matrices
+        .iter()
+        .filter(move |m| i < m.height())
+        .flat_map(move |m| m.row(i))
+}
+
+/// Mix the digest of some injected rows into a node's digest. The two are compressed together,
+/// alongside `ARITY - 2` default digests, so for a binary tree this is just `c([node, rows])`.
+#[inline]
+pub(crate) fn inject<T: Copy, C, const ARITY: usize>(c: &C, node: T, rows: T, default: T) -> T
+where
+    C: PseudoCompressionFunction<T, ARITY>,
+{
+    c.compress(array::from_fn(|i| match i {
+        0 => node,
+        1 => rows,
+        _ => default,
+    }))
+}
+
 /// Converts a packed array `[P; N]` into its underlying `P::WIDTH` scalar arrays.
 #[inline]
-fn unpack_array<P: PackedValue, const N: usize>(
+pub(crate) fn unpack_array<P: PackedValue, const N: usize>(
     packed_digest: [P; N],
 ) -> impl Iterator<Item = [P::Value; N]> {
     (0..P::WIDTH).map(move |j| packed_digest.map(|p| p.as_slice()[j]))
@@ -0,0 +1,403 @@
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::ops::Range;
+
+use itertools::Itertools;
+use p3_field::PackedValue;
+use p3_matrix::{Dimensions, Matrix};
+use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
+use p3_util::{log2_ceil_usize, log2_strict_usize};
+use serde::{Deserialize, Serialize};
+
+use crate::merkle_tree::inject;
+use crate::MerkleTreeError::{
+    IncompatibleLayout, RootMismatch, UnalignedHeight, WrongBatchSize, WrongHeight,
+};
+use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
+
+/// An opening of several indices of a `MerkleTreeMmcs` commitment at once.
+///
+/// Rather than one authentication path per index, this holds only the digests which the verifier
+/// can't recompute from the opened rows, so indices which share ancestors share the digests of
+/// their siblings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiProof<F, W, const DIGEST_ELEMS: usize> {
+    /// For each distinct index in ascending order, the opened row of each matrix.
+    pub opened_values: Vec<Vec<Vec<F>>>,
+    /// The missing digests of each layer, from the leaves up, and from left to right within a layer.
+    #[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
+    #[serde(bound(deserialize = "[W; DIGEST_ELEMS]: Deserialize<'de>"))]
+    pub siblings: Vec<[W; DIGEST_ELEMS]>,
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> MultiProof<F, W, DIGEST_ELEMS> {
+    /// The size of the proof, counting each opened value and each element of a digest as one.
+    pub fn size_in_field_elements(&self) -> usize {
+        let num_opened_values: usize = self.opened_values.iter().flatten().map(Vec::len).sum();
+        num_opened_values + self.siblings.len() * DIGEST_ELEMS
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+    H: Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+    C: Sync,
+    PW::Value: Eq,
+    [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
+{
+    /// Open every matrix at each of `indices`, which may contain duplicates and be in any order,
+    /// like `open_batch` does for a single index.
+    pub fn open_multi<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    ) -> MultiProof<P::Value, PW::Value, DIGEST_ELEMS> {
+        assert!(!indices.is_empty(), "No indices to open?");
+        let mut positions = distinct_sorted(indices);
+        let opened_values = positions
+            .iter()
+            .map(|&index| prover_data.rows_at(index))
+            .collect();
+
+        let log_arity = log2_strict_usize(ARITY);
+        let num_layers = prover_data.digest_layers.len() - 1;
+        let mut siblings = Vec::new();
+        for layer in &prover_data.digest_layers[..num_layers] {
+            for group in positions.chunk_by(|a, b| a >> log_arity == b >> log_arity) {
+                let group_start = group[0] & !(ARITY - 1);
+                siblings.extend(
+                    (group_start..group_start + ARITY)
+                        .filter(|j| !group.contains(j))
+                        .map(|j| layer[j]),
+                );
+            }
+            positions = positions.iter().map(|p| p >> log_arity).dedup().collect();
+        }
+
+        MultiProof {
+            opened_values,
+            siblings,
+        }
+    }
+
+    /// Verify an opening produced by `open_multi` for the same `indices`.
+    pub fn verify_multi_batch(
+        &self,
+        commit: &Hash<P::Value, PW::Value, DIGEST_ELEMS>,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        proof: &MultiProof<P::Value, PW::Value, DIGEST_ELEMS>,
+    ) -> Result<(), MerkleTreeError> {
+        let indices = distinct_sorted(indices);
+        if indices.is_empty()
+            || dimensions.is_empty()
+            || proof.opened_values.len() != indices.len()
+            || proof
+                .opened_values
+                .iter()
+                .any(|rows| rows.len() != dimensions.len())
+        {
+            return Err(WrongBatchSize);
+        }
+        if !self.compat_mode().supports(ARITY, dimensions) {
+            return Err(IncompatibleLayout);
+        }
+
+        let max_height = dimensions.iter().map(|dim| dim.height).max().unwrap();
+        let log_arity = log2_strict_usize(ARITY);
+        let num_layers = log2_ceil_usize(max_height).div_ceil(log_arity);
+        let wrong_height = || WrongHeight {
+            max_height,
+            num_siblings: proof.siblings.len(),
+        };
+
+        let mut heights_tallest_first = dimensions
+            .iter()
+            .enumerate()
+            .sorted_by_key(|(_, dims)| Reverse(dims.height))
+            .peekable();
+
+        let mut curr_height_padded = heights_tallest_first
+            .peek()
+            .unwrap()
+            .1
+            .height
+            .next_power_of_two();
+
+        let tallest = heights_tallest_first
+            .peeking_take_while(|(_, dims)| dims.height.next_power_of_two() == curr_height_padded)
+            .map(|(i, _)| i)
+            .collect_vec();
+
+        // The known nodes of the current layer, each with its position in the layer and the range
+        // of `indices` below it.
+        let mut nodes: Vec<(usize, [PW::Value; DIGEST_ELEMS], Range<usize>)> = indices
+            .iter()
+            .enumerate()
+            .map(|(k, &index)| {
+                let digest =
+                    self.rows_digest(dimensions, &tallest, index, &proof.opened_values[k])?;
+                Ok((index, digest, k..k + 1))
+            })
+            .collect::<Result<_, MerkleTreeError>>()?;
+
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        let mut siblings = proof.siblings.iter();
+        for _ in 0..num_layers {
+            curr_height_padded = curr_height_padded.div_ceil(ARITY);
+            let injected = heights_tallest_first
+                .peeking_take_while(|(_, dims)| {
+                    dims.height.next_power_of_two() == curr_height_padded
+                })
+                .map(|(i, _)| i)
+                .collect_vec();
+
+            let mut parents = Vec::with_capacity(nodes.len());
+            for group in nodes.chunk_by(|(a, ..), (b, ..)| a >> log_arity == b >> log_arity) {
+                let group_start = group[0].0 & !(ARITY - 1);
+                let mut known = group.iter().peekable();
+                let mut children = [default_digest; ARITY];
+                for (j, child) in children.iter_mut().enumerate() {
+                    *child = match known.next_if(|(position, ..)| *position == group_start + j) {
+                        Some((_, digest, _)) => *digest,
+                        None => *siblings.next().ok_or_else(wrong_height)?,
+                    };
+                }
+                let mut parent = self.compress.compress(children);
+
+                let below = group[0].2.start..group[group.len() - 1].2.end;
+                if !injected.is_empty() {
+                    // All the indices below this node open the same rows of the injected
+                    // matrices, so they must agree on them.
+                    let rows = &proof.opened_values[below.start];
+                    if proof.opened_values[below.clone()]
+                        .iter()
+                        .any(|other| injected.iter().any(|&i| other[i] != rows[i]))
+                    {
+                        return Err(RootMismatch);
+                    }
+                    let rows_digest =
+                        self.rows_digest(dimensions, &injected, group_start >> log_arity, rows)?;
+                    parent =
+                        inject::<_, _, ARITY>(&self.compress, parent, rows_digest, default_digest);
+                }
+
+                parents.push((group_start >> log_arity, parent, below));
+            }
+            nodes = parents;
+        }
+
+        if siblings.next().is_some() {
+            return Err(wrong_height());
+        }
+
+        // Any remaining matrices weren't mixed in at any layer.
+        if let Some((_, dims)) = heights_tallest_first.next() {
+            return Err(UnalignedHeight {
+                height: dims.height,
+            });
+        }
+
+        // Indices beyond the tree's leaves don't all end up at the root.
+        match nodes.as_slice() {
+            [(0, root, _)] if commit == root => Ok(()),
+            _ => Err(RootMismatch),
+        }
+    }
+}
+
+fn distinct_sorted(indices: &[usize]) -> Vec<usize> {
+    indices.iter().copied().sorted().dedup().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_commit::Mmcs;
+    use p3_field::{Field, FieldAlgebra};
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::{Dimensions, Matrix};
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::MultiProof;
+    use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
+
+    type F = BabyBear;
+
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyMmcs =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress, 8>;
+
+    type MyHash8 = PaddingFreeSponge<Perm, 16, 8, 2>;
+    type MyCompress8 = TruncatedPermutation<Perm, 8, 2, 16>;
+    type MyMmcs8 =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash8, MyCompress8, 2, 8>;
+
+    fn test_mmcs(rng: &mut StdRng) -> MyMmcs {
+        let perm = Perm::new_from_rng_128(rng);
+        MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm))
+    }
+
+    /// A tall matrix alongside two shorter ones, which are mixed in at higher layers.
+    fn commit_mixed(
+        mmcs: &MyMmcs,
+        rng: &mut StdRng,
+    ) -> (
+        <MyMmcs as Mmcs<F>>::Commitment,
+        MerkleTree<F, F, RowMajorMatrix<F>, 8>,
+        Vec<Dimensions>,
+    ) {
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(rng, 1 << 10, 3),
+            RowMajorMatrix::<F>::rand(rng, 1 << 7, 5),
+            RowMajorMatrix::<F>::rand(rng, 1 << 10, 2),
+            RowMajorMatrix::<F>::rand(rng, 1 << 3, 1),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect();
+        let (commit, data) = mmcs.commit(mats);
+        (commit, data, dims)
+    }
+
+    fn naive_size(
+        mmcs: &MyMmcs,
+        data: &MerkleTree<F, F, RowMajorMatrix<F>, 8>,
+        indices: &[usize],
+    ) -> usize {
+        indices
+            .iter()
+            .map(|&index| {
+                let (opened_values, proof) = mmcs.open_batch(index, data);
+                opened_values.iter().map(Vec::len).sum::<usize>() + proof.len() * 8
+            })
+            .sum()
+    }
+
+    #[test]
+    fn open_multi_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mmcs = test_mmcs(&mut rng);
+        let (commit, data, dims) = commit_mixed(&mmcs, &mut rng);
+
+        let indices: Vec<usize> = (0..100).map(|_| rng.gen_range(0..1 << 10)).collect();
+        let proof = mmcs.open_multi(&indices, &data);
+        mmcs.verify_multi_batch(&commit, &dims, &indices, &proof)
+            .unwrap();
+
+        // The openings are the same as those of single indices.
+        let mut distinct = indices.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        for (&index, opened_values) in distinct.iter().zip(&proof.opened_values) {
+            assert_eq!(*opened_values, mmcs.open_batch(index, &data).0);
+        }
+
+        assert!(proof.size_in_field_elements() < naive_size(&mmcs, &data, &indices));
+    }
+
+    #[test]
+    fn open_multi_edge_cases() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mmcs = test_mmcs(&mut rng);
+        let (commit, data, dims) = commit_mixed(&mmcs, &mut rng);
+
+        let cases: [&[usize]; 5] = [
+            &[17],
+            &[5, 9, 5, 5],
+            &[6, 7],
+            &[300, 300, 300],
+            &[1023, 0, 512, 511],
+        ];
+        for indices in cases {
+            let proof = mmcs.open_multi(indices, &data);
+            mmcs.verify_multi_batch(&commit, &dims, indices, &proof)
+                .unwrap();
+        }
+
+        // Adjacent indices sharing a parent need one sibling fewer per layer on the way up,
+        // and duplicates cost nothing.
+        let single = mmcs.open_multi(&[6], &data);
+        let pair = mmcs.open_multi(&[6, 7, 7], &data);
+        assert_eq!(pair.siblings.len(), single.siblings.len() - 1);
+
+        // Identical indices give the same proof as a single index.
+        let identical = mmcs.open_multi(&[300, 300, 300], &data);
+        assert_eq!(identical.siblings, mmcs.open_batch(300, &data).1);
+    }
+
+    #[test]
+    fn verify_multi_rejects_bad_openings() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mmcs = test_mmcs(&mut rng);
+        let (commit, data, dims) = commit_mixed(&mmcs, &mut rng);
+        let indices = [3, 4, 100, 101, 900];
+        let proof = mmcs.open_multi(&indices, &data);
+
+        let mut tampered = proof.clone();
+        tampered.opened_values[2][1][0] += F::ONE;
+        assert!(matches!(
+            mmcs.verify_multi_batch(&commit, &dims, &indices, &tampered),
+            Err(MerkleTreeError::RootMismatch)
+        ));
+
+        let mut truncated = proof.clone();
+        truncated.siblings.pop();
+        assert!(matches!(
+            mmcs.verify_multi_batch(&commit, &dims, &indices, &truncated),
+            Err(MerkleTreeError::WrongHeight { .. })
+        ));
+
+        assert!(matches!(
+            mmcs.verify_multi_batch(&commit, &dims, &[3, 4, 100, 101], &proof),
+            Err(MerkleTreeError::WrongBatchSize)
+        ));
+        assert!(mmcs
+            .verify_multi_batch(&commit, &dims, &[3, 4, 100, 102, 900], &proof)
+            .is_err());
+    }
+
+    #[test]
+    fn multi_proof_serde_round_trip() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mmcs = test_mmcs(&mut rng);
+        let (commit, data, dims) = commit_mixed(&mmcs, &mut rng);
+        let indices = [1, 2, 3, 500];
+        let proof = mmcs.open_multi(&indices, &data);
+
+        let bytes = postcard::to_allocvec(&proof).unwrap();
+        let decoded: MultiProof<F, F, 8> = postcard::from_bytes(&bytes).unwrap();
+        mmcs.verify_multi_batch(&commit, &dims, &indices, &decoded)
+            .unwrap();
+    }
+
+    #[test]
+    fn open_multi_arity_8() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let mmcs = MyMmcs8::new(MyHash8::new(perm.clone()), MyCompress8::new(perm));
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 1 << 9, 4),
+            RowMajorMatrix::<F>::rand(&mut rng, 1 << 6, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect::<Vec<_>>();
+        let (commit, data) = mmcs.commit(mats);
+
+        let indices = [0, 1, 7, 8, 63, 64, 511];
+        let proof = mmcs.open_multi(&indices, &data);
+        mmcs.verify_multi_batch(&commit, &dims, &indices, &proof)
+            .unwrap();
+    }
+}
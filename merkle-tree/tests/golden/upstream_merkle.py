@@ -0,0 +1,147 @@
+"""Reference model of the Merkle tree layout of upstream Plonky3 0.1.
+
+Writes `upstream_v0_1.txt`, which `tests/upstream_compat.rs` checks `CompatMode::Upstream` against.
+The trees are those of `MerkleTreeMmcs<Mersenne31, u8, SerializingHasher32<Keccak256Hash>,
+CompressionFunctionFromHasher<Keccak256Hash, 2, 32>, 32>`. Each line is one commitment:
+
+    <height>x<width> ... <root>
+
+listing the committed matrices in order, then the root digest in hex. Entry `(r, c)` of the `m`th
+matrix is `value(m, r, c)` below.
+"""
+
+import os
+
+P = 2**31 - 1  # Mersenne31
+DEFAULT_DIGEST = bytes(32)
+
+RC = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+]
+ROTATIONS = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+]
+MASK = 2**64 - 1
+
+
+def rotl(x, n):
+    return ((x << n) | (x >> (64 - n))) & MASK if n else x
+
+
+def keccak_f(a):
+    for rc in RC:
+        c = [a[x][0] ^ a[x][1] ^ a[x][2] ^ a[x][3] ^ a[x][4] for x in range(5)]
+        d = [c[(x - 1) % 5] ^ rotl(c[(x + 1) % 5], 1) for x in range(5)]
+        a = [[a[x][y] ^ d[x] for y in range(5)] for x in range(5)]
+        b = [[0] * 5 for _ in range(5)]
+        for x in range(5):
+            for y in range(5):
+                b[y][(2 * x + 3 * y) % 5] = rotl(a[x][y], ROTATIONS[x][y])
+        a = [
+            [b[x][y] ^ (~b[(x + 1) % 5][y] & b[(x + 2) % 5][y]) for y in range(5)]
+            for x in range(5)
+        ]
+        a[0][0] ^= rc
+    return a
+
+
+def keccak256(data):
+    rate = 136
+    data = bytearray(data) + b"\x01" + b"\x00" * (rate - 1 - len(data) % rate)
+    data[-1] |= 0x80
+    a = [[0] * 5 for _ in range(5)]
+    for offset in range(0, len(data), rate):
+        block = data[offset : offset + rate]
+        for i in range(rate // 8):
+            a[i % 5][i // 5] ^= int.from_bytes(block[8 * i : 8 * i + 8], "little")
+        a = keccak_f(a)
+    return b"".join(a[i % 5][i // 5].to_bytes(8, "little") for i in range(4))
+
+
+def value(m, r, c):
+    return (1 + m * 1_000_003 + r * 7919 + c * 104_729) % P
+
+
+def rows_digest(matrices, r):
+    """The hash of the concatenated rows `r` of `matrices`, as little-endian canonical values."""
+    return keccak256(
+        b"".join(value(m, r, c).to_bytes(4, "little") for m, (_, width) in matrices for c in range(width))
+    )
+
+
+def compress(left, right):
+    return keccak256(left + right)
+
+
+def pad(layer):
+    """Pad a layer with default digests to an even length, unless it is the root."""
+    return layer + [DEFAULT_DIGEST] * (len(layer) % 2 if len(layer) > 1 else 0)
+
+
+def root(shapes):
+    # Matrices are taken tallest first; ties keep the order they were given in.
+    matrices = sorted(enumerate(shapes), key=lambda m: -m[1][0])
+    max_height = matrices[0][1][0]
+    tallest = [m for m in matrices if m[1][0] == max_height]
+    rest = [m for m in matrices if m[1][0] != max_height]
+
+    layer = pad([rows_digest(tallest, r) for r in range(max_height)])
+    while len(layer) > 1:
+        next_len = len(layer) // 2
+        # The matrices of the next height, if it rounds up to this layer's size.
+        layer_size = 1 << (next_len - 1).bit_length()
+        injected = []
+        if rest and 1 << (rest[0][1][0] - 1).bit_length() == layer_size:
+            height = rest[0][1][0]
+            injected = [m for m in rest if m[1][0] == height]
+            rest = [m for m in rest if m[1][0] != height]
+        next_layer = []
+        for i in range(next_len):
+            digest = compress(layer[2 * i], layer[2 * i + 1])
+            if injected:
+                rows = rows_digest(injected, i) if i < injected[0][1][0] else DEFAULT_DIGEST
+                digest = compress(digest, rows)
+            next_layer.append(digest)
+        layer = pad(next_layer)
+    assert not rest, "heights that round up to the same power of two must be equal"
+    return layer[0]
+
+
+CASES = [
+    [(1, 1)],
+    [(1, 8)],
+    [(8, 1)],
+    [(2, 3)],
+    [(5, 2)],
+    [(8, 2), (4, 3), (2, 1)],
+    [(2, 1), (4, 3), (8, 2)],
+    [(8, 2), (8, 1), (3, 2), (1, 4)],
+    [(4, 1), (4, 2)],
+    [(4, 2), (4, 1)],
+    [(7, 1), (4, 2)],
+    [(16, 2), (5, 3), (2, 2)],
+    [(33, 3), (9, 1), (9, 2), (1, 1)],
+]
+
+
+def main():
+    lines = [
+        " ".join(f"{height}x{width}" for height, width in shapes) + " " + root(shapes).hex()
+        for shapes in CASES
+    ]
+    path = os.path.join(os.path.dirname(__file__), "upstream_v0_1.txt")
+    with open(path, "w") as f:
+        f.write("\n".join(lines) + "\n")
+
+
+if __name__ == "__main__":
+    main()
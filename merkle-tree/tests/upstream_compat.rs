@@ -0,0 +1,138 @@
+//! Conformance of `CompatMode::Upstream` with the roots of upstream Plonky3's Merkle trees, as
+//! recorded in `tests/golden`. Supporting another upstream release means adding its
+//! `UpstreamVersion` and fixture to `FIXTURES`, along with the model or recording it came from.
+
+use itertools::Itertools;
+use p3_commit::Mmcs;
+use p3_field::FieldAlgebra;
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::{CompatMode, MerkleTreeError, MerkleTreeMmcs, UpstreamVersion};
+use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+
+type F = Mersenne31;
+type FieldHash = SerializingHasher32<Keccak256Hash>;
+type MyCompress = CompressionFunctionFromHasher<Keccak256Hash, 2, 32>;
+type MyMmcs = MerkleTreeMmcs<F, u8, FieldHash, MyCompress, 32>;
+
+/// The fixtures of each supported upstream release, in the format described by the script which
+/// generated them.
+const FIXTURES: &[(UpstreamVersion, &str)] = &[(
+    UpstreamVersion::V0_1,
+    include_str!("golden/upstream_v0_1.txt"),
+)];
+
+struct Case {
+    /// The height and width of each committed matrix.
+    shapes: Vec<(usize, usize)>,
+    root: [u8; 32],
+}
+
+fn parse_fixture(fixture: &str) -> Vec<Case> {
+    fixture
+        .lines()
+        .map(|line| {
+            let mut fields = line.split_whitespace().collect_vec();
+            let root = fields.pop().unwrap();
+            let shapes = fields
+                .into_iter()
+                .map(|shape| {
+                    let (height, width) = shape.split_once('x').unwrap();
+                    (height.parse().unwrap(), width.parse().unwrap())
+                })
+                .collect();
+            let root = (0..32)
+                .map(|i| u8::from_str_radix(&root[2 * i..2 * i + 2], 16).unwrap())
+                .collect_vec();
+            Case {
+                shapes,
+                root: root.try_into().unwrap(),
+            }
+        })
+        .collect()
+}
+
+/// The matrices of `shapes`, filled as in the fixture scripts.
+fn matrices(shapes: &[(usize, usize)]) -> Vec<RowMajorMatrix<F>> {
+    shapes
+        .iter()
+        .enumerate()
+        .map(|(m, &(height, width))| {
+            let values = (0..height)
+                .flat_map(|r| (0..width).map(move |c| (r, c)))
+                .map(|(r, c)| {
+                    let value = (1 + m * 1_000_003 + r * 7919 + c * 104_729) % ((1 << 31) - 1);
+                    F::from_canonical_u32(value as u32)
+                })
+                .collect();
+            RowMajorMatrix::new(values, width)
+        })
+        .collect()
+}
+
+fn mmcs(compat_mode: CompatMode) -> MyMmcs {
+    MyMmcs::new(
+        FieldHash::new(Keccak256Hash {}),
+        MyCompress::new(Keccak256Hash {}),
+    )
+    .with_compat_mode(compat_mode)
+}
+
+#[test]
+fn roots_match_upstream_fixtures() {
+    for &(version, fixture) in FIXTURES {
+        let upstream = mmcs(CompatMode::Upstream(version));
+        for case in parse_fixture(fixture) {
+            let mats = matrices(&case.shapes);
+            let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+            let (commit, data) = upstream.commit(mats.clone());
+            assert_eq!(
+                <[u8; 32]>::from(commit),
+                case.root,
+                "{version:?}, {:?}",
+                case.shapes
+            );
+
+            let max_height = dims.iter().map(|d| d.height).max().unwrap();
+            for index in 0..max_height {
+                let (opened_values, proof) = upstream.open_batch(index, &data);
+                upstream
+                    .verify_batch(&commit, &dims, index, &opened_values, &proof)
+                    .unwrap();
+            }
+
+            // The native layout agrees wherever upstream can commit.
+            let (native_commit, _) = mmcs(CompatMode::Native).commit(mats);
+            assert_eq!(native_commit, commit, "{version:?}, {:?}", case.shapes);
+        }
+    }
+}
+
+#[test]
+fn upstream_rejects_native_only_layouts() {
+    let upstream = mmcs(CompatMode::Upstream(UpstreamVersion::V0_1));
+    let native = mmcs(CompatMode::Native);
+
+    // Heights which round up to the same power of two, which only the native layout mixes into the
+    // same layer of the tree.
+    let mats = matrices(&[(8, 1), (7, 2)]);
+    let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+    let (commit, data) = native.commit(mats);
+    let (opened_values, proof) = native.open_batch(3, &data);
+    native
+        .verify_batch(&commit, &dims, 3, &opened_values, &proof)
+        .unwrap();
+    assert!(matches!(
+        upstream.verify_batch(&commit, &dims, 3, &opened_values, &proof),
+        Err(MerkleTreeError::IncompatibleLayout)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "can't commit to matrices")]
+fn upstream_refuses_to_commit_native_only_layouts() {
+    let upstream = mmcs(CompatMode::Upstream(UpstreamVersion::V0_1));
+    upstream.commit(matrices(&[(4, 1), (3, 1)]));
+}
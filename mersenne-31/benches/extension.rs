@@ -1,9 +1,12 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use p3_field::extension::{BinomialExtensionField, Complex};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use p3_field::extension::{BinomialExtensionField, BinomiallyExtendable, Complex};
+use p3_field::{FieldAlgebra, FieldExtensionAlgebra};
 use p3_field_testing::bench_func::{
     benchmark_inv, benchmark_mul_latency, benchmark_mul_throughput, benchmark_square,
 };
 use p3_mersenne_31::Mersenne31;
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
 
 type EF2 = BinomialExtensionField<Complex<Mersenne31>, 2>;
 type EF3 = BinomialExtensionField<Complex<Mersenne31>, 3>;
@@ -27,7 +30,64 @@ fn bench_cubic_extension(c: &mut Criterion) {
     benchmark_mul_latency::<EF3, L_REPS>(c, name);
 }
 
+/// The product in `F[X] / (X^2 - W)` with four base multiplications, which the extension's
+/// Karatsuba multiplication replaced.
+fn schoolbook_mul<F: BinomiallyExtendable<2>>(
+    a: BinomialExtensionField<F, 2>,
+    b: BinomialExtensionField<F, 2>,
+) -> BinomialExtensionField<F, 2> {
+    let (a, b) = (a.as_base_slice(), b.as_base_slice());
+    BinomialExtensionField::from_base_slice(&[
+        a[0] * b[0] + F::W * a[1] * b[1],
+        a[0] * b[1] + a[1] * b[0],
+    ])
+}
+
+/// Compare the quadratic extension's multiplication with schoolbook multiplication, over chains of
+/// `L_REPS` dependent products.
+fn bench_quadratic_mul<F: BinomiallyExtendable<2>>(c: &mut Criterion, name: &str)
+where
+    Standard: Distribution<BinomialExtensionField<F, 2>>,
+{
+    type MulFn<EF> = fn(EF, EF) -> EF;
+    let muls: [(&str, MulFn<BinomialExtensionField<F, 2>>); 2] =
+        [("karatsuba", |x, y| x * y), ("schoolbook", schoolbook_mul)];
+
+    let mut group = c.benchmark_group(format!("quadratic_mul/{L_REPS} {name}"));
+    for (mul_name, mul) in muls {
+        group.bench_function(mul_name, |b| {
+            b.iter_batched(
+                || {
+                    let mut rng = rand::thread_rng();
+                    (0..L_REPS)
+                        .map(|_| rng.gen::<BinomialExtensionField<F, 2>>())
+                        .collect::<Vec<_>>()
+                },
+                |x| {
+                    x.into_iter()
+                        .fold(BinomialExtensionField::ONE, |acc, y| mul(black_box(acc), y))
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_karatsuba(c: &mut Criterion) {
+    bench_quadratic_mul::<Mersenne31>(c, "Mersenne31Complex<Mersenne31>");
+    bench_quadratic_mul::<Complex<Mersenne31>>(
+        c,
+        "BinomialExtensionField<Mersenne31Complex<Mersenne31>, 2>",
+    );
+}
+
 criterion_group!(bench_mersennecomplex_ef2, bench_qudratic_extension);
 criterion_group!(bench_mersennecomplex_ef3, bench_cubic_extension);
+criterion_group!(bench_quadratic_karatsuba, bench_karatsuba);
 
-criterion_main!(bench_mersennecomplex_ef2, bench_mersennecomplex_ef3);
+criterion_main!(
+    bench_mersennecomplex_ef2,
+    bench_mersennecomplex_ef3,
+    bench_quadratic_karatsuba
+);
@@ -146,6 +146,21 @@ fn idft_postprocess(input: RowMajorMatrix<C>) -> RowMajorMatrix<F> {
     RowMajorMatrix::new(output, input.width())
 }
 
+/// Given the (h+1)xw matrix returned by `dft_postprocess()`, which holds the evaluations at the
+/// first h+1 of the 2h-th roots of unity, return the 2hxw matrix of the evaluations at all of
+/// them, using that the evaluation at the (2h-k)th root is the conjugate of the one at the kth.
+fn dft_unpack(input: RowMajorMatrix<C>) -> RowMajorMatrix<C> {
+    let h = input.height() - 1;
+    let width = input.width();
+    let conjugates = (1..h)
+        .rev()
+        .flat_map(|j| input.row(j).map(|x| x.conjugate()).collect_vec())
+        .collect_vec();
+    let mut output = input.values;
+    output.extend(conjugates);
+    RowMajorMatrix::new(output, width)
+}
+
 /// The DFT for Mersenne31
 #[derive(Debug, Default, Clone)]
 pub struct Mersenne31Dft;
@@ -169,6 +184,24 @@ impl Mersenne31Dft {
         let dft = Dft::default();
         idft_postprocess(dft.idft_batch(idft_preprocess(mat)))
     }
+
+    /// Compute the low-degree extension of each column of `mat`, which holds evaluations over the
+    /// `n`th roots of unity in the packed form returned by `dft_batch()`, onto the coset
+    /// `shift * K`, where `K` is the group of `(n << added_bits)`th roots of unity.
+    ///
+    /// NB: The evaluations over a coset aren't conjugate symmetric in general, so unlike the
+    /// input, the output has a row for every point of the coset, in their natural order. The
+    /// extension itself is `Dft`'s `TwoAdicSubgroupDft::coset_lde_batch` of the evaluations over
+    /// all `n` roots, which are recovered from the packed ones by conjugating.
+    pub fn coset_lde_batch<Dft: TwoAdicSubgroupDft<C>>(
+        mat: RowMajorMatrix<C>,
+        added_bits: usize,
+        shift: C,
+    ) -> RowMajorMatrix<C> {
+        let dft = Dft::default();
+        dft.coset_lde_batch(dft_unpack(mat), added_bits, shift)
+            .to_row_major_matrix()
+    }
 }
 
 #[cfg(test)]
@@ -182,6 +215,56 @@ mod tests {
     type Base = Mersenne31;
     type Dft = Mersenne31ComplexRadix2Dit;
 
+    /// Evaluate the polynomial with coefficients `coeffs` at `x`, the slow way.
+    fn naive_eval(coeffs: &[Base], x: C) -> C {
+        coeffs
+            .iter()
+            .rev()
+            .fold(C::ZERO, |acc, &coeff| acc * x + C::new_real(coeff))
+    }
+
+    #[test]
+    fn dft_matches_naive_evaluation() {
+        for log_n in 1..7 {
+            let n = 1 << log_n;
+            let coeffs = RowMajorMatrix::<Base>::rand(&mut thread_rng(), n, 3);
+            let evals = Mersenne31Dft::dft_batch::<Dft>(coeffs.clone());
+            // Only the evaluations at the first `n / 2 + 1` roots are returned; the rest are their
+            // conjugates.
+            assert_eq!(evals.height(), n / 2 + 1);
+
+            let omega = C::two_adic_generator(log_n);
+            for (k, point) in omega.powers().take(n / 2 + 1).enumerate() {
+                for col in 0..coeffs.width() {
+                    let col_coeffs = (0..n).map(|r| coeffs.get(r, col)).collect_vec();
+                    assert_eq!(evals.get(k, col), naive_eval(&col_coeffs, point));
+                }
+            }
+
+            assert_eq!(Mersenne31Dft::idft_batch::<Dft>(evals), coeffs);
+        }
+    }
+
+    #[test]
+    fn coset_lde_matches_naive_evaluation() {
+        let shift = C::new(Base::new(3), Base::new(7));
+        for (log_n, added_bits) in [(1, 1), (2, 1), (3, 2), (5, 1), (5, 3)] {
+            let n = 1 << log_n;
+            let coeffs = RowMajorMatrix::<Base>::rand(&mut thread_rng(), n, 2);
+            let evals = Mersenne31Dft::dft_batch::<Dft>(coeffs.clone());
+            let lde = Mersenne31Dft::coset_lde_batch::<Dft>(evals, added_bits, shift);
+            assert_eq!(lde.height(), n << added_bits);
+
+            let omega = C::two_adic_generator(log_n + added_bits);
+            for (k, point) in omega.shifted_powers(shift).take(lde.height()).enumerate() {
+                for col in 0..coeffs.width() {
+                    let col_coeffs = (0..n).map(|r| coeffs.get(r, col)).collect_vec();
+                    assert_eq!(lde.get(k, col), naive_eval(&col_coeffs, point));
+                }
+            }
+        }
+    }
+
     #[test]
     fn consistency()
     where
@@ -25,6 +25,9 @@ impl BinomiallyExtendable<3> for Mersenne31 {
     const EXT_GENERATOR: [Self; 3] = [Self::new(10), Self::new(1), Self::ZERO];
 }
 
+// Mersenne31 itself has no binomial extensions of degree 4 or 5: `x^4 - w` is reducible over any
+// field of order `3 mod 4`, and `x^5 - w` can only be irreducible if 5 divides `p - 1`. Its
+// quartic extension is instead this quadratic extension of `Complex<Mersenne31>`.
 impl HasComplexBinomialExtension<2> for Mersenne31 {
     // Verifiable in Sage with
     // ```sage
@@ -105,10 +108,70 @@ impl HasTwoAdicComplexBinomialExtension<3> for Mersenne31 {
     }
 }
 
+// The quartic extension of `Complex<Mersenne31>`, which as `ComplexBinomialExtensionField<_, 4>` is
+// an extension of degree 8 of Mersenne31. Its square `Y = X^2` satisfies `Y^2 = 2 + i`, so it
+// contains the quadratic extension above.
+impl HasComplexBinomialExtension<4> for Mersenne31 {
+    // Verifiable in Sage with
+    // ```sage
+    // p = 2**31 - 1  # Mersenne31
+    // F = GF(p)  # The base field GF(p)
+    // R.<x> = F[]  # The polynomial ring over F
+    // K.<i> = F.extension(x^2 + 1)  # The complex extension field
+    // R2.<y> = K[]
+    // f4 = y^4 - i - 2
+    // assert f4.is_irreducible()
+    // ```
+    const W: Complex<Self> = Complex::new(Mersenne31::new(2), Mersenne31::ONE);
+
+    // DTH_ROOT = W^((p^2 - 1)/4).
+    const DTH_ROOT: Complex<Self> = Complex::new_imag(Mersenne31::ONE);
+
+    // Verifiable in Sage with
+    // ```sage
+    // K4.<j> = K.extension(f4)
+    //  g = j + 4
+    // for f in factor(p^8 - 1):
+    //   assert g^((p^8-1) // f) != 1
+    // ```
+    const EXT_GENERATOR: [Complex<Self>; 4] = [
+        Complex::new_real(Mersenne31::new(4)),
+        Complex::new_real(Mersenne31::ONE),
+        Complex::ZERO,
+        Complex::ZERO,
+    ];
+}
+
+impl HasTwoAdicComplexBinomialExtension<4> for Mersenne31 {
+    // p^8 - 1 = (p^2 - 1)(p^2 + 1)(p^4 + 1) where the last two factors are each twice an odd number.
+    const COMPLEX_EXT_TWO_ADICITY: usize = 34;
+
+    fn complex_ext_two_adic_generator(bits: usize) -> [Complex<Self>; 4] {
+        assert!(bits <= 34);
+        match bits {
+            // A square root of the generator below.
+            34 => [
+                Complex::ZERO,
+                Complex::new(Mersenne31::new(1521286925), Mersenne31::new(491753981)),
+                Complex::ZERO,
+                Complex::ZERO,
+            ],
+            // The generator of the quadratic extension, in which `j = X^2`.
+            33 => [
+                Complex::ZERO,
+                Complex::ZERO,
+                Complex::new(Mersenne31::new(1437746044), Mersenne31::new(946469285)),
+                Complex::ZERO,
+            ],
+            _ => field_to_array::<Complex<Self>, 4>(Complex::two_adic_generator(bits)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_cubic_extension {
     use p3_field::extension::{BinomialExtensionField, Complex};
-    use p3_field_testing::{test_field, test_two_adic_extension_field};
+    use p3_field_testing::{test_field, test_field_dft, test_two_adic_extension_field};
 
     use crate::Mersenne31;
 
@@ -118,13 +181,20 @@ mod test_cubic_extension {
     test_field!(super::EF);
 
     test_two_adic_extension_field!(super::F, super::EF);
+
+    // Mersenne31 itself only has two-adicity 1, so these DFTs all need the extension's generators.
+    test_field_dft!(radix2dit, super::EF, p3_dft::Radix2Dit<_>);
 }
 
 #[cfg(test)]
 mod test_quadratic_extension {
 
-    use p3_field::extension::{BinomialExtensionField, Complex};
-    use p3_field_testing::{test_field, test_two_adic_extension_field};
+    use p3_field::extension::{
+        BinomialExtensionField, BinomiallyExtendable, Complex, HasFrobenius,
+    };
+    use p3_field::{ExtensionField, Field, FieldAlgebra, FieldExtensionAlgebra};
+    use p3_field_testing::{test_field, test_field_dft, test_two_adic_extension_field};
+    use rand::random;
 
     use crate::Mersenne31;
 
@@ -134,4 +204,134 @@ mod test_quadratic_extension {
     test_field!(super::EF);
 
     test_two_adic_extension_field!(super::F, super::EF);
+
+    test_field_dft!(radix2dit, super::EF, p3_dft::Radix2Dit<_>);
+
+    fn ef(coeffs: [F; 2]) -> EF {
+        <EF as FieldExtensionAlgebra<F>>::from_base_slice(&coeffs)
+    }
+
+    #[test]
+    fn mul_matches_schoolbook() {
+        for _ in 0..100 {
+            let (a, b): ([F; 2], [F; 2]) = (random(), random());
+            let expected = ef([
+                a[0] * b[0] + <F as BinomiallyExtendable<2>>::W * a[1] * b[1],
+                a[0] * b[1] + a[1] * b[0],
+            ]);
+            assert_eq!(ef(a) * ef(b), expected);
+        }
+    }
+
+    #[test]
+    fn frobenius_has_order_2_and_fixes_the_complex_field() {
+        for _ in 0..100 {
+            let x: EF = random();
+            assert_ne!(x.frobenius(), x);
+            assert_eq!(x.frobenius().frobenius(), x);
+            assert_eq!(x.frobenius_inv(), x.inverse());
+            assert!(ExtensionField::<F>::is_in_basefield(&(x * x.frobenius())));
+
+            let c = ef([random(), F::ZERO]);
+            assert_eq!(c.frobenius(), c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_octic_extension {
+    use alloc::format;
+    use alloc::vec::Vec;
+
+    use num_bigint::BigUint;
+    use p3_field::extension::{
+        BinomialExtensionField, Complex, ComplexBinomialExtensionField, HasFrobenius,
+    };
+    use p3_field::{
+        eval_poly, ExtensionField, Field, FieldAlgebra, FieldExtensionAlgebra, PrimeField64,
+    };
+    use p3_field_testing::{test_ef_order_bits, test_field, test_field_dft, test_two_adic_field};
+    use rand::random;
+
+    use crate::Mersenne31;
+
+    type F = Mersenne31;
+    type EF = ComplexBinomialExtensionField<F, 4>;
+    type Inner = BinomialExtensionField<Complex<F>, 4>;
+
+    test_field!(super::EF);
+
+    // Mersenne31 isn't a two-adic field, so only the extension's own generators can be tested.
+    test_two_adic_field!(super::EF);
+
+    test_field_dft!(radix2dit, super::EF, p3_dft::Radix2Dit<_>);
+
+    #[test]
+    fn order_bits() {
+        test_ef_order_bits::<F, EF>();
+        assert_eq!(EF::order(), BigUint::from(F::ORDER_U64).pow(8));
+    }
+
+    /// The coefficients over Mersenne31 are those over `Complex<Mersenne31>`, split into their real
+    /// and imaginary parts.
+    #[test]
+    fn base_coefficients_split_complex_ones() {
+        let x: EF = random();
+        let inner = x.into_complex_extension();
+        let complex_coeffs = <Inner as FieldExtensionAlgebra<Complex<F>>>::as_base_slice(&inner);
+        let base_coeffs = <EF as FieldExtensionAlgebra<F>>::as_base_slice(&x);
+        assert_eq!(base_coeffs.len(), 8);
+        assert_eq!(format!("{x:?}"), format!("{base_coeffs:?}"));
+        for (i, c) in complex_coeffs.iter().enumerate() {
+            assert_eq!(
+                [base_coeffs[2 * i], base_coeffs[2 * i + 1]],
+                [c.real(), c.imag()]
+            );
+        }
+        assert_eq!(
+            <EF as FieldExtensionAlgebra<F>>::from_base_slice(base_coeffs),
+            x
+        );
+        assert_eq!(
+            <EF as FieldExtensionAlgebra<F>>::from_base_iter(base_coeffs.iter().copied()),
+            x
+        );
+        assert!(ExtensionField::<F>::is_in_basefield(&EF::from_base(
+            base_coeffs[0]
+        )));
+    }
+
+    /// The Karatsuba multiplication agrees with the schoolbook one over `Complex<Mersenne31>`.
+    #[test]
+    fn mul_matches_complex_extension() {
+        for _ in 0..100 {
+            let (a, b): (Inner, Inner) = (random(), random());
+            let product = EF::from_complex_extension(a) * EF::from_complex_extension(b);
+            assert_eq!(product.into_complex_extension(), a * b);
+
+            let c: F = random();
+            assert_eq!(
+                (EF::from_complex_extension(a) * c).into_complex_extension(),
+                a * Complex::new_real(c)
+            );
+        }
+    }
+
+    #[test]
+    fn frobenius_is_exponentiation_by_p() {
+        for _ in 0..10 {
+            let x: EF = random();
+            assert_eq!(x.frobenius(), x.exp_u64(F::ORDER_U64));
+            assert_eq!(
+                x.repeated_frobenius(3),
+                x.exp_u64(F::ORDER_U64).frobenius().frobenius()
+            );
+            assert_eq!(x.repeated_frobenius(8), x);
+            assert_eq!(x.frobenius_inv() * x, EF::ONE);
+
+            let m: Vec<EF> = x.minimal_poly().into_iter().map(EF::from_base).collect();
+            assert_eq!(m.len(), 9);
+            assert!(eval_poly(&m, x).is_zero());
+        }
+    }
 }
@@ -12,6 +12,7 @@ use p3_field::{
     exp_1717986917, exp_u64_by_squaring, halve_u32, Field, FieldAlgebra, Packable, PrimeField,
     PrimeField32, PrimeField64,
 };
+use p3_util::encoding::{CanonicalEncoding, DecodingError};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 use serde::de::Error;
@@ -34,6 +35,40 @@ impl Mersenne31 {
         debug_assert!((value >> 31) == 0);
         Self { value }
     }
+
+    /// Convert a constant u32 array into a constant array of field elements.
+    /// Constant version of array.map(Mersenne31::new).
+    #[inline]
+    pub const fn new_array<const N: usize>(input: [u32; N]) -> [Self; N] {
+        let mut output = [Self::new(0); N];
+        let mut i = 0;
+        loop {
+            if i == N {
+                break;
+            }
+            output[i] = Self::new(input[i]);
+            i += 1;
+        }
+        output
+    }
+
+    /// Convert a constant 2d u32 array into a constant 2d array of field elements.
+    /// Constant version of array.map(Mersenne31::new_array).
+    #[inline]
+    pub const fn new_2d_array<const N: usize, const M: usize>(
+        input: [[u32; N]; M],
+    ) -> [[Self; N]; M] {
+        let mut output = [[Self::new(0); N]; M];
+        let mut i = 0;
+        loop {
+            if i == M {
+                break;
+            }
+            output[i] = Self::new_array(input[i]);
+            i += 1;
+        }
+        output
+    }
 }
 
 impl PartialEq for Mersenne31 {
@@ -69,13 +104,13 @@ impl PartialOrd for Mersenne31 {
 
 impl Display for Mersenne31 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        Display::fmt(&self.value, f)
+        Display::fmt(&self.as_canonical_u32(), f)
     }
 }
 
 impl Debug for Mersenne31 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        Debug::fmt(&self.value, f)
+        Debug::fmt(&self.as_canonical_u32(), f)
     }
 }
 
@@ -93,16 +128,15 @@ impl Distribution<Mersenne31> for Standard {
 
 impl Serialize for Mersenne31 {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        // No need to convert to canonical.
-        serializer.serialize_u32(self.value)
+        serializer.serialize_u32(self.as_canonical_u32())
     }
 }
 
 impl<'a> Deserialize<'a> for Mersenne31 {
     fn deserialize<D: Deserializer<'a>>(d: D) -> Result<Self, D::Error> {
         let val = u32::deserialize(d)?;
-        // Ensure that `val` satisfies our invariant. i.e. Not necessarily canonical, but must fit in 31 bits.
-        if val <= P {
+        // Only accept canonical values, so that every element has a unique encoding.
+        if val < P {
             Ok(Mersenne31::new(val))
         } else {
             Err(D::Error::custom("Value is out of range"))
@@ -110,6 +144,21 @@ impl<'a> Deserialize<'a> for Mersenne31 {
     }
 }
 
+impl CanonicalEncoding for Mersenne31 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_canonical_u32().encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        let val = u32::decode(input)?;
+        if val < P {
+            Ok(Mersenne31::new(val))
+        } else {
+            Err(DecodingError::NonCanonical)
+        }
+    }
+}
+
 impl FieldAlgebra for Mersenne31 {
     type F = Self;
 
@@ -230,6 +279,8 @@ impl Field for Mersenne31 {
     // Sage: GF(2^31 - 1).multiplicative_generator()
     const GENERATOR: Self = Self::new(7);
 
+    const BITS: usize = (u32::BITS - P.leading_zeros()) as usize;
+
     #[inline]
     fn is_zero(&self) -> bool {
         self.value == 0 || self.value == Self::ORDER_U32
@@ -453,12 +504,23 @@ pub const fn to_mersenne31_array<const N: usize>(input: [u32; N]) -> [Mersenne31
 #[cfg(test)]
 mod tests {
     use p3_field::{Field, FieldAlgebra, PrimeField32};
-    use p3_field_testing::test_field;
+    use p3_field_testing::{test_field, test_prime_field_64};
 
     use crate::Mersenne31;
 
     type F = Mersenne31;
 
+    #[test]
+    fn serde() {
+        // Elements are serialized in canonical form, and out of range values are rejected.
+        let zero = F::new(F::ORDER_U32);
+        assert_eq!(serde_json::to_string(&zero).unwrap(), "0");
+        let f = F::new(12345);
+        let serialized = serde_json::to_string(&f).unwrap();
+        assert_eq!(serde_json::from_str::<F>(&serialized).unwrap(), f);
+        assert!(serde_json::from_str::<F>(&F::ORDER_U32.to_string()).is_err());
+    }
+
     #[test]
     fn add() {
         assert_eq!(F::ONE + F::ONE, F::TWO);
@@ -509,4 +571,5 @@ mod tests {
     }
 
     test_field!(crate::Mersenne31);
+    test_prime_field_64!(crate::Mersenne31);
 }
@@ -1,5 +1,7 @@
 mod packing;
 mod poseidon2;
+mod utils;
 
 pub use packing::*;
 pub use poseidon2::*;
+pub use utils::*;
@@ -11,7 +11,8 @@ use p3_util::convert_vec;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 
-use crate::{FieldParameters, MontyField31, PackedMontyParameters};
+use crate::monty_31::sum_packed;
+use crate::{Canonicalize, FieldParameters, MontyField31, PackedMontyParameters};
 
 const WIDTH: usize = 4;
 
@@ -29,7 +30,7 @@ impl<PMP: PackedMontyParameters> PackedMontyField31Neon<PMP> {
     #[inline]
     #[must_use]
     /// Get an arch-specific vector representing the packed values.
-    fn to_vector(self) -> uint32x4_t {
+    pub(crate) fn to_vector(self) -> uint32x4_t {
         unsafe {
             // Safety: `MontyField31` is `repr(transparent)` so it can be transmuted to `u32`. It
             // follows that `[MontyField31; WIDTH]` can be transmuted to `[u32; WIDTH]`, which can be
@@ -46,7 +47,7 @@ impl<PMP: PackedMontyParameters> PackedMontyField31Neon<PMP> {
     ///
     /// SAFETY: The caller must ensure that each element of `vector` represents a valid `MontyField31`.
     /// In particular, each element of vector must be in `0..P` (canonical form).
-    unsafe fn from_vector(vector: uint32x4_t) -> Self {
+    pub(crate) unsafe fn from_vector(vector: uint32x4_t) -> Self {
         // Safety: It is up to the user to ensure that elements of `vector` represent valid
         // `MontyField31` values. We must only reason about memory representations. `uint32x4_t` can be
         // transmuted to `[u32; WIDTH]` (since arrays elements are contiguous in memory), which can
@@ -65,6 +66,20 @@ impl<PMP: PackedMontyParameters> PackedMontyField31Neon<PMP> {
     }
 }
 
+impl<PMP: PackedMontyParameters> Canonicalize for PackedMontyField31Neon<PMP> {
+    #[inline]
+    fn canonicalize(self) -> Self {
+        // This is the final step of `add`: for t in 0, ..., 2P - 1, unsigned_min(t, t - P) is t if
+        // t < P and t - P otherwise.
+        let t = self.to_vector();
+        unsafe {
+            // Safety: If this code got compiled then NEON intrinsics are available.
+            let u = aarch64::vsubq_u32(t, PMP::PACKED_P);
+            Self::from_vector(aarch64::vminq_u32(t, u))
+        }
+    }
+}
+
 impl<PMP: PackedMontyParameters> Add for PackedMontyField31Neon<PMP> {
     type Output = Self;
     #[inline]
@@ -504,6 +519,11 @@ impl<FP: FieldParameters> FieldAlgebra for PackedMontyField31Neon<FP> {
         // SAFETY: this is a repr(transparent) wrapper around an array.
         unsafe { convert_vec(Self::F::zero_vec(len * WIDTH)) }
     }
+
+    #[inline]
+    fn sum_slice(xs: &[Self]) -> Self {
+        sum_packed(xs)
+    }
 }
 
 impl<PMP: PackedMontyParameters> Add<MontyField31<PMP>> for PackedMontyField31Neon<PMP> {
@@ -690,6 +710,20 @@ unsafe impl<FP: FieldParameters> PackedValue for PackedMontyField31Neon<FP> {
 
 unsafe impl<FP: FieldParameters> PackedField for PackedMontyField31Neon<FP> {
     type Scalar = MontyField31<FP>;
+
+    #[inline]
+    fn packed_sum(self) -> Self::Scalar {
+        // Fold the two halves of each block onto each other, halving the block length each time,
+        // until every lane holds the full sum.
+        let mut acc = self;
+        let mut block_len = WIDTH / 2;
+        while block_len > 0 {
+            let (lo, hi) = acc.interleave(acc, block_len);
+            acc = lo + hi;
+            block_len /= 2;
+        }
+        acc.0[0]
+    }
 }
 
 unsafe impl<FP: FieldParameters> PackedFieldPow2 for PackedMontyField31Neon<FP> {
@@ -1,7 +1,8 @@
-//! Eventually this will hold a Vectorized Neon implementation of Poseidon2 for MontyField31
-//! Currently this is essentially a placeholder to allow compilation on Neon devices.
+//! Neon implementation of Poseidon2 for MontyField31.
 //!
-//! Converting the AVX2/AVX512 code across to Neon is on the TODO list.
+//! The internal linear layer can be specialised per field and width through
+//! [`InternalLayerParametersNeon`]; the remaining layers use the generic code. Converting the rest
+//! of the AVX2/AVX512 code across to Neon is on the TODO list.
 
 use alloc::vec::Vec;
 use core::marker::PhantomData;
@@ -13,13 +14,29 @@ use p3_poseidon2::{
 };
 
 use crate::{
-    FieldParameters, InternalLayerBaseParameters, MontyField31, MontyParameters,
-    PackedMontyField31Neon,
+    canonicalize_state, FieldParameters, InternalLayerBaseParameters, MontyField31,
+    MontyParameters, PackedMontyField31Neon,
 };
 
+/// A specialised Neon version of the internal linear layer.
+///
+/// By default this falls back to the generic internal linear layer. Implementations for which the
+/// diagonal consists of small powers of two and their negatives can override it, trading the
+/// Monty multiplications for shifts.
+pub trait InternalLayerParametersNeon<FP: FieldParameters, const WIDTH: usize>:
+    InternalLayerBaseParameters<FP, WIDTH>
+{
+    /// Perform the internal matrix multiplication, `x[i] -> D[i]*x[i] + sum`, on the whole state.
+    #[inline(always)]
+    fn internal_layer_mat_mul_neon(state: &mut [PackedMontyField31Neon<FP>; WIDTH]) {
+        Self::generic_internal_linear_layer(state);
+    }
+}
+
 /// The internal layers of the Poseidon2 permutation for Monty31 fields.
 ///
-/// This is currently not optimized for the Neon architecture but this is on the TODO list.
+/// Only the linear layer is optimized for the Neon architecture, see
+/// [`InternalLayerParametersNeon`].
 #[derive(Debug, Clone)]
 pub struct Poseidon2InternalLayerMonty31<
     MP: MontyParameters,
@@ -65,13 +82,13 @@ impl<FP, ILP, const WIDTH: usize, const D: u64> InternalLayer<PackedMontyField31
     for Poseidon2InternalLayerMonty31<FP, WIDTH, ILP>
 where
     FP: FieldParameters,
-    ILP: InternalLayerBaseParameters<FP, WIDTH>,
+    ILP: InternalLayerParametersNeon<FP, WIDTH>,
 {
     /// Perform the internal layers of the Poseidon2 permutation on the given state.
     fn permute_state(&self, state: &mut [PackedMontyField31Neon<FP>; WIDTH]) {
         self.internal_constants.iter().for_each(|&rc| {
             add_rc_and_sbox_generic::<_, D>(&mut state[0], rc);
-            ILP::generic_internal_linear_layer(state);
+            ILP::internal_layer_mat_mul_neon(state);
         })
     }
 }
@@ -83,6 +100,8 @@ where
 {
     /// Perform the initial external layers of the Poseidon2 permutation on the given state.
     fn permute_state_initial(&self, state: &mut [PackedMontyField31Neon<FP>; WIDTH]) {
+        // A non-canonical input is reduced before it meets any round.
+        canonicalize_state(state);
         external_initial_permute_state(
             state,
             self.external_constants.get_initial_constants(),
@@ -99,5 +118,7 @@ where
             add_rc_and_sbox_generic::<_, D>,
             &MDSMat4,
         );
+        // Nothing non-canonical leaves the permutation, see `CanonicalPermutation`.
+        canonicalize_state(state);
     }
 }
@@ -0,0 +1,89 @@
+use core::arch::aarch64;
+
+use crate::{PackedMontyField31Neon, PackedMontyParameters, TwoAdicData};
+
+/// Check, in debug builds, that every element of `input` is in canonical form.
+#[inline(always)]
+fn debug_assert_canonical<PMP: PackedMontyParameters>(input: PackedMontyField31Neon<PMP>) {
+    debug_assert!(
+        input.0.iter().all(|x| x.value < PMP::PRIME),
+        "input is not in canonical form"
+    );
+}
+
+/// Halve a vector of Monty31 field elements.
+#[inline(always)]
+#[must_use]
+pub fn halve_neon<PMP: PackedMontyParameters>(
+    input: PackedMontyField31Neon<PMP>,
+) -> PackedMontyField31Neon<PMP> {
+    /*
+        We want this to compile to:
+            and   least_bit.4s, val.4s, ONE.4s
+            ushr  t.4s, val.4s, #1
+            mla   t.4s, least_bit.4s, half.4s
+
+        Given an element val in [0, P), we want to compute val/2 mod P.
+        If val is even: val/2 mod P = val/2 = val >> 1.
+        If val is odd: val/2 mod P = (val + P)/2 = (val >> 1) + (P + 1)/2
+        In the latter case the result is at most (P - 3)/2 + (P + 1)/2 = P - 1, so it is canonical.
+    */
+    debug_assert_canonical(input);
+
+    unsafe {
+        // Safety: If this code got compiled then NEON intrinsics are available.
+        let val = input.to_vector();
+        let half = aarch64::vdupq_n_u32((PMP::PRIME + 1) / 2); // Compiler realises this is constant.
+
+        let least_bit = aarch64::vandq_u32(val, aarch64::vdupq_n_u32(1));
+        let t = aarch64::vshrq_n_u32::<1>(val);
+        PackedMontyField31Neon::from_vector(aarch64::vmlaq_u32(t, least_bit, half))
+    }
+}
+
+/// Multiply a vector of Monty31 field elements by `-2^{-N}`.
+///
+/// Write the prime as `P = r 2^j + 1` for odd `r`, so that `-2^{-N} = r 2^{j - N} mod P`. Splitting
+/// `x = x_lo + 2^N x_hi` with `x_lo < 2^N` gives `-2^{-N} x = r 2^{j - N} x_lo - x_hi`, which lies in
+/// `(-P, P)` and is brought into canonical form by adding `P` to the negative values. This works
+/// for any `0 < N <= j`, as `r 2^{j - N} x_lo < r 2^j < P`.
+///
+/// `N_PRIME` must be `j - N`.
+#[inline(always)]
+#[must_use]
+pub fn mul_neg_2exp_neg_n_neon<
+    TAD: TwoAdicData + PackedMontyParameters,
+    const N: i32,
+    const N_PRIME: i32,
+>(
+    input: PackedMontyField31Neon<TAD>,
+) -> PackedMontyField31Neon<TAD> {
+    /*
+        We want this to compile to:
+            ushr  hi.4s, val.4s, #N
+            and   lo.4s, val.4s, MASK.4s
+            mul   lo_x_r.4s, lo.4s, r.4s
+            shl   lo_shft.4s, lo_x_r.4s, #N_PRIME
+            sub   t.4s, lo_shft.4s, hi.4s
+            add   u.4s, t.4s, P.4s
+            umin  res.4s, t.4s, u.4s
+    */
+    assert_eq!(N + N_PRIME, TAD::TWO_ADICITY as i32); // Compiler removes this provided it is satisfied.
+    debug_assert_canonical(input);
+    unsafe {
+        // Safety: If this code got compiled then NEON intrinsics are available.
+        let val = input.to_vector();
+        let mask = aarch64::vdupq_n_u32((1 << N) - 1); // Compiler realises this is constant.
+
+        let hi = aarch64::vshrq_n_u32::<N>(val);
+        let lo = aarch64::vandq_u32(val, mask);
+        let lo_x_r = aarch64::vmulq_n_u32(lo, TAD::ODD_FACTOR as u32);
+        let lo_shft = aarch64::vshlq_n_u32::<N_PRIME>(lo_x_r);
+
+        // t is in (-P, P). If it is negative then, as a u32, it is at least 2^32 - P > P, while
+        // u = t + P is in [0, P), so the minimum is the canonical value in either case.
+        let t = aarch64::vsubq_u32(lo_shft, hi);
+        let u = aarch64::vaddq_u32(t, TAD::PACKED_P);
+        PackedMontyField31Neon::from_vector(aarch64::vminq_u32(t, u))
+    }
+}
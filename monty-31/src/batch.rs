@@ -0,0 +1,95 @@
+//! Converting many MontyField31 values into and out of MONTY form at once.
+//!
+//! Both directions are a MONTY multiplication by a constant: multiplying the MONTY form `xR` by the
+//! element whose MONTY form is `1` gives `x`, and multiplying `x`, read as a MONTY form, by the
+//! element whose MONTY form is `R^2` gives `xR`. This lets us use the packed multiplication of the
+//! current architecture, and work in place, reinterpreting the buffer as `u32`s since
+//! `MontyField31` is `repr(transparent)`.
+
+use alloc::vec::Vec;
+use core::mem::ManuallyDrop;
+
+use p3_field::{Field, PackedValue};
+use p3_maybe_rayon::prelude::*;
+
+use crate::utils::to_monty;
+use crate::{FieldParameters, MontyField31};
+
+/// The number of elements converted by each parallel task.
+const CHUNK_SIZE: usize = 1 << 12;
+
+/// The canonical values of `vals`, reusing its allocation.
+pub fn into_canonical_u32_vec<FP: FieldParameters>(vals: Vec<MontyField31<FP>>) -> Vec<u32> {
+    let mut vals = ManuallyDrop::new(vals);
+    to_canonical_slice(&mut vals);
+    // SAFETY: `MontyField31` is `repr(transparent)` over `u32`, and the original vector is never
+    // dropped.
+    unsafe { Vec::from_raw_parts(vals.as_mut_ptr().cast(), vals.len(), vals.capacity()) }
+}
+
+/// The field elements with the canonical values `vals`, reusing its allocation.
+///
+/// # Panics
+/// If any value is at least `P`.
+pub fn from_canonical_u32_vec<FP: FieldParameters>(vals: Vec<u32>) -> Vec<MontyField31<FP>> {
+    let mut vals = ManuallyDrop::new(vals);
+    from_canonical_slice::<FP>(&mut vals);
+    // SAFETY: `MontyField31` is `repr(transparent)` over `u32`, every value is now a MONTY form
+    // below `P`, and the original vector is never dropped.
+    unsafe { Vec::from_raw_parts(vals.as_mut_ptr().cast(), vals.len(), vals.capacity()) }
+}
+
+/// Replace each element of `vals` by its canonical value, returning the same buffer as `u32`s.
+pub fn to_canonical_slice<FP: FieldParameters>(vals: &mut [MontyField31<FP>]) -> &mut [u32] {
+    mul_in_place(vals, MontyField31::new_monty(1));
+    // SAFETY: `MontyField31` is `repr(transparent)` over `u32`.
+    unsafe { &mut *(vals as *mut [MontyField31<FP>] as *mut [u32]) }
+}
+
+/// Replace each canonical value in `vals` by its MONTY form, returning the same buffer as field
+/// elements.
+///
+/// # Panics
+/// If any value is at least `P`.
+pub fn from_canonical_slice<FP: FieldParameters>(vals: &mut [u32]) -> &mut [MontyField31<FP>] {
+    assert!(
+        vals.par_iter().all(|&x| x < FP::PRIME),
+        "Values must be canonical"
+    );
+    // SAFETY: every value was just checked to be below `P`.
+    unsafe { from_canonical_slice_unchecked(vals) }
+}
+
+/// [`from_canonical_slice`], without checking that the values are below `P`.
+///
+/// The buffer is converted in place and borrowed by the result, so nothing is copied; only the
+/// range check is skipped, for values which are already known to be canonical, e.g. because they
+/// were written by [`to_canonical_slice`].
+///
+/// # Safety
+/// Every value must be less than `P`. The packed multiplications give undefined results
+/// otherwise, which would break the invariant that each element has a unique representation.
+pub unsafe fn from_canonical_slice_unchecked<FP: FieldParameters>(
+    vals: &mut [u32],
+) -> &mut [MontyField31<FP>] {
+    // SAFETY: `MontyField31` is `repr(transparent)` over `u32`, and the values are below `P`.
+    let vals = unsafe { &mut *(vals as *mut [u32] as *mut [MontyField31<FP>]) };
+    let r_squared = to_monty::<FP>(to_monty::<FP>(1));
+    mul_in_place(vals, MontyField31::new_monty(r_squared));
+    vals
+}
+
+/// Multiply every element of `vals` by `c`, a packed vector at a time.
+fn mul_in_place<FP: FieldParameters>(vals: &mut [MontyField31<FP>], c: MontyField31<FP>) {
+    let packed_c = <MontyField31<FP> as Field>::Packing::from(c);
+    vals.par_chunks_mut(CHUNK_SIZE).for_each(|chunk| {
+        let (packed, suffix) =
+            <MontyField31<FP> as Field>::Packing::pack_slice_with_suffix_mut(chunk);
+        for x in packed {
+            *x *= packed_c;
+        }
+        for x in suffix {
+            *x *= c;
+        }
+    });
+}
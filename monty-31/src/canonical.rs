@@ -0,0 +1,153 @@
+//! Types that put MontyField31 values back into canonical form, and a wrapper recording that this
+//! has been done.
+//!
+//! Some of the vectorized Poseidon2 layers work with values in `[0, P]` rather than `[0, P)`, as
+//! this saves a reduction in each round. The extra representative of zero must not escape: the
+//! rest of the code, e.g. `PartialEq` and the hashers, assumes that each element has exactly one
+//! representation. `Canonical` marks the values which are known to be safe to hand out.
+//!
+//! The Poseidon2 permutations of the MONTY-31 fields, scalar and packed on every architecture,
+//! reduce their state where it enters the first external layer and where it leaves the last one.
+//! So every hasher and compression function built on them, e.g. those of `MerkleTreeMmcs`, hashes
+//! a non-canonical element as its canonical form and hands out canonical digests.
+//! `CanonicalPermutation` and `CanonicalHasher` record this in the types they return.
+
+use p3_poseidon2::Poseidon2;
+use p3_symmetric::{CryptographicHasher, CryptographicPermutation, PaddingFreeSponge, Permutation};
+
+use crate::{MontyField31, MontyParameters, PackedMontyParameters, Poseidon2ExternalLayerMonty31};
+
+/// A value built from MontyField31 elements which can be reduced into canonical form.
+pub trait Canonicalize: Copy {
+    /// Reduce every element into `[0, P)`, given elements in `[0, 2P)`.
+    ///
+    /// For packed values this is a conditional subtraction across all lanes.
+    #[must_use]
+    fn canonicalize(self) -> Self;
+}
+
+impl<MP: MontyParameters> Canonicalize for MontyField31<MP> {
+    #[inline]
+    fn canonicalize(self) -> Self {
+        if self.value >= MP::PRIME {
+            Self::new_monty(self.value - MP::PRIME)
+        } else {
+            self
+        }
+    }
+}
+
+impl<T: Canonicalize, const N: usize> Canonicalize for [T; N] {
+    #[inline]
+    fn canonicalize(self) -> Self {
+        self.map(T::canonicalize)
+    }
+}
+
+/// A value whose elements are all known to be in canonical form.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Canonical<T>(T);
+
+impl<T: Canonicalize> Canonical<T> {
+    /// Reduce `value` into canonical form.
+    #[inline]
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self(value.canonicalize())
+    }
+
+    /// Wrap `value` without reducing it.
+    ///
+    /// # Safety
+    /// Every element of `value` must be in `[0, P)`.
+    #[inline]
+    #[must_use]
+    pub const unsafe fn new_unchecked(value: T) -> Self {
+        Self(value)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Canonicalize, const N: usize> Canonical<[T; N]> {
+    /// Split a canonical state into its canonical elements.
+    #[inline]
+    #[must_use]
+    pub fn into_array(self) -> [Canonical<T>; N] {
+        self.0.map(Canonical)
+    }
+}
+
+impl<T> AsRef<T> for Canonical<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Reduce a permutation's state into canonical form, where it enters or leaves the permutation.
+#[inline(always)]
+pub(crate) fn canonicalize_state<T: Canonicalize, const WIDTH: usize>(state: &mut [T; WIDTH]) {
+    *state = Canonical::new(*state).into_inner();
+}
+
+/// A permutation which only hands out canonical elements.
+///
+/// This is the boundary at which values leave a packed permutation: it accepts elements in
+/// `[0, 2P)`, and whatever representation its layers use internally, its outputs are canonical.
+///
+/// # Safety
+/// Every element `permute` returns must be in `[0, P)`, for any input in `[0, 2P)`.
+pub unsafe trait CanonicalPermutation<T: Canonicalize>: Permutation<T> {
+    #[inline]
+    fn permute_canonical(&self, input: T) -> Canonical<T> {
+        // Safety: The implementor guarantees that the output is canonical.
+        unsafe { Canonical::new_unchecked(self.permute(input)) }
+    }
+}
+
+// Safety: The MONTY-31 external layers, scalar and packed, reduce the state after the last round.
+unsafe impl<F, MP, InternalPerm, T, const WIDTH: usize, const D: u64>
+    CanonicalPermutation<[T; WIDTH]>
+    for Poseidon2<F, Poseidon2ExternalLayerMonty31<MP, WIDTH>, InternalPerm, WIDTH, D>
+where
+    MP: PackedMontyParameters,
+    T: Canonicalize,
+    Self: Permutation<[T; WIDTH]>,
+{
+}
+
+/// A hasher which takes canonical elements and hands out canonical digests.
+///
+/// Hashing a non-canonical element as is could give a different digest from its canonical form,
+/// so two equal inputs could hash differently.
+///
+/// # Safety
+/// Every element of a digest which `hash_iter` returns must be in `[0, P)`.
+pub unsafe trait CanonicalHasher<T: Canonicalize, Out: Canonicalize>:
+    CryptographicHasher<T, Out>
+{
+    #[inline]
+    fn hash_canonical<I>(&self, input: I) -> Canonical<Out>
+    where
+        I: IntoIterator<Item = Canonical<T>>,
+    {
+        let digest = self.hash_iter(input.into_iter().map(Canonical::into_inner));
+        // Safety: The implementor guarantees that the digest is canonical.
+        unsafe { Canonical::new_unchecked(digest) }
+    }
+}
+
+// Safety: The digest is part of the state which the permutation last handed out.
+unsafe impl<T, P, const WIDTH: usize, const RATE: usize, const OUT: usize>
+    CanonicalHasher<T, [T; OUT]> for PaddingFreeSponge<P, WIDTH, RATE, OUT>
+where
+    T: Canonicalize + Default,
+    P: CanonicalPermutation<[T; WIDTH]> + CryptographicPermutation<[T; WIDTH]>,
+{
+}
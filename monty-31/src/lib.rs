@@ -1,3 +1,16 @@
+//! Fields with 31-bit primes in MONTY form, generic over the prime.
+//!
+//! A field is instantiated by implementing the parameter traits for a marker type:
+//! [`MontyParameters`], [`PackedMontyParameters`], [`BarrettParameters`], [`FieldParameters`],
+//! [`TwoAdicData`] and, for extension fields, [`BinomialExtensionData`]. The packed fields also
+//! need one of `MontyParametersNeon`, `MontyParametersAVX2` or `MontyParametersAVX512` for the
+//! architecture. Poseidon2 needs [`InternalLayerBaseParameters`] and [`InternalLayerParameters`]
+//! for each width, the latter with an architecture-specific internal layer, e.g. through
+//! `InternalLayerParametersNeon`.
+//!
+//! `p3-koala-bear`, the field `2^31 - 2^24 + 1`, and `p3-baby-bear` are worked examples of all of
+//! these, including the tests each instantiation should run.
+
 #![no_std]
 #![cfg_attr(
     all(
@@ -10,13 +23,18 @@
 
 extern crate alloc;
 
+mod batch;
+mod canonical;
 mod data_traits;
 pub mod dft;
 mod extension;
 mod mds;
 mod monty_31;
+pub mod packed_ops;
 mod poseidon2;
 mod utils;
+pub use batch::*;
+pub use canonical::*;
 pub use data_traits::*;
 pub use mds::*;
 pub use monty_31::*;
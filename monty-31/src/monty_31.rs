@@ -2,8 +2,8 @@
 
 use alloc::vec;
 use alloc::vec::Vec;
-use core::fmt::{self, Debug, Display, Formatter};
-use core::hash::Hash;
+use core::fmt::{self, Debug, Display, Formatter, LowerHex};
+use core::hash::{Hash, Hasher};
 use core::intrinsics::transmute;
 use core::iter::{Product, Sum};
 use core::marker::PhantomData;
@@ -11,8 +11,10 @@ use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use num_bigint::BigUint;
 use p3_field::{
-    Field, FieldAlgebra, Packable, PrimeField, PrimeField32, PrimeField64, TwoAdicField,
+    assert_two_adicity, Field, FieldAlgebra, Packable, PrimeField, PrimeField32, PrimeField64,
+    TwoAdicField,
 };
+use p3_util::encoding::{CanonicalEncoding, DecodingError};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 use serde::de::Error;
@@ -21,7 +23,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use crate::utils::{from_monty, halve_u32, monty_reduce, to_monty, to_monty_64};
 use crate::{FieldParameters, MontyParameters, TwoAdicData};
 
-#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
 #[repr(transparent)] // Packed field implementations rely on this!
 pub struct MontyField31<MP: MontyParameters> {
     /// The MONTY form of the field element, saved as a positive integer less than `P`.
@@ -43,17 +45,27 @@ impl<MP: MontyParameters> MontyField31<MP> {
         }
     }
 
-    // Create a new field element from something already in MONTY form.
-    // This is `pub(crate)` for tests and delayed reduction strategies. If you're using it outside of those, you're
-    // likely doing something fishy.
+    /// Create a field element from its internal MONTY form, `x * 2^32 mod P`, without any
+    /// conversion.
+    ///
+    /// This exposes the internal representation: `value` must already be in MONTY form and less
+    /// than `P`. It's meant for precomputed tables; use [`Self::new`] for ordinary values.
     #[inline(always)]
-    pub(crate) const fn new_monty(value: u32) -> Self {
+    pub const fn new_monty(value: u32) -> Self {
         Self {
             value,
             _phantom: PhantomData,
         }
     }
 
+    /// The internal MONTY form of this element, `x * 2^32 mod P`, rather than its value `x`.
+    ///
+    /// The inverse of [`Self::new_monty`]; use `as_canonical_u32` for the value itself.
+    #[inline(always)]
+    pub const fn to_monty(self) -> u32 {
+        self.value
+    }
+
     /// Produce a u32 in range [0, P) from a field element corresponding to the true value.
     #[inline(always)]
     pub(crate) fn to_u32(elem: &Self) -> u32 {
@@ -108,6 +120,14 @@ impl<MP: MontyParameters> MontyField31<MP> {
     }
 }
 
+/// Hashes the canonical value, like the other prime fields, so the hash doesn't depend on the
+/// MONTY constant.
+impl<FP: MontyParameters> Hash for MontyField31<FP> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u32(MontyField31::to_u32(self));
+    }
+}
+
 impl<FP: MontyParameters> Ord for MontyField31<FP> {
     #[inline]
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
@@ -134,6 +154,14 @@ impl<FP: MontyParameters> Debug for MontyField31<FP> {
     }
 }
 
+/// Formats the internal MONTY form, `x * 2^32 mod P`, rather than the value `x`, for debugging
+/// code which works with the MONTY form directly.
+impl<FP: MontyParameters> LowerHex for MontyField31<FP> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        LowerHex::fmt(&self.value, f)
+    }
+}
+
 impl<FP: MontyParameters> Distribution<MontyField31<FP>> for Standard {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> MontyField31<FP> {
@@ -149,24 +177,38 @@ impl<FP: MontyParameters> Distribution<MontyField31<FP>> for Standard {
 
 impl<FP: FieldParameters> Serialize for MontyField31<FP> {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        // It's faster to Serialize and Deserialize in monty form.
-        serializer.serialize_u32(self.value)
+        // Serialize the canonical value, so that the encoding does not depend on the MONTY constant.
+        serializer.serialize_u32(self.as_canonical_u32())
     }
 }
 
 impl<'de, FP: FieldParameters> Deserialize<'de> for MontyField31<FP> {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
         let val = u32::deserialize(d)?;
-        // Ensure that `val` satisfies our invariant, namely is `< P`.
+        // Only accept canonical values, that is `val < P`.
         if val < FP::PRIME {
-            // It's faster to Serialize and Deserialize in monty form.
-            Ok(MontyField31::new_monty(val))
+            Ok(MontyField31::new(val))
         } else {
             Err(D::Error::custom("Value is out of range"))
         }
     }
 }
 
+impl<FP: FieldParameters> CanonicalEncoding for MontyField31<FP> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_canonical_u32().encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        let val = u32::decode(input)?;
+        if val < FP::PRIME {
+            Ok(MontyField31::new(val))
+        } else {
+            Err(DecodingError::NonCanonical)
+        }
+    }
+}
+
 impl<FP: FieldParameters> Packable for MontyField31<FP> {}
 
 impl<FP: FieldParameters> FieldAlgebra for MontyField31<FP> {
@@ -237,6 +279,12 @@ impl<FP: FieldParameters> FieldAlgebra for MontyField31<FP> {
         // SAFETY: repr(transparent) ensures transmutation safety.
         unsafe { transmute(vec![0u32; len]) }
     }
+
+    #[inline]
+    fn sum_slice(xs: &[Self]) -> Self {
+        let [sum] = sum_lanes(xs.iter().map(|&x| [x]));
+        sum
+    }
 }
 
 impl<FP: FieldParameters> Field for MontyField31<FP> {
@@ -271,6 +319,8 @@ impl<FP: FieldParameters> Field for MontyField31<FP> {
 
     const GENERATOR: Self = FP::MONTY_GEN;
 
+    const BITS: usize = (u32::BITS - FP::PRIME.leading_zeros()) as usize;
+
     #[inline]
     fn exp_u64_generic<FA: FieldAlgebra<F = Self>>(val: FA, power: u64) -> FA {
         FP::exp_u64_generic(val, power)
@@ -332,7 +382,7 @@ impl<FP: FieldParameters> PrimeField32 for MontyField31<FP> {
 impl<FP: FieldParameters + TwoAdicData> TwoAdicField for MontyField31<FP> {
     const TWO_ADICITY: usize = FP::TWO_ADICITY;
     fn two_adic_generator(bits: usize) -> Self {
-        assert!(bits <= Self::TWO_ADICITY);
+        assert_two_adicity::<Self>(bits);
         FP::TWO_ADIC_GENERATORS.as_ref()[bits]
     }
 }
@@ -358,6 +408,53 @@ impl<FP: MontyParameters> AddAssign for MontyField31<FP> {
     }
 }
 
+/// Sum arrays of elements lane by lane.
+///
+/// The MONTY forms are added as `u64`s, so each lane is reduced only once every `2^32` arrays
+/// rather than after every addition.
+#[inline]
+pub(crate) fn sum_lanes<MP: MontyParameters, const N: usize>(
+    xs: impl IntoIterator<Item = [MontyField31<MP>; N]>,
+) -> [MontyField31<MP>; N] {
+    // Each lane is below P < 2^31 after a reduction, so 2^32 more values below 2^31 can be added
+    // without overflowing.
+    const BATCH: u64 = 1 << 32;
+    let mut acc = [0u64; N];
+    let mut count = 0;
+    for x in xs {
+        for (acc, x) in acc.iter_mut().zip(x) {
+            *acc += x.value as u64;
+        }
+        count += 1;
+        if count == BATCH {
+            acc.iter_mut().for_each(|acc| *acc %= MP::PRIME as u64);
+            count = 0;
+        }
+    }
+    acc.map(|acc| MontyField31::new_monty((acc % MP::PRIME as u64) as u32))
+}
+
+/// Sum packed elements with packed additions.
+///
+/// Every addition keeps its lanes reduced, so the whole sum stays in vector registers. Four
+/// independent accumulators hide the latency of each addition.
+#[cfg(any(
+    all(target_arch = "aarch64", target_feature = "neon"),
+    all(target_arch = "x86_64", target_feature = "avx2"),
+))]
+#[inline]
+pub(crate) fn sum_packed<P: FieldAlgebra + Copy>(xs: &[P]) -> P {
+    let mut chunks = xs.chunks_exact(4);
+    let mut acc = [P::ZERO; 4];
+    for chunk in &mut chunks {
+        for (acc, &x) in acc.iter_mut().zip(chunk) {
+            *acc += x;
+        }
+    }
+    let rest = chunks.remainder().iter().fold(P::ZERO, |acc, &x| acc + x);
+    (acc[0] + acc[1]) + (acc[2] + acc[3]) + rest
+}
+
 impl<FP: MontyParameters> Sum for MontyField31<FP> {
     #[inline]
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
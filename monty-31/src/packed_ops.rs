@@ -0,0 +1,104 @@
+//! Multiplications by small powers of two, for building cheap Poseidon2 internal diagonals.
+//!
+//! Write the prime as `P = r 2^j + 1` for odd `r`. Then `-2^{-N} = r 2^{j - N} mod P`, so writing a
+//! canonical `x` as `x_lo + 2^N x_hi` with `x_lo < 2^N` gives `-2^{-N} x = r 2^{j - N} x_lo - x_hi`,
+//! which needs a shift, a mask and a small multiplication rather than a Monty multiplication. As
+//! the map is linear it applies to the MONTY form of `x` directly.
+//!
+//! This module gathers the vectorized versions of these maps for the architecture being compiled
+//! for, together with scalar versions with the same meaning, so that a diagonal written with the
+//! vectorized functions can be checked against, or expressed with, the scalar ones.
+//!
+//! All of the vectorized functions require their input to be in canonical form, which is checked
+//! by `debug_assert`s in debug builds. Their outputs are not always canonical:
+//!
+//! | Function                               | Computes      | Output range  | Requirements              |
+//! |----------------------------------------|---------------|---------------|---------------------------|
+//! | `halve_*`                              | `x/2`         | `[0, P)`      |                           |
+//! | `mul_2exp_neg_n_avx2`                  | `2^{-N} x`    | `(-P, P)`     | `r < 2^15`, `N < 15`      |
+//! | `mul_neg_2exp_neg_n_avx2`              | `-2^{-N} x`   | `(-P, P)`     | `r < 2^15`, `N < 15`      |
+//! | `mul_2exp_neg_8_avx2`                  | `2^{-8} x`    | `(-P, P)`     | `r < 2^7`                 |
+//! | `mul_neg_2exp_neg_8_avx2`              | `-2^{-8} x`   | `(-P, P)`     | `r < 2^7`                 |
+//! | `mul_2exp_neg_two_adicity_avx2`        | `2^{-j} x`    | `(-P, P)`     | `P = 2^31 - 2^j + 1`      |
+//! | `mul_neg_2exp_neg_two_adicity_avx2`    | `-2^{-j} x`   | `(-P, P)`     | `P = 2^31 - 2^j + 1`      |
+//! | `mul_neg_2exp_neg_n_avx512`            | `-2^{-N} x`   | `[0, P]`      | `r < 2^15`, `N < 15`      |
+//! | `mul_neg_2exp_neg_8_avx512`            | `-2^{-8} x`   | `[0, P]`      | `r < 2^7`                 |
+//! | `mul_neg_2exp_neg_two_adicity_avx512`  | `-2^{-j} x`   | `[0, P]`      | `P = 2^31 - 2^j + 1`      |
+//! | `mul_neg_2exp_neg_n_neon`              | `-2^{-N} x`   | `[0, P)`      | `N <= j`                  |
+//!
+//! `N_PRIME` is `j - N` for the functions taking `N`, and `31 - j` for the `two_adicity` ones.
+//!
+//! Note that the `neg` functions compute the negative of the power of two. A diagonal entry `2^{-N}`
+//! can be computed as `sum - (-2^{-N} x)` instead of `sum + 2^{-N} x`, so the Poseidon2 layers
+//! mostly use the `neg` functions and defer the negation to the addition of the sum.
+
+use crate::utils::halve_u32;
+use crate::{FieldParameters, MontyField31, TwoAdicData};
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub use crate::aarch64_neon::{halve_neon, mul_neg_2exp_neg_n_neon};
+#[cfg(all(
+    target_arch = "x86_64",
+    target_feature = "avx2",
+    not(all(feature = "nightly-features", target_feature = "avx512f"))
+))]
+pub use crate::x86_64_avx2::{
+    halve_avx2, mul_2exp_neg_8_avx2, mul_2exp_neg_n_avx2, mul_2exp_neg_two_adicity_avx2,
+    mul_neg_2exp_neg_8_avx2, mul_neg_2exp_neg_n_avx2, mul_neg_2exp_neg_two_adicity_avx2,
+};
+#[cfg(all(
+    feature = "nightly-features",
+    target_arch = "x86_64",
+    target_feature = "avx512f"
+))]
+pub use crate::x86_64_avx512::{
+    halve_avx512, mul_neg_2exp_neg_8_avx512, mul_neg_2exp_neg_n_avx512,
+    mul_neg_2exp_neg_two_adicity_avx512,
+};
+
+/// Compute `x/2`.
+#[inline]
+#[must_use]
+pub fn halve<FP: FieldParameters>(x: MontyField31<FP>) -> MontyField31<FP> {
+    MontyField31::new_monty(halve_u32::<FP>(x.value))
+}
+
+/// Compute `2^{-n} x` for any `n` between `0` and the two-adicity of `P`.
+#[inline]
+#[must_use]
+pub fn mul_2exp_neg_n<TAD: TwoAdicData>(x: MontyField31<TAD>, n: u32) -> MontyField31<TAD> {
+    let (lo_term, hi) = split_2exp_neg_n::<TAD>(x.value, n);
+    // 2^{-n} x = x_hi - r 2^{j - n} x_lo, with both terms in [0, P).
+    if hi >= lo_term {
+        MontyField31::new_monty(hi - lo_term)
+    } else {
+        MontyField31::new_monty(hi + TAD::PRIME - lo_term)
+    }
+}
+
+/// Compute `-2^{-n} x` for any `n` between `0` and the two-adicity of `P`.
+#[inline]
+#[must_use]
+pub fn mul_neg_2exp_neg_n<TAD: TwoAdicData>(x: MontyField31<TAD>, n: u32) -> MontyField31<TAD> {
+    let (lo_term, hi) = split_2exp_neg_n::<TAD>(x.value, n);
+    // -2^{-n} x = r 2^{j - n} x_lo - x_hi, with both terms in [0, P).
+    if lo_term >= hi {
+        MontyField31::new_monty(lo_term - hi)
+    } else {
+        MontyField31::new_monty(lo_term + TAD::PRIME - hi)
+    }
+}
+
+/// Split a canonical `x` into `(r 2^{j - n} x_lo, x_hi)`, both of which are in `[0, P)`.
+#[inline]
+fn split_2exp_neg_n<TAD: TwoAdicData>(x: u32, n: u32) -> (u32, u32) {
+    assert!(
+        n as usize <= TAD::TWO_ADICITY,
+        "2^{n} does not divide P - 1"
+    );
+    // The two-adicity is less than 32, so these shifts don't overflow.
+    let hi = x >> n;
+    let lo = x & ((1 << n) - 1);
+    let lo_term = ((TAD::ODD_FACTOR as u32) * lo) << (TAD::TWO_ADICITY as u32 - n);
+    (lo_term, hi)
+}
@@ -4,12 +4,12 @@ use core::ops::Mul;
 use p3_field::FieldAlgebra;
 use p3_poseidon2::{
     add_rc_and_sbox_generic, external_initial_permute_state, external_terminal_permute_state,
-    ExternalLayer, GenericPoseidon2LinearLayers, InternalLayer, MDSMat4,
+    ExternalLayer, GenericPoseidon2LinearLayers, InternalLayer, MDSMat4, Poseidon2DefaultParams,
 };
 
 use crate::{
-    FieldParameters, MontyField31, MontyParameters, Poseidon2ExternalLayerMonty31,
-    Poseidon2InternalLayerMonty31,
+    canonicalize_state, FieldParameters, MontyField31, MontyParameters,
+    Poseidon2ExternalLayerMonty31, Poseidon2InternalLayerMonty31,
 };
 
 /// Trait which handles the Poseidon2 internal layers.
@@ -39,7 +39,7 @@ pub trait InternalLayerBaseParameters<MP: MontyParameters, const WIDTH: usize>:
 
 #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
 pub trait InternalLayerParameters<FP: FieldParameters, const WIDTH: usize>:
-    InternalLayerBaseParameters<FP, WIDTH>
+    InternalLayerBaseParameters<FP, WIDTH> + crate::InternalLayerParametersNeon<FP, WIDTH>
 {
 }
 #[cfg(all(
@@ -97,6 +97,19 @@ where
     }
 }
 
+/// A Monty31 field's default Poseidon2 parameters are those of its internal layer parameters.
+impl<FP, const WIDTH: usize, P2P> Poseidon2DefaultParams<MontyField31<FP>, WIDTH>
+    for Poseidon2InternalLayerMonty31<FP, WIDTH, P2P>
+where
+    FP: FieldParameters,
+    P2P: InternalLayerParameters<FP, WIDTH> + Poseidon2DefaultParams<MontyField31<FP>, WIDTH>,
+{
+    const NAME: &'static str = P2P::NAME;
+    const EXTERNAL_INITIAL: &'static [[MontyField31<FP>; WIDTH]] = P2P::EXTERNAL_INITIAL;
+    const INTERNAL: &'static [MontyField31<FP>] = P2P::INTERNAL;
+    const EXTERNAL_TERMINAL: &'static [[MontyField31<FP>; WIDTH]] = P2P::EXTERNAL_TERMINAL;
+}
+
 impl<FP, const WIDTH: usize, const D: u64> ExternalLayer<MontyField31<FP>, WIDTH, D>
     for Poseidon2ExternalLayerMonty31<FP, WIDTH>
 where
@@ -104,6 +117,8 @@ where
 {
     /// Perform the initial external layers of the Poseidon2 permutation on the given state.
     fn permute_state_initial(&self, state: &mut [MontyField31<FP>; WIDTH]) {
+        // A non-canonical input is reduced before it meets any round.
+        canonicalize_state(state);
         external_initial_permute_state(
             state,
             self.external_constants.get_initial_constants(),
@@ -120,6 +135,8 @@ where
             add_rc_and_sbox_generic::<_, D>,
             &MDSMat4,
         );
+        // Nothing non-canonical leaves the permutation, see `CanonicalPermutation`.
+        canonicalize_state(state);
     }
 }
 
@@ -13,7 +13,9 @@ pub(crate) const fn to_monty<MP: MontyParameters>(x: u32) -> u32 {
 /// The output will be a u32 in range [0, P).
 #[inline]
 pub(crate) const fn to_monty_64<MP: MontyParameters>(x: u64) -> u32 {
-    (((x as u128) << MP::MONTY_BITS) % MP::PRIME as u128) as u32
+    // Reducing first keeps this in 64-bit arithmetic, as a `u128` remainder is a slow call into
+    // compiler-rt on every target.
+    to_monty::<MP>((x % MP::PRIME as u64) as u32)
 }
 
 /// Convert a u32 out of MONTY form.
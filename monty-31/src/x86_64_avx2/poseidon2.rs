@@ -12,9 +12,9 @@ use p3_poseidon2::{
 };
 
 use crate::{
-    add, apply_func_to_even_odd, halve_avx2, packed_exp_3, packed_exp_5, packed_exp_7,
-    signed_add_avx2, sub, FieldParameters, InternalLayerBaseParameters, MontyField31,
-    MontyParameters, PackedMontyField31AVX2, PackedMontyParameters,
+    add, apply_func_to_even_odd, canonicalize_state, halve_avx2, packed_exp_3, packed_exp_5,
+    packed_exp_7, signed_add_avx2, sub, Canonical, FieldParameters, InternalLayerBaseParameters,
+    MontyField31, MontyParameters, PackedMontyField31AVX2, PackedMontyParameters,
 };
 
 // In the internal layers, it is valuable to treat the first entry of the state differently
@@ -31,20 +31,20 @@ pub struct InternalLayer16<PMP: PackedMontyParameters> {
 impl<PMP: PackedMontyParameters> InternalLayer16<PMP> {
     #[inline]
     #[must_use]
-    /// Convert from `InternalLayer16<PMP>` to `[PackedMontyField31AVX2<PMP>; 16]`
+    /// Convert from `InternalLayer16<PMP>` to `[PackedMontyField31AVX2<PMP>; 16]`, reducing any element equal to `P`.
     ///
-    /// SAFETY: The caller must ensure that each element of `s_hi` represents a valid `MontyField31<PMP>`.
-    /// In particular, each element of each vector must be in `[0, P)` (canonical form).
-    unsafe fn to_packed_field_array(self) -> [PackedMontyField31AVX2<PMP>; 16] {
-        // Safety: It is up to the user to ensure that elements of `s_hi` represent valid
-        // `MontyField31<PMP>` values. We must only reason about memory representations.
-        // As described in packing.rs, PackedMontyField31AVX2<PMP> can be transmuted to and from `__m256i`.
-
-        // `InternalLayer16` is `repr(C)` so its memory layout looks like:
-        // `[PackedMontyField31AVX2<PMP>, __m256i, ..., __m256i]`
-        // Thus as `__m256i` can be can be transmuted to `PackedMontyField31AVX2<FP>`,
-        // `InternalLayer16` can be transmuted to `[PackedMontyField31AVX2<FP>; 16]`.
-        transmute(self)
+    /// Each element of each vector must be in `[0, P]`, as the diagonal multiplications may leave them.
+    fn to_packed_field_array(self) -> Canonical<[PackedMontyField31AVX2<PMP>; 16]> {
+        unsafe {
+            // Safety: As described in packing.rs, PackedMontyField31AVX2<PMP> can be transmuted to and from `__m256i`.
+            // The elements of the array may briefly equal `P`, until `Canonical::new` reduces them.
+
+            // `InternalLayer16` is `repr(C)` so its memory layout looks like:
+            // `[PackedMontyField31AVX2<PMP>, __m256i, ..., __m256i]`
+            // Thus as `__m256i` can be can be transmuted to `PackedMontyField31AVX2<FP>`,
+            // `InternalLayer16` can be transmuted to `[PackedMontyField31AVX2<FP>; 16]`.
+            Canonical::new(transmute(self))
+        }
     }
 
     #[inline]
@@ -73,18 +73,20 @@ pub struct InternalLayer24<PMP: PackedMontyParameters> {
 impl<PMP: PackedMontyParameters> InternalLayer24<PMP> {
     #[inline]
     #[must_use]
-    /// Convert from `InternalLayer24<PMP>` to `[PackedMontyField31AVX2<PMP>; 24]`
+    /// Convert from `InternalLayer24<PMP>` to `[PackedMontyField31AVX2<PMP>; 24]`, reducing any element equal to `P`.
     ///
-    /// SAFETY: The caller must ensure that each element of `s_hi` represents a valid `MontyField31<PMP>`.
-    /// In particular, each element of each vector must be in `[0, P)` (canonical form).
-    unsafe fn to_packed_field_array(self) -> [PackedMontyField31AVX2<PMP>; 24] {
-        // Safety: As described in packing.rs, PackedMontyField31AVX2<PMP> can be transmuted to and from `__m256i`.
-
-        // `InternalLayer24` is `repr(C)` so its memory layout looks like:
-        // `[PackedMontyField31AVX2<PMP>, __m256i, ..., __m256i]`
-        // Thus as `__m256i` can be can be transmuted to `PackedMontyField31AVX2<FP>`,
-        // `InternalLayer24` can be transmuted to `[PackedMontyField31AVX2<FP>; 24]`.
-        transmute(self)
+    /// Each element of each vector must be in `[0, P]`, as the diagonal multiplications may leave them.
+    fn to_packed_field_array(self) -> Canonical<[PackedMontyField31AVX2<PMP>; 24]> {
+        unsafe {
+            // Safety: As described in packing.rs, PackedMontyField31AVX2<PMP> can be transmuted to and from `__m256i`.
+            // The elements of the array may briefly equal `P`, until `Canonical::new` reduces them.
+
+            // `InternalLayer24` is `repr(C)` so its memory layout looks like:
+            // `[PackedMontyField31AVX2<PMP>, __m256i, ..., __m256i]`
+            // Thus as `__m256i` can be can be transmuted to `PackedMontyField31AVX2<FP>`,
+            // `InternalLayer24` can be transmuted to `[PackedMontyField31AVX2<FP>; 24]`.
+            Canonical::new(transmute(self))
+        }
     }
 
     #[inline]
@@ -185,11 +187,12 @@ impl<FP: FieldParameters, const WIDTH: usize>
 #[inline(always)]
 #[must_use]
 fn exp_small<PMP: PackedMontyParameters, const D: u64>(val: __m256i) -> __m256i {
+    const { assert!(D == 3 || D == 5 || D == 7, "No exp function for given D") };
     match D {
         3 => packed_exp_3::<PMP>(val),
         5 => packed_exp_5::<PMP>(val),
         7 => packed_exp_7::<PMP>(val),
-        _ => panic!("No exp function for given D"),
+        _ => unreachable!(),
     }
 }
 
@@ -380,9 +383,9 @@ where
                 ); // si -> si + sum for all i > 0.
             });
 
-            // This transformation is safe as the above function returns elements
-            // in canonical form when given elements in canonical form.
-            *state = InternalLayer16::to_packed_field_array(internal_state);
+            // The state leaves the internal layer in canonical form, whatever the diagonal
+            // multiplications left in `s_hi`.
+            *state = internal_state.to_packed_field_array().into_inner();
         }
     }
 }
@@ -431,9 +434,9 @@ where
                 ); // si -> si + sum for all i > 0.
             });
 
-            // This transformation is safe as the above function returns elements
-            // in canonical form when given elements in canonical form.
-            *state = InternalLayer24::to_packed_field_array(internal_state);
+            // The state leaves the internal layer in canonical form, whatever the diagonal
+            // multiplications left in `s_hi`.
+            *state = internal_state.to_packed_field_array().into_inner();
         }
     }
 }
@@ -445,6 +448,8 @@ where
 {
     /// Perform the initial external layers of the Poseidon2 permutation on the given state.
     fn permute_state_initial(&self, state: &mut [PackedMontyField31AVX2<FP>; WIDTH]) {
+        // A non-canonical input is reduced before it meets any round.
+        canonicalize_state(state);
         external_initial_permute_state(
             state,
             &self.packed_initial_external_constants,
@@ -461,5 +466,7 @@ where
             add_rc_and_sbox::<FP, D>,
             &MDSMat4,
         );
+        // Nothing non-canonical leaves the permutation, see `CanonicalPermutation`.
+        canonicalize_state(state);
     }
 }
@@ -6,10 +6,21 @@ use crate::{MontyParameters, PackedMontyParameters, TwoAdicData};
 // Godbolt file showing that these all compile to the expected instructions. (Potentially plus a few memory ops):
 // https://godbolt.org/z/9P71nYrqh
 
+/// Check, in debug builds, that every element of `input` is in canonical form.
+#[inline(always)]
+fn debug_assert_canonical<MP: MontyParameters>(input: __m256i) {
+    debug_assert!(
+        unsafe { transmute::<__m256i, [u32; 8]>(input) }
+            .iter()
+            .all(|&x| x < MP::PRIME),
+        "input is not in canonical form"
+    );
+}
+
 /// Halve a vector of Monty31 field elements in canonical form.
 /// If the inputs are not in canonical form, the result is undefined.
 #[inline(always)]
-pub(crate) fn halve_avx2<MP: MontyParameters>(input: __m256i) -> __m256i {
+pub fn halve_avx2<MP: MontyParameters>(input: __m256i) -> __m256i {
     /*
         We want this to compile to:
             vpand    least_bit, val, ONE
@@ -23,6 +34,8 @@ pub(crate) fn halve_avx2<MP: MontyParameters>(input: __m256i) -> __m256i {
         If val is even: val/2 mod P = val/2 = val >> 1.
         If val is odd: val/2 mod P = (val + P)/2 = (val >> 1) + (P + 1)/2
     */
+    debug_assert_canonical::<MP>(input);
+
     unsafe {
         // Safety: If this code got compiled then AVX2 intrinsics are available.
         const ONE: __m256i = unsafe { transmute([1u32; 8]) };
@@ -134,6 +147,8 @@ pub unsafe fn mul_2exp_neg_n_avx2<TAD: TwoAdicData, const N: i32, const N_PRIME:
         throughput: 1.67
         latency: 8
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(N + N_PRIME, TAD::TWO_ADICITY as i32); // Compiler removes this provided it is satisfied.
 
@@ -174,6 +189,8 @@ pub unsafe fn mul_neg_2exp_neg_n_avx2<TAD: TwoAdicData, const N: i32, const N_PR
         throughput: 1.67
         latency: 8
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(N + N_PRIME, TAD::TWO_ADICITY as i32); // Compiler removes this provided it is satisfied.
 
@@ -210,6 +227,8 @@ pub unsafe fn mul_2exp_neg_8_avx2<TAD: TwoAdicData, const N_PRIME: i32>(input: _
         throughput: 1.33
         latency: 7
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(8 + N_PRIME, TAD::TWO_ADICITY as i32); // Compiler removes this provided it is satisfied.
 
@@ -249,6 +268,8 @@ pub unsafe fn mul_neg_2exp_neg_8_avx2<TAD: TwoAdicData, const N_PRIME: i32>(
         throughput: 1.33
         latency: 7
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(8 + N_PRIME, TAD::TWO_ADICITY as i32); // Compiler removes this provided it is satisfied.
 
@@ -289,6 +310,8 @@ pub unsafe fn mul_2exp_neg_two_adicity_avx2<TAD: TwoAdicData, const N: i32, cons
         throughput: 1.67
         latency: 3
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(N, (TAD::TWO_ADICITY as i32)); // Compiler removes this provided it is satisfied.
         assert_eq!(N + N_PRIME, 31); // Compiler removes this provided it is satisfied.
@@ -329,6 +352,8 @@ pub unsafe fn mul_neg_2exp_neg_two_adicity_avx2<
         throughput: 1.67
         latency: 3
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(N, (TAD::TWO_ADICITY as i32)); // Compiler removes this provided it is satisfied.
         assert_eq!(N + N_PRIME, 31); // Compiler removes this provided it is satisfied.
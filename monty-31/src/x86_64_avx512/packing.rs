@@ -11,7 +11,8 @@ use p3_util::convert_vec;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 
-use crate::{FieldParameters, MontyField31, PackedMontyParameters};
+use crate::monty_31::sum_packed;
+use crate::{Canonicalize, FieldParameters, MontyField31, PackedMontyParameters};
 
 const WIDTH: usize = 16;
 
@@ -68,6 +69,20 @@ impl<PMP: PackedMontyParameters> PackedMontyField31AVX512<PMP> {
     }
 }
 
+impl<PMP: PackedMontyParameters> Canonicalize for PackedMontyField31AVX512<PMP> {
+    #[inline]
+    fn canonicalize(self) -> Self {
+        // This is the final step of `add`: for t in 0, ..., 2P - 1, unsigned_min(t, t - P) is t if
+        // t < P and t - P otherwise.
+        let t = self.to_vector();
+        unsafe {
+            // Safety: If this code got compiled then AVX-512F intrinsics are available.
+            let u = x86_64::_mm512_sub_epi32(t, PMP::PACKED_P);
+            Self::from_vector(x86_64::_mm512_min_epu32(t, u))
+        }
+    }
+}
+
 impl<PMP: PackedMontyParameters> Add for PackedMontyField31AVX512<PMP> {
     type Output = Self;
     #[inline]
@@ -635,6 +650,11 @@ impl<FP: FieldParameters> FieldAlgebra for PackedMontyField31AVX512<FP> {
         unsafe { convert_vec(Self::F::zero_vec(len * WIDTH)) }
     }
 
+    #[inline]
+    fn sum_slice(xs: &[Self]) -> Self {
+        sum_packed(xs)
+    }
+
     #[inline]
     fn cube(&self) -> Self {
         let val = self.to_vector();
@@ -1004,6 +1024,20 @@ unsafe impl<FP: FieldParameters> PackedValue for PackedMontyField31AVX512<FP> {
 
 unsafe impl<FP: FieldParameters> PackedField for PackedMontyField31AVX512<FP> {
     type Scalar = MontyField31<FP>;
+
+    #[inline]
+    fn packed_sum(self) -> Self::Scalar {
+        // Fold the two halves of each block onto each other, halving the block length each time,
+        // until every lane holds the full sum.
+        let mut acc = self;
+        let mut block_len = WIDTH / 2;
+        while block_len > 0 {
+            let (lo, hi) = acc.interleave(acc, block_len);
+            acc = lo + hi;
+            block_len /= 2;
+        }
+        acc.0[0]
+    }
 }
 
 unsafe impl<FP: FieldParameters> PackedFieldPow2 for PackedMontyField31AVX512<FP> {
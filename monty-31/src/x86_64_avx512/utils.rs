@@ -6,10 +6,21 @@ use crate::{MontyParameters, PackedMontyParameters, TwoAdicData};
 // Godbolt file showing that these all compile to the expected instructions. (Potentially plus a few memory ops):
 // https://godbolt.org/z/dvW7r1zjj
 
+/// Check, in debug builds, that every element of `input` is in canonical form.
+#[inline(always)]
+fn debug_assert_canonical<MP: MontyParameters>(input: __m512i) {
+    debug_assert!(
+        unsafe { transmute::<__m512i, [u32; 16]>(input) }
+            .iter()
+            .all(|&x| x < MP::PRIME),
+        "input is not in canonical form"
+    );
+}
+
 /// Halve a vector of Monty31 field elements in canonical form.
 /// If the inputs are not in canonical form, the result is undefined.
 #[inline(always)]
-pub(crate) fn halve_avx512<MP: MontyParameters>(input: __m512i) -> __m512i {
+pub fn halve_avx512<MP: MontyParameters>(input: __m512i) -> __m512i {
     /*
         We want this to compile to:
             vptestmd  least_bit, val, ONE
@@ -22,6 +33,8 @@ pub(crate) fn halve_avx512<MP: MontyParameters>(input: __m512i) -> __m512i {
         If val is even: val/2 mod P = val/2 = val >> 1.
         If val is odd: val/2 mod P = (val + P)/2 = (val >> 1) + (P + 1)/2
     */
+    debug_assert_canonical::<MP>(input);
+
     unsafe {
         // Safety: If this code got compiled then AVX2 intrinsics are available.
         const ONE: __m512i = unsafe { transmute([1u32; 16]) };
@@ -90,6 +103,8 @@ pub unsafe fn mul_neg_2exp_neg_n_avx512<
         throughput: 3
         latency: 9
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(N + N_PRIME, TAD::TWO_ADICITY as u32); // Compiler removes this provided it is satisfied.
 
@@ -122,7 +137,7 @@ pub unsafe fn mul_neg_2exp_neg_n_avx512<
 ///
 /// The prime P must be of the form P = r * 2^j + 1 with r odd and r < 2^7.
 /// Input must be given in canonical form.
-/// Output is not in canonical form, outputs are only guaranteed to lie in (-P, P).
+/// Output may not be in canonical form but will lie in [0, P].
 #[inline(always)]
 pub unsafe fn mul_neg_2exp_neg_8_avx512<
     TAD: TwoAdicData + PackedMontyParameters,
@@ -141,6 +156,8 @@ pub unsafe fn mul_neg_2exp_neg_8_avx512<
         throughput: 3
         latency: 7
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(8 + N_PRIME, TAD::TWO_ADICITY as u32); // Compiler removes this provided it is satisfied.
 
@@ -173,7 +190,7 @@ pub unsafe fn mul_neg_2exp_neg_8_avx512<
 ///
 /// The prime P must have the form P = 2^31 - 2^N + 1.
 /// Input must be given in canonical form.
-/// Output is not in canonical form, outputs are only guaranteed to lie in (-P, P).
+/// Output may not be in canonical form but will lie in [0, P].
 #[inline(always)]
 pub unsafe fn mul_neg_2exp_neg_two_adicity_avx512<
     TAD: TwoAdicData + PackedMontyParameters,
@@ -194,6 +211,8 @@ pub unsafe fn mul_neg_2exp_neg_two_adicity_avx512<
         throughput: 3
         latency: 5
     */
+    debug_assert_canonical::<TAD>(input);
+
     unsafe {
         assert_eq!(N, (TAD::TWO_ADICITY as u32)); // Compiler removes this provided it is satisfied.
         assert_eq!(N + N_PRIME, 31); // Compiler removes this provided it is satisfied.
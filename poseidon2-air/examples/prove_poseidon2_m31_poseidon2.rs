@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 use p3_challenger::DuplexChallenger;
 use p3_circle::CirclePcs;
 use p3_commit::ExtensionMmcs;
-use p3_field::extension::BinomialExtensionField;
+use p3_field::extension::ComplexBinomialExtensionField;
 use p3_field::Field;
 use p3_fri::create_benchmark_fri_config;
 use p3_merkle_tree::MerkleTreeMmcs;
@@ -46,7 +46,7 @@ fn main() -> Result<(), impl Debug> {
         .init();
 
     type Val = Mersenne31;
-    type Challenge = BinomialExtensionField<Val, 3>;
+    type Challenge = ComplexBinomialExtensionField<Val, 4>;
 
     type Perm16 = Poseidon2Mersenne31<16>;
     let perm16 = Perm16::new_from_rng_128(&mut thread_rng());
@@ -81,7 +81,7 @@ fn main() -> Result<(), impl Debug> {
         VECTOR_LEN,
     > = VectorizedPoseidon2Air::new(constants);
 
-    let trace = air.generate_vectorized_trace_rows(NUM_PERMUTATIONS);
+    let trace = air.generate_vectorized_trace_rows(NUM_PERMUTATIONS, &mut thread_rng());
 
     let fri_config = create_benchmark_fri_config(challenge_mmcs);
     type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
@@ -95,7 +95,7 @@ fn main() -> Result<(), impl Debug> {
     let config = MyConfig::new(pcs);
 
     let mut challenger = Challenger::new(perm24.clone());
-    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+    let proof = prove(&config, &air, &mut challenger, trace, &vec![]).unwrap();
 
     let mut challenger = Challenger::new(perm24.clone());
     verify(&config, &air, &mut challenger, &proof, &vec![])
@@ -1,6 +1,7 @@
 use alloc::vec::Vec;
 
 use p3_field::Field;
+use p3_poseidon2::ExternalLayerConstants;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 
@@ -32,6 +33,30 @@ impl<F: Field, const WIDTH: usize, const HALF_FULL_ROUNDS: usize, const PARTIAL_
         }
     }
 
+    /// The constants of an existing Poseidon2 permutation, so that the AIR proves exactly that
+    /// permutation.
+    ///
+    /// # Panics
+    /// Panics if the permutation's number of full or partial rounds differs from the AIR's.
+    pub fn from_poseidon2_constants(
+        external_constants: &ExternalLayerConstants<F, WIDTH>,
+        internal_constants: &[F],
+    ) -> Self {
+        let half_full_rounds = |constants: &Vec<[F; WIDTH]>| {
+            constants
+                .as_slice()
+                .try_into()
+                .expect("The permutation should have HALF_FULL_ROUNDS rounds on either side")
+        };
+        Self::new(
+            half_full_rounds(external_constants.get_initial_constants()),
+            internal_constants
+                .try_into()
+                .expect("The permutation should have PARTIAL_ROUNDS partial rounds"),
+            half_full_rounds(external_constants.get_terminal_constants()),
+        )
+    }
+
     pub fn from_rng<R: Rng>(rng: &mut R) -> Self
     where
         Standard: Distribution<F> + Distribution<[F; WIDTH]>,
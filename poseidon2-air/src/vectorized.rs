@@ -8,7 +8,7 @@ use p3_matrix::Matrix;
 use p3_poseidon2::GenericPoseidon2LinearLayers;
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
-use rand::random;
+use rand::Rng;
 
 use crate::air::eval;
 use crate::constants::RoundConstants;
@@ -181,13 +181,17 @@ impl<
         }
     }
 
-    pub fn generate_vectorized_trace_rows(&self, num_hashes: usize) -> RowMajorMatrix<F>
+    pub fn generate_vectorized_trace_rows<R: Rng>(
+        &self,
+        num_hashes: usize,
+        rng: &mut R,
+    ) -> RowMajorMatrix<F>
     where
         F: PrimeField,
         LinearLayers: GenericPoseidon2LinearLayers<F, WIDTH>,
         Standard: Distribution<[F; WIDTH]>,
     {
-        let inputs = (0..num_hashes).map(|_| random()).collect::<Vec<_>>();
+        let inputs = (0..num_hashes).map(|_| rng.gen()).collect::<Vec<_>>();
         generate_vectorized_trace_rows::<
             F,
             LinearLayers,
@@ -0,0 +1,129 @@
+use core::borrow::Borrow;
+use std::fmt::Debug;
+
+use p3_baby_bear::{BabyBear, GenericPoseidon2LinearLayersBabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::ExternalLayerConstants;
+use p3_poseidon2_air::{generate_trace_rows, Poseidon2Air, Poseidon2Cols, RoundConstants};
+use p3_symmetric::{PaddingFreeSponge, Permutation, TruncatedPermutation};
+use p3_uni_stark::{prove, verify, StarkConfig};
+use rand::distributions::Standard;
+use rand::{thread_rng, Rng};
+
+// The parameters of `Poseidon2BabyBear<16>`.
+const WIDTH: usize = 16;
+const SBOX_DEGREE: u64 = 7;
+const SBOX_REGISTERS: usize = 1;
+const HALF_FULL_ROUNDS: usize = 4;
+const PARTIAL_ROUNDS: usize = 13;
+
+const NUM_PERMUTATIONS: usize = 64;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+type Perm = Poseidon2BabyBear<WIDTH>;
+type LinearLayers = GenericPoseidon2LinearLayersBabyBear;
+type Cols<T> =
+    Poseidon2Cols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>;
+type Constants = RoundConstants<Val, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>;
+
+/// A permutation with random constants, and the same constants in the AIR's format.
+fn random_perm() -> (Perm, Constants) {
+    let mut rng = thread_rng();
+    let external_constants = ExternalLayerConstants::new_from_rng(2 * HALF_FULL_ROUNDS, &mut rng);
+    let internal_constants: Vec<Val> = (&mut rng)
+        .sample_iter(Standard)
+        .take(PARTIAL_ROUNDS)
+        .collect();
+    let constants =
+        RoundConstants::from_poseidon2_constants(&external_constants, &internal_constants);
+    let perm = Perm::new(external_constants, internal_constants);
+    (perm, constants)
+}
+
+fn random_inputs() -> Vec<[Val; WIDTH]> {
+    let mut rng = thread_rng();
+    (0..NUM_PERMUTATIONS).map(|_| rng.gen()).collect()
+}
+
+fn trace(inputs: Vec<[Val; WIDTH]>, constants: &Constants) -> RowMajorMatrix<Val> {
+    generate_trace_rows::<
+        Val,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >(inputs, constants)
+}
+
+#[test]
+fn trace_matches_permutation() {
+    let (perm, constants) = random_perm();
+    let inputs = random_inputs();
+    let trace = trace(inputs.clone(), &constants);
+
+    for (i, input) in inputs.into_iter().enumerate() {
+        let row = trace.row_slice(i);
+        let cols: &Cols<Val> = (*row).borrow();
+        assert_eq!(cols.inputs, input);
+        assert_eq!(
+            cols.ending_full_rounds[HALF_FULL_ROUNDS - 1].post,
+            perm.permute(input),
+            "permutation {i}"
+        );
+    }
+}
+
+#[test]
+fn prove_permutations() -> Result<(), impl Debug> {
+    let (perm, constants) = random_perm();
+
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    let hash = MyHash::new(perm.clone());
+
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    let compress = MyCompress::new(perm.clone());
+
+    type ValMmcs =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+    let val_mmcs = ValMmcs::new(hash, compress);
+
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+    type Dft = Radix2DitParallel<Val>;
+    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+    let config = MyConfig::new(pcs);
+
+    let trace = trace(random_inputs(), &constants);
+    let air: Poseidon2Air<
+        Val,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    > = Poseidon2Air::new(constants);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &vec![]).unwrap();
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &vec![])
+}
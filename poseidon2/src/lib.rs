@@ -22,11 +22,35 @@ use p3_field::{Field, FieldAlgebra, PrimeField, PrimeField64};
 use p3_symmetric::{CryptographicPermutation, Permutation};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-pub use round_numbers::poseidon2_round_numbers_128;
+pub use round_numbers::{check_round_numbers_128, poseidon2_round_numbers_128, RoundNumberError};
 
 const SUPPORTED_WIDTHS: [usize; 8] = [2, 3, 4, 8, 12, 16, 20, 24];
 
+/// A fixed set of round constants for a Poseidon2 instance, so that applications using the instance
+/// compute the same permutation without agreeing on how to seed an RNG.
+///
+/// It is implemented by the internal layer of the instance, for each width with a fixed set. The
+/// constants of a named set never change; a different set would get a new `NAME`.
+pub trait Poseidon2DefaultParams<F: 'static, const WIDTH: usize> {
+    /// The name and version of the set, e.g. `"poseidon2-babybear-16-v1"`.
+    const NAME: &'static str;
+
+    /// The round constants of the initial external rounds.
+    const EXTERNAL_INITIAL: &'static [[F; WIDTH]];
+
+    /// The round constants of the internal rounds.
+    const INTERNAL: &'static [F];
+
+    /// The round constants of the terminal external rounds.
+    const EXTERNAL_TERMINAL: &'static [[F; WIDTH]];
+}
+
 /// The Poseidon2 permutation.
+///
+/// `D` is the degree of the S-box `x -> x^D`. Each field has a preferred degree, fixed by its type
+/// aliases, e.g. `Poseidon2KoalaBear`, but any `D` coprime to `p - 1` can be used by naming this type
+/// directly, together with round numbers for that degree. The AVX2 and AVX512 layers of the
+/// MONTY-31 fields only have kernels for 3, 5 and 7, and fail to compile for other degrees.
 #[derive(Clone, Debug)]
 pub struct Poseidon2<F, ExternalPerm, InternalPerm, const WIDTH: usize, const D: u64> {
     /// The permutations used in External Rounds.
@@ -63,6 +87,33 @@ where
         }
     }
 
+    /// Create a new Poseidon2 configuration from a custom set of round constants, given as the
+    /// constants of the initial and terminal external rounds, and those of the internal rounds.
+    pub fn from_round_constants(
+        [initial, terminal]: [&[[FA::F; WIDTH]]; 2],
+        internal: &[FA::F],
+    ) -> Self {
+        Self::new(
+            ExternalLayerConstants::new(initial.to_vec(), terminal.to_vec()),
+            internal.to_vec(),
+        )
+    }
+
+    /// Create a new Poseidon2 configuration with the fixed round constants of
+    /// [`Poseidon2DefaultParams`].
+    pub fn default_params() -> Self
+    where
+        InternalPerm: Poseidon2DefaultParams<FA::F, WIDTH>,
+    {
+        Self::from_round_constants(
+            [
+                InternalPerm::EXTERNAL_INITIAL,
+                InternalPerm::EXTERNAL_TERMINAL,
+            ],
+            InternalPerm::INTERNAL,
+        )
+    }
+
     /// Create a new Poseidon2 configuration with random parameters.
     pub fn new_from_rng<R: Rng>(rounds_f: usize, rounds_p: usize, rng: &mut R) -> Self
     where
@@ -84,6 +135,9 @@ where
     InternalPerm: InternalLayerConstructor<FA>,
 {
     /// Create a new Poseidon2 configuration with 128 bit security and random rounds constants.
+    ///
+    /// The permutation depends on the RNG and how it was seeded, so only applications which share
+    /// both compute the same hashes. For interoperability, use [`Self::default_params`] instead.
     pub fn new_from_rng_128<R: Rng>(rng: &mut R) -> Self
     where
         Standard: Distribution<FA::F> + Distribution<[FA::F; WIDTH]>,
@@ -91,6 +145,35 @@ where
         let (rounds_f, rounds_p) = poseidon2_round_numbers_128::<FA::F>(WIDTH, D);
         Self::new_from_rng(rounds_f, rounds_p, rng)
     }
+
+    /// Create a new Poseidon2 configuration, as [`Self::new`], checking first that there are enough
+    /// rounds for 128 bit security with an S-box of degree `D`.
+    pub fn try_new(
+        external_constants: ExternalLayerConstants<FA::F, WIDTH>,
+        internal_constants: Vec<FA::F>,
+    ) -> Result<Self, RoundNumberError> {
+        check_round_numbers_128::<FA::F>(
+            WIDTH,
+            D,
+            2 * external_constants.get_initial_constants().len(),
+            internal_constants.len(),
+        )?;
+        Ok(Self::new(external_constants, internal_constants))
+    }
+
+    /// Create a new Poseidon2 configuration with random parameters, as [`Self::new_from_rng`],
+    /// checking first that there are enough rounds for 128 bit security with an S-box of degree `D`.
+    pub fn try_new_from_rng<R: Rng>(
+        rounds_f: usize,
+        rounds_p: usize,
+        rng: &mut R,
+    ) -> Result<Self, RoundNumberError>
+    where
+        Standard: Distribution<FA::F> + Distribution<[FA::F; WIDTH]>,
+    {
+        check_round_numbers_128::<FA::F>(WIDTH, D, rounds_f, rounds_p)?;
+        Ok(Self::new_from_rng(rounds_f, rounds_p, rng))
+    }
 }
 
 impl<FA, ExternalPerm, InternalPerm, const WIDTH: usize, const D: u64> Permutation<[FA; WIDTH]>
@@ -23,11 +23,20 @@
 //! https://github.com/0xPolygonZero/hash-constants/blob/master/calc_round_numbers.py
 //! Using the above analysis we can conclude that the round numbers are equal
 //! for all 31 bit primes and 64 bit primes respectively.
+//!
+//! [`round_numbers`] reimplements that script using exact integer arithmetic, treating the prime as
+//! `2^field_bits` which by the above is never less conservative than the real prime. The hard-coded
+//! tables in [`poseidon2_round_numbers_128`] are kept as a fast path and are checked against it.
+
+use alloc::vec;
+use alloc::vec::Vec;
 
 use gcd::Gcd;
 use p3_field::PrimeField64;
 
 /// Given a field, a width and an D return the number of full and partial rounds needed to achieve 128 bit security.
+///
+/// Combinations which are not in the precomputed tables are derived using [`round_numbers`].
 pub fn poseidon2_round_numbers_128<F: PrimeField64>(width: usize, d: u64) -> (usize, usize) {
     // Start by checking that d is a valid permutation.
     assert_eq!(d.gcd(F::ORDER_U64 - 1), 1);
@@ -47,7 +56,7 @@ pub fn poseidon2_round_numbers_128<F: PrimeField64>(width: usize, d: u64) -> (us
             (24, 7) => (8, 21),
             (24, 9) => (8, 21),
             (24, 11) => (8, 21),
-            _ => panic!("The given pair of width and D has not been checked for these fields"),
+            _ => round_numbers(prime_bit_number, width, d, 128),
         },
         64 => match (width, d) {
             (8, 3) => (8, 41),
@@ -65,8 +74,360 @@ pub fn poseidon2_round_numbers_128<F: PrimeField64>(width: usize, d: u64) -> (us
             (16, 7) => (8, 22),
             (16, 9) => (8, 20),
             (16, 11) => (8, 18),
-            _ => panic!("The given pair of width and D has not been checked for these fields"),
+            _ => round_numbers(prime_bit_number, width, d, 128),
         },
-        _ => panic!("The optimal parameters for that size of prime have not been computed."),
+        _ => round_numbers(prime_bit_number, width, d, 128),
+    }
+}
+
+/// A reason the round numbers of a Poseidon2 instance were rejected by [`check_round_numbers_128`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundNumberError {
+    /// `x -> x^d` is not a permutation of the field, as `d` is not coprime to `p - 1`.
+    SBoxNotPermutation { d: u64 },
+    /// Fewer rounds than [`poseidon2_round_numbers_128`] gives for this width and S-box degree.
+    TooFewRounds {
+        rounds_f: usize,
+        rounds_p: usize,
+        required_f: usize,
+        required_p: usize,
+    },
+}
+
+/// Check that `rounds_f` full and `rounds_p` partial rounds with the S-box `x -> x^d` give 128 bit
+/// security at the given width, i.e. that each is at least the number [`poseidon2_round_numbers_128`]
+/// gives, security margin included.
+///
+/// This is conservative: lowering one number while raising the other can also be secure, but such
+/// pairs cost more S-boxes and are rejected here.
+pub fn check_round_numbers_128<F: PrimeField64>(
+    width: usize,
+    d: u64,
+    rounds_f: usize,
+    rounds_p: usize,
+) -> Result<(), RoundNumberError> {
+    if d.gcd(F::ORDER_U64 - 1) != 1 {
+        return Err(RoundNumberError::SBoxNotPermutation { d });
+    }
+    let (required_f, required_p) = poseidon2_round_numbers_128::<F>(width, d);
+    if rounds_f < required_f || rounds_p < required_p {
+        return Err(RoundNumberError::TooFewRounds {
+            rounds_f,
+            rounds_p,
+            required_f,
+            required_p,
+        });
+    }
+    Ok(())
+}
+
+/// Compute the number of full and partial rounds for a field with `field_bits` bit primes, a given
+/// width and S-box degree, achieving `security_bits` bits of security.
+///
+/// This follows `calc_round_numbers.py`: it searches for the pair satisfying the statistical,
+/// interpolation, Groebner 1, 2, 3 and https://eprint.iacr.org/2023/537.pdf constraints which
+/// minimises the number of S-boxes, and then adds the standard security margin of 2 full rounds
+/// and 7.5% partial rounds. All comparisons are done exactly using integer arithmetic.
+///
+/// The caller is responsible for checking that `x -> x^sbox_degree` is a permutation of the field.
+pub fn round_numbers(
+    field_bits: u32,
+    width: usize,
+    sbox_degree: u64,
+    security_bits: u32,
+) -> (usize, usize) {
+    assert!(width >= 2, "The width must be at least 2");
+    assert!(sbox_degree >= 3, "The S-box degree must be at least 3");
+    assert!(security_bits > 0, "The security level must be positive");
+
+    let t = width as u64;
+    let constraints = Constraints::new(field_bits, t, sbox_degree, security_bits);
+    // (cost, full rounds, partial rounds)
+    let mut best: Option<(u64, u64, u64)> = None;
+    for rounds_p in 1..500_u64 {
+        // Ceiling of 1.075 * rounds_p.
+        let margin_p = (rounds_p * 43 + 39) / 40;
+        if best.is_some_and(|(cost, _, _)| 6 * t + margin_p > cost) {
+            // The cost only goes up from here.
+            break;
+        }
+        // The constraints are monotone in the number of full rounds, so only the smallest one
+        // satisfying them can be optimal for this number of partial rounds.
+        let rounds_f = (4..100_u64)
+            .step_by(2)
+            .find(|&rounds_f| constraints.are_satisfied(rounds_f, rounds_p));
+        if let Some(rounds_f) = rounds_f {
+            let margin_f = rounds_f + 2;
+            let cost = t * margin_f + margin_p;
+            let improves = match best {
+                None => true,
+                Some((best_cost, best_f, _)) => {
+                    cost < best_cost || (cost == best_cost && margin_f < best_f)
+                }
+            };
+            if improves {
+                best = Some((cost, margin_f, margin_p));
+            }
+        }
+    }
+
+    let (_, rounds_f, rounds_p) =
+        best.expect("No round numbers achieve the given security level for these parameters");
+    (rounds_f as usize, rounds_p as usize)
+}
+
+/// The security constraints for a prime of `n` bits, width `t`, S-box degree `alpha` and
+/// security level `m`.
+struct Constraints {
+    n: u32,
+    t: u64,
+    alpha: u64,
+    m: u32,
+    /// The minimal number of full rounds given by the statistical constraint.
+    statistical: i64,
+    /// `1 + ceil(log_alpha(2) * min(M, n)) + ceil(log_alpha(t))`, from the interpolation constraint.
+    interpolation: i64,
+}
+
+impl Constraints {
+    fn new(n: u32, t: u64, alpha: u64, m: u32) -> Self {
+        // floor(log2(p) - (alpha - 1) / 2) * (t + 1) >= M requires 6 full rounds, 10 otherwise.
+        let statistical =
+            if m as i64 <= (2 * n as i64 - alpha as i64 + 1).div_euclid(2) * (t as i64 + 1) {
+                6
+            } else {
+                10
+            };
+
+        let log_alpha_two_pow_x = (0..)
+            .find(|&k| pow_at_least_pow2(alpha, k, m.min(n)))
+            .unwrap();
+        let log_alpha_t = (0..).find(|&k| pow_at_least(alpha, k, t)).unwrap();
+
+        Self {
+            n,
+            t,
+            alpha,
+            m,
+            statistical,
+            interpolation: 1 + log_alpha_two_pow_x as i64 + log_alpha_t as i64,
+        }
+    }
+
+    /// Whether `rounds_f` full and `rounds_p` partial rounds, without security margin, satisfy
+    /// every constraint.
+    fn are_satisfied(&self, rounds_f: u64, rounds_p: u64) -> bool {
+        let (n, t, alpha, m) = (self.n, self.t, self.alpha, self.m);
+        let (rf, rp, t_i) = (rounds_f as i64, rounds_p as i64, t as i64);
+
+        // Statistical and interpolation: RF >= 1 + ceil(log_alpha(2) * min(M, n)) + ceil(log_alpha(t)) - RP.
+        if rf < self.statistical || rf < self.interpolation - rp {
+            return false;
+        }
+
+        // Groebner 1: RF >= log_alpha(2) * min(M, log2(p)) - RP.
+        if !pow_at_least_pow2(alpha, rounds_f + rounds_p, m.min(n)) {
+            return false;
+        }
+
+        // Groebner 2: RF >= t - 1 + log_alpha(2) * min(M / (t + 1), log2(p) / 2) - RP.
+        let c = rf - t_i + 1 + rp;
+        if c < 0
+            || !(pow_at_least_pow2(alpha, c as u64 * (t + 1), m)
+                || pow_at_least_pow2(alpha, 2 * c as u64, n))
+        {
+            return false;
+        }
+
+        // Groebner 3: RF >= (t - 2 + M / (2 * log2(alpha)) - RP) / (t - 1).
+        let c = rf * (t_i - 1) - t_i + 2 + rp;
+        if c <= 0 || !pow_at_least_pow2(alpha, 2 * c as u64, m) {
+            return false;
+        }
+
+        // https://eprint.iacr.org/2023/537.pdf: ceil(2 * log2(binomial(over, under))) >= M.
+        let r = t / 3;
+        let under = r * rounds_f / 2 + rounds_p + alpha;
+        let over = (rounds_f - 1) * t + rounds_p + r + under;
+        binomial_squared_exceeds_pow2(over, under, m - 1)
+    }
+}
+
+/// Check whether `base^exp >= bound`.
+fn pow_at_least(base: u64, exp: u64, bound: u64) -> bool {
+    let mut power = 1_u64;
+    for _ in 0..exp {
+        if power >= bound {
+            return true;
+        }
+        power = power.saturating_mul(base);
+    }
+    power >= bound
+}
+
+/// Check whether `base^exp >= 2^bits`.
+fn pow_at_least_pow2(base: u64, exp: u64, bits: u32) -> bool {
+    let mut power = vec![1];
+    for _ in 0..exp {
+        if bit_length(&power) > bits as usize {
+            return true;
+        }
+        mul_small(&mut power, base);
+    }
+    bit_length(&power) > bits as usize
+}
+
+/// Check whether `binomial(n, k)^2 > 2^bits`.
+fn binomial_squared_exceeds_pow2(n: u64, k: u64, bits: u32) -> bool {
+    let k = k.min(n - k);
+    // binomial(n, i) is increasing for i <= n / 2, so we can stop as soon as it is large enough.
+    let mut binomial = vec![1];
+    for i in 0..k {
+        mul_small(&mut binomial, n - i);
+        div_small_exact(&mut binomial, i + 1);
+        if 2 * (bit_length(&binomial) - 1) > bits as usize {
+            return true;
+        }
+    }
+    // A square exceeds 2^bits if it is at least 2^(bits + 1), or has bits + 1 bits and is not a power of 2.
+    let square = mul(&binomial, &binomial);
+    let length = bit_length(&square);
+    length > bits as usize + 1
+        || (length == bits as usize + 1
+            && square.iter().map(|limb| limb.count_ones()).sum::<u32>() > 1)
+}
+
+/// The number of bits needed to represent the little-endian multi-limb integer `a`.
+fn bit_length(a: &[u64]) -> usize {
+    a.iter()
+        .rposition(|&limb| limb != 0)
+        .map_or(0, |i| 64 * (i + 1) - a[i].leading_zeros() as usize)
+}
+
+fn mul_small(a: &mut Vec<u64>, b: u64) {
+    let mut carry = 0;
+    for limb in a.iter_mut() {
+        let product = *limb as u128 * b as u128 + carry;
+        *limb = product as u64;
+        carry = product >> 64;
+    }
+    if carry != 0 {
+        a.push(carry as u64);
+    }
+}
+
+fn div_small_exact(a: &mut [u64], b: u64) {
+    let mut remainder = 0_u128;
+    for limb in a.iter_mut().rev() {
+        let current = (remainder << 64) | *limb as u128;
+        *limb = (current / b as u128) as u64;
+        remainder = current % b as u128;
+    }
+    debug_assert_eq!(remainder, 0);
+}
+
+fn mul(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let mut result = vec![0; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0_u128;
+        for (j, &y) in b.iter().enumerate() {
+            let current = result[i + j] as u128 + x as u128 * y as u128 + carry;
+            result[i + j] = current as u64;
+            carry = current >> 64;
+        }
+        result[i + b.len()] = carry as u64;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_goldilocks::Goldilocks;
+    use p3_koala_bear::KoalaBear;
+    use p3_mersenne_31::Mersenne31;
+
+    use super::*;
+
+    fn check_table<F: PrimeField64>(widths: &[usize]) {
+        let field_bits = F::ORDER_U64.ilog2() + 1;
+        for &width in widths {
+            for d in [3, 5, 7, 9, 11] {
+                if d.gcd(F::ORDER_U64 - 1) != 1 {
+                    continue;
+                }
+                assert_eq!(
+                    poseidon2_round_numbers_128::<F>(width, d),
+                    round_numbers(field_bits, width, d, 128),
+                    "width {width}, D = {d}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matches_table_31_bit() {
+        check_table::<BabyBear>(&[16, 24]);
+        check_table::<KoalaBear>(&[16, 24]);
+        check_table::<Mersenne31>(&[16, 24]);
+    }
+
+    #[test]
+    fn matches_table_64_bit() {
+        check_table::<Goldilocks>(&[8, 12, 16]);
+    }
+
+    #[test]
+    fn matches_published_values() {
+        // Every entry of the precomputed tables, independent of which D the fields admit.
+        let table_31 = [
+            (16, 3, 20),
+            (16, 5, 14),
+            (16, 7, 13),
+            (24, 3, 23),
+            (24, 5, 22),
+            (24, 7, 21),
+        ];
+        for (width, d, rounds_p) in table_31 {
+            assert_eq!(round_numbers(31, width, d, 128), (8, rounds_p));
+        }
+        let table_64 = [
+            (8, 3, 41),
+            (8, 11, 17),
+            (12, 9, 20),
+            (16, 3, 42),
+            (16, 7, 22),
+        ];
+        for (width, d, rounds_p) in table_64 {
+            assert_eq!(round_numbers(64, width, d, 128), (8, rounds_p));
+        }
+    }
+
+    #[test]
+    fn check_round_numbers() {
+        // KoalaBear admits 3 and 7, which need 20 and 13 partial rounds at width 16.
+        assert_eq!(check_round_numbers_128::<KoalaBear>(16, 3, 8, 20), Ok(()));
+        assert_eq!(check_round_numbers_128::<KoalaBear>(16, 7, 10, 13), Ok(()));
+        assert_eq!(
+            check_round_numbers_128::<KoalaBear>(16, 3, 8, 13),
+            Err(RoundNumberError::TooFewRounds {
+                rounds_f: 8,
+                rounds_p: 13,
+                required_f: 8,
+                required_p: 20,
+            })
+        );
+        assert!(check_round_numbers_128::<KoalaBear>(16, 7, 6, 30).is_err());
+        // p - 1 is divisible by 3 for BabyBear.
+        assert_eq!(
+            check_round_numbers_128::<BabyBear>(16, 3, 8, 20),
+            Err(RoundNumberError::SBoxNotPermutation { d: 3 })
+        );
+    }
+
+    #[test]
+    fn falls_back_outside_table() {
+        assert_eq!(poseidon2_round_numbers_128::<Goldilocks>(24, 7), (8, 22));
+        assert_eq!(poseidon2_round_numbers_128::<BabyBear>(8, 7), (8, 12));
     }
 }
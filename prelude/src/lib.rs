@@ -0,0 +1,149 @@
+//! A curated, stable entry point into Plonky3.
+//!
+//! The individual `p3-*` crates expose many internal building blocks whose paths and names change
+//! between releases. This crate re-exports the subset needed to build an end-to-end proving
+//! pipeline (fields, hashes, commitment schemes, the STARK prover and verifier, and a few AIRs)
+//! under module paths which are kept stable, so that downstream projects can depend on it alone.
+//!
+//! Items are only added here once they are considered part of the public API; removing or
+//! renaming one is a breaking change for this crate, even if the underlying crate moved it.
+
+#![no_std]
+
+/// Field traits and extension fields.
+pub mod field {
+    #[doc(inline)]
+    pub use p3_field::extension::{BinomialExtensionField, ComplexBinomialExtensionField};
+    #[doc(inline)]
+    pub use p3_field::{
+        ExtensionField, Field, FieldAlgebra, PackedField, PackedValue, PrimeField, PrimeField32,
+        PrimeField64, TwoAdicField,
+    };
+}
+
+/// The BabyBear field and its Poseidon2 instantiations.
+pub mod baby_bear {
+    #[doc(inline)]
+    pub use p3_baby_bear::{BabyBear, GenericPoseidon2LinearLayersBabyBear, Poseidon2BabyBear};
+}
+
+/// The KoalaBear field and its Poseidon2 instantiations.
+pub mod koala_bear {
+    #[doc(inline)]
+    pub use p3_koala_bear::{GenericPoseidon2LinearLayersKoalaBear, KoalaBear, Poseidon2KoalaBear};
+}
+
+/// The Mersenne31 field and its Poseidon2 instantiations.
+pub mod mersenne_31 {
+    #[doc(inline)]
+    pub use p3_mersenne_31::{
+        GenericPoseidon2LinearLayersMersenne31, Mersenne31, Poseidon2Mersenne31,
+    };
+}
+
+/// The Goldilocks field and its Poseidon2 instantiations.
+pub mod goldilocks {
+    #[doc(inline)]
+    pub use p3_goldilocks::{Goldilocks, Poseidon2Goldilocks};
+}
+
+/// Dense matrices and the matrix trait.
+pub mod matrix {
+    #[doc(inline)]
+    pub use p3_matrix::bitrev::BitReversedMatrixView;
+    #[doc(inline)]
+    pub use p3_matrix::dense::RowMajorMatrix;
+    #[doc(inline)]
+    pub use p3_matrix::{Dimensions, Matrix};
+}
+
+/// Discrete Fourier transforms over two-adic subgroups.
+pub mod dft {
+    #[doc(inline)]
+    pub use p3_dft::{Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+    #[doc(inline)]
+    pub use p3_monty_31::dft::RecursiveDft;
+}
+
+/// Permutations, hashers and compression functions.
+pub mod symmetric {
+    #[doc(inline)]
+    pub use p3_symmetric::{
+        CompressionFunctionFromHasher, CryptographicHasher, CryptographicPermutation,
+        PaddingFreeSponge, Permutation, PseudoCompressionFunction, SerializingHasher32,
+        SerializingHasher32To64, SerializingHasher64, TruncatedPermutation,
+    };
+}
+
+/// Poseidon2 linear layers, for code which is generic over the field.
+pub mod poseidon2 {
+    #[doc(inline)]
+    pub use p3_poseidon2::{ExternalLayerConstants, GenericPoseidon2LinearLayers};
+}
+
+/// The Keccak permutation and Keccak-256 hash.
+pub mod keccak {
+    #[doc(inline)]
+    pub use p3_keccak::{Keccak256Hash, KeccakF, VECTOR_LEN};
+}
+
+/// Fiat-Shamir challengers.
+pub mod challenger {
+    #[doc(inline)]
+    pub use p3_challenger::{
+        CanObserve, CanSample, CanSampleBits, DuplexChallenger, FieldChallenger,
+        GrindingChallenger, HashChallenger, SerializingChallenger32, SerializingChallenger64,
+    };
+}
+
+/// Mixed matrix commitment schemes and polynomial commitment schemes.
+pub mod commit {
+    #[doc(inline)]
+    pub use p3_commit::{ExtensionMmcs, Mmcs, Pcs, PolynomialSpace};
+    #[doc(inline)]
+    pub use p3_merkle_tree::MerkleTreeMmcs;
+}
+
+/// FRI-based polynomial commitment schemes.
+pub mod fri {
+    #[doc(inline)]
+    pub use p3_circle::CirclePcs;
+    #[doc(inline)]
+    pub use p3_fri::{
+        create_benchmark_fri_config, create_test_fri_config, FriConfig, TwoAdicFriPcs,
+    };
+}
+
+/// Control over the thread pool used by parallel phases.
+pub mod parallelism {
+    #[doc(inline)]
+    pub use p3_maybe_rayon::ParallelismConfig;
+}
+
+/// AIR definition traits.
+pub mod air {
+    #[doc(inline)]
+    pub use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+}
+
+/// Ready-made AIRs for common hash functions.
+pub mod airs {
+    #[doc(inline)]
+    pub use p3_blake3_air::Blake3Air;
+    #[doc(inline)]
+    pub use p3_keccak_air::KeccakAir;
+    #[doc(inline)]
+    pub use p3_poseidon2_air::{
+        generate_trace_rows as generate_poseidon2_trace_rows, Poseidon2Air, Poseidon2Cols,
+        RoundConstants, VectorizedPoseidon2Air,
+    };
+}
+
+/// The univariate STARK prover and verifier.
+pub mod stark {
+    #[doc(inline)]
+    pub use p3_uni_stark::{
+        prove, verify, Proof, StarkConfig, StarkGenericConfig, SymbolicExpression,
+        VerificationError,
+    };
+}
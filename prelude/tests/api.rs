@@ -0,0 +1,70 @@
+//! Every item below is part of the stable API of `p3-prelude`. If one of them is removed or moved
+//! by an underlying crate, this file stops compiling.
+
+#![allow(unused_imports)]
+
+use p3_prelude::air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_prelude::airs::{
+    Blake3Air, KeccakAir, Poseidon2Air, RoundConstants, VectorizedPoseidon2Air,
+};
+use p3_prelude::baby_bear::{BabyBear, GenericPoseidon2LinearLayersBabyBear, Poseidon2BabyBear};
+use p3_prelude::challenger::{
+    CanObserve, CanSample, CanSampleBits, DuplexChallenger, FieldChallenger, GrindingChallenger,
+    HashChallenger, SerializingChallenger32, SerializingChallenger64,
+};
+use p3_prelude::commit::{ExtensionMmcs, MerkleTreeMmcs, Mmcs, Pcs, PolynomialSpace};
+use p3_prelude::dft::{Radix2Dit, Radix2DitParallel, RecursiveDft, TwoAdicSubgroupDft};
+use p3_prelude::field::{
+    BinomialExtensionField, ExtensionField, Field, FieldAlgebra, PackedField, PackedValue,
+    PrimeField, PrimeField32, PrimeField64, TwoAdicField,
+};
+use p3_prelude::fri::{
+    create_benchmark_fri_config, create_test_fri_config, CirclePcs, FriConfig, TwoAdicFriPcs,
+};
+use p3_prelude::goldilocks::{Goldilocks, Poseidon2Goldilocks};
+use p3_prelude::keccak::{Keccak256Hash, KeccakF, VECTOR_LEN};
+use p3_prelude::koala_bear::{
+    GenericPoseidon2LinearLayersKoalaBear, KoalaBear, Poseidon2KoalaBear,
+};
+use p3_prelude::matrix::{BitReversedMatrixView, Dimensions, Matrix, RowMajorMatrix};
+use p3_prelude::mersenne_31::{
+    GenericPoseidon2LinearLayersMersenne31, Mersenne31, Poseidon2Mersenne31,
+};
+use p3_prelude::parallelism::ParallelismConfig;
+use p3_prelude::poseidon2::GenericPoseidon2LinearLayers;
+use p3_prelude::stark::{
+    prove, verify, Proof, StarkConfig, StarkGenericConfig, SymbolicExpression, VerificationError,
+};
+use p3_prelude::symmetric::{
+    CompressionFunctionFromHasher, CryptographicHasher, CryptographicPermutation,
+    PaddingFreeSponge, Permutation, PseudoCompressionFunction, SerializingHasher32,
+    SerializingHasher32To64, SerializingHasher64, TruncatedPermutation,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type MyMmcs = MerkleTreeMmcs<
+    <BabyBear as Field>::Packing,
+    <BabyBear as Field>::Packing,
+    MyHash,
+    MyCompress,
+    8,
+>;
+
+#[test]
+fn facade_is_enough_to_commit_and_open() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+
+    let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 16, 4);
+    let dims = [matrix.dimensions()];
+    let (commit, data) = mmcs.commit_matrix(matrix);
+
+    let (opened_values, proof) = mmcs.open_batch(5, &data);
+    mmcs.verify_batch(&commit, &dims, 5, &opened_values, &proof)
+        .unwrap();
+}
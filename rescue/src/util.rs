@@ -2,7 +2,6 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use gcd::Gcd;
-use modinverse::modinverse;
 use p3_field::PrimeField64;
 use sha3::digest::{ExtendableOutput, Update, XofReader};
 use sha3::Shake256;
@@ -16,12 +15,17 @@ pub(crate) fn get_alpha<F: PrimeField64>() -> u64 {
 
 /// Given alpha, find its multiplicative inverse in `Z/⟨p − 1⟩`.
 pub(crate) fn get_inverse<F: PrimeField64>(alpha: u64) -> u64 {
-    let p = F::ORDER_U64 as i128;
-    modinverse(alpha as i128, p - 1)
-        .expect("x^alpha not a permutation")
-        .unsigned_abs()
-        .try_into()
-        .unwrap()
+    let modulus = (F::ORDER_U64 - 1) as i128;
+    // The extended Euclidean algorithm, tracking only the coefficient of alpha.
+    let (mut r0, mut r1) = (modulus, alpha as i128);
+    let (mut t0, mut t1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    assert_eq!(r0, 1, "x^alpha not a permutation");
+    t0.rem_euclid(modulus) as u64
 }
 
 /// Compute the SHAKE256 variant of SHA-3.
@@ -34,3 +38,27 @@ pub(crate) fn shake256_hash(seed_bytes: &[u8], num_bytes: usize) -> Vec<u8> {
     reader.read(&mut result);
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_goldilocks::Goldilocks;
+    use p3_mersenne_31::Mersenne31;
+
+    use super::*;
+
+    fn check_inverse<F: PrimeField64>() {
+        let alpha = get_alpha::<F>();
+        let inverse = get_inverse::<F>(alpha);
+        let modulus = F::ORDER_U64 - 1;
+        assert!(inverse < modulus);
+        assert_eq!((alpha as u128 * inverse as u128) % modulus as u128, 1);
+    }
+
+    #[test]
+    fn test_get_inverse() {
+        check_inverse::<BabyBear>();
+        check_inverse::<Goldilocks>();
+        check_inverse::<Mersenne31>();
+    }
+}
@@ -46,8 +46,9 @@ pub struct Sha256Compress;
 impl PseudoCompressionFunction<[u8; 32], 2> for Sha256Compress {
     fn compress(&self, input: [[u8; 32]; 2]) -> [u8; 32] {
         let mut state = H256_256;
-        // GenericArray<u8, U64> has same memory layout as [u8; 64]
-        let block: GenericArray<u8, U64> = unsafe { core::mem::transmute(input) };
+        let mut block = GenericArray::<u8, U64>::default();
+        block[..32].copy_from_slice(&input[0]);
+        block[32..].copy_from_slice(&input[1]);
         sha2::compress256(&mut state, &[block]);
 
         let mut output = [0u8; 32];
@@ -1,3 +1,7 @@
+use core::marker::PhantomData;
+
+use p3_field::{ExtensionField, Field, FieldExtensionAlgebra};
+
 use crate::hasher::CryptographicHasher;
 use crate::permutation::CryptographicPermutation;
 
@@ -13,6 +17,13 @@ pub trait PseudoCompressionFunction<T, const N: usize>: Clone {
 /// An `N`-to-1 compression function.
 pub trait CompressionFunction<T, const N: usize>: PseudoCompressionFunction<T, N> {}
 
+/// Compresses `N` digests of `CHUNK` elements by permuting them together and keeping the first
+/// `CHUNK` elements of the result.
+///
+/// Input `i` fills lanes `i * CHUNK..(i + 1) * CHUNK` of the permutation's state, and the trailing
+/// `WIDTH - N * CHUNK` lanes are capacity, set to `T::default()` (zero, for fields). For example a
+/// 2-to-1 compression of 8-element digests with a width-24 permutation has 8 capacity lanes,
+/// `16..24`. Using more lanes than the permutation has, `N * CHUNK > WIDTH`, fails to compile.
 #[derive(Clone, Debug)]
 pub struct TruncatedPermutation<InnerP, const N: usize, const CHUNK: usize, const WIDTH: usize> {
     inner_permutation: InnerP,
@@ -22,6 +33,12 @@ impl<InnerP, const N: usize, const CHUNK: usize, const WIDTH: usize>
     TruncatedPermutation<InnerP, N, CHUNK, WIDTH>
 {
     pub const fn new(inner_permutation: InnerP) -> Self {
+        const {
+            assert!(
+                N * CHUNK <= WIDTH,
+                "TruncatedPermutation needs N * CHUNK <= WIDTH lanes"
+            )
+        };
         Self { inner_permutation }
     }
 }
@@ -33,10 +50,15 @@ where
     InnerP: CryptographicPermutation<[T; WIDTH]>,
 {
     fn compress(&self, input: [[T; CHUNK]; N]) -> [T; CHUNK] {
-        debug_assert!(CHUNK * N <= WIDTH);
+        const {
+            assert!(
+                N * CHUNK <= WIDTH,
+                "TruncatedPermutation needs N * CHUNK <= WIDTH lanes"
+            )
+        };
         let mut pre = [T::default(); WIDTH];
-        for i in 0..N {
-            pre[i * CHUNK..(i + 1) * CHUNK].copy_from_slice(&input[i]);
+        for (lanes, digest) in pre.chunks_exact_mut(CHUNK).zip(input) {
+            lanes.copy_from_slice(&digest);
         }
         let post = self.inner_permutation.permute(pre);
         post[..CHUNK].try_into().unwrap()
@@ -72,3 +94,57 @@ where
     H: CryptographicHasher<T, [T; CHUNK]>,
 {
 }
+
+/// Compresses digests of extension field elements with a compression function over the base field.
+///
+/// Each `[EF; CHUNK]` digest is flattened to the `BASE_CHUNK = CHUNK * EF::D` base field
+/// coefficients of its elements in order, each element contributing its coefficients in the order
+/// of [`as_base_slice`](FieldExtensionAlgebra::as_base_slice). The inner compression's output is
+/// read back the same way.
+#[derive(Clone, Debug)]
+pub struct ExtensionCompression<F, EF, InnerC, const BASE_CHUNK: usize> {
+    inner: InnerC,
+    _phantom: PhantomData<(F, EF)>,
+}
+
+impl<F, EF, InnerC, const BASE_CHUNK: usize> ExtensionCompression<F, EF, InnerC, BASE_CHUNK> {
+    pub const fn new(inner: InnerC) -> Self {
+        Self {
+            inner,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F, EF, InnerC, const N: usize, const CHUNK: usize, const BASE_CHUNK: usize>
+    PseudoCompressionFunction<[EF; CHUNK], N> for ExtensionCompression<F, EF, InnerC, BASE_CHUNK>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    InnerC: PseudoCompressionFunction<[F; BASE_CHUNK], N>,
+{
+    fn compress(&self, input: [[EF; CHUNK]; N]) -> [EF; CHUNK] {
+        let d = <EF as FieldExtensionAlgebra<F>>::D;
+        assert_eq!(BASE_CHUNK, CHUNK * d);
+        let flattened = input.map(|digest| {
+            let mut base = [F::ZERO; BASE_CHUNK];
+            for (coeffs, x) in base.chunks_exact_mut(d).zip(digest) {
+                coeffs.copy_from_slice(<EF as FieldExtensionAlgebra<F>>::as_base_slice(&x));
+            }
+            base
+        });
+        let output = self.inner.compress(flattened);
+        core::array::from_fn(|i| {
+            <EF as FieldExtensionAlgebra<F>>::from_base_slice(&output[i * d..(i + 1) * d])
+        })
+    }
+}
+
+impl<F, EF, InnerC, const N: usize, const CHUNK: usize, const BASE_CHUNK: usize>
+    CompressionFunction<[EF; CHUNK], N> for ExtensionCompression<F, EF, InnerC, BASE_CHUNK>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    InnerC: CompressionFunction<[F; BASE_CHUNK], N>,
+{
+}
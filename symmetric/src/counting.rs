@@ -0,0 +1,77 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use p3_field::PackedValue;
+
+use crate::{CryptographicPermutation, Permutation};
+
+/// A permutation which counts its calls, once for each lane of a packed call, e.g. to check an
+/// estimate of a verifier's work against what it does. Clones share their count.
+#[derive(Clone, Debug)]
+pub struct CountingPermutation<P> {
+    inner: P,
+    calls: Arc<AtomicUsize>,
+}
+
+impl<P> CountingPermutation<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// The calls made since this permutation, or any of its clones, was created or last reset.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.calls.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<T, P, const WIDTH: usize> Permutation<[T; WIDTH]> for CountingPermutation<P>
+where
+    T: PackedValue,
+    P: Permutation<[T; WIDTH]>,
+{
+    fn permute_mut(&self, input: &mut [T; WIDTH]) {
+        self.calls.fetch_add(T::WIDTH, Ordering::Relaxed);
+        self.inner.permute_mut(input);
+    }
+}
+
+impl<T, P, const WIDTH: usize> CryptographicPermutation<[T; WIDTH]> for CountingPermutation<P>
+where
+    T: PackedValue,
+    P: CryptographicPermutation<[T; WIDTH]>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{Field, FieldAlgebra};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct Identity;
+
+    impl<T: Clone> Permutation<T> for Identity {
+        fn permute_mut(&self, _input: &mut T) {}
+    }
+
+    #[test]
+    fn counts_each_lane() {
+        type Packing = <BabyBear as Field>::Packing;
+        let perm = CountingPermutation::new(Identity);
+        let clone = perm.clone();
+        perm.permute([BabyBear::ZERO; 4]);
+        clone.permute([Packing::ZERO; 4]);
+        assert_eq!(perm.calls(), 1 + Packing::WIDTH);
+        perm.reset();
+        assert_eq!(clone.calls(), 0);
+    }
+}
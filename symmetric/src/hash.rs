@@ -1,6 +1,8 @@
+use alloc::vec::Vec;
 use core::borrow::Borrow;
 use core::marker::PhantomData;
 
+use p3_util::encoding::{CanonicalEncoding, DecodingError};
 use serde::{Deserialize, Serialize};
 
 /// A wrapper around an array digest, with a phantom type parameter to ensure that the digest is
@@ -13,6 +15,18 @@ pub struct Hash<F, W, const DIGEST_ELEMS: usize> {
     _marker: PhantomData<F>,
 }
 
+impl<F, W: CanonicalEncoding, const DIGEST_ELEMS: usize> CanonicalEncoding
+    for Hash<F, W, DIGEST_ELEMS>
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.value.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        <[W; DIGEST_ELEMS]>::decode(input).map(Self::from)
+    }
+}
+
 impl<F, W, const DIGEST_ELEMS: usize> From<[W; DIGEST_ELEMS]> for Hash<F, W, DIGEST_ELEMS> {
     fn from(value: [W; DIGEST_ELEMS]) -> Self {
         Self {
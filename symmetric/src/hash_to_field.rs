@@ -0,0 +1,120 @@
+//! Deriving field elements and indices from arbitrary bytes, such as a block hash, for randomness
+//! which doesn't come from a challenger's transcript.
+//!
+//! The bytes are first expanded with `expand_message_xmd` from RFC 9380, instantiated with a
+//! hasher giving 32-byte digests such as blake3 or Keccak-256. Each value in `[0, m)` is then read
+//! from `ceil((ceil(log2(m)) + 128) / 8)` of the expanded bytes, as a big-endian integer reduced
+//! mod `m`. That integer is uniform in `[0, 2^(8L))` for `L` bytes, so reducing it gives each
+//! residue with probability `floor(2^(8L) / m) / 2^(8L)` or one more than that, and the
+//! statistical distance from the uniform distribution is below `m / 2^(8L) <= 2^-128`.
+
+use alloc::vec::Vec;
+use core::array;
+
+use p3_field::PrimeField64;
+
+use crate::CryptographicHasher;
+
+/// The number of bits by which each value is oversampled, which bounds its bias by `2^-128`.
+const SECURITY_BITS: usize = 128;
+
+/// The number of zero bytes which the message is prefixed with, `s_in_bytes` in RFC 9380. This is
+/// the block size of blake3; hashers with other block sizes lose none of the construction's
+/// security, but their outputs differ from the RFC's.
+const ZERO_PAD_BYTES: usize = 64;
+
+/// Expand `msg` into `len` pseudorandom bytes, following `expand_message_xmd` from RFC 9380 with
+/// `domain_tag` as its domain separation tag.
+///
+/// # Panics
+/// If `len` is more than `255 * 32` bytes, or `domain_tag` is longer than 255 bytes.
+pub fn expand_message_xmd<H>(hasher: &H, domain_tag: &[u8], msg: &[u8], len: usize) -> Vec<u8>
+where
+    H: CryptographicHasher<u8, [u8; 32]>,
+{
+    let num_blocks = len.div_ceil(32);
+    assert!(num_blocks <= 255, "can't expand a message to {len} bytes");
+    assert!(domain_tag.len() <= 255, "the domain tag is too long");
+    let tag_len = [domain_tag.len() as u8];
+
+    let b_0 = hasher.hash_iter_slices([
+        &[0; ZERO_PAD_BYTES][..],
+        msg,
+        &(len as u16).to_be_bytes(),
+        &[0],
+        domain_tag,
+        &tag_len,
+    ]);
+    let mut b_i = hasher.hash_iter_slices([&b_0[..], &[1], domain_tag, &tag_len]);
+    let mut bytes = b_i.to_vec();
+    for i in 2..=num_blocks {
+        let mixed: [u8; 32] = array::from_fn(|j| b_0[j] ^ b_i[j]);
+        b_i = hasher.hash_iter_slices([&mixed[..], &[i as u8], domain_tag, &tag_len]);
+        bytes.extend_from_slice(&b_i);
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+/// Derive `count` field elements from `msg`, with a bias of at most `2^-128` each.
+///
+/// # Panics
+/// If the elements take more bytes than [`expand_message_xmd`] can give, e.g. more than 408
+/// elements of a 31-bit field.
+pub fn hash_to_field<F, H>(hasher: &H, domain_tag: &[u8], msg: &[u8], count: usize) -> Vec<F>
+where
+    F: PrimeField64,
+    H: CryptographicHasher<u8, [u8; 32]>,
+{
+    sample_below(hasher, domain_tag, msg, F::ORDER_U64, count)
+        .into_iter()
+        .map(F::from_canonical_u64)
+        .collect()
+}
+
+/// Derive `count` indices into a domain of `domain_size` elements from `msg`, with a bias of at
+/// most `2^-128` each. The indices are drawn independently, so they may repeat.
+///
+/// # Panics
+/// If `domain_size` is zero, or the indices take more bytes than [`expand_message_xmd`] can give.
+pub fn hash_to_indices<H>(
+    hasher: &H,
+    domain_tag: &[u8],
+    msg: &[u8],
+    domain_size: usize,
+    count: usize,
+) -> Vec<usize>
+where
+    H: CryptographicHasher<u8, [u8; 32]>,
+{
+    assert_ne!(domain_size, 0, "can't sample from an empty domain");
+    sample_below(hasher, domain_tag, msg, domain_size as u64, count)
+        .into_iter()
+        .map(|index| index as usize)
+        .collect()
+}
+
+/// `count` values in `[0, modulus)`, each reduced from its own oversampled bytes.
+fn sample_below<H>(
+    hasher: &H,
+    domain_tag: &[u8],
+    msg: &[u8],
+    modulus: u64,
+    count: usize,
+) -> Vec<u64>
+where
+    H: CryptographicHasher<u8, [u8; 32]>,
+{
+    let modulus_bits = (u64::BITS - (modulus - 1).leading_zeros()) as usize;
+    let bytes_per_value = (modulus_bits + SECURITY_BITS).div_ceil(8);
+    expand_message_xmd(hasher, domain_tag, msg, count * bytes_per_value)
+        .chunks_exact(bytes_per_value)
+        .map(|bytes| {
+            // Horner's rule keeps the accumulator below `modulus` between steps, so it fits in a
+            // u128 after shifting in each byte.
+            bytes.iter().fold(0, |acc, &byte| {
+                ((((acc as u128) << 8) | byte as u128) % modulus as u128) as u64
+            })
+        })
+        .collect()
+}
@@ -19,3 +19,21 @@ pub trait CryptographicHasher<Item: Clone, Out>: Clone {
         self.hash_slice(&[input])
     }
 }
+
+/// A hasher which can take its input in pieces, e.g. as it arrives, without buffering all of it.
+///
+/// Feeding the input to [`update`](Self::update) in any number of pieces gives the same digest
+/// as hashing it in one go.
+pub trait StatefulHasher<Item: Clone, Out>: CryptographicHasher<Item, Out> {
+    /// The hasher's state partway through its input.
+    type State;
+
+    /// The state before any input.
+    fn new_state(&self) -> Self::State;
+
+    /// Absorb `input` into `state`.
+    fn update(&self, state: &mut Self::State, input: &[Item]);
+
+    /// The digest of everything absorbed into `state`.
+    fn finalize(&self, state: Self::State) -> Out;
+}
@@ -5,14 +5,20 @@
 extern crate alloc;
 
 mod compression;
+#[cfg(feature = "instrumented")]
+mod counting;
 mod hash;
+mod hash_to_field;
 mod hasher;
 mod permutation;
 mod serializing_hasher;
 mod sponge;
 
 pub use compression::*;
+#[cfg(feature = "instrumented")]
+pub use counting::*;
 pub use hash::*;
+pub use hash_to_field::*;
 pub use hasher::*;
 pub use permutation::*;
 pub use serializing_hasher::*;
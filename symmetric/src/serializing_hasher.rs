@@ -11,6 +11,15 @@ pub struct SerializingHasher32<Inner> {
     inner: Inner,
 }
 
+/// Like [`SerializingHasher32`], but serializes the canonical value of each field element rather
+/// than its possibly non-canonical `to_unique_u32` representation, such as Montgomery form. The
+/// bytes hashed are then independent of the field's internal representation, which matters when
+/// the digests are recomputed outside of Plonky3.
+#[derive(Copy, Clone, Debug)]
+pub struct CanonicalSerializingHasher32<Inner> {
+    inner: Inner,
+}
+
 /// Serializes 32-bit field elements to u64s (packing two canonical values together), then hashes
 /// those u64s using some inner hasher, and outputs a `[u64; 4]`.
 #[derive(Copy, Clone, Debug)]
@@ -31,6 +40,12 @@ impl<Inner> SerializingHasher32<Inner> {
     }
 }
 
+impl<Inner> CanonicalSerializingHasher32<Inner> {
+    pub const fn new(inner: Inner) -> Self {
+        Self { inner }
+    }
+}
+
 impl<Inner> SerializingHasher32To64<Inner> {
     pub const fn new(inner: Inner) -> Self {
         Self { inner }
@@ -60,6 +75,23 @@ where
     }
 }
 
+impl<F, Inner> CryptographicHasher<F, [u8; 32]> for CanonicalSerializingHasher32<Inner>
+where
+    F: PrimeField32,
+    Inner: CryptographicHasher<u8, [u8; 32]>,
+{
+    fn hash_iter<I>(&self, input: I) -> [u8; 32]
+    where
+        I: IntoIterator<Item = F>,
+    {
+        self.inner.hash_iter(
+            input
+                .into_iter()
+                .flat_map(|x| x.as_canonical_u32().to_le_bytes()),
+        )
+    }
+}
+
 impl<P, PW, Inner> CryptographicHasher<P, [PW; 8]> for SerializingHasher32<Inner>
 where
     P: PackedValue,
@@ -71,6 +103,7 @@ where
     where
         I: IntoIterator<Item = P>,
     {
+        const { assert!(P::WIDTH == PW::WIDTH, "Packing widths must match") };
         self.inner.hash_iter(
             input
                 .into_iter()
@@ -90,7 +123,7 @@ where
     where
         I: IntoIterator<Item = P>,
     {
-        assert_eq!(P::WIDTH, PW::WIDTH);
+        const { assert!(P::WIDTH == PW::WIDTH, "Packing widths must match") };
         let mut input = input.into_iter();
         self.inner.hash_iter(iter::from_fn(
             #[inline]
@@ -140,6 +173,7 @@ where
     where
         I: IntoIterator<Item = P>,
     {
+        const { assert!(P::WIDTH == PW::WIDTH, "Packing widths must match") };
         self.inner.hash_iter(
             input
                 .into_iter()
@@ -147,3 +181,94 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::FieldAlgebra;
+    use p3_goldilocks::Goldilocks;
+    use p3_mersenne_31::Mersenne31;
+
+    use super::*;
+
+    // The expected bytes below are written out rather than computed with `to_le_bytes`, so that
+    // they pin down the format on targets of either endianness.
+
+    /// A "hash" which returns the start of its input, so the tests can see what was hashed.
+    #[derive(Clone)]
+    struct Prefix;
+
+    impl<T: Copy + Default, const N: usize> CryptographicHasher<T, [T; N]> for Prefix {
+        fn hash_iter<I>(&self, input: I) -> [T; N]
+        where
+            I: IntoIterator<Item = T>,
+        {
+            let mut output = [T::default(); N];
+            for (o, x) in output.iter_mut().zip(input) {
+                *o = x;
+            }
+            output
+        }
+    }
+
+    fn babybears() -> [BabyBear; 3] {
+        [
+            BabyBear::ONE,
+            BabyBear::from_canonical_u32(0x12345678),
+            BabyBear::NEG_ONE,
+        ]
+    }
+
+    #[test]
+    fn serializing_hasher_32_bytes() {
+        // The little-endian bytes of each element's Montgomery form.
+        let bytes: [u8; 32] = SerializingHasher32::new(Prefix).hash_iter(babybears());
+        assert_eq!(
+            bytes[..12],
+            [0xfe, 0xff, 0xff, 0x0f, 0x34, 0xf2, 0x29, 0x39, 0x03, 0x00, 0x00, 0x68]
+        );
+
+        // The packed implementation hashes the same values.
+        let words: [u32; 8] = SerializingHasher32::new(Prefix).hash_iter(babybears());
+        assert_eq!(words[..3], [0x0fff_fffe, 0x3929_f234, 0x6800_0003]);
+    }
+
+    #[test]
+    fn canonical_serializing_hasher_32_bytes() {
+        let bytes: [u8; 32] = CanonicalSerializingHasher32::new(Prefix).hash_iter(babybears());
+        assert_eq!(
+            bytes[..12],
+            [0x01, 0x00, 0x00, 0x00, 0x78, 0x56, 0x34, 0x12, 0x00, 0x00, 0x00, 0x78]
+        );
+    }
+
+    #[test]
+    fn serializing_hasher_32_to_64_words() {
+        let input = [1, 2, 0x7fff_fffe].map(Mersenne31::from_canonical_u32);
+        let words: [u64; 4] = SerializingHasher32To64::new(Prefix).hash_iter(input);
+        assert_eq!(words, [0x0000_0002_0000_0001, 0x7fff_fffe, 0, 0]);
+    }
+
+    #[test]
+    fn serializing_hasher_64_bytes() {
+        let input = [
+            Goldilocks::ONE,
+            Goldilocks::from_canonical_u64(0x0123_4567_89ab_cdef),
+            Goldilocks::NEG_ONE,
+        ];
+        let bytes: [u8; 32] = SerializingHasher64::new(Prefix).hash_iter(input);
+        assert_eq!(
+            bytes[..24],
+            [
+                0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xef, 0xcd, 0xab, 0x89, 0x67, 0x45,
+                0x23, 0x01, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff,
+            ]
+        );
+
+        let words: [u64; 4] = SerializingHasher64::new(Prefix).hash_iter(input);
+        assert_eq!(
+            words[..3],
+            [1, 0x0123_4567_89ab_cdef, 0xffff_ffff_0000_0000]
+        );
+    }
+}
@@ -4,7 +4,7 @@ use core::marker::PhantomData;
 use itertools::Itertools;
 use p3_field::{reduce_32, Field, PrimeField, PrimeField32};
 
-use crate::hasher::CryptographicHasher;
+use crate::hasher::{CryptographicHasher, StatefulHasher};
 use crate::permutation::CryptographicPermutation;
 
 /// A padding-free, overwrite-mode sponge function.
@@ -57,6 +57,49 @@ where
     }
 }
 
+/// The state of a [`PaddingFreeSponge`] partway through its input.
+#[derive(Copy, Clone, Debug)]
+pub struct SpongeHasherState<T, const WIDTH: usize> {
+    sponge: [T; WIDTH],
+    /// How many elements of the current block have been absorbed. A full block is only permuted
+    /// once more input arrives or the state is finalized, as the one-shot hash does.
+    absorbed: usize,
+}
+
+impl<T, P, const WIDTH: usize, const RATE: usize, const OUT: usize> StatefulHasher<T, [T; OUT]>
+    for PaddingFreeSponge<P, WIDTH, RATE, OUT>
+where
+    T: Default + Copy,
+    P: CryptographicPermutation<[T; WIDTH]>,
+{
+    type State = SpongeHasherState<T, WIDTH>;
+
+    fn new_state(&self) -> Self::State {
+        SpongeHasherState {
+            sponge: [T::default(); WIDTH],
+            absorbed: 0,
+        }
+    }
+
+    fn update(&self, state: &mut Self::State, input: &[T]) {
+        for &x in input {
+            if state.absorbed == RATE {
+                self.permutation.permute_mut(&mut state.sponge);
+                state.absorbed = 0;
+            }
+            state.sponge[state.absorbed] = x;
+            state.absorbed += 1;
+        }
+    }
+
+    fn finalize(&self, mut state: Self::State) -> [T; OUT] {
+        if state.absorbed != 0 {
+            self.permutation.permute_mut(&mut state.sponge);
+        }
+        state.sponge[..OUT].try_into().unwrap()
+    }
+}
+
 /// A padding-free, overwrite-mode sponge function that operates natively over PF but accepts elements
 /// of F: PrimeField32.
 ///
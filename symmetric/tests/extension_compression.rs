@@ -0,0 +1,38 @@
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_field::extension::BinomialExtensionField;
+use p3_field::FieldExtensionAlgebra;
+use p3_symmetric::{ExtensionCompression, PseudoCompressionFunction, TruncatedPermutation};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+type F = BabyBear;
+type EF = BinomialExtensionField<F, 4>;
+type Inner = TruncatedPermutation<Poseidon2BabyBear<16>, 2, 8, 16>;
+
+/// The documented order: each element's coefficients in turn.
+fn flatten(digest: [EF; 2]) -> [F; 8] {
+    core::array::from_fn(|i| <EF as FieldExtensionAlgebra<F>>::as_base_slice(&digest[i / 4])[i % 4])
+}
+
+#[test]
+fn compression_matches_manual_flattening() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let inner = Inner::new(Poseidon2BabyBear::new_from_rng_128(&mut rng));
+    let compress = ExtensionCompression::<F, EF, _, 8>::new(inner.clone());
+
+    for _ in 0..10 {
+        let input: [[EF; 2]; 2] = rng.gen();
+        let output = compress.compress(input);
+        assert_eq!(flatten(output), inner.compress(input.map(flatten)));
+    }
+}
+
+#[test]
+fn base_field_compression_is_unchanged() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let inner = Inner::new(Poseidon2BabyBear::new_from_rng_128(&mut rng));
+    let compress = ExtensionCompression::<F, F, _, 8>::new(inner.clone());
+
+    let input: [[F; 8]; 2] = rng.gen();
+    assert_eq!(compress.compress(input), inner.compress(input));
+}
@@ -0,0 +1,95 @@
+use p3_baby_bear::BabyBear;
+use p3_blake3::Blake3;
+use p3_field::{FieldAlgebra, PrimeField64};
+use p3_goldilocks::Goldilocks;
+use p3_symmetric::{expand_message_xmd, hash_to_field, hash_to_indices};
+
+// The expected values below were computed with an independent implementation of the construction
+// over blake3.
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[test]
+fn expand_message_vectors() {
+    assert_eq!(
+        hex(&expand_message_xmd(&Blake3, b"p3-test", b"", 32)),
+        "f755093a9223e87ee4d4d8f851bd6b4fcb8f0f414d2e6f200fc729b4498918bd"
+    );
+    // Three blocks, truncated.
+    assert_eq!(
+        hex(&expand_message_xmd(&Blake3, b"p3-test", b"abc", 80)),
+        "810b35e42aa6a7fefea89782020f52063815a18749c80d5b140a20c42a72fa68\
+         f4dc9b3a2b3fefbc8c465a45bb91f65ec27a363325a0573332f71a3a45130a86\
+         7a3ae7df41f6a93f8cc840792141a87d"
+    );
+}
+
+#[test]
+fn hash_to_field_vectors() {
+    let babybears: Vec<BabyBear> = hash_to_field(&Blake3, b"p3-test-field", b"abc", 4);
+    assert_eq!(
+        babybears,
+        [89318301, 1293610325, 1310037224, 241754873].map(BabyBear::from_canonical_u32)
+    );
+
+    let goldilocks: Vec<Goldilocks> = hash_to_field(&Blake3, b"p3-test-field", b"abc", 2);
+    assert_eq!(
+        goldilocks,
+        [11726335304321430065, 1988948188273765366].map(Goldilocks::from_canonical_u64)
+    );
+}
+
+#[test]
+fn hash_to_indices_vectors() {
+    assert_eq!(
+        hash_to_indices(&Blake3, b"p3-test-indices", b"abc", 1000, 6),
+        [952, 111, 939, 757, 117, 127]
+    );
+}
+
+#[test]
+fn domain_tags_separate_outputs() {
+    let a: Vec<BabyBear> = hash_to_field(&Blake3, b"p3-test-a", b"abc", 4);
+    let b: Vec<BabyBear> = hash_to_field(&Blake3, b"p3-test-b", b"abc", 4);
+    assert_ne!(a, b);
+}
+
+#[test]
+fn indices_are_uniform() {
+    // 70000 indices into a domain whose size isn't a power of two. Each bucket expects 10000, with
+    // a standard deviation of about 93; the bound is five of those.
+    let mut counts = [0; 7];
+    for i in 0u32..200 {
+        for index in hash_to_indices(&Blake3, b"p3-test-indices", &i.to_le_bytes(), 7, 350) {
+            counts[index] += 1;
+        }
+    }
+    for count in counts {
+        assert!(count.abs_diff(10000) < 465, "{counts:?}");
+    }
+}
+
+#[test]
+fn field_elements_are_uniform() {
+    // 80000 elements, half of which are expected below half the order, with a standard deviation
+    // of about 141; the bound is five of those.
+    let mut below_half = 0usize;
+    for i in 0u32..200 {
+        let elements: Vec<BabyBear> =
+            hash_to_field(&Blake3, b"p3-test-field", &i.to_le_bytes(), 400);
+        below_half += elements
+            .iter()
+            .filter(|x| x.as_canonical_u64() < BabyBear::ORDER_U64 / 2)
+            .count();
+    }
+    assert!(below_half.abs_diff(40000) < 710, "{below_half}");
+}
+
+#[test]
+#[should_panic]
+fn too_many_elements_panics() {
+    // 409 elements of 20 bytes each are more than 255 blocks of 32 bytes.
+    let _: Vec<BabyBear> = hash_to_field(&Blake3, b"p3-test-field", b"abc", 409);
+}
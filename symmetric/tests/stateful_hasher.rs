@@ -0,0 +1,85 @@
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_blake3::Blake3;
+use p3_keccak::Keccak256Hash;
+use p3_symmetric::{PaddingFreeSponge, StatefulHasher};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Check that hashing `input` in two pieces, split anywhere near a multiple of `block`, and in
+/// single items, gives the same digest as hashing it in one go.
+fn check_pieces<T, Out, H>(hasher: &H, input: &[T], block: usize)
+where
+    T: Clone,
+    Out: PartialEq + core::fmt::Debug,
+    H: StatefulHasher<T, Out>,
+{
+    let expected = hasher.hash_slice(input);
+
+    let mut splits = vec![0, input.len()];
+    for multiple in [block, 2 * block] {
+        splits.extend([multiple - 1, multiple, multiple + 1]);
+    }
+    for split in splits.into_iter().filter(|&split| split <= input.len()) {
+        let mut state = hasher.new_state();
+        hasher.update(&mut state, &input[..split]);
+        hasher.update(&mut state, &input[split..]);
+        assert_eq!(hasher.finalize(state), expected, "split at {split}");
+    }
+
+    let mut state = hasher.new_state();
+    for item in input {
+        hasher.update(&mut state, core::slice::from_ref(item));
+    }
+    assert_eq!(hasher.finalize(state), expected, "one item at a time");
+
+    let mut state = hasher.new_state();
+    hasher.update(&mut state, &[]);
+    hasher.update(&mut state, input);
+    hasher.update(&mut state, &[]);
+    assert_eq!(hasher.finalize(state), expected, "with empty pieces");
+}
+
+#[test]
+fn sponge_pieces() {
+    const RATE: usize = 8;
+    let mut rng = StdRng::seed_from_u64(1);
+    let perm = Poseidon2BabyBear::<16>::new_from_rng_128(&mut rng);
+    let sponge = PaddingFreeSponge::<_, 16, RATE, 8>::new(perm);
+
+    // Lengths on, one short of, and one past whole blocks, and none at all.
+    for len in [
+        0,
+        1,
+        RATE - 1,
+        RATE,
+        RATE + 1,
+        2 * RATE,
+        2 * RATE + 1,
+        5 * RATE + 3,
+    ] {
+        let input: Vec<BabyBear> = (0..len).map(|_| rng.gen()).collect();
+        check_pieces(&sponge, &input, RATE);
+    }
+}
+
+#[test]
+fn keccak_pieces() {
+    /// The rate of Keccak-256, in bytes.
+    const RATE: usize = 136;
+    let mut rng = StdRng::seed_from_u64(2);
+    for len in [0, RATE - 1, RATE, RATE + 1, 3 * RATE + 5] {
+        let input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        check_pieces(&Keccak256Hash, &input, RATE);
+    }
+}
+
+#[test]
+fn blake3_pieces() {
+    /// The size of a blake3 chunk, in bytes.
+    const CHUNK: usize = 1024;
+    let mut rng = StdRng::seed_from_u64(3);
+    for len in [0, 63, 64, 65, CHUNK - 1, CHUNK, CHUNK + 1, 2 * CHUNK + 7] {
+        let input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        check_pieces(&Blake3, &input, CHUNK);
+    }
+}
@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_baby_bear::BabyBear;
+use p3_commit::{PolynomialSpace, TwoAdicMultiplicativeCoset};
+use p3_field::{Field, FieldAlgebra};
+use p3_uni_stark::ProverCache;
+
+type F = BabyBear;
+
+/// The selectors a proof of a trace of each height needs, computed afresh as in `prove`, and
+/// looked up in a warm cache as in a repeated `prove_with_cache`.
+fn bench_selectors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selectors_on_coset");
+    group.sample_size(10);
+
+    for log_n in [12, 14, 16, 18, 20] {
+        let trace_domain = TwoAdicMultiplicativeCoset {
+            log_n,
+            shift: F::ONE,
+        };
+        let quotient_domain = TwoAdicMultiplicativeCoset {
+            log_n: log_n + 1,
+            shift: F::GENERATOR,
+        };
+
+        group.bench_function(BenchmarkId::new("uncached", 1 << log_n), |b| {
+            b.iter(|| trace_domain.selectors_on_coset(quotient_domain))
+        });
+
+        let mut cache = ProverCache::new(4 << (log_n + 1));
+        cache.selectors_on_coset(trace_domain, quotient_domain);
+        group.bench_function(BenchmarkId::new("cached", 1 << log_n), |b| {
+            b.iter(|| cache.selectors_on_coset(trace_domain, quotient_domain))
+        });
+        assert_eq!(cache.misses(), 1);
+    }
+}
+
+criterion_group!(benches, bench_selectors);
+criterion_main!(benches);
@@ -0,0 +1,162 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use p3_commit::{LagrangeSelectors, PolynomialSpace};
+
+/// Selectors of a trace domain evaluated over a quotient domain, which the prover can keep
+/// between proofs of traces of the same height instead of computing them again.
+///
+/// Each entry holds four columns the size of the quotient domain: the first row, last row and
+/// transition selectors and the inverse of the vanishing polynomial. The cache holds at most
+/// `max_elements` field elements in total, evicting the least recently used entries to stay below
+/// that; an entry which is bigger than the whole budget is computed but not kept.
+#[derive(Debug)]
+pub struct ProverCache<D: PolynomialSpace> {
+    max_elements: usize,
+    /// Entries from least to most recently used.
+    entries: Vec<CacheEntry<D>>,
+    hits: usize,
+    misses: usize,
+}
+
+#[derive(Debug)]
+struct CacheEntry<D: PolynomialSpace> {
+    trace_domain: D,
+    quotient_domain: D,
+    selectors: Arc<LagrangeSelectors<Vec<D::Val>>>,
+}
+
+impl<D: PolynomialSpace> CacheEntry<D> {
+    fn num_elements(&self) -> usize {
+        4 * self.quotient_domain.size()
+    }
+}
+
+impl<D: PolynomialSpace> ProverCache<D> {
+    /// A cache holding at most `max_elements` field elements.
+    pub const fn new(max_elements: usize) -> Self {
+        Self {
+            max_elements,
+            entries: Vec::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The number of field elements currently held.
+    pub fn num_elements(&self) -> usize {
+        self.entries.iter().map(CacheEntry::num_elements).sum()
+    }
+
+    /// The number of lookups which found their selectors in the cache.
+    pub const fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of lookups which had to compute their selectors.
+    pub const fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// The selectors of `trace_domain` over `quotient_domain`, as computed by
+    /// [`PolynomialSpace::selectors_on_coset`].
+    pub fn selectors_on_coset(
+        &mut self,
+        trace_domain: D,
+        quotient_domain: D,
+    ) -> Arc<LagrangeSelectors<Vec<D::Val>>> {
+        if let Some(i) = self.entries.iter().position(|entry| {
+            entry.trace_domain == trace_domain && entry.quotient_domain == quotient_domain
+        }) {
+            self.hits += 1;
+            let entry = self.entries.remove(i);
+            let selectors = entry.selectors.clone();
+            self.entries.push(entry);
+            return selectors;
+        }
+
+        self.misses += 1;
+        let entry = CacheEntry {
+            trace_domain,
+            quotient_domain,
+            selectors: Arc::new(trace_domain.selectors_on_coset(quotient_domain)),
+        };
+        let selectors = entry.selectors.clone();
+        if entry.num_elements() <= self.max_elements {
+            let mut num_elements = self.num_elements() + entry.num_elements();
+            while num_elements > self.max_elements {
+                num_elements -= self.entries.remove(0).num_elements();
+            }
+            self.entries.push(entry);
+        }
+        selectors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_commit::TwoAdicMultiplicativeCoset;
+    use p3_field::{Field, FieldAlgebra};
+
+    use super::*;
+
+    type F = BabyBear;
+
+    fn domains(log_n: usize) -> (TwoAdicMultiplicativeCoset<F>, TwoAdicMultiplicativeCoset<F>) {
+        let trace_domain = TwoAdicMultiplicativeCoset {
+            log_n,
+            shift: F::ONE,
+        };
+        let quotient_domain = TwoAdicMultiplicativeCoset {
+            log_n: log_n + 1,
+            shift: F::GENERATOR,
+        };
+        (trace_domain, quotient_domain)
+    }
+
+    #[test]
+    fn cached_selectors_match_uncached() {
+        let mut cache = ProverCache::new(1 << 10);
+        for log_n in [3, 4, 3] {
+            let (trace_domain, quotient_domain) = domains(log_n);
+            let expected = trace_domain.selectors_on_coset(quotient_domain);
+            let actual = cache.selectors_on_coset(trace_domain, quotient_domain);
+            assert_eq!(actual.is_first_row, expected.is_first_row);
+            assert_eq!(actual.is_last_row, expected.is_last_row);
+            assert_eq!(actual.is_transition, expected.is_transition);
+            assert_eq!(actual.inv_zeroifier, expected.inv_zeroifier);
+        }
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+        assert_eq!(cache.num_elements(), 4 * (16 + 32));
+    }
+
+    #[test]
+    fn least_recently_used_entries_are_evicted() {
+        // Room for the selectors of two trace domains of height 8, or one of height 16.
+        let mut cache = ProverCache::new(4 * 32);
+        let (trace_8, quotient_16) = domains(3);
+        let (trace_16, quotient_32) = domains(4);
+        let other_quotient_16 = TwoAdicMultiplicativeCoset {
+            log_n: 4,
+            shift: F::GENERATOR.square(),
+        };
+
+        cache.selectors_on_coset(trace_8, quotient_16);
+        cache.selectors_on_coset(trace_8, other_quotient_16);
+        cache.selectors_on_coset(trace_8, quotient_16);
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+        // This evicts both entries, so the next lookup computes its selectors again.
+        cache.selectors_on_coset(trace_16, quotient_32);
+        assert_eq!(cache.num_elements(), 4 * 32);
+        cache.selectors_on_coset(trace_8, other_quotient_16);
+        assert_eq!((cache.hits(), cache.misses()), (1, 4));
+        assert_eq!(cache.num_elements(), 4 * 16);
+
+        // An entry bigger than the budget isn't kept.
+        let (trace_32, quotient_64) = domains(5);
+        cache.selectors_on_coset(trace_32, quotient_64);
+        assert_eq!(cache.num_elements(), 4 * 16);
+    }
+}
@@ -1,37 +1,53 @@
+use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues};
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPublicValues, ExternalBuilder, LookupBuilder, PairBuilder,
+};
 use p3_field::Field;
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 use p3_matrix::stack::VerticalPair;
 use p3_matrix::Matrix;
 use tracing::instrument;
 
+use crate::{Entry, SymbolicExpression, SymbolicVariable};
+
 #[instrument(name = "check constraints", skip_all)]
-pub(crate) fn check_constraints<F, A>(air: &A, main: &RowMajorMatrix<F>, public_values: &Vec<F>)
-where
+pub(crate) fn check_constraints<F, A>(
+    air: &A,
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    external: Option<&RowMajorMatrix<F>>,
+    public_values: &Vec<F>,
+    symbolic_constraints: &[SymbolicExpression<F>],
+    column_names: Option<&[&'static str]>,
+) where
     F: Field,
-    A: for<'a> Air<DebugConstraintBuilder<'a, F>>,
+    A: ?Sized + for<'a> Air<DebugConstraintBuilder<'a, F>>,
 {
     let height = main.height();
 
     (0..height).for_each(|i| {
-        let i_next = (i + 1) % height;
-
-        let local = main.row_slice(i);
-        let next = main.row_slice(i_next);
-        let main = VerticalPair::new(
-            RowMajorMatrixView::new_row(&*local),
-            RowMajorMatrixView::new_row(&*next),
-        );
+        let (local, next) = main.row_pair(i);
+        let main = window(local, next);
+        let (local, next) = preprocessed.map_or((&[][..], &[][..]), |m| m.row_pair(i));
+        let preprocessed = window(local, next);
+        let (local, next) = external.map_or((&[][..], &[][..]), |m| m.row_pair(i));
+        let external = window(local, next);
 
         let mut builder = DebugConstraintBuilder {
             row_index: i,
             main,
+            preprocessed,
+            external,
             public_values,
             is_first_row: F::from_bool(i == 0),
             is_last_row: F::from_bool(i == height - 1),
             is_transition: F::from_bool(i != height - 1),
+            constraint_index: 0,
+            symbolic_constraints,
+            column_names,
         };
 
         air.eval(&mut builder);
@@ -44,10 +60,98 @@ where
 pub struct DebugConstraintBuilder<'a, F: Field> {
     row_index: usize,
     main: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+    preprocessed: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+    external: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
     public_values: &'a [F],
     is_first_row: F,
     is_last_row: F,
     is_transition: F,
+    constraint_index: usize,
+    /// The constraints evaluated symbolically, which are used to report the columns involved in a
+    /// failed constraint.
+    symbolic_constraints: &'a [SymbolicExpression<F>],
+    column_names: Option<&'a [&'static str]>,
+}
+
+impl<F: Field> DebugConstraintBuilder<'_, F> {
+    /// Describe the current constraint by the values of the variables it involves, naming main
+    /// trace columns if the AIR has names for them.
+    fn describe_constraint(&self) -> String {
+        let Some(constraint) = self.symbolic_constraints.get(self.constraint_index) else {
+            return format!("constraint {}", self.constraint_index);
+        };
+        let mut variables = Vec::new();
+        collect_variables(constraint, &mut variables);
+        let values = variables
+            .iter()
+            .map(|v| format!("{} = {}", self.variable_name(v), self.variable_value(v)))
+            .collect::<Vec<_>>();
+        format!(
+            "constraint {} involving {}",
+            self.constraint_index,
+            values.join(", ")
+        )
+    }
+
+    fn variable_name(&self, v: &SymbolicVariable<F>) -> String {
+        let row = |offset| if offset == 0 { "local" } else { "next" };
+        match v.entry {
+            Entry::Main { offset } => {
+                match self.column_names.and_then(|names| names.get(v.index)) {
+                    Some(name) => format!("{} {}", row(offset), name),
+                    None => format!("{} main[{}]", row(offset), v.index),
+                }
+            }
+            Entry::Preprocessed { offset } => format!("{} preprocessed[{}]", row(offset), v.index),
+            Entry::External { offset } => format!("{} external[{}]", row(offset), v.index),
+            Entry::Permutation { offset } => format!("{} permutation[{}]", row(offset), v.index),
+            Entry::Public => format!("public[{}]", v.index),
+            Entry::Challenge => format!("challenge[{}]", v.index),
+        }
+    }
+
+    fn variable_value(&self, v: &SymbolicVariable<F>) -> String {
+        let value = match v.entry {
+            Entry::Main { offset } => Some(self.main.get(offset, v.index)),
+            Entry::Preprocessed { offset } => Some(self.preprocessed.get(offset, v.index)),
+            Entry::External { offset } => Some(self.external.get(offset, v.index)),
+            Entry::Public => Some(self.public_values[v.index]),
+            Entry::Permutation { .. } | Entry::Challenge => None,
+        };
+        value.map_or_else(|| String::from("?"), |value| format!("{}", value))
+    }
+}
+
+fn window<'a, F: Field>(
+    local: &'a [F],
+    next: &'a [F],
+) -> VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>> {
+    VerticalPair::new(
+        RowMajorMatrixView::new_row(local),
+        RowMajorMatrixView::new_row(next),
+    )
+}
+
+/// The distinct variables in `expr`, in the order they first appear.
+fn collect_variables<F>(expr: &SymbolicExpression<F>, out: &mut Vec<SymbolicVariable<F>>) {
+    match expr {
+        SymbolicExpression::Variable(v) => {
+            if !out.iter().any(|u| u.entry == v.entry && u.index == v.index) {
+                out.push(*v);
+            }
+        }
+        SymbolicExpression::Add { x, y, .. }
+        | SymbolicExpression::Sub { x, y, .. }
+        | SymbolicExpression::Mul { x, y, .. } => {
+            collect_variables(x, out);
+            collect_variables(y, out);
+        }
+        SymbolicExpression::Neg { x, .. } => collect_variables(x, out),
+        SymbolicExpression::IsFirstRow
+        | SymbolicExpression::IsLastRow
+        | SymbolicExpression::IsTransition
+        | SymbolicExpression::Constant(_) => {}
+    }
 }
 
 impl<'a, F> AirBuilder for DebugConstraintBuilder<'a, F>
@@ -80,22 +184,32 @@ where
     }
 
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
-        assert_eq!(
-            x.into(),
-            F::ZERO,
-            "constraints had nonzero value on row {}",
-            self.row_index
-        );
+        let x = x.into();
+        if x != F::ZERO {
+            let description = self.describe_constraint();
+            assert_eq!(
+                x,
+                F::ZERO,
+                "constraints had nonzero value on row {}: {}",
+                self.row_index,
+                description
+            );
+        }
+        self.constraint_index += 1;
     }
 
     fn assert_eq<I1: Into<Self::Expr>, I2: Into<Self::Expr>>(&mut self, x: I1, y: I2) {
         let x = x.into();
         let y = y.into();
-        assert_eq!(
-            x, y,
-            "values didn't match on row {}: {} != {}",
-            self.row_index, x, y
-        );
+        if x != y {
+            let description = self.describe_constraint();
+            assert_eq!(
+                x, y,
+                "values didn't match on row {}: {} != {}: {}",
+                self.row_index, x, y, description
+            );
+        }
+        self.constraint_index += 1;
     }
 }
 
@@ -106,3 +220,35 @@ impl<F: Field> AirBuilderWithPublicValues for DebugConstraintBuilder<'_, F> {
         self.public_values
     }
 }
+
+impl<F: Field> PairBuilder for DebugConstraintBuilder<'_, F> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<F: Field> ExternalBuilder for DebugConstraintBuilder<'_, F> {
+    fn external(&self) -> Self::M {
+        self.external
+    }
+}
+
+impl<F: Field> LookupBuilder for DebugConstraintBuilder<'_, F> {
+    /// Lookups relate values across the whole trace, so they aren't checked row by row; a failed
+    /// lookup only shows up as a proof which doesn't verify.
+    fn lookup_with_count<I0, I1, I2, I3>(
+        &mut self,
+        _count: I0,
+        _send: I1,
+        _receive: I2,
+        _multiplicity: I3,
+    ) where
+        I0: Into<Self::Expr>,
+        I1: Into<Self::Expr>,
+        I2: Into<Self::Expr>,
+        I3: Into<Self::Expr>,
+    {
+        // Each lookup has a constraint among the symbolic ones, which this skips.
+        self.constraint_index += 1;
+    }
+}
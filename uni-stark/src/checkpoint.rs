@@ -0,0 +1,387 @@
+use alloc::vec::Vec;
+
+use p3_air::Air;
+use p3_challenger::CanSnapshot;
+use p3_commit::{OpenedValues, ResumablePcs};
+use p3_matrix::dense::RowMajorMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::prover::prove_inner;
+use crate::{
+    Com, PcsError, PcsProof, PcsProverData, Proof, ProverConstraintFolder, ProverError, ProvingKey,
+    StarkGenericConfig, SymbolicAirBuilder, Val,
+};
+
+/// A point between the phases of [`prove_with_checkpoints`], from which [`prove_resume`] can finish
+/// the proof, e.g. on another machine after this one was interrupted.
+///
+/// Committed data isn't kept, since the PCS's prover data can't be serialized. Resuming commits to
+/// the trace, and to the quotient if it was, again, which is deterministic and checked against the
+/// commitments in the checkpoint.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ProverCheckpoint<Com, Val, State, Opening> {
+    /// The challenger has observed the trace's commitment and the public values.
+    TraceCommitted {
+        trace_commit: Com,
+        challenger: State,
+    },
+    /// The challenger has also observed the commitment to the quotient's chunks. The quotient's
+    /// evaluations over the quotient domain, flattened to the base field, are kept since they are
+    /// the most expensive part of the proof to compute.
+    QuotientCommitted {
+        trace_commit: Com,
+        quotient_commit: Com,
+        quotient: Vec<Val>,
+        challenger: State,
+    },
+    /// The PCS's opening proof is part way through, e.g. some of FRI's commit phase rounds are
+    /// done. The rest is as at [`QuotientCommitted`](Self::QuotientCommitted), from which the
+    /// opening started.
+    Opening {
+        trace_commit: Com,
+        quotient_commit: Com,
+        quotient: Vec<Val>,
+        challenger: State,
+        opening: Opening,
+    },
+}
+
+/// The checkpoint of a proof by `SC`.
+pub type StarkCheckpoint<SC> = ProverCheckpoint<
+    Com<SC>,
+    Val<SC>,
+    <<SC as StarkGenericConfig>::Challenger as CanSnapshot>::State,
+    <<SC as StarkGenericConfig>::Pcs as ResumablePcs<
+        <SC as StarkGenericConfig>::Challenge,
+        <SC as StarkGenericConfig>::Challenger,
+    >>::OpeningCheckpoint,
+>;
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The checkpoint's challenger state can't be restored into the challenger.
+    InvalidChallengerState,
+    /// Committing to the trace gave a different commitment from the checkpoint's, so the trace
+    /// isn't the one the checkpoint was made from.
+    TraceCommitmentMismatch,
+    /// Committing to the checkpoint's quotient gave a different commitment from the checkpoint's.
+    QuotientCommitmentMismatch,
+    /// The checkpoint's quotient has the wrong number of evaluations for the trace and AIR.
+    InvalidQuotientShape,
+}
+
+/// Like [`prove`](crate::prove), but calls `on_checkpoint` once the trace is committed to, once
+/// the quotient is, and at each checkpoint of the PCS's opening proof, e.g. after each of FRI's
+/// commit phase rounds, with what [`prove_resume`] needs to continue from there.
+///
+/// Only AIRs without lookups are supported, and proofs can't be zero-knowledge.
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_checkpoints<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    on_checkpoint: impl FnMut(StarkCheckpoint<SC>),
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    SC::Pcs: ResumablePcs<SC::Challenge, SC::Challenger>,
+    SC::Challenger: CanSnapshot,
+    Com<SC>: PartialEq,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    prove_checkpointed(
+        config,
+        air,
+        challenger,
+        trace,
+        public_values,
+        None,
+        on_checkpoint,
+    )
+}
+
+/// Finish a proof from a checkpoint made by [`prove_with_checkpoints`] or by an earlier call of
+/// this function, giving the same proof as if it hadn't been interrupted. `trace` and
+/// `public_values` must be those the checkpoint was made from, and `challenger` must have the same
+/// parameters, but its state is replaced by the checkpoint's. `on_checkpoint` is called at each of
+/// the remaining phase boundaries.
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_resume<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    checkpoint: StarkCheckpoint<SC>,
+    on_checkpoint: impl FnMut(StarkCheckpoint<SC>),
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    SC::Pcs: ResumablePcs<SC::Challenge, SC::Challenger>,
+    SC::Challenger: CanSnapshot,
+    Com<SC>: PartialEq,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    prove_checkpointed(
+        config,
+        air,
+        challenger,
+        trace,
+        public_values,
+        Some(checkpoint),
+        on_checkpoint,
+    )
+}
+
+/// Prove as [`prove`](crate::prove) does, skipping the phases before `checkpoint` if there is one.
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+fn prove_checkpointed<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    checkpoint: Option<StarkCheckpoint<SC>>,
+    on_checkpoint: impl FnMut(StarkCheckpoint<SC>),
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    SC::Pcs: ResumablePcs<SC::Challenge, SC::Challenger>,
+    SC::Challenger: CanSnapshot,
+    Com<SC>: PartialEq,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    let proving_key = ProvingKey {
+        preprocessed: None,
+        column_names: air.column_names(),
+    };
+    let mut checkpoints = Checkpoints {
+        resume: checkpoint,
+        resumed_quotient_commit: None,
+        resumed_opening: None,
+        quotient_committed: None,
+        on_checkpoint,
+    };
+    prove_inner(
+        config,
+        &proving_key,
+        None,
+        None,
+        None,
+        Some(&mut checkpoints),
+        air,
+        challenger,
+        trace,
+        public_values,
+    )
+}
+
+/// How far the checkpoint which a proof resumes from got.
+pub(crate) enum Resumed<Val> {
+    /// The proof isn't resumed.
+    Nothing,
+    /// Only the trace was committed to.
+    TraceCommitted,
+    /// The quotient was committed to, with these evaluations.
+    QuotientCommitted(Vec<Val>),
+}
+
+/// The checkpoints of a proof, which [`prove_inner`] resumes from and reports to at each phase
+/// boundary.
+pub(crate) trait CheckpointHook<SC: StarkGenericConfig> {
+    /// Restore the challenger from the checkpoint being resumed, if there is one, once the trace
+    /// has been committed to again as `trace_commit`.
+    fn resume(
+        &mut self,
+        trace_commit: &Com<SC>,
+        challenger: &mut SC::Challenger,
+    ) -> Result<Resumed<Val<SC>>, CheckpointError>;
+
+    /// The challenger has observed the trace's commitment and the public values.
+    fn trace_committed(&mut self, trace_commit: &Com<SC>, challenger: &SC::Challenger);
+
+    /// The challenger has observed the commitment to the quotient's chunks, which were computed
+    /// from `quotient`.
+    fn quotient_committed(
+        &mut self,
+        trace_commit: &Com<SC>,
+        quotient_commit: &Com<SC>,
+        quotient: Vec<Val<SC>>,
+        challenger: &SC::Challenger,
+    ) -> Result<(), CheckpointError>;
+
+    /// Make the opening proof as [`Pcs::open`](p3_commit::Pcs::open) does, resuming it from the
+    /// checkpoint being resumed if that got so far.
+    #[allow(clippy::type_complexity)]
+    fn open(
+        &mut self,
+        pcs: &SC::Pcs,
+        rounds: Vec<(&PcsProverData<SC>, Vec<Vec<SC::Challenge>>)>,
+        challenger: &mut SC::Challenger,
+    ) -> Result<(OpenedValues<SC::Challenge>, PcsProof<SC>), ProverError<PcsError<SC>>>;
+}
+
+/// The checkpoints of [`prove_with_checkpoints`] and [`prove_resume`].
+struct Checkpoints<SC: StarkGenericConfig, F>
+where
+    SC::Pcs: ResumablePcs<SC::Challenge, SC::Challenger>,
+    SC::Challenger: CanSnapshot,
+{
+    resume: Option<StarkCheckpoint<SC>>,
+    /// The quotient commitment of the checkpoint resumed from, which the quotient must commit to
+    /// again.
+    resumed_quotient_commit: Option<Com<SC>>,
+    /// The opening checkpoint of the checkpoint resumed from, if it got that far.
+    resumed_opening:
+        Option<<SC::Pcs as ResumablePcs<SC::Challenge, SC::Challenger>>::OpeningCheckpoint>,
+    /// The trace and quotient commitments, the quotient and the challenger's state once the
+    /// quotient was committed to, which each opening checkpoint starts from.
+    quotient_committed: Option<(
+        Com<SC>,
+        Com<SC>,
+        Vec<Val<SC>>,
+        <SC::Challenger as CanSnapshot>::State,
+    )>,
+    on_checkpoint: F,
+}
+
+impl<SC, F> CheckpointHook<SC> for Checkpoints<SC, F>
+where
+    SC: StarkGenericConfig,
+    SC::Pcs: ResumablePcs<SC::Challenge, SC::Challenger>,
+    SC::Challenger: CanSnapshot,
+    Com<SC>: PartialEq,
+    F: FnMut(StarkCheckpoint<SC>),
+{
+    fn resume(
+        &mut self,
+        trace_commit: &Com<SC>,
+        challenger: &mut SC::Challenger,
+    ) -> Result<Resumed<Val<SC>>, CheckpointError> {
+        let Some(checkpoint) = self.resume.take() else {
+            return Ok(Resumed::Nothing);
+        };
+        let (checkpoint_trace_commit, resumed, state) = match checkpoint {
+            ProverCheckpoint::TraceCommitted {
+                trace_commit,
+                challenger,
+            } => (trace_commit, Resumed::TraceCommitted, challenger),
+            ProverCheckpoint::QuotientCommitted {
+                trace_commit,
+                quotient_commit,
+                quotient,
+                challenger,
+            } => {
+                self.resumed_quotient_commit = Some(quotient_commit);
+                (
+                    trace_commit,
+                    Resumed::QuotientCommitted(quotient),
+                    challenger,
+                )
+            }
+            ProverCheckpoint::Opening {
+                trace_commit,
+                quotient_commit,
+                quotient,
+                challenger,
+                opening,
+            } => {
+                self.resumed_quotient_commit = Some(quotient_commit);
+                self.resumed_opening = Some(opening);
+                (
+                    trace_commit,
+                    Resumed::QuotientCommitted(quotient),
+                    challenger,
+                )
+            }
+        };
+        if checkpoint_trace_commit != *trace_commit {
+            return Err(CheckpointError::TraceCommitmentMismatch);
+        }
+        challenger
+            .restore(state)
+            .map_err(|_| CheckpointError::InvalidChallengerState)?;
+        Ok(resumed)
+    }
+
+    fn trace_committed(&mut self, trace_commit: &Com<SC>, challenger: &SC::Challenger) {
+        (self.on_checkpoint)(ProverCheckpoint::TraceCommitted {
+            trace_commit: trace_commit.clone(),
+            challenger: challenger.snapshot(),
+        });
+    }
+
+    fn quotient_committed(
+        &mut self,
+        trace_commit: &Com<SC>,
+        quotient_commit: &Com<SC>,
+        quotient: Vec<Val<SC>>,
+        challenger: &SC::Challenger,
+    ) -> Result<(), CheckpointError> {
+        let resumed = self.resumed_quotient_commit.take();
+        if let Some(resumed_quotient_commit) = &resumed {
+            if resumed_quotient_commit != quotient_commit {
+                return Err(CheckpointError::QuotientCommitmentMismatch);
+            }
+        }
+        let committed = (
+            trace_commit.clone(),
+            quotient_commit.clone(),
+            quotient,
+            challenger.snapshot(),
+        );
+        if resumed.is_none() {
+            let (trace_commit, quotient_commit, quotient, challenger) = committed.clone();
+            (self.on_checkpoint)(ProverCheckpoint::QuotientCommitted {
+                trace_commit,
+                quotient_commit,
+                quotient,
+                challenger,
+            });
+        }
+        self.quotient_committed = Some(committed);
+        Ok(())
+    }
+
+    fn open(
+        &mut self,
+        pcs: &SC::Pcs,
+        rounds: Vec<(&PcsProverData<SC>, Vec<Vec<SC::Challenge>>)>,
+        challenger: &mut SC::Challenger,
+    ) -> Result<(OpenedValues<SC::Challenge>, PcsProof<SC>), ProverError<PcsError<SC>>> {
+        let (trace_commit, quotient_commit, quotient, state) = self
+            .quotient_committed
+            .take()
+            .expect("the quotient is committed to before the opening");
+        let on_checkpoint = &mut self.on_checkpoint;
+        pcs.open_with_checkpoints(
+            rounds,
+            challenger,
+            self.resumed_opening.take(),
+            &mut |opening| {
+                on_checkpoint(ProverCheckpoint::Opening {
+                    trace_commit: trace_commit.clone(),
+                    quotient_commit: quotient_commit.clone(),
+                    quotient: quotient.clone(),
+                    challenger: state.clone(),
+                    opening,
+                })
+            },
+        )
+        .map_err(ProverError::Opening)
+    }
+}
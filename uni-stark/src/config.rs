@@ -2,7 +2,10 @@ use core::marker::PhantomData;
 
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
-use p3_field::{ExtensionField, Field};
+use p3_field::{ExtensionField, Field, FieldExtensionAlgebra};
+use p3_maybe_rayon::ParallelismConfig;
+
+use crate::{AirParameters, SecurityReport};
 
 pub type PcsError<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
@@ -34,11 +37,48 @@ pub trait StarkGenericConfig {
         + CanSample<Self::Challenge>;
 
     fn pcs(&self) -> &Self::Pcs;
+
+    /// How the prover's parallel phases are run. Defaults to the current rayon pool, with no
+    /// threshold.
+    fn parallelism(&self) -> &ParallelismConfig {
+        static DEFAULT: ParallelismConfig = ParallelismConfig::new();
+        &DEFAULT
+    }
+
+    /// Whether proofs are zero-knowledge, see [`prove_zk`](crate::prove_zk). Defaults to false.
+    fn zk(&self) -> bool {
+        false
+    }
+
+    /// The bits of security of proofs of a trace of `2^log_trace_len` rows of an AIR with `air`'s
+    /// constraints, or `None` if the PCS doesn't say what its soundness rests on. The challenge
+    /// field's size is taken to be `2^(b - 1)` to the power of its degree, for a base field of `b`
+    /// bits, which never overstates it.
+    fn security_bits(&self, air: AirParameters, log_trace_len: usize) -> Option<SecurityReport> {
+        let params = self.pcs().query_parameters()?;
+        let ext_degree = <Self::Challenge as FieldExtensionAlgebra<Val<Self>>>::D;
+        let log_field_size = (Val::<Self>::bits() - 1) * ext_degree;
+        Some(SecurityReport::new(
+            log_field_size,
+            params,
+            air,
+            log_trace_len,
+        ))
+    }
+
+    /// The fewest bits of conjectured security which [`prove`](crate::prove) accepts without a
+    /// warning, see [`check_security`](crate::check_security). Defaults to no threshold.
+    fn security_threshold(&self) -> Option<usize> {
+        None
+    }
 }
 
 #[derive(Debug)]
 pub struct StarkConfig<Pcs, Challenge, Challenger> {
     pcs: Pcs,
+    parallelism: ParallelismConfig,
+    zk: bool,
+    security_threshold: Option<usize>,
     _phantom: PhantomData<(Challenge, Challenger)>,
 }
 
@@ -46,9 +86,36 @@ impl<Pcs, Challenge, Challenger> StarkConfig<Pcs, Challenge, Challenger> {
     pub const fn new(pcs: Pcs) -> Self {
         Self {
             pcs,
+            parallelism: ParallelismConfig::new(),
+            zk: false,
+            security_threshold: None,
             _phantom: PhantomData,
         }
     }
+
+    /// Use the given thread pool and parallelism threshold when proving.
+    #[must_use]
+    pub fn with_parallelism(mut self, parallelism: ParallelismConfig) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// Make proofs which are zero-knowledge, or not. They must then be proven with
+    /// [`prove_zk`](crate::prove_zk), and the PCS and its MMCSs must also be hiding.
+    #[must_use]
+    pub fn with_zk(mut self, zk: bool) -> Self {
+        self.zk = zk;
+        self
+    }
+
+    /// Warn when proving with less than `bits` bits of conjectured security, or with the
+    /// `strict-security` feature, fail with
+    /// [`ProverError::InsufficientSecurity`](crate::ProverError::InsufficientSecurity).
+    #[must_use]
+    pub fn with_security_threshold(mut self, bits: usize) -> Self {
+        self.security_threshold = Some(bits);
+        self
+    }
 }
 
 impl<Pcs, Challenge, Challenger> StarkGenericConfig for StarkConfig<Pcs, Challenge, Challenger>
@@ -66,4 +133,16 @@ where
     fn pcs(&self) -> &Self::Pcs {
         &self.pcs
     }
+
+    fn parallelism(&self) -> &ParallelismConfig {
+        &self.parallelism
+    }
+
+    fn zk(&self) -> bool {
+        self.zk
+    }
+
+    fn security_threshold(&self) -> Option<usize> {
+        self.security_threshold
+    }
 }
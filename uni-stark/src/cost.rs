@@ -0,0 +1,316 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use p3_air::Air;
+use p3_commit::{Cost, PcsCostModel};
+use p3_field::{Field, FieldExtensionAlgebra};
+use p3_matrix::Dimensions;
+
+use crate::prover::unchecked_log_quotient_degree;
+use crate::{
+    get_symbolic_constraints_with_external, get_symbolic_lookups, Lookup, StarkGenericConfig,
+    SymbolicAirBuilder, SymbolicExpression, Val,
+};
+
+/// An estimate of what [`prove`](crate::prove) needs, phase by phase, from which the machine to
+/// run it on can be sized. Memory is counted on top of the trace given to the prover; the
+/// preprocessed and external prover data are assumed to be allocated already.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// Evaluating what the AIR's lookups send and receive, then committing to the main trace: its
+    /// LDE and Merkle tree.
+    pub trace_commitment: Cost,
+    /// Computing the running sums of the AIR's lookups and committing to them, if it makes any.
+    pub lookup_commitment: Cost,
+    /// Evaluating the quotient over the quotient domain and committing to its chunks.
+    pub quotient: Cost,
+    /// Opening every commitment: the reduced openings, and the codewords of FRI with their trees.
+    pub opening: Cost,
+}
+
+impl CostEstimate {
+    /// The cost of the whole proof.
+    pub const fn total(&self) -> Cost {
+        self.trace_commitment
+            .then(self.lookup_commitment)
+            .then(self.quotient)
+            .then(self.opening)
+    }
+
+    /// The most bytes the prover holds at once, on top of the trace.
+    pub const fn peak_bytes(&self) -> usize {
+        self.total().peak_bytes
+    }
+}
+
+/// Estimate the memory and work needed to prove `air` over traces of the given dimensions,
+/// without a [`ProverCache`](crate::ProverCache).
+///
+/// `trace_dims` holds the dimensions of the main trace, followed by those of the preprocessed and
+/// external traces. A trace which the AIR doesn't have may be given a width of zero, or left out if
+/// no trace follows it.
+pub fn prover_cost_estimate<SC, A>(
+    config: &SC,
+    air: &A,
+    trace_dims: &[Dimensions],
+    num_public_values: usize,
+) -> CostEstimate
+where
+    SC: StarkGenericConfig,
+    SC::Pcs: PcsCostModel<SC::Challenge>,
+    A: Air<SymbolicAirBuilder<Val<SC>>>,
+{
+    let main = trace_dims[0];
+    let width = |i: usize| trace_dims.get(i).map_or(0, |dims| dims.width);
+    let (preprocessed_width, external_width) = (width(1), width(2));
+    let degree = main.height;
+
+    let (symbolic_constraints, num_lookups, quotient_degree) =
+        air_shape::<Val<SC>, A>(air, trace_dims, num_public_values, config.zk());
+    let quotient_size = degree * quotient_degree;
+    let committed_main = committed_main(config, main);
+
+    let pcs = config.pcs();
+    let val_bytes = size_of::<Val<SC>>();
+    let ext_bytes = size_of::<SC::Challenge>();
+    let ext_degree = <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D;
+
+    let lookup_values_bytes = num_lookups * degree * size_of::<Lookup<Val<SC>>>();
+    let mut trace_commitment = Cost::default();
+    if num_lookups > 0 {
+        // The preprocessed trace is generated again to evaluate them.
+        trace_commitment = Cost::allocation(lookup_values_bytes)
+            .then(Cost::temporary(preprocessed_width * degree * val_bytes));
+    }
+    if config.zk() {
+        // The trace is interleaved with as many random rows.
+        trace_commitment =
+            trace_commitment.then(Cost::allocation(2 * main.width * degree * val_bytes));
+    }
+    let trace_commitment = trace_commitment.then(pcs.commit_cost(&[committed_main]));
+
+    let lookup = Dimensions {
+        width: num_lookups * ext_degree,
+        height: degree,
+    };
+    let mut lookup_commitment = Cost::default();
+    if num_lookups > 0 {
+        let sums_bytes = num_lookups * degree * ext_bytes;
+        let sums = Cost {
+            // Inverting both denominators of each row, then scaling them.
+            extension_muls: num_lookups * degree * 8,
+            ..Cost::allocation(sums_bytes)
+        };
+        // The denominators of a lookup and their inverses.
+        let sums = sums.then(Cost::temporary(4 * degree * ext_bytes));
+        // The sums are flattened to the base field before they're committed to.
+        lookup_commitment = sums
+            .then(Cost::allocation(sums_bytes))
+            .freeing(sums_bytes)
+            .then(pcs.commit_cost(&[lookup]))
+            .freeing(lookup_values_bytes);
+    }
+
+    // The traces other than the main one are copied onto the quotient domain.
+    let copies_bytes =
+        (preprocessed_width + external_width + lookup.width) * quotient_size * val_bytes;
+    // Four selectors, and the points of the quotient domain while they're computed.
+    let selectors = Cost::allocation(4 * quotient_size * val_bytes)
+        .then(Cost::temporary(quotient_size * val_bytes));
+    let quotient_bytes = quotient_size * ext_bytes;
+    let quotient_values = Cost {
+        // Accumulating each constraint, and the extension arithmetic of the lookup constraints.
+        extension_muls: quotient_size * (symbolic_constraints.len() + 4 * num_lookups),
+        ..Cost::allocation(quotient_bytes)
+    };
+    let chunk = Dimensions {
+        width: ext_degree,
+        height: degree,
+    };
+    let blinding = quotient_blinding(config, quotient_size);
+    let mut quotient_matrices = vec![chunk; quotient_degree];
+    quotient_matrices.extend(blinding);
+    let quotient = Cost::allocation(copies_bytes)
+        .then(selectors)
+        .then(quotient_values)
+        .freeing(copies_bytes)
+        // The quotient is flattened to the base field, then split into chunks.
+        .then(Cost::allocation(quotient_bytes))
+        .freeing(quotient_bytes)
+        .then(Cost::allocation(quotient_bytes))
+        .freeing(quotient_bytes)
+        // A zero-knowledge quotient's random blinding, which is committed to with the chunks.
+        .then(Cost::allocation(
+            blinding.map_or(0, |_| quotient_size * ext_bytes),
+        ))
+        .then(pcs.commit_cost(&quotient_matrices));
+
+    let opening = pcs.open_cost(&opening_rounds(
+        committed_main,
+        &trace_dims[1..],
+        lookup,
+        &quotient_matrices,
+    ));
+
+    CostEstimate {
+        trace_commitment,
+        lookup_commitment,
+        quotient,
+        opening,
+    }
+}
+
+/// An estimate of the work [`verify`](crate::verify) does for a proof, e.g. for sizing a
+/// recursive verifier. Hashing by the challenger isn't counted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifierCostEstimate {
+    /// Evaluating the AIR's constraints at the out-of-domain point, and recomposing the quotient
+    /// there from its chunks.
+    pub constraints: Cost,
+    /// Verifying the opening proof: the Merkle paths of every query, and the FRI folds along them.
+    pub opening: Cost,
+}
+
+impl VerifierCostEstimate {
+    /// The cost of the whole verification.
+    pub const fn total(&self) -> Cost {
+        self.constraints.then(self.opening)
+    }
+}
+
+/// Estimate the work needed to verify a proof of `air` over traces of the given dimensions, which
+/// are given as to [`prover_cost_estimate`].
+pub fn verifier_cost_estimate<SC, A>(
+    config: &SC,
+    air: &A,
+    trace_dims: &[Dimensions],
+    num_public_values: usize,
+) -> VerifierCostEstimate
+where
+    SC: StarkGenericConfig,
+    SC::Pcs: PcsCostModel<SC::Challenge>,
+    A: Air<SymbolicAirBuilder<Val<SC>>>,
+{
+    let main = trace_dims[0];
+    let (symbolic_constraints, num_lookups, quotient_degree) =
+        air_shape::<Val<SC>, A>(air, trace_dims, num_public_values, config.zk());
+    let ext_degree = <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D;
+
+    // The products in each constraint, and folding it into the accumulator.
+    let constraint_muls: usize = symbolic_constraints
+        .iter()
+        .map(|constraint| num_muls(constraint) + 1)
+        .sum();
+    let constraints = Cost {
+        // Each chunk is weighted by the zerofiers of the others, and its coefficients by the
+        // extension's monomials.
+        extension_muls: constraint_muls
+            + 4 * num_lookups
+            + quotient_degree * (quotient_degree + ext_degree),
+        ..Cost::default()
+    };
+
+    let lookup = Dimensions {
+        width: num_lookups * ext_degree,
+        height: main.height,
+    };
+    let chunk = Dimensions {
+        width: ext_degree,
+        height: main.height,
+    };
+    let mut quotient_matrices = vec![chunk; quotient_degree];
+    quotient_matrices.extend(quotient_blinding(config, main.height * quotient_degree));
+    let opening = config.pcs().verify_cost(&opening_rounds(
+        committed_main(config, main),
+        &trace_dims[1..],
+        lookup,
+        &quotient_matrices,
+    ));
+
+    VerifierCostEstimate {
+        constraints,
+        opening,
+    }
+}
+
+/// The constraints of `air`, the number of lookups it makes, and the number of chunks its quotient
+/// is split into, as the prover computes it.
+fn air_shape<F: Field, A: Air<SymbolicAirBuilder<F>>>(
+    air: &A,
+    trace_dims: &[Dimensions],
+    num_public_values: usize,
+    zk: bool,
+) -> (Vec<SymbolicExpression<F>>, usize, usize) {
+    let width = |i: usize| trace_dims.get(i).map_or(0, |dims| dims.width);
+    let (preprocessed_width, external_width) = (width(1), width(2));
+    let symbolic_constraints = get_symbolic_constraints_with_external::<F, A>(
+        air,
+        preprocessed_width,
+        external_width,
+        num_public_values,
+    );
+    let num_lookups =
+        get_symbolic_lookups::<F, A>(air, preprocessed_width, external_width, num_public_values)
+            .len();
+    let constraint_degree = symbolic_constraints
+        .iter()
+        .map(SymbolicExpression::degree_multiple)
+        .max()
+        .unwrap_or(0);
+    let quotient_degree = 1 << unchecked_log_quotient_degree(constraint_degree, zk);
+    (symbolic_constraints, num_lookups, quotient_degree)
+}
+
+/// The dimensions of the main trace as committed to, which a zero-knowledge proof doubles.
+fn committed_main<SC: StarkGenericConfig>(config: &SC, main: Dimensions) -> Dimensions {
+    Dimensions {
+        height: main.height << usize::from(config.zk()),
+        ..main
+    }
+}
+
+/// The dimensions of the random polynomial blinding a zero-knowledge proof's quotient of
+/// `quotient_size` evaluations, if the proof is zero-knowledge.
+fn quotient_blinding<SC: StarkGenericConfig>(
+    config: &SC,
+    quotient_size: usize,
+) -> Option<Dimensions> {
+    config.zk().then_some(Dimensions {
+        width: <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D,
+        height: quotient_size,
+    })
+}
+
+/// The matrices opened in each round, with the number of points each is opened at: the main trace
+/// at two points, each quotient matrix at one, then any other traces at two.
+fn opening_rounds(
+    committed_main: Dimensions,
+    other_traces: &[Dimensions],
+    lookup: Dimensions,
+    quotient_matrices: &[Dimensions],
+) -> Vec<Vec<(Dimensions, usize)>> {
+    let mut rounds = vec![
+        vec![(committed_main, 2)],
+        quotient_matrices.iter().map(|&dims| (dims, 1)).collect(),
+    ];
+    for dims in other_traces.iter().chain([&lookup]) {
+        if dims.width > 0 {
+            rounds.push(vec![(*dims, 2)]);
+        }
+    }
+    rounds
+}
+
+/// The multiplications evaluating `expr` takes, counting shared subexpressions each time they're
+/// used.
+fn num_muls<F>(expr: &SymbolicExpression<F>) -> usize {
+    match expr {
+        SymbolicExpression::Add { x, y, .. } | SymbolicExpression::Sub { x, y, .. } => {
+            num_muls(x) + num_muls(y)
+        }
+        SymbolicExpression::Mul { x, y, .. } => num_muls(x) + num_muls(y) + 1,
+        SymbolicExpression::Neg { x, .. } => num_muls(x),
+        _ => 0,
+    }
+}
@@ -0,0 +1,52 @@
+use alloc::vec;
+
+use p3_commit::Pcs;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+
+use crate::{Com, PcsProverData, StarkGenericConfig, Val};
+
+/// A trace committed to outside of a proof, e.g. the main trace of a previous proof which this
+/// one continues. The AIR refers to its rows through
+/// [`ExternalBuilder`](p3_air::ExternalBuilder), and it must have the same height as the trace.
+///
+/// Since the commitment is deterministic, committing to a previous proof's trace gives the
+/// commitment found in that proof, see [`Proof::trace_commitment`](crate::Proof::trace_commitment).
+pub struct ExternalTrace<SC: StarkGenericConfig> {
+    pub(crate) trace: RowMajorMatrix<Val<SC>>,
+    pub(crate) commitment: Com<SC>,
+    pub(crate) data: PcsProverData<SC>,
+}
+
+/// What the verifier is told about an external trace: the commitment, and the shape of the trace
+/// claimed to be behind it. The proof is checked against these, so they must come from a trusted
+/// source such as a previously verified proof.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalCommitment<Com> {
+    pub commitment: Com,
+    pub width: usize,
+    pub degree_bits: usize,
+}
+
+impl<SC: StarkGenericConfig> ExternalTrace<SC> {
+    pub fn commit(config: &SC, trace: RowMajorMatrix<Val<SC>>) -> Self {
+        let pcs = config.pcs();
+        let domain = pcs.natural_domain_for_degree(trace.height());
+        let (commitment, data) = pcs.commit(vec![(domain, trace.clone())]);
+        Self {
+            trace,
+            commitment,
+            data,
+        }
+    }
+
+    pub fn commitment(&self) -> ExternalCommitment<Com<SC>> {
+        ExternalCommitment {
+            commitment: self.commitment.clone(),
+            width: self.trace.width(),
+            degree_bits: log2_strict_usize(self.trace.height()),
+        }
+    }
+}
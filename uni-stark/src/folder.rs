@@ -1,15 +1,24 @@
 use alloc::vec::Vec;
 
-use p3_air::{AirBuilder, AirBuilderWithPublicValues};
+use p3_air::{AirBuilder, AirBuilderWithPublicValues, ExternalBuilder, LookupBuilder, PairBuilder};
 use p3_field::FieldAlgebra;
 use p3_matrix::dense::RowMajorMatrixView;
 use p3_matrix::stack::VerticalPair;
+use p3_matrix::Matrix;
 
-use crate::{PackedChallenge, PackedVal, StarkGenericConfig, Val};
+use crate::{Lookup, PackedChallenge, PackedVal, StarkGenericConfig, Val};
 
 #[derive(Debug)]
 pub struct ProverConstraintFolder<'a, SC: StarkGenericConfig> {
     pub main: RowMajorMatrixView<'a, PackedVal<SC>>,
+    /// Empty if the AIR has no preprocessed trace.
+    pub preprocessed: RowMajorMatrixView<'a, PackedVal<SC>>,
+    /// Empty if the proof has no external trace.
+    pub external: RowMajorMatrixView<'a, PackedVal<SC>>,
+    /// The running sums of the AIR's lookups, empty if it makes none.
+    pub lookup: RowMajorMatrixView<'a, PackedChallenge<SC>>,
+    pub lookup_challenge: SC::Challenge,
+    pub lookup_index: usize,
     pub public_values: &'a Vec<Val<SC>>,
     pub is_first_row: PackedVal<SC>,
     pub is_last_row: PackedVal<SC>,
@@ -24,6 +33,14 @@ type ViewPair<'a, T> = VerticalPair<RowMajorMatrixView<'a, T>, RowMajorMatrixVie
 #[derive(Debug)]
 pub struct VerifierConstraintFolder<'a, SC: StarkGenericConfig> {
     pub main: ViewPair<'a, SC::Challenge>,
+    /// Empty if the AIR has no preprocessed trace.
+    pub preprocessed: ViewPair<'a, SC::Challenge>,
+    /// Empty if the proof has no external trace.
+    pub external: ViewPair<'a, SC::Challenge>,
+    /// The running sums of the AIR's lookups, empty if it makes none.
+    pub lookup: ViewPair<'a, SC::Challenge>,
+    pub lookup_challenge: SC::Challenge,
+    pub lookup_index: usize,
     pub public_values: &'a Vec<Val<SC>>,
     pub is_first_row: SC::Challenge,
     pub is_last_row: SC::Challenge,
@@ -80,6 +97,51 @@ impl<SC: StarkGenericConfig> AirBuilderWithPublicValues for ProverConstraintFold
     }
 }
 
+impl<SC: StarkGenericConfig> PairBuilder for ProverConstraintFolder<'_, SC> {
+    #[inline]
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<SC: StarkGenericConfig> ExternalBuilder for ProverConstraintFolder<'_, SC> {
+    #[inline]
+    fn external(&self) -> Self::M {
+        self.external
+    }
+}
+
+impl<SC: StarkGenericConfig> LookupBuilder for ProverConstraintFolder<'_, SC> {
+    fn lookup_with_count<I0, I1, I2, I3>(
+        &mut self,
+        count: I0,
+        send: I1,
+        receive: I2,
+        multiplicity: I3,
+    ) where
+        I0: Into<Self::Expr>,
+        I1: Into<Self::Expr>,
+        I2: Into<Self::Expr>,
+        I3: Into<Self::Expr>,
+    {
+        let lookup = Lookup {
+            count: count.into(),
+            send: send.into(),
+            receive: receive.into(),
+            multiplicity: multiplicity.into(),
+        };
+        let x = lookup.constraint(
+            PackedChallenge::<SC>::from_f(self.lookup_challenge),
+            self.lookup.get(0, self.lookup_index),
+            self.lookup.get(1, self.lookup_index),
+        );
+        self.lookup_index += 1;
+        let alpha_power = self.alpha_powers[self.constraint_index];
+        self.accumulator += PackedChallenge::<SC>::from_f(alpha_power) * x;
+        self.constraint_index += 1;
+    }
+}
+
 impl<'a, SC: StarkGenericConfig> AirBuilder for VerifierConstraintFolder<'a, SC> {
     type F = Val<SC>;
     type Expr = SC::Challenge;
@@ -120,3 +182,45 @@ impl<SC: StarkGenericConfig> AirBuilderWithPublicValues for VerifierConstraintFo
         self.public_values
     }
 }
+
+impl<SC: StarkGenericConfig> PairBuilder for VerifierConstraintFolder<'_, SC> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<SC: StarkGenericConfig> ExternalBuilder for VerifierConstraintFolder<'_, SC> {
+    fn external(&self) -> Self::M {
+        self.external
+    }
+}
+
+impl<SC: StarkGenericConfig> LookupBuilder for VerifierConstraintFolder<'_, SC> {
+    fn lookup_with_count<I0, I1, I2, I3>(
+        &mut self,
+        count: I0,
+        send: I1,
+        receive: I2,
+        multiplicity: I3,
+    ) where
+        I0: Into<Self::Expr>,
+        I1: Into<Self::Expr>,
+        I2: Into<Self::Expr>,
+        I3: Into<Self::Expr>,
+    {
+        let lookup = Lookup {
+            count: count.into(),
+            send: send.into(),
+            receive: receive.into(),
+            multiplicity: multiplicity.into(),
+        };
+        let x = lookup.constraint(
+            self.lookup_challenge,
+            self.lookup.get(0, self.lookup_index),
+            self.lookup.get(1, self.lookup_index),
+        );
+        self.lookup_index += 1;
+        self.accumulator *= self.alpha;
+        self.accumulator += x;
+    }
+}
@@ -0,0 +1,130 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_air::BaseAir;
+use p3_commit::Pcs;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{Com, ProofSection, StarkGenericConfig, Val};
+
+pub(crate) type PcsProverData<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::ProverData;
+
+/// What the prover needs to know about the parts of an AIR which are fixed ahead of time.
+///
+/// The default key declares no optional sections, and is what [`prove`](crate::prove) uses.
+pub struct ProvingKey<SC: StarkGenericConfig> {
+    pub(crate) preprocessed: Option<PreprocessedProverData<SC>>,
+    /// The AIR's column names, which the debug constraint checker reports failures with.
+    pub(crate) column_names: Option<Vec<&'static str>>,
+}
+
+pub(crate) struct PreprocessedProverData<SC: StarkGenericConfig> {
+    pub(crate) width: usize,
+    pub(crate) degree_bits: usize,
+    pub(crate) commitment: Com<SC>,
+    pub(crate) data: PcsProverData<SC>,
+}
+
+/// What the verifier needs to know about the parts of an AIR which are fixed ahead of time. This
+/// also declares which optional sections a proof must contain; see [`ProofSection`].
+///
+/// The default key declares no optional sections, and is what [`verify`](crate::verify) uses.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct VerifyingKey<SC: StarkGenericConfig> {
+    pub(crate) preprocessed: Option<PreprocessedVerifierKey<Com<SC>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreprocessedVerifierKey<Com> {
+    pub(crate) width: usize,
+    pub(crate) degree_bits: usize,
+    pub(crate) commitment: Com,
+}
+
+impl<SC: StarkGenericConfig> Default for ProvingKey<SC> {
+    fn default() -> Self {
+        Self {
+            preprocessed: None,
+            column_names: None,
+        }
+    }
+}
+
+impl<SC: StarkGenericConfig> ProvingKey<SC> {
+    pub fn column_names(&self) -> Option<&[&'static str]> {
+        self.column_names.as_deref()
+    }
+}
+
+impl<SC: StarkGenericConfig> Default for VerifyingKey<SC> {
+    fn default() -> Self {
+        Self { preprocessed: None }
+    }
+}
+
+impl<SC: StarkGenericConfig> VerifyingKey<SC> {
+    /// Whether proofs checked against this key must contain `section`. The external and lookup
+    /// sections are not part of the key: the first is required when the verifier is given an
+    /// external commitment, and the second when the AIR makes lookups.
+    pub fn has_section(&self, section: ProofSection) -> bool {
+        match section {
+            ProofSection::Preprocessed => self.preprocessed.is_some(),
+            ProofSection::External | ProofSection::Lookup => false,
+        }
+    }
+}
+
+/// Commit to the fixed parts of `air`, i.e. its preprocessed trace if it has one, and note its
+/// column names.
+///
+/// Proofs made with the resulting proving key are for traces of the same height as the
+/// preprocessed trace.
+#[instrument(skip_all)]
+pub fn setup<SC, A>(config: &SC, air: &A) -> (ProvingKey<SC>, VerifyingKey<SC>)
+where
+    SC: StarkGenericConfig,
+    A: BaseAir<Val<SC>>,
+{
+    let (prover, verifier) = air
+        .preprocessed_trace()
+        .map(|trace| {
+            let width = trace.width();
+            let degree = trace.height();
+            let degree_bits = log2_strict_usize(degree);
+
+            let pcs = config.pcs();
+            let domain = pcs.natural_domain_for_degree(degree);
+            let (commitment, data) = pcs.commit(vec![(domain, trace)]);
+
+            let verifier = PreprocessedVerifierKey {
+                width,
+                degree_bits,
+                commitment: commitment.clone(),
+            };
+            let prover = PreprocessedProverData {
+                width,
+                degree_bits,
+                commitment,
+                data,
+            };
+            (prover, verifier)
+        })
+        .unzip();
+
+    (
+        ProvingKey {
+            preprocessed: prover,
+            column_names: air.column_names(),
+        },
+        VerifyingKey {
+            preprocessed: verifier,
+        },
+    )
+}
@@ -4,27 +4,47 @@
 
 extern crate alloc;
 
+mod cache;
+mod checkpoint;
 mod config;
+mod cost;
+mod external;
 mod folder;
+mod key;
+mod lookup;
+mod machine;
 mod proof;
 mod prover;
+mod security;
+mod stacked;
 mod symbolic_builder;
 mod symbolic_expression;
 mod symbolic_variable;
+mod trace;
 mod verifier;
 mod zerofier_coset;
 
 #[cfg(debug_assertions)]
 mod check_constraints;
 
+pub use cache::*;
 #[cfg(debug_assertions)]
 pub use check_constraints::*;
+pub use checkpoint::*;
 pub use config::*;
+pub use cost::*;
+pub use external::*;
 pub use folder::*;
+pub use key::*;
+pub use lookup::*;
+pub use machine::*;
 pub use proof::*;
 pub use prover::*;
+pub use security::*;
+pub use stacked::*;
 pub use symbolic_builder::*;
 pub use symbolic_expression::*;
 pub use symbolic_variable::*;
+pub use trace::*;
 pub use verifier::*;
 pub use zerofier_coset::*;
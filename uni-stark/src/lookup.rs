@@ -0,0 +1,136 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Mul, Sub};
+
+use p3_field::{batch_multiplicative_inverse, ExtensionField, Field, FieldAlgebra};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::{Entry, SymbolicExpression};
+
+/// A lookup made by an AIR through [`LookupBuilder`](p3_air::LookupBuilder): on each row, `send`
+/// is sent `count` times and `receive` is received `multiplicity` times.
+///
+/// Lookups are checked with LogUp. Given a challenge `beta` sampled after the main trace is
+/// committed to, the prover commits to a running sum `s` in the extension field with `s_0 = 0` and
+///
+/// ```text
+/// s_{i + 1} = s_i + count_i / (beta - send_i) - multiplicity_i / (beta - receive_i)
+/// ```
+///
+/// The step is constrained on every row, including from the last row back to the first, which
+/// checks the grand sum: the terms must sum to zero over the whole trace, which holds exactly when
+/// the values sent and received are the same multiset.
+#[derive(Debug, Clone)]
+pub struct Lookup<E> {
+    pub count: E,
+    pub send: E,
+    pub receive: E,
+    pub multiplicity: E,
+}
+
+impl<Expr> Lookup<Expr> {
+    /// The step of the running sum from `local` to `next`, with the denominators cleared.
+    pub(crate) fn constraint<ExprEF>(self, challenge: ExprEF, local: ExprEF, next: ExprEF) -> ExprEF
+    where
+        ExprEF: FieldAlgebra + Sub<Expr, Output = ExprEF> + Mul<Expr, Output = ExprEF>,
+    {
+        let send_denominator = challenge.clone() - self.send;
+        let receive_denominator = challenge - self.receive;
+        send_denominator.clone() * receive_denominator.clone() * (next - local)
+            - receive_denominator * self.count
+            + send_denominator * self.multiplicity
+    }
+}
+
+/// Evaluate each of `lookups` on every row of the trace, giving the values for each lookup in row
+/// order.
+pub(crate) fn evaluate_lookups<F: Field>(
+    lookups: &[Lookup<SymbolicExpression<F>>],
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    external: Option<&RowMajorMatrix<F>>,
+    public_values: &[F],
+) -> Vec<Vec<Lookup<F>>> {
+    let height = main.height();
+    let mut values = vec![Vec::with_capacity(height); lookups.len()];
+    for i in 0..height {
+        let row = Row {
+            preprocessed: preprocessed.map_or([&[][..]; 2], |m| m.row_pair(i).into()),
+            main: main.row_pair(i).into(),
+            external: external.map_or([&[][..]; 2], |m| m.row_pair(i).into()),
+            public_values,
+            is_first_row: F::from_bool(i == 0),
+            is_last_row: F::from_bool(i == height - 1),
+            is_transition: F::from_bool(i != height - 1),
+        };
+        for (lookup, values) in lookups.iter().zip(&mut values) {
+            values.push(Lookup {
+                count: row.evaluate(&lookup.count),
+                send: row.evaluate(&lookup.send),
+                receive: row.evaluate(&lookup.receive),
+                multiplicity: row.evaluate(&lookup.multiplicity),
+            });
+        }
+    }
+    values
+}
+
+/// The running sum of each lookup, as a column of the result.
+pub(crate) fn running_sums<F: Field, EF: ExtensionField<F>>(
+    lookup_values: &[Vec<Lookup<F>>],
+    challenge: EF,
+) -> RowMajorMatrix<EF> {
+    let width = lookup_values.len();
+    let height = lookup_values.first().map_or(0, Vec::len);
+    let mut sums = vec![EF::ZERO; width * height];
+    for (k, values) in lookup_values.iter().enumerate() {
+        let denominators = values
+            .iter()
+            .flat_map(|lookup| [challenge - lookup.send, challenge - lookup.receive])
+            .collect::<Vec<_>>();
+        let inverses = batch_multiplicative_inverse(&denominators);
+        let mut sum = EF::ZERO;
+        for (i, (lookup, inverses)) in values.iter().zip(inverses.chunks_exact(2)).enumerate() {
+            sums[i * width + k] = sum;
+            sum += inverses[0] * lookup.count - inverses[1] * lookup.multiplicity;
+        }
+    }
+    RowMajorMatrix::new(sums, width)
+}
+
+/// The local and next rows of each trace, and the other values which the expressions of a lookup
+/// may refer to.
+struct Row<'a, F> {
+    preprocessed: [&'a [F]; 2],
+    main: [&'a [F]; 2],
+    external: [&'a [F]; 2],
+    public_values: &'a [F],
+    is_first_row: F,
+    is_last_row: F,
+    is_transition: F,
+}
+
+impl<F: Field> Row<'_, F> {
+    fn evaluate(&self, expr: &SymbolicExpression<F>) -> F {
+        match expr {
+            SymbolicExpression::Variable(v) => match v.entry {
+                Entry::Preprocessed { offset } => self.preprocessed[offset][v.index],
+                Entry::Main { offset } => self.main[offset][v.index],
+                Entry::External { offset } => self.external[offset][v.index],
+                Entry::Public => self.public_values[v.index],
+                Entry::Permutation { .. } | Entry::Challenge => {
+                    panic!("lookups can't refer to the running sums or their challenge")
+                }
+            },
+            SymbolicExpression::IsFirstRow => self.is_first_row,
+            SymbolicExpression::IsLastRow => self.is_last_row,
+            SymbolicExpression::IsTransition => self.is_transition,
+            SymbolicExpression::Constant(c) => *c,
+            SymbolicExpression::Add { x, y, .. } => self.evaluate(x) + self.evaluate(y),
+            SymbolicExpression::Sub { x, y, .. } => self.evaluate(x) - self.evaluate(y),
+            SymbolicExpression::Neg { x, .. } => -self.evaluate(x),
+            SymbolicExpression::Mul { x, y, .. } => self.evaluate(x) * self.evaluate(y),
+        }
+    }
+}
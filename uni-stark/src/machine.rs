@@ -0,0 +1,454 @@
+//! Proving several AIRs together, as the tables of one machine, under a single commitment to their
+//! traces and a single transcript.
+//!
+//! The traces may have different heights. They're committed to in one batch, and so are the chunks
+//! of their quotients, which are all combined with the same `alpha`, so that the whole machine is
+//! opened by one PCS opening proof. Each table only has a main trace: preprocessed and external
+//! traces and lookups aren't supported here.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{izip, Itertools};
+use p3_air::{Air, BaseAir};
+use p3_challenger::{CanObserve, FieldChallenger};
+use p3_commit::{Pcs, PolynomialSpace};
+use p3_field::{FieldAlgebra, FieldExtensionAlgebra};
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::stack::VerticalPair;
+use p3_matrix::{Dimensions, Matrix};
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+use tracing::{info_span, instrument};
+
+use crate::proof::PcsProof;
+use crate::prover::{log_quotient_degree, quotient_values};
+use crate::security::enforce_security;
+use crate::verifier::{check_degree_bits, recompose_quotient};
+use crate::{
+    get_log_quotient_degree_with_external, get_symbolic_constraints_with_external,
+    get_symbolic_lookups, AirParameters, Com, PcsError, ProverConstraintFolder, ProverError,
+    StarkGenericConfig, SymbolicAirBuilder, SymbolicExpression, Val, VerificationError,
+    VerifierConstraintFolder,
+};
+
+/// An AIR which can be proven as a table of a machine by [`prove_multi`], behind a trait object so
+/// that the tables can be of different types.
+#[cfg(debug_assertions)]
+pub trait DynamicAir<SC: StarkGenericConfig>:
+    Air<SymbolicAirBuilder<Val<SC>>>
+    + for<'a> Air<ProverConstraintFolder<'a, SC>>
+    + for<'a> Air<VerifierConstraintFolder<'a, SC>>
+    + for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>
+{
+}
+
+#[cfg(debug_assertions)]
+impl<SC, A> DynamicAir<SC> for A
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>
+        + for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+{
+}
+
+/// An AIR which can be proven as a table of a machine by [`prove_multi`], behind a trait object so
+/// that the tables can be of different types.
+#[cfg(not(debug_assertions))]
+pub trait DynamicAir<SC: StarkGenericConfig>:
+    Air<SymbolicAirBuilder<Val<SC>>>
+    + for<'a> Air<ProverConstraintFolder<'a, SC>>
+    + for<'a> Air<VerifierConstraintFolder<'a, SC>>
+{
+}
+
+#[cfg(not(debug_assertions))]
+impl<SC, A> DynamicAir<SC> for A
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+}
+
+/// A proof of every table of a machine, made by [`prove_multi`].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MultiProof<SC: StarkGenericConfig> {
+    pub(crate) commitments: MultiCommitments<Com<SC>>,
+    /// The openings of each table, in the order the tables were given.
+    pub(crate) opened_values: Vec<TableOpenedValues<SC::Challenge>>,
+    pub(crate) opening_proof: PcsProof<SC>,
+    /// The log of the height of each table's trace.
+    pub(crate) degree_bits: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MultiCommitments<Com> {
+    /// The commitment to the traces of all the tables.
+    pub(crate) traces: Com,
+    /// The commitment to the quotient chunks of all the tables.
+    pub(crate) quotient_chunks: Com,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableOpenedValues<Challenge> {
+    pub(crate) trace_local: Vec<Challenge>,
+    pub(crate) trace_next: Vec<Challenge>,
+    pub(crate) quotient_chunks: Vec<Vec<Challenge>>,
+}
+
+impl<SC: StarkGenericConfig> MultiProof<SC> {
+    /// The log of the height of each table's trace.
+    pub fn degree_bits(&self) -> &[usize] {
+        &self.degree_bits
+    }
+}
+
+/// Prove that each of `traces` satisfies the AIR of the same index in `airs`, with the public values
+/// of the same index in `public_values`.
+///
+/// # Panics
+/// If the numbers of AIRs, traces and public values differ, an AIR makes lookups, or `config` is
+/// zero-knowledge.
+#[instrument(skip_all)]
+pub fn prove_multi<SC: StarkGenericConfig>(
+    config: &SC,
+    airs: &[&dyn DynamicAir<SC>],
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<MultiProof<SC>, ProverError<PcsError<SC>>> {
+    assert_eq!(airs.len(), traces.len(), "each table needs one trace");
+    assert_eq!(
+        airs.len(),
+        public_values.len(),
+        "each table needs its public values"
+    );
+    assert!(
+        !config.zk(),
+        "zero-knowledge proofs of several tables aren't supported"
+    );
+
+    // Parallel phases below (DFTs, Merkle trees, FRI and the quotients) pick this up.
+    let _parallelism = config.parallelism().enter();
+
+    let symbolic_constraints = izip!(airs, public_values)
+        .map(|(&air, public_values)| {
+            assert!(
+                get_symbolic_lookups::<Val<SC>, _>(air, 0, 0, public_values.len()).is_empty(),
+                "tables proven together can't make lookups"
+            );
+            get_symbolic_constraints_with_external::<Val<SC>, _>(air, 0, 0, public_values.len())
+        })
+        .collect_vec();
+
+    #[cfg(debug_assertions)]
+    for (&air, trace, public_values, constraints) in
+        izip!(airs, &traces, public_values, &symbolic_constraints)
+    {
+        crate::check_constraints::check_constraints(
+            air,
+            None,
+            trace,
+            None,
+            public_values,
+            constraints,
+            BaseAir::<Val<SC>>::column_names(air).as_deref(),
+        );
+    }
+
+    let pcs = config.pcs();
+    let degree_bits = traces
+        .iter()
+        .map(|trace| log2_strict_usize(trace.height()))
+        .collect_vec();
+    for (&log_degree, constraints) in izip!(&degree_bits, &symbolic_constraints) {
+        enforce_security(
+            config,
+            AirParameters::from_constraints(constraints),
+            log_degree,
+        )
+        .map_err(ProverError::InsufficientSecurity)?;
+    }
+    let trace_domains = traces
+        .iter()
+        .map(|trace| pcs.natural_domain_for_degree(trace.height()))
+        .collect_vec();
+
+    let traces_shape = traces.iter().map(|trace| trace.dimensions()).collect_vec();
+    let (traces_commit, traces_data) = info_span!("commit to trace data")
+        .in_scope(|| pcs.commit(izip!(trace_domains.clone(), traces).collect_vec()));
+
+    // Observe the instance.
+    challenger.begin_phase("instance");
+    challenger.observe(Val::<SC>::from_canonical_usize(airs.len()));
+    for &log_degree in &degree_bits {
+        challenger.observe(Val::<SC>::from_canonical_usize(log_degree));
+    }
+    challenger.observe_shape(&traces_shape);
+    challenger.observe(traces_commit.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+
+    challenger.begin_phase("quotient");
+    let alpha: SC::Challenge = challenger.sample_ext_element();
+
+    let mut quotient_degrees = Vec::with_capacity(airs.len());
+    let mut chunks = Vec::new();
+    for (i, (&air, public_values, constraints)) in
+        izip!(airs, public_values, &symbolic_constraints).enumerate()
+    {
+        let constraint_degree = constraints
+            .iter()
+            .map(SymbolicExpression::degree_multiple)
+            .max()
+            .unwrap_or(0);
+        let log_quotient_degree = log_quotient_degree(config, constraint_degree)?;
+        let quotient_degree = 1 << log_quotient_degree;
+        let trace_domain = trace_domains[i];
+        let quotient_domain =
+            trace_domain.create_disjoint_domain(trace_domain.size() << log_quotient_degree);
+
+        let trace_on_quotient_domain =
+            pcs.get_evaluations_on_domain(&traces_data, i, quotient_domain);
+        let selectors = trace_domain.selectors_on_coset(quotient_domain);
+        let quotient_values = quotient_values(
+            air,
+            public_values,
+            trace_domain,
+            quotient_domain,
+            &selectors,
+            trace_on_quotient_domain,
+            None,
+            None,
+            None,
+            SC::Challenge::ZERO,
+            alpha,
+            constraints.len(),
+        );
+        let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
+        chunks.extend(izip!(
+            quotient_domain.split_domains(quotient_degree),
+            quotient_domain.split_evals(quotient_degree, quotient_flat)
+        ));
+        quotient_degrees.push(quotient_degree);
+    }
+
+    let quotient_shape = chunks
+        .iter()
+        .map(|(_, chunk)| chunk.dimensions())
+        .collect_vec();
+    let (quotient_commit, quotient_data) =
+        info_span!("commit to quotient poly chunks").in_scope(|| pcs.commit(chunks));
+    challenger.observe_shape(&quotient_shape);
+    challenger.observe(quotient_commit.clone());
+
+    challenger.begin_phase("opening");
+    let zeta: SC::Challenge = challenger.sample_ext_element();
+
+    let (opened_values, opening_proof) = info_span!("open")
+        .in_scope(|| {
+            let trace_points = trace_domains
+                .iter()
+                .map(|domain| vec![zeta, domain.next_point(zeta).unwrap()])
+                .collect_vec();
+            let quotient_points = vec![vec![zeta]; quotient_degrees.iter().sum()];
+            pcs.open(
+                vec![
+                    (&traces_data, trace_points),
+                    (&quotient_data, quotient_points),
+                ],
+                challenger,
+            )
+        })
+        .map_err(ProverError::Opening)?;
+    let mut quotient_chunks = opened_values[1].iter().map(|chunk| chunk[0].clone());
+    let opened_values = izip!(&opened_values[0], quotient_degrees)
+        .map(|(trace, quotient_degree)| TableOpenedValues {
+            trace_local: trace[0].clone(),
+            trace_next: trace[1].clone(),
+            quotient_chunks: quotient_chunks.by_ref().take(quotient_degree).collect(),
+        })
+        .collect();
+
+    Ok(MultiProof {
+        commitments: MultiCommitments {
+            traces: traces_commit,
+            quotient_chunks: quotient_commit,
+        },
+        opened_values,
+        opening_proof,
+        degree_bits,
+    })
+}
+
+/// Verify a proof made by [`prove_multi`] of the tables `airs`, with the public values of the same
+/// index in `public_values`.
+#[instrument(skip_all)]
+pub fn verify_multi<SC: StarkGenericConfig>(
+    config: &SC,
+    airs: &[&dyn DynamicAir<SC>],
+    challenger: &mut SC::Challenger,
+    proof: &MultiProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>> {
+    let MultiProof {
+        commitments,
+        opened_values,
+        opening_proof,
+        degree_bits,
+    } = proof;
+    let ext_degree = <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D;
+
+    if degree_bits.len() != airs.len()
+        || opened_values.len() != airs.len()
+        || public_values.len() != airs.len()
+    {
+        return Err(VerificationError::InvalidProofShape);
+    }
+    let log_quotient_degrees = izip!(airs, public_values)
+        .map(|(&air, public_values)| {
+            get_log_quotient_degree_with_external::<Val<SC>, _>(air, 0, 0, public_values.len())
+        })
+        .collect_vec();
+    for (&log_degree, &log_quotient_degree) in izip!(degree_bits, &log_quotient_degrees) {
+        check_degree_bits(config, log_degree, log_quotient_degree)?;
+    }
+    let quotient_degrees = log_quotient_degrees
+        .iter()
+        .map(|&log_quotient_degree| 1 << log_quotient_degree)
+        .collect_vec();
+    let valid_shape =
+        izip!(airs, opened_values, &quotient_degrees).all(|(&air, values, &quotient_degree)| {
+            let width = BaseAir::<Val<SC>>::width(air);
+            values.trace_local.len() == width
+                && values.trace_next.len() == width
+                && values.quotient_chunks.len() == quotient_degree
+                && values
+                    .quotient_chunks
+                    .iter()
+                    .all(|qc| qc.len() == ext_degree)
+        });
+    if !valid_shape {
+        return Err(VerificationError::InvalidProofShape);
+    }
+
+    let pcs = config.pcs();
+    let trace_domains = degree_bits
+        .iter()
+        .map(|&log_degree| pcs.natural_domain_for_degree(1 << log_degree))
+        .collect_vec();
+    let quotient_chunks_domains = izip!(&trace_domains, &quotient_degrees)
+        .map(|(trace_domain, &quotient_degree)| {
+            trace_domain
+                .create_disjoint_domain(trace_domain.size() * quotient_degree)
+                .split_domains(quotient_degree)
+        })
+        .collect_vec();
+
+    // Observe the instance.
+    challenger.begin_phase("instance");
+    challenger.observe(Val::<SC>::from_canonical_usize(airs.len()));
+    for &log_degree in degree_bits {
+        challenger.observe(Val::<SC>::from_canonical_usize(log_degree));
+    }
+    let traces_shape = izip!(airs, &trace_domains)
+        .map(|(&air, domain)| Dimensions {
+            width: BaseAir::<Val<SC>>::width(air),
+            height: domain.size(),
+        })
+        .collect_vec();
+    challenger.observe_shape(&traces_shape);
+    challenger.observe(commitments.traces.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+
+    challenger.begin_phase("quotient");
+    let alpha: SC::Challenge = challenger.sample_ext_element();
+    let quotient_shape = quotient_chunks_domains
+        .iter()
+        .flatten()
+        .map(|domain| Dimensions {
+            width: ext_degree,
+            height: domain.size(),
+        })
+        .collect_vec();
+    challenger.observe_shape(&quotient_shape);
+    challenger.observe(commitments.quotient_chunks.clone());
+
+    challenger.begin_phase("opening");
+    let zeta: SC::Challenge = challenger.sample_ext_element();
+
+    let trace_round = izip!(&trace_domains, opened_values)
+        .map(|(domain, values)| {
+            (
+                *domain,
+                vec![
+                    (zeta, values.trace_local.clone()),
+                    (domain.next_point(zeta).unwrap(), values.trace_next.clone()),
+                ],
+            )
+        })
+        .collect_vec();
+    let quotient_round = izip!(&quotient_chunks_domains, opened_values)
+        .flat_map(|(domains, values)| {
+            izip!(domains, &values.quotient_chunks)
+                .map(|(domain, chunk)| (*domain, vec![(zeta, chunk.clone())]))
+        })
+        .collect_vec();
+    pcs.verify(
+        vec![
+            (commitments.traces.clone(), trace_round),
+            (commitments.quotient_chunks.clone(), quotient_round),
+        ],
+        opening_proof,
+        challenger,
+    )
+    .map_err(VerificationError::InvalidOpeningArgument)?;
+
+    for (&air, values, public_values, trace_domain, chunk_domains) in izip!(
+        airs,
+        opened_values,
+        public_values,
+        &trace_domains,
+        &quotient_chunks_domains
+    ) {
+        let quotient = recompose_quotient::<SC>(chunk_domains, &values.quotient_chunks, zeta);
+
+        let sels = trace_domain.selectors_at_point(zeta);
+        let empty = VerticalPair::new(
+            RowMajorMatrixView::new_row(&[][..]),
+            RowMajorMatrixView::new_row(&[][..]),
+        );
+        let mut folder = VerifierConstraintFolder {
+            main: VerticalPair::new(
+                RowMajorMatrixView::new_row(&values.trace_local),
+                RowMajorMatrixView::new_row(&values.trace_next),
+            ),
+            preprocessed: empty,
+            external: empty,
+            lookup: empty,
+            lookup_challenge: SC::Challenge::ZERO,
+            lookup_index: 0,
+            public_values,
+            is_first_row: sels.is_first_row,
+            is_last_row: sels.is_last_row,
+            is_transition: sels.is_transition,
+            alpha,
+            accumulator: SC::Challenge::ZERO,
+        };
+        Air::eval(air, &mut folder);
+
+        if folder.accumulator * sels.inv_zeroifier != quotient {
+            return Err(VerificationError::OodEvaluationMismatch);
+        }
+    }
+
+    Ok(())
+}
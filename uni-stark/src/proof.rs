@@ -1,15 +1,16 @@
 use alloc::vec::Vec;
 
 use p3_commit::Pcs;
+use p3_util::encoding::{decode_versioned, encode_versioned, CanonicalEncoding, DecodingError};
 use serde::{Deserialize, Serialize};
 
 use crate::StarkGenericConfig;
 
-type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+pub(crate) type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
     <SC as StarkGenericConfig>::Challenger,
 >>::Commitment;
-type PcsProof<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+pub(crate) type PcsProof<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
     <SC as StarkGenericConfig>::Challenger,
 >>::Proof;
@@ -26,6 +27,8 @@ pub struct Proof<SC: StarkGenericConfig> {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Commitments<Com> {
     pub(crate) trace: Com,
+    /// The commitment to the running sums of the AIR's lookups, if it makes any.
+    pub(crate) lookup: Option<Com>,
     pub(crate) quotient_chunks: Com,
 }
 
@@ -33,5 +36,332 @@ pub struct Commitments<Com> {
 pub struct OpenedValues<Challenge> {
     pub(crate) trace_local: Vec<Challenge>,
     pub(crate) trace_next: Vec<Challenge>,
+    /// The openings of the quotient's chunks, followed in a zero-knowledge proof by that of the
+    /// random polynomial which blinds them.
     pub(crate) quotient_chunks: Vec<Vec<Challenge>>,
+    pub(crate) preprocessed: Option<OpenedRows<Challenge>>,
+    pub(crate) external: Option<OpenedRows<Challenge>>,
+    /// The running sums of the lookups, each flattened to its base field coefficients.
+    pub(crate) lookup: Option<OpenedRows<Challenge>>,
+}
+
+/// The openings of a trace which is committed to outside of the proof, such as the preprocessed
+/// trace, whose commitment is in the verifying key.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenedRows<Challenge> {
+    pub(crate) local: Vec<Challenge>,
+    pub(crate) next: Vec<Challenge>,
+}
+
+/// A section which a proof may or may not contain, depending on the AIR. Which sections must be
+/// present is determined by the [`VerifyingKey`](crate::VerifyingKey) and by whether the
+/// verifier is given an [`ExternalCommitment`](crate::ExternalCommitment), and the verifier
+/// rejects any proof which differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofSection {
+    /// Openings of the AIR's preprocessed trace.
+    Preprocessed,
+    /// Openings of an external trace, see [`ExternalBuilder`](p3_air::ExternalBuilder).
+    External,
+    /// The commitment to and openings of the running sums of the AIR's lookups, see
+    /// [`LookupBuilder`](p3_air::LookupBuilder).
+    Lookup,
+}
+
+impl ProofSection {
+    pub const ALL: [Self; 3] = [Self::Preprocessed, Self::External, Self::Lookup];
+}
+
+impl<SC: StarkGenericConfig> Proof<SC> {
+    /// Whether this proof contains `section`.
+    pub fn has_section(&self, section: ProofSection) -> bool {
+        match section {
+            ProofSection::Preprocessed => self.opened_values.preprocessed.is_some(),
+            ProofSection::External => self.opened_values.external.is_some(),
+            ProofSection::Lookup => self.opened_values.lookup.is_some(),
+        }
+    }
+
+    /// The commitment to the main trace, which a later proof can refer to as its external trace.
+    pub const fn trace_commitment(&self) -> &Com<SC> {
+        &self.commitments.trace
+    }
+
+    /// The log of the height of the trace.
+    pub const fn degree_bits(&self) -> usize {
+        self.degree_bits
+    }
+}
+
+/// The version of the byte encoding produced by [`Proof::to_bytes`]. This is bumped whenever the
+/// layout of a proof changes.
+pub const PROOF_ENCODING_VERSION: u32 = 6;
+
+impl<SC: StarkGenericConfig> Proof<SC>
+where
+    Com<SC>: CanonicalEncoding,
+    PcsProof<SC>: CanonicalEncoding,
+    SC::Challenge: CanonicalEncoding,
+{
+    /// Encode this proof in the canonical format described in [`p3_util::encoding`], prefixed
+    /// with [`PROOF_ENCODING_VERSION`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_versioned(PROOF_ENCODING_VERSION, self)
+    }
+
+    /// Decode a proof produced by [`Proof::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodingError> {
+        decode_versioned(PROOF_ENCODING_VERSION, bytes)
+    }
+}
+
+impl<SC: StarkGenericConfig> CanonicalEncoding for Proof<SC>
+where
+    Com<SC>: CanonicalEncoding,
+    PcsProof<SC>: CanonicalEncoding,
+    SC::Challenge: CanonicalEncoding,
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.commitments.encode(out);
+        self.opened_values.encode(out);
+        self.opening_proof.encode(out);
+        self.degree_bits.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            commitments: CanonicalEncoding::decode(input)?,
+            opened_values: CanonicalEncoding::decode(input)?,
+            opening_proof: CanonicalEncoding::decode(input)?,
+            degree_bits: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+impl<Com: CanonicalEncoding> CanonicalEncoding for Commitments<Com> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.trace.encode(out);
+        self.lookup.encode(out);
+        self.quotient_chunks.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            trace: CanonicalEncoding::decode(input)?,
+            lookup: CanonicalEncoding::decode(input)?,
+            quotient_chunks: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+impl<Challenge: CanonicalEncoding> CanonicalEncoding for OpenedValues<Challenge> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.trace_local.encode(out);
+        self.trace_next.encode(out);
+        self.quotient_chunks.encode(out);
+        self.preprocessed.encode(out);
+        self.external.encode(out);
+        self.lookup.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            trace_local: CanonicalEncoding::decode(input)?,
+            trace_next: CanonicalEncoding::decode(input)?,
+            quotient_chunks: CanonicalEncoding::decode(input)?,
+            preprocessed: CanonicalEncoding::decode(input)?,
+            external: CanonicalEncoding::decode(input)?,
+            lookup: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+impl<Challenge: CanonicalEncoding> CanonicalEncoding for OpenedRows<Challenge> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.local.encode(out);
+        self.next.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        Ok(Self {
+            local: CanonicalEncoding::decode(input)?,
+            next: CanonicalEncoding::decode(input)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use alloc::string::String;
+    use alloc::{format, vec};
+
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_commit::ExtensionMmcs;
+    use p3_dft::Radix2DitParallel;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{Field, FieldAlgebra, FieldExtensionAlgebra};
+    use p3_fri::{
+        BatchOpening, CommitPhaseMode, CommitPhaseProofStep, FriProof, QueryProof, TwoAdicFriPcs,
+    };
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_symmetric::{Hash, PaddingFreeSponge, TruncatedPermutation};
+
+    use super::*;
+    use crate::StarkConfig;
+
+    type Val = BabyBear;
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type ValMmcs =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+    type Challenge = BinomialExtensionField<Val, 4>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type Pcs = TwoAdicFriPcs<Val, Radix2DitParallel<Val>, ValMmcs, ChallengeMmcs>;
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+    fn val(k: u32) -> Val {
+        Val::from_canonical_u32(k)
+    }
+
+    fn challenge(k: u32) -> Challenge {
+        Challenge::from_base_fn(|i| val(k + i as u32))
+    }
+
+    fn digest(k: u32) -> [Val; 8] {
+        core::array::from_fn(|i| val(k + i as u32))
+    }
+
+    /// The golden file of `golden_proof`'s encoding, relative to the crate root.
+    const GOLDEN_FILE: &str = "tests/golden/proof.hex";
+
+    /// A proof with small, distinct values in every position, so that the golden file can be
+    /// checked by hand against the layout in `p3_util::encoding`.
+    fn golden_proof() -> Proof<MyConfig> {
+        let query_proof = QueryProof {
+            input_proof: vec![BatchOpening {
+                opened_values: vec![vec![val(49), val(50)]],
+                opening_proof: vec![digest(51)],
+            }],
+            commit_phase_openings: vec![CommitPhaseProofStep {
+                sibling_values: vec![challenge(59)],
+                opening_proof: vec![digest(63)],
+            }],
+        };
+        Proof {
+            commitments: Commitments {
+                trace: Hash::from(digest(1)),
+                lookup: Some(Hash::from(digest(83))),
+                quotient_chunks: Hash::from(digest(9)),
+            },
+            opened_values: OpenedValues {
+                trace_local: vec![challenge(17)],
+                trace_next: vec![challenge(21)],
+                quotient_chunks: vec![vec![challenge(25)]],
+                preprocessed: Some(OpenedRows {
+                    local: vec![challenge(75)],
+                    next: vec![challenge(79)],
+                }),
+                external: None,
+                lookup: Some(OpenedRows {
+                    local: vec![challenge(91)],
+                    next: vec![challenge(95)],
+                }),
+            },
+            opening_proof: FriProof {
+                commit_phase_commits: vec![Hash::from(digest(29)), Hash::from(digest(41))],
+                commit_phase_modes: vec![
+                    CommitPhaseMode::Queried,
+                    CommitPhaseMode::Direct(vec![challenge(37)]),
+                ],
+                commit_phase_log_arities: vec![1, 1],
+                query_proofs: vec![query_proof],
+                final_poly: vec![challenge(71)],
+                pow_witness: Val::NEG_ONE,
+            },
+            degree_bits: 3,
+        }
+    }
+
+    fn decode_hex(hex: &str) -> Vec<u8> {
+        let digits: Vec<u8> = hex
+            .bytes()
+            .filter(|b| !b.is_ascii_whitespace())
+            .map(|b| (b as char).to_digit(16).unwrap() as u8)
+            .collect();
+        digits.chunks_exact(2).map(|d| (d[0] << 4) | d[1]).collect()
+    }
+
+    /// Lowercase hex, 32 bytes to a line.
+    fn encode_hex(bytes: &[u8]) -> String {
+        bytes
+            .chunks(32)
+            .map(|line| line.iter().map(|b| format!("{b:02x}")).collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn encoding_matches_golden_file() {
+        // If this fails, the proof encoding changed: bump `PROOF_ENCODING_VERSION` and regenerate
+        // the golden file with `regenerate_golden_file` rather than editing it to match.
+        let golden = decode_hex(include_str!("../tests/golden/proof.hex"));
+        assert_eq!(golden_proof().to_bytes(), golden);
+
+        let decoded = Proof::<MyConfig>::from_bytes(&golden).unwrap();
+        assert_eq!(decoded.to_bytes(), golden);
+    }
+
+    /// Rewrites the golden file after a deliberate change to the encoding, with
+    /// `cargo test -p p3-uni-stark --lib regenerate_golden_file -- --ignored`.
+    #[test]
+    #[ignore]
+    fn regenerate_golden_file() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_FILE);
+        std::fs::write(path, encode_hex(&golden_proof().to_bytes())).unwrap();
+    }
+
+    #[test]
+    fn rejects_malformed_encodings() {
+        let bytes = golden_proof().to_bytes();
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] += 1;
+        assert!(matches!(
+            Proof::<MyConfig>::from_bytes(&wrong_version),
+            Err(DecodingError::UnsupportedVersion { expected, found })
+                if expected == PROOF_ENCODING_VERSION && found == PROOF_ENCODING_VERSION + 1
+        ));
+
+        assert!(matches!(
+            Proof::<MyConfig>::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DecodingError::Truncated)
+        ));
+
+        // The first word of the trace commitment, replaced by the BabyBear prime.
+        let mut non_canonical = bytes.clone();
+        non_canonical[4..8].copy_from_slice(&0x7800_0001_u32.to_le_bytes());
+        assert!(matches!(
+            Proof::<MyConfig>::from_bytes(&non_canonical),
+            Err(DecodingError::NonCanonical)
+        ));
+
+        // The tag of the preprocessed section.
+        let mut invalid_tag = bytes.clone();
+        invalid_tag[165] = 2;
+        assert!(matches!(
+            Proof::<MyConfig>::from_bytes(&invalid_tag),
+            Err(DecodingError::InvalidTag(2))
+        ));
+
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert!(matches!(
+            Proof::<MyConfig>::from_bytes(&trailing),
+            Err(DecodingError::TrailingBytes)
+        ));
+    }
 }
@@ -1,23 +1,54 @@
+use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
 
 use itertools::{izip, Itertools};
 use p3_air::Air;
-use p3_challenger::{CanObserve, CanSample, FieldChallenger};
-use p3_commit::{Pcs, PolynomialSpace};
+use p3_challenger::{CanObserve, FieldChallenger};
+use p3_commit::{LagrangeSelectors, Pcs, PolynomialSpace};
 use p3_field::{FieldAlgebra, FieldExtensionAlgebra, PackedValue};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_ceil_usize, log2_strict_usize};
+use rand::distributions::{Distribution, Standard};
+use rand::{CryptoRng, Rng};
 use tracing::{info_span, instrument};
 
+use crate::checkpoint::{CheckpointHook, Resumed};
+use crate::lookup::{evaluate_lookups, running_sums};
+use crate::security::enforce_security;
 use crate::{
-    get_symbolic_constraints, Commitments, Domain, OpenedValues, PackedChallenge, PackedVal, Proof,
-    ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder, SymbolicExpression, Val,
+    get_symbolic_constraints_with_external, get_symbolic_lookups, AirParameters, CheckpointError,
+    Commitments, Domain, ExternalTrace, InsufficientSecurity, OpenedRows, OpenedValues,
+    PackedChallenge, PackedVal, PcsError, Proof, ProverCache, ProverConstraintFolder, ProvingKey,
+    StarkGenericConfig, SymbolicAirBuilder, SymbolicExpression, Val,
 };
 
-#[instrument(skip_all)]
+/// Why a proof couldn't be made.
+#[derive(Debug)]
+pub enum ProverError<PcsErr> {
+    /// With the `strict-security` feature, the config is less secure than its
+    /// [`security_threshold`](StarkGenericConfig::security_threshold) for this trace and AIR.
+    InsufficientSecurity(InsufficientSecurity),
+    /// The checkpoint a proof was resumed from doesn't fit it, see
+    /// [`prove_resume`](crate::prove_resume).
+    Checkpoint(CheckpointError),
+    /// The AIR's constraints, of degree `constraint_degree`, need the traces evaluated with a
+    /// blowup of `2^log_blowup`, but the PCS supports at most `2^max_log_blowup`.
+    BlowupTooSmall {
+        constraint_degree: usize,
+        log_blowup: usize,
+        max_log_blowup: usize,
+    },
+    /// The config is zero-knowledge, which this kind of proof, e.g. a
+    /// [stacked](crate::prove_stacked) one, doesn't support.
+    ZkUnsupported,
+    /// The PCS couldn't make the opening proof, or resume it from a checkpoint.
+    Opening(PcsErr),
+}
+
+/// Prove an AIR with no preprocessed trace. See [`prove_with_key`] for AIRs which have one.
 #[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
 pub fn prove<
     SC,
@@ -29,172 +60,858 @@ pub fn prove<
     challenger: &mut SC::Challenger,
     trace: RowMajorMatrix<Val<SC>>,
     public_values: &Vec<Val<SC>>,
-) -> Proof<SC>
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
 where
     SC: StarkGenericConfig,
     A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
 {
-    #[cfg(debug_assertions)]
-    crate::check_constraints::check_constraints(air, &trace, public_values);
+    let proving_key = ProvingKey {
+        preprocessed: None,
+        column_names: air.column_names(),
+    };
+    prove_with_key(config, &proving_key, air, challenger, trace, public_values)
+}
+
+/// Prove an AIR with no preprocessed trace and no lookups in zero knowledge, with a config made by
+/// [`StarkConfig::with_zk`](crate::StarkConfig::with_zk) and blinding drawn from `rng`.
+///
+/// Each row of the trace is followed by a random row before it's committed to, as evaluations over
+/// a domain of twice its height, so that the trace's polynomials are unchanged on the trace domain
+/// but random everywhere else. A random polynomial is added to the quotient, and committed to and
+/// opened along with its chunks so that the verifier can take it off again. Only PCSs whose trace
+/// domains are subgroups in their natural order, such as two-adic FRI, are supported.
+///
+/// The blinding is the only randomness the prover uses, so proofs made with the same seeded `rng`,
+/// e.g. a `ChaCha20Rng`, are byte for byte the same, whatever the thread pool.
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_zk<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+    R,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    rng: &mut R,
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+    R: Rng + CryptoRng,
+    Standard: Distribution<Val<SC>>,
+{
+    let proving_key = ProvingKey {
+        preprocessed: None,
+        column_names: air.column_names(),
+    };
+    let mut random = |len: usize| -> Vec<Val<SC>> { (0..len).map(|_| rng.gen()).collect() };
+    prove_inner(
+        config,
+        &proving_key,
+        None,
+        None,
+        Some(&mut random),
+        None,
+        air,
+        challenger,
+        trace,
+        public_values,
+    )
+}
+
+/// Prove an AIR whose fixed parts were committed to by [`setup`](crate::setup).
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_key<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    proving_key: &ProvingKey<SC>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    prove_inner(
+        config,
+        proving_key,
+        None,
+        None,
+        None,
+        None,
+        air,
+        challenger,
+        trace,
+        public_values,
+    )
+}
+
+/// Prove an AIR which also constrains its trace against `external`, e.g. the trace of a previous
+/// proof. The external trace must have the same height as the trace, and the verifier must be
+/// given its commitment, see [`verify_with_external`](crate::verify_with_external).
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_external<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    proving_key: &ProvingKey<SC>,
+    external: &ExternalTrace<SC>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    prove_inner(
+        config,
+        proving_key,
+        Some(external),
+        None,
+        None,
+        None,
+        air,
+        challenger,
+        trace,
+        public_values,
+    )
+}
+
+/// Like [`prove_with_key`], but looks up the selectors over the quotient domain in `cache`, so that
+/// repeated proofs of traces of the same height compute them only once.
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_cache<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    proving_key: &ProvingKey<SC>,
+    cache: &mut ProverCache<Domain<SC>>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    prove_inner(
+        config,
+        proving_key,
+        None,
+        Some(cache),
+        None,
+        None,
+        air,
+        challenger,
+        trace,
+        public_values,
+    )
+}
+
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn prove_inner<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    proving_key: &ProvingKey<SC>,
+    external: Option<&ExternalTrace<SC>>,
+    cache: Option<&mut ProverCache<Domain<SC>>>,
+    mut random: Option<&mut dyn FnMut(usize) -> Vec<Val<SC>>>,
+    mut checkpoints: Option<&mut dyn CheckpointHook<SC>>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<Proof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    // Parallel phases below (DFTs, Merkle trees, FRI and the quotient) pick this up.
+    let _parallelism = config.parallelism().enter();
+
+    let preprocessed = proving_key.preprocessed.as_ref();
 
     let degree = trace.height();
     let log_degree = log2_strict_usize(degree);
+    if let Some(preprocessed) = preprocessed {
+        assert_eq!(
+            preprocessed.degree_bits, log_degree,
+            "the trace must have the same height as the preprocessed trace"
+        );
+    }
+    if let Some(external) = external {
+        assert_eq!(
+            external.trace.height(),
+            degree,
+            "the trace must have the same height as the external trace"
+        );
+    }
+    let preprocessed_width = preprocessed.map_or(0, |preprocessed| preprocessed.width);
+    let external_width = external.map_or(0, |external| external.trace.width());
+
+    let symbolic_constraints = get_symbolic_constraints_with_external::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        external_width,
+        public_values.len(),
+    );
+    let lookups = get_symbolic_lookups::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        external_width,
+        public_values.len(),
+    );
+
+    #[cfg(debug_assertions)]
+    crate::check_constraints::check_constraints(
+        air,
+        preprocessed.and_then(|_| air.preprocessed_trace()).as_ref(),
+        &trace,
+        external.map(|external| &external.trace),
+        public_values,
+        &symbolic_constraints,
+        proving_key.column_names(),
+    );
+
+    enforce_security(
+        config,
+        AirParameters::from_constraints(&symbolic_constraints),
+        log_degree,
+    )
+    .map_err(ProverError::InsufficientSecurity)?;
 
-    let symbolic_constraints = get_symbolic_constraints::<Val<SC>, A>(air, 0, public_values.len());
     let constraint_count = symbolic_constraints.len();
     let constraint_degree = symbolic_constraints
         .iter()
         .map(SymbolicExpression::degree_multiple)
         .max()
         .unwrap_or(0);
-    let log_quotient_degree = log2_ceil_usize(constraint_degree - 1);
+    let pcs = config.pcs();
+    assert_eq!(
+        config.zk(),
+        random.is_some(),
+        "zero-knowledge proofs are made by prove_zk, with a zero-knowledge config"
+    );
+    if config.zk() {
+        assert!(
+            lookups.is_empty(),
+            "zero-knowledge proofs can't make lookups"
+        );
+    }
+    if checkpoints.is_some() {
+        assert!(lookups.is_empty(), "checkpointed proofs can't make lookups");
+        assert!(!config.zk(), "checkpointed proofs can't be zero-knowledge");
+    }
+    let log_quotient_degree = log_quotient_degree(config, constraint_degree)?;
     let quotient_degree = 1 << log_quotient_degree;
 
-    let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(degree);
 
+    // What each lookup sends and receives doesn't depend on its challenge, so it can be evaluated
+    // before committing to the trace takes ownership of it.
+    let lookup_values = (!lookups.is_empty()).then(|| {
+        evaluate_lookups(
+            &lookups,
+            preprocessed.and_then(|_| air.preprocessed_trace()).as_ref(),
+            &trace,
+            external.map(|external| &external.trace),
+            public_values,
+        )
+    });
+
+    let committed_trace = match random.as_deref_mut() {
+        Some(random) => blind_trace::<SC>(pcs, trace_domain, trace, random),
+        None => (trace_domain, trace),
+    };
+    let trace_shape = committed_trace.1.dimensions();
     let (trace_commit, trace_data) =
-        info_span!("commit to trace data").in_scope(|| pcs.commit(vec![(trace_domain, trace)]));
+        info_span!("commit to trace data").in_scope(|| pcs.commit(vec![committed_trace]));
+
+    // A resumed proof restores the challenger from its checkpoint rather than observing the
+    // phases the checkpoint got through again.
+    let resumed = match checkpoints.as_deref_mut() {
+        Some(checkpoints) => checkpoints
+            .resume(&trace_commit, challenger)
+            .map_err(ProverError::Checkpoint)?,
+        None => Resumed::Nothing,
+    };
+
+    if matches!(resumed, Resumed::Nothing) {
+        // Observe the instance.
+        challenger.begin_phase("instance");
+        challenger.observe(Val::<SC>::from_canonical_usize(log_degree));
+        if let Some(preprocessed) = preprocessed {
+            challenger.observe(preprocessed.commitment.clone());
+        }
+        if let Some(external) = external {
+            challenger.observe(external.commitment.clone());
+        }
+        // TODO: Might be best practice to include other instance data here; see verifier comment.
 
-    // Observe the instance.
-    challenger.observe(Val::<SC>::from_canonical_usize(log_degree));
-    // TODO: Might be best practice to include other instance data here; see verifier comment.
+        challenger.observe_shape(&[trace_shape]);
+        challenger.observe(trace_commit.clone());
+        challenger.observe_slice(public_values);
+        if let Some(checkpoints) = checkpoints.as_deref_mut() {
+            checkpoints.trace_committed(&trace_commit, challenger);
+        }
+    }
 
-    challenger.observe(trace_commit.clone());
-    challenger.observe_slice(public_values);
-    let alpha: SC::Challenge = challenger.sample_ext_element();
+    let mut lookup_challenge = SC::Challenge::ZERO;
+    let lookup = lookup_values.map(|lookup_values| {
+        challenger.begin_phase("lookups");
+        lookup_challenge = challenger.sample_ext_element();
+        let sums = running_sums(&lookup_values, lookup_challenge).flatten_to_base();
+        let sums_shape = sums.dimensions();
+        let (lookup_commit, lookup_data) = info_span!("commit to lookup running sums")
+            .in_scope(|| pcs.commit(vec![(trace_domain, sums)]));
+        challenger.observe_shape(&[sums_shape]);
+        challenger.observe(lookup_commit.clone());
+        (lookup_commit, lookup_data)
+    });
 
     let quotient_domain =
         trace_domain.create_disjoint_domain(1 << (log_degree + log_quotient_degree));
+    let ext_degree = <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D;
 
-    let trace_on_quotient_domain = pcs.get_evaluations_on_domain(&trace_data, 0, quotient_domain);
+    let resumed_quotient = matches!(resumed, Resumed::QuotientCommitted(_));
+    let mut quotient_flat = if let Resumed::QuotientCommitted(quotient) = resumed {
+        if quotient.len() != quotient_domain.size() * ext_degree {
+            return Err(ProverError::Checkpoint(
+                CheckpointError::InvalidQuotientShape,
+            ));
+        }
+        RowMajorMatrix::new(quotient, ext_degree)
+    } else {
+        challenger.begin_phase("quotient");
+        let alpha: SC::Challenge = challenger.sample_ext_element();
 
-    let quotient_values = quotient_values(
-        air,
-        public_values,
-        trace_domain,
-        quotient_domain,
-        trace_on_quotient_domain,
-        alpha,
-        constraint_count,
-    );
-    let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
+        let trace_on_quotient_domain =
+            pcs.get_evaluations_on_domain(&trace_data, 0, quotient_domain);
+        let preprocessed_on_quotient_domain = preprocessed.map(|preprocessed| {
+            pcs.get_evaluations_on_domain(&preprocessed.data, 0, quotient_domain)
+                .to_row_major_matrix()
+        });
+        let external_on_quotient_domain = external.map(|external| {
+            pcs.get_evaluations_on_domain(&external.data, 0, quotient_domain)
+                .to_row_major_matrix()
+        });
+        let lookup_on_quotient_domain = lookup.as_ref().map(|(_, lookup_data)| {
+            pcs.get_evaluations_on_domain(lookup_data, 0, quotient_domain)
+                .to_row_major_matrix()
+        });
+
+        let selectors = match cache {
+            Some(cache) => cache.selectors_on_coset(trace_domain, quotient_domain),
+            None => Arc::new(trace_domain.selectors_on_coset(quotient_domain)),
+        };
+
+        let quotient_values = quotient_values(
+            air,
+            public_values,
+            trace_domain,
+            quotient_domain,
+            &selectors,
+            trace_on_quotient_domain,
+            preprocessed_on_quotient_domain,
+            external_on_quotient_domain,
+            lookup_on_quotient_domain,
+            lookup_challenge,
+            alpha,
+            constraint_count,
+        );
+        RowMajorMatrix::new_col(quotient_values).flatten_to_base()
+    };
+    // Only the checkpoints made from here need the quotient's evaluations.
+    let checkpoint_quotient = checkpoints.is_some().then(|| quotient_flat.values.clone());
+    // A random polynomial is added to the quotient and committed to with its chunks, so that the
+    // chunks' openings are random but for what they recompose to, which the verifier corrects for.
+    let quotient_blinding = random.map(|random| {
+        let blinding = RowMajorMatrix::new(random(quotient_flat.values.len()), quotient_flat.width);
+        for (value, r) in quotient_flat.values.iter_mut().zip(&blinding.values) {
+            *value += *r;
+        }
+        (quotient_domain, blinding)
+    });
     let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
     let qc_domains = quotient_domain.split_domains(quotient_degree);
+    let num_quotient_matrices = quotient_degree + usize::from(quotient_blinding.is_some());
 
-    let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
-        .in_scope(|| pcs.commit(izip!(qc_domains, quotient_chunks).collect_vec()));
-    challenger.observe(quotient_commit.clone());
+    let quotient_matrices = izip!(qc_domains, quotient_chunks)
+        .chain(quotient_blinding)
+        .collect_vec();
+    let quotient_shape = quotient_matrices
+        .iter()
+        .map(|(_, matrix)| matrix.dimensions())
+        .collect_vec();
+    let (quotient_commit, quotient_data) =
+        info_span!("commit to quotient poly chunks").in_scope(|| pcs.commit(quotient_matrices));
+    if !resumed_quotient {
+        challenger.observe_shape(&quotient_shape);
+        challenger.observe(quotient_commit.clone());
+    }
+    if let (Some(checkpoints), Some(quotient)) = (checkpoints.as_deref_mut(), checkpoint_quotient) {
+        checkpoints
+            .quotient_committed(&trace_commit, &quotient_commit, quotient, challenger)
+            .map_err(ProverError::Checkpoint)?;
+    }
 
     let commitments = Commitments {
         trace: trace_commit,
+        lookup: lookup
+            .as_ref()
+            .map(|(lookup_commit, _)| lookup_commit.clone()),
         quotient_chunks: quotient_commit,
     };
 
-    let zeta: SC::Challenge = challenger.sample();
+    challenger.begin_phase("opening");
+    let zeta: SC::Challenge = challenger.sample_ext_element();
     let zeta_next = trace_domain.next_point(zeta).unwrap();
 
     let (opened_values, opening_proof) = info_span!("open").in_scope(|| {
-        pcs.open(
-            vec![
-                (&trace_data, vec![vec![zeta, zeta_next]]),
-                (
-                    &quotient_data,
-                    // open every chunk at zeta
-                    (0..quotient_degree).map(|_| vec![zeta]).collect_vec(),
-                ),
-            ],
-            challenger,
-        )
-    });
+        let mut rounds = vec![
+            (&trace_data, vec![vec![zeta, zeta_next]]),
+            (
+                &quotient_data,
+                // open every chunk, and the blinding if there is one, at zeta
+                (0..num_quotient_matrices).map(|_| vec![zeta]).collect_vec(),
+            ),
+        ];
+        if let Some(preprocessed) = preprocessed {
+            rounds.push((&preprocessed.data, vec![vec![zeta, zeta_next]]));
+        }
+        if let Some(external) = external {
+            rounds.push((&external.data, vec![vec![zeta, zeta_next]]));
+        }
+        if let Some((_, lookup_data)) = &lookup {
+            rounds.push((lookup_data, vec![vec![zeta, zeta_next]]));
+        }
+        match checkpoints {
+            Some(checkpoints) => checkpoints.open(pcs, rounds, challenger),
+            None => pcs.open(rounds, challenger).map_err(ProverError::Opening),
+        }
+    })?;
     let trace_local = opened_values[0][0][0].clone();
     let trace_next = opened_values[0][0][1].clone();
     let quotient_chunks = opened_values[1].iter().map(|v| v[0].clone()).collect_vec();
+    // The optional rounds follow in a fixed order, each present only if its section is.
+    let mut optional_rounds = opened_values[2..].iter().map(|round| OpenedRows {
+        local: round[0][0].clone(),
+        next: round[0][1].clone(),
+    });
+    let preprocessed = preprocessed.map(|_| optional_rounds.next().unwrap());
+    let external = external.map(|_| optional_rounds.next().unwrap());
+    let lookup = lookup.map(|_| optional_rounds.next().unwrap());
     let opened_values = OpenedValues {
         trace_local,
         trace_next,
         quotient_chunks,
+        preprocessed,
+        external,
+        lookup,
     };
-    Proof {
+    Ok(Proof {
         commitments,
         opened_values,
         opening_proof,
         degree_bits: log_degree,
+    })
+}
+
+/// Interleave the rows of `trace` with random rows, as evaluations over the domain of twice its
+/// height whose even points are the trace domain.
+fn blind_trace<SC: StarkGenericConfig>(
+    pcs: &SC::Pcs,
+    trace_domain: Domain<SC>,
+    trace: RowMajorMatrix<Val<SC>>,
+    random: &mut dyn FnMut(usize) -> Vec<Val<SC>>,
+) -> (Domain<SC>, RowMajorMatrix<Val<SC>>) {
+    let domain = pcs.natural_domain_for_degree(2 * trace_domain.size());
+    let first_point = domain.first_point();
+    let second_next_point = domain
+        .next_point(first_point)
+        .and_then(|point| domain.next_point(point));
+    assert!(
+        trace_domain.first_point() == first_point
+            && second_next_point == trace_domain.next_point(first_point),
+        "zero-knowledge proofs need a PCS whose trace domains are subgroups in their natural order"
+    );
+
+    let width = trace.width();
+    let mut random_values = random(trace.values.len()).into_iter();
+    let mut values = Vec::with_capacity(2 * trace.values.len());
+    for row in trace.rows() {
+        values.extend(row);
+        values.extend(random_values.by_ref().take(width));
+    }
+    (domain, RowMajorMatrix::new(values, width))
+}
+
+/// The log of the number of chunks which the quotient of constraints of degree `constraint_degree`
+/// is split into, as in
+/// [`get_log_quotient_degree_with_external`](crate::get_log_quotient_degree_with_external).
+///
+/// Fails with [`ProverError::BlowupTooSmall`] if the PCS can't evaluate the traces over a quotient
+/// domain of that many times their height.
+pub(crate) fn log_quotient_degree<SC: StarkGenericConfig>(
+    config: &SC,
+    constraint_degree: usize,
+) -> Result<usize, ProverError<PcsError<SC>>> {
+    let log_quotient_degree = unchecked_log_quotient_degree(constraint_degree, config.zk());
+    // The LDEs of a zero-knowledge proof's trace are already twice as high.
+    let log_blowup = log_quotient_degree - usize::from(config.zk());
+    if let Some(max_log_blowup) = config.pcs().max_log_evaluation_blowup() {
+        if log_blowup > max_log_blowup {
+            return Err(ProverError::BlowupTooSmall {
+                constraint_degree,
+                log_blowup,
+                max_log_blowup,
+            });
+        }
     }
+    Ok(log_quotient_degree)
+}
+
+/// [`log_quotient_degree`], without checking it against the PCS.
+///
+/// In a zero-knowledge proof, the committed trace polynomials have up to twice the trace's degree,
+/// so the quotient's degree is below `2 * constraint_degree - 1` times the trace's height.
+pub(crate) fn unchecked_log_quotient_degree(constraint_degree: usize, zk: bool) -> usize {
+    log2_ceil_usize((constraint_degree.max(2) << usize::from(zk)) - 1)
 }
 
 #[instrument(name = "compute quotient polynomial", skip_all)]
-fn quotient_values<SC, A, Mat>(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn quotient_values<SC, A, Mat>(
     air: &A,
     public_values: &Vec<Val<SC>>,
     trace_domain: Domain<SC>,
     quotient_domain: Domain<SC>,
+    selectors: &LagrangeSelectors<Vec<Val<SC>>>,
     trace_on_quotient_domain: Mat,
+    preprocessed_on_quotient_domain: Option<RowMajorMatrix<Val<SC>>>,
+    external_on_quotient_domain: Option<RowMajorMatrix<Val<SC>>>,
+    lookup_on_quotient_domain: Option<RowMajorMatrix<Val<SC>>>,
+    lookup_challenge: SC::Challenge,
     alpha: SC::Challenge,
     constraint_count: usize,
 ) -> Vec<SC::Challenge>
 where
     SC: StarkGenericConfig,
-    A: for<'a> Air<ProverConstraintFolder<'a, SC>>,
+    A: ?Sized + for<'a> Air<ProverConstraintFolder<'a, SC>>,
     Mat: Matrix<Val<SC>> + Sync,
 {
     let quotient_size = quotient_domain.size();
     let width = trace_on_quotient_domain.width();
-    let mut sels = trace_domain.selectors_on_coset(quotient_domain);
 
     let qdb = log2_strict_usize(quotient_domain.size()) - log2_strict_usize(trace_domain.size());
     let next_step = 1 << qdb;
+    let ext_degree = <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D;
 
     // We take PackedVal::<SC>::WIDTH worth of values at a time from a quotient_size slice, so we need to
     // pad with default values in the case where quotient_size is smaller than PackedVal::<SC>::WIDTH.
-    for _ in quotient_size..PackedVal::<SC>::WIDTH {
-        sels.is_first_row.push(Val::<SC>::default());
-        sels.is_last_row.push(Val::<SC>::default());
-        sels.is_transition.push(Val::<SC>::default());
-        sels.inv_zeroifier.push(Val::<SC>::default());
-    }
+    let mut padded;
+    let sels = if quotient_size < PackedVal::<SC>::WIDTH {
+        padded = selectors.clone();
+        for _ in quotient_size..PackedVal::<SC>::WIDTH {
+            padded.is_first_row.push(Val::<SC>::default());
+            padded.is_last_row.push(Val::<SC>::default());
+            padded.is_transition.push(Val::<SC>::default());
+            padded.inv_zeroifier.push(Val::<SC>::default());
+        }
+        &padded
+    } else {
+        selectors
+    };
 
-    let mut alpha_powers = alpha.powers().take(constraint_count).collect_vec();
+    let mut alpha_powers = alpha.powers().collect_n(constraint_count);
     alpha_powers.reverse();
 
-    (0..quotient_size)
-        .into_par_iter()
-        .step_by(PackedVal::<SC>::WIDTH)
-        .flat_map_iter(|i_start| {
-            let i_range = i_start..i_start + PackedVal::<SC>::WIDTH;
+    p3_maybe_rayon::install(quotient_size * width, || {
+        (0..quotient_size)
+            .into_par_iter()
+            .step_by(PackedVal::<SC>::WIDTH)
+            .flat_map_iter(|i_start| {
+                let i_range = i_start..i_start + PackedVal::<SC>::WIDTH;
 
-            let is_first_row = *PackedVal::<SC>::from_slice(&sels.is_first_row[i_range.clone()]);
-            let is_last_row = *PackedVal::<SC>::from_slice(&sels.is_last_row[i_range.clone()]);
-            let is_transition = *PackedVal::<SC>::from_slice(&sels.is_transition[i_range.clone()]);
-            let inv_zeroifier = *PackedVal::<SC>::from_slice(&sels.inv_zeroifier[i_range.clone()]);
+                let is_first_row =
+                    *PackedVal::<SC>::from_slice(&sels.is_first_row[i_range.clone()]);
+                let is_last_row = *PackedVal::<SC>::from_slice(&sels.is_last_row[i_range.clone()]);
+                let is_transition =
+                    *PackedVal::<SC>::from_slice(&sels.is_transition[i_range.clone()]);
+                let inv_zeroifier =
+                    *PackedVal::<SC>::from_slice(&sels.inv_zeroifier[i_range.clone()]);
 
-            let main = RowMajorMatrix::new(
-                trace_on_quotient_domain.vertically_packed_row_pair(i_start, next_step),
-                width,
-            );
+                let main = RowMajorMatrix::new(
+                    trace_on_quotient_domain.vertically_packed_row_pair(i_start, next_step),
+                    width,
+                );
+                let preprocessed = match &preprocessed_on_quotient_domain {
+                    Some(preprocessed) => RowMajorMatrix::new(
+                        preprocessed.vertically_packed_row_pair(i_start, next_step),
+                        preprocessed.width(),
+                    ),
+                    None => RowMajorMatrix::new(vec![], 0),
+                };
+                let external = match &external_on_quotient_domain {
+                    Some(external) => RowMajorMatrix::new(
+                        external.vertically_packed_row_pair(i_start, next_step),
+                        external.width(),
+                    ),
+                    None => RowMajorMatrix::new(vec![], 0),
+                };
+                // The running sums were committed to flattened to the base field.
+                let lookup = match &lookup_on_quotient_domain {
+                    Some(lookup) => RowMajorMatrix::new(
+                        lookup
+                            .vertically_packed_row_pair::<PackedVal<SC>>(i_start, next_step)
+                            .chunks_exact(ext_degree)
+                            .map(|coeffs| PackedChallenge::<SC>::from_base_fn(|i| coeffs[i]))
+                            .collect(),
+                        lookup.width() / ext_degree,
+                    ),
+                    None => RowMajorMatrix::new(vec![], 0),
+                };
+
+                let accumulator = PackedChallenge::<SC>::ZERO;
+                let mut folder = ProverConstraintFolder {
+                    main: main.as_view(),
+                    preprocessed: preprocessed.as_view(),
+                    external: external.as_view(),
+                    lookup: lookup.as_view(),
+                    lookup_challenge,
+                    lookup_index: 0,
+                    public_values,
+                    is_first_row,
+                    is_last_row,
+                    is_transition,
+                    alpha_powers: &alpha_powers,
+                    accumulator,
+                    constraint_index: 0,
+                };
+                air.eval(&mut folder);
+
+                // quotient(x) = constraints(x) / Z_H(x)
+                let quotient = folder.accumulator * inv_zeroifier;
+
+                // "Transpose" D packed base coefficients into WIDTH scalar extension coefficients.
+                (0..core::cmp::min(quotient_size, PackedVal::<SC>::WIDTH)).map(
+                    move |idx_in_packing| {
+                        SC::Challenge::from_base_fn(|coeff_idx| {
+                            quotient.as_base_slice()[coeff_idx].as_slice()[idx_in_packing]
+                        })
+                    },
+                )
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use core::marker::PhantomData;
+
+    use p3_air::{AirBuilder, AirBuilderWithPublicValues, BaseAir};
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_commit::testing::TrivialPcs;
+    use p3_dft::Radix2DitParallel;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_keccak_air::KeccakAir;
+    use p3_matrix::dense::RowMajorMatrixView;
+    use p3_matrix::stack::VerticalPair;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::{get_symbolic_constraints_with_external, StarkConfig, VerifierConstraintFolder};
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+    type Challenger = DuplexChallenger<Val, Poseidon2BabyBear<16>, 16, 8>;
+    type MyPcs = TrivialPcs<Val, Radix2DitParallel<Val>>;
+    type MyConfig = StarkConfig<MyPcs, Challenge, Challenger>;
 
-            let accumulator = PackedChallenge::<SC>::ZERO;
-            let mut folder = ProverConstraintFolder {
-                main: main.as_view(),
-                public_values,
-                is_first_row,
-                is_last_row,
-                is_transition,
-                alpha_powers: &alpha_powers,
-                accumulator,
-                constraint_index: 0,
+    const WIDTH: usize = 3;
+
+    /// Constraints of the given degree using every selector and the public values. The traces are
+    /// random, so they don't satisfy them, but the quotient is computed all the same.
+    struct TestAir {
+        degree: usize,
+    }
+
+    impl<F> BaseAir<F> for TestAir {
+        fn width(&self) -> usize {
+            WIDTH
+        }
+    }
+
+    impl<AB: AirBuilderWithPublicValues> Air<AB> for TestAir {
+        fn eval(&self, builder: &mut AB) {
+            let main = builder.main();
+            let pis = builder.public_values();
+            let (first, last) = (pis[0], pis[1]);
+            let (local, next) = (main.row_slice(0), main.row_slice(1));
+
+            builder.when_first_row().assert_eq(local[0], first);
+            let product = local
+                .iter()
+                .cycle()
+                .take(self.degree)
+                .fold(AB::Expr::ONE, |product, &x| product * x);
+            builder.when_transition().assert_eq(next[0], product);
+            builder.when_last_row().assert_eq(local[1], last);
+            builder.assert_eq(local[0] + local[1], local[2]);
+        }
+    }
+
+    /// Check the packed quotient against the AIR evaluated at each point of the quotient domain
+    /// separately, with scalar extension field values as the verifier does.
+    fn check_quotient_matches_scalar_evaluation<A>(
+        air: &A,
+        trace: RowMajorMatrix<Val>,
+        public_values: Vec<Val>,
+    ) where
+        A: Air<SymbolicAirBuilder<Val>>
+            + for<'a> Air<ProverConstraintFolder<'a, MyConfig>>
+            + for<'a> Air<VerifierConstraintFolder<'a, MyConfig>>,
+    {
+        let mut rng = thread_rng();
+        let log_n = log2_strict_usize(trace.height());
+        let config = MyConfig::new(TrivialPcs {
+            dft: Radix2DitParallel::default(),
+            log_n,
+            _phantom: PhantomData,
+        });
+        let pcs = config.pcs();
+
+        let constraints =
+            get_symbolic_constraints_with_external::<Val, _>(air, 0, 0, public_values.len());
+        let constraint_degree = constraints
+            .iter()
+            .map(SymbolicExpression::degree_multiple)
+            .max()
+            .unwrap();
+        let log_quotient_degree = log_quotient_degree(&config, constraint_degree).unwrap();
+
+        let trace_domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(pcs, 1 << log_n);
+        let quotient_domain =
+            trace_domain.create_disjoint_domain(1 << (log_n + log_quotient_degree));
+        let (_, trace_data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(pcs, vec![(trace_domain, trace)]);
+        let trace_on_quotient_domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::get_evaluations_on_domain(
+                pcs,
+                &trace_data,
+                0,
+                quotient_domain,
+            )
+            .to_row_major_matrix();
+        let selectors = trace_domain.selectors_on_coset(quotient_domain);
+        let alpha: Challenge = rng.gen();
+
+        let quotient = quotient_values::<MyConfig, _, _>(
+            air,
+            &public_values,
+            trace_domain,
+            quotient_domain,
+            &selectors,
+            trace_on_quotient_domain.as_view(),
+            None,
+            None,
+            None,
+            Challenge::ZERO,
+            alpha,
+            constraints.len(),
+        );
+        assert_eq!(quotient.len(), quotient_domain.size());
+
+        let height = trace_on_quotient_domain.height();
+        let next_step = 1 << log_quotient_degree;
+        let ext = |x: Val| <Challenge as FieldExtensionAlgebra<Val>>::from_base(x);
+        let row = |r: usize| {
+            trace_on_quotient_domain
+                .row(r % height)
+                .map(ext)
+                .collect_vec()
+        };
+        let empty = VerticalPair::new(
+            RowMajorMatrixView::new_row(&[]),
+            RowMajorMatrixView::new_row(&[]),
+        );
+        for (i, &value) in quotient.iter().enumerate() {
+            let (local, next) = (row(i), row(i + next_step));
+            let mut folder = VerifierConstraintFolder::<MyConfig> {
+                main: VerticalPair::new(
+                    RowMajorMatrixView::new_row(&local),
+                    RowMajorMatrixView::new_row(&next),
+                ),
+                preprocessed: empty,
+                external: empty,
+                lookup: empty,
+                lookup_challenge: Challenge::ZERO,
+                lookup_index: 0,
+                public_values: &public_values,
+                is_first_row: ext(selectors.is_first_row[i]),
+                is_last_row: ext(selectors.is_last_row[i]),
+                is_transition: ext(selectors.is_transition[i]),
+                alpha,
+                accumulator: Challenge::ZERO,
             };
             air.eval(&mut folder);
+            assert_eq!(
+                value,
+                folder.accumulator * ext(selectors.inv_zeroifier[i]),
+                "point {i}"
+            );
+        }
+    }
 
-            // quotient(x) = constraints(x) / Z_H(x)
-            let quotient = folder.accumulator * inv_zeroifier;
+    fn check_test_air(degree: usize, log_n: usize) {
+        let mut rng = thread_rng();
+        let trace = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, WIDTH);
+        check_quotient_matches_scalar_evaluation(
+            &TestAir { degree },
+            trace,
+            vec![rng.gen(), rng.gen()],
+        );
+    }
 
-            // "Transpose" D packed base coefficients into WIDTH scalar extension coefficients.
-            (0..core::cmp::min(quotient_size, PackedVal::<SC>::WIDTH)).map(move |idx_in_packing| {
-                SC::Challenge::from_base_fn(|coeff_idx| {
-                    quotient.as_base_slice()[coeff_idx].as_slice()[idx_in_packing]
-                })
-            })
-        })
-        .collect()
+    #[test]
+    fn test_quotient_values_match_scalar_evaluation() {
+        check_test_air(2, 5);
+        check_test_air(3, 4);
+    }
+
+    #[test]
+    fn test_quotient_values_smaller_than_packing() {
+        // A quotient domain of 4 points, fewer than most packings' width.
+        check_test_air(2, 1);
+    }
+
+    #[test]
+    fn test_keccak_quotient_values_match_scalar_evaluation() {
+        let air = KeccakAir {};
+        let trace = air.generate_trace_rows::<Val>(1);
+        check_quotient_matches_scalar_evaluation(&air, trace, vec![]);
+    }
 }
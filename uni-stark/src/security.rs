@@ -0,0 +1,261 @@
+use p3_commit::QueryParameters;
+use p3_field::Field;
+
+use crate::{StarkGenericConfig, SymbolicExpression};
+
+/// The bits of security of proofs of a trace, see [`StarkGenericConfig::security_bits`].
+///
+/// Each bound is the smaller of what the challenge field and the PCS's queries give. The field
+/// part is the log of the field's size, less that of the sum of the union bounds over it: the
+/// number of constraints for their random combination, their degree times the trace's length for
+/// the out-of-domain point, and the size of the LDE domain for the FRI challenges, squared for the
+/// proven bound. The query part is the bits each query gives, times the number of queries, plus
+/// the proof-of-work bits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SecurityReport {
+    /// Bits of security assuming the ethSTARK conjecture, under which a query passes a word far
+    /// from the code with probability at most the code's rate.
+    pub conjectured_bits: usize,
+    /// Bits of security in the unique decoding regime, where a query passes a word far from the
+    /// code with probability at most `(1 + rate) / 2`.
+    pub proven_bits: usize,
+}
+
+impl SecurityReport {
+    /// The security of proofs of a trace of `2^log_trace_len` rows of an AIR with `air`'s
+    /// constraints, with challenges drawn from a field of at least `2^log_field_size` elements.
+    pub fn new(
+        log_field_size: usize,
+        params: QueryParameters,
+        air: AirParameters,
+        log_trace_len: usize,
+    ) -> Self {
+        let log_lde_size = log_trace_len + params.log_blowup;
+        let field_bits = |log_fri_error: usize| {
+            let error = (air.num_constraints as u128)
+                .saturating_add((air.constraint_degree as u128) << log_trace_len)
+                .saturating_add(1 << log_fri_error);
+            log_field_size.saturating_sub(log2_ceil_u128(error))
+        };
+
+        let conjectured_queries = params.num_queries * params.log_blowup;
+
+        let rate = (0..params.log_blowup).fold(1.0, |rate, _| rate / 2.0);
+        let bits_per_query = 1.0 - log2_fraction(1.0 + rate);
+        // Rounding down, as `as` does for positive floats, never overstates the security.
+        let proven_queries = (params.num_queries as f64 * bits_per_query) as usize;
+
+        Self {
+            conjectured_bits: field_bits(log_lde_size)
+                .min(conjectured_queries + params.proof_of_work_bits),
+            proven_bits: field_bits(2 * log_lde_size)
+                .min(proven_queries + params.proof_of_work_bits),
+        }
+    }
+}
+
+/// What the soundness of a proof depends on of its AIR, see [`SecurityReport::new`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AirParameters {
+    /// The number of constraints, which the prover combines with powers of a random challenge.
+    pub num_constraints: usize,
+    /// The largest degree of a constraint.
+    pub constraint_degree: usize,
+}
+
+impl AirParameters {
+    /// The parameters of an AIR with the given constraints, as from
+    /// [`get_symbolic_constraints_with_external`](crate::get_symbolic_constraints_with_external).
+    pub fn from_constraints<F: Field>(constraints: &[SymbolicExpression<F>]) -> Self {
+        Self {
+            num_constraints: constraints.len(),
+            constraint_degree: constraints
+                .iter()
+                .map(SymbolicExpression::degree_multiple)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// `ceil(log2(x))` for a nonzero `x`.
+const fn log2_ceil_u128(x: u128) -> usize {
+    (u128::BITS - (x - 1).leading_zeros()) as usize
+}
+
+/// `log2(x)` for `x` in `[1, 2]`, bit by bit, as `core` has no logarithms.
+fn log2_fraction(mut x: f64) -> f64 {
+    let mut log = 0.0;
+    let mut bit = 1.0;
+    for _ in 0..f64::MANTISSA_DIGITS {
+        bit /= 2.0;
+        x *= x;
+        if x >= 2.0 {
+            x /= 2.0;
+            log += bit;
+        }
+    }
+    log
+}
+
+/// A config whose conjectured security is below its
+/// [`security_threshold`](StarkGenericConfig::security_threshold).
+#[derive(Debug)]
+pub struct InsufficientSecurity {
+    pub report: SecurityReport,
+    pub threshold_bits: usize,
+}
+
+/// Check the conjectured security of proofs of a trace of `2^log_trace_len` rows of an AIR with
+/// `air`'s constraints against the config's threshold. Configs without a threshold, or whose PCS
+/// has no security estimate, pass.
+pub fn check_security<SC: StarkGenericConfig>(
+    config: &SC,
+    air: AirParameters,
+    log_trace_len: usize,
+) -> Result<(), InsufficientSecurity> {
+    let (Some(threshold_bits), Some(report)) = (
+        config.security_threshold(),
+        config.security_bits(air, log_trace_len),
+    ) else {
+        return Ok(());
+    };
+    if report.conjectured_bits < threshold_bits {
+        return Err(InsufficientSecurity {
+            report,
+            threshold_bits,
+        });
+    }
+    Ok(())
+}
+
+/// Warn about a config which fails [`check_security`], or with the `strict-security` feature,
+/// refuse to prove with it.
+pub(crate) fn enforce_security<SC: StarkGenericConfig>(
+    config: &SC,
+    air: AirParameters,
+    log_trace_len: usize,
+) -> Result<(), InsufficientSecurity> {
+    let Err(err) = check_security(config, air, log_trace_len) else {
+        return Ok(());
+    };
+    if cfg!(feature = "strict-security") {
+        return Err(err);
+    }
+    tracing::warn!(
+        "proving with {} bits of conjectured security, below the threshold of {}",
+        err.report.conjectured_bits,
+        err.threshold_bits
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A BabyBear degree 4 extension has at least 2^120 elements.
+    const BABY_BEAR_QUARTIC_BITS: usize = 120;
+
+    /// Eight constraints of degree 3.
+    const AIR: AirParameters = AirParameters {
+        num_constraints: 8,
+        constraint_degree: 3,
+    };
+
+    fn params(log_blowup: usize, num_queries: usize, proof_of_work_bits: usize) -> QueryParameters {
+        QueryParameters {
+            log_blowup,
+            num_queries,
+            proof_of_work_bits,
+        }
+    }
+
+    #[test]
+    fn test_log2_fraction() {
+        assert_eq!(log2_fraction(1.0), 0.0);
+        assert!((log2_fraction(1.5) - 0.584_962_500_721_156).abs() < 1e-12);
+        assert!((log2_fraction(2.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_baby_bear_blowup_2_with_100_queries() {
+        // Each query gives 1 conjectured bit and log2(4/3) ~ 0.415 proven bits.
+        let report = SecurityReport::new(BABY_BEAR_QUARTIC_BITS, params(1, 100, 16), AIR, 10);
+        assert_eq!(
+            report,
+            SecurityReport {
+                conjectured_bits: 107,
+                proven_bits: 57,
+            }
+        );
+
+        // Without proof of work, the queries are what limit the conjectured bound.
+        let report = SecurityReport::new(BABY_BEAR_QUARTIC_BITS, params(1, 100, 0), AIR, 10);
+        assert_eq!(
+            report,
+            SecurityReport {
+                conjectured_bits: 100,
+                proven_bits: 41,
+            }
+        );
+    }
+
+    #[test]
+    fn test_baby_bear_blowup_8_with_28_queries() {
+        // Each query gives 3 conjectured bits and log2(16/9) ~ 0.830 proven bits.
+        let report = SecurityReport::new(BABY_BEAR_QUARTIC_BITS, params(3, 28, 16), AIR, 20);
+        assert_eq!(
+            report,
+            SecurityReport {
+                conjectured_bits: 96,
+                proven_bits: 39,
+            }
+        );
+    }
+
+    #[test]
+    fn test_large_traces_are_limited_by_the_field() {
+        // A degree 2 extension is too small for a trace of 2^26 rows, whatever the queries.
+        let report = SecurityReport::new(60, params(1, 1000, 20), AIR, 26);
+        assert_eq!(
+            report,
+            SecurityReport {
+                conjectured_bits: 31,
+                proven_bits: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_many_or_high_degree_constraints_cost_security() {
+        // 2^20 constraints are a larger union bound than the rest of the field part.
+        let air = AirParameters {
+            num_constraints: 1 << 20,
+            constraint_degree: 2,
+        };
+        let report = SecurityReport::new(BABY_BEAR_QUARTIC_BITS, params(1, 100, 16), air, 10);
+        assert_eq!(report.conjectured_bits, 99);
+
+        // As is a degree of 2^12, for the out-of-domain point.
+        let air = AirParameters {
+            num_constraints: 1,
+            constraint_degree: 1 << 12,
+        };
+        let report = SecurityReport::new(BABY_BEAR_QUARTIC_BITS, params(1, 100, 16), air, 10);
+        assert_eq!(report.conjectured_bits, 97);
+    }
+
+    #[test]
+    fn test_test_config_is_insecure() {
+        // What `create_test_fri_config` uses.
+        let report = SecurityReport::new(BABY_BEAR_QUARTIC_BITS, params(1, 2, 1), AIR, 10);
+        assert_eq!(
+            report,
+            SecurityReport {
+                conjectured_bits: 3,
+                proven_bits: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Proving several executions of one AIR, each with its own public values, as one trace.
+//!
+//! The instances' traces are stacked vertically in the order given, and then padded to a power of
+//! two with copies of the shortest instance. Next to the AIR's columns, each row holds its
+//! instance's public values, selectors for the instance's first and last rows, and the public
+//! values again on the instance's first row only. The AIR is evaluated against the first two and
+//! its own columns, so that each instance sees its own boundaries and public values. The verifier
+//! computes the other three itself from the instances' heights and public values, and checks them
+//! against the trace's opening, while the public values columns are constrained to be constant
+//! within each instance and to start from those on its first row.
+
+use alloc::vec::Vec;
+use core::iter;
+
+use itertools::izip;
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_challenger::CanObserve;
+use p3_commit::{Pcs, TwoAdicMultiplicativeCoset};
+use p3_field::{ExtensionField, Field, FieldAlgebra, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use serde::{Deserialize, Serialize};
+
+use crate::verifier::verify_inner;
+use crate::{
+    prove_with_key, PcsError, Proof, ProverConstraintFolder, ProverError, ProvingKey,
+    StarkGenericConfig, SymbolicAirBuilder, Val, VerificationError, VerifierConstraintFolder,
+    VerifyingKey,
+};
+
+/// A proof of several instances of one AIR, made by [`prove_stacked`].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct StackedProof<SC: StarkGenericConfig> {
+    pub(crate) proof: Proof<SC>,
+    /// The height of each instance's trace.
+    pub(crate) heights: Vec<usize>,
+}
+
+impl<SC: StarkGenericConfig> StackedProof<SC> {
+    /// The height of each instance's trace, in the order they were stacked.
+    pub fn heights(&self) -> &[usize] {
+        &self.heights
+    }
+}
+
+/// The builder an AIR is evaluated with when it's proven by [`prove_stacked`], which gives it the
+/// rows, boundaries and public values of the instance the current row belongs to.
+pub struct StackedBuilder<AB: AirBuilder> {
+    main: RowMajorMatrix<AB::Var>,
+    is_first_row: AB::Expr,
+    is_last_row: AB::Expr,
+    public_values: Vec<AB::Var>,
+    /// The AIR's constraints, which are asserted on the stacked trace's builder once it's done.
+    constraints: Vec<AB::Expr>,
+}
+
+impl<AB: AirBuilder> AirBuilder for StackedBuilder<AB> {
+    type F = AB::F;
+    type Expr = AB::Expr;
+    type Var = AB::Var;
+    type M = RowMajorMatrix<AB::Var>;
+
+    fn main(&self) -> Self::M {
+        self.main.clone()
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.is_first_row.clone()
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.is_last_row.clone()
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        if size == 2 {
+            AB::Expr::ONE - self.is_last_row.clone()
+        } else {
+            panic!("uni-stark only supports a window size of 2")
+        }
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.constraints.push(x.into());
+    }
+}
+
+impl<AB: AirBuilder> AirBuilderWithPublicValues for StackedBuilder<AB> {
+    type PublicVar = AB::Var;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        &self.public_values
+    }
+}
+
+/// An AIR over the stacked trace of instances of `inner`, each with `num_public_values` public
+/// values.
+struct StackedAir<'a, A> {
+    inner: &'a A,
+    num_public_values: usize,
+}
+
+impl<F, A: BaseAir<F>> BaseAir<F> for StackedAir<'_, A> {
+    fn width(&self) -> usize {
+        self.inner.width() + 2 * self.num_public_values + 2
+    }
+}
+
+impl<AB: AirBuilder, A: Air<StackedBuilder<AB>>> Air<AB> for StackedAir<'_, A> {
+    fn eval(&self, builder: &mut AB) {
+        let width = BaseAir::<AB::F>::width(self.inner);
+        let k = self.num_public_values;
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+
+        let public_values = &local[width..width + k];
+        let is_first_row: AB::Expr = local[width + k].into();
+        let is_last_row: AB::Expr = local[width + k + 1].into();
+        let first_row_public_values = &local[width + k + 2..];
+        for (&value, &first_row_value, &next_value) in izip!(
+            public_values,
+            first_row_public_values,
+            &next[width..width + k]
+        ) {
+            builder.assert_zero(is_first_row.clone() * (first_row_value - value));
+            builder.assert_zero((AB::Expr::ONE - is_last_row.clone()) * (next_value - value));
+        }
+
+        let mut instance_builder = StackedBuilder::<AB> {
+            main: RowMajorMatrix::new([&local[..width], &next[..width]].concat(), width),
+            is_first_row,
+            is_last_row,
+            public_values: public_values.to_vec(),
+            constraints: Vec::new(),
+        };
+        self.inner.eval(&mut instance_builder);
+        for constraint in instance_builder.constraints {
+            builder.assert_zero(constraint);
+        }
+    }
+}
+
+/// The instance behind each block of rows of the stacked trace: the instances themselves, then
+/// copies of the shortest one until the height is a power of two. The instances' heights are
+/// powers of two, so the gap is a multiple of the shortest.
+fn stacking_order(heights: &[usize]) -> Vec<usize> {
+    let total: usize = heights.iter().sum();
+    let (shortest, &min_height) = heights
+        .iter()
+        .enumerate()
+        .min_by_key(|&(_, &height)| height)
+        .expect("there must be at least one instance");
+    let num_copies = (total.next_power_of_two() - total) / min_height;
+    (0..heights.len())
+        .chain(iter::repeat(shortest).take(num_copies))
+        .collect()
+}
+
+/// Observe the number of instances and their heights, which fix the stacked trace's layout.
+fn observe_instances<SC: StarkGenericConfig>(challenger: &mut SC::Challenger, heights: &[usize]) {
+    challenger.observe(Val::<SC>::from_canonical_usize(heights.len()));
+    for &height in heights {
+        challenger.observe(Val::<SC>::from_canonical_usize(height));
+    }
+}
+
+/// Prove several instances of `air` with one proof, each with its own trace and public values.
+///
+/// The traces must all have the AIR's width and power-of-two heights, which may differ, and every
+/// instance must have the same number of public values. See the [module docs](self) for how they
+/// are laid out. Zero-knowledge configs aren't supported, and fail with
+/// [`ProverError::ZkUnsupported`].
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_stacked<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<StackedBuilder<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    instances: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<StackedProof<SC>, ProverError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<StackedBuilder<SymbolicAirBuilder<Val<SC>>>>
+        + for<'a> Air<StackedBuilder<ProverConstraintFolder<'a, SC>>>,
+{
+    if config.zk() {
+        return Err(ProverError::ZkUnsupported);
+    }
+    assert_eq!(
+        instances.len(),
+        public_values.len(),
+        "each instance must have its own public values"
+    );
+    let num_public_values = public_values.first().map_or(0, Vec::len);
+    assert!(
+        public_values.iter().all(|p| p.len() == num_public_values),
+        "every instance must have the same number of public values"
+    );
+    let air_width = BaseAir::<Val<SC>>::width(air);
+    for instance in &instances {
+        assert_eq!(
+            instance.width(),
+            air_width,
+            "the traces must have the AIR's width"
+        );
+        assert!(
+            instance.height().is_power_of_two(),
+            "the traces' heights must be powers of two"
+        );
+    }
+
+    let heights: Vec<usize> = instances.iter().map(|instance| instance.height()).collect();
+    let order = stacking_order(&heights);
+    let width = air_width + 2 * num_public_values + 2;
+    let mut values = Vec::with_capacity(heights.iter().sum::<usize>().next_power_of_two() * width);
+    for &i in &order {
+        let (instance, instance_public_values) = (&instances[i], &public_values[i]);
+        for (r, row) in instance.rows().enumerate() {
+            values.extend(row);
+            values.extend_from_slice(instance_public_values);
+            values.push(Val::<SC>::from_bool(r == 0));
+            values.push(Val::<SC>::from_bool(r == heights[i] - 1));
+            if r == 0 {
+                values.extend_from_slice(instance_public_values);
+            } else {
+                values.extend(iter::repeat(Val::<SC>::ZERO).take(num_public_values));
+            }
+        }
+    }
+    drop(instances);
+
+    observe_instances::<SC>(challenger, &heights);
+    let stacked_air = StackedAir {
+        inner: air,
+        num_public_values,
+    };
+    let proof = prove_with_key(
+        config,
+        &ProvingKey::default(),
+        &stacked_air,
+        challenger,
+        RowMajorMatrix::new(values, width),
+        &public_values.concat(),
+    )?;
+    Ok(StackedProof { proof, heights })
+}
+
+/// Verify a proof made by [`prove_stacked`], given each instance's public values.
+///
+/// The verifier evaluates the stacked trace's selectors itself, which is only implemented for
+/// PCSs over two-adic multiplicative cosets.
+pub fn verify_stacked<SC, A, F>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &StackedProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    SC::Pcs: Pcs<SC::Challenge, SC::Challenger, Domain = TwoAdicMultiplicativeCoset<F>>,
+    F: TwoAdicField,
+    A: Air<StackedBuilder<SymbolicAirBuilder<Val<SC>>>>
+        + for<'a> Air<StackedBuilder<VerifierConstraintFolder<'a, SC>>>,
+{
+    if config.zk() {
+        return Err(VerificationError::ZkUnsupported);
+    }
+    let heights = &proof.heights;
+    let num_public_values = public_values.first().map_or(0, Vec::len);
+    let padded_height = heights
+        .iter()
+        .try_fold(0usize, |total, &height| total.checked_add(height))
+        .and_then(usize::checked_next_power_of_two);
+    let valid_shape = !heights.is_empty()
+        && heights.len() == public_values.len()
+        && public_values.iter().all(|p| p.len() == num_public_values)
+        && heights.iter().all(|height| height.is_power_of_two())
+        && padded_height.map(|height| height.trailing_zeros() as usize)
+            == Some(proof.proof.degree_bits);
+    if !valid_shape {
+        return Err(VerificationError::InvalidProofShape);
+    }
+
+    let air_width = BaseAir::<Val<SC>>::width(air);
+    let order = stacking_order(heights);
+    let trace_domain = TwoAdicMultiplicativeCoset {
+        log_n: proof.proof.degree_bits,
+        shift: F::ONE,
+    };
+    let selectors_check = |zeta: SC::Challenge, trace_local: &[SC::Challenge]| {
+        let mut expected = SC::Challenge::zero_vec(2 + num_public_values);
+        let mut start = 0;
+        for &i in &order {
+            let is_first_row = lagrange_basis_at::<F, SC::Challenge>(trace_domain, start, zeta);
+            expected[0] += is_first_row;
+            expected[1] +=
+                lagrange_basis_at::<F, SC::Challenge>(trace_domain, start + heights[i] - 1, zeta);
+            for (e, &value) in expected[2..].iter_mut().zip(&public_values[i]) {
+                *e += is_first_row * value;
+            }
+            start += heights[i];
+        }
+        trace_local[air_width + num_public_values..] == expected[..]
+    };
+
+    observe_instances::<SC>(challenger, heights);
+    let stacked_air = StackedAir {
+        inner: air,
+        num_public_values,
+    };
+    verify_inner(
+        config,
+        &VerifyingKey::default(),
+        None,
+        Some(&selectors_check),
+        &stacked_air,
+        challenger,
+        &proof.proof,
+        &public_values.concat(),
+    )
+}
+
+/// The Lagrange basis polynomial of the `i`th point of `domain`, evaluated at `point`.
+fn lagrange_basis_at<F: TwoAdicField, EF: ExtensionField<F>>(
+    domain: TwoAdicMultiplicativeCoset<F>,
+    i: usize,
+    point: EF,
+) -> EF {
+    // L_i(x) = g^i (x^n - 1) / (n (x - g^i)) over the subgroup generated by g, after unshifting x.
+    let unshifted_point = point * domain.shift.inverse();
+    let z_h = unshifted_point.exp_power_of_2(domain.log_n) - EF::ONE;
+    let x_i = F::two_adic_generator(domain.log_n).exp_u64(i as u64);
+    let n_inv = F::from_canonical_usize(1 << domain.log_n).inverse();
+    z_h * (x_i * n_inv) / (unshifted_point - x_i)
+}
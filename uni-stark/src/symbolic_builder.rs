@@ -1,7 +1,9 @@
 use alloc::vec;
 use alloc::vec::Vec;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, PairBuilder};
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPublicValues, ExternalBuilder, LookupBuilder, PairBuilder,
+};
 use p3_field::Field;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_util::log2_ceil_usize;
@@ -9,9 +11,9 @@ use tracing::instrument;
 
 use crate::symbolic_expression::SymbolicExpression;
 use crate::symbolic_variable::SymbolicVariable;
-use crate::Entry;
+use crate::{Entry, Lookup};
 
-#[instrument(name = "infer log of constraint degree", skip_all)]
+#[deprecated(note = "use `get_log_quotient_degree_with_external`")]
 pub fn get_log_quotient_degree<F, A>(
     air: &A,
     preprocessed_width: usize,
@@ -19,11 +21,56 @@ pub fn get_log_quotient_degree<F, A>(
 ) -> usize
 where
     F: Field,
-    A: Air<SymbolicAirBuilder<F>>,
+    A: ?Sized + Air<SymbolicAirBuilder<F>>,
+{
+    get_log_quotient_degree_with_external(air, preprocessed_width, 0, num_public_values)
+}
+
+#[deprecated(note = "use `get_max_constraint_degree_with_external`")]
+pub fn get_max_constraint_degree<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> usize
+where
+    F: Field,
+    A: ?Sized + Air<SymbolicAirBuilder<F>>,
+{
+    get_max_constraint_degree_with_external(air, preprocessed_width, 0, num_public_values)
+}
+
+#[deprecated(note = "use `get_symbolic_constraints_with_external`")]
+pub fn get_symbolic_constraints<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> Vec<SymbolicExpression<F>>
+where
+    F: Field,
+    A: ?Sized + Air<SymbolicAirBuilder<F>>,
+{
+    get_symbolic_constraints_with_external(air, preprocessed_width, 0, num_public_values)
+}
+
+#[instrument(name = "infer log of constraint degree", skip_all)]
+pub fn get_log_quotient_degree_with_external<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    external_width: usize,
+    num_public_values: usize,
+) -> usize
+where
+    F: Field,
+    A: ?Sized + Air<SymbolicAirBuilder<F>>,
 {
     // We pad to at least degree 2, since a quotient argument doesn't make sense with smaller degrees.
-    let constraint_degree =
-        get_max_constraint_degree(air, preprocessed_width, num_public_values).max(2);
+    let constraint_degree = get_max_constraint_degree_with_external(
+        air,
+        preprocessed_width,
+        external_width,
+        num_public_values,
+    )
+    .max(2);
 
     // The quotient's actual degree is approximately (max_constraint_degree - 1) n,
     // where subtracting 1 comes from division by the zerofier.
@@ -32,35 +79,77 @@ where
 }
 
 #[instrument(name = "infer constraint degree", skip_all, level = "debug")]
-pub fn get_max_constraint_degree<F, A>(
+pub fn get_max_constraint_degree_with_external<F, A>(
     air: &A,
     preprocessed_width: usize,
+    external_width: usize,
     num_public_values: usize,
 ) -> usize
 where
     F: Field,
-    A: Air<SymbolicAirBuilder<F>>,
+    A: ?Sized + Air<SymbolicAirBuilder<F>>,
 {
-    get_symbolic_constraints(air, preprocessed_width, num_public_values)
-        .iter()
-        .map(|c| c.degree_multiple())
-        .max()
-        .unwrap_or(0)
+    get_symbolic_constraints_with_external(
+        air,
+        preprocessed_width,
+        external_width,
+        num_public_values,
+    )
+    .iter()
+    .map(|c| c.degree_multiple())
+    .max()
+    .unwrap_or(0)
 }
 
 #[instrument(name = "evaluate constraints symbolically", skip_all, level = "debug")]
-pub fn get_symbolic_constraints<F, A>(
+pub fn get_symbolic_constraints_with_external<F, A>(
     air: &A,
     preprocessed_width: usize,
+    external_width: usize,
     num_public_values: usize,
 ) -> Vec<SymbolicExpression<F>>
 where
     F: Field,
-    A: Air<SymbolicAirBuilder<F>>,
+    A: ?Sized + Air<SymbolicAirBuilder<F>>,
 {
-    let mut builder = SymbolicAirBuilder::new(preprocessed_width, air.width(), num_public_values);
+    eval_symbolically(air, preprocessed_width, external_width, num_public_values).constraints
+}
+
+/// The lookups which `air` makes, see [`LookupBuilder`]. Each comes with one constraint among
+/// those returned by [`get_symbolic_constraints_with_external`].
+#[instrument(name = "evaluate lookups symbolically", skip_all, level = "debug")]
+pub fn get_symbolic_lookups<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    external_width: usize,
+    num_public_values: usize,
+) -> Vec<Lookup<SymbolicExpression<F>>>
+where
+    F: Field,
+    A: ?Sized + Air<SymbolicAirBuilder<F>>,
+{
+    eval_symbolically(air, preprocessed_width, external_width, num_public_values).lookups
+}
+
+fn eval_symbolically<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    external_width: usize,
+    num_public_values: usize,
+) -> SymbolicAirBuilder<F>
+where
+    F: Field,
+    A: ?Sized + Air<SymbolicAirBuilder<F>>,
+{
+    let mut builder = SymbolicAirBuilder::new(
+        preprocessed_width,
+        air.width(),
+        external_width,
+        num_public_values,
+    )
+    .with_column_names(air.column_names());
     air.eval(&mut builder);
-    builder.constraints()
+    builder
 }
 
 /// An `AirBuilder` for evaluating constraints symbolically, and recording them for later use.
@@ -68,12 +157,19 @@ where
 pub struct SymbolicAirBuilder<F: Field> {
     preprocessed: RowMajorMatrix<SymbolicVariable<F>>,
     main: RowMajorMatrix<SymbolicVariable<F>>,
+    external: RowMajorMatrix<SymbolicVariable<F>>,
     public_values: Vec<SymbolicVariable<F>>,
     constraints: Vec<SymbolicExpression<F>>,
+    lookups: Vec<Lookup<SymbolicExpression<F>>>,
 }
 
 impl<F: Field> SymbolicAirBuilder<F> {
-    pub(crate) fn new(preprocessed_width: usize, width: usize, num_public_values: usize) -> Self {
+    pub(crate) fn new(
+        preprocessed_width: usize,
+        width: usize,
+        external_width: usize,
+        num_public_values: usize,
+    ) -> Self {
         let prep_values = [0, 1]
             .into_iter()
             .flat_map(|offset| {
@@ -87,19 +183,34 @@ impl<F: Field> SymbolicAirBuilder<F> {
                 (0..width).map(move |index| SymbolicVariable::new(Entry::Main { offset }, index))
             })
             .collect();
+        let external_values = [0, 1]
+            .into_iter()
+            .flat_map(|offset| {
+                (0..external_width)
+                    .map(move |index| SymbolicVariable::new(Entry::External { offset }, index))
+            })
+            .collect();
         let public_values = (0..num_public_values)
             .map(move |index| SymbolicVariable::new(Entry::Public, index))
             .collect();
         Self {
             preprocessed: RowMajorMatrix::new(prep_values, preprocessed_width),
             main: RowMajorMatrix::new(main_values, width),
+            external: RowMajorMatrix::new(external_values, external_width),
             public_values,
             constraints: vec![],
+            lookups: vec![],
         }
     }
 
-    pub(crate) fn constraints(self) -> Vec<SymbolicExpression<F>> {
-        self.constraints
+    /// Attach `names` to the main trace's variables, so that they show up in the constraints.
+    pub(crate) fn with_column_names(mut self, names: Option<Vec<&'static str>>) -> Self {
+        if let Some(names) = names {
+            for variable in &mut self.main.values {
+                *variable = variable.with_name(names.get(variable.index).copied());
+            }
+        }
+        self
     }
 }
 
@@ -146,3 +257,147 @@ impl<F: Field> PairBuilder for SymbolicAirBuilder<F> {
         self.preprocessed.clone()
     }
 }
+
+impl<F: Field> ExternalBuilder for SymbolicAirBuilder<F> {
+    fn external(&self) -> Self::M {
+        self.external.clone()
+    }
+}
+
+impl<F: Field> LookupBuilder for SymbolicAirBuilder<F> {
+    /// Record the lookup, and constrain its running sum, which is a `Permutation` variable over
+    /// the `Challenge` variable.
+    fn lookup_with_count<I0, I1, I2, I3>(
+        &mut self,
+        count: I0,
+        send: I1,
+        receive: I2,
+        multiplicity: I3,
+    ) where
+        I0: Into<Self::Expr>,
+        I1: Into<Self::Expr>,
+        I2: Into<Self::Expr>,
+        I3: Into<Self::Expr>,
+    {
+        let lookup = Lookup {
+            count: count.into(),
+            send: send.into(),
+            receive: receive.into(),
+            multiplicity: multiplicity.into(),
+        };
+        let index = self.lookups.len();
+        let [local, next]: [SymbolicExpression<F>; 2] =
+            [0, 1].map(|offset| SymbolicVariable::new(Entry::Permutation { offset }, index).into());
+        let challenge: SymbolicExpression<F> = SymbolicVariable::new(Entry::Challenge, 0).into();
+        self.constraints
+            .push(lookup.clone().constraint(challenge, local, next));
+        self.lookups.push(lookup);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_air::BaseAir;
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::Matrix;
+
+    use super::*;
+
+    type F = BabyBear;
+
+    /// An AIR over three columns whose constraints are given by `eval`.
+    struct TestAir {
+        eval: fn(&mut SymbolicAirBuilder<F>),
+    }
+
+    impl BaseAir<F> for TestAir {
+        fn width(&self) -> usize {
+            3
+        }
+    }
+
+    impl Air<SymbolicAirBuilder<F>> for TestAir {
+        fn eval(&self, builder: &mut SymbolicAirBuilder<F>) {
+            (self.eval)(builder)
+        }
+    }
+
+    fn local_and_next(
+        builder: &SymbolicAirBuilder<F>,
+    ) -> (Vec<SymbolicVariable<F>>, Vec<SymbolicVariable<F>>) {
+        let main = builder.main();
+        (main.row_slice(0).to_vec(), main.row_slice(1).to_vec())
+    }
+
+    fn degrees(eval: fn(&mut SymbolicAirBuilder<F>)) -> (usize, usize, usize) {
+        let air = TestAir { eval };
+        (
+            get_symbolic_constraints_with_external::<F, _>(&air, 0, 0, 0).len(),
+            get_max_constraint_degree_with_external::<F, _>(&air, 0, 0, 0),
+            get_log_quotient_degree_with_external::<F, _>(&air, 0, 0, 0),
+        )
+    }
+
+    #[test]
+    fn linear_constraints() {
+        let (count, degree, log_quotient_degree) = degrees(|builder| {
+            let (local, _) = local_and_next(builder);
+            builder.assert_eq(local[0], local[1] + local[2]);
+            builder.assert_zero(local[0] - F::ONE);
+        });
+        assert_eq!(count, 2);
+        assert_eq!(degree, 1);
+        // Degrees below 2 are padded to 2.
+        assert_eq!(log_quotient_degree, 0);
+    }
+
+    #[test]
+    fn quadratic_constraint() {
+        let (count, degree, log_quotient_degree) = degrees(|builder| {
+            let (local, _) = local_and_next(builder);
+            builder.assert_bool(local[0]);
+        });
+        assert_eq!(count, 1);
+        assert_eq!(degree, 2);
+        assert_eq!(log_quotient_degree, 0);
+    }
+
+    #[test]
+    fn cubic_constraint() {
+        let (count, degree, log_quotient_degree) = degrees(|builder| {
+            let (local, _) = local_and_next(builder);
+            builder.assert_eq(local[0] * local[1] * local[2], local[0]);
+            builder.assert_bool(local[1]);
+        });
+        assert_eq!(count, 2);
+        assert_eq!(degree, 3);
+        assert_eq!(log_quotient_degree, 1);
+    }
+
+    #[test]
+    fn selectors_add_to_degree() {
+        // The transition selector doesn't vanish on the whole trace domain, so it adds nothing,
+        // while the first and last row selectors each add one.
+        let (count, degree, log_quotient_degree) = degrees(|builder| {
+            let (local, next) = local_and_next(builder);
+            builder
+                .when_transition()
+                .assert_eq(next[0], local[0] * local[1]);
+            builder.when_first_row().assert_eq(local[2], F::ONE);
+        });
+        assert_eq!(count, 2);
+        assert_eq!(degree, 2);
+        assert_eq!(log_quotient_degree, 0);
+
+        let (count, degree, log_quotient_degree) = degrees(|builder| {
+            let (local, next) = local_and_next(builder);
+            builder
+                .when_transition()
+                .assert_eq(next[0], local[0] * local[1]);
+            builder.when_last_row().assert_bool(local[2]);
+        });
+        assert_eq!(count, 2);
+        assert_eq!(degree, 3);
+        assert_eq!(log_quotient_degree, 1);
+    }
+}
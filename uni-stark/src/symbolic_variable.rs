@@ -9,6 +9,7 @@ use crate::symbolic_expression::SymbolicExpression;
 pub enum Entry {
     Preprocessed { offset: usize },
     Main { offset: usize },
+    External { offset: usize },
     Permutation { offset: usize },
     Public,
     Challenge,
@@ -19,6 +20,8 @@ pub enum Entry {
 pub struct SymbolicVariable<F> {
     pub entry: Entry,
     pub index: usize,
+    /// The column's name, if the AIR gives main trace columns names.
+    pub name: Option<&'static str>,
     pub(crate) _phantom: PhantomData<F>,
 }
 
@@ -27,13 +30,23 @@ impl<F> SymbolicVariable<F> {
         Self {
             entry,
             index,
+            name: None,
             _phantom: PhantomData,
         }
     }
 
+    #[must_use]
+    pub const fn with_name(mut self, name: Option<&'static str>) -> Self {
+        self.name = name;
+        self
+    }
+
     pub const fn degree_multiple(&self) -> usize {
         match self.entry {
-            Entry::Preprocessed { .. } | Entry::Main { .. } | Entry::Permutation { .. } => 1,
+            Entry::Preprocessed { .. }
+            | Entry::Main { .. }
+            | Entry::External { .. }
+            | Entry::Permutation { .. } => 1,
             Entry::Public | Entry::Challenge => 0,
         }
     }
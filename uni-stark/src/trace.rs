@@ -0,0 +1,139 @@
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+
+/// Generate a `height x width` trace whose rows can be computed independently of each other, in
+/// parallel. `fill_row(r, row)` writes row `r`, which starts out zeroed.
+pub fn generate_trace_rows_parallel<F, G>(
+    height: usize,
+    width: usize,
+    fill_row: G,
+) -> RowMajorMatrix<F>
+where
+    F: Field,
+    G: Fn(usize, &mut [F]) + Sync,
+{
+    assert!(width > 0, "A trace must have at least one column");
+    let mut trace = RowMajorMatrix::new(F::zero_vec(height * width), width);
+    p3_maybe_rayon::install(height * width, || {
+        trace
+            .par_rows_mut()
+            .enumerate()
+            .for_each(|(r, row)| fill_row(r, row));
+    });
+    trace
+}
+
+/// Generate a `height x width` trace where each row depends on the rows before it, through a state
+/// carried from one row to the next. `step(state, row)` writes a row from `state` and advances it
+/// to the next row's state.
+pub fn generate_trace_scan<F, S, G>(
+    height: usize,
+    width: usize,
+    initial_state: S,
+    mut step: G,
+) -> RowMajorMatrix<F>
+where
+    F: Field,
+    G: FnMut(&mut S, &mut [F]),
+{
+    assert!(width > 0, "A trace must have at least one column");
+    let mut trace = RowMajorMatrix::new(F::zero_vec(height * width), width);
+    let mut state = initial_state;
+    for row in trace.rows_mut() {
+        step(&mut state, row);
+    }
+    trace
+}
+
+/// The same trace as [`generate_trace_scan`], generated in parallel when the state `rows` rows
+/// ahead can be computed directly: `advance(state, rows)` must equal the state reached by calling
+/// `step` `rows` times from `state`. This is the case when the transition is associative, e.g. it
+/// is linear and can be raised to a power.
+///
+/// The rows are split into one chunk per thread. Each chunk jumps to its starting state with
+/// `advance`, then fills its rows with `step`.
+pub fn generate_trace_scan_parallel<F, S, A, G>(
+    height: usize,
+    width: usize,
+    initial_state: S,
+    advance: A,
+    step: G,
+) -> RowMajorMatrix<F>
+where
+    F: Field,
+    S: Sync,
+    A: Fn(&S, usize) -> S + Sync,
+    G: Fn(&mut S, &mut [F]) + Sync,
+{
+    assert!(width > 0, "A trace must have at least one column");
+    let mut trace = RowMajorMatrix::new(F::zero_vec(height * width), width);
+    p3_maybe_rayon::install(height * width, || {
+        let chunk_rows = height.div_ceil(current_num_threads()).max(1);
+        trace
+            .values
+            .par_chunks_mut(chunk_rows * width)
+            .enumerate()
+            .for_each(|(i, chunk)| {
+                let mut state = advance(&initial_state, i * chunk_rows);
+                for row in chunk.chunks_exact_mut(width) {
+                    step(&mut state, row);
+                }
+            });
+    });
+    trace
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::FieldAlgebra;
+
+    use super::*;
+
+    type F = BabyBear;
+
+    #[test]
+    fn test_parallel_rows_match_serial() {
+        let fill_row = |r: usize, row: &mut [F]| {
+            for (c, x) in row.iter_mut().enumerate() {
+                *x = F::from_canonical_usize(r * r + c);
+            }
+        };
+        for height in [0, 1, 7, 64, 1000] {
+            let parallel = generate_trace_rows_parallel(height, 3, fill_row);
+            let mut serial = RowMajorMatrix::new(F::zero_vec(height * 3), 3);
+            for (r, row) in serial.rows_mut().enumerate() {
+                fill_row(r, row);
+            }
+            assert_eq!(parallel, serial, "height {height}");
+        }
+    }
+
+    #[test]
+    fn test_parallel_scan_matches_serial() {
+        // Running sums of 1, 2, 3, ..., whose state after `rows` more rows has a closed form.
+        let step = |state: &mut (u64, u64), row: &mut [F]| {
+            row[0] = F::from_canonical_u64(state.0);
+            row[1] = F::from_canonical_u64(state.1);
+            state.0 += 1;
+            state.1 += state.0;
+        };
+        let advance = |&(n, sum): &(u64, u64), rows: usize| {
+            let rows = rows as u64;
+            (n + rows, sum + rows * n + rows * (rows + 1) / 2)
+        };
+        for height in [0, 1, 5, 64, 1023] {
+            let serial = generate_trace_scan(height, 2, (0, 0), step);
+            let parallel = generate_trace_scan_parallel(height, 2, (0, 0), advance, step);
+            assert_eq!(parallel, serial, "height {height}");
+            let sums: Vec<F> = serial.values.iter().skip(1).step_by(2).copied().collect();
+            assert!(sums
+                .iter()
+                .enumerate()
+                .all(|(i, &s)| s == F::from_canonical_usize(i * (i + 1) / 2)));
+        }
+    }
+}
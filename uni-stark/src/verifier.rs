@@ -3,17 +3,25 @@ use alloc::vec::Vec;
 
 use itertools::Itertools;
 use p3_air::{Air, BaseAir};
-use p3_challenger::{CanObserve, CanSample, FieldChallenger};
+use p3_challenger::{CanObserve, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
 use p3_field::{Field, FieldAlgebra, FieldExtensionAlgebra};
 use p3_matrix::dense::RowMajorMatrixView;
 use p3_matrix::stack::VerticalPair;
+use p3_matrix::Dimensions;
 use tracing::instrument;
 
-use crate::symbolic_builder::{get_log_quotient_degree, SymbolicAirBuilder};
-use crate::{PcsError, Proof, StarkGenericConfig, Val, VerifierConstraintFolder};
+use crate::prover::unchecked_log_quotient_degree;
+use crate::symbolic_builder::{
+    get_max_constraint_degree_with_external, get_symbolic_lookups, SymbolicAirBuilder,
+};
+use crate::{
+    Com, Domain, ExternalCommitment, OpenedRows, PcsError, Proof, ProofSection, StarkGenericConfig,
+    Val, VerifierConstraintFolder, VerifyingKey,
+};
 
-#[instrument(skip_all)]
+/// Verify a proof of an AIR with no preprocessed trace. See [`verify_with_key`] for AIRs which
+/// have one.
 pub fn verify<SC, A>(
     config: &SC,
     air: &A,
@@ -25,6 +33,114 @@ where
     SC: StarkGenericConfig,
     A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
 {
+    verify_with_key(
+        config,
+        &VerifyingKey::default(),
+        air,
+        challenger,
+        proof,
+        public_values,
+    )
+}
+
+/// Verify a proof of an AIR whose fixed parts were committed to by [`setup`](crate::setup).
+///
+/// The proof must contain exactly the optional sections declared by `verifying_key`; this is
+/// checked before anything is observed by the challenger.
+pub fn verify_with_key<SC, A>(
+    config: &SC,
+    verifying_key: &VerifyingKey<SC>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    verify_inner(
+        config,
+        verifying_key,
+        None,
+        None,
+        air,
+        challenger,
+        proof,
+        public_values,
+    )
+}
+
+/// Verify a proof made by [`prove_with_external`](crate::prove_with_external), whose trace is
+/// constrained against the external trace behind `external`.
+///
+/// The proof must contain an external section in addition to those declared by `verifying_key`,
+/// and its openings are checked against `external.commitment`.
+pub fn verify_with_external<SC, A>(
+    config: &SC,
+    verifying_key: &VerifyingKey<SC>,
+    external: &ExternalCommitment<Com<SC>>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    verify_inner(
+        config,
+        verifying_key,
+        Some(external),
+        None,
+        air,
+        challenger,
+        proof,
+        public_values,
+    )
+}
+
+/// Verify a proof, with `trace_check` given `zeta` and the trace's opening there if the verifier
+/// can also compute some of the trace's columns itself.
+#[instrument(skip_all)]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify_inner<SC, A>(
+    config: &SC,
+    verifying_key: &VerifyingKey<SC>,
+    external: Option<&ExternalCommitment<Com<SC>>>,
+    trace_check: Option<&dyn Fn(SC::Challenge, &[SC::Challenge]) -> bool>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let preprocessed_key = verifying_key.preprocessed.as_ref();
+    let preprocessed_width = preprocessed_key.map_or(0, |key| key.width);
+    let external_width = external.map_or(0, |external| external.width);
+    let num_lookups = get_symbolic_lookups::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        external_width,
+        public_values.len(),
+    )
+    .len();
+
+    for section in ProofSection::ALL {
+        let expected = match section {
+            ProofSection::External => external.is_some(),
+            ProofSection::Lookup => num_lookups > 0,
+            _ => verifying_key.has_section(section),
+        };
+        if proof.has_section(section) != expected {
+            return Err(VerificationError::SectionMismatch { section, expected });
+        }
+    }
+
     let Proof {
         commitments,
         opened_values,
@@ -32,12 +148,27 @@ where
         degree_bits,
     } = proof;
 
+    let constraint_degree = get_max_constraint_degree_with_external::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        external_width,
+        public_values.len(),
+    );
+    let log_quotient_degree = unchecked_log_quotient_degree(constraint_degree, config.zk());
+    check_degree_bits(config, *degree_bits, log_quotient_degree)?;
     let degree = 1 << degree_bits;
-    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(air, 0, public_values.len());
     let quotient_degree = 1 << log_quotient_degree;
 
     let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(degree);
+    // A zero-knowledge proof's trace is committed to over twice the trace domain, and its quotient
+    // is blinded by a random polynomial opened after the chunks.
+    let committed_trace_domain = if config.zk() {
+        pcs.natural_domain_for_degree(2 * degree)
+    } else {
+        trace_domain
+    };
+    let num_quotient_openings = quotient_degree + usize::from(config.zk());
     let quotient_domain =
         trace_domain.create_disjoint_domain(1 << (degree_bits + log_quotient_degree));
     let quotient_chunks_domains = quotient_domain.split_domains(quotient_degree);
@@ -45,84 +176,155 @@ where
     let air_width = <A as BaseAir<Val<SC>>>::width(air);
     let valid_shape = opened_values.trace_local.len() == air_width
         && opened_values.trace_next.len() == air_width
-        && opened_values.quotient_chunks.len() == quotient_degree
+        && opened_values.quotient_chunks.len() == num_quotient_openings
         && opened_values
             .quotient_chunks
             .iter()
-            .all(|qc| qc.len() == <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D);
+            .all(|qc| qc.len() == <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D)
+        && match (preprocessed_key, &opened_values.preprocessed) {
+            (Some(key), Some(values)) => {
+                key.degree_bits == *degree_bits
+                    && values.local.len() == key.width
+                    && values.next.len() == key.width
+            }
+            // The sections were checked to match the key above.
+            _ => true,
+        }
+        && match (external, &opened_values.external) {
+            (Some(external), Some(values)) => {
+                external.degree_bits == *degree_bits
+                    && values.local.len() == external.width
+                    && values.next.len() == external.width
+            }
+            _ => true,
+        }
+        && match (&commitments.lookup, &opened_values.lookup) {
+            (Some(_), Some(values)) => {
+                let width = num_lookups * <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D;
+                values.local.len() == width && values.next.len() == width
+            }
+            (None, None) => true,
+            _ => false,
+        };
     if !valid_shape {
         return Err(VerificationError::InvalidProofShape);
     }
 
     // Observe the instance.
+    challenger.begin_phase("instance");
     challenger.observe(Val::<SC>::from_canonical_usize(proof.degree_bits));
+    if let Some(key) = preprocessed_key {
+        challenger.observe(key.commitment.clone());
+    }
+    if let Some(external) = external {
+        challenger.observe(external.commitment.clone());
+    }
     // TODO: Might be best practice to include other instance data here in the transcript, like some
     // encoding of the AIR. This protects against transcript collisions between distinct instances.
     // Practically speaking though, the only related known attack is from failing to include public
     // values. It's not clear if failing to include other instance data could enable a transcript
     // collision, since most such changes would completely change the set of satisfying witnesses.
 
+    // The shapes assumed below for each of the proof's commitments are observed with it.
+    let ext_degree = <SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D;
+    challenger.observe_shape(&[Dimensions {
+        width: air_width,
+        height: committed_trace_domain.size(),
+    }]);
     challenger.observe(commitments.trace.clone());
     challenger.observe_slice(public_values);
+    let lookup_challenge = match &commitments.lookup {
+        Some(commitment) => {
+            challenger.begin_phase("lookups");
+            let lookup_challenge: SC::Challenge = challenger.sample_ext_element();
+            challenger.observe_shape(&[Dimensions {
+                width: num_lookups * ext_degree,
+                height: degree,
+            }]);
+            challenger.observe(commitment.clone());
+            lookup_challenge
+        }
+        None => SC::Challenge::ZERO,
+    };
+    challenger.begin_phase("quotient");
     let alpha: SC::Challenge = challenger.sample_ext_element();
+    let quotient_shape = quotient_chunks_domains
+        .iter()
+        .map(|domain| Dimensions {
+            width: ext_degree,
+            height: domain.size(),
+        })
+        .chain(config.zk().then_some(Dimensions {
+            width: ext_degree,
+            height: quotient_domain.size(),
+        }))
+        .collect_vec();
+    challenger.observe_shape(&quotient_shape);
     challenger.observe(commitments.quotient_chunks.clone());
 
-    let zeta: SC::Challenge = challenger.sample();
+    challenger.begin_phase("opening");
+    let zeta: SC::Challenge = challenger.sample_ext_element();
     let zeta_next = trace_domain.next_point(zeta).unwrap();
 
-    pcs.verify(
-        vec![
-            (
-                commitments.trace.clone(),
+    let mut rounds = vec![
+        (
+            commitments.trace.clone(),
+            vec![(
+                committed_trace_domain,
+                vec![
+                    (zeta, opened_values.trace_local.clone()),
+                    (zeta_next, opened_values.trace_next.clone()),
+                ],
+            )],
+        ),
+        (
+            commitments.quotient_chunks.clone(),
+            quotient_chunks_domains
+                .iter()
+                .chain(config.zk().then_some(&quotient_domain))
+                .zip(&opened_values.quotient_chunks)
+                .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
+                .collect_vec(),
+        ),
+    ];
+    let optional_rounds = [
+        preprocessed_key.map(|key| &key.commitment),
+        external.map(|external| &external.commitment),
+        commitments.lookup.as_ref(),
+    ];
+    let optional_values = [
+        &opened_values.preprocessed,
+        &opened_values.external,
+        &opened_values.lookup,
+    ];
+    for (commitment, values) in optional_rounds.into_iter().zip(optional_values) {
+        if let (Some(commitment), Some(values)) = (commitment, values) {
+            rounds.push((
+                commitment.clone(),
                 vec![(
                     trace_domain,
                     vec![
-                        (zeta, opened_values.trace_local.clone()),
-                        (zeta_next, opened_values.trace_next.clone()),
+                        (zeta, values.local.clone()),
+                        (zeta_next, values.next.clone()),
                     ],
                 )],
-            ),
-            (
-                commitments.quotient_chunks.clone(),
-                quotient_chunks_domains
-                    .iter()
-                    .zip(&opened_values.quotient_chunks)
-                    .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
-                    .collect_vec(),
-            ),
-        ],
-        opening_proof,
-        challenger,
-    )
-    .map_err(VerificationError::InvalidOpeningArgument)?;
-
-    let zps = quotient_chunks_domains
-        .iter()
-        .enumerate()
-        .map(|(i, domain)| {
-            quotient_chunks_domains
-                .iter()
-                .enumerate()
-                .filter(|(j, _)| *j != i)
-                .map(|(_, other_domain)| {
-                    other_domain.zp_at_point(zeta)
-                        * other_domain.zp_at_point(domain.first_point()).inverse()
-                })
-                .product::<SC::Challenge>()
-        })
-        .collect_vec();
+            ));
+        }
+    }
+    pcs.verify(rounds, opening_proof, challenger)
+        .map_err(VerificationError::InvalidOpeningArgument)?;
+    if let Some(trace_check) = trace_check {
+        if !trace_check(zeta, &opened_values.trace_local) {
+            return Err(VerificationError::ComputedColumnMismatch);
+        }
+    }
 
-    let quotient = opened_values
-        .quotient_chunks
-        .iter()
-        .enumerate()
-        .map(|(ch_i, ch)| {
-            ch.iter()
-                .enumerate()
-                .map(|(e_i, &c)| zps[ch_i] * SC::Challenge::monomial(e_i) * c)
-                .sum::<SC::Challenge>()
-        })
-        .sum::<SC::Challenge>();
+    let (quotient_chunks, quotient_blinding) =
+        opened_values.quotient_chunks.split_at(quotient_degree);
+    let mut quotient = recompose_quotient::<SC>(&quotient_chunks_domains, quotient_chunks, zeta);
+    if let Some(blinding) = quotient_blinding.first() {
+        quotient -= unflatten::<SC>(blinding)[0];
+    }
 
     let sels = trace_domain.selectors_at_point(zeta);
 
@@ -130,9 +332,22 @@ where
         RowMajorMatrixView::new_row(&opened_values.trace_local),
         RowMajorMatrixView::new_row(&opened_values.trace_next),
     );
+    let preprocessed = opened_rows_view(&opened_values.preprocessed);
+    let external_rows = opened_rows_view(&opened_values.external);
+    // The running sums were committed to flattened to the base field.
+    let lookup_sums = opened_values.lookup.as_ref().map(|values| OpenedRows {
+        local: unflatten::<SC>(&values.local),
+        next: unflatten::<SC>(&values.next),
+    });
+    let lookup = opened_rows_view(&lookup_sums);
 
     let mut folder = VerifierConstraintFolder {
         main,
+        preprocessed,
+        external: external_rows,
+        lookup,
+        lookup_challenge,
+        lookup_index: 0,
         public_values,
         is_first_row: sels.is_first_row,
         is_last_row: sels.is_last_row,
@@ -152,12 +367,122 @@ where
     Ok(())
 }
 
+/// Check that a claimed trace of `2^degree_bits` rows, and its quotient domain
+/// `2^log_quotient_degree` times as large, are small enough for the PCS to have committed to them,
+/// so that building their domains can't overflow.
+pub(crate) fn check_degree_bits<SC: StarkGenericConfig>(
+    config: &SC,
+    degree_bits: usize,
+    log_quotient_degree: usize,
+) -> Result<(), VerificationError<PcsError<SC>>> {
+    let pcs = config.pcs();
+    let zk = usize::from(config.zk());
+    // A zero-knowledge proof's trace is committed over twice the trace domain, and its quotient
+    // evaluated over an LDE which is already twice as high.
+    let committed_fits = match pcs.max_log_domain_size() {
+        Some(max_log_size) => degree_bits
+            .checked_add(zk)
+            .is_some_and(|bits| bits <= max_log_size),
+        None => true,
+    };
+    let quotient_fits = match pcs.max_log_evaluation_blowup() {
+        Some(max_log_blowup) => log_quotient_degree <= max_log_blowup + zk,
+        None => true,
+    };
+    let quotient_bits = degree_bits.checked_add(log_quotient_degree);
+    if !committed_fits
+        || !quotient_fits
+        || !quotient_bits.is_some_and(|bits| bits < usize::BITS as usize)
+    {
+        return Err(VerificationError::DegreeOutOfRange);
+    }
+    Ok(())
+}
+
+/// Evaluate the quotient at `zeta` from the openings of its chunks, each over one of
+/// `chunk_domains` and flattened to its base field coefficients.
+pub(crate) fn recompose_quotient<SC: StarkGenericConfig>(
+    chunk_domains: &[Domain<SC>],
+    chunks: &[Vec<SC::Challenge>],
+    zeta: SC::Challenge,
+) -> SC::Challenge {
+    let zps = chunk_domains
+        .iter()
+        .enumerate()
+        .map(|(i, domain)| {
+            chunk_domains
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other_domain)| {
+                    other_domain.zp_at_point(zeta)
+                        * other_domain.zp_at_point(domain.first_point()).inverse()
+                })
+                .product::<SC::Challenge>()
+        })
+        .collect_vec();
+
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(ch_i, ch)| {
+            ch.iter()
+                .enumerate()
+                .map(|(e_i, &c)| zps[ch_i] * SC::Challenge::monomial(e_i) * c)
+                .sum::<SC::Challenge>()
+        })
+        .sum()
+}
+
+/// Recombine extension field elements from the openings of their base field coefficients.
+fn unflatten<SC: StarkGenericConfig>(values: &[SC::Challenge]) -> Vec<SC::Challenge> {
+    values
+        .chunks_exact(<SC::Challenge as FieldExtensionAlgebra<Val<SC>>>::D)
+        .map(|coeffs| {
+            coeffs
+                .iter()
+                .enumerate()
+                .map(|(e_i, &c)| SC::Challenge::monomial(e_i) * c)
+                .sum()
+        })
+        .collect()
+}
+
+/// View optional openings as a pair of rows, which are empty if the section is absent.
+fn opened_rows_view<Challenge>(
+    values: &Option<OpenedRows<Challenge>>,
+) -> VerticalPair<RowMajorMatrixView<'_, Challenge>, RowMajorMatrixView<'_, Challenge>> {
+    let (local, next) = match values {
+        Some(values) => (&values.local[..], &values.next[..]),
+        None => (&[][..], &[][..]),
+    };
+    VerticalPair::new(
+        RowMajorMatrixView::new_row(local),
+        RowMajorMatrixView::new_row(next),
+    )
+}
+
 #[derive(Debug)]
 pub enum VerificationError<PcsErr> {
+    /// The proof contains an optional section which the verifier doesn't expect, or lacks one
+    /// which it does.
+    SectionMismatch {
+        section: ProofSection,
+        /// Whether the verifier expects the section.
+        expected: bool,
+    },
     InvalidProofShape,
+    /// The proof claims a trace so tall that the PCS couldn't have committed to it or its quotient.
+    DegreeOutOfRange,
     /// An error occurred while verifying the claimed openings.
     InvalidOpeningArgument(PcsErr),
     /// Out-of-domain evaluation mismatch, i.e. `constraints(zeta)` did not match
     /// `quotient(zeta) Z_H(zeta)`.
     OodEvaluationMismatch,
+    /// The opening of a trace column which the verifier computes itself, such as the selectors of
+    /// a [stacked](crate::prove_stacked) trace, differs from what it computed.
+    ComputedColumnMismatch,
+    /// The config is zero-knowledge, which this kind of proof, e.g. a
+    /// [stacked](crate::prove_stacked) one, doesn't support.
+    ZkUnsupported,
 }
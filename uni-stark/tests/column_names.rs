@@ -0,0 +1,120 @@
+//! The debug constraint checker only runs in debug builds.
+#![cfg(debug_assertions)]
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    get_symbolic_constraints_with_external, prove, Entry, StarkConfig, SymbolicExpression,
+};
+use rand::thread_rng;
+
+const HEIGHT: usize = 8;
+
+/// A counter alongside its square, with or without column names.
+struct SquaresAir {
+    named: bool,
+}
+
+impl<F> BaseAir<F> for SquaresAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn column_names(&self) -> Option<Vec<&'static str>> {
+        self.named.then(|| vec!["counter", "square"])
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for SquaresAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let (counter, square) = (local[0], local[1]);
+
+        builder.when_first_row().assert_zero(counter);
+        builder
+            .when_transition()
+            .assert_eq(next[0], counter + AB::Expr::ONE);
+        builder.assert_eq(square, counter * counter);
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+/// Prove `SquaresAir` for a trace whose square is wrong on row 3.
+fn prove_bad_trace(named: bool) {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = RowMajorMatrix::new(
+        (0..HEIGHT)
+            .flat_map(|i| {
+                let square = if i == 3 { 10 } else { i * i };
+                [i, square].map(Val::from_canonical_usize)
+            })
+            .collect(),
+        2,
+    );
+    let mut challenger = Challenger::new(perm);
+    prove(
+        &config,
+        &SquaresAir { named },
+        &mut challenger,
+        trace,
+        &vec![],
+    )
+    .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "constraint 2 involving local square = 10, local counter = 3")]
+fn test_failure_reports_column_names() {
+    prove_bad_trace(true);
+}
+
+#[test]
+#[should_panic(expected = "constraint 2 involving local main[1] = 10, local main[0] = 3")]
+fn test_failure_without_names_reports_indices() {
+    prove_bad_trace(false);
+}
+
+#[test]
+fn test_symbolic_variables_are_named() {
+    let constraints =
+        get_symbolic_constraints_with_external::<Val, _>(&SquaresAir { named: true }, 0, 0, 0);
+    let SymbolicExpression::Sub { x, .. } = &constraints[2] else {
+        panic!("expected a difference");
+    };
+    let SymbolicExpression::Variable(square) = &**x else {
+        panic!("expected a variable");
+    };
+    assert_eq!(square.entry, Entry::Main { offset: 0 });
+    assert_eq!(square.name, Some("square"));
+}
@@ -0,0 +1,187 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, FriConfig, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::{Dimensions, Matrix};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{CountingPermutation, PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove, prove_zk, prover_cost_estimate, verifier_cost_estimate, verify, StarkConfig,
+};
+use rand::thread_rng;
+
+/// Columns which each square themselves from one row to the next, so that the quotient domain is
+/// twice the size of the trace domain.
+struct SquaresAir {
+    width: usize,
+}
+
+impl<F> BaseAir<F> for SquaresAir {
+    fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for SquaresAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        for (&local, &next) in local.iter().zip(next.iter()) {
+            let local: AB::Expr = local.into();
+            builder.when_transition().assert_eq(next, local.square());
+        }
+    }
+}
+
+/// Columns without any constraints, whose quotient the prover still splits like one of degree 2.
+struct UnconstrainedAir {
+    width: usize,
+}
+
+impl<F> BaseAir<F> for UnconstrainedAir {
+    fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for UnconstrainedAir {
+    fn eval(&self, _builder: &mut AB) {}
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn trace(height: usize, width: usize) -> RowMajorMatrix<Val> {
+    let mut values = Val::zero_vec(height * width);
+    for (j, value) in values[..width].iter_mut().enumerate() {
+        *value = Val::from_canonical_usize(j + 2);
+    }
+    for i in 1..height {
+        for j in 0..width {
+            values[i * width + j] = values[(i - 1) * width + j].square();
+        }
+    }
+    RowMajorMatrix::new(values, width)
+}
+
+#[test]
+fn test_estimates_an_air_without_constraints() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let pcs = Pcs::new(
+        Dft::default(),
+        val_mmcs,
+        create_test_fri_config(challenge_mmcs),
+    );
+    let config = MyConfig::new(pcs);
+
+    let dims = [Dimensions {
+        width: 16,
+        height: 1 << 10,
+    }];
+    let unconstrained = prover_cost_estimate(&config, &UnconstrainedAir { width: 16 }, &dims, 0);
+    let squares = prover_cost_estimate(&config, &SquaresAir { width: 16 }, &dims, 0);
+    // Both quotients have one chunk.
+    assert_eq!(unconstrained.opening, squares.opening);
+    assert_eq!(
+        verifier_cost_estimate(&config, &UnconstrainedAir { width: 16 }, &dims, 0).opening,
+        verifier_cost_estimate(&config, &SquaresAir { width: 16 }, &dims, 0).opening,
+    );
+}
+
+type CountingPerm = CountingPermutation<Perm>;
+type CountingHash = PaddingFreeSponge<CountingPerm, 16, 8, 8>;
+type CountingCompress = TruncatedPermutation<CountingPerm, 2, 8, 16>;
+type CountingValMmcs = MerkleTreeMmcs<
+    <Val as Field>::Packing,
+    <Val as Field>::Packing,
+    CountingHash,
+    CountingCompress,
+    8,
+>;
+type CountingChallengeMmcs = ExtensionMmcs<Val, Challenge, CountingValMmcs>;
+type CountingPcs = TwoAdicFriPcs<Val, Dft, CountingValMmcs, CountingChallengeMmcs>;
+type CountingConfig = StarkConfig<CountingPcs, Challenge, Challenger>;
+
+#[test]
+fn test_verifier_cost_estimate_matches_permutation_calls() {
+    // Only the MMCS counts its calls; the challenger's aren't estimated.
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let counting = CountingPerm::new(perm.clone());
+    let val_mmcs = CountingValMmcs::new(
+        CountingHash::new(counting.clone()),
+        CountingCompress::new(counting.clone()),
+    );
+    let challenge_mmcs = CountingChallengeMmcs::new(val_mmcs.clone());
+    let fri_configs = [
+        (create_test_fri_config(challenge_mmcs.clone()), false),
+        // Folding by 4, with the last two codewords opened directly.
+        (
+            FriConfig {
+                log_blowup: 2,
+                log_final_poly_len: 2,
+                num_queries: 10,
+                proof_of_work_bits: 1,
+                max_direct_opening_height: 64,
+                log_folding_arity: 2,
+                uniform_query_indices: false,
+                mmcs: challenge_mmcs.clone(),
+            },
+            false,
+        ),
+        // A zero-knowledge proof, whose quotient has four chunks and a blinding.
+        (create_test_fri_config(challenge_mmcs), true),
+    ];
+
+    let (height, width) = (1 << 10, 16);
+    let air = SquaresAir { width };
+    for (fri_config, zk) in fri_configs {
+        let pcs = CountingPcs::new(Dft::default(), val_mmcs.clone(), fri_config);
+        let config = CountingConfig::new(pcs).with_zk(zk);
+        let estimate = verifier_cost_estimate(&config, &air, &[Dimensions { width, height }], 0);
+
+        let mut challenger = Challenger::new(perm.clone());
+        let trace = trace(height, width);
+        let proof = if zk {
+            prove_zk(
+                &config,
+                &air,
+                &mut challenger,
+                trace,
+                &vec![],
+                &mut thread_rng(),
+            )
+            .unwrap()
+        } else {
+            prove(&config, &air, &mut challenger, trace, &vec![]).unwrap()
+        };
+
+        counting.reset();
+        let mut challenger = Challenger::new(perm.clone());
+        verify(&config, &air, &mut challenger, &proof, &vec![]).unwrap();
+        let measured = counting.calls();
+
+        // The estimate rounds each hash up to a whole extra call, and is otherwise exact.
+        let estimated = estimate.total().permutation_calls(8);
+        assert!(
+            measured <= estimated && estimated - estimate.total().hashes <= measured,
+            "estimated {estimated} permutation calls, measured {measured} (zk: {zk})"
+        );
+    }
+}
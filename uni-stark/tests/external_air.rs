@@ -0,0 +1,232 @@
+use p3_air::{Air, AirBuilder, BaseAir, ExternalBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove, prove_with_external, verify, verify_with_external, verify_with_key, ExternalCommitment,
+    ExternalTrace, ProofSection, ProvingKey, StarkConfig, VerificationError, VerifyingKey,
+};
+use rand::thread_rng;
+
+const HEIGHT: usize = 8;
+
+/// A single column counting up from `start`.
+struct CounterAir {
+    start: u32,
+}
+
+impl<F: Field> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+
+        builder
+            .when_first_row()
+            .assert_eq(local, AB::Expr::from_canonical_u32(self.start));
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+    }
+}
+
+/// Two columns, the first copying the external trace of a `CounterAir` proof and the second
+/// holding its double.
+struct DoublingAir;
+
+impl<F: Field> BaseAir<F> for DoublingAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: ExternalBuilder> Air<AB> for DoublingAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let external = builder.external();
+        let local = main.row_slice(0);
+        let (value, double) = (local[0], local[1]);
+        let counter = external.row_slice(0)[0];
+
+        builder.assert_eq(value, counter);
+        builder.assert_eq(double, value + value);
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+fn counter_trace(start: u32) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col(
+        (0..HEIGHT as u32)
+            .map(|i| Val::from_canonical_u32(start + i))
+            .collect(),
+    )
+}
+
+fn doubling_trace(start: u32) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new(
+        (0..HEIGHT as u32)
+            .flat_map(|i| {
+                let value = Val::from_canonical_u32(start + i);
+                [value, value.double()]
+            })
+            .collect(),
+        2,
+    )
+}
+
+#[test]
+fn test_chained_proofs() {
+    let (config, perm) = setup();
+
+    // The first proof, whose trace the second one refers to.
+    let counter_air = CounterAir { start: 5 };
+    let mut challenger = Challenger::new(perm.clone());
+    let first = prove(
+        &config,
+        &counter_air,
+        &mut challenger,
+        counter_trace(5),
+        &vec![],
+    )
+    .unwrap();
+    let mut challenger = Challenger::new(perm.clone());
+    verify(&config, &counter_air, &mut challenger, &first, &vec![]).expect("verification failed");
+
+    let external = ExternalTrace::commit(&config, counter_trace(5));
+    assert_eq!(external.commitment().commitment, *first.trace_commitment());
+
+    let mut challenger = Challenger::new(perm.clone());
+    let second = prove_with_external(
+        &config,
+        &ProvingKey::default(),
+        &external,
+        &DoublingAir,
+        &mut challenger,
+        doubling_trace(5),
+        &vec![],
+    )
+    .unwrap();
+    assert!(second.has_section(ProofSection::External));
+
+    // The verifier only needs the first proof to check the second.
+    let claimed = ExternalCommitment {
+        commitment: first.trace_commitment().clone(),
+        width: 1,
+        degree_bits: first.degree_bits(),
+    };
+    let mut challenger = Challenger::new(perm);
+    verify_with_external(
+        &config,
+        &VerifyingKey::default(),
+        &claimed,
+        &DoublingAir,
+        &mut challenger,
+        &second,
+        &vec![],
+    )
+    .expect("verification failed");
+}
+
+#[test]
+fn test_wrong_external_commitment_is_rejected() {
+    let (config, perm) = setup();
+
+    let external = ExternalTrace::commit(&config, counter_trace(5));
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_external(
+        &config,
+        &ProvingKey::default(),
+        &external,
+        &DoublingAir,
+        &mut challenger,
+        doubling_trace(5),
+        &vec![],
+    )
+    .unwrap();
+
+    // A commitment to a different counter trace doesn't match the openings in the proof.
+    let other = ExternalTrace::commit(&config, counter_trace(6)).commitment();
+    let mut challenger = Challenger::new(perm.clone());
+    let result = verify_with_external(
+        &config,
+        &VerifyingKey::default(),
+        &other,
+        &DoublingAir,
+        &mut challenger,
+        &proof,
+        &vec![],
+    );
+    assert!(matches!(
+        result,
+        Err(VerificationError::InvalidOpeningArgument(_))
+    ));
+
+    // A wrong claimed shape is rejected outright.
+    let mut wrong_width = external.commitment();
+    wrong_width.width = 2;
+    let mut challenger = Challenger::new(perm.clone());
+    let result = verify_with_external(
+        &config,
+        &VerifyingKey::default(),
+        &wrong_width,
+        &DoublingAir,
+        &mut challenger,
+        &proof,
+        &vec![],
+    );
+    assert!(matches!(result, Err(VerificationError::InvalidProofShape)));
+
+    // Without an external commitment, the verifier doesn't expect the section.
+    let mut challenger = Challenger::new(perm);
+    let result = verify_with_key(
+        &config,
+        &VerifyingKey::default(),
+        &DoublingAir,
+        &mut challenger,
+        &proof,
+        &vec![],
+    );
+    assert!(matches!(
+        result,
+        Err(VerificationError::SectionMismatch {
+            section: ProofSection::External,
+            expected: false,
+        })
+    ));
+}
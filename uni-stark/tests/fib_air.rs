@@ -1,19 +1,35 @@
 use std::borrow::Borrow;
+use std::marker::PhantomData;
 
 use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
-use p3_challenger::DuplexChallenger;
+use p3_challenger::{
+    diff_transcripts, CanSample, DuplexChallenger, HashChallenger, SerializingChallenger32,
+    TranscriptEntry, TranscriptEvent, TranscriptRecorder,
+};
+use p3_circle::CirclePcs;
 use p3_commit::ExtensionMmcs;
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{Field, FieldAlgebra, PrimeField64};
-use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_fri::verifier::FriError;
+use p3_fri::{create_benchmark_fri_config, create_test_fri_config, TwoAdicFriPcs};
+use p3_keccak::Keccak256Hash;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
-use p3_merkle_tree::MerkleTreeMmcs;
-use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark::{prove, verify, StarkConfig};
-use rand::thread_rng;
+use p3_merkle_tree::{MerkleTreeError, MerkleTreeMmcs};
+use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{
+    CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32, TruncatedPermutation,
+};
+use p3_uni_stark::{
+    check_security, generate_trace_scan, generate_trace_scan_parallel,
+    get_symbolic_constraints_with_external, prove, prove_resume, prove_with_checkpoints, prove_zk,
+    verify, AirParameters, CheckpointError, PcsError, Proof, ProverError, SecurityReport,
+    StarkConfig, StarkGenericConfig, VerificationError,
+};
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
 
 /// For testing the public values feature
 pub struct FibonacciAir {}
@@ -54,24 +70,59 @@ impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
     }
 }
 
-pub fn generate_trace_rows<F: PrimeField64>(a: u64, b: u64, n: usize) -> RowMajorMatrix<F> {
-    assert!(n.is_power_of_two());
+/// [`FibonacciAir`], but also requiring the right column to start at zero. Its constraints have the
+/// same degree, so a proof of `FibonacciAir` has the shape it expects, but not its constraints.
+struct ZeroRightFibonacciAir;
+
+impl<F> BaseAir<F> for ZeroRightFibonacciAir {
+    fn width(&self) -> usize {
+        NUM_FIBONACCI_COLS
+    }
+}
 
-    let mut trace = RowMajorMatrix::new(F::zero_vec(n * NUM_FIBONACCI_COLS), NUM_FIBONACCI_COLS);
+impl<AB: AirBuilderWithPublicValues> Air<AB> for ZeroRightFibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        FibonacciAir {}.eval(builder);
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &FibonacciRow<AB::Var> = (*local).borrow();
+        builder.when_first_row().assert_zero(local.right);
+    }
+}
 
-    let (prefix, rows, suffix) = unsafe { trace.values.align_to_mut::<FibonacciRow<F>>() };
-    assert!(prefix.is_empty(), "Alignment should match");
-    assert!(suffix.is_empty(), "Alignment should match");
-    assert_eq!(rows.len(), n);
+pub fn generate_trace_rows<F: PrimeField64>(a: u64, b: u64, n: usize) -> RowMajorMatrix<F> {
+    assert!(n.is_power_of_two());
 
-    rows[0] = FibonacciRow::new(F::from_canonical_u64(a), F::from_canonical_u64(b));
+    generate_trace_scan_parallel(
+        n,
+        NUM_FIBONACCI_COLS,
+        (F::from_canonical_u64(a), F::from_canonical_u64(b)),
+        |&(left, right), rows| {
+            // The transition is linear, so `rows` steps at once are its `rows`-th power.
+            let (f_k, f_k1) = fibonacci_pair::<F>(rows);
+            ((f_k1 - f_k) * left + f_k * right, f_k * left + f_k1 * right)
+        },
+        |state, row| {
+            row[0] = state.0;
+            row[1] = state.1;
+            *state = (state.1, state.0 + state.1);
+        },
+    )
+}
 
-    for i in 1..n {
-        rows[i].left = rows[i - 1].right;
-        rows[i].right = rows[i - 1].left + rows[i - 1].right;
+/// The Fibonacci numbers `(F_k, F_{k+1})`, by fast doubling.
+fn fibonacci_pair<F: PrimeField64>(k: usize) -> (F, F) {
+    let (mut a, mut b) = (F::ZERO, F::ONE);
+    for bit in (0..usize::BITS - k.leading_zeros()).rev() {
+        let c = a * (b.double() - a);
+        let d = a.square() + b.square();
+        (a, b) = if (k >> bit) & 1 == 1 {
+            (d, c + d)
+        } else {
+            (c, d)
+        };
     }
-
-    trace
+    (a, b)
 }
 
 const NUM_FIBONACCI_COLS: usize = 2;
@@ -81,12 +132,6 @@ pub struct FibonacciRow<F> {
     pub right: F,
 }
 
-impl<F> FibonacciRow<F> {
-    const fn new(left: F, right: F) -> FibonacciRow<F> {
-        FibonacciRow { left, right }
-    }
-}
-
 impl<F> Borrow<FibonacciRow<F>> for [F] {
     fn borrow(&self) -> &FibonacciRow<F> {
         debug_assert_eq!(self.len(), NUM_FIBONACCI_COLS);
@@ -129,11 +174,77 @@ fn test_public_value_impl(n: usize, x: u64) {
         BabyBear::from_canonical_u64(1),
         BabyBear::from_canonical_u64(x),
     ];
-    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis).unwrap();
     let mut challenger = Challenger::new(perm);
     verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
 }
 
+/// The same AIR over Mersenne31, whose two-adicity is too small for the two-adic PCS, with the
+/// circle PCS instead.
+#[test]
+fn test_public_value_circle_pcs() {
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Val, 3>;
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+    let byte_hash = ByteHash {};
+    let val_mmcs = ValMmcs::new(FieldHash::new(byte_hash), MyCompress::new(byte_hash));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let pcs = Pcs {
+        mmcs: val_mmcs,
+        fri_config: create_test_fri_config(challenge_mmcs),
+        _phantom: PhantomData,
+    };
+    let config = MyConfig::new(pcs);
+
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = vec![
+        Mersenne31::from_canonical_u64(0),
+        Mersenne31::from_canonical_u64(1),
+        Mersenne31::from_canonical_u64(21),
+    ];
+    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis).unwrap();
+    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+    verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
+
+    let wrong_pis = vec![pis[0], pis[1], Mersenne31::from_canonical_u64(22)];
+    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+    assert!(verify(
+        &config,
+        &FibonacciAir {},
+        &mut challenger,
+        &proof,
+        &wrong_pis
+    )
+    .is_err());
+}
+
+#[test]
+fn test_parallel_trace_matches_serial() {
+    for log_n in [0, 3, 10] {
+        let n = 1 << log_n;
+        let serial = generate_trace_scan(
+            n,
+            NUM_FIBONACCI_COLS,
+            (BabyBear::ZERO, BabyBear::ONE),
+            |state, row| {
+                row[0] = state.0;
+                row[1] = state.1;
+                *state = (state.1, state.0 + state.1);
+            },
+        );
+        assert_eq!(generate_trace_rows::<BabyBear>(0, 1, n), serial);
+    }
+}
+
 #[test]
 fn test_one_row_trace() {
     test_public_value_impl(1, 1);
@@ -144,6 +255,657 @@ fn test_public_value() {
     test_public_value_impl(1 << 3, 21);
 }
 
+#[test]
+fn test_serialized_proof_verifies() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = vec![
+        BabyBear::from_canonical_u64(0),
+        BabyBear::from_canonical_u64(1),
+        BabyBear::from_canonical_u64(21),
+    ];
+    let proof = prove(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm.clone()),
+        trace,
+        &pis,
+    )
+    .unwrap();
+
+    let bytes = bincode::serialize(&proof).expect("unable to serialize proof");
+    let from_bincode: Proof<MyConfig> =
+        bincode::deserialize(&bytes).expect("unable to deserialize proof");
+    verify(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm.clone()),
+        &from_bincode,
+        &pis,
+    )
+    .expect("verification failed");
+    assert_eq!(bincode::serialize(&from_bincode).unwrap(), bytes);
+
+    let json = serde_json::to_string(&proof).expect("unable to serialize proof");
+    let from_json: Proof<MyConfig> =
+        serde_json::from_str(&json).expect("unable to deserialize proof");
+    verify(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm),
+        &from_json,
+        &pis,
+    )
+    .expect("verification failed");
+    assert_eq!(serde_json::to_string(&from_json).unwrap(), json);
+
+    // An opened value which is not a canonical field element is rejected rather than reduced.
+    let mut tampered: serde_json::Value = serde_json::from_str(&json).unwrap();
+    tampered["opened_values"]["trace_local"][0]["value"][0] = BabyBear::ORDER_U64.into();
+    assert!(serde_json::from_value::<Proof<MyConfig>>(tampered).is_err());
+}
+
+#[test]
+fn test_proof_bytes_round_trip() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = vec![
+        BabyBear::from_canonical_u64(0),
+        BabyBear::from_canonical_u64(1),
+        BabyBear::from_canonical_u64(21),
+    ];
+    let proof = prove(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm.clone()),
+        trace,
+        &pis,
+    )
+    .unwrap();
+
+    let bytes = proof.to_bytes();
+    let decoded = Proof::<MyConfig>::from_bytes(&bytes).expect("unable to decode proof");
+    assert_eq!(decoded.to_bytes(), bytes);
+    verify(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm),
+        &decoded,
+        &pis,
+    )
+    .expect("verification failed");
+}
+
+#[test]
+fn test_wrong_public_value_is_rejected() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis).unwrap();
+
+    // The verifier claims a different 8th Fibonacci number than the prover proved.
+    let wrong_pis = [0, 1, 22].map(BabyBear::from_canonical_u64).to_vec();
+    let mut challenger = Challenger::new(perm);
+    assert!(verify(
+        &config,
+        &FibonacciAir {},
+        &mut challenger,
+        &proof,
+        &wrong_pis
+    )
+    .is_err());
+}
+
+fn test_config(perm: &Perm) -> MyConfig {
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+#[test]
+fn test_zk_proof_verifies() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = test_config(&perm).with_zk(true);
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+
+    let proofs = [(); 2].map(|_| {
+        let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+        let mut challenger = Challenger::new(perm.clone());
+        let proof = prove_zk(
+            &config,
+            &FibonacciAir {},
+            &mut challenger,
+            trace,
+            &pis,
+            &mut thread_rng(),
+        )
+        .unwrap();
+        let mut challenger = Challenger::new(perm.clone());
+        verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis)
+            .expect("verification failed");
+        proof
+    });
+
+    // The same trace is blinded differently each time it's proven.
+    assert_ne!(proofs[0].trace_commitment(), proofs[1].trace_commitment());
+}
+
+#[test]
+fn test_proofs_are_reproducible_from_seed() {
+    let perm = Perm::new_from_rng_128(&mut StdRng::seed_from_u64(0));
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+
+    let prove_with_seed = |seed: Option<u64>| {
+        let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+        let mut challenger = Challenger::new(perm.clone());
+        let proof = match seed {
+            Some(seed) => prove_zk(
+                &test_config(&perm).with_zk(true),
+                &FibonacciAir {},
+                &mut challenger,
+                trace,
+                &pis,
+                &mut StdRng::seed_from_u64(seed),
+            )
+            .unwrap(),
+            None => prove(
+                &test_config(&perm),
+                &FibonacciAir {},
+                &mut challenger,
+                trace,
+                &pis,
+            )
+            .unwrap(),
+        };
+        proof.to_bytes()
+    };
+
+    // Without zero knowledge, proving is deterministic.
+    assert_eq!(prove_with_seed(None), prove_with_seed(None));
+    // With it, the blinding is the only randomness, so the seed determines the proof.
+    assert_eq!(prove_with_seed(Some(1)), prove_with_seed(Some(1)));
+    assert_ne!(prove_with_seed(Some(1)), prove_with_seed(Some(2)));
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| (b as char).to_digit(16).unwrap() as u8)
+        .collect();
+    digits.chunks_exact(2).map(|d| (d[0] << 4) | d[1]).collect()
+}
+
+/// The proof, with or without zero knowledge asked for, is the one which
+/// `tests/golden/fibonacci_keccak.py` computes independently. Keccak keeps it free of the
+/// randomness of the Poseidon2 constants.
+#[test]
+fn test_proof_matches_golden_file() {
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+    let byte_hash = ByteHash {};
+    let config = || {
+        let val_mmcs = ValMmcs::new(FieldHash::new(byte_hash), MyCompress::new(byte_hash));
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = create_test_fri_config(challenge_mmcs);
+        MyConfig::new(Pcs::new(Dft::default(), val_mmcs, fri_config))
+    };
+    let golden = decode_hex(include_str!("golden/fibonacci_keccak.hex"));
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+
+    for config in [config(), config().with_zk(false)] {
+        let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+        let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+        let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis).unwrap();
+        assert_eq!(proof.to_bytes(), golden);
+
+        let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+        verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis)
+            .expect("verification failed");
+    }
+}
+
+#[test]
+fn test_security_of_configs() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_benchmark_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs).with_security_threshold(100);
+
+    // Five constraints of degree 2.
+    let air = AirParameters::from_constraints(&get_symbolic_constraints_with_external::<Val, _>(
+        &FibonacciAir {},
+        0,
+        0,
+        3,
+    ));
+    assert_eq!(
+        air,
+        AirParameters {
+            num_constraints: 5,
+            constraint_degree: 2,
+        }
+    );
+
+    // Blowup 2 with 100 queries and 16 bits of proof of work, in a degree 4 extension.
+    assert_eq!(
+        config.security_bits(air, 10),
+        Some(SecurityReport {
+            conjectured_bits: 107,
+            proven_bits: 57,
+        })
+    );
+    assert!(check_security(&config, air, 10).is_ok());
+
+    // A larger trace leaves less of the field for the out-of-domain point.
+    let err = check_security(&config, air, 20).unwrap_err();
+    assert_eq!(err.report.conjectured_bits, 97);
+    assert_eq!(err.threshold_bits, 100);
+
+    // Without a threshold, anything goes.
+    assert!(check_security(&test_config(&perm), air, 20).is_ok());
+    let config = test_config(&perm).with_security_threshold(100);
+    assert_eq!(
+        check_security(&config, air, 3).unwrap_err().report,
+        SecurityReport {
+            conjectured_bits: 3,
+            proven_bits: 1,
+        }
+    );
+}
+
+#[test]
+fn test_insecure_configs_fail_only_when_strict() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = test_config(&perm).with_security_threshold(100);
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+    let result = prove(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm),
+        generate_trace_rows::<Val>(0, 1, 1 << 3),
+        &pis,
+    );
+    if cfg!(feature = "strict-security") {
+        let Err(ProverError::InsufficientSecurity(err)) = result else {
+            panic!("an insecure config was proven with");
+        };
+        assert_eq!(err.threshold_bits, 100);
+    } else {
+        assert!(result.is_ok());
+    }
+}
+
+#[test]
+fn test_resume_from_every_checkpoint() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = test_config(&perm);
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+    let trace = || generate_trace_rows::<Val>(0, 1, 1 << 3);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace(), &pis)
+        .unwrap()
+        .to_bytes();
+    let challenge: Val = challenger.sample();
+
+    let mut checkpoints = vec![];
+    let mut challenger = Challenger::new(perm.clone());
+    let checkpointed = prove_with_checkpoints(
+        &config,
+        &FibonacciAir {},
+        &mut challenger,
+        trace(),
+        &pis,
+        |checkpoint| checkpoints.push(postcard::to_allocvec(&checkpoint).unwrap()),
+    )
+    .unwrap();
+    assert_eq!(checkpointed.to_bytes(), proof);
+    // The trace and quotient commitments, then each of FRI's commit phase rounds.
+    assert!(checkpoints.len() > 2, "FRI's rounds weren't checkpointed");
+
+    for bytes in &checkpoints {
+        // The challenger to resume with is fresh, as on a machine which didn't start the proof.
+        let mut challenger = Challenger::new(perm.clone());
+        let resumed = prove_resume(
+            &config,
+            &FibonacciAir {},
+            &mut challenger,
+            trace(),
+            &pis,
+            postcard::from_bytes(bytes).unwrap(),
+            |_| {},
+        )
+        .expect("resuming failed");
+        assert_eq!(resumed.to_bytes(), proof);
+        let resumed_challenge: Val = challenger.sample();
+        assert_eq!(resumed_challenge, challenge);
+    }
+}
+
+#[test]
+fn test_resume_rejects_other_trace() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = test_config(&perm);
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+
+    let mut checkpoints = vec![];
+    prove_with_checkpoints(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm.clone()),
+        generate_trace_rows::<Val>(0, 1, 1 << 3),
+        &pis,
+        |checkpoint| checkpoints.push(checkpoint),
+    )
+    .unwrap();
+
+    // A trace of the same AIR and public values, but for another length.
+    let other_pis = [0, 1, 987].map(BabyBear::from_canonical_u64).to_vec();
+    let result = prove_resume(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm),
+        generate_trace_rows::<Val>(0, 1, 1 << 4),
+        &other_pis,
+        checkpoints.remove(0),
+        |_| {},
+    );
+    assert!(matches!(
+        result,
+        Err(ProverError::Checkpoint(
+            CheckpointError::TraceCommitmentMismatch
+        ))
+    ));
+}
+
+/// Verify `proof` after `corrupt` has edited its JSON serialization.
+fn verify_edited(
+    perm: &Perm,
+    config: &MyConfig,
+    proof: &Proof<MyConfig>,
+    pis: &Vec<Val>,
+    corrupt: impl FnOnce(&mut serde_json::Value),
+) -> Result<(), VerificationError<PcsError<MyConfig>>> {
+    let mut json = serde_json::to_value(proof).unwrap();
+    corrupt(&mut json);
+    let corrupted: Proof<MyConfig> = serde_json::from_value(json).unwrap();
+    let mut challenger = Challenger::new(perm.clone());
+    verify(config, &FibonacciAir {}, &mut challenger, &corrupted, pis)
+}
+
+/// Increment the base field element `value`, keeping it canonical.
+fn increment(value: &mut serde_json::Value) {
+    *value = ((value.as_u64().unwrap() + 1) % BabyBear::ORDER_U64).into();
+}
+
+#[test]
+fn test_corrupted_proofs_are_rejected() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = test_config(&perm);
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let proof = prove(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm.clone()),
+        trace,
+        &pis,
+    )
+    .unwrap();
+    let verify_corrupted =
+        |corrupt: fn(&mut serde_json::Value)| verify_edited(&perm, &config, &proof, &pis, corrupt);
+
+    // Traces too tall for the PCS are rejected before any domain is built.
+    assert!(matches!(
+        verify_corrupted(|proof| proof["degree_bits"] = 40.into()),
+        Err(VerificationError::DegreeOutOfRange)
+    ));
+    assert!(matches!(
+        verify_corrupted(|proof| proof["degree_bits"] = usize::MAX.into()),
+        Err(VerificationError::DegreeOutOfRange)
+    ));
+    assert!(matches!(
+        verify_corrupted(|proof| {
+            proof["opened_values"]["trace_local"]
+                .as_array_mut()
+                .unwrap()
+                .pop();
+        }),
+        Err(VerificationError::InvalidProofShape)
+    ));
+
+    // Queries opening too few rounds, or rows of the wrong width.
+    assert!(matches!(
+        verify_corrupted(|proof| {
+            proof["opening_proof"]["query_proofs"][0]["input_proof"]
+                .as_array_mut()
+                .unwrap()
+                .pop();
+        }),
+        Err(VerificationError::InvalidOpeningArgument(
+            FriError::InvalidProofShape
+        ))
+    ));
+    assert!(matches!(
+        verify_corrupted(|proof| {
+            proof["opening_proof"]["query_proofs"][0]["input_proof"][0]["opened_values"][0]
+                .as_array_mut()
+                .unwrap()
+                .pop();
+        }),
+        Err(VerificationError::InvalidOpeningArgument(
+            FriError::InvalidProofShape
+        ))
+    ));
+    assert!(matches!(
+        verify_corrupted(|proof| {
+            proof["opening_proof"]["final_poly"]
+                .as_array_mut()
+                .unwrap()
+                .pop();
+        }),
+        Err(VerificationError::InvalidOpeningArgument(
+            FriError::InvalidProofShape
+        ))
+    ));
+
+    // Opened rows which don't match their commitments.
+    assert!(matches!(
+        verify_corrupted(|proof| increment(
+            &mut proof["opening_proof"]["query_proofs"][0]["input_proof"][0]["opened_values"][0][0]
+        )),
+        Err(VerificationError::InvalidOpeningArgument(
+            FriError::InputError(MerkleTreeError::RootMismatch)
+        ))
+    ));
+    assert!(matches!(
+        verify_corrupted(|proof| increment(
+            &mut proof["opening_proof"]["query_proofs"][0]["commit_phase_openings"][0]
+                ["sibling_values"][0]["value"][0]
+        )),
+        Err(VerificationError::InvalidOpeningArgument(
+            FriError::CommitPhaseMmcsError(MerkleTreeError::RootMismatch)
+        ))
+    ));
+
+    // Openings which are all correct, but of a trace which doesn't satisfy the verifier's AIR.
+    let mut challenger = Challenger::new(perm.clone());
+    assert!(matches!(
+        verify(
+            &config,
+            &ZeroRightFibonacciAir,
+            &mut challenger,
+            &proof,
+            &pis
+        ),
+        Err(VerificationError::OodEvaluationMismatch)
+    ));
+}
+
+#[test]
+fn test_transcript_recorder_finds_divergence() {
+    type RecordingConfig = StarkConfig<Pcs, Challenge, TranscriptRecorder<Challenger>>;
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = RecordingConfig::new(pcs);
+
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let mut prover_challenger = TranscriptRecorder::new(Challenger::new(perm.clone()));
+    let proof = prove(
+        &config,
+        &FibonacciAir {},
+        &mut prover_challenger,
+        trace,
+        &pis,
+    )
+    .unwrap();
+
+    let mut verifier_challenger = TranscriptRecorder::new(Challenger::new(perm.clone()));
+    verify(
+        &config,
+        &FibonacciAir {},
+        &mut verifier_challenger,
+        &proof,
+        &pis,
+    )
+    .expect("verification failed");
+    assert_eq!(
+        diff_transcripts(prover_challenger.entries(), verifier_challenger.entries()),
+        None
+    );
+
+    // Opened values aren't observed, so a wrong one is only caught when the first query's
+    // reduced opening doesn't match, and the verifier's transcript ends there.
+    let mut json = serde_json::to_value(&proof).unwrap();
+    increment(&mut json["opened_values"]["trace_local"][0]["value"][0]);
+    let corrupted: Proof<RecordingConfig> = serde_json::from_value(json).unwrap();
+    let mut verifier_challenger = TranscriptRecorder::new(Challenger::new(perm.clone()));
+    assert!(verify(
+        &config,
+        &FibonacciAir {},
+        &mut verifier_challenger,
+        &corrupted,
+        &pis
+    )
+    .is_err());
+    let divergence =
+        diff_transcripts(prover_challenger.entries(), verifier_challenger.entries()).unwrap();
+    assert_eq!(divergence.verifier, None);
+    assert_eq!(divergence.phase(), "fri query phase");
+
+    // A proof declaring a taller trace than was committed to diverges at the very first
+    // observation, of `degree_bits`, instead of only failing when the query paths don't fit the
+    // commitment.
+    let mut json = serde_json::to_value(&proof).unwrap();
+    json["degree_bits"] = 4.into();
+    let corrupted: Proof<RecordingConfig> = serde_json::from_value(json).unwrap();
+    let mut verifier_challenger = TranscriptRecorder::new(Challenger::new(perm));
+    assert!(verify(
+        &config,
+        &FibonacciAir {},
+        &mut verifier_challenger,
+        &corrupted,
+        &pis
+    )
+    .is_err());
+    let divergence =
+        diff_transcripts(prover_challenger.entries(), verifier_challenger.entries()).unwrap();
+    let observed_degree_bits = |degree_bits: usize| {
+        Some(TranscriptEntry {
+            phase: "instance",
+            event: TranscriptEvent::Observe(format!(
+                "{:?}",
+                Val::from_canonical_usize(degree_bits)
+            )),
+        })
+    };
+    assert_eq!(divergence.index, 0);
+    assert_eq!(divergence.prover, observed_degree_bits(3));
+    assert_eq!(divergence.verifier, observed_degree_bits(4));
+}
+
+#[test]
+fn test_random_bytes_are_rejected_without_panicking() {
+    let mut rng = thread_rng();
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let config = test_config(&perm);
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let bytes = prove(
+        &config,
+        &FibonacciAir {},
+        &mut Challenger::new(perm.clone()),
+        trace,
+        &pis,
+    )
+    .unwrap()
+    .to_bytes();
+    let decode_and_verify = |bytes: &[u8]| {
+        if let Ok(proof) = Proof::<MyConfig>::from_bytes(bytes) {
+            let mut challenger = Challenger::new(perm.clone());
+            // Whether the proof verifies depends on which bytes were changed, e.g. a proof of work
+            // witness may be replaced by another valid one; only the lack of a panic matters here.
+            let _ = verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis);
+        }
+    };
+
+    // A few bytes of a valid proof changed, which usually still decodes.
+    for _ in 0..100 {
+        let mut mutated = bytes.clone();
+        for _ in 0..rng.gen_range(1..4) {
+            let i = rng.gen_range(0..mutated.len());
+            mutated[i] = rng.gen();
+        }
+        decode_and_verify(&mutated);
+    }
+
+    // Random bytes after the version, so that decoding gets past it.
+    for _ in 0..100 {
+        let len = rng.gen_range(0..2 * bytes.len());
+        let random = bytes[..4]
+            .iter()
+            .copied()
+            .chain((0..len).map(|_| rng.gen::<u8>()))
+            .collect::<Vec<_>>();
+        decode_and_verify(&random);
+    }
+}
+
 #[cfg(debug_assertions)]
 #[test]
 #[should_panic(expected = "assertion `left == right` failed: constraints had nonzero value")]
@@ -164,5 +926,132 @@ fn test_incorrect_public_value() {
         BabyBear::from_canonical_u64(1),
         BabyBear::from_canonical_u64(123_123), // incorrect result
     ];
-    prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+    prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis).unwrap();
+}
+
+#[cfg(feature = "parallel")]
+mod parallelism {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use p3_maybe_rayon::{ParallelismConfig, ThreadPool, ThreadPoolBuilder};
+
+    use super::*;
+
+    /// `FibonacciAir`, recording the size of the thread pool its constraints are evaluated in.
+    #[derive(Default)]
+    struct PoolRecordingAir {
+        pool_threads: AtomicUsize,
+    }
+
+    impl<F> BaseAir<F> for PoolRecordingAir {
+        fn width(&self) -> usize {
+            NUM_FIBONACCI_COLS
+        }
+    }
+
+    impl<AB: AirBuilderWithPublicValues> Air<AB> for PoolRecordingAir {
+        fn eval(&self, builder: &mut AB) {
+            // Only the quotient evaluation runs on a pool's threads.
+            if rayon::current_thread_index().is_some() {
+                self.pool_threads
+                    .fetch_max(rayon::current_num_threads(), Ordering::SeqCst);
+            }
+            FibonacciAir {}.eval(builder);
+        }
+    }
+
+    fn counting_pool(num_threads: usize, spawned: Arc<AtomicUsize>) -> Arc<ThreadPool> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .spawn_handler(move |thread| {
+                spawned.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || thread.run());
+                Ok(())
+            })
+            .build()
+            .unwrap();
+        Arc::new(pool)
+    }
+
+    /// Prove and verify the 32nd Fibonacci number, returning the serialized proof.
+    fn prove_fib(perm: &Perm, parallelism: ParallelismConfig, air: &PoolRecordingAir) -> Vec<u8> {
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = create_test_fri_config(challenge_mmcs);
+        let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+        let config = MyConfig::new(pcs).with_parallelism(parallelism);
+
+        let trace = generate_trace_rows::<Val>(0, 1, 1 << 5);
+        let pis = vec![
+            BabyBear::from_canonical_u64(0),
+            BabyBear::from_canonical_u64(1),
+            BabyBear::from_canonical_u64(2_178_309),
+        ];
+        let proof = prove(
+            &config,
+            air,
+            &mut Challenger::new(perm.clone()),
+            trace,
+            &pis,
+        )
+        .unwrap();
+        verify(
+            &config,
+            air,
+            &mut Challenger::new(perm.clone()),
+            &proof,
+            &pis,
+        )
+        .expect("verification failed");
+        postcard::to_allocvec(&proof).unwrap()
+    }
+
+    #[test]
+    fn proofs_match_across_pool_configurations() {
+        let perm = Perm::new_from_rng_128(&mut thread_rng());
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let air = PoolRecordingAir::default();
+
+        let expected = prove_fib(&perm, ParallelismConfig::default(), &air);
+        for parallelism in [
+            ParallelismConfig::new().with_pool(counting_pool(1, spawned.clone())),
+            ParallelismConfig::new().with_pool(counting_pool(2, spawned.clone())),
+            ParallelismConfig::new().with_pool(counting_pool(4, spawned.clone())),
+            ParallelismConfig::new().with_min_parallel_len(usize::MAX),
+            ParallelismConfig::new()
+                .with_pool(counting_pool(2, spawned.clone()))
+                .with_min_parallel_len(1 << 6),
+        ] {
+            assert_eq!(prove_fib(&perm, parallelism, &air), expected);
+        }
+    }
+
+    #[test]
+    fn two_thread_pool_is_respected() {
+        let perm = Perm::new_from_rng_128(&mut thread_rng());
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let air = PoolRecordingAir::default();
+
+        let parallelism = ParallelismConfig::new().with_pool(counting_pool(2, spawned.clone()));
+        prove_fib(&perm, parallelism, &air);
+
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+        assert_eq!(air.pool_threads.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn small_phases_run_serially() {
+        let perm = Perm::new_from_rng_128(&mut thread_rng());
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let air = PoolRecordingAir::default();
+
+        // Every phase is below the threshold, so none of them are installed in the 2-thread pool.
+        let parallelism = ParallelismConfig::new()
+            .with_pool(counting_pool(2, spawned))
+            .with_min_parallel_len(usize::MAX);
+        prove_fib(&perm, parallelism, &air);
+
+        assert_eq!(air.pool_threads.load(Ordering::SeqCst), 1);
+    }
 }
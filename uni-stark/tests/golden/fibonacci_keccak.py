@@ -0,0 +1,390 @@
+"""Reference model of the proof which `test_proof_matches_golden_file` in `tests/fib_air.rs` makes.
+
+Writes `fibonacci_keccak.hex`, the proof's `Proof::to_bytes`, in the layout of
+`p3_util::encoding`. The proof is of `FibonacciAir` over 8 rows with public values `[0, 1, 21]`,
+with BabyBear and its degree 4 extension, two-adic FRI from `create_test_fri_config`, Merkle trees
+of `SerializingHasher32<Keccak256Hash>` and the challenger `SerializingChallenger32` over
+keccak256, so that it needs no randomness. It follows the prover step by step, but computes each polynomial
+from its definition rather than with DFTs, so it doesn't share the prover's shortcuts.
+
+Committed matrices are the evaluations of their polynomials over the coset `g H` of twice their
+height, for `g` the field's generator, in bit-reversed order. Merkle leaves hash field elements in
+Montgomery form, as `SerializingHasher32` does, while the challenger observes canonical values.
+"""
+
+import copy
+import os
+import sys
+
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), "../../../challenger/tests/golden"))
+from serializing_challenger32 import Transcript, keccak256  # noqa: E402
+
+VERSION = 6  # `PROOF_ENCODING_VERSION`
+P = 2**31 - 2**27 + 1  # BabyBear
+W = 11  # The extension is F[X] / (X^4 - W).
+GENERATOR = 31
+LOG_BLOWUP = 1
+NUM_QUERIES = 2
+PROOF_OF_WORK_BITS = 1
+
+LOG_N = 3
+PUBLIC_VALUES = [0, 1, 21]
+
+
+def two_adic_generator(bits):
+    return pow(0x1A427A41, 2 ** (27 - bits), P)
+
+
+def reverse_bits(i, bits):
+    return int(format(i, f"0{bits}b")[::-1], 2) if bits else 0
+
+
+# Extension field elements are tuples of their 4 coefficients, lowest first.
+
+
+def ext(x):
+    return (x % P, 0, 0, 0)
+
+
+def add(a, b):
+    return tuple((x + y) % P for x, y in zip(a, b))
+
+
+def sub(a, b):
+    return tuple((x - y) % P for x, y in zip(a, b))
+
+
+def mul(a, b):
+    prod = [0] * 7
+    for i, x in enumerate(a):
+        for j, y in enumerate(b):
+            prod[i + j] += x * y
+    return tuple((prod[i] + W * (prod[i + 4] if i < 3 else 0)) % P for i in range(4))
+
+
+def power(a, e):
+    result = ext(1)
+    while e:
+        if e & 1:
+            result = mul(result, a)
+        a = mul(a, a)
+        e >>= 1
+    return result
+
+
+def inv(a):
+    return power(a, P**4 - 2)
+
+
+def div(a, b):
+    return mul(a, inv(b))
+
+
+def poly_eval(coeffs, x):
+    result = ext(0)
+    for c in reversed(coeffs):
+        result = add(mul(result, x), c)
+    return result
+
+
+def interpolate(values, shift):
+    """The polynomial which is `values[i]` at `shift * w^i`, for `w` generating their subgroup, as
+    a function of an extension element."""
+    log_n = (len(values) - 1).bit_length()
+    w_inv = pow(two_adic_generator(log_n), P - 2, P)
+    n_inv = pow(len(values), P - 2, P)
+    coeffs = []
+    for k in range(len(values)):
+        c = ext(0)
+        for i, v in enumerate(values):
+            c = add(c, mul(v, ext(pow(w_inv, i * k, P))))
+        coeffs.append(mul(c, ext(n_inv)))
+    shift_inv = ext(pow(shift, P - 2, P))
+    return lambda x: poly_eval(coeffs, mul(x, shift_inv))
+
+
+def lde(polys, log_n):
+    """The bit-reversed LDE of the base field polynomials `polys`, as rows of canonical values."""
+    log_height = log_n + LOG_BLOWUP
+    w = two_adic_generator(log_height)
+    rows = []
+    for r in range(1 << log_height):
+        x = ext(GENERATOR * pow(w, reverse_bits(r, log_height), P))
+        row = [poly(x) for poly in polys]
+        assert all(v[1:] == (0, 0, 0) for v in row)
+        rows.append([v[0] for v in row])
+    return rows
+
+
+# Merkle trees
+
+
+def monty(x):
+    return x * 2**32 % P
+
+
+def leaf_digest(row):
+    return keccak256(b"".join(monty(v).to_bytes(4, "little") for v in row))
+
+
+class MerkleTree:
+    def __init__(self, rows):
+        self.rows = rows
+        self.layers = [[leaf_digest(row) for row in rows]]
+        while len(self.layers[-1]) > 1:
+            layer = self.layers[-1]
+            self.layers.append(
+                [keccak256(layer[i] + layer[i + 1]) for i in range(0, len(layer), 2)]
+            )
+
+    def root(self):
+        return self.layers[-1][0]
+
+    def open(self, index):
+        """The opened row and the sibling at each layer, from the leaves up."""
+        siblings = [layer[(index >> i) ^ 1] for i, layer in enumerate(self.layers[:-1])]
+        return self.rows[index], siblings
+
+
+def flatten(row):
+    return [c for value in row for c in value]
+
+
+# The transcript
+
+
+class Challenger(Transcript):
+    def observe_value(self, value):
+        self.observe_bytes(value.to_bytes(4, "little"))
+
+    def observe_ext(self, value):
+        for c in value:
+            self.observe_value(c)
+
+    def observe_shape(self, dims):
+        self.observe_value(len(dims))
+        for width, height in dims:
+            self.observe_value(width)
+            self.observe_value(height)
+
+    def sample_ext_element(self):
+        return tuple(self.sample_base() for _ in range(4))
+
+    def sample_bits(self, bits):
+        return self.sample_uint(4) & ((1 << bits) - 1)
+
+    def check_witness(self, bits, witness):
+        self.observe_value(witness)
+        return self.sample_bits(bits) == 0
+
+    def grind(self, bits):
+        witness = next(w for w in range(P) if copy.deepcopy(self).check_witness(bits, w))
+        assert self.check_witness(bits, witness)
+        return witness
+
+
+# Encoding
+#
+# Integers and field elements are little-endian, a `usize` as 4 bytes, a `Vec` as its length
+# followed by its elements and an `Option` as a one byte tag followed by its value.
+
+
+def u8(x):
+    return x.to_bytes(1, "little")
+
+
+def u32(x):
+    return x.to_bytes(4, "little")
+
+
+def vec(items):
+    return u32(len(items)) + b"".join(items)
+
+
+def option(item):
+    return u8(0) if item is None else u8(1) + item
+
+
+def encode_val(x):
+    return u32(x)
+
+
+def encode_ext(x):
+    return b"".join(encode_val(c) for c in x)
+
+
+def batch_opening(tree, index):
+    row, siblings = tree.open(index)
+    return vec([vec([encode_val(v) for v in row])]) + vec(siblings)
+
+
+def prove():
+    n = 1 << LOG_N
+    w = two_adic_generator(LOG_N)
+    w_inv = pow(w, P - 2, P)
+    left, right = [0], [1]
+    for _ in range(n - 1):
+        left, right = left + [right[-1]], right + [left[-1] + right[-1]]
+    assert right[-1] == PUBLIC_VALUES[2]
+
+    challenger = Challenger(b"")
+
+    # The trace, over the subgroup of its height.
+    trace_polys = [interpolate([ext(v) for v in column], 1) for column in (left, right)]
+    trace_tree = MerkleTree(lde(trace_polys, LOG_N))
+    challenger.observe_value(LOG_N)
+    challenger.observe_shape([(2, n)])
+    challenger.observe_bytes(trace_tree.root())
+    for value in PUBLIC_VALUES:
+        challenger.observe_value(value)
+
+    # The constraints have degree 2, so the quotient has a single chunk, over the coset
+    # `GENERATOR H`.
+    alpha = challenger.sample_ext_element()
+
+    def quotient(x):
+        trace_local = [poly(x) for poly in trace_polys]
+        trace_next = [poly(mul(x, ext(w))) for poly in trace_polys]
+        (l, r), (next_l, next_r) = trace_local, trace_next
+        a, b, fib = (ext(v) for v in PUBLIC_VALUES)
+        z_h = sub(power(x, n), ext(1))
+        is_first_row = div(z_h, sub(x, ext(1)))
+        is_last_row = div(z_h, sub(x, ext(w_inv)))
+        is_transition = sub(x, ext(w_inv))
+        constraints = [
+            mul(is_first_row, sub(l, a)),
+            mul(is_first_row, sub(r, b)),
+            mul(is_transition, sub(r, next_l)),
+            mul(is_transition, sub(add(l, r), next_r)),
+            mul(is_last_row, sub(r, fib)),
+        ]
+        # The first constraint is weighted by the highest power of alpha.
+        accumulator = ext(0)
+        for constraint in constraints:
+            accumulator = add(mul(accumulator, alpha), constraint)
+        return div(accumulator, z_h)
+
+    quotient_domain = [ext(GENERATOR * pow(w, i, P)) for i in range(n)]
+    quotient_values = [quotient(x) for x in quotient_domain]
+    # Committed flattened to its base field coefficients.
+    quotient_polys = [
+        interpolate([ext(q[i]) for q in quotient_values], GENERATOR) for i in range(4)
+    ]
+    quotient_tree = MerkleTree(lde(quotient_polys, LOG_N))
+    challenger.observe_shape([(4, n)])
+    challenger.observe_bytes(quotient_tree.root())
+
+    # The quotient is a polynomial, so its definition agrees with its interpolation off the
+    # quotient domain.
+    x = ext(GENERATOR * pow(two_adic_generator(LOG_N + 1), 3, P))
+    assert quotient(x) == tuple(poly(x)[0] for poly in quotient_polys)
+
+    zeta = challenger.sample_ext_element()
+    zeta_next = mul(zeta, ext(w))
+    trace_local = [poly(zeta) for poly in trace_polys]
+    trace_next = [poly(zeta_next) for poly in trace_polys]
+    quotient_at_zeta = [poly(zeta) for poly in quotient_polys]
+
+    # The verifier's check of the constraints at zeta, against the quotient recomposed from its
+    # coefficients.
+    recomposed = ext(0)
+    for i, c in enumerate(quotient_at_zeta):
+        recomposed = add(recomposed, mul(c, tuple(int(j == i) for j in range(4))))
+    assert recomposed == quotient(zeta)
+
+    # Reduce the openings into a single FRI input: column `i` of the trace at its `j`th point is
+    # weighted by `alpha^(2j + i)`, and column `i` of the quotient by `alpha^(4 + i)`.
+    fri_alpha = challenger.sample_ext_element()
+    log_height = LOG_N + LOG_BLOWUP
+    openings = [
+        (trace_polys, [zeta, zeta_next]),
+        (quotient_polys, [zeta]),
+    ]
+    lde_w = two_adic_generator(log_height)
+    reduced = []
+    for r in range(1 << log_height):
+        x = ext(GENERATOR * pow(lde_w, reverse_bits(r, log_height), P))
+        ro = ext(0)
+        weight = ext(1)
+        for polys, points in openings:
+            for z in points:
+                for poly in polys:
+                    term = div(sub(poly(x), poly(z)), sub(x, z))
+                    ro = add(ro, mul(weight, term))
+                    weight = mul(weight, fri_alpha)
+        reduced.append(ro)
+
+    # The commit phase, folding in half each round down to the blown up constant.
+    folded = reduced
+    commit_trees = []
+    while len(folded) > 1 << LOG_BLOWUP:
+        rows = [folded[2 * i : 2 * i + 2] for i in range(len(folded) // 2)]
+        tree = MerkleTree([flatten(row) for row in rows])
+        commit_trees.append((tree, rows))
+        challenger.observe_bytes(tree.root())
+        beta = challenger.sample_ext_element()
+
+        log_rows = (len(rows) - 1).bit_length()
+        g_inv = pow(two_adic_generator(log_rows + 1), P - 2, P)
+        half = ext(pow(2, P - 2, P))
+        next_folded = []
+        for i, (lo, hi) in enumerate(rows):
+            twiddle = ext(pow(g_inv, reverse_bits(i, log_rows), P))
+            diff = mul(sub(lo, hi), twiddle)
+            next_folded.append(mul(add(add(lo, hi), mul(beta, diff)), half))
+        folded = next_folded
+    assert all(v == folded[0] for v in folded)
+    final_poly = [folded[0]]
+    for c in final_poly:
+        challenger.observe_ext(c)
+
+    pow_witness = challenger.grind(PROOF_OF_WORK_BITS)
+
+    query_proofs = []
+    for _ in range(NUM_QUERIES):
+        index = challenger.sample_bits(log_height)
+        input_proof = vec(
+            [batch_opening(trace_tree, index), batch_opening(quotient_tree, index)]
+        )
+        steps = []
+        for tree, rows in commit_trees:
+            index_in_row = index % 2
+            index >>= 1
+            _, siblings = tree.open(index)
+            sibling_value = rows[index][1 - index_in_row]
+            steps.append(vec([encode_ext(sibling_value)]) + vec(siblings))
+        query_proofs.append(input_proof + vec(steps))
+
+    commitments = trace_tree.root() + option(None) + quotient_tree.root()
+    opened_values = (
+        vec([encode_ext(v) for v in trace_local])
+        + vec([encode_ext(v) for v in trace_next])
+        + vec([vec([encode_ext(v) for v in quotient_at_zeta])])
+        + option(None)
+        + option(None)
+        + option(None)
+    )
+    num_rounds = len(commit_trees)
+    fri_proof = (
+        vec([tree.root() for tree, _ in commit_trees])
+        + vec([u8(0)] * num_rounds)
+        + vec([u32(1)] * num_rounds)
+        + vec(query_proofs)
+        + vec([encode_ext(c) for c in final_poly])
+        + encode_val(pow_witness)
+    )
+    return u32(VERSION) + commitments + opened_values + fri_proof + u32(LOG_N)
+
+
+def main():
+    assert two_adic_generator(3) == 0x5EE99486 and pow(GENERATOR, (P - 1) // 2, P) == P - 1
+
+    hex_digits = prove().hex()
+    lines = [hex_digits[i : i + 64] for i in range(0, len(hex_digits), 64)]
+    path = os.path.join(os.path.dirname(__file__), "fibonacci_keccak.hex")
+    with open(path, "w") as f:
+        f.write("\n".join(lines) + "\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,131 @@
+use p3_air::{Air, BaseAir, LookupBuilder, PairBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove_with_key, setup, verify_with_key, PcsError, ProofSection, StarkConfig, VerificationError,
+};
+use rand::{thread_rng, Rng};
+
+/// The number of 8-bit values, which is also the height of the trace.
+const NUM_BYTES: usize = 256;
+
+/// Range checks a column of values to 8 bits, by looking them up in a preprocessed table of every
+/// byte. The second column counts how many times the byte on the same row of the table is looked
+/// up.
+struct RangeCheckAir;
+
+impl<F: Field> BaseAir<F> for RangeCheckAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            (0..NUM_BYTES).map(F::from_canonical_usize).collect(),
+        ))
+    }
+}
+
+impl<AB: PairBuilder + LookupBuilder> Air<AB> for RangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let byte = builder.preprocessed().row_slice(0)[0];
+
+        builder.lookup(local[0], byte, local[1]);
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn config(perm: &Perm) -> MyConfig {
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+/// A trace range checking `values`, counting only those which are in range.
+fn trace(values: &[usize]) -> RowMajorMatrix<Val> {
+    let mut multiplicities = [0; NUM_BYTES];
+    for &value in values.iter().filter(|&&value| value < NUM_BYTES) {
+        multiplicities[value] += 1;
+    }
+    let rows = values
+        .iter()
+        .zip(multiplicities)
+        .flat_map(|(&value, multiplicity)| [value, multiplicity])
+        .map(Val::from_canonical_usize)
+        .collect();
+    RowMajorMatrix::new(rows, 2)
+}
+
+fn prove_and_verify(values: &[usize]) -> Result<(), VerificationError<PcsError<MyConfig>>> {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+    let (proving_key, verifying_key) = setup(&config, &RangeCheckAir);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_key(
+        &config,
+        &proving_key,
+        &RangeCheckAir,
+        &mut challenger,
+        trace(values),
+        &vec![],
+    )
+    .unwrap();
+    assert!(proof.has_section(ProofSection::Lookup));
+
+    let mut challenger = Challenger::new(perm);
+    verify_with_key(
+        &config,
+        &verifying_key,
+        &RangeCheckAir,
+        &mut challenger,
+        &proof,
+        &vec![],
+    )
+}
+
+#[test]
+fn test_bytes_pass_range_check() {
+    let mut rng = thread_rng();
+    let values: Vec<usize> = (0..NUM_BYTES)
+        .map(|_| rng.gen_range(0..NUM_BYTES))
+        .collect();
+    prove_and_verify(&values).expect("verification failed");
+
+    // Every row looking up the same byte.
+    prove_and_verify(&[NUM_BYTES - 1; NUM_BYTES]).expect("verification failed");
+}
+
+#[test]
+fn test_out_of_range_value_is_rejected() {
+    let mut values: Vec<usize> = (0..NUM_BYTES).collect();
+    values[17] = NUM_BYTES;
+    assert!(prove_and_verify(&values).is_err());
+}
@@ -20,7 +20,10 @@ use p3_mersenne_31::Mersenne31;
 use p3_symmetric::{
     CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32, TruncatedPermutation,
 };
-use p3_uni_stark::{prove, verify, StarkConfig, StarkGenericConfig, Val};
+use p3_uni_stark::{
+    get_log_quotient_degree_with_external, prove, verify, PcsError, StarkConfig,
+    StarkGenericConfig, Val, VerificationError,
+};
 use rand::distributions::{Distribution, Standard};
 use rand::{thread_rng, Rng};
 
@@ -118,7 +121,22 @@ fn do_test<SC: StarkGenericConfig>(
     air: MulAir,
     log_height: usize,
     challenger: SC::Challenger,
-) -> Result<(), impl Debug>
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC::Challenger: Clone,
+    Standard: Distribution<Val<SC>>,
+{
+    do_test_with_verifier_air(config, &air, &air, log_height, challenger)
+}
+
+/// Prove `air`, then verify the proof against `verifier_air`.
+fn do_test_with_verifier_air<SC: StarkGenericConfig>(
+    config: SC,
+    air: &MulAir,
+    verifier_air: &MulAir,
+    log_height: usize,
+    challenger: SC::Challenger,
+) -> Result<(), VerificationError<PcsError<SC>>>
 where
     SC::Challenger: Clone,
     Standard: Distribution<Val<SC>>,
@@ -126,7 +144,7 @@ where
     let trace = air.random_valid_trace(log_height, true);
 
     let mut p_challenger = challenger.clone();
-    let proof = prove(&config, &air, &mut p_challenger, trace, &vec![]);
+    let proof = prove(&config, air, &mut p_challenger, trace, &vec![]).unwrap();
 
     let serialized_proof = postcard::to_allocvec(&proof).expect("unable to serialize proof");
     tracing::debug!("serialized_proof len: {} bytes", serialized_proof.len());
@@ -137,7 +155,7 @@ where
     let mut v_challenger = challenger.clone();
     verify(
         &config,
-        &air,
+        verifier_air,
         &mut v_challenger,
         &deserialized_proof,
         &vec![],
@@ -145,6 +163,14 @@ where
 }
 
 fn do_test_bb_trivial(degree: u64, log_n: usize) -> Result<(), impl Debug> {
+    do_test_bb_trivial_with_verifier_degree(degree, degree, log_n)
+}
+
+fn do_test_bb_trivial_with_verifier_degree(
+    degree: u64,
+    verifier_degree: u64,
+    log_n: usize,
+) -> Result<(), VerificationError<impl Debug>> {
     type Val = BabyBear;
     type Challenge = BinomialExtensionField<Val, 4>;
 
@@ -170,8 +196,18 @@ fn do_test_bb_trivial(degree: u64, log_n: usize) -> Result<(), impl Debug> {
         degree,
         ..Default::default()
     };
+    let verifier_air = MulAir {
+        degree: verifier_degree,
+        ..Default::default()
+    };
 
-    do_test(config, air, 1 << log_n, Challenger::new(perm))
+    do_test_with_verifier_air(
+        config,
+        &air,
+        &verifier_air,
+        1 << log_n,
+        Challenger::new(perm),
+    )
 }
 
 #[test]
@@ -189,6 +225,13 @@ fn prove_bb_trivial_deg4() -> Result<(), impl Debug> {
     do_test_bb_trivial(4, 8)
 }
 
+#[test]
+fn verify_rejects_wrong_number_of_quotient_chunks() {
+    // Constraints of degree 3 have a quotient of 2 chunks, and those of degree 5 one of 4 chunks.
+    let result = do_test_bb_trivial_with_verifier_degree(3, 5, 8);
+    assert!(matches!(result, Err(VerificationError::InvalidProofShape)));
+}
+
 fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<(), impl Debug> {
     type Val = BabyBear;
     type Challenge = BinomialExtensionField<Val, 4>;
@@ -219,6 +262,9 @@ fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
         log_final_poly_len: 5,
         num_queries: 40,
         proof_of_work_bits: 8,
+        max_direct_opening_height: 0,
+        log_folding_arity: 1,
+        uniform_query_indices: false,
         mmcs: challenge_mmcs,
     };
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
@@ -255,6 +301,25 @@ fn prove_bb_twoadic_deg5() -> Result<(), impl Debug> {
     do_test_bb_twoadic(2, 5, 6)
 }
 
+#[test]
+fn prove_bb_twoadic_deg5_with_derived_blowup() -> Result<(), impl Debug> {
+    let air = MulAir {
+        degree: 5,
+        ..Default::default()
+    };
+    let log_blowup = get_log_quotient_degree_with_external::<BabyBear, _>(&air, 0, 0, 0);
+    assert_eq!(log_blowup, 2);
+    do_test_bb_twoadic(log_blowup, 5, 6)
+}
+
+#[test]
+#[should_panic(
+    expected = "BlowupTooSmall { constraint_degree: 5, log_blowup: 2, max_log_blowup: 1 }"
+)]
+fn prove_bb_twoadic_deg5_with_insufficient_blowup() {
+    let _ = do_test_bb_twoadic(1, 5, 6);
+}
+
 fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<(), impl Debug> {
     type Val = Mersenne31;
     type Challenge = BinomialExtensionField<Val, 3>;
@@ -280,6 +345,9 @@ fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
         log_final_poly_len: 0,
         num_queries: 40,
         proof_of_work_bits: 8,
+        max_direct_opening_height: 0,
+        log_folding_arity: 1,
+        uniform_query_indices: false,
         mmcs: challenge_mmcs,
     };
 
@@ -0,0 +1,184 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove_multi, verify_multi, DynamicAir, MultiProof, PcsError, StarkConfig, VerificationError,
+};
+use rand::thread_rng;
+
+/// The Fibonacci sequence from the public values `a` and `b`, ending in the public value `x`.
+struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        builder.when_first_row().assert_eq(local[0], a);
+        builder.when_first_row().assert_eq(local[1], b);
+        builder.when_transition().assert_eq(next[0], local[1]);
+        builder
+            .when_transition()
+            .assert_eq(next[1], local[0] + local[1]);
+        builder.when_last_row().assert_eq(local[1], x);
+    }
+}
+
+/// A column which starts at 2 and squares itself from one row to the next, so that its quotient has
+/// twice as many chunks as that of [`FibonacciAir`].
+struct SquaresAir;
+
+impl<F> BaseAir<F> for SquaresAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for SquaresAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let x: AB::Expr = local[0].into();
+        builder.when_first_row().assert_eq(x.clone(), AB::Expr::TWO);
+        builder.when_transition().assert_eq(next[0], x.square());
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn config(perm: &Perm) -> MyConfig {
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+/// The Fibonacci trace from 0 and 1, and its public values.
+fn fibonacci_trace(height: usize) -> (RowMajorMatrix<Val>, Vec<Val>) {
+    let mut values = vec![Val::ZERO, Val::ONE];
+    for i in 1..height {
+        let (left, right) = (values[2 * i - 2], values[2 * i - 1]);
+        values.extend([right, left + right]);
+    }
+    let public_values = vec![Val::ZERO, Val::ONE, values[2 * height - 1]];
+    (RowMajorMatrix::new(values, 2), public_values)
+}
+
+fn squares_trace(height: usize) -> RowMajorMatrix<Val> {
+    let mut values = vec![Val::TWO];
+    for i in 1..height {
+        values.push(values[i - 1].square());
+    }
+    RowMajorMatrix::new_col(values)
+}
+
+/// Prove a Fibonacci table of 8 rows and a squares table of 32 rows together.
+fn prove_tables(perm: &Perm, config: &MyConfig) -> (MultiProof<MyConfig>, Vec<Vec<Val>>) {
+    let (fibonacci, fibonacci_public_values) = fibonacci_trace(1 << 3);
+    let public_values = vec![fibonacci_public_values, vec![]];
+    let airs: [&dyn DynamicAir<MyConfig>; 2] = [&FibonacciAir, &SquaresAir];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi(
+        config,
+        &airs,
+        &mut challenger,
+        vec![fibonacci, squares_trace(1 << 5)],
+        &public_values,
+    )
+    .unwrap();
+    (proof, public_values)
+}
+
+fn verify_tables(
+    perm: &Perm,
+    config: &MyConfig,
+    airs: &[&dyn DynamicAir<MyConfig>],
+    proof: &MultiProof<MyConfig>,
+    public_values: &[Vec<Val>],
+) -> Result<(), VerificationError<PcsError<MyConfig>>> {
+    let mut challenger = Challenger::new(perm.clone());
+    verify_multi(config, airs, &mut challenger, proof, public_values)
+}
+
+#[test]
+fn test_tables_of_different_heights() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+    let (proof, public_values) = prove_tables(&perm, &config);
+    assert_eq!(proof.degree_bits(), [3, 5]);
+
+    verify_tables(
+        &perm,
+        &config,
+        &[&FibonacciAir, &SquaresAir],
+        &proof,
+        &public_values,
+    )
+    .expect("verification failed");
+}
+
+#[test]
+fn test_wrong_public_values_are_rejected() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+    let (proof, mut public_values) = prove_tables(&perm, &config);
+    public_values[0][2] += Val::ONE;
+
+    let result = verify_tables(
+        &perm,
+        &config,
+        &[&FibonacciAir, &SquaresAir],
+        &proof,
+        &public_values,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_tables_out_of_order_are_rejected() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+    let (proof, mut public_values) = prove_tables(&perm, &config);
+    public_values.reverse();
+
+    let result = verify_tables(
+        &perm,
+        &config,
+        &[&SquaresAir, &FibonacciAir],
+        &proof,
+        &public_values,
+    );
+    assert!(matches!(result, Err(VerificationError::InvalidProofShape)));
+}
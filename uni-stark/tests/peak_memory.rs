@@ -0,0 +1,170 @@
+//! Checks the prover's peak memory estimate against a counting global allocator. This runs
+//! without the test harness, since the allocator counts every thread and the harness runs tests,
+//! and its own bookkeeping, concurrently.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::{Dimensions, Matrix};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove, prover_cost_estimate, StarkConfig};
+use rand::thread_rng;
+
+/// Counts the bytes currently allocated, and the most allocated at once since `PEAK` was reset.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn grow(bytes: usize) {
+    let allocated = ALLOCATED.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    PEAK.fetch_max(allocated, Ordering::Relaxed);
+}
+
+fn shrink(bytes: usize) {
+    ALLOCATED.fetch_sub(bytes, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                grow(new_size - layout.size());
+            } else {
+                shrink(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Columns which each square themselves from one row to the next, so that the quotient domain is
+/// twice the size of the trace domain.
+struct SquaresAir {
+    width: usize,
+}
+
+impl<F> BaseAir<F> for SquaresAir {
+    fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for SquaresAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        for (&local, &next) in local.iter().zip(next.iter()) {
+            let local: AB::Expr = local.into();
+            builder.when_transition().assert_eq(next, local.square());
+        }
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn trace(height: usize, width: usize) -> RowMajorMatrix<Val> {
+    let mut values = Val::zero_vec(height * width);
+    for (j, value) in values[..width].iter_mut().enumerate() {
+        *value = Val::from_canonical_usize(j + 2);
+    }
+    for i in 1..height {
+        for j in 0..width {
+            values[i * width + j] = values[(i - 1) * width + j].square();
+        }
+    }
+    RowMajorMatrix::new(values, width)
+}
+
+/// The most bytes allocated at once while running `f`, on top of what was allocated before.
+fn peak_allocated(f: impl FnOnce()) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    f();
+    PEAK.load(Ordering::Relaxed) - before
+}
+
+fn main() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let measure = |log_height: usize, width: usize| {
+        let height = 1 << log_height;
+        let air = SquaresAir { width };
+        let estimated =
+            prover_cost_estimate(&config, &air, &[Dimensions { width, height }], 0).peak_bytes();
+        let trace = trace(height, width);
+        let mut challenger = Challenger::new(perm.clone());
+        let measured = peak_allocated(|| {
+            prove(&config, &air, &mut challenger, trace, &vec![]).unwrap();
+        });
+        (estimated, measured)
+    };
+
+    // The estimate only counts buffers which grow with the trace. What a tiny proof allocates on
+    // top of its estimate is the overhead which doesn't, e.g. twiddles and the proof itself.
+    let (baseline_estimated, baseline_measured) = measure(4, 1);
+    let overhead = baseline_measured.saturating_sub(baseline_estimated);
+
+    for (log_height, width) in [(10, 16), (12, 64)] {
+        let (estimated, measured) = measure(log_height, width);
+        assert!(
+            estimated.abs_diff(measured) <= overhead + measured / 2,
+            "estimated {estimated} bytes at peak for a {width}x{} trace, measured {measured} \
+             with an overhead of {overhead}",
+            1 << log_height
+        );
+    }
+}
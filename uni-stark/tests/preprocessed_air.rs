@@ -0,0 +1,203 @@
+use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{CanSample, DuplexChallenger};
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove_with_key, setup, verify_with_key, PcsError, ProofSection, StarkConfig, VerificationError,
+};
+use rand::thread_rng;
+
+const HEIGHT: usize = 8;
+
+/// A single column counting up from zero. With a preprocessed trace, the AIR also checks that the
+/// preprocessed column holds the square of the counter.
+struct CounterAir {
+    preprocessed: bool,
+}
+
+impl<F: Field> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        self.preprocessed.then(|| {
+            RowMajorMatrix::new_col(
+                (0..HEIGHT)
+                    .map(|i| F::from_canonical_usize(i * i))
+                    .collect(),
+            )
+        })
+    }
+}
+
+/// The shape of `CounterAir`'s preprocessed trace, but with one square wrong.
+struct WrongSquaresAir;
+
+impl<F: Field> BaseAir<F> for WrongSquaresAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        let mut trace: RowMajorMatrix<F> =
+            CounterAir { preprocessed: true }.preprocessed_trace()?;
+        trace.values[3] += F::ONE;
+        Some(trace)
+    }
+}
+
+impl<AB: PairBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+
+        builder.when_first_row().assert_zero(local);
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+
+        if self.preprocessed {
+            let square = builder.preprocessed().row_slice(0)[0];
+            builder.assert_eq(square, local * local);
+        }
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn config(perm: &Perm) -> MyConfig {
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+/// Prove `CounterAir` with or without its preprocessed trace, and verify it against a key which
+/// does or doesn't declare one.
+fn prove_and_verify(
+    proof_has_preprocessed: bool,
+    key_has_preprocessed: bool,
+) -> Result<(), VerificationError<PcsError<MyConfig>>> {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+
+    let prover_air = CounterAir {
+        preprocessed: proof_has_preprocessed,
+    };
+    let verifier_air = CounterAir {
+        preprocessed: key_has_preprocessed,
+    };
+    let (proving_key, _) = setup(&config, &prover_air);
+    let (_, verifying_key) = setup(&config, &verifier_air);
+    assert_eq!(
+        verifying_key.has_section(ProofSection::Preprocessed),
+        key_has_preprocessed
+    );
+
+    let trace = RowMajorMatrix::new_col((0..HEIGHT).map(Val::from_canonical_usize).collect());
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_key(
+        &config,
+        &proving_key,
+        &prover_air,
+        &mut challenger,
+        trace,
+        &vec![],
+    )
+    .unwrap();
+    assert_eq!(
+        proof.has_section(ProofSection::Preprocessed),
+        proof_has_preprocessed
+    );
+
+    let mut challenger = Challenger::new(perm.clone());
+    let result = verify_with_key(
+        &config,
+        &verifying_key,
+        &verifier_air,
+        &mut challenger,
+        &proof,
+        &vec![],
+    );
+    if result.is_err() {
+        // Rejections must happen before the verifier touches the transcript.
+        let mut fresh = Challenger::new(perm);
+        let expected: Val = fresh.sample();
+        let actual: Val = challenger.sample();
+        assert_eq!(actual, expected);
+    }
+    result
+}
+
+#[test]
+fn test_matching_sections_verify() {
+    prove_and_verify(false, false).expect("verification failed");
+    prove_and_verify(true, true).expect("verification failed");
+}
+
+#[test]
+fn test_mismatched_sections_are_rejected() {
+    assert!(matches!(
+        prove_and_verify(true, false),
+        Err(VerificationError::SectionMismatch {
+            section: ProofSection::Preprocessed,
+            expected: false,
+        })
+    ));
+    assert!(matches!(
+        prove_and_verify(false, true),
+        Err(VerificationError::SectionMismatch {
+            section: ProofSection::Preprocessed,
+            expected: true,
+        })
+    ));
+}
+
+#[test]
+fn test_wrong_preprocessed_commitment_is_rejected() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+    let air = CounterAir { preprocessed: true };
+    let (proving_key, _) = setup(&config, &air);
+    // A key of the right shape, committing to a different preprocessed trace.
+    let (_, verifying_key) = setup(&config, &WrongSquaresAir);
+
+    let trace = RowMajorMatrix::new_col((0..HEIGHT).map(Val::from_canonical_usize).collect());
+    let mut challenger = Challenger::new(perm.clone());
+    let proof =
+        prove_with_key(&config, &proving_key, &air, &mut challenger, trace, &vec![]).unwrap();
+
+    let mut challenger = Challenger::new(perm);
+    assert!(verify_with_key(
+        &config,
+        &verifying_key,
+        &air,
+        &mut challenger,
+        &proof,
+        &vec![]
+    )
+    .is_err());
+}
@@ -0,0 +1,101 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove, prove_with_cache, verify, ProverCache, ProvingKey, StarkConfig};
+use rand::thread_rng;
+
+/// A single column counting up from zero, whose square is constrained so that the quotient domain
+/// is bigger than the trace domain.
+struct SquareCounterAir;
+
+impl<F> BaseAir<F> for SquareCounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for SquareCounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+
+        builder.when_first_row().assert_zero(local);
+        builder
+            .when_transition()
+            .assert_eq(next * next, (local + AB::Expr::ONE).square());
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn trace(height: usize) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col((0..height).map(Val::from_canonical_usize).collect())
+}
+
+#[test]
+fn test_cached_proofs_match_uncached() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let air = SquareCounterAir;
+    let proving_key = ProvingKey::default();
+    let mut cache = ProverCache::new(1 << 20);
+
+    for (i, height) in [8, 8, 16, 8].into_iter().enumerate() {
+        let mut challenger = Challenger::new(perm.clone());
+        let uncached = prove(&config, &air, &mut challenger, trace(height), &vec![]).unwrap();
+
+        let mut challenger = Challenger::new(perm.clone());
+        let cached = prove_with_cache(
+            &config,
+            &proving_key,
+            &mut cache,
+            &air,
+            &mut challenger,
+            trace(height),
+            &vec![],
+        )
+        .unwrap();
+        assert_eq!(
+            bincode::serialize(&cached).unwrap(),
+            bincode::serialize(&uncached).unwrap()
+        );
+
+        let mut challenger = Challenger::new(perm.clone());
+        verify(&config, &air, &mut challenger, &cached, &vec![]).expect("verification failed");
+
+        // Only the first proof of each height computes the selectors.
+        let computed = if i == 0 { 1 } else { 2 };
+        assert_eq!(cache.misses(), computed);
+        assert_eq!(cache.hits(), i + 1 - computed);
+    }
+}
@@ -0,0 +1,181 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, FieldAlgebra};
+use p3_fri::{create_test_fri_config, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove, prove_stacked, verify_stacked, PcsError, ProverError, StackedProof, StarkConfig,
+    VerificationError,
+};
+use rand::thread_rng;
+
+/// The Fibonacci sequence from the public values `a` and `b`, ending in the public value `x`.
+struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        builder.when_first_row().assert_eq(local[0], a);
+        builder.when_first_row().assert_eq(local[1], b);
+        builder.when_transition().assert_eq(next[0], local[1]);
+        builder
+            .when_transition()
+            .assert_eq(next[1], local[0] + local[1]);
+        builder.when_last_row().assert_eq(local[1], x);
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn config(perm: &Perm) -> MyConfig {
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+/// The Fibonacci trace from `a` and `b`, and its public values.
+fn fibonacci_trace(a: u32, b: u32, height: usize) -> (RowMajorMatrix<Val>, Vec<Val>) {
+    let mut values = vec![Val::from_canonical_u32(a), Val::from_canonical_u32(b)];
+    for i in 1..height {
+        let (left, right) = (values[2 * i - 2], values[2 * i - 1]);
+        values.extend([right, left + right]);
+    }
+    let public_values = vec![values[0], values[1], values[2 * height - 1]];
+    (RowMajorMatrix::new(values, 2), public_values)
+}
+
+/// Three instances of 16, 8 and 4 rows, from different starting values.
+fn instances() -> (Vec<RowMajorMatrix<Val>>, Vec<Vec<Val>>) {
+    [(0, 1, 1 << 4), (1, 1, 1 << 3), (2, 3, 1 << 2)]
+        .into_iter()
+        .map(|(a, b, height)| fibonacci_trace(a, b, height))
+        .unzip()
+}
+
+fn prove_instances(perm: &Perm, config: &MyConfig) -> (StackedProof<MyConfig>, Vec<Vec<Val>>) {
+    let (traces, public_values) = instances();
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_stacked(
+        config,
+        &FibonacciAir,
+        &mut challenger,
+        traces,
+        &public_values,
+    )
+    .unwrap();
+    (proof, public_values)
+}
+
+fn verify_instances(
+    perm: &Perm,
+    config: &MyConfig,
+    proof: &StackedProof<MyConfig>,
+    public_values: &[Vec<Val>],
+) -> Result<(), VerificationError<PcsError<MyConfig>>> {
+    let mut challenger = Challenger::new(perm.clone());
+    verify_stacked(config, &FibonacciAir, &mut challenger, proof, public_values)
+}
+
+#[test]
+fn test_instances_of_different_heights() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+    let (proof, public_values) = prove_instances(&perm, &config);
+    assert_eq!(proof.heights(), [16, 8, 4]);
+
+    verify_instances(&perm, &config, &proof, &public_values).expect("verification failed");
+}
+
+#[test]
+fn test_wrong_public_values_are_rejected() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+    let (proof, mut public_values) = prove_instances(&perm, &config);
+    public_values[1][2] += Val::ONE;
+
+    let result = verify_instances(&perm, &config, &proof, &public_values);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_zk_configs_are_rejected() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let zk_config = config(&perm).with_zk(true);
+    let (traces, public_values) = instances();
+    let mut challenger = Challenger::new(perm.clone());
+    let result = prove_stacked(
+        &zk_config,
+        &FibonacciAir,
+        &mut challenger,
+        traces,
+        &public_values,
+    );
+    assert!(matches!(result, Err(ProverError::ZkUnsupported)));
+
+    let (proof, public_values) = prove_instances(&perm, &config(&perm));
+    let result = verify_instances(&perm, &zk_config, &proof, &public_values);
+    assert!(matches!(result, Err(VerificationError::ZkUnsupported)));
+}
+
+#[test]
+fn test_stacked_proof_is_smaller_than_separate_proofs() {
+    let perm = Perm::new_from_rng_128(&mut thread_rng());
+    let config = config(&perm);
+    let (stacked, _) = prove_instances(&perm, &config);
+    let stacked_size = postcard::to_allocvec(&stacked).unwrap().len();
+
+    let (traces, public_values) = instances();
+    let separate_size: usize = traces
+        .into_iter()
+        .zip(&public_values)
+        .map(|(trace, public_values)| {
+            let mut challenger = Challenger::new(perm.clone());
+            let proof = prove(
+                &config,
+                &FibonacciAir,
+                &mut challenger,
+                trace,
+                public_values,
+            )
+            .unwrap();
+            postcard::to_allocvec(&proof).unwrap().len()
+        })
+        .sum();
+
+    assert!(
+        stacked_size < separate_size,
+        "the stacked proof takes {stacked_size} bytes, and the separate proofs {separate_size}"
+    );
+}
@@ -0,0 +1,216 @@
+//! A canonical byte encoding for proofs.
+//!
+//! Unlike the `serde` representation, which depends on the serializer used, this layout is fixed
+//! and is suitable for verifiers which parse proofs themselves, e.g. on chain. Everything is
+//! little-endian:
+//! - `u8`, `u32` and `u64` are written as 1, 4 and 8 bytes respectively.
+//! - `usize` values, including the lengths of sequences, are written as `u32`.
+//! - Arrays are written element by element, without a length prefix.
+//! - `Vec`s are written as their length followed by their elements.
+//! - `Option`s are written as a `0` byte if absent, or a `1` byte followed by the value.
+//! - Prime field elements are written in canonical form, as a `u32` if the prime fits in 32 bits
+//!   and as a `u64` otherwise. Extension field elements are written as their coefficients.
+//! - Digests are written as their raw words.
+//!
+//! Top level structures are prefixed with a 4 byte version tag, see [`encode_versioned`].
+
+use alloc::vec::Vec;
+
+/// A type with a canonical byte encoding.
+pub trait CanonicalEncoding: Sized {
+    /// Append the encoding of `self` to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decode a value from the start of `input`, advancing it past the bytes which were read.
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodingError {
+    /// The input ended before the value was fully decoded.
+    Truncated,
+    /// The version tag does not match the version of the encoding this library implements.
+    UnsupportedVersion { expected: u32, found: u32 },
+    /// A field element was not in canonical form, i.e. was not less than the field order.
+    NonCanonical,
+    /// An enum discriminant did not correspond to any variant.
+    InvalidTag(u8),
+    /// The input had bytes left over after the value was decoded.
+    TrailingBytes,
+}
+
+/// Encode `value` prefixed with the 4 byte `version` tag.
+pub fn encode_versioned<T: CanonicalEncoding>(version: u32, value: &T) -> Vec<u8> {
+    let mut out = Vec::new();
+    version.encode(&mut out);
+    value.encode(&mut out);
+    out
+}
+
+/// Decode a value encoded by [`encode_versioned`], checking its version tag and that no bytes are
+/// left over.
+pub fn decode_versioned<T: CanonicalEncoding>(
+    version: u32,
+    mut bytes: &[u8],
+) -> Result<T, DecodingError> {
+    let found = u32::decode(&mut bytes)?;
+    if found != version {
+        return Err(DecodingError::UnsupportedVersion {
+            expected: version,
+            found,
+        });
+    }
+    let value = T::decode(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(DecodingError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+/// Split the first `len` bytes off `input`.
+pub fn take_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodingError> {
+    if input.len() < len {
+        return Err(DecodingError::Truncated);
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+macro_rules! impl_canonical_encoding_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl CanonicalEncoding for $t {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+                    let bytes = take_bytes(input, core::mem::size_of::<$t>())?;
+                    Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+impl_canonical_encoding_for_int!(u8, u32, u64);
+
+impl CanonicalEncoding for usize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        u32::try_from(*self)
+            .expect("usize values must fit in a u32 to be encoded")
+            .encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        u32::decode(input).map(|value| value as usize)
+    }
+}
+
+impl<T: CanonicalEncoding, const N: usize> CanonicalEncoding for [T; N] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.iter().for_each(|value| value.encode(out));
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        let values = (0..N)
+            .map(|_| T::decode(input))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+impl<T: CanonicalEncoding> CanonicalEncoding for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        self.iter().for_each(|value| value.encode(out));
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        let len = usize::decode(input)?;
+        // Don't trust `len` for the allocation, the input may be truncated.
+        let mut values = Vec::new();
+        for _ in 0..len {
+            values.push(T::decode(input)?);
+        }
+        Ok(values)
+    }
+}
+
+impl<T: CanonicalEncoding> CanonicalEncoding for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            None => 0_u8.encode(out),
+            Some(value) => {
+                1_u8.encode(out);
+                value.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodingError> {
+        match u8::decode(input)? {
+            0 => Ok(None),
+            1 => T::decode(input).map(Some),
+            tag => Err(DecodingError::InvalidTag(tag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let value: Vec<[u32; 2]> = vec![[1, 2], [0x0304_0506, u32::MAX]];
+        let bytes = encode_versioned(7, &value);
+        assert_eq!(
+            bytes,
+            [
+                7, 0, 0, 0, // version
+                2, 0, 0, 0, // length
+                1, 0, 0, 0, 2, 0, 0, 0, // first array
+                6, 5, 4, 3, 255, 255, 255, 255, // second array
+            ]
+        );
+        assert_eq!(decode_versioned::<Vec<[u32; 2]>>(7, &bytes), Ok(value));
+
+        let value = (Some(0x0102_u32), None::<u32>);
+        let mut bytes = Vec::new();
+        value.0.encode(&mut bytes);
+        value.1.encode(&mut bytes);
+        assert_eq!(bytes, [1, 2, 1, 0, 0, 0]);
+        assert_eq!(
+            decode_versioned::<Option<u32>>(0, &[0, 0, 0, 0, 2]),
+            Err(DecodingError::InvalidTag(2))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let bytes = encode_versioned(1, &vec![5_u64, 6]);
+        assert_eq!(
+            decode_versioned::<Vec<u64>>(2, &bytes),
+            Err(DecodingError::UnsupportedVersion {
+                expected: 2,
+                found: 1
+            })
+        );
+        for len in 0..bytes.len() {
+            assert_eq!(
+                decode_versioned::<Vec<u64>>(1, &bytes[..len]),
+                Err(DecodingError::Truncated)
+            );
+        }
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            decode_versioned::<Vec<u64>>(1, &extended),
+            Err(DecodingError::TrailingBytes)
+        );
+    }
+}